@@ -104,6 +104,9 @@ macro_rules! define_blocks {
                 $(offset $offsetfunc:expr,)?
                 $(offsets $offsetsfunc:expr,)?
                 $(material $mat:expr,)?
+                $(hardness $hardness:expr,)?
+                $(tool $tool:expr,)?
+                $(requires_tool $requires_tool:expr,)?
                 model $model:expr,
                 $(variant $variant:expr,)?
                 $(tint $tint:expr,)?
@@ -214,6 +217,53 @@ macro_rules! define_blocks {
                 }
             }
 
+            /// How long the block takes to break, `UNBREAKABLE` if it can't be. Blocks
+            /// without a hardness break instantly, unless they can be collided with.
+            #[allow(unused_variables, unreachable_code)]
+            pub fn get_hardness(&self) -> f64 {
+                match *self {
+                    $(
+                        Block::$name {
+                            $($fname,)?
+                        } => {
+                            $(return $hardness;)?
+                            if self.get_material().collidable { 1.0 } else { 0.0 }
+                        }
+                    )+
+                }
+            }
+
+            /// The tool that breaks the block fastest.
+            #[allow(unused_variables, unreachable_code)]
+            pub fn get_tool(&self) -> Option<Tool> {
+                match *self {
+                    $(
+                        Block::$name {
+                            $($fname,)?
+                        } => {
+                            $(return Some($tool);)?
+                            None
+                        }
+                    )+
+                }
+            }
+
+            /// Whether the block only drops when broken with its tool, which also
+            /// makes it slower to break without one.
+            #[allow(unused_variables, unreachable_code)]
+            pub fn requires_tool(&self) -> bool {
+                match *self {
+                    $(
+                        Block::$name {
+                            $($fname,)?
+                        } => {
+                            $(return $requires_tool;)?
+                            false
+                        }
+                    )+
+                }
+            }
+
             #[allow(unused_variables)]
             pub fn get_model(&self) -> (String, String) {
                 match *self {
@@ -521,6 +571,18 @@ macro_rules! define_blocks {
     );
 }
 
+/// Hardness of blocks that can't be broken, such as bedrock.
+pub const UNBREAKABLE: f64 = f64::INFINITY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Sword,
+    Shears,
+}
+
 #[derive(Clone, Copy)]
 pub enum TintType {
     Default,
@@ -536,6 +598,7 @@ define_blocks! {
             collidable: false,
             .. material::INVISIBLE
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "air") },
         collision vec![],
     }
@@ -552,6 +615,9 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", variant.as_string() ) },
     }
     Grass {
@@ -560,6 +626,8 @@ define_blocks! {
         },
         data { if snowy { None } else { Some(0) } },
         offset { if snowy { Some(0) } else { Some(1) } },
+        hardness 0.6,
+        tool Tool::Shovel,
         model { ("minecraft", "grass") },
         variant format!("snowy={}", snowy),
         tint TintType::Grass,
@@ -586,6 +654,8 @@ define_blocks! {
                 }
             }
         },
+        hardness 0.5,
+        tool Tool::Shovel,
         model { ("minecraft", variant.as_string()) },
         variant {
             if variant == DirtVariant::Podzol {
@@ -602,6 +672,9 @@ define_blocks! {
     }
     Cobblestone {
         props {},
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "cobblestone") },
     }
     Planks {
@@ -616,6 +689,8 @@ define_blocks! {
             ],
         },
         data Some(variant.plank_data()),
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_planks", variant.as_string()) ) },
     }
     Sapling {
@@ -633,12 +708,14 @@ define_blocks! {
         data Some(variant.plank_data() | ((stage as usize) << 3)),
         offset Some((variant.plank_data() << 1) | (stage as usize)),
         material material::NON_SOLID,
+        hardness 0.0,
         model { ("minecraft", format!("{}_sapling", variant.as_string()) ) },
         variant format!("stage={}", stage),
         collision vec![],
     }
     Bedrock {
         props {},
+        hardness UNBREAKABLE,
         model { ("minecraft", "bedrock") },
     }
     FlowingWater {
@@ -651,6 +728,7 @@ define_blocks! {
             absorbed_light: 2,
             ..material::TRANSPARENT
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "flowing_water") },
         collision vec![],
     }
@@ -663,6 +741,7 @@ define_blocks! {
             absorbed_light: 2,
             ..material::TRANSPARENT
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "water") },
         collision vec![],
     }
@@ -677,6 +756,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::NON_SOLID
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "flowing_lava") },
         collision vec![],
     }
@@ -690,6 +770,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::NON_SOLID
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "lava") },
         collision vec![],
     }
@@ -698,28 +779,44 @@ define_blocks! {
             red: bool = [false, true],
         },
         data Some(if red { 1 } else { 0 }),
+        hardness 0.5,
+        tool Tool::Shovel,
         model { ("minecraft", if red { "red_sand" } else { "sand" } ) },
     }
     Gravel {
         props {},
+        hardness 0.6,
+        tool Tool::Shovel,
         model { ("minecraft", "gravel") },
     }
     GoldOre {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "gold_ore") },
     }
     IronOre {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "iron_ore") },
     }
     CoalOre {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "coal_ore") },
     }
     NetherGoldOre {
         props {},
         data None,
         offsets |protocol_version| { if protocol_version >= 735 { Some(0) } else { None } },
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "nether_gold_ore") },
     }
     Log {
@@ -751,6 +848,8 @@ define_blocks! {
             Axis::Y => Some(variant.offset() * 3 + 1),
             Axis::Z => Some(variant.offset() * 3 + 2),
         },
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_log", variant.as_string()) ) },
         variant format!("axis={}", axis.as_string()),
     }
@@ -774,6 +873,8 @@ define_blocks! {
         },
         data None::<usize>,
         offset Some(variant.offset() * 3 + axis.index()),
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_wood", variant.as_string()) ) },
         variant format!("axis={}", axis.as_string()),
     }
@@ -808,6 +909,8 @@ define_blocks! {
             Some(variant.offset() * (7 * 2) + ((distance as usize - 1) << 1) + (if decayable { 0 } else { 1 }))
         },
         material material::LEAVES,
+        hardness 0.2,
+        tool Tool::Shears,
         model { ("minecraft", format!("{}_leaves", variant.as_string()) ) },
         tint TintType::Foliage,
     }
@@ -816,20 +919,28 @@ define_blocks! {
             wet: bool = [false, true],
         },
         data Some(if wet { 1 } else { 0 }),
+        hardness 0.6,
         model { ("minecraft", "sponge") },
         variant format!("wet={}", wet),
     }
     Glass {
         props {},
         material material::PARTIALLY_SOLID,
+        hardness 0.3,
         model { ("minecraft", "glass") },
     }
     LapisOre {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "lapis_ore") },
     }
     LapisBlock {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "lapis_block") },
     }
     Dispenser {
@@ -846,6 +957,9 @@ define_blocks! {
         },
         data Some(facing.index() | (if triggered { 0x8 } else { 0x0 })),
         offset Some((facing.offset() << 1) | (if triggered { 0 } else { 1 })),
+        hardness 3.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "dispenser") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -858,6 +972,9 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 0.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", variant.as_string() ) },
     }
     NoteBlock {
@@ -886,6 +1003,8 @@ define_blocks! {
         data if instrument == NoteBlockInstrument::Harp && note == 0 && powered { Some(0) } else { None },
         offsets |protocol_version| (instrument.offsets(protocol_version)
             .map(|offset| offset * (25 * 2) + ((note as usize) << 1) + if powered { 0 } else { 1 })),
+        hardness 0.8,
+        tool Tool::Axe,
         model { ("minecraft", "noteblock") },
     }
     Bed {
@@ -925,6 +1044,7 @@ define_blocks! {
                   + (if occupied { 0 } else { 2 })
                   + (if part == BedPart::Head { 0 } else { 1 })),
         material material::PARTIALLY_SOLID,
+        hardness 0.2,
         model { ("minecraft", "bed") },
         variant format!("facing={},part={}", facing.as_string(), part.as_string()),
         collision vec![Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 9.0/16.0, 1.0))],
@@ -944,6 +1064,8 @@ define_blocks! {
         data Some(shape.data() | (if powered { 0x8 } else { 0x0 })),
         offset Some(shape.data() + (if powered { 0 } else { 6 })),
         material material::NON_SOLID,
+        hardness 0.7,
+        tool Tool::Pickaxe,
         model { ("minecraft", "golden_rail") },
         variant format!("powered={},shape={}", powered, shape.as_string()),
         collision vec![],
@@ -963,6 +1085,8 @@ define_blocks! {
         data Some(shape.data() | (if powered { 0x8 } else { 0x0 })),
         offset Some(shape.data() + (if powered { 0 } else { 6 })),
         material material::NON_SOLID,
+        hardness 0.7,
+        tool Tool::Pickaxe,
         model { ("minecraft", "detector_rail") },
         variant format!("powered={},shape={}", powered, shape.as_string()),
         collision vec![],
@@ -985,6 +1109,8 @@ define_blocks! {
             should_cull_against: !extended,
             ..material::PARTIALLY_SOLID
         },
+        hardness 0.5,
+        tool Tool::Pickaxe,
         model { ("minecraft", "sticky_piston") },
         variant format!("extended={},facing={}", extended, facing.as_string()),
         collision piston_collision(extended, facing),
@@ -992,6 +1118,9 @@ define_blocks! {
     Web {
         props {},
         material material::NON_SOLID,
+        hardness 4.0,
+        tool Tool::Sword,
+        requires_tool true,
         model { ("minecraft", "web") },
         collision vec![],
     }
@@ -1056,6 +1185,8 @@ define_blocks! {
             should_cull_against: !extended,
             ..material::PARTIALLY_SOLID
         },
+        hardness 0.5,
+        tool Tool::Pickaxe,
         model { ("minecraft", "piston") },
         variant format!("extended={},facing={}", extended, facing.as_string()),
         collision piston_collision(extended, facing),
@@ -1078,6 +1209,8 @@ define_blocks! {
                     (if short { 0 } else { 2 }) +
                     (if variant == PistonType::Normal { 0 } else { 1 })),
         material material::PARTIALLY_SOLID,
+        hardness 0.5,
+        tool Tool::Pickaxe,
         model { ("minecraft", "piston_head") },
         variant format!("facing={},short={},type={}", facing.as_string(), short, variant.as_string()),
         collision {
@@ -1119,6 +1252,8 @@ define_blocks! {
             ],
         },
         data Some(color.data()),
+        hardness 0.8,
+        tool Tool::Shears,
         model { ("minecraft", format!("{}_wool", color.as_string()) ) },
     }
     PistonExtension {
@@ -1133,9 +1268,10 @@ define_blocks! {
             ],
             variant: PistonType = [PistonType::Normal, PistonType::Sticky],
         },
-        data if facing == Direction::Up && variant == PistonType::Normal { Some(0) } else { None },
+        data Some(facing.index() | (if variant == PistonType::Sticky { 0x8 } else { 0x0 })),
         offset Some(facing.offset() * 2 + (if variant == PistonType::Normal { 0 } else { 1 })),
         material material::INVISIBLE,
+        hardness UNBREAKABLE,
         model { ("minecraft", "piston_extension") },
     }
     YellowFlower {
@@ -1184,10 +1320,16 @@ define_blocks! {
     }
     GoldBlock {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "gold_block") },
     }
     IronBlock {
         props {},
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "iron_block") },
     }
     DoubleStoneSlab {
@@ -1219,6 +1361,9 @@ define_blocks! {
             Some(data)
         },
         offset None,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_double_slab", variant.as_string()) ) },
         variant if seamless { "all" } else { "normal" },
     }
@@ -1239,12 +1384,18 @@ define_blocks! {
         data Some(variant.data() | (if half == BlockHalf::Top { 0x8 } else { 0x0 })),
         offset None,
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_slab", variant.as_string()) ) },
         variant format!("half={}", half.as_string()),
         collision slab_collision(half),
     }
     BrickBlock {
         props {},
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "brick_block") },
     }
     TNT {
@@ -1253,18 +1404,27 @@ define_blocks! {
         },
         data Some(if explode { 1 } else { 0 }),
         offset Some(if explode { 0 } else { 1 }),
+        hardness 0.0,
         model { ("minecraft", "tnt") },
     }
     BookShelf {
         props {},
+        hardness 1.5,
+        tool Tool::Axe,
         model { ("minecraft", "bookshelf") },
     }
     MossyCobblestone {
         props {},
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "mossy_cobblestone") },
     }
     Obsidian {
         props {},
+        hardness 50.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "obsidian") },
     }
     Torch {
@@ -1357,6 +1517,9 @@ define_blocks! {
     MobSpawner {
         props {},
         material material::PARTIALLY_SOLID,
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "mob_spawner") },
     }
     OakStairs {
@@ -1380,6 +1543,8 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "oak_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -1405,6 +1570,8 @@ define_blocks! {
             type_.offset() * 2 +
             facing.horizontal_offset() * (2 * 3)),
         material material::PARTIALLY_SOLID,
+        hardness 2.5,
+        tool Tool::Axe,
         model { ("minecraft", "chest") },
     }
     RedstoneWire {
@@ -1450,14 +1617,22 @@ define_blocks! {
     }
     DiamondOre {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "diamond_ore") },
     }
     DiamondBlock {
         props {},
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "diamond_block") },
     }
     CraftingTable {
         props {},
+        hardness 2.5,
+        tool Tool::Axe,
         model { ("minecraft", "crafting_table") },
     }
     Wheat {
@@ -1476,6 +1651,8 @@ define_blocks! {
         },
         data Some(moisture as usize),
         material material::PARTIALLY_SOLID,
+        hardness 0.6,
+        tool Tool::Shovel,
         model { ("minecraft", "farmland") },
         variant format!("moisture={}", moisture),
         collision vec![Aabb3::new(
@@ -1495,6 +1672,9 @@ define_blocks! {
         },
         data if !lit { Some(facing.index()) } else { None },
         offset Some(if lit { 0 } else { 1 } + facing.horizontal_offset() * 2),
+        hardness 3.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "furnace") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -1513,6 +1693,9 @@ define_blocks! {
             emitted_light: 13,
             ..material::SOLID
         },
+        hardness 3.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "lit_furnace") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -1560,6 +1743,8 @@ define_blocks! {
             }
         },
         material material::INTERACTABLE,
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "standing_sign") },
         collision vec![],
     }
@@ -1579,6 +1764,8 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "wooden_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -1600,6 +1787,8 @@ define_blocks! {
         data if !waterlogged { Some(facing.index()) } else { None },
         offset Some(if waterlogged { 0 } else { 1 } + facing.horizontal_offset() * 2),
         material material::PARTIALLY_SOLID,
+        hardness 0.4,
+        tool Tool::Axe,
         model { ("minecraft", "ladder") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -1620,6 +1809,8 @@ define_blocks! {
         },
         data Some(shape.data()),
         material material::NON_SOLID,
+        hardness 0.7,
+        tool Tool::Pickaxe,
         model { ("minecraft", "rail") },
         variant format!("shape={}", shape.as_string()),
         collision vec![],
@@ -1645,6 +1836,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "stone_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -1682,6 +1876,8 @@ define_blocks! {
             }
         },
         material material::INTERACTABLE,
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "wall_sign") },
         variant format!("facing={}", facing.as_string()),
         collision vec![],
@@ -1704,6 +1900,7 @@ define_blocks! {
         data face.data_with_facing_and_powered(facing, powered),
         offset Some(face.offset() * (4 * 2) + facing.horizontal_offset() * 2 + if powered { 0 } else { 1 }),
         material material::NON_SOLID,
+        hardness 0.5,
         model { ("minecraft", "lever") },
         variant format!("facing={},powered={}", face.variant_with_facing(facing), powered),
         collision vec![],
@@ -1715,6 +1912,9 @@ define_blocks! {
         data Some(if powered { 1 } else { 0 }),
         offset Some(if powered { 0 } else { 1 }),
         material material::NON_SOLID,
+        hardness 0.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "stone_pressure_plate") },
         variant format!("powered={}", powered),
         collision vec![],
@@ -1735,6 +1935,9 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "iron_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -1758,6 +1961,8 @@ define_blocks! {
         data if wood == TreeVariant::Oak { Some(if powered { 1 } else { 0 }) } else { None },
         offset Some(wood.offset() * 2 + if powered { 0 } else { 1 }),
         material material::NON_SOLID,
+        hardness 0.5,
+        tool Tool::Axe,
         model { ("minecraft", "wooden_pressure_plate") },
         variant format!("powered={}", powered),
         collision vec![],
@@ -1768,6 +1973,9 @@ define_blocks! {
         },
         data if !lit { Some(0) } else { None },
         offset Some(if lit { 0 } else { 1 }),
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", if lit { "lit_redstone_ore" } else { "redstone_ore" }) },
     }
     RedstoneOreLit {
@@ -1777,6 +1985,9 @@ define_blocks! {
             emitted_light: 9,
             ..material::SOLID
         },
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "lit_redstone_ore") },
     }
     RedstoneTorchUnlit {
@@ -1883,6 +2094,7 @@ define_blocks! {
         data face.data_with_facing_and_powered(facing, powered),
         offset Some(face.offset() * (4 * 2) + facing.horizontal_offset() * 2 + if powered { 0 } else { 1 }),
         material material::NON_SOLID,
+        hardness 0.5,
         model { ("minecraft", "stone_button") },
         variant format!("facing={},powered={}", face.variant_with_facing(facing), powered),
     }
@@ -1892,6 +2104,9 @@ define_blocks! {
         },
         data Some(layers as usize - 1),
         material material::PARTIALLY_SOLID,
+        hardness 0.1,
+        tool Tool::Shovel,
+        requires_tool true,
         model { ("minecraft", "snow_layer") },
         variant format!("layers={}", layers),
         collision vec![Aabb3::new(
@@ -1905,10 +2120,15 @@ define_blocks! {
             absorbed_light: 2,
             ..material::TRANSPARENT
         },
+        hardness 0.5,
+        tool Tool::Pickaxe,
         model { ("minecraft", "ice") },
     }
     Snow {
         props {},
+        hardness 0.2,
+        tool Tool::Shovel,
+        requires_tool true,
         model { ("minecraft", "snow") },
     }
     Cactus {
@@ -1917,6 +2137,7 @@ define_blocks! {
         },
         data Some(age as usize),
         material material::PARTIALLY_SOLID,
+        hardness 0.4,
         model { ("minecraft", "cactus") },
         collision vec![Aabb3::new(
             Point3::new(1.0/16.0, 0.0, 1.0/16.0),
@@ -1925,6 +2146,8 @@ define_blocks! {
     }
     Clay {
         props {},
+        hardness 0.6,
+        tool Tool::Shovel,
         model { ("minecraft", "clay") },
     }
     Reeds {
@@ -1943,6 +2166,8 @@ define_blocks! {
         },
         data Some(if has_record { 1 } else { 0 }),
         offset Some(if has_record { 0 } else { 1 }),
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "jukebox") },
     }
     Fence {
@@ -1960,6 +2185,8 @@ define_blocks! {
             if north { 0 } else { 1<<3 } +
             if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -1986,6 +2213,8 @@ define_blocks! {
         },
         data Some(facing.horizontal_index() | (if without_face { 0x4 } else { 0x0 })),
         offset None,
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "pumpkin") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -1993,15 +2222,22 @@ define_blocks! {
         props {},
         data None::<usize>,
         offset Some(0),
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "pumpkin") },
     }
     Netherrack {
         props {},
+        hardness 0.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "netherrack") },
     }
     SoulSand {
         props {},
         material material::PARTIALLY_SOLID,
+        hardness 0.5,
+        tool Tool::Shovel,
         model { ("minecraft", "soul_sand") },
         collision vec![Aabb3::new(
             Point3::new(0.0, 0.0, 0.0),
@@ -2012,6 +2248,8 @@ define_blocks! {
         props {},
         data None,
         offsets |protocol_version| { if protocol_version >= 735 { Some(0) } else { None } },
+        hardness 0.5,
+        tool Tool::Shovel,
         model { ("minecraft", "soul_soil") },
     }
     Basalt {
@@ -2026,6 +2264,9 @@ define_blocks! {
                 Axis::Z => 2,
                 _ => unreachable!()
             }) } else { None } },
+        hardness 1.25,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "basalt") },
     }
     PolishedBasalt {
@@ -2040,6 +2281,9 @@ define_blocks! {
                 Axis::Z => 2,
                 _ => unreachable!()
             }) } else { None } },
+        hardness 1.25,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "polished_basalt") },
     }
     SoulTorch {
@@ -2067,6 +2311,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::SOLID
         },
+        hardness 0.3,
         model { ("minecraft", "glowstone") },
     }
     Portal {
@@ -2079,6 +2324,7 @@ define_blocks! {
             emitted_light: 11,
             ..material::TRANSPARENT
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "portal") },
         variant format!("axis={}", axis.as_string()),
         collision vec![],
@@ -2098,6 +2344,8 @@ define_blocks! {
             emitted_light: 15,
             ..material::SOLID
         },
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "carved_pumpkin") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -2117,6 +2365,8 @@ define_blocks! {
             emitted_light: 15,
             ..material::SOLID
         },
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "lit_pumpkin") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -2126,6 +2376,7 @@ define_blocks! {
         },
         data Some(bites as usize),
         material material::PARTIALLY_SOLID,
+        hardness 0.5,
         model { ("minecraft", "cake") },
         variant format!("bites={}", bites),
         collision vec![Aabb3::new(
@@ -2204,6 +2455,7 @@ define_blocks! {
         },
         data Some(color.data()),
         material material::TRANSPARENT,
+        hardness 0.3,
         model { ("minecraft", format!("{}_stained_glass", color.as_string()) ) },
     }
     TrapDoor {
@@ -2241,6 +2493,8 @@ define_blocks! {
             facing.horizontal_offset() * (2 * 2 * 2 * 2) +
             wood.offset() * (2 * 2 * 2 * 2 * 4)),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "trapdoor") },
         variant format!("facing={},half={},open={}", facing.as_string(), half.as_string(), open),
         collision trapdoor_collision(facing, half, open),
@@ -2257,6 +2511,7 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 0.75,
         model { ("minecraft", format!("{}_monster_egg", variant.as_string())) },
     }
     StoneBrick {
@@ -2269,6 +2524,9 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", variant.as_string() ) },
     }
     BrownMushroomBlock {
@@ -2283,6 +2541,8 @@ define_blocks! {
         },
         data mushroom_block_data(is_stem, west, up, south, north, east, down),
         offset mushroom_block_offset(is_stem, west, up, south, north, east, down),
+        hardness 0.2,
+        tool Tool::Axe,
         model { ("minecraft", "brown_mushroom_block") },
         variant format!("variant={}", mushroom_block_variant(is_stem, west, up, south, north, east, down)),
     }
@@ -2298,6 +2558,8 @@ define_blocks! {
         },
         data mushroom_block_data(is_stem, west, up, south, north, east, down),
         offset mushroom_block_offset(is_stem, west, up, south, north, east, down),
+        hardness 0.2,
+        tool Tool::Axe,
         model { ("minecraft", "red_mushroom_block") },
         variant format!("variant={}", mushroom_block_variant(is_stem, west, up, south, north, east, down)),
     }
@@ -2312,6 +2574,8 @@ define_blocks! {
         },
         data None::<usize>,
         offset mushroom_block_offset(false, west, up, south, north, east, down),
+        hardness 0.2,
+        tool Tool::Axe,
         model { ("minecraft", "mushroom_stem") },
         variant "variant=all_stem".to_string(),
     }
@@ -2330,6 +2594,9 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "iron_bars") },
         collision pane_collision(north, south, east, west),
         update_state (world, pos) => {
@@ -2372,6 +2639,9 @@ define_blocks! {
                 None
             }
         },
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "chain") },
     }
     GlassPane {
@@ -2389,6 +2659,7 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 0.3,
         model { ("minecraft", "glass_pane") },
         collision pane_collision(north, south, east, west),
         update_state (world, pos) => {
@@ -2405,6 +2676,8 @@ define_blocks! {
     }
     MelonBlock {
         props {},
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "melon_block") },
     }
     AttachedPumpkinStem {
@@ -2557,6 +2830,8 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 0.2,
+        tool Tool::Shears,
         model { ("minecraft", "vine") },
         variant format!("east={},north={},south={},up={},west={}", east, north, south, up, west),
         tint TintType::Foliage,
@@ -2582,6 +2857,8 @@ define_blocks! {
         data fence_gate_data(facing, in_wall, open, powered),
         offset fence_gate_offset(facing, in_wall, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "fence_gate") },
         variant format!("facing={},in_wall={},open={}", facing.as_string(), in_wall, open),
         collision fence_gate_collision(facing, in_wall, open),
@@ -2613,6 +2890,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "brick_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -2639,6 +2919,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "stone_brick_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -2651,6 +2934,8 @@ define_blocks! {
         data if snowy { None } else { Some(0) },
         offset Some(if snowy { 0 } else { 1 }),
         material material::SOLID,
+        hardness 0.6,
+        tool Tool::Shovel,
         model { ("minecraft", "mycelium") },
         variant format!("snowy={}", snowy),
         update_state (world, pos) => Block::Mycelium{snowy: is_snowy(world, pos)},
@@ -2667,6 +2952,9 @@ define_blocks! {
     }
     NetherBrick {
         props {},
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "nether_brick") },
     }
     NetherBrickFence {
@@ -2684,6 +2972,9 @@ define_blocks! {
             if north { 0 } else { 1<<3 } +
             if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "nether_brick_fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -2727,6 +3018,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "nether_brick_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -2745,6 +3039,9 @@ define_blocks! {
     EnchantingTable {
         props {},
         material material::PARTIALLY_SOLID,
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "enchanting_table") },
         collision vec![Aabb3::new(
             Point3::new(0.0, 0.0, 0.0),
@@ -2767,6 +3064,9 @@ define_blocks! {
             emitted_light: 1,
             ..material::PARTIALLY_SOLID
         },
+        hardness 0.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "brewing_stand") },
         multipart (key, val) => match key {
             "has_bottle_0" => (val == "true") == has_bottle_0,
@@ -2781,6 +3081,9 @@ define_blocks! {
         },
         data Some(level as usize),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "cauldron") },
         variant format!("level={}", level),
     }
@@ -2790,6 +3093,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::NON_SOLID
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "end_portal") },
         collision vec![],
     }
@@ -2809,6 +3113,7 @@ define_blocks! {
             emitted_light: 1,
             ..material::PARTIALLY_SOLID
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "end_portal_frame") },
         variant format!("eye={},facing={}", eye, facing.as_string()),
         collision {
@@ -2829,6 +3134,9 @@ define_blocks! {
     }
     EndStone {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "end_stone") },
     }
     DragonEgg {
@@ -2837,6 +3145,7 @@ define_blocks! {
             emitted_light: 1,
             ..material::PARTIALLY_SOLID
         },
+        hardness 3.0,
         model { ("minecraft", "dragon_egg") },
         collision vec![Aabb3::new(
             Point3::new(1.0/16.0, 0.0, 1.0/16.0),
@@ -2845,6 +3154,7 @@ define_blocks! {
     }
     RedstoneLamp {
         props {},
+        hardness 0.3,
         model { ("minecraft", "redstone_lamp") },
     }
     RedstoneLampLit {
@@ -2853,6 +3163,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::PARTIALLY_SOLID
         },
+        hardness 0.3,
         model { ("minecraft", "lit_redstone_lamp") },
     }
     DoubleWoodenSlab {
@@ -2868,6 +3179,8 @@ define_blocks! {
         },
         data Some(variant.data()),
         offset None,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_double_slab", variant.as_string()) ) },
     }
     WoodenSlab {
@@ -2885,6 +3198,8 @@ define_blocks! {
         data Some(variant.data() | (if half == BlockHalf::Top { 0x8 } else { 0x0 })),
         offset None,
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_slab", variant.as_string()) ) },
         variant format!("half={}", half.as_string()),
         collision slab_collision(half),
@@ -2902,6 +3217,8 @@ define_blocks! {
         data Some(facing.horizontal_index() | ((age as usize) << 2)),
         offset Some(facing.horizontal_offset() + ((age as usize) * 4)),
         material material::PARTIALLY_SOLID,
+        hardness 0.2,
+        tool Tool::Axe,
         model { ("minecraft", "cocoa") },
         variant format!("age={},facing={}", age, facing.as_string()),
         collision {
@@ -2944,6 +3261,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 0.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "sandstone_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -2952,6 +3272,9 @@ define_blocks! {
     EmeraldOre {
         props {},
         material material::SOLID,
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "emerald_ore") },
     }
     EnderChest {
@@ -2970,6 +3293,9 @@ define_blocks! {
             emitted_light: 7,
             ..material::PARTIALLY_SOLID
         },
+        hardness 22.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "ender_chest") },
         variant format!("facing={}", facing.as_string()),
         collision vec![Aabb3::new(
@@ -3056,6 +3382,9 @@ define_blocks! {
     }
     EmeraldBlock {
         props {},
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "emerald_block") },
     }
     SpruceStairs {
@@ -3079,6 +3408,8 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "spruce_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -3105,6 +3436,8 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "birch_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -3131,6 +3464,8 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "jungle_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -3150,6 +3485,7 @@ define_blocks! {
         },
         data Some(facing.index() | (if conditional { 0x8 } else { 0x0 })),
         offset Some(facing.offset() + (if conditional { 0 } else { 6 })),
+        hardness UNBREAKABLE,
         model { ("minecraft", "command_block") },
         variant format!("conditional={},facing={}", conditional, facing.as_string()),
     }
@@ -3159,6 +3495,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::PARTIALLY_SOLID
         },
+        hardness 3.0,
         model { ("minecraft", "beacon") },
     }
     CobblestoneWall {
@@ -3183,6 +3520,9 @@ define_blocks! {
                     if east { 0 } else { 1<<5 } +
                     if variant == CobblestoneWallVariant::Normal { 0 } else { 1<<6 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_wall", variant.as_string())) },
         update_state (world, pos) => {
             let f = |block| matches!(block, Block::CobblestoneWall{..} |
@@ -3291,6 +3631,7 @@ define_blocks! {
         data if variant == TreeVariant::Oak { face.data_with_facing_and_powered(facing, powered) } else { None },
         offset Some(variant.offset() * (3 * 4 * 2) + face.offset() * (4 * 2) + facing.horizontal_offset() * 2 + if powered { 0 } else { 1 }),
         material material::NON_SOLID,
+        hardness 0.5,
         model { ("minecraft", "wooden_button") },
         variant format!("facing={},powered={}", face.variant_with_facing(facing), powered),
     }
@@ -3308,6 +3649,7 @@ define_blocks! {
         data if !nodrop { Some(facing.index()) } else { None },
         offset if !nodrop && facing != Direction::Up { Some(facing.horizontal_offset()) } else { None },
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "skull") },
         variant format!("facing={},nodrop={}", facing.as_string(), nodrop),
         collision {
@@ -3356,6 +3698,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(facing.horizontal_offset()),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "skull") },
         collision {
             let (min_x, min_y, min_z, max_x, max_y, max_z) = match facing {
@@ -3379,6 +3722,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(rotation as usize),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "skull") },
         collision {
             let (min_x, min_y, min_z, max_x, max_y, max_z) = (0.25, 0.0, 0.25, 0.75, 0.5, 0.75);
@@ -3401,6 +3745,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(facing.horizontal_offset()),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "zombie_wall_head") },
     }
     ZombieHead {
@@ -3410,6 +3755,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(rotation as usize),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "zombie_head") },
     }
     PlayerWallHead {
@@ -3424,6 +3770,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(facing.horizontal_offset()),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "player_wall_head") },
     }
     PlayerHead {
@@ -3433,6 +3780,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(rotation as usize),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "player_head") },
     }
     CreeperWallHead {
@@ -3447,6 +3795,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(facing.horizontal_offset()),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "creeper_wall_head") },
     }
     CreeperHead {
@@ -3456,6 +3805,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(rotation as usize),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "creeper_head") },
     }
     DragonWallHead {
@@ -3470,6 +3820,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(facing.horizontal_offset()),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "dragon_wall_head") },
     }
     DragonHead {
@@ -3479,6 +3830,7 @@ define_blocks! {
         data None::<usize>,
         offset Some(rotation as usize),
         material material::PARTIALLY_SOLID,
+        hardness 1.0,
         model { ("minecraft", "dragon_head") },
     }
     Anvil {
@@ -3494,6 +3846,9 @@ define_blocks! {
         data Some(facing.horizontal_index() | (match damage { 0 => 0x0, 1 => 0x4, 2 => 0x8, _ => unreachable!() })),
         offset Some(facing.horizontal_offset() + (damage as usize) * 4),
         material material::PARTIALLY_SOLID,
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "anvil") },
         variant format!("damage={},facing={}", damage, facing.as_string()),
         collision match facing.axis() {
@@ -3528,6 +3883,8 @@ define_blocks! {
             type_.offset() * 2 +
             facing.horizontal_offset() * (2 * 3)),
         material material::PARTIALLY_SOLID,
+        hardness 2.5,
+        tool Tool::Axe,
         model { ("minecraft", "trapped_chest") },
         variant format!("facing={}", facing.as_string()),
         collision vec![Aabb3::new(
@@ -3541,6 +3898,9 @@ define_blocks! {
         },
         data Some(power as usize),
         material material::NON_SOLID,
+        hardness 0.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "light_weighted_pressure_plate") },
         variant format!("power={}", power),
         collision vec![],
@@ -3551,6 +3911,9 @@ define_blocks! {
         },
         data Some(power as usize),
         material material::NON_SOLID,
+        hardness 0.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "heavy_weighted_pressure_plate") },
         variant format!("power={}", power),
         collision vec![],
@@ -3611,6 +3974,8 @@ define_blocks! {
         data if inverted { None } else { Some(power as usize) },
         offset Some((power as usize) + if inverted { 0 } else { 16 }),
         material material::PARTIALLY_SOLID,
+        hardness 0.2,
+        tool Tool::Axe,
         model { ("minecraft", "daylight_detector") },
         variant format!("power={}", power),
         collision vec![Aabb3::new(
@@ -3620,10 +3985,16 @@ define_blocks! {
     }
     RedstoneBlock {
         props {},
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "redstone_block") },
     }
     QuartzOre {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "quartz_ore") },
     }
     Hopper {
@@ -3647,6 +4018,9 @@ define_blocks! {
             _ => unreachable!(),
         } + if enabled { 0 } else { 5 }),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "hopper") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -3661,6 +4035,9 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 0.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", match variant {
             QuartzVariant::Normal => "quartz_block",
             QuartzVariant::Chiseled => "chiseled_quartz_block",
@@ -3691,6 +4068,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 0.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "quartz_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -3711,6 +4091,8 @@ define_blocks! {
         data Some(shape.data() | (if powered { 0x8 } else { 0x0 })),
         offset Some(shape.data() + (if powered { 0 } else { 6 })),
         material material::NON_SOLID,
+        hardness 0.7,
+        tool Tool::Pickaxe,
         model { ("minecraft", "activator_rail") },
         variant format!("powered={},shape={}", powered, shape.as_string()),
         collision vec![],
@@ -3729,6 +4111,9 @@ define_blocks! {
         },
         data Some(facing.index() | (if triggered { 0x8 } else { 0x0 })),
         offset Some(if triggered { 0 } else { 1 } + facing.offset() * 2),
+        hardness 3.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "dropper") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -3754,6 +4139,9 @@ define_blocks! {
             ],
         },
         data Some(color.data()),
+        hardness 1.25,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_stained_hardened_clay", color.as_string()) ) },
     }
     StainedGlassPane {
@@ -3790,6 +4178,7 @@ define_blocks! {
                     if east { 0 } else { 1<<4 } +
                     color.data() * (1<<5)),
         material material::TRANSPARENT,
+        hardness 0.3,
         model { ("minecraft", format!("{}_stained_glass_pane", color.as_string()) ) },
         collision pane_collision(north, south, east, west),
         update_state (world, pos) => {
@@ -3818,6 +4207,8 @@ define_blocks! {
                   | (if check_decay { 0x8 } else { 0x0 })),
         offset None,
         material material::LEAVES,
+        hardness 0.2,
+        tool Tool::Shears,
         model { ("minecraft", format!("{}_leaves", variant.as_string()) ) },
         tint TintType::Foliage,
     }
@@ -3831,6 +4222,8 @@ define_blocks! {
         },
         data Some(variant.data() | (axis.index() << 2)),
         offset None,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_log", variant.as_string()) ) },
         variant format!("axis={}", axis.as_string()),
     }
@@ -3855,6 +4248,8 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "acacia_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -3881,6 +4276,8 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "dark_oak_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -3894,6 +4291,7 @@ define_blocks! {
     Barrier {
         props {},
         material material::INVISIBLE,
+        hardness UNBREAKABLE,
         model { ("minecraft", "barrier") },
     }
     IronTrapDoor {
@@ -3922,6 +4320,9 @@ define_blocks! {
             if half == BlockHalf::Top { 0 } else { 1<<3 } +
             facing.horizontal_offset() * (1<<4)),
         material material::PARTIALLY_SOLID,
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "iron_trapdoor") },
         variant format!("facing={},half={},open={}", facing.as_string(), half.as_string(), open),
         collision trapdoor_collision(facing, half, open),
@@ -3935,6 +4336,9 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", variant.as_string() ) },
     }
     PrismarineStairs {
@@ -3963,6 +4367,9 @@ define_blocks! {
         data None::<usize>,
         offset Some(stair_offset(facing, half, shape, waterlogged).unwrap() + (2 * 5 * 2 * 4) * variant.data()),
         material material::PARTIALLY_SOLID,
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", match variant {
             PrismarineVariant::Normal => "prismarine_stairs",
             PrismarineVariant::Brick => "prismarine_brick_stairs",
@@ -3989,6 +4396,9 @@ define_blocks! {
         data None::<usize>,
         offset Some(if waterlogged { 0 } else { 1 } + type_.offset() * 2 + variant.data() * (2 * 3)),
         material material::PARTIALLY_SOLID,
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", match variant {
             PrismarineVariant::Normal => "prismarine_slab",
             PrismarineVariant::Brick => "prismarine_brick_slab",
@@ -4003,6 +4413,7 @@ define_blocks! {
             emitted_light: 15,
             ..material::SOLID
         },
+        hardness 0.3,
         model { ("minecraft", "sea_lantern") },
     }
     HayBlock {
@@ -4011,6 +4422,7 @@ define_blocks! {
         },
         data Some(match axis { Axis::X => 0x4, Axis::Y => 0x0, Axis::Z => 0x8, _ => unreachable!() }),
         offset Some(match axis { Axis::X => 0, Axis::Y => 1, Axis::Z => 2, _ => unreachable!() }),
+        hardness 0.5,
         model { ("minecraft", "hay_block") },
         variant format!("axis={}", axis.as_string()),
     }
@@ -4037,6 +4449,7 @@ define_blocks! {
         },
         data Some(color.data()),
         material material::PARTIALLY_SOLID,
+        hardness 0.1,
         model { ("minecraft", format!("{}_carpet", color.as_string()) ) },
         collision vec![Aabb3::new(
             Point3::new(0.0, 0.0, 0.0),
@@ -4045,14 +4458,22 @@ define_blocks! {
     }
     HardenedClay {
         props {},
+        hardness 1.25,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "hardened_clay") },
     }
     CoalBlock {
         props {},
+        hardness 5.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "coal_block") },
     }
     PackedIce {
         props {},
+        hardness 0.5,
+        tool Tool::Pickaxe,
         model { ("minecraft", "packed_ice") },
     }
     DoublePlant {
@@ -4121,6 +4542,8 @@ define_blocks! {
         data if color != ColoredVariant::White { None } else { Some(rotation.data()) },
         offset Some(rotation.data() + color.data() * 16),
         material material::NON_SOLID,
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "standing_banner") },
         variant format!("rotation={}", rotation.as_string()),
     }
@@ -4154,6 +4577,8 @@ define_blocks! {
         data if color != ColoredVariant::White { None } else { Some(facing.index()) },
         offset Some(facing.horizontal_offset() + color.data() * 4),
         material material::NON_SOLID,
+        hardness 1.0,
+        tool Tool::Axe,
         model { ("minecraft", "wall_banner") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -4164,6 +4589,8 @@ define_blocks! {
         data Some(power as usize),
         offset None,
         material material::PARTIALLY_SOLID,
+        hardness 0.2,
+        tool Tool::Axe,
         model { ("minecraft", "daylight_detector_inverted") },
         variant format!("power={}", power),
         collision vec![Aabb3::new(
@@ -4180,6 +4607,9 @@ define_blocks! {
             ],
         },
         data Some(variant.data()),
+        hardness 0.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", variant.as_string()) },
     }
     RedSandstoneStairs {
@@ -4203,6 +4633,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 0.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "red_sandstone_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -4228,6 +4661,8 @@ define_blocks! {
         data None::<usize>,
         offset Some(if waterlogged { 0 } else { 1 } + type_.offset() * 2 + variant.data() * (2 * 3)),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", format!("{}_slab", variant.as_string()) ) },
         variant format!("type={}", type_.as_string()),
         collision slab_collision(type_),
@@ -4257,6 +4692,9 @@ define_blocks! {
             variant.offsets(protocol_version).map(|o| if waterlogged { 0 } else { 1 } + type_.offset() * 2 + o * (2 * 3))
         },
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_slab", variant.as_string()) ) },
         variant format!("type={}", type_.as_string()),
         collision slab_collision(type_),
@@ -4271,6 +4709,9 @@ define_blocks! {
         data Some(variant.data() | (if seamless { 0x8 } else { 0x0 })),
         offset None,
         material material::SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_double_slab", variant.as_string()) ) },
         variant if seamless { "all" } else { "normal" },
     }
@@ -4282,6 +4723,9 @@ define_blocks! {
         data Some(variant.data() | (if half == BlockHalf::Top { 0x8 } else { 0x0 })),
         offset None,
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_slab", variant.as_string()) ) },
         variant format!("half={}", half.as_string()),
         collision slab_collision(half),
@@ -4303,6 +4747,9 @@ define_blocks! {
             StoneSlabVariant::RedSandstone => 3,
             _ => unreachable!(),
         }),
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("smooth_{}", variant.as_string()) ) },
     }
     SpruceFenceGate {
@@ -4320,6 +4767,8 @@ define_blocks! {
         data fence_gate_data(facing, in_wall, open, powered),
         offset fence_gate_offset(facing, in_wall, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "spruce_fence_gate") },
         variant format!("facing={},in_wall={},open={}", facing.as_string(), in_wall, open),
         collision fence_gate_collision(facing, in_wall, open),
@@ -4345,6 +4794,8 @@ define_blocks! {
         data fence_gate_data(facing, in_wall, open, powered),
         offset fence_gate_offset(facing, in_wall, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "birch_fence_gate") },
         variant format!("facing={},in_wall={},open={}", facing.as_string(), in_wall, open),
         collision fence_gate_collision(facing, in_wall, open),
@@ -4370,6 +4821,8 @@ define_blocks! {
         data fence_gate_data(facing, in_wall, open, powered),
         offset fence_gate_offset(facing, in_wall, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "jungle_fence_gate") },
         variant format!("facing={},in_wall={},open={}", facing.as_string(), in_wall, open),
         collision fence_gate_collision(facing, in_wall, open),
@@ -4395,6 +4848,8 @@ define_blocks! {
         data fence_gate_data(facing, in_wall, open, powered),
         offset fence_gate_offset(facing, in_wall, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "dark_oak_fence_gate") },
         variant format!("facing={},in_wall={},open={}", facing.as_string(), in_wall, open),
         collision fence_gate_collision(facing, in_wall, open),
@@ -4420,6 +4875,8 @@ define_blocks! {
         data fence_gate_data(facing, in_wall, open, powered),
         offset fence_gate_offset(facing, in_wall, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "acacia_fence_gate") },
         variant format!("facing={},in_wall={},open={}", facing.as_string(), in_wall, open),
         collision fence_gate_collision(facing, in_wall, open),
@@ -4445,6 +4902,8 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "spruce_fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -4474,6 +4933,8 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "birch_fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -4503,6 +4964,8 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "jungle_fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -4532,6 +4995,8 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "dark_oak_fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -4561,6 +5026,8 @@ define_blocks! {
                     if north { 0 } else { 1<<3 } +
                     if east { 0 } else { 1<<4 }),
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Axe,
         model { ("minecraft", "acacia_fence") },
         collision fence_collision(north, south, west, east),
         update_state (world, pos) => {
@@ -4591,6 +5058,8 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "spruce_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -4615,6 +5084,8 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "birch_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -4639,6 +5110,8 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "jungle_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -4663,6 +5136,8 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "acacia_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -4687,6 +5162,8 @@ define_blocks! {
         data door_data(facing, half, hinge, open, powered),
         offset door_offset(facing, half, hinge, open, powered),
         material material::PARTIALLY_SOLID,
+        hardness 3.0,
+        tool Tool::Axe,
         model { ("minecraft", "dark_oak_door") },
         variant format!("facing={},half={},hinge={},open={}", facing.as_string(), half.as_string(), hinge.as_string(), open),
         collision door_collision(facing, hinge, open),
@@ -4749,6 +5226,8 @@ define_blocks! {
                     if east { 0 } else { 1<<4 } +
                     if down { 0 } else { 1<<5 }),
         material material::PARTIALLY_SOLID,
+        hardness 0.4,
+        tool Tool::Axe,
         model { ("minecraft", "chorus_plant") },
         collision {
             let mut collision = vec![Aabb3::new(
@@ -4824,11 +5303,16 @@ define_blocks! {
         },
         data Some(age as usize),
         material material::PARTIALLY_SOLID,
+        hardness 0.4,
+        tool Tool::Axe,
         model { ("minecraft", "chorus_flower") },
         variant format!("age={}", age),
     }
     PurpurBlock {
         props {},
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "purpur_block") },
     }
     PurpurPillar {
@@ -4837,6 +5321,9 @@ define_blocks! {
         },
         data Some(match axis { Axis::X => 0x4, Axis::Y => 0x0, Axis::Z => 0x8, _ => unreachable!() }),
         offset Some(match axis { Axis::X => 0, Axis::Y => 1, Axis::Z => 2, _ => unreachable!() }),
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "purpur_pillar") },
         variant format!("axis={}", axis.as_string()),
     }
@@ -4861,6 +5348,9 @@ define_blocks! {
         data stair_data(facing, half, shape, waterlogged),
         offset stair_offset(facing, half, shape, waterlogged),
         material material::PARTIALLY_SOLID,
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "purpur_stairs") },
         variant format!("facing={},half={},shape={}", facing.as_string(), half.as_string(), shape.as_string()),
         collision stair_collision(facing, shape, half),
@@ -4871,6 +5361,9 @@ define_blocks! {
             variant: StoneSlabVariant = [StoneSlabVariant::Purpur],
         },
         offset None,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_double_slab", variant.as_string()) ) },
     }
     PurpurSlab {
@@ -4881,12 +5374,18 @@ define_blocks! {
         data if half == BlockHalf::Top { Some(0x8) } else { Some(0) },
         offset None,
         material material::PARTIALLY_SOLID,
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_slab", variant.as_string()) ) },
         variant format!("half={},variant=default", half.as_string()),
         collision slab_collision(half),
     }
     EndBricks {
         props {},
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "end_bricks") },
     }
     Beetroots {
@@ -4902,6 +5401,8 @@ define_blocks! {
     GrassPath {
         props {},
         material material::PARTIALLY_SOLID,
+        hardness 0.65,
+        tool Tool::Shovel,
         model { ("minecraft", "grass_path") },
         collision vec![Aabb3::new(
             Point3::new(0.0, 0.0, 0.0),
@@ -4911,6 +5412,7 @@ define_blocks! {
     EndGateway {
         props {},
         material material::NON_SOLID,
+        hardness UNBREAKABLE,
         model { ("minecraft", "end_gateway") },
         collision vec![],
     }
@@ -4928,6 +5430,7 @@ define_blocks! {
         },
         data Some(facing.index() | (if conditional { 0x8 } else { 0x0 })),
         offset Some(facing.offset() + (if conditional { 0 } else { 6 })),
+        hardness UNBREAKABLE,
         model { ("minecraft", "repeating_command_block") },
         variant format!("conditional={},facing={}", conditional, facing.as_string()),
     }
@@ -4945,6 +5448,7 @@ define_blocks! {
         },
         data Some(facing.index() | (if conditional { 0x8 } else { 0x0 })),
         offset Some(facing.offset() + (if conditional { 0 } else { 6 })),
+        hardness UNBREAKABLE,
         model { ("minecraft", "chain_command_block") },
         variant format!("conditional={},facing={}", conditional, facing.as_string()),
     }
@@ -4954,18 +5458,27 @@ define_blocks! {
         },
         data if age == 0 { Some(0) } else { None },
         offset Some(age as usize),
+        hardness 0.5,
+        tool Tool::Pickaxe,
         model { ("minecraft", "frosted_ice") },
     }
     MagmaBlock {
         props {},
+        hardness 0.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "magma") },
     }
     NetherWartBlock {
         props {},
+        hardness 1.0,
         model { ("minecraft", "nether_wart_block") },
     }
     RedNetherBrick {
         props {},
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "red_nether_brick") },
     }
     BoneBlock {
@@ -4974,6 +5487,9 @@ define_blocks! {
         },
         data Some(axis.index() << 2),
         offset Some(match axis { Axis::X => 0, Axis::Y => 1, Axis::Z => 2, _ => unreachable!() }),
+        hardness 2.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "bone_block") },
         variant format!("axis={}", axis.as_string()),
     }
@@ -4983,6 +5499,7 @@ define_blocks! {
             collidable: false,
             .. material::INVISIBLE
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "structure_void") },
         // TODO: a small hit box but no collision
         collision vec![],
@@ -5001,6 +5518,9 @@ define_blocks! {
         },
         data Some(facing.index() | (if powered { 0x8 } else { 0x0 })),
         offset Some(if powered { 0 } else { 1 } + facing.offset() * 2),
+        hardness 3.0,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "observer") },
         variant format!("facing={},powered={}", facing.as_string(), powered),
     }
@@ -5020,6 +5540,8 @@ define_blocks! {
         },
         data None::<usize>,
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "sponge") },
     }
     WhiteShulkerBox {
@@ -5035,6 +5557,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "white_wool") },
     }
     OrangeShulkerBox {
@@ -5050,6 +5574,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "orange_wool") },
     }
     MagentaShulkerBox {
@@ -5065,6 +5591,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "magenta_wool") },
     }
     LightBlueShulkerBox {
@@ -5080,6 +5608,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "light_blue_wool") },
     }
     YellowShulkerBox {
@@ -5095,6 +5625,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "yellow_wool") },
     }
     LimeShulkerBox {
@@ -5110,6 +5642,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "lime_wool") },
     }
     PinkShulkerBox {
@@ -5125,6 +5659,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "pink_wool") },
     }
     GrayShulkerBox {
@@ -5140,6 +5676,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "gray_wool") },
     }
     LightGrayShulkerBox {
@@ -5155,6 +5693,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "light_gray_wool") },
     }
     CyanShulkerBox {
@@ -5170,6 +5710,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "cyan_wool") },
     }
     PurpleShulkerBox {
@@ -5185,6 +5727,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "purple_wool") },
     }
     BlueShulkerBox {
@@ -5200,6 +5744,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "blue_wool") },
     }
     BrownShulkerBox {
@@ -5215,6 +5761,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "brown_wool") },
     }
     GreenShulkerBox {
@@ -5230,6 +5778,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "green_wool") },
     }
     RedShulkerBox {
@@ -5245,6 +5795,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "red_wool") },
     }
     BlackShulkerBox {
@@ -5260,6 +5812,8 @@ define_blocks! {
         },
         data Some(facing.index()),
         offset Some(facing.offset()),
+        hardness 2.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "black_wool") },
     }
     WhiteGlazedTerracotta {
@@ -5273,6 +5827,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "white_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5287,6 +5844,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "orange_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5301,6 +5861,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "magenta_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5315,6 +5878,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "light_blue_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5329,6 +5895,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "yellow_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5343,6 +5912,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "lime_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5357,6 +5929,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "pink_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5371,6 +5946,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "gray_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5385,6 +5963,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "silver_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5399,6 +5980,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "cyan_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5413,6 +5997,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "purple_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5427,6 +6014,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "blue_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5441,6 +6031,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "brown_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5455,6 +6048,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "green_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5469,6 +6065,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "red_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5483,6 +6082,9 @@ define_blocks! {
         },
         data Some(facing.horizontal_index()),
         offset Some(facing.horizontal_offset()),
+        hardness 1.4,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", "black_glazed_terracotta") },
         variant format!("facing={}", facing.as_string()),
     }
@@ -5508,6 +6110,9 @@ define_blocks! {
             ],
         },
         data Some(color.data()),
+        hardness 1.8,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_concrete", color.as_string()) ) },
     }
     ConcretePowder {
@@ -5532,6 +6137,8 @@ define_blocks! {
             ],
         },
         data Some(color.data()),
+        hardness 0.5,
+        tool Tool::Shovel,
         model { ("minecraft", format!("{}_concrete_powder", color.as_string()) ) },
     }
     Kelp {
@@ -5552,6 +6159,7 @@ define_blocks! {
         props {},
         data None::<usize>,
         offset Some(0),
+        hardness 0.5,
         model { ("minecraft", "dried_kelp_block") },
     }
     TurtleEgg {
@@ -5561,6 +6169,7 @@ define_blocks! {
         },
         data None::<usize>,
         offset Some((hatch as usize) + ((age - 1) as usize) * 3),
+        hardness 0.5,
         model { ("minecraft", "turtle_egg") },
     }
     CoralBlock {
@@ -5580,6 +6189,9 @@ define_blocks! {
         },
         data None::<usize>,
         offset Some(variant.offset()),
+        hardness 1.5,
+        tool Tool::Pickaxe,
+        requires_tool true,
         model { ("minecraft", format!("{}_block", variant.as_string())) },
     }
     Coral {
@@ -5666,6 +6278,8 @@ define_blocks! {
         props {},
         data None::<usize>,
         offset Some(0),
+        hardness 2.8,
+        tool Tool::Pickaxe,
         model { ("minecraft", "blue_ice") },
     }
     Conduit {
@@ -5675,6 +6289,8 @@ define_blocks! {
         data None::<usize>,
         offset Some(if waterlogged { 0 } else { 1 }),
         material material::NON_SOLID,
+        hardness 3.0,
+        tool Tool::Pickaxe,
         model { ("minecraft", "conduit") },
     }
     VoidAir {
@@ -5685,6 +6301,7 @@ define_blocks! {
             collidable: false,
             .. material::INVISIBLE
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "air") },
         collision vec![],
     }
@@ -5696,6 +6313,7 @@ define_blocks! {
             collidable: false,
             .. material::INVISIBLE
         },
+        hardness UNBREAKABLE,
         model { ("minecraft", "air") },
         collision vec![],
     }
@@ -5705,6 +6323,7 @@ define_blocks! {
         },
         data None::<usize>,
         offset Some(if drag { 0 } else { 1 }),
+        hardness UNBREAKABLE,
         model { ("minecraft", "bubble_column") },
     }
     Missing253 {
@@ -5729,6 +6348,7 @@ define_blocks! {
             ],
         },
         data Some(mode.data()),
+        hardness UNBREAKABLE,
         model { ("minecraft", "structure_block") },
         variant format!("mode={}", mode.as_string()),
     }
@@ -5834,6 +6454,104 @@ mod tests {
     }
 }
 
+impl Block {
+    /// Whether placing a block here replaces this one, instead of being placed against it.
+    pub fn is_replaceable(&self) -> bool {
+        match *self {
+            Air {}
+            | VoidAir {}
+            | CaveAir {}
+            | FlowingWater { .. }
+            | Water { .. }
+            | FlowingLava { .. }
+            | Lava { .. }
+            | BubbleColumn { .. }
+            | Seagrass {}
+            | TallSeagrass { .. }
+            | DeadBush {}
+            | Vine { .. }
+            | Fire { .. }
+            | SoulFire {}
+            | StructureVoid {} => true,
+            TallGrass { variant } => variant != TallGrassVariant::DeadBush,
+            DoublePlant { variant, .. } => matches!(
+                variant,
+                DoublePlantVariant::DoubleTallgrass | DoublePlantVariant::LargeFern
+            ),
+            SnowLayer { layers } => layers == 1,
+            _ => false,
+        }
+    }
+
+    /// Whether right clicking the block uses it, instead of placing the held block against
+    /// it. Sneaking players place blocks either way.
+    pub fn is_interactable(&self) -> bool {
+        matches!(
+            *self,
+            Chest { .. }
+                | TrappedChest { .. }
+                | EnderChest { .. }
+                | CraftingTable {}
+                | Furnace { .. }
+                | FurnaceLit { .. }
+                | Dispenser { .. }
+                | Dropper { .. }
+                | Hopper { .. }
+                | BrewingStand { .. }
+                | EnchantingTable {}
+                | Anvil { .. }
+                | Beacon {}
+                | WhiteShulkerBox { .. }
+                | OrangeShulkerBox { .. }
+                | MagentaShulkerBox { .. }
+                | LightBlueShulkerBox { .. }
+                | YellowShulkerBox { .. }
+                | LimeShulkerBox { .. }
+                | PinkShulkerBox { .. }
+                | GrayShulkerBox { .. }
+                | LightGrayShulkerBox { .. }
+                | CyanShulkerBox { .. }
+                | PurpleShulkerBox { .. }
+                | BlueShulkerBox { .. }
+                | BrownShulkerBox { .. }
+                | GreenShulkerBox { .. }
+                | RedShulkerBox { .. }
+                | BlackShulkerBox { .. }
+                | ShulkerBox { .. }
+                | WoodenDoor { .. }
+                | SpruceDoor { .. }
+                | BirchDoor { .. }
+                | JungleDoor { .. }
+                | AcaciaDoor { .. }
+                | DarkOakDoor { .. }
+                | TrapDoor { .. }
+                | FenceGate { .. }
+                | SpruceFenceGate { .. }
+                | BirchFenceGate { .. }
+                | JungleFenceGate { .. }
+                | DarkOakFenceGate { .. }
+                | AcaciaFenceGate { .. }
+                | Lever { .. }
+                | StoneButton { .. }
+                | WoodenButton { .. }
+                | Repeater { .. }
+                | RepeaterPowered { .. }
+                | ComparatorUnpowered { .. }
+                | ComparatorPowered { .. }
+                | DaylightDetector { .. }
+                | DaylightDetectorInverted { .. }
+                | NoteBlock { .. }
+                | Jukebox { .. }
+                | Bed { .. }
+                | Cake { .. }
+                | DragonEgg {}
+                | CommandBlock { .. }
+                | RepeatingCommandBlock { .. }
+                | ChainCommandBlock { .. }
+        )
+    }
+}
+
 fn can_burn<W: WorldAccess>(world: &W, pos: Position) -> bool {
     matches!(
         world.get_block(pos),
@@ -6195,7 +6913,7 @@ fn piston_collision(extended: bool, facing: Direction) -> Vec<Aabb3<f64>> {
             Direction::Down => (0.0, 0.25, 0.0, 1.0, 1.0, 1.0),
             Direction::North => (0.0, 0.0, 0.25, 1.0, 1.0, 1.0),
             Direction::South => (0.0, 0.0, 0.0, 1.0, 1.0, 0.75),
-            Direction::West => (0.25, 0.0, 0.0, 1.0, 1.0, 0.75),
+            Direction::West => (0.25, 0.0, 0.0, 1.0, 1.0, 1.0),
             Direction::East => (0.0, 0.0, 0.0, 0.75, 1.0, 1.0),
             _ => unreachable!(),
         }
@@ -12,78 +12,131 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::mem;
+use std::sync::RwLock;
 
-#[derive(Debug, Clone)]
+use lazy_static::lazy_static;
+use log::warn;
+
+lazy_static! {
+    static ref TRANSLATIONS: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+}
+
+/// Replaces the translations `translate` components are resolved with, the client loads
+/// them from the resource packs.
+pub fn set_translations(translations: HashMap<String, String>) {
+    *TRANSLATIONS.write().unwrap() = translations;
+}
+
+/// Looks up the translation of `key` and fills in `args`, the key itself is returned when
+/// there's no translation for it.
+pub fn translate(key: &str, args: &[String]) -> String {
+    let translations = TRANSLATIONS.read().unwrap();
+    // Chat needs to be readable even before the vanilla assets are downloaded
+    let pattern = translations.get(key).map(String::as_str).or(match key {
+        "chat.type.text" => Some("<%s> %s"),
+        "chat.type.announcement" => Some("[%s] %s"),
+        _ => None,
+    });
+    match pattern {
+        Some(pattern) => format_translation(pattern, args),
+        None => key.to_owned(),
+    }
+}
+
+/// The translation of `key` without any arguments, or `default` when there's none yet,
+/// for the client's own strings which have to be shown before the assets are downloaded.
+pub fn translate_or(key: &str, default: &str) -> String {
+    match TRANSLATIONS.read().unwrap().get(key) {
+        Some(translation) => translation.clone(),
+        None => default.to_owned(),
+    }
+}
+
+/// Substitutes `args` into a translation, `%s` takes the next argument, `%1$s` a specific
+/// one and `%%` is a literal percent sign.
+fn format_translation(pattern: &str, args: &[String]) -> String {
+    let mut out = String::new();
+    let mut next_arg = 0;
+    let mut rest = pattern;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix('%') {
+            out.push('%');
+            rest = after;
+            continue;
+        }
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (position, conversion) = match rest[digits..].strip_prefix('$') {
+            Some(conversion) if digits > 0 => (rest[..digits].parse::<usize>().ok(), conversion),
+            _ => (None, rest),
+        };
+        if !conversion.starts_with('s') && !conversion.starts_with('d') {
+            out.push('%');
+            continue;
+        }
+        let index = match position {
+            Some(position) => position.checked_sub(1),
+            None => {
+                next_arg += 1;
+                Some(next_arg - 1)
+            }
+        };
+        if let Some(arg) = index.and_then(|index| args.get(index)) {
+            out.push_str(arg);
+        }
+        rest = &conversion[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Component {
     Text(TextComponent),
+    /// Translated when shown, so the text follows the language the client is set to.
+    Translate(TranslateComponent),
 }
 
 impl Component {
     pub fn from_string(str: &str) -> Self {
-        let mut component;
         match serde_json::from_str::<serde_json::Value>(str) {
-            Ok(value) => component = Component::from_value(&value),
+            Ok(value) => Component::from_value(&value),
             // Sometimes mojang sends a literal string, so we should interpret it literally
-            Err(_) => {
-                component = Component::Text(TextComponent::new(str));
-                convert_legacy(&mut component);
-            }
+            Err(_) => Component::from_legacy(str),
         }
-        component
     }
 
     pub fn from_value(v: &serde_json::Value) -> Self {
         let mut modifier = Modifier::from_value(v);
         if let Some(val) = v.as_str() {
+            // Old servers send their MOTD as a plain string with legacy codes
+            if val.contains(LEGACY_CHAR) {
+                return Component::from_legacy(val);
+            }
             Component::Text(TextComponent {
                 text: val.to_owned(),
                 modifier,
             })
         } else if v.get("text").is_some() {
             Component::Text(TextComponent::from_value(v, modifier))
-        } else if let Some(translate) = v.get("translate") {
-            let translate_key = translate.as_str().unwrap_or_default();
-            if let Some(serde_json::Value::Array(args)) = v.get("with") {
-                // TODO: recursively build components, avoid throwing away all but "text"
-                let text_args: Vec<&str> = args
-                    .iter()
-                    .map(|v| {
-                        if let serde_json::Value::Object(obj) = v {
-                            // Usernames might be in "extra":["text":"foo"] and "text":"" empty for
-                            // some reason; use extra instead if present TODO: use both
-                            if let Some(serde_json::Value::Array(extra)) = obj.get("extra") {
-                                if let Some(item) = extra.get(0) {
-                                    if let Some(text) = item.get("text") {
-                                        return text.as_str().unwrap_or_default();
-                                    }
-                                }
-                            }
-
-                            if let Some(text) = obj.get("text") {
-                                text.as_str().unwrap_or_default()
-                            } else {
-                                Default::default()
-                            }
-                        } else {
-                            v.as_str().unwrap_or_default()
-                        }
-                    })
-                    .collect();
-                // TODO: translations, https://wiki.vg/Chat#Translation_component
-                Component::Text(TextComponent::new(
-                    match translate_key {
-                        "chat.type.text" => format!("<{}> {}", text_args[0], text_args[1]),
-                        "chat.type.announcement" => format!("[{}] {}", text_args[0], text_args[1]),
-                        _ => format!("unhandled: {}", translate_key),
-                    }
-                    .as_str(),
-                ))
-            } else {
-                // TODO
-                Component::Text(TextComponent::new(translate_key))
-            }
+        } else if let Some(key) = v.get("translate") {
+            let args = match v.get("with") {
+                Some(serde_json::Value::Array(args)) => {
+                    args.iter().map(Component::from_value).collect()
+                }
+                _ => Vec::new(),
+            };
+            Component::Translate(TranslateComponent {
+                key: key.as_str().unwrap_or_default().to_owned(),
+                args,
+                modifier,
+            })
         } else {
             modifier.color = Some(Color::RGB(255, 0, 0));
             Component::Text(TextComponent {
@@ -96,12 +149,127 @@ impl Component {
     pub fn to_value(&self) -> serde_json::Value {
         unimplemented!()
     }
+
+    /// Parses text formatted with legacy `§` codes into a component with a span for each
+    /// change of style.
+    pub fn from_legacy(text: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut current = TextComponent::new("");
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c != LEGACY_CHAR {
+                current.text.push(c);
+                continue;
+            }
+            let code = match chars.next() {
+                Some(code) => code.to_ascii_lowercase(),
+                None => break,
+            };
+            // Colors and resets clear the formatting, the others add to it
+            let mut modifier = if code.is_ascii_hexdigit() || code == 'r' {
+                Modifier::default()
+            } else {
+                current.modifier.clone()
+            };
+            match code {
+                '0' => modifier.color = Some(Color::Black),
+                '1' => modifier.color = Some(Color::DarkBlue),
+                '2' => modifier.color = Some(Color::DarkGreen),
+                '3' => modifier.color = Some(Color::DarkAqua),
+                '4' => modifier.color = Some(Color::DarkRed),
+                '5' => modifier.color = Some(Color::DarkPurple),
+                '6' => modifier.color = Some(Color::Gold),
+                '7' => modifier.color = Some(Color::Gray),
+                '8' => modifier.color = Some(Color::DarkGray),
+                '9' => modifier.color = Some(Color::Blue),
+                'a' => modifier.color = Some(Color::Green),
+                'b' => modifier.color = Some(Color::Aqua),
+                'c' => modifier.color = Some(Color::Red),
+                'd' => modifier.color = Some(Color::LightPurple),
+                'e' => modifier.color = Some(Color::Yellow),
+                'f' => modifier.color = Some(Color::White),
+                'k' => modifier.obfuscated = Some(true),
+                'l' => modifier.bold = Some(true),
+                'm' => modifier.strikethrough = Some(true),
+                'n' => modifier.underlined = Some(true),
+                'o' => modifier.italic = Some(true),
+                'r' => {}
+                _ => warn!("Unsupported color code {:?} in text '{}'", code, text),
+            }
+            if !current.text.is_empty() {
+                parts.push(Component::Text(mem::replace(
+                    &mut current,
+                    TextComponent::new(""),
+                )));
+            }
+            current.modifier = modifier;
+        }
+        if !current.text.is_empty() {
+            parts.push(Component::Text(current));
+        }
+        Component::Text(TextComponent {
+            text: "".to_owned(),
+            modifier: Modifier {
+                extra: Some(parts),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// The text without any formatting, legacy color codes are stripped too, e.g. for logs.
+    pub fn to_plain_string(&self) -> String {
+        let mut out = String::new();
+        self.legacy_converted()
+            .render(&Modifier::default(), false, &mut out);
+        out
+    }
+
+    /// The text with its colors and formatting as ANSI escape codes, for terminals.
+    pub fn to_ansi_string(&self) -> String {
+        let mut out = String::new();
+        self.legacy_converted()
+            .render(&Modifier::default(), true, &mut out);
+        out
+    }
+
+    fn legacy_converted(&self) -> Component {
+        let mut component = self.clone();
+        convert_legacy(&mut component);
+        component
+    }
+
+    /// Appends the text of this component and its extras to `out`, which inherit the
+    /// formatting they don't set themselves from their parent.
+    fn render(&self, parent: &Modifier, ansi: bool, out: &mut String) {
+        match *self {
+            Component::Text(ref txt) => {
+                let modifier = txt.modifier.inherit(parent);
+                if !txt.text.is_empty() {
+                    let codes = modifier.ansi_codes();
+                    if ansi && !codes.is_empty() {
+                        out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), txt.text));
+                    } else {
+                        out.push_str(&txt.text);
+                    }
+                }
+                if let Some(ref extra) = txt.modifier.extra {
+                    for c in extra {
+                        c.render(&modifier, ansi, out);
+                    }
+                }
+            }
+            Component::Translate(ref translation) => {
+                Component::Text(translation.to_text()).render(parent, ansi, out)
+            }
+        }
+    }
 }
 
 impl fmt::Display for Component {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Component::Text(ref txt) => write!(f, "{}", txt),
+            Component::Translate(ref translation) => write!(f, "{}", translation.to_text()),
         }
     }
 }
@@ -115,7 +283,7 @@ impl Default for Component {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Modifier {
     pub extra: Option<Vec<Component>>,
     pub bold: Option<bool>,
@@ -157,9 +325,42 @@ impl Modifier {
     pub fn to_value(&self) -> serde_json::Value {
         unimplemented!()
     }
+
+    /// This modifier's formatting with what it doesn't set taken from `parent`, without
+    /// the extras.
+    fn inherit(&self, parent: &Modifier) -> Modifier {
+        Modifier {
+            extra: None,
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            color: self.color.or(parent.color),
+        }
+    }
+
+    /// The ANSI SGR parameters for this formatting, obfuscated text has no equivalent.
+    fn ansi_codes(&self) -> Vec<String> {
+        let mut codes = Vec::new();
+        for &(set, code) in &[
+            (self.bold, "1"),
+            (self.italic, "3"),
+            (self.underlined, "4"),
+            (self.strikethrough, "9"),
+        ] {
+            if set == Some(true) {
+                codes.push(code.to_owned());
+            }
+        }
+        if let Some(color) = self.color {
+            codes.push(color.ansi_code());
+        }
+        codes
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TextComponent {
     pub text: String,
     pub modifier: Modifier,
@@ -199,7 +400,31 @@ impl fmt::Display for TextComponent {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// A translation key and the components filled into its translation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslateComponent {
+    pub key: String,
+    pub args: Vec<Component>,
+    pub modifier: Modifier,
+}
+
+impl TranslateComponent {
+    /// The translation in the language currently loaded.
+    pub fn to_text(&self) -> TextComponent {
+        // The arguments lose their own formatting
+        let args = self
+            .args
+            .iter()
+            .map(Component::to_plain_string)
+            .collect::<Vec<_>>();
+        TextComponent {
+            text: translate(&self.key, &args),
+            modifier: self.modifier.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Color {
     Black,
     DarkBlue,
@@ -286,6 +511,29 @@ impl Color {
         }
     }
 
+    /// The ANSI SGR parameter for this color as the foreground, hex colors use 24-bit color.
+    pub fn ansi_code(&self) -> String {
+        match *self {
+            Color::Black => "30".to_owned(),
+            Color::DarkBlue => "34".to_owned(),
+            Color::DarkGreen => "32".to_owned(),
+            Color::DarkAqua => "36".to_owned(),
+            Color::DarkRed => "31".to_owned(),
+            Color::DarkPurple => "35".to_owned(),
+            Color::Gold => "33".to_owned(),
+            Color::Gray => "37".to_owned(),
+            Color::DarkGray => "90".to_owned(),
+            Color::Blue => "94".to_owned(),
+            Color::Green => "92".to_owned(),
+            Color::Aqua => "96".to_owned(),
+            Color::Red => "91".to_owned(),
+            Color::LightPurple => "95".to_owned(),
+            Color::Yellow => "93".to_owned(),
+            Color::White => "97".to_owned(),
+            Color::RGB(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+        }
+    }
+
     pub fn to_rgb(&self) -> (u8, u8, u8) {
         match *self {
             Color::Black => (0, 0, 0),
@@ -333,89 +581,112 @@ fn test_color_from() {
     }
 }
 
+#[test]
+fn test_component_to_string() {
+    let component = Component::from_string(
+        r##"{"text":"Hello ","color":"gold","extra":[
+            {"text":"world","bold":true},
+            {"text":"!","color":"#102030","extra":[{"text":"?","italic":true}]}
+        ]}"##,
+    );
+    assert_eq!(component.to_plain_string(), "Hello world!?");
+    assert_eq!(
+        component.to_ansi_string(),
+        "\x1b[33mHello \x1b[0m\x1b[1;33mworld\x1b[0m\x1b[38;2;16;32;48m!\x1b[0m\
+         \x1b[3;38;2;16;32;48m?\x1b[0m"
+    );
+
+    let legacy = Component::from_string("§cRed §lbold");
+    assert_eq!(legacy.to_plain_string(), "Red bold");
+    assert_eq!(
+        legacy.to_ansi_string(),
+        "\x1b[91mRed \x1b[0m\x1b[1;91mbold\x1b[0m"
+    );
+}
+
+#[test]
+fn test_translate() {
+    let args = vec!["Steve".to_owned(), "Skeleton".to_owned()];
+    assert_eq!(
+        format_translation("%1$s was shot by %2$s", &args),
+        "Steve was shot by Skeleton"
+    );
+    assert_eq!(
+        format_translation("%2$s shot %1$s", &args),
+        "Skeleton shot Steve"
+    );
+    assert_eq!(
+        format_translation("%s and %s, 100%%", &args),
+        "Steve and Skeleton, 100%"
+    );
+    assert_eq!(translate("test.missing.key", &args), "test.missing.key");
+
+    let component = Component::from_string(
+        r#"{"translate":"chat.type.text","with":["Steve",{"text":"","extra":[{"text":"hi"}]}]}"#,
+    );
+    assert_eq!(component.to_plain_string(), "<Steve> hi");
+
+    // Translated when shown instead of when received
+    let component = Component::from_string(r#"{"translate":"test.later.key","with":["Alex"]}"#);
+    assert!(matches!(component, Component::Translate(_)));
+    assert_eq!(component.to_plain_string(), "test.later.key");
+    let mut translations = HashMap::new();
+    translations.insert("test.later.key".to_owned(), "Hello %s".to_owned());
+    set_translations(translations);
+    assert_eq!(component.to_plain_string(), "Hello Alex");
+    assert_eq!(translate_or("test.later.key", "Default"), "Hello %s");
+    assert_eq!(translate_or("test.missing.key", "Default"), "Default");
+}
+
+#[test]
+fn test_component_from_legacy() {
+    let component = Component::from_legacy("§aGreen §lBold");
+    let spans = match component {
+        Component::Text(txt) => txt.modifier.extra.unwrap(),
+        component => panic!("expected text, got {:?}", component),
+    };
+    assert_eq!(spans.len(), 2);
+    match (&spans[0], &spans[1]) {
+        (Component::Text(green), Component::Text(bold)) => {
+            assert_eq!(green.text, "Green ");
+            assert!(matches!(green.modifier.color, Some(Color::Green)));
+            assert_eq!(green.modifier.bold, None);
+            assert_eq!(bold.text, "Bold");
+            assert!(matches!(bold.modifier.color, Some(Color::Green)));
+            assert_eq!(bold.modifier.bold, Some(true));
+        }
+        spans => panic!("expected text, got {:?}", spans),
+    }
+
+    let reset = Component::from_legacy("§c§lLoud§r quiet");
+    assert_eq!(reset.to_ansi_string(), "\x1b[1;91mLoud\x1b[0m quiet");
+}
+
 const LEGACY_CHAR: char = '§';
 
 pub fn convert_legacy(c: &mut Component) {
-    match *c {
-        Component::Text(ref mut txt) => {
-            if let Some(ref mut extra) = txt.modifier.extra.as_mut() {
-                for e in extra.iter_mut() {
-                    convert_legacy(e);
-                }
+    if let Component::Translate(ref translation) = *c {
+        *c = Component::Text(translation.to_text());
+    }
+    if let Component::Text(ref mut txt) = *c {
+        if let Some(ref mut extra) = txt.modifier.extra.as_mut() {
+            for e in extra.iter_mut() {
+                convert_legacy(e);
             }
-            if txt.text.contains(LEGACY_CHAR) {
-                let mut parts = Vec::new();
-                let mut last = 0;
-                let mut current = TextComponent::new("");
-                {
-                    let mut iter = txt.text.char_indices();
-                    while let Some((i, c)) = iter.next() {
-                        if c == LEGACY_CHAR {
-                            let next = match iter.next() {
-                                Some(val) => val,
-                                None => break,
-                            };
-                            let color_char = next.1.to_lowercase().next().unwrap();
-                            current.text = txt.text[last..i].to_owned();
-                            last = next.0 + 1;
-
-                            let mut modifier = if ('a'..='f').contains(&color_char)
-                                || ('0'..='9').contains(&color_char)
-                            {
-                                Default::default()
-                            } else {
-                                current.modifier.clone()
-                            };
-
-                            let new = TextComponent::new("");
-                            parts.push(Component::Text(mem::replace(&mut current, new)));
-
-                            match color_char {
-                                '0' => modifier.color = Some(Color::Black),
-                                '1' => modifier.color = Some(Color::DarkBlue),
-                                '2' => modifier.color = Some(Color::DarkGreen),
-                                '3' => modifier.color = Some(Color::DarkAqua),
-                                '4' => modifier.color = Some(Color::DarkRed),
-                                '5' => modifier.color = Some(Color::DarkPurple),
-                                '6' => modifier.color = Some(Color::Gold),
-                                '7' => modifier.color = Some(Color::Gray),
-                                '8' => modifier.color = Some(Color::DarkGray),
-                                '9' => modifier.color = Some(Color::Blue),
-                                'a' => modifier.color = Some(Color::Green),
-                                'b' => modifier.color = Some(Color::Aqua),
-                                'c' => modifier.color = Some(Color::Red),
-                                'd' => modifier.color = Some(Color::LightPurple),
-                                'e' => modifier.color = Some(Color::Yellow),
-                                'f' => modifier.color = Some(Color::White),
-                                'k' => modifier.obfuscated = Some(true),
-                                'l' => modifier.bold = Some(true),
-                                'm' => modifier.strikethrough = Some(true),
-                                'n' => modifier.underlined = Some(true),
-                                'o' => modifier.italic = Some(true),
-                                'r' => {}
-                                _ => println!(
-                                    "warning: unsupported color code {:?} in text '{}'",
-                                    color_char, txt
-                                ),
-                            }
-
-                            current.modifier = modifier;
-                        }
-                    }
-                }
-                if last < txt.text.len() {
-                    current.text = txt.text[last..].to_owned();
-                    parts.push(Component::Text(current));
-                }
+        }
+        if txt.text.contains(LEGACY_CHAR) {
+            let parts = match Component::from_legacy(&txt.text) {
+                Component::Text(legacy) => legacy.modifier.extra.unwrap_or_default(),
+                Component::Translate(_) => unreachable!(),
+            };
 
-                let old = mem::replace(&mut txt.modifier.extra, Some(parts));
-                if let Some(old_extra) = old {
-                    if let Some(ref mut extra) = txt.modifier.extra.as_mut() {
-                        extra.extend(old_extra);
-                    }
+            let old = mem::replace(&mut txt.modifier.extra, Some(parts));
+            if let Some(old_extra) = old {
+                if let Some(ref mut extra) = txt.modifier.extra.as_mut() {
+                    extra.extend(old_extra);
                 }
-                txt.text = "".to_owned();
             }
+            txt.text = "".to_owned();
         }
     }
 }
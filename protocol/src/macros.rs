@@ -1,3 +1,29 @@
+/// Reads a field of `state_packets!`, ones with a `when` condition are read with
+/// `read_present`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! read_field {
+    ($buf:expr) => {
+        Serializable::read_from($buf)
+    };
+    ($buf:expr, $($cond:expr),+) => {
+        Serializable::read_present($buf)
+    };
+}
+
+/// Writes a field of `state_packets!`, ones with a `when` condition are written with
+/// `write_present`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! write_field {
+    ($field:expr, $buf:expr) => {
+        $field.write_to($buf)
+    };
+    ($field:expr, $buf:expr, $($cond:expr),+) => {
+        $field.write_present($buf)
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! create_ids {
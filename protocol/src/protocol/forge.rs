@@ -194,7 +194,122 @@ impl Serializable for FmlHs {
 
 pub mod fml2 {
     // https://wiki.vg/Minecraft_Forge_Handshake#FML2_protocol_.281.13_-_Current.29
+    //
+    // Supported: Forge 1.13 - 1.16 servers (FML network version 2) whose mods only talk over
+    // the `fml:handshake` login channel, i.e. the mod list, registry and configuration sync.
+    // Mods adding their own login channels wrapped in `fml:loginwrapper` aren't supported.
     use super::*;
+    use serde_json::Value;
+
+    /// The `forgeData` object of a server list ping response.
+    #[derive(Clone, Default, Debug)]
+    pub struct ForgeData {
+        pub mods: Vec<ForgeMod>,
+        pub channels: Vec<StatusChannel>,
+        /// Forge itself only announces registries in the `ModList` handshake packet, so this is
+        /// usually empty.
+        pub registries: Vec<Registry>,
+        pub fml_network_version: i64,
+    }
+
+    /// A network channel announced in the server list ping.
+    #[derive(Clone, Default, Debug)]
+    pub struct StatusChannel {
+        pub channel: Channel,
+        /// Whether clients must have the channel to be able to join.
+        pub required: bool,
+    }
+
+    impl ForgeData {
+        pub fn from_json(val: &Value) -> Result<ForgeData, Error> {
+            fn invalid(what: &str) -> Error {
+                Error::Err(format!("Invalid forgeData {}", what))
+            }
+            fn string(obj: &Value, key: &str) -> Result<String, Error> {
+                obj.get(key)
+                    .and_then(Value::as_str)
+                    .map(|v| v.to_owned())
+                    .ok_or_else(|| invalid(key))
+            }
+            fn array<'a>(obj: &'a Value, key: &str) -> Result<&'a [Value], Error> {
+                match obj.get(key) {
+                    Some(Value::Array(items)) => Ok(items),
+                    Some(_) => Err(invalid(key)),
+                    None => Ok(&[]),
+                }
+            }
+
+            let mut mods = vec![];
+            for item in array(val, "mods")? {
+                mods.push(ForgeMod {
+                    modid: string(item, "modId")?,
+                    version: string(item, "modmarker")?,
+                });
+            }
+            let mut channels = vec![];
+            for item in array(val, "channels")? {
+                channels.push(StatusChannel {
+                    channel: Channel {
+                        name: string(item, "res")?,
+                        version: string(item, "version")?,
+                    },
+                    required: item
+                        .get("required")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false),
+                });
+            }
+            let mut registries = vec![];
+            for item in array(val, "registries")? {
+                registries.push(Registry {
+                    name: string(item, "name")?,
+                    marker: string(item, "marker").unwrap_or_default(),
+                });
+            }
+            let fml_network_version = val
+                .get("fmlNetworkVersion")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| invalid("fmlNetworkVersion"))?;
+
+            Ok(ForgeData {
+                mods,
+                channels,
+                registries,
+                fml_network_version,
+            })
+        }
+
+        /// Builds the reply to the server's `ModList`, claiming to have the same mods and
+        /// channels. Registries get the marker announced in the ping, if any, and channels the
+        /// ping marks as required but the `ModList` misses are added.
+        pub fn mod_list_reply(
+            &self,
+            mod_names: LenPrefixed<VarInt, String>,
+            mut channels: LenPrefixed<VarInt, Channel>,
+            mut registries: LenPrefixed<VarInt, Registry>,
+        ) -> FmlHandshake {
+            for status_channel in &self.channels {
+                if status_channel.required
+                    && !channels
+                        .data
+                        .iter()
+                        .any(|c| c.name == status_channel.channel.name)
+                {
+                    channels.data.push(status_channel.channel.clone());
+                }
+            }
+            for registry in &mut registries.data {
+                if let Some(known) = self.registries.iter().find(|r| r.name == registry.name) {
+                    registry.marker = known.marker.clone();
+                }
+            }
+            FmlHandshake::ModListReply {
+                mod_names,
+                channels,
+                registries,
+            }
+        }
+    }
 
     #[derive(Clone, Default, Debug)]
     pub struct Channel {
@@ -281,7 +396,12 @@ pub mod fml2 {
                     filename: Serializable::read_from(buf)?,
                     contents: Serializable::read_from(buf)?,
                 },
-                _ => unimplemented!(),
+                _ => {
+                    return Err(Error::Err(format!(
+                        "Unsupported FML2 handshake packet id: {}",
+                        id
+                    )))
+                }
             })
         }
     }
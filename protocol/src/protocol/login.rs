@@ -0,0 +1,156 @@
+//! Logging in to a server, from the handshake until the connection is in the play state.
+
+use std::str::FromStr;
+
+use log::{debug, warn};
+use rand::Rng;
+
+use super::packet::login::clientbound::LoginPluginRequest;
+use super::packet::login::serverbound::{EncryptionResponse, EncryptionResponse_i16, LoginStart};
+use super::packet::{handshake::serverbound::Handshake, Packet};
+use super::{mojang, Conn, Error, LenPrefixedBytes, State, VarInt, UUID};
+
+/// Who the server logged the player in as.
+#[derive(Debug)]
+pub struct LoginResult {
+    pub username: String,
+    pub uuid: UUID,
+    /// Whether the server asked for encryption, offline mode servers don't.
+    pub online_mode: bool,
+}
+
+impl Conn {
+    /// Logs in as `profile`, joining the server through Mojang's session server when it is
+    /// in online mode and enabling encryption and compression when asked to. Login plugin
+    /// requests are declined. Leaves the connection in the play state.
+    pub fn login(&mut self, profile: &mojang::Profile) -> Result<LoginResult, Error> {
+        self.login_with(
+            profile,
+            "",
+            |_| {},
+            |conn, request| {
+                warn!(
+                    "Declining unsupported LoginPluginRequest channel: {:?}",
+                    request.channel
+                );
+                conn.write_login_plugin_response(request.message_id, false, &[])
+            },
+        )
+    }
+
+    /// Like `login`, with `host_suffix` added to the host sent in the handshake, Forge uses
+    /// it to recognize modded clients. `on_packet` sees every packet received while logging
+    /// in before it's handled, and `on_plugin_request` answers the login plugin requests.
+    pub fn login_with<P, F>(
+        &mut self,
+        profile: &mojang::Profile,
+        host_suffix: &str,
+        mut on_packet: P,
+        mut on_plugin_request: F,
+    ) -> Result<LoginResult, Error>
+    where
+        P: FnMut(&Packet),
+        F: FnMut(&mut Conn, LoginPluginRequest) -> Result<(), Error>,
+    {
+        let host = self.host.clone() + host_suffix;
+        let port = self.port;
+        self.write_packet(Handshake {
+            protocol_version: VarInt(self.protocol_version),
+            host,
+            port,
+            next: VarInt(2),
+        })?;
+        self.state = State::Login;
+        self.write_packet(LoginStart {
+            username: profile.username.clone(),
+        })?;
+
+        let mut online_mode = false;
+        loop {
+            let packet = self.read_packet()?;
+            on_packet(&packet);
+            let (username, uuid) = match packet {
+                Packet::SetInitialCompression(val) => {
+                    self.set_compression(val.threshold.0);
+                    continue;
+                }
+                Packet::EncryptionRequest(val) => {
+                    self.encrypt(
+                        profile,
+                        &val.server_id,
+                        &val.public_key.data,
+                        &val.verify_token.data,
+                    )?;
+                    online_mode = true;
+                    continue;
+                }
+                Packet::EncryptionRequest_i16(val) => {
+                    self.encrypt(
+                        profile,
+                        &val.server_id,
+                        &val.public_key.data,
+                        &val.verify_token.data,
+                    )?;
+                    online_mode = true;
+                    continue;
+                }
+                Packet::LoginPluginRequest(req) => {
+                    on_plugin_request(self, req)?;
+                    continue;
+                }
+                Packet::LoginSuccess_String(val) => {
+                    let uuid = UUID::from_str(&val.uuid)
+                        .map_err(|_| Error::Err(format!("Invalid UUID: {}", val.uuid)))?;
+                    (val.username, uuid)
+                }
+                Packet::LoginSuccess_UUID(val) => (val.username, val.uuid),
+                Packet::LoginDisconnect(val) => return Err(Error::Disconnect(val.reason)),
+                val => return Err(Error::Err(format!("Unexpected login packet: {:?}", val))),
+            };
+            if !online_mode {
+                warn!("Server is running in offline mode");
+            }
+            debug!("Login: {} {:?}", username, uuid);
+            self.state = State::Play;
+            return Ok(LoginResult {
+                username,
+                uuid,
+                online_mode,
+            });
+        }
+    }
+
+    /// Answers an encryption request, everything sent afterwards is encrypted.
+    fn encrypt(
+        &mut self,
+        profile: &mojang::Profile,
+        server_id: &str,
+        public_key: &[u8],
+        verify_token: &[u8],
+    ) -> Result<(), Error> {
+        let mut shared = [0; 16];
+        rand::thread_rng().fill(&mut shared);
+
+        let invalid_key = |_| Error::Err("Invalid public key".to_owned());
+        let shared_e =
+            rsa_public_encrypt_pkcs1::encrypt(public_key, &shared).map_err(invalid_key)?;
+        let token_e =
+            rsa_public_encrypt_pkcs1::encrypt(public_key, verify_token).map_err(invalid_key)?;
+
+        profile.join_server(server_id, &shared, public_key)?;
+
+        if self.protocol_version >= 47 {
+            self.write_packet(EncryptionResponse {
+                shared_secret: LenPrefixedBytes::new(shared_e),
+                verify_token: LenPrefixedBytes::new(token_e),
+            })?;
+        } else {
+            self.write_packet(EncryptionResponse_i16 {
+                shared_secret: LenPrefixedBytes::new(shared_e),
+                verify_token: LenPrefixedBytes::new(token_e),
+            })?;
+        }
+
+        self.enable_encryption(&shared)
+    }
+}
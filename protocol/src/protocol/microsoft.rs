@@ -0,0 +1,465 @@
+//! Logging in with a Microsoft account, which replaced Mojang accounts.
+//!
+//! The user signs in to Microsoft in their browser with the OAuth device code flow. The
+//! Microsoft token is exchanged for an Xbox Live token, then an XSTS token, which logs in
+//! to Minecraft. The Microsoft refresh token is kept to repeat this once the Minecraft
+//! token expires, without signing in again.
+
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use instant::{Duration, Instant};
+use serde_json::{json, Value};
+
+use super::mojang::Profile;
+use super::Error;
+
+const SCOPE: &str = "XboxLive.signin offline_access";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Minecraft tokens are refreshed this many seconds before they expire, so they don't
+/// expire while joining a server.
+const EXPIRY_MARGIN: u64 = 60;
+
+/// XSTS errors for accounts that can't log in to Minecraft.
+const XERR_NO_XBOX_ACCOUNT: u64 = 2148916233;
+const XERR_COUNTRY_UNAVAILABLE: u64 = 2148916235;
+const XERR_CHILD_ACCOUNT: u64 = 2148916238;
+
+/// The URLs of the services taking part in logging in.
+#[derive(Clone, Debug)]
+pub struct Endpoints {
+    pub device_code: String,
+    pub token: String,
+    pub xbox_live: String,
+    pub xsts: String,
+    pub minecraft_login: String,
+    pub minecraft_profile: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            device_code: "https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode"
+                .to_owned(),
+            token: "https://login.microsoftonline.com/consumers/oauth2/v2.0/token".to_owned(),
+            xbox_live: "https://user.auth.xboxlive.com/user/authenticate".to_owned(),
+            xsts: "https://xsts.auth.xboxlive.com/xsts/authorize".to_owned(),
+            minecraft_login: "https://api.minecraftservices.com/authentication/login_with_xbox"
+                .to_owned(),
+            minecraft_profile: "https://api.minecraftservices.com/minecraft/profile".to_owned(),
+        }
+    }
+}
+
+/// The code the user enters at `verification_uri` to sign in.
+#[derive(Clone, Debug)]
+pub struct DeviceCode {
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Instructions for the user, as worded by Microsoft.
+    pub message: String,
+    device_code: String,
+    interval: Duration,
+    expires: Instant,
+}
+
+/// A Minecraft profile logged in with a Microsoft account.
+#[derive(Clone, Debug)]
+pub struct Account {
+    pub profile: Profile,
+    pub refresh_token: String,
+    /// When the profile's access token expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+impl Account {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now + EXPIRY_MARGIN >= self.expires_at
+    }
+
+    /// Logs in again with the refresh token if the access token expired, `now` being
+    /// the seconds since the Unix epoch.
+    pub fn refresh_if_expired(self, client: &Client, now: u64) -> Result<Account, Error> {
+        if self.is_expired(now) {
+            client.refresh(&self.refresh_token, now)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+pub struct Client {
+    client_id: String,
+    endpoints: Endpoints,
+    http: reqwest::blocking::Client,
+}
+
+impl Client {
+    /// A client for the Azure application `client_id`, which has to allow public client
+    /// flows.
+    pub fn new(client_id: &str) -> Client {
+        Client::with_endpoints(client_id, Endpoints::default())
+    }
+
+    pub fn with_endpoints(client_id: &str, endpoints: Endpoints) -> Client {
+        Client {
+            client_id: client_id.to_owned(),
+            endpoints,
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Starts signing in, the user has to enter the returned code in their browser
+    /// before `wait_for_login` returns.
+    pub fn request_device_code(&self) -> Result<DeviceCode, Error> {
+        let res = self
+            .http
+            .post(&self.endpoints.device_code)
+            .form(&[("client_id", self.client_id.as_str()), ("scope", SCOPE)])
+            .send()?;
+        let ret: Value = serde_json::from_reader(res)?;
+        check_oauth_error(&ret)?;
+        Ok(DeviceCode {
+            user_code: field(&ret, "/user_code")?,
+            verification_uri: field(&ret, "/verification_uri")?,
+            message: field(&ret, "/message")?,
+            device_code: field(&ret, "/device_code")?,
+            interval: Duration::from_secs(ret.get("interval").and_then(Value::as_u64).unwrap_or(5)),
+            expires: Instant::now()
+                + Duration::from_secs(ret.get("expires_in").and_then(Value::as_u64).unwrap_or(900)),
+        })
+    }
+
+    /// Waits for the user to sign in with `code` and logs in to Minecraft with their
+    /// account.
+    pub fn wait_for_login(&self, code: &DeviceCode) -> Result<Account, Error> {
+        let mut interval = code.interval;
+        loop {
+            if Instant::now() >= code.expires {
+                return Err(Error::Err(
+                    "The code expired before signing in to Microsoft".to_owned(),
+                ));
+            }
+            thread::sleep(interval);
+            let ret = self.request_token(&[
+                ("grant_type", DEVICE_CODE_GRANT),
+                ("client_id", self.client_id.as_str()),
+                ("device_code", code.device_code.as_str()),
+            ])?;
+            match ret.get("error").and_then(Value::as_str) {
+                Some("authorization_pending") => continue,
+                Some("slow_down") => interval += Duration::from_secs(5),
+                _ => {
+                    check_oauth_error(&ret)?;
+                    return self.login_with_token(&ret, unix_time());
+                }
+            }
+        }
+    }
+
+    /// Logs in to Minecraft again with the Microsoft refresh token of an account.
+    pub fn refresh(&self, refresh_token: &str, now: u64) -> Result<Account, Error> {
+        let ret = self.request_token(&[
+            ("grant_type", "refresh_token"),
+            ("client_id", self.client_id.as_str()),
+            ("refresh_token", refresh_token),
+            ("scope", SCOPE),
+        ])?;
+        check_oauth_error(&ret)?;
+        self.login_with_token(&ret, now)
+    }
+
+    fn request_token(&self, form: &[(&str, &str)]) -> Result<Value, Error> {
+        let res = self.http.post(&self.endpoints.token).form(form).send()?;
+        Ok(serde_json::from_reader(res)?)
+    }
+
+    /// Exchanges a Microsoft token response for an Xbox Live token, that for an XSTS
+    /// token and that for a Minecraft access token.
+    fn login_with_token(&self, token: &Value, now: u64) -> Result<Account, Error> {
+        let access_token = field(token, "/access_token")?;
+        let refresh_token = field(token, "/refresh_token")?;
+
+        let xbox_live = self.post_json(
+            &self.endpoints.xbox_live,
+            json!({
+                "Properties": {
+                    "AuthMethod": "RPS",
+                    "SiteName": "user.auth.xboxlive.com",
+                    "RpsTicket": format!("d={}", access_token)
+                },
+                "RelyingParty": "http://auth.xboxlive.com",
+                "TokenType": "JWT"
+            }),
+        )?;
+        let xbox_live_token = field(&xbox_live, "/Token")?;
+        let user_hash = field(&xbox_live, "/DisplayClaims/xui/0/uhs")?;
+
+        let res = self.send_json(
+            &self.endpoints.xsts,
+            json!({
+                "Properties": {
+                    "SandboxId": "RETAIL",
+                    "UserTokens": [xbox_live_token]
+                },
+                "RelyingParty": "rp://api.minecraftservices.com/",
+                "TokenType": "JWT"
+            }),
+        )?;
+        if res.status() == reqwest::StatusCode::UNAUTHORIZED {
+            let ret: Value = serde_json::from_reader(res)?;
+            return Err(Error::Err(
+                match ret.get("XErr").and_then(Value::as_u64) {
+                    Some(XERR_NO_XBOX_ACCOUNT) => "This Microsoft account has no Xbox account",
+                    Some(XERR_COUNTRY_UNAVAILABLE) => "Xbox Live isn't available in your country",
+                    Some(XERR_CHILD_ACCOUNT) => {
+                        "This is a child account, it has to be added to a family first"
+                    }
+                    _ => "Xbox Live refused to log in",
+                }
+                .to_owned(),
+            ));
+        }
+        let xsts = response_json(res)?;
+        let xsts_token = field(&xsts, "/Token")?;
+
+        let minecraft = self.post_json(
+            &self.endpoints.minecraft_login,
+            json!({ "identityToken": format!("XBL3.0 x={};{}", user_hash, xsts_token) }),
+        )?;
+        let minecraft_token = field(&minecraft, "/access_token")?;
+        let expires_in = minecraft
+            .get("expires_in")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| unexpected_response("/expires_in"))?;
+
+        let res = self
+            .http
+            .get(&self.endpoints.minecraft_profile)
+            .bearer_auth(&minecraft_token)
+            .send()?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::Err(
+                "This Microsoft account doesn't own Minecraft".to_owned(),
+            ));
+        }
+        let profile = response_json(res)?;
+        Ok(Account {
+            profile: Profile {
+                username: field(&profile, "/name")?,
+                id: field(&profile, "/id")?,
+                access_token: minecraft_token,
+            },
+            refresh_token,
+            expires_at: now + expires_in,
+        })
+    }
+
+    fn send_json(&self, url: &str, body: Value) -> Result<reqwest::blocking::Response, Error> {
+        Ok(self
+            .http
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::ACCEPT, "application/json")
+            .body(serde_json::to_string(&body)?)
+            .send()?)
+    }
+
+    fn post_json(&self, url: &str, body: Value) -> Result<Value, Error> {
+        response_json(self.send_json(url, body)?)
+    }
+}
+
+/// The current time in seconds since the Unix epoch, as `Account` expiry times are given.
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+fn response_json(res: reqwest::blocking::Response) -> Result<Value, Error> {
+    if !res.status().is_success() {
+        return Err(Error::Err(format!(
+            "Logging in failed with {} from {}",
+            res.status(),
+            res.url()
+        )));
+    }
+    Ok(serde_json::from_reader(res)?)
+}
+
+fn check_oauth_error(ret: &Value) -> Result<(), Error> {
+    match ret.get("error").and_then(Value::as_str) {
+        Some(error) => Err(Error::Err(format!(
+            "{}: {}",
+            error,
+            ret.get("error_description")
+                .and_then(Value::as_str)
+                .unwrap_or("signing in to Microsoft failed")
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn field(ret: &Value, pointer: &str) -> Result<String, Error> {
+    ret.pointer(pointer)
+        .and_then(Value::as_str)
+        .map(|v| v.to_owned())
+        .ok_or_else(|| unexpected_response(pointer))
+}
+
+fn unexpected_response(pointer: &str) -> Error {
+    Error::Err(format!("Unexpected login response, missing {}", pointer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers HTTP requests like the login services would, recording the path and body
+    /// of every request.
+    fn mock_services() -> (Endpoints, Arc<Mutex<Vec<(String, String)>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = BufReader::new(stream.unwrap());
+                let mut request_line = String::new();
+                stream.read_line(&mut request_line).unwrap();
+                let path = request_line.split(' ').nth(1).unwrap().to_owned();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    stream.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    let mut parts = header.splitn(2, ':');
+                    if parts.next().unwrap().eq_ignore_ascii_case("content-length") {
+                        content_length = parts.next().unwrap().trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                stream.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+
+                let response = match path.as_str() {
+                    "/token" if body.contains("refresh_token=old-refresh") => json!({
+                        "access_token": "microsoft-token",
+                        "refresh_token": "new-refresh",
+                    }),
+                    "/token" => json!({
+                        "error": "invalid_grant",
+                        "error_description": "bad refresh token",
+                    }),
+                    "/xbox" => json!({
+                        "Token": "xbox-token",
+                        "DisplayClaims": { "xui": [{ "uhs": "user-hash" }] },
+                    }),
+                    "/xsts" => json!({ "Token": "xsts-token" }),
+                    "/minecraft/login" => json!({
+                        "access_token": "minecraft-token",
+                        "expires_in": 86400,
+                    }),
+                    "/minecraft/profile" => json!({
+                        "id": "069a79f444e94726a5befca90e38aaf5",
+                        "name": "Notch",
+                    }),
+                    _ => json!({}),
+                }
+                .to_string();
+                recorded.lock().unwrap().push((path, body));
+                write!(
+                    stream.get_mut(),
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+        let endpoints = Endpoints {
+            device_code: format!("{}/devicecode", url),
+            token: format!("{}/token", url),
+            xbox_live: format!("{}/xbox", url),
+            xsts: format!("{}/xsts", url),
+            minecraft_login: format!("{}/minecraft/login", url),
+            minecraft_profile: format!("{}/minecraft/profile", url),
+        };
+        (endpoints, requests)
+    }
+
+    fn account(refresh_token: &str, expires_at: u64) -> Account {
+        Account {
+            profile: Profile {
+                username: "Notch".to_owned(),
+                id: "069a79f444e94726a5befca90e38aaf5".to_owned(),
+                access_token: "old-minecraft-token".to_owned(),
+            },
+            refresh_token: refresh_token.to_owned(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn expired_token_is_refreshed() {
+        let (endpoints, requests) = mock_services();
+        let client = Client::with_endpoints("client-id", endpoints);
+        let now = 1_000_000;
+
+        // Still valid for an hour, nothing to do
+        let valid = account("old-refresh", now + 3600)
+            .refresh_if_expired(&client, now)
+            .unwrap();
+        assert_eq!(valid.profile.access_token, "old-minecraft-token");
+        assert!(requests.lock().unwrap().is_empty());
+
+        // Expiring within the margin counts as expired
+        assert!(account("old-refresh", now + 30).is_expired(now));
+
+        let refreshed = account("old-refresh", now - 10)
+            .refresh_if_expired(&client, now)
+            .unwrap();
+        assert_eq!(refreshed.profile.username, "Notch");
+        assert_eq!(refreshed.profile.access_token, "minecraft-token");
+        assert_eq!(refreshed.refresh_token, "new-refresh");
+        assert_eq!(refreshed.expires_at, now + 86400);
+        assert!(!refreshed.is_expired(now));
+
+        let requests = requests.lock().unwrap();
+        let paths: Vec<_> = requests.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/token",
+                "/xbox",
+                "/xsts",
+                "/minecraft/login",
+                "/minecraft/profile"
+            ]
+        );
+        assert!(requests[0].1.contains("grant_type=refresh_token"));
+        assert!(requests[1].1.contains("d=microsoft-token"));
+        assert!(requests[3].1.contains("XBL3.0 x=user-hash;xsts-token"));
+    }
+
+    #[test]
+    fn rejected_refresh_token_fails() {
+        let (endpoints, _requests) = mock_services();
+        let client = Client::with_endpoints("client-id", endpoints);
+        let err = account("revoked", 0)
+            .refresh_if_expired(&client, 1_000_000)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "protocol error: invalid_grant: bad refresh token"
+        );
+    }
+}
@@ -16,16 +16,16 @@
 #![allow(non_camel_case_types)]
 
 extern crate lazy_static;
-extern crate regex;
 
+use std::collections::{BTreeMap, HashMap};
 use std::convert;
 use std::default;
 use std::fmt;
 use std::fs;
 use std::io;
 use std::io::{Read, Write};
-use std::net::TcpStream;
-use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::net::{IpAddr, Ipv6Addr, Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU16, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 
 use aes::Aes128;
@@ -38,7 +38,6 @@ use instant::{Duration, Instant};
 use lazy_static::lazy_static;
 use log::{debug, warn};
 use num_traits::cast::{cast, NumCast};
-use regex::Regex;
 use trust_dns_resolver::config::ResolverConfig;
 use trust_dns_resolver::config::ResolverOpts;
 use trust_dns_resolver::Resolver;
@@ -48,7 +47,12 @@ use crate::nbt;
 use crate::shared::Position;
 
 pub mod forge;
+pub mod login;
+pub mod microsoft;
 pub mod mojang;
+pub mod recording;
+pub mod socks;
+pub mod timings;
 
 pub const SUPPORTED_PROTOCOLS: [i32; 24] = [
     754, 753, 751, 736, 735, 578, 575, 498, 490, 485, 480, 477, 452, 451, 404, 340, 316, 315, 210,
@@ -57,6 +61,12 @@ pub const SUPPORTED_PROTOCOLS: [i32; 24] = [
 
 static CURRENT_PROTOCOL_VERSION: AtomicI32 = AtomicI32::new(SUPPORTED_PROTOCOLS[0]);
 static NETWORK_DEBUG: AtomicBool = AtomicBool::new(false);
+static PACKET_TIMINGS: AtomicBool = AtomicBool::new(false);
+static STRICT_VARINTS: AtomicBool = AtomicBool::new(false);
+static DEFAULT_PORT: AtomicU16 = AtomicU16::new(VANILLA_PORT);
+
+/// The port servers listen on unless told otherwise.
+pub const VANILLA_PORT: u16 = 25565;
 
 /// A list of all supported versions
 #[derive(PartialOrd, PartialEq, Debug, Copy, Clone)]
@@ -106,6 +116,42 @@ impl Version {
             _ => true,
         }
     }
+
+    /// A human readable name of the version, e.g. "1.16.x".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Version::Other => "Unknown",
+            Version::Old => "Old",
+            Version::V1_7 => "1.7.x",
+            Version::V1_8 => "1.8.x",
+            Version::V1_9 => "1.9.x",
+            Version::V1_10 => "1.10.x",
+            Version::V1_11 => "1.11.x",
+            Version::V1_12 => "1.12.x",
+            Version::V1_13 => "1.13.x",
+            Version::V1_14 => "1.14.x",
+            Version::V1_15 => "1.15.x",
+            Version::V1_16 => "1.16.x",
+            Version::New => "New",
+        }
+    }
+
+    /// The supported protocol versions belonging to this version, newest first.
+    pub fn protocol_ids(&self) -> &'static [i32] {
+        match self {
+            Version::Other | Version::Old | Version::New => &[],
+            Version::V1_7 => &[5],
+            Version::V1_8 => &[47],
+            Version::V1_9 => &[109, 107],
+            Version::V1_10 => &[210],
+            Version::V1_11 => &[316, 315],
+            Version::V1_12 => &[340],
+            Version::V1_13 => &[404],
+            Version::V1_14 => &[498, 490, 485, 480, 477],
+            Version::V1_15 => &[578, 575],
+            Version::V1_16 => &[754, 753, 751, 736, 735],
+        }
+    }
 }
 
 pub fn current_protocol_version() -> i32 {
@@ -120,6 +166,34 @@ pub fn is_network_debug() -> bool {
     NETWORK_DEBUG.load(Ordering::Relaxed)
 }
 
+/// Records how long each packet type takes to decode, see `timings::packet_timings`.
+pub fn enable_packet_timings() {
+    PACKET_TIMINGS.store(true, Ordering::Relaxed);
+}
+
+pub fn is_packet_timings() -> bool {
+    PACKET_TIMINGS.load(Ordering::Relaxed)
+}
+
+/// Makes reading a `VarInt` or `VarLong` fail on encodings the vanilla encoder never
+/// produces, see `VarInt::read_strict`. Useful for validating captured traffic.
+pub fn enable_strict_varints() {
+    STRICT_VARINTS.store(true, Ordering::Relaxed);
+}
+
+pub fn is_strict_varints() -> bool {
+    STRICT_VARINTS.load(Ordering::Relaxed)
+}
+
+/// Sets the port `Conn::new` connects to when an address has none and no SRV record.
+pub fn set_default_port(port: u16) {
+    DEFAULT_PORT.store(port, Ordering::Relaxed);
+}
+
+pub fn default_port() -> u16 {
+    DEFAULT_PORT.load(Ordering::Relaxed)
+}
+
 /// Helper macro for defining packets
 #[macro_export]
 macro_rules! state_packets {
@@ -147,6 +221,21 @@ macro_rules! state_packets {
         )+
         }
 
+        impl Packet {
+            /// The name of the packet's variant, e.g. `KeepAliveClientbound_i64`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                $(
+                    $(
+                        $(
+                Packet::$name(_) => stringify!($name),
+                        )*
+                    )+
+                )+
+                }
+            }
+        }
+
         $(
         pub mod $state {
 
@@ -182,7 +271,7 @@ macro_rules! state_packets {
                         fn write<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
                             $(
                                 if true $(&& ($cond(&self)))* {
-                                    self.$field.write_to(buf)?;
+                                    write_field!(self.$field, buf $(, $cond)*)?;
                                 }
                             )+
 
@@ -212,7 +301,7 @@ macro_rules! state_packets {
                                             let mut packet : $name = $name::default();
                                             $(
                                                 if true $(&& ($cond(&packet)))* {
-                                                    packet.$field = Serializable::read_from(&mut buf)?;
+                                                    packet.$field = read_field!(&mut buf $(, $cond)*)?;
                                                 }
                                             )+
                                             Ok(Option::Some(Packet::$name(packet)))
@@ -279,6 +368,18 @@ pub mod versions;
 pub trait Serializable: Sized {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Self, Error>;
     fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error>;
+
+    /// Reads a packet field with a `when` condition, which is only there when the
+    /// condition holds. Only differs from `read_from` for `Option`, which then has no
+    /// presence prefix.
+    fn read_present<R: io::Read>(buf: &mut R) -> Result<Self, Error> {
+        Self::read_from(buf)
+    }
+
+    /// Writes a packet field with a `when` condition, see `read_present`.
+    fn write_present<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
+        self.write_to(buf)
+    }
 }
 
 impl Serializable for Vec<u8> {
@@ -317,16 +418,31 @@ impl Serializable for Option<nbt::NamedTag> {
     }
 }
 
+/// The protocol's optional values, prefixed with a bool telling whether they're present.
+/// Packet fields whose presence follows from other fields use `when` instead, which reads
+/// them with `read_present`.
 impl<T> Serializable for Option<T>
 where
     T: Serializable,
 {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Option<T>, Error> {
-        Ok(Some(T::read_from(buf)?))
+        if bool::read_from(buf)? {
+            Ok(Some(T::read_from(buf)?))
+        } else {
+            Ok(None)
+        }
     }
     fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
-        if self.is_some() {
-            self.as_ref().unwrap().write_to(buf)?;
+        self.is_some().write_to(buf)?;
+        self.write_present(buf)
+    }
+
+    fn read_present<R: io::Read>(buf: &mut R) -> Result<Option<T>, Error> {
+        Ok(Some(T::read_from(buf)?))
+    }
+    fn write_present<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
+        if let Some(val) = self {
+            val.write_to(buf)?;
         }
         Ok(())
     }
@@ -510,6 +626,20 @@ impl std::str::FromStr for UUID {
     }
 }
 
+impl fmt::Display for UUID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            self.0 >> 32,
+            (self.0 >> 16) & 0xFFFF,
+            self.0 & 0xFFFF,
+            self.1 >> 48,
+            self.1 & 0xFFFF_FFFF_FFFF
+        )
+    }
+}
+
 impl Default for UUID {
     fn default() -> Self {
         UUID(0, 0)
@@ -552,12 +682,11 @@ impl Default for Biomes3D {
 
 impl Serializable for Biomes3D {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Biomes3D, Error> {
-        let data: [i32; 1024] = [0; 1024];
+        let mut data: [i32; 1024] = [0; 1024];
 
         // Non-length-prefixed three-dimensional biome data
-        for item in &mut data.to_vec() {
-            let b: i32 = Serializable::read_from(buf)?;
-            *item = b;
+        for item in data.iter_mut() {
+            *item = Serializable::read_from(buf)?;
         }
 
         Ok(Biomes3D { data })
@@ -626,6 +755,65 @@ impl<L: Lengthable, V: fmt::Debug> fmt::Debug for LenPrefixed<L, V> {
     }
 }
 
+/// A map serialized as its length followed by each key and value, in key order.
+pub struct LenPrefixedMap<L: Lengthable, K, V> {
+    len: L,
+    pub data: BTreeMap<K, V>,
+}
+
+impl<L: Lengthable, K: Ord, V> LenPrefixedMap<L, K, V> {
+    pub fn new(data: BTreeMap<K, V>) -> LenPrefixedMap<L, K, V> {
+        LenPrefixedMap {
+            len: Default::default(),
+            data,
+        }
+    }
+}
+
+impl<L: Lengthable, K: Serializable + Ord, V: Serializable> Serializable
+    for LenPrefixedMap<L, K, V>
+{
+    fn read_from<R: io::Read>(buf: &mut R) -> Result<LenPrefixedMap<L, K, V>, Error> {
+        let len_data: L = Serializable::read_from(buf)?;
+        let len: usize = len_data.into_len();
+        let mut data = BTreeMap::new();
+        for _ in 0..len {
+            let key = Serializable::read_from(buf)?;
+            let value = Serializable::read_from(buf)?;
+            data.insert(key, value);
+        }
+        Ok(LenPrefixedMap {
+            len: len_data,
+            data,
+        })
+    }
+
+    fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
+        let len_data: L = L::from_len(self.data.len());
+        len_data.write_to(buf)?;
+        for (key, value) in &self.data {
+            key.write_to(buf)?;
+            value.write_to(buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<L: Lengthable, K: Ord, V> Default for LenPrefixedMap<L, K, V> {
+    fn default() -> Self {
+        LenPrefixedMap {
+            len: default::Default::default(),
+            data: default::Default::default(),
+        }
+    }
+}
+
+impl<L: Lengthable, K: fmt::Debug, V: fmt::Debug> fmt::Debug for LenPrefixedMap<L, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.data.fmt(f)
+    }
+}
+
 // Optimization
 pub struct LenPrefixedBytes<L: Lengthable> {
     len: L,
@@ -813,6 +1001,57 @@ where
     }
 }
 
+/// `Angle` is a rotation in steps of 1/256 of a full turn, used for the rotations of
+/// entities: https://wiki.vg/Data_types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    /// The closest step to `degrees`, which wraps around like a full turn.
+    pub fn from_degrees(degrees: f32) -> Angle {
+        Angle((degrees / 360.0 * 256.0).round().rem_euclid(256.0) as u8)
+    }
+
+    pub fn from_radians(radians: f32) -> Angle {
+        Angle::from_degrees(radians.to_degrees())
+    }
+
+    /// The angle in degrees, from 0 up to 360.
+    pub fn degrees(self) -> f32 {
+        self.0 as f32 * 360.0 / 256.0
+    }
+
+    /// The angle in radians, from 0 up to 2π.
+    pub fn radians(self) -> f32 {
+        self.degrees().to_radians()
+    }
+}
+
+impl Serializable for Angle {
+    fn read_from<R: io::Read>(buf: &mut R) -> Result<Self, Error> {
+        Ok(Angle(buf.read_u8()?))
+    }
+
+    fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
+        buf.write_u8(self.0)?;
+        Ok(())
+    }
+}
+
+/// Converts from degrees.
+impl convert::From<f32> for Angle {
+    fn from(degrees: f32) -> Self {
+        Angle::from_degrees(degrees)
+    }
+}
+
+/// Converts to degrees.
+impl convert::From<Angle> for f32 {
+    fn from(angle: Angle) -> Self {
+        angle.degrees()
+    }
+}
+
 /// `VarInt` have a variable size (between 1 and 5 bytes) when encoded based
 /// on the size of the number
 #[derive(Clone, Copy)]
@@ -828,26 +1067,45 @@ impl Lengthable for VarInt {
     }
 }
 
-impl Serializable for VarInt {
-    /// Decodes a `VarInt` from the Reader
-    fn read_from<R: io::Read>(buf: &mut R) -> Result<VarInt, Error> {
+impl VarInt {
+    /// Decodes a `VarInt`, rejecting encodings with bits that don't fit an i32 or with
+    /// padding bytes that don't add to the value.
+    pub fn read_strict<R: io::Read>(buf: &mut R) -> Result<VarInt, Error> {
+        VarInt::decode(buf, true)
+    }
+
+    fn decode<R: io::Read>(buf: &mut R, strict: bool) -> Result<VarInt, Error> {
         const PART: u32 = 0x7F;
         let mut size = 0;
         let mut val = 0u32;
         loop {
             let b = buf.read_u8()? as u32;
+            // Only the low 4 bits of the fifth byte fit
+            if strict && size == 4 && (b & 0x70) != 0 {
+                return Err(Error::Err("VarInt overflows i32".to_owned()));
+            }
             val |= (b & PART) << (size * 7);
             size += 1;
             if size > 5 {
                 return Err(Error::Err("VarInt too big".to_owned()));
             }
             if (b & 0x80) == 0 {
+                if strict && size > 1 && b == 0 {
+                    return Err(Error::Err("Overlong VarInt".to_owned()));
+                }
                 break;
             }
         }
 
         Ok(VarInt(val as i32))
     }
+}
+
+impl Serializable for VarInt {
+    /// Decodes a `VarInt` from the Reader
+    fn read_from<R: io::Read>(buf: &mut R) -> Result<VarInt, Error> {
+        VarInt::decode(buf, is_strict_varints())
+    }
 
     /// Encodes a `VarInt` into the Writer
     fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
@@ -954,26 +1212,45 @@ impl Lengthable for VarLong {
     }
 }
 
-impl Serializable for VarLong {
-    /// Decodes a `VarLong` from the Reader
-    fn read_from<R: io::Read>(buf: &mut R) -> Result<VarLong, Error> {
+impl VarLong {
+    /// Decodes a `VarLong`, rejecting encodings with bits that don't fit an i64 or with
+    /// padding bytes that don't add to the value.
+    pub fn read_strict<R: io::Read>(buf: &mut R) -> Result<VarLong, Error> {
+        VarLong::decode(buf, true)
+    }
+
+    fn decode<R: io::Read>(buf: &mut R, strict: bool) -> Result<VarLong, Error> {
         const PART: u64 = 0x7F;
         let mut size = 0;
         let mut val = 0u64;
         loop {
             let b = buf.read_u8()? as u64;
+            // Only the lowest bit of the tenth byte fits
+            if strict && size == 9 && (b & 0x7E) != 0 {
+                return Err(Error::Err("VarLong overflows i64".to_owned()));
+            }
             val |= (b & PART) << (size * 7);
             size += 1;
             if size > 10 {
                 return Err(Error::Err("VarLong too big".to_owned()));
             }
             if (b & 0x80) == 0 {
+                if strict && size > 1 && b == 0 {
+                    return Err(Error::Err("Overlong VarLong".to_owned()));
+                }
                 break;
             }
         }
 
         Ok(VarLong(val as i64))
     }
+}
+
+impl Serializable for VarLong {
+    /// Decodes a `VarLong` from the Reader
+    fn read_from<R: io::Read>(buf: &mut R) -> Result<VarLong, Error> {
+        VarLong::decode(buf, is_strict_varints())
+    }
 
     /// Encodes a `VarLong` into the Writer
     fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
@@ -1040,7 +1317,7 @@ impl Serializable for Position {
 
 /// Direction is used to define whether packets are going to the
 /// server or the client.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Serverbound,
     Clientbound,
@@ -1056,6 +1333,19 @@ pub enum State {
     Login,
 }
 
+impl std::str::FromStr for State {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "handshaking" | "handshake" => Ok(State::Handshaking),
+            "play" => Ok(State::Play),
+            "status" => Ok(State::Status),
+            "login" => Ok(State::Login),
+            _ => Err(Error::Err(format!("Unknown protocol state: {}", s))),
+        }
+    }
+}
+
 /// Return for any protocol related error.
 #[derive(Debug)]
 pub enum Error {
@@ -1084,6 +1374,16 @@ impl convert::From<reqwest::Error> for Error {
     }
 }
 
+impl Error {
+    /// The message to show to the user, keeping the formatting of a disconnect reason.
+    pub fn to_component(&self) -> format::Component {
+        match self {
+            Error::Disconnect(reason) => reason.clone(),
+            err => format::Component::Text(format::TextComponent::new(&err.to_string())),
+        }
+    }
+}
+
 impl ::std::error::Error for Error {}
 
 impl ::std::fmt::Display for Error {
@@ -1111,85 +1411,229 @@ pub struct Conn {
     read_cipher: Arc<RwLock<Option<Aes128Cfb>>>,
     write_cipher: Arc<RwLock<Option<Aes128Cfb>>>,
 
-    pub compression_threshold: i32,
+    /// Shared between clones so the reading and writing halves agree on it. -1 disables
+    /// compression, 0 compresses every packet.
+    compression_threshold: Arc<AtomicI32>,
     pub send: Arc<Mutex<Option<bool>>>,
+    /// Shared between clones so both directions end up in the same recording.
+    recorder: Option<Arc<Mutex<recording::Recorder<fs::File>>>>,
 }
 
 lazy_static! {
-    static ref IPADDRESS_PATTERN: Regex = Regex::new(
-        format!(
-            "{}{}{}{}",
-            "^([01]?\\d\\d?|2[0-4]\\d|25[0-5])\\.",
-            "([01]?\\d\\d?|2[0-4]\\d|25[0-5])\\.",
-            "([01]?\\d\\d?|2[0-4]\\d|25[0-5])\\.",
-            "([01]?\\d\\d?|2[0-4]\\d|25[0-5])$"
-        )
-        .as_str()
-    )
-    .unwrap();
     static ref RESOLVER: Resolver =
         Resolver::new(ResolverConfig::default(), ResolverOpts::default()).unwrap();
+    static ref SRV_CACHE: Mutex<DnsCache> = Mutex::new(DnsCache::default());
+}
+
+/// How long to remember SRV lookups whose TTL isn't known, including failed ones.
+const DEFAULT_DNS_TTL: Duration = Duration::from_secs(60);
+/// How many hostnames the SRV cache remembers at most.
+const MAX_DNS_CACHE_ENTRIES: usize = 256;
+
+fn parse_port(address: &str, port: Option<&str>) -> Result<Option<u16>, Error> {
+    port.map(|port| {
+        port.parse()
+            .map_err(|_| Error::Err(format!("Invalid port in {}", address)))
+    })
+    .transpose()
+}
+
+/// Splits an address that has been resolved, which always has a port.
+fn parse_resolved_address(address: &str) -> Result<(&str, u16), Error> {
+    match split_address(address) {
+        (host, Some(port)) if !host.is_empty() => port
+            .parse()
+            .map(|port| (host, port))
+            .map_err(|_| Error::Err(format!("Invalid port in {}", address))),
+        _ => Err(Error::Err(format!("Missing host or port in {}", address))),
+    }
+}
+
+/// Remembers the SRV records of hostnames until their TTL runs out.
+#[derive(Default)]
+struct DnsCache {
+    entries: HashMap<String, (Instant, Vec<String>)>,
+}
+
+impl DnsCache {
+    /// The cached addresses of `hostname`, `None` if there are none or they expired.
+    fn get(&self, hostname: &str, now: Instant) -> Option<Vec<String>> {
+        match self.entries.get(hostname) {
+            Some((expires, addresses)) if now < *expires => Some(addresses.clone()),
+            _ => None,
+        }
+    }
+
+    /// Remembers the addresses of `hostname` for `ttl`. Expired entries are dropped first,
+    /// then the ones expiring soonest while the cache is full.
+    fn insert(&mut self, hostname: &str, now: Instant, addresses: Vec<String>, ttl: Duration) {
+        self.entries.retain(|_, (expires, _)| now < *expires);
+        while self.entries.len() >= MAX_DNS_CACHE_ENTRIES {
+            let soonest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (expires, _))| *expires)
+                .map(|(hostname, _)| hostname.clone())
+                .unwrap();
+            self.entries.remove(&soonest);
+        }
+        self.entries
+            .insert(hostname.to_owned(), (now + ttl, addresses));
+    }
+}
+
+/// Splits `address` into its host and port, if it has one. IPv6 literals are given in
+/// brackets like `[::1]:25565`, or bare without a port.
+pub fn split_address(address: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = address.strip_prefix('[') {
+        if let Some(end) = rest.find(']') {
+            let port = rest[end + 1..].strip_prefix(':');
+            return (&rest[..end], port);
+        }
+    }
+    if address.parse::<Ipv6Addr>().is_ok() {
+        return (address, None);
+    }
+    let mut parts = address.splitn(2, ':');
+    (parts.next().unwrap(), parts.next())
 }
 
 impl Conn {
-    fn get_server_addresses(mut hostname: &str) -> Vec<String> {
+    fn get_server_addresses(hostname: &str, port: Option<u16>, default_port: u16) -> Vec<String> {
         let mut addresses = vec![];
-        let parts = hostname.split(':').collect::<Vec<&str>>();
-        if parts.len() > 1 {
-            addresses.push(hostname.to_string());
-            hostname = parts[0];
-        }
-        let records = RESOLVER.srv_lookup(format!("_minecraft._tcp.{}", hostname));
-        if records.is_ok() {
-            for record in records.unwrap() {
-                debug!("{}:{}", record.target(), record.port());
-                addresses.push(format!("{}:{}", record.target(), record.port()));
-            }
+        if let Some(port) = port {
+            addresses.push(format!("{}:{}", hostname, port));
+        }
+        let cached = SRV_CACHE.lock().unwrap().get(hostname, Instant::now());
+        // Looked up without holding the cache, so lookups of other servers can go ahead
+        let records = cached.unwrap_or_else(|| {
+            let (records, ttl) = match RESOLVER.srv_lookup(format!("_minecraft._tcp.{}", hostname))
+            {
+                Ok(records) => {
+                    let ttl = records
+                        .as_lookup()
+                        .valid_until()
+                        .saturating_duration_since(std::time::Instant::now());
+                    let records = records
+                        .iter()
+                        .map(|record| format!("{}:{}", record.target(), record.port()))
+                        .collect::<Vec<_>>();
+                    (records, ttl)
+                }
+                Err(_) => (vec![], DEFAULT_DNS_TTL),
+            };
+            SRV_CACHE
+                .lock()
+                .unwrap()
+                .insert(hostname, Instant::now(), records.clone(), ttl);
+            records
+        });
+        for record in records {
+            debug!("{}", record);
+            addresses.push(record);
         }
-        addresses.push(format!("{}:25565", hostname));
+        addresses.push(format!("{}:{}", hostname, default_port));
         addresses
     }
 
     pub fn new(target: &str, protocol_version: i32) -> Result<Conn, Error> {
-        CURRENT_PROTOCOL_VERSION.store(protocol_version, Ordering::Relaxed);
-
-        let mut address = target.to_string();
-        if !IPADDRESS_PATTERN.is_match(target) {
-            debug!("{} has an no address! :(", address);
-            let result = Conn::get_server_addresses(target);
+        Conn::new_with_default_port(target, protocol_version, default_port())
+    }
+
+    /// Like `new`, connecting to `default_port` when `target` has no port. SRV records
+    /// still take precedence over it.
+    pub fn new_with_default_port(
+        target: &str,
+        protocol_version: i32,
+        default_port: u16,
+    ) -> Result<Conn, Error> {
+        let (host, port) = split_address(target);
+        let port = parse_port(target, port)?;
+
+        let proxy = socks::proxy()?;
+        // IP addresses are connected to directly, only hostnames can have SRV records
+        let (host, port) = if host.parse::<IpAddr>().is_ok() {
+            (host.to_owned(), port.unwrap_or(default_port))
+        } else {
+            debug!("{} has an no address! :(", target);
+            let result = Conn::get_server_addresses(host, port, default_port);
             // TODO: Try all possible ips not just the first!
-            let next = result.iter().next().unwrap();
-            debug!("{}'s ip may be {}.", address, next);
-            address = next.to_string();
-        }
+            let next = result
+                .first()
+                .ok_or_else(|| Error::Err(format!("No address found for {}", target)))?;
+            debug!("{}'s ip may be {}.", target, next);
+            let (host, port) = parse_resolved_address(next)?;
+            (host.to_owned(), port)
+        };
+        let stream = match proxy {
+            Some(proxy) => proxy.connect(&host, port)?,
+            None => TcpStream::connect((host.as_str(), port))?,
+        };
+
+        let mut conn = Conn::from_stream(stream, protocol_version)?;
+        // The name is sent in the handshake, servers can tell virtual hosts apart by it
+        conn.host = host;
+        conn.port = port;
+        Ok(conn)
+    }
 
-        let stream = TcpStream::connect(&*address)?;
-        let parts = address.split(':').collect::<Vec<&str>>();
+    /// Wraps a stream that is already connected to a server.
+    pub fn from_stream(stream: TcpStream, protocol_version: i32) -> Result<Conn, Error> {
+        CURRENT_PROTOCOL_VERSION.store(protocol_version, Ordering::Relaxed);
+
+        let address = stream.peer_addr()?;
         Ok(Conn {
-            stream: stream,
-            host: parts[0].to_owned(),
-            port: parts[1].parse().unwrap(),
+            stream,
+            host: address.ip().to_string(),
+            port: address.port(),
             direction: Direction::Serverbound,
             state: State::Handshaking,
             protocol_version,
             read_cipher: Arc::new(RwLock::new(None)),
             write_cipher: Arc::new(RwLock::new(None)),
-            compression_threshold: -1,
+            compression_threshold: Arc::new(AtomicI32::new(-1)),
             send: Arc::new(Mutex::new(None)),
+            recorder: None,
         })
     }
 
+    /// Flushes what was written and closes the connection, for clones too. There's no
+    /// packet for leaving a server, closing the connection is how it's done.
+    pub fn close(&mut self) -> Result<(), Error> {
+        self.flush()?;
+        self.stream.shutdown(Shutdown::Both)?;
+        Ok(())
+    }
+
+    /// Records the packets of this connection from now on, if enabled with
+    /// `recording::enable_recording`.
+    pub fn start_recording(&mut self) -> Result<(), Error> {
+        self.recorder = recording::start(self.protocol_version)?.map(|r| Arc::new(Mutex::new(r)));
+        Ok(())
+    }
+
+    fn record_packet(&self, direction: Direction, data: &[u8]) {
+        if let Some(recorder) = &self.recorder {
+            if let Err(err) = recorder.lock().unwrap().record(direction, self.state, data) {
+                warn!("Failed to record packet: {}", err);
+            }
+        }
+    }
+
     pub fn write_packet<T: PacketType>(&mut self, packet: T) -> Result<(), Error> {
+        self.write_packet_ref(&packet)
+    }
+
+    /// Like `write_packet`, but borrows the packet so it can be sent again or logged.
+    pub fn write_packet_ref<T: PacketType>(&mut self, packet: &T) -> Result<(), Error> {
         let mut buf = Vec::new();
         VarInt(packet.packet_id(self.protocol_version)).write_to(&mut buf)?;
         packet.write(&mut buf)?;
+        self.record_packet(self.direction, &buf);
 
-        let mut extra = if self.compression_threshold >= 0 {
-            1
-        } else {
-            0
-        };
-        if self.compression_threshold >= 0 && buf.len() as i32 > self.compression_threshold {
+        let compression_threshold = self.compression_threshold();
+        let mut extra = if compression_threshold >= 0 { 1 } else { 0 };
+        if compression_threshold >= 0 && buf.len() as i32 > compression_threshold {
             extra = 0;
             let uncompressed_size = buf.len();
             let mut new = Vec::new();
@@ -1201,7 +1645,7 @@ impl Conn {
                     "Compressed for sending {} bytes to {} since > threshold {}, new={:?}",
                     uncompressed_size,
                     new.len(),
-                    self.compression_threshold,
+                    compression_threshold,
                     new
                 );
             }
@@ -1210,7 +1654,7 @@ impl Conn {
         let lock = self.send.clone();
         let _lock = lock.lock();
         VarInt(buf.len() as i32 + extra).write_to(self)?;
-        if self.compression_threshold >= 0 && extra == 1 {
+        if compression_threshold >= 0 && extra == 1 {
             VarInt(0).write_to(self)?;
         }
         self.write_all(&buf)?;
@@ -1284,7 +1728,8 @@ impl Conn {
 
             self.write_login_plugin_response(message_id, true, &outer_buf)
         } else {
-            unimplemented!() // successful: false, no payload
+            // Declines the request
+            self.write_login_plugin_response(message_id, false, &[])
         }
     }
 
@@ -1328,13 +1773,14 @@ impl Conn {
     }
 
     pub fn read_packet(&mut self) -> Result<packet::Packet, Error> {
-        let compression_threshold = self.compression_threshold;
+        let compression_threshold = self.compression_threshold();
         let (id, mut buf) = Conn::read_raw_packet_from(self, compression_threshold)?;
 
         let dir = match self.direction {
             Direction::Clientbound => Direction::Serverbound,
             Direction::Serverbound => Direction::Clientbound,
         };
+        self.record_packet(dir, buf.get_ref());
 
         if is_network_debug() {
             debug!(
@@ -1344,7 +1790,15 @@ impl Conn {
             fs::File::create("last-packet")?.write_all(buf.get_ref())?;
         }
 
+        let start = if is_packet_timings() {
+            Some(Instant::now())
+        } else {
+            None
+        };
         let packet = packet::packet_by_id(self.protocol_version, self.state, dir, id, &mut buf)?;
+        if let (Some(start), Some(packet)) = (start, &packet) {
+            timings::record_packet_timing(packet.name(), start.elapsed());
+        }
 
         if is_network_debug() {
             debug!("packet = {:?}", packet);
@@ -1370,9 +1824,15 @@ impl Conn {
         }
     }
 
+    #[deprecated(note = "use `enable_encryption` instead")]
     pub fn enable_encyption(&mut self, key: &[u8]) {
-        let read_cipher = Aes128Cfb::new_from_slices(key, key).unwrap();
-        let write_cipher = Aes128Cfb::new_from_slices(key, key).unwrap();
+        self.enable_encryption(key).unwrap();
+    }
+
+    pub fn enable_encryption(&mut self, key: &[u8]) -> Result<(), Error> {
+        let invalid_key = |_| Error::Err(format!("Invalid shared secret length: {}", key.len()));
+        let read_cipher = Aes128Cfb::new_from_slices(key, key).map_err(invalid_key)?;
+        let write_cipher = Aes128Cfb::new_from_slices(key, key).map_err(invalid_key)?;
         self.read_cipher
             .clone()
             .write()
@@ -1383,10 +1843,23 @@ impl Conn {
             .write()
             .unwrap()
             .replace(write_cipher);
+        Ok(())
     }
 
     pub fn set_compression(&mut self, threshold: i32) {
-        self.compression_threshold = threshold;
+        let old = self.compression_threshold.swap(threshold, Ordering::SeqCst);
+        if old != threshold {
+            debug!("Compression threshold changed: {} -> {}", old, threshold);
+        }
+    }
+
+    pub fn compression_threshold(&self) -> i32 {
+        self.compression_threshold.load(Ordering::SeqCst)
+    }
+
+    /// Returns the shared threshold, for observing when the server changes it.
+    pub fn compression_threshold_handle(&self) -> Arc<AtomicI32> {
+        self.compression_threshold.clone()
     }
 
     pub fn do_status(mut self) -> Result<(Status, Duration), Error> {
@@ -1462,30 +1935,13 @@ impl Conn {
                 }
             }
         }
-        // Forge 1.13+ TODO: update for 1.14+ and test
-        if let Some(forge_data) = val.get("forgeData") {
-            if let Some(mods) = forge_data.get("mods") {
-                if let Value::Array(items) = mods {
-                    for item in items {
-                        if let Value::Object(obj) = item {
-                            let modid = obj.get("modId").unwrap().as_str().unwrap().to_string();
-                            let modmarker =
-                                obj.get("modmarker").unwrap().as_str().unwrap().to_string();
-
-                            let version = modmarker;
-
-                            forge_mods.push(crate::protocol::forge::ForgeMod { modid, version });
-                        }
-                    }
-                }
-            }
-            fml_network_version = Some(
-                forge_data
-                    .get("fmlNetworkVersion")
-                    .unwrap()
-                    .as_i64()
-                    .unwrap(),
-            );
+        // Forge 1.13+
+        let mut forge_data = None;
+        if let Some(val) = val.get("forgeData") {
+            let data = forge::fml2::ForgeData::from_json(val)?;
+            forge_mods.extend(data.mods.iter().cloned());
+            fml_network_version = Some(data.fml_network_version);
+            forge_data = Some(data);
         }
 
         Ok((
@@ -1521,35 +1977,40 @@ impl Conn {
                     .map(|v| v.to_owned()),
                 forge_mods,
                 fml_network_version,
+                forge_data,
             },
             ping,
         ))
     }
 }
 
-/// Parse a clientbound packet, for debugging packet parsing issues (Conn::read_packet)
-pub fn try_parse_packet(ibuf: Vec<u8>, protocol_version: i32) {
+/// Parse a packet in the given state and direction, for debugging packet parsing issues
+/// (Conn::read_packet)
+pub fn try_parse_packet(
+    ibuf: Vec<u8>,
+    protocol_version: i32,
+    state: State,
+    dir: Direction,
+) -> Result<Option<packet::Packet>, Error> {
     debug!("trying to parse packet data {:?}", ibuf);
 
     let mut buf = io::Cursor::new(ibuf);
 
-    let id = VarInt::read_from(&mut buf).unwrap().0;
-    let dir = Direction::Clientbound;
-    let state = State::Play; // TODO: allow parsing other states
+    let id = VarInt::read_from(&mut buf)?.0;
 
     debug!(
         "about to parse id={:x}, dir={:?} state={:?}",
         id, dir, state
     );
 
-    let packet = packet::packet_by_id(protocol_version, state, dir, id, &mut buf).unwrap();
+    let packet = packet::packet_by_id(protocol_version, state, dir, id, &mut buf)?;
 
     debug!("packet = {:?}", packet);
 
     match packet {
-        Some(_val) => {
+        Some(_) => {
             let pos = buf.position() as usize;
-            let ibuf = buf.into_inner();
+            let ibuf = buf.get_ref();
             if ibuf.len() != pos {
                 debug!("pos = {:?}", pos);
                 debug!("ibuf = {:?}", ibuf);
@@ -1563,6 +2024,8 @@ pub fn try_parse_packet(ibuf: Vec<u8>, protocol_version: i32) {
         }
         None => debug!("missing packet"),
     }
+
+    Ok(packet)
 }
 
 #[derive(Debug)]
@@ -1573,6 +2036,7 @@ pub struct Status {
     pub favicon: Option<String>,
     pub forge_mods: Vec<crate::protocol::forge::ForgeMod>,
     pub fml_network_version: Option<i64>,
+    pub forge_data: Option<forge::fml2::ForgeData>,
 }
 
 #[derive(Debug)]
@@ -1640,8 +2104,9 @@ impl Clone for Conn {
             protocol_version: self.protocol_version,
             read_cipher: self.read_cipher.clone(),
             write_cipher: self.write_cipher.clone(),
-            compression_threshold: self.compression_threshold,
+            compression_threshold: self.compression_threshold.clone(),
             send: self.send.clone(),
+            recorder: self.recorder.clone(),
         }
     }
 }
@@ -1651,3 +2116,377 @@ pub trait PacketType {
 
     fn write<W: io::Write>(&self, buf: &mut W) -> Result<(), Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn test_conn() -> (Conn, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+        let (remote, _) = listener.accept().unwrap();
+        let mut conn = Conn::from_stream(stream, SUPPORTED_PROTOCOLS[0]).unwrap();
+        conn.state = State::Login;
+        (conn, remote)
+    }
+
+    #[test]
+    fn enable_encryption_rejects_bad_key() {
+        let (mut conn, _remote) = test_conn();
+        assert!(conn.enable_encryption(&[0; 15]).is_err());
+        assert!(conn.read_cipher.read().unwrap().is_none());
+        assert!(conn.enable_encryption(&[0; 16]).is_ok());
+    }
+
+    #[test]
+    fn protocol_version_names() {
+        use versions::{protocol_name_to_protocol_version, protocol_version_to_protocol_name};
+        assert_eq!(protocol_version_to_protocol_name(754).unwrap(), "1.16.5");
+        assert_eq!(protocol_version_to_protocol_name(340).unwrap(), "1.12.2");
+        assert_eq!(protocol_version_to_protocol_name(451).unwrap(), "18w50a");
+        assert_eq!(protocol_version_to_protocol_name(5).unwrap(), "1.7.10");
+        assert_eq!(protocol_version_to_protocol_name(1000), None);
+        for &id in SUPPORTED_PROTOCOLS.iter() {
+            let name = protocol_version_to_protocol_name(id).unwrap();
+            assert_eq!(protocol_name_to_protocol_version(name), id);
+        }
+    }
+
+    #[test]
+    fn version_protocol_ids() {
+        let versions = [
+            Version::Other,
+            Version::Old,
+            Version::V1_7,
+            Version::V1_8,
+            Version::V1_9,
+            Version::V1_10,
+            Version::V1_11,
+            Version::V1_12,
+            Version::V1_13,
+            Version::V1_14,
+            Version::V1_15,
+            Version::V1_16,
+            Version::New,
+        ];
+        for version in &versions {
+            assert_eq!(version.protocol_ids().is_empty(), !version.is_supported());
+            for &id in version.protocol_ids() {
+                assert_eq!(Version::from_id(id as u32), *version, "{}", id);
+                assert!(SUPPORTED_PROTOCOLS.contains(&id), "{}", id);
+            }
+        }
+        // Every supported protocol belonging to a version is listed by it, the remaining
+        // ones are snapshots
+        for &id in SUPPORTED_PROTOCOLS.iter() {
+            let version = Version::from_id(id as u32);
+            if version.is_supported() {
+                assert!(version.protocol_ids().contains(&id), "{}", id);
+            }
+        }
+        assert_eq!(Version::from_id(754).name(), "1.16.x");
+    }
+
+    #[test]
+    fn angle_conversions() {
+        let step = 360.0 / 256.0;
+        for &degrees in &[0.0f32, 90.0, 180.0, 270.0] {
+            let angle = Angle::from(degrees);
+            let mut buf = Vec::new();
+            angle.write_to(&mut buf).unwrap();
+            let read = Angle::read_from(&mut io::Cursor::new(buf)).unwrap();
+            assert_eq!(read, angle);
+            assert!((f32::from(read) - degrees).abs() < step, "{}", degrees);
+        }
+        assert_eq!(Angle::from(90.0), Angle(64));
+        assert_eq!(Angle::from(-90.0), Angle(192));
+        assert_eq!(Angle::from(359.9), Angle(0));
+        assert_eq!(Angle::from_radians(std::f32::consts::PI), Angle(128));
+        assert!((Angle(64).radians() - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn strict_varints() {
+        let read = |bytes: &[u8]| VarInt::read_strict(&mut io::Cursor::new(bytes.to_vec()));
+        assert_eq!(read(&[0xff, 0xff, 0xff, 0xff, 0x07]).unwrap().0, i32::MAX);
+        assert_eq!(read(&[0xff, 0xff, 0xff, 0xff, 0x0f]).unwrap().0, -1);
+        assert_eq!(read(&[0x00]).unwrap().0, 0);
+        assert!(read(&[0xff, 0xff, 0xff, 0xff, 0x7f]).is_err());
+        assert!(read(&[0x80, 0x00]).is_err());
+
+        // Lenient reading still accepts them
+        let lenient = VarInt::decode(
+            &mut io::Cursor::new(vec![0xff, 0xff, 0xff, 0xff, 0x7f]),
+            false,
+        );
+        assert_eq!(lenient.unwrap().0, -1);
+        assert_eq!(
+            VarInt::decode(&mut io::Cursor::new(vec![0x80, 0x00]), false)
+                .unwrap()
+                .0,
+            0
+        );
+
+        let mut max = Vec::new();
+        VarLong(i64::MIN).write_to(&mut max).unwrap();
+        assert_eq!(
+            VarLong::read_strict(&mut io::Cursor::new(max)).unwrap().0,
+            i64::MIN
+        );
+        let overflowing = vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x03];
+        assert!(VarLong::read_strict(&mut io::Cursor::new(overflowing)).is_err());
+    }
+
+    #[test]
+    fn option_round_trip() {
+        for value in &[Some("text".to_owned()), None] {
+            let mut buf = Vec::new();
+            value.write_to(&mut buf).unwrap();
+            assert_eq!(buf[0], value.is_some() as u8);
+            let read: Option<String> = Serializable::read_from(&mut io::Cursor::new(buf)).unwrap();
+            assert_eq!(&read, value);
+        }
+
+        // Fields with a `when` condition have no presence prefix
+        let mut buf = Vec::new();
+        Some(5u8).write_present(&mut buf).unwrap();
+        assert_eq!(buf, [5]);
+        let read: Option<u8> = Serializable::read_present(&mut io::Cursor::new(buf)).unwrap();
+        assert_eq!(read, Some(5));
+    }
+
+    #[test]
+    fn close_ends_the_connection() {
+        let (mut conn, mut remote) = test_conn();
+        let mut read = conn.clone();
+        conn.close().unwrap();
+        assert_eq!(remote.read(&mut [0; 1]).unwrap(), 0);
+        assert!(read.read_packet().is_err());
+    }
+
+    #[test]
+    fn dns_cache_respects_ttl() {
+        let mut cache = DnsCache::default();
+        let now = Instant::now();
+        let records = vec!["mc.example.com:25566".to_string()];
+        assert_eq!(cache.get("example.com", now), None);
+        cache.insert("example.com", now, records.clone(), Duration::from_secs(30));
+        assert_eq!(
+            cache.get("example.com", now + Duration::from_secs(10)),
+            Some(records)
+        );
+        assert_eq!(
+            cache.get("example.com", now + Duration::from_secs(31)),
+            None
+        );
+
+        // Expired entries are pruned, and the cache never grows past its limit
+        let later = now + Duration::from_secs(31);
+        cache.insert("other.com", later, vec![], Duration::from_secs(60));
+        assert!(!cache.entries.contains_key("example.com"));
+        for i in 0..MAX_DNS_CACHE_ENTRIES * 2 {
+            let ttl = Duration::from_secs(60 + i as u64);
+            cache.insert(&format!("{}.example.com", i), later, vec![], ttl);
+        }
+        assert_eq!(cache.entries.len(), MAX_DNS_CACHE_ENTRIES);
+        let last = format!("{}.example.com", MAX_DNS_CACHE_ENTRIES * 2 - 1);
+        assert_eq!(cache.get(&last, later), Some(vec![]));
+    }
+
+    #[test]
+    fn ipv6_addresses_are_split() {
+        assert_eq!(split_address("[::1]:25565"), ("::1", Some("25565")));
+        assert_eq!(split_address("[::1]"), ("::1", None));
+        assert_eq!(split_address("::1"), ("::1", None));
+        assert_eq!(split_address("2001:db8::7"), ("2001:db8::7", None));
+        assert_eq!(
+            split_address("127.0.0.1:25566"),
+            ("127.0.0.1", Some("25566"))
+        );
+        assert_eq!(split_address("example.com"), ("example.com", None));
+    }
+
+    #[test]
+    fn malformed_ports_are_errors() {
+        assert!(parse_resolved_address("mc.example.com.:25566").is_ok());
+        assert!(parse_resolved_address("mc.example.com.").is_err());
+        assert!(parse_resolved_address("mc.example.com.:port").is_err());
+        assert!(parse_resolved_address(":25565").is_err());
+        assert!(Conn::new("localhost:port", SUPPORTED_PROTOCOLS[0]).is_err());
+        assert!(Conn::new("[::1]:99999", SUPPORTED_PROTOCOLS[0]).is_err());
+    }
+
+    #[test]
+    fn address_without_port_uses_default_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let conn = Conn::new_with_default_port("127.0.0.1", SUPPORTED_PROTOCOLS[0], port).unwrap();
+        assert_eq!(conn.port, port);
+        assert_eq!(conn.stream.peer_addr().unwrap().port(), port);
+    }
+
+    #[test]
+    fn connect_to_ipv6_literal() {
+        // Not every machine has an IPv6 loopback
+        let listener = match TcpListener::bind("[::1]:0") {
+            Ok(listener) => listener,
+            Err(_) => return,
+        };
+        let port = listener.local_addr().unwrap().port();
+        let conn = Conn::new(&format!("[::1]:{}", port), SUPPORTED_PROTOCOLS[0]).unwrap();
+        assert_eq!(conn.host, "::1");
+        assert_eq!(conn.port, port);
+    }
+
+    #[test]
+    fn compression_threshold_is_shared() {
+        let (mut conn, _remote) = test_conn();
+        let read = conn.clone();
+        let handle = read.compression_threshold_handle();
+        assert_eq!(read.compression_threshold(), -1);
+        conn.set_compression(256);
+        assert_eq!(read.compression_threshold(), 256);
+        assert_eq!(handle.load(Ordering::SeqCst), 256);
+    }
+
+    #[test]
+    fn decline_fml2_handshake_message() {
+        let (mut conn, mut remote) = test_conn();
+        conn.write_fml2_handshake_plugin_message(VarInt(7), None)
+            .unwrap();
+
+        // Length, LoginPluginResponse id, message id and `successful: false`, with no data
+        let mut buf = [0; 4];
+        remote.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [3, 0x02, 7, 0]);
+    }
+
+    #[test]
+    fn errors_are_shown_as_components() {
+        let reason = format::Component::from_string(r#"{"text":"Banned","color":"red"}"#);
+        match Error::Disconnect(reason).to_component() {
+            format::Component::Text(txt) => {
+                assert_eq!(txt.text, "Banned");
+                assert!(matches!(txt.modifier.color, Some(format::Color::Red)));
+            }
+            component => panic!("expected the reason, got {:?}", component),
+        }
+        match Error::Err("Wrong packet".to_string()).to_component() {
+            format::Component::Text(txt) => {
+                assert_eq!(txt.text, "protocol error: Wrong packet");
+                assert!(txt.modifier.color.is_none());
+            }
+            component => panic!("expected the message, got {:?}", component),
+        }
+    }
+
+    #[test]
+    fn parse_login_disconnect() {
+        let mut data = vec![];
+        VarInt(0x00).write_to(&mut data).unwrap();
+        r#"{"text":"Kicked"}"#.to_string().write_to(&mut data).unwrap();
+
+        let packet = try_parse_packet(
+            data,
+            SUPPORTED_PROTOCOLS[0],
+            State::Login,
+            Direction::Clientbound,
+        )
+        .unwrap();
+        match packet {
+            Some(packet::Packet::LoginDisconnect(disconnect)) => {
+                assert_eq!(disconnect.reason.to_string(), "Kicked");
+            }
+            _ => panic!("expected LoginDisconnect, got {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn len_prefixed_map_round_trip() {
+        let mut data = BTreeMap::new();
+        data.insert("minecraft".to_string(), VarInt(1));
+        data.insert("forge".to_string(), VarInt(-2));
+        let map: LenPrefixedMap<VarInt, String, VarInt> = LenPrefixedMap::new(data);
+
+        let mut buf = vec![];
+        map.write_to(&mut buf).unwrap();
+        // Length, then the pairs in key order
+        let mut expected = vec![];
+        VarInt(2).write_to(&mut expected).unwrap();
+        "forge".to_string().write_to(&mut expected).unwrap();
+        VarInt(-2).write_to(&mut expected).unwrap();
+        "minecraft".to_string().write_to(&mut expected).unwrap();
+        VarInt(1).write_to(&mut expected).unwrap();
+        assert_eq!(buf, expected);
+
+        let read: LenPrefixedMap<VarInt, String, VarInt> =
+            Serializable::read_from(&mut io::Cursor::new(buf)).unwrap();
+        let read: Vec<(String, i32)> = read.data.into_iter().map(|(k, v)| (k, v.0)).collect();
+        assert_eq!(
+            read,
+            vec![("forge".to_string(), -2), ("minecraft".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn parse_fml2_forge_data() {
+        let val: serde_json::Value = serde_json::from_str(
+            r#"{
+                "channels": [
+                    {"res": "fml:handshake", "version": "FML2", "required": true},
+                    {"res": "forge:tier_sorting", "version": "1.0", "required": false},
+                    {"res": "jei:channel", "version": "1.0.0", "required": true}
+                ],
+                "mods": [
+                    {"modId": "forge", "modmarker": "ANY"},
+                    {"modId": "jei", "modmarker": "7.6.1.75"}
+                ],
+                "fmlNetworkVersion": 2
+            }"#,
+        )
+        .unwrap();
+        let data = forge::fml2::ForgeData::from_json(&val).unwrap();
+        assert_eq!(data.fml_network_version, 2);
+        let mods: Vec<_> = data
+            .mods
+            .iter()
+            .map(|m| (m.modid.as_str(), m.version.as_str()))
+            .collect();
+        assert_eq!(mods, vec![("forge", "ANY"), ("jei", "7.6.1.75")]);
+        let channels: Vec<_> = data
+            .channels
+            .iter()
+            .map(|c| (c.channel.name.as_str(), c.required))
+            .collect();
+        assert_eq!(
+            channels,
+            vec![
+                ("fml:handshake", true),
+                ("forge:tier_sorting", false),
+                ("jei:channel", true)
+            ]
+        );
+        assert!(data.registries.is_empty());
+
+        let reply = data.mod_list_reply(
+            LenPrefixed::new(vec!["forge".to_string(), "jei".to_string()]),
+            LenPrefixed::new(vec![forge::fml2::Channel {
+                name: "fml:handshake".to_string(),
+                version: "FML2".to_string(),
+            }]),
+            LenPrefixed::new(vec![]),
+        );
+        match reply {
+            forge::fml2::FmlHandshake::ModListReply { channels, .. } => {
+                let names: Vec<_> = channels.data.iter().map(|c| c.name.as_str()).collect();
+                assert_eq!(names, vec!["fml:handshake", "jei:channel"]);
+            }
+            _ => panic!("expected ModListReply, got {:?}", reply),
+        }
+
+        let missing_version = serde_json::json!({ "mods": [] });
+        assert!(forge::fml2::ForgeData::from_json(&missing_version).is_err());
+    }
+}
@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use md5::Md5;
 use serde_json::json;
 use sha1::{self, Digest};
 
+use super::UUID;
+
 #[derive(Clone, Debug)]
 pub struct Profile {
     pub username: String,
@@ -181,6 +184,22 @@ impl Profile {
     }
 }
 
+/// Returns the UUID vanilla servers in offline mode assign to the given username,
+/// a version 3 (MD5 based) UUID of `OfflinePlayer:<username>`.
+pub fn offline_uuid(username: &str) -> UUID {
+    let mut hash = Md5::digest(format!("OfflinePlayer:{}", username).as_bytes());
+    hash[6] = (hash[6] & 0x0f) | 0x30;
+    hash[8] = (hash[8] & 0x3f) | 0x80;
+
+    let mut high = 0u64;
+    let mut low = 0u64;
+    for i in 0..8 {
+        high |= (hash[i] as u64) << (56 - i * 8);
+        low |= (hash[i + 8] as u64) << (56 - i * 8);
+    }
+    UUID(high, low)
+}
+
 fn twos_compliment(data: &mut [u8]) {
     let mut carry = true;
     for i in (0..data.len()).rev() {
@@ -191,3 +210,17 @@ fn twos_compliment(data: &mut [u8]) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn offline_uuid_matches_vanilla() {
+        assert_eq!(
+            offline_uuid("Notch"),
+            UUID::from_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap()
+        );
+    }
+}
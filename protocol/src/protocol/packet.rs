@@ -76,6 +76,12 @@ state_packets!(
             packet TabComplete_NoAssume_NoTarget {
                 field text: String =,
             }
+            /// Since 1.13 the reply carries the id of the request, as the client may
+            /// send a new one before the previous one was answered.
+            packet TabComplete_TransactionId {
+                field transaction_id: VarInt =,
+                field text: String =,
+            }
             /// ChatMessage is sent by the client when it sends a chat message or
             /// executes a command (prefixed by '/').
             packet ChatMessage {
@@ -869,6 +875,13 @@ state_packets!(
             packet TabCompleteReply {
                 field matches: LenPrefixed<VarInt, String> =,
             }
+            /// Since 1.13 the reply also says which part of the text the matches replace.
+            packet TabCompleteReply_TransactionId {
+                field transaction_id: VarInt =,
+                field start: VarInt =,
+                field length: VarInt =,
+                field matches: LenPrefixed<VarInt, packet::TabCompleteMatch> =,
+            }
             packet DeclareCommands {
                 field nodes: LenPrefixed<VarInt, packet::CommandNode> =,
                 field root_index: VarInt =,
@@ -2293,6 +2306,34 @@ impl Serializable for Statistic {
     }
 }
 
+#[derive(Debug, Default)]
+pub struct TabCompleteMatch {
+    pub text: String,
+    pub tooltip: Option<format::Component>,
+}
+
+impl Serializable for TabCompleteMatch {
+    fn read_from<R: io::Read>(buf: &mut R) -> Result<Self, Error> {
+        let text = Serializable::read_from(buf)?;
+        let has_tooltip: bool = Serializable::read_from(buf)?;
+        let tooltip = if has_tooltip {
+            Some(Serializable::read_from(buf)?)
+        } else {
+            None
+        };
+        Ok(TabCompleteMatch { text, tooltip })
+    }
+
+    fn write_to<W: io::Write>(&self, buf: &mut W) -> Result<(), Error> {
+        self.text.write_to(buf)?;
+        self.tooltip.is_some().write_to(buf)?;
+        if let Some(tooltip) = &self.tooltip {
+            tooltip.write_to(buf)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct BlockChangeRecord {
     pub xz: u8,
@@ -2468,7 +2509,7 @@ impl Serializable for AdvancementDisplay {
         let frame_type: VarInt = Serializable::read_from(buf)?;
         let flags: i32 = Serializable::read_from(buf)?;
         let background_texture: Option<String> = if flags & 1 != 0 {
-            Serializable::read_from(buf)?
+            Some(Serializable::read_from(buf)?)
         } else {
             None
         };
@@ -2494,7 +2535,7 @@ impl Serializable for AdvancementDisplay {
         self.frame_type.write_to(buf)?;
         self.flags.write_to(buf)?;
         if self.flags & 1 != 0 {
-            self.background_texture.write_to(buf)?;
+            self.background_texture.write_present(buf)?;
         }
         self.x_coord.write_to(buf)?;
         self.y_coord.write_to(buf)
@@ -2530,12 +2571,7 @@ pub struct CriterionProgress {
 impl Serializable for CriterionProgress {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Self, Error> {
         let id = Serializable::read_from(buf)?;
-        let achieved: u8 = Serializable::read_from(buf)?;
-        let date_of_achieving: Option<i64> = if achieved != 0 {
-            Serializable::read_from(buf)?
-        } else {
-            None
-        };
+        let date_of_achieving = Serializable::read_from(buf)?;
 
         Ok(CriterionProgress {
             id,
@@ -3164,12 +3200,12 @@ impl Serializable for CommandNode {
 
         let name: Option<String> =
             if node_type == CommandNodeType::Argument || node_type == CommandNodeType::Literal {
-                Serializable::read_from(buf)?
+                Some(Serializable::read_from(buf)?)
             } else {
                 None
             };
         let parser: Option<String> = if node_type == CommandNodeType::Argument {
-            Serializable::read_from(buf)?
+            Some(Serializable::read_from(buf)?)
         } else {
             None
         };
@@ -3279,7 +3315,7 @@ impl Serializable for CommandNode {
         };
 
         let suggestions_type: Option<String> = if has_suggestions_type {
-            Serializable::read_from(buf)?
+            Some(Serializable::read_from(buf)?)
         } else {
             None
         };
@@ -0,0 +1,282 @@
+//! Records the raw packets of a connection to a file, so that a session can be replayed
+//! and decoded later without the server it was recorded from.
+//!
+//! A recording starts with `MAGIC`, the format version and the protocol version the
+//! packets were sent with. Every packet after that is stored as the milliseconds since
+//! the recording started, its direction, the state it was sent in and the length
+//! prefixed packet id and data, decrypted and uncompressed.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use instant::{Duration, Instant};
+use lazy_static::lazy_static;
+
+use super::{packet, try_parse_packet, Direction, Error, Serializable, State, VarInt};
+
+pub const MAGIC: &[u8; 4] = b"LFPR";
+const FORMAT_VERSION: u8 = 1;
+/// The longest packet a record can hold, what servers allow uncompressed packets to be.
+const MAX_RECORD_LEN: i32 = 8 * 1024 * 1024;
+
+lazy_static! {
+    static ref OPTIONS: Mutex<Option<Options>> = Mutex::new(None);
+}
+
+#[derive(Clone, Debug)]
+struct Options {
+    path: PathBuf,
+    serverbound: bool,
+}
+
+/// Records the connections started from now on to `path`, each new connection
+/// replacing the previous recording. Packets sent by the client are only included
+/// with `serverbound`.
+pub fn enable_recording(path: PathBuf, serverbound: bool) {
+    OPTIONS
+        .lock()
+        .unwrap()
+        .replace(Options { path, serverbound });
+}
+
+/// Creates the recorder for a new connection, if recording is enabled.
+pub(crate) fn start(protocol_version: i32) -> Result<Option<Recorder<fs::File>>, Error> {
+    let options = match OPTIONS.lock().unwrap().clone() {
+        Some(options) => options,
+        None => return Ok(None),
+    };
+    let file = fs::File::create(&options.path)?;
+    let mut recorder = Recorder::new(file, protocol_version)?;
+    recorder.serverbound = options.serverbound;
+    Ok(Some(recorder))
+}
+
+pub struct Recorder<W: io::Write> {
+    writer: W,
+    start: Instant,
+    serverbound: bool,
+}
+
+impl<W: io::Write> Recorder<W> {
+    pub fn new(mut writer: W, protocol_version: i32) -> Result<Recorder<W>, Error> {
+        writer.write_all(MAGIC)?;
+        writer.write_u8(FORMAT_VERSION)?;
+        writer.write_i32::<BigEndian>(protocol_version)?;
+        Ok(Recorder {
+            writer,
+            start: Instant::now(),
+            serverbound: true,
+        })
+    }
+
+    /// Writes a packet, `data` being its id followed by its fields.
+    pub fn record(&mut self, direction: Direction, state: State, data: &[u8]) -> Result<(), Error> {
+        if direction == Direction::Serverbound && !self.serverbound {
+            return Ok(());
+        }
+        // Written at once, so a recording read while still being written only ever
+        // ends with a partial record
+        let mut record = Vec::with_capacity(data.len() + 16);
+        record.write_u64::<BigEndian>(self.start.elapsed().as_millis() as u64)?;
+        record.write_u8(direction_id(direction))?;
+        record.write_u8(state_id(state))?;
+        VarInt(data.len() as i32).write_to(&mut record)?;
+        record.extend_from_slice(data);
+        self.writer.write_all(&record)?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A packet read back from a recording.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// When the packet was recorded, relative to the start of the recording.
+    pub time: Duration,
+    pub direction: Direction,
+    pub state: State,
+    /// The packet id followed by its fields.
+    pub data: Vec<u8>,
+}
+
+impl Record {
+    pub fn decode(&self, protocol_version: i32) -> Result<Option<packet::Packet>, Error> {
+        try_parse_packet(
+            self.data.clone(),
+            protocol_version,
+            self.state,
+            self.direction,
+        )
+    }
+}
+
+/// Reads the packets of a recording in the order they were recorded.
+pub struct Reader<R: io::Read> {
+    reader: R,
+    pub protocol_version: i32,
+}
+
+impl<R: io::Read> Reader<R> {
+    pub fn new(mut reader: R) -> Result<Reader<R>, Error> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::Err("not a packet recording".to_owned()));
+        }
+        let format_version = reader.read_u8()?;
+        if format_version != FORMAT_VERSION {
+            return Err(Error::Err(format!(
+                "unsupported packet recording version {}",
+                format_version
+            )));
+        }
+        let protocol_version = reader.read_i32::<BigEndian>()?;
+        Ok(Reader {
+            reader,
+            protocol_version,
+        })
+    }
+
+    fn read_record(&mut self) -> Result<Option<Record>, Error> {
+        let millis = match self.reader.read_u64::<BigEndian>() {
+            Ok(millis) => millis,
+            // The recording ended, possibly in the middle of the last record
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let direction = match self.reader.read_u8()? {
+            0 => Direction::Serverbound,
+            1 => Direction::Clientbound,
+            id => return Err(Error::Err(format!("invalid packet direction {}", id))),
+        };
+        let state = match self.reader.read_u8()? {
+            0 => State::Handshaking,
+            1 => State::Status,
+            2 => State::Login,
+            3 => State::Play,
+            id => return Err(Error::Err(format!("invalid protocol state {}", id))),
+        };
+        let len = VarInt::read_from(&mut self.reader)?.0;
+        if !(0..=MAX_RECORD_LEN).contains(&len) {
+            return Err(Error::Err(format!("invalid packet length {}", len)));
+        }
+        let mut data = vec![0; len as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some(Record {
+            time: Duration::from_millis(millis),
+            direction,
+            state,
+            data,
+        }))
+    }
+}
+
+impl<R: io::Read> Iterator for Reader<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Opens the recording at `path` for reading.
+pub fn open(path: &Path) -> Result<Reader<io::BufReader<fs::File>>, Error> {
+    Reader::new(io::BufReader::new(fs::File::open(path)?))
+}
+
+fn direction_id(direction: Direction) -> u8 {
+    match direction {
+        Direction::Serverbound => 0,
+        Direction::Clientbound => 1,
+    }
+}
+
+fn state_id(state: State) -> u8 {
+    match state {
+        State::Handshaking => 0,
+        State::Status => 1,
+        State::Login => 2,
+        State::Play => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packet::{handshake, play, Packet};
+    use crate::protocol::PacketType;
+
+    fn packet_data<T: PacketType>(packet: &T, protocol_version: i32) -> Vec<u8> {
+        let mut data = vec![];
+        VarInt(packet.packet_id(protocol_version))
+            .write_to(&mut data)
+            .unwrap();
+        packet.write(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn recorded_packets_read_back() {
+        let protocol_version = 754;
+        let handshake = packet_data(
+            &handshake::serverbound::Handshake {
+                protocol_version: VarInt(protocol_version),
+                host: "localhost".to_owned(),
+                port: 25565,
+                next: VarInt(2),
+            },
+            protocol_version,
+        );
+        let keep_alive = packet_data(
+            &play::clientbound::KeepAliveClientbound_i64 { id: 42 },
+            protocol_version,
+        );
+
+        let mut recorder = Recorder::new(vec![], protocol_version).unwrap();
+        recorder
+            .record(Direction::Serverbound, State::Handshaking, &handshake)
+            .unwrap();
+        recorder
+            .record(Direction::Clientbound, State::Play, &keep_alive)
+            .unwrap();
+        let recording = recorder.into_inner();
+
+        let mut reader = Reader::new(io::Cursor::new(recording)).unwrap();
+        assert_eq!(reader.protocol_version, protocol_version);
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.direction, Direction::Serverbound);
+        assert_eq!(first.state, State::Handshaking);
+        assert_eq!(first.data, handshake);
+        match first.decode(protocol_version).unwrap() {
+            Some(Packet::Handshake(packet)) => assert_eq!(packet.host, "localhost"),
+            packet => panic!("expected a handshake, got {:?}", packet),
+        }
+        let second = reader.next().unwrap().unwrap();
+        assert_eq!(second.direction, Direction::Clientbound);
+        assert_eq!(second.state, State::Play);
+        assert!(second.time >= first.time);
+        match second.decode(protocol_version).unwrap() {
+            Some(Packet::KeepAliveClientbound_i64(packet)) => assert_eq!(packet.id, 42),
+            packet => panic!("expected a keep alive, got {:?}", packet),
+        }
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn corrupt_lengths_are_errors() {
+        for &len in &[-1, MAX_RECORD_LEN + 1, i32::MAX] {
+            let mut recording = Recorder::new(vec![], 754).unwrap().into_inner();
+            recording.write_u64::<BigEndian>(0).unwrap();
+            recording.extend_from_slice(&[1, 3]);
+            VarInt(len).write_to(&mut recording).unwrap();
+            let mut reader = Reader::new(io::Cursor::new(recording)).unwrap();
+            assert!(reader.next().unwrap().is_err());
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Connecting to servers through a SOCKS5 proxy (RFC 1928), optionally with a username
+//! and password (RFC 1929).
+//!
+//! The proxy is given as `[username:password@]host[:port]`. Hostnames of servers are
+//! passed on to the proxy to resolve, only SRV records are still looked up locally.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::debug;
+
+use super::{split_address, Error};
+
+/// The port SOCKS proxies listen on unless told otherwise.
+pub const DEFAULT_PROXY_PORT: u16 = 1080;
+
+const SOCKS_VERSION: u8 = 5;
+const AUTH_VERSION: u8 = 1;
+
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_PASSWORD: u8 = 0x02;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xFF;
+
+const COMMAND_CONNECT: u8 = 0x01;
+
+const ADDRESS_IPV4: u8 = 0x01;
+const ADDRESS_DOMAIN: u8 = 0x03;
+const ADDRESS_IPV6: u8 = 0x04;
+
+lazy_static! {
+    static ref PROXY: Mutex<String> = Mutex::new(String::new());
+}
+
+/// Sets the proxy `Conn::new` connects through, an empty string connects directly.
+pub fn set_proxy(proxy: &str) {
+    let mut current = PROXY.lock().unwrap();
+    if *current != proxy {
+        *current = proxy.to_owned();
+    }
+}
+
+/// The proxy set with `set_proxy`, `None` for direct connections.
+pub fn proxy() -> Result<Option<Proxy>, Error> {
+    let proxy = PROXY.lock().unwrap();
+    if proxy.trim().is_empty() {
+        Ok(None)
+    } else {
+        Proxy::parse(&proxy).map(Some)
+    }
+}
+
+/// A SOCKS5 proxy and the credentials to log in to it with, if it needs any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proxy {
+    pub host: String,
+    pub port: u16,
+    pub credentials: Option<(String, String)>,
+}
+
+impl Proxy {
+    /// Parses `[username:password@]host[:port]`.
+    pub fn parse(proxy: &str) -> Result<Proxy, Error> {
+        let proxy = proxy.trim();
+        let proxy = proxy.strip_prefix("socks5://").unwrap_or(proxy);
+        let (credentials, address) = match proxy.rfind('@') {
+            Some(at) => {
+                let mut parts = proxy[..at].splitn(2, ':');
+                let username = parts.next().unwrap().to_owned();
+                let password = parts.next().unwrap_or("").to_owned();
+                (Some((username, password)), &proxy[at + 1..])
+            }
+            None => (None, proxy),
+        };
+        let (host, port) = split_address(address);
+        if host.is_empty() {
+            return Err(Error::Err(format!("Missing host in proxy {}", address)));
+        }
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| Error::Err(format!("Invalid port in proxy {}", address)))?,
+            None => DEFAULT_PROXY_PORT,
+        };
+        Ok(Proxy {
+            host: host.to_owned(),
+            port,
+            credentials,
+        })
+    }
+
+    /// Connects to `host` and `port` through the proxy.
+    pub fn connect(&self, host: &str, port: u16) -> Result<TcpStream, Error> {
+        debug!(
+            "Connecting to {}:{} through proxy {}:{}",
+            host, port, self.host, self.port
+        );
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+        self.handshake(&mut stream, host, port)?;
+        Ok(stream)
+    }
+
+    /// Logs in to the proxy over `stream` and asks it to connect to `host` and `port`,
+    /// after which everything sent over `stream` goes to the server.
+    pub fn handshake<S: Read + Write>(
+        &self,
+        stream: &mut S,
+        host: &str,
+        port: u16,
+    ) -> Result<(), Error> {
+        let method = if self.credentials.is_some() {
+            METHOD_PASSWORD
+        } else {
+            METHOD_NO_AUTH
+        };
+        stream.write_all(&[SOCKS_VERSION, 1, method])?;
+        let mut reply = [0; 2];
+        stream.read_exact(&mut reply)?;
+        check_version(reply[0], SOCKS_VERSION)?;
+        match reply[1] {
+            METHOD_NO_AUTH => {}
+            METHOD_PASSWORD if method == METHOD_PASSWORD => self.authenticate(stream)?,
+            METHOD_NONE_ACCEPTABLE if method == METHOD_PASSWORD => {
+                return Err(Error::Err(
+                    "The proxy doesn't accept username and password logins".to_owned(),
+                ))
+            }
+            METHOD_NONE_ACCEPTABLE => {
+                return Err(Error::Err(
+                    "The proxy requires a username and password".to_owned(),
+                ))
+            }
+            other => {
+                return Err(Error::Err(format!(
+                    "The proxy chose an unknown login method {}",
+                    other
+                )))
+            }
+        }
+
+        let mut request = vec![SOCKS_VERSION, COMMAND_CONNECT, 0];
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                request.push(ADDRESS_IPV4);
+                request.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                request.push(ADDRESS_IPV6);
+                request.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                if host.len() > u8::MAX as usize {
+                    return Err(Error::Err(format!("Hostname {} is too long", host)));
+                }
+                request.push(ADDRESS_DOMAIN);
+                request.push(host.len() as u8);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request)?;
+
+        let mut reply = [0; 4];
+        stream.read_exact(&mut reply)?;
+        check_version(reply[0], SOCKS_VERSION)?;
+        if reply[1] != 0 {
+            return Err(Error::Err(format!(
+                "The proxy failed to connect to {}:{}: {}",
+                host,
+                port,
+                reply_message(reply[1])
+            )));
+        }
+        // The address the proxy connected from, which isn't needed
+        let address_len = match reply[3] {
+            ADDRESS_IPV4 => 4,
+            ADDRESS_IPV6 => 16,
+            ADDRESS_DOMAIN => {
+                let mut len = [0; 1];
+                stream.read_exact(&mut len)?;
+                len[0] as u64
+            }
+            other => {
+                return Err(Error::Err(format!(
+                    "The proxy replied with an unknown address type {}",
+                    other
+                )))
+            }
+        };
+        io::copy(&mut stream.take(address_len + 2), &mut io::sink())?;
+        Ok(())
+    }
+
+    fn authenticate<S: Read + Write>(&self, stream: &mut S) -> Result<(), Error> {
+        let (username, password) = self.credentials.as_ref().unwrap();
+        if username.len() > u8::MAX as usize || password.len() > u8::MAX as usize {
+            return Err(Error::Err(
+                "Proxy usernames and passwords can be at most 255 bytes long".to_owned(),
+            ));
+        }
+        let mut request = vec![AUTH_VERSION, username.len() as u8];
+        request.extend_from_slice(username.as_bytes());
+        request.push(password.len() as u8);
+        request.extend_from_slice(password.as_bytes());
+        stream.write_all(&request)?;
+
+        let mut reply = [0; 2];
+        stream.read_exact(&mut reply)?;
+        check_version(reply[0], AUTH_VERSION)?;
+        if reply[1] != 0 {
+            return Err(Error::Err(
+                "The proxy rejected the username and password".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn check_version(version: u8, expected: u8) -> Result<(), Error> {
+    if version == expected {
+        Ok(())
+    } else {
+        Err(Error::Err(format!(
+            "Not a SOCKS5 proxy, it replied with version {}",
+            version
+        )))
+    }
+}
+
+fn reply_message(reply: u8) -> &'static str {
+    match reply {
+        0x01 => "general failure",
+        0x02 => "not allowed by the proxy's rules",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Accepts one connection like a SOCKS5 proxy would, checking the client asks for
+    /// `target` with the given credentials, then echoes a greeting from the "server".
+    fn mock_proxy(credentials: Option<(&'static str, &'static str)>, target: Vec<u8>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut client, _) = listener.accept().unwrap();
+            let mut greeting = [0; 3];
+            client.read_exact(&mut greeting).unwrap();
+            match credentials {
+                Some((username, password)) => {
+                    assert_eq!(greeting, [5, 1, METHOD_PASSWORD]);
+                    client.write_all(&[5, METHOD_PASSWORD]).unwrap();
+                    let mut login = vec![0; 3 + username.len() + password.len()];
+                    client.read_exact(&mut login).unwrap();
+                    let mut expected = vec![1, username.len() as u8];
+                    expected.extend_from_slice(username.as_bytes());
+                    expected.push(password.len() as u8);
+                    expected.extend_from_slice(password.as_bytes());
+                    assert_eq!(login, expected);
+                    client.write_all(&[1, 0]).unwrap();
+                }
+                None => {
+                    assert_eq!(greeting, [5, 1, METHOD_NO_AUTH]);
+                    client.write_all(&[5, METHOD_NO_AUTH]).unwrap();
+                }
+            }
+            let mut request = vec![0; target.len()];
+            client.read_exact(&mut request).unwrap();
+            assert_eq!(request, target);
+            client
+                .write_all(&[5, 0, 0, ADDRESS_IPV4, 127, 0, 0, 1, 0x1F, 0x90])
+                .unwrap();
+            client.write_all(b"hello").unwrap();
+        });
+        port
+    }
+
+    #[test]
+    fn connects_through_mock_proxy() {
+        // mc.example.com:25565, resolved by the proxy
+        let mut target = vec![5, COMMAND_CONNECT, 0, ADDRESS_DOMAIN, 14];
+        target.extend_from_slice(b"mc.example.com");
+        target.extend_from_slice(&[0x63, 0xDD]);
+        let port = mock_proxy(None, target);
+        let proxy = Proxy::parse(&format!("127.0.0.1:{}", port)).unwrap();
+        let mut stream = proxy.connect("mc.example.com", 25565).unwrap();
+        let mut greeting = [0; 5];
+        stream.read_exact(&mut greeting).unwrap();
+        assert_eq!(&greeting, b"hello");
+    }
+
+    #[test]
+    fn logs_in_to_mock_proxy() {
+        let target = vec![5, COMMAND_CONNECT, 0, ADDRESS_IPV4, 10, 0, 0, 2, 0x63, 0xDD];
+        let port = mock_proxy(Some(("steve", "hunter2")), target);
+        let proxy = Proxy::parse(&format!("steve:hunter2@127.0.0.1:{}", port)).unwrap();
+        let mut stream = proxy.connect("10.0.0.2", 25565).unwrap();
+        let mut greeting = [0; 5];
+        stream.read_exact(&mut greeting).unwrap();
+        assert_eq!(&greeting, b"hello");
+    }
+
+    #[test]
+    fn proxy_addresses_are_parsed() {
+        assert_eq!(
+            Proxy::parse("localhost").unwrap(),
+            Proxy {
+                host: "localhost".to_owned(),
+                port: DEFAULT_PROXY_PORT,
+                credentials: None,
+            }
+        );
+        assert_eq!(
+            Proxy::parse("socks5://user:p@ss@[::1]:9050").unwrap(),
+            Proxy {
+                host: "::1".to_owned(),
+                port: 9050,
+                credentials: Some(("user".to_owned(), "p@ss".to_owned())),
+            }
+        );
+        assert!(Proxy::parse("localhost:socks").is_err());
+        assert!(Proxy::parse("user:pass@").is_err());
+    }
+}
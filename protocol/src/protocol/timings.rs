@@ -0,0 +1,136 @@
+//! Per packet type decode timings, collected while `super::is_packet_timings()` is enabled.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use instant::Duration;
+use lazy_static::lazy_static;
+
+/// Upper bounds of the histogram buckets, anything slower lands in the last bucket.
+pub const BUCKET_LIMITS: [Duration; 4] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+];
+
+lazy_static! {
+    static ref TIMINGS: Mutex<PacketTimings> = Mutex::new(PacketTimings::default());
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PacketTiming {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+    /// Number of packets per bucket of `BUCKET_LIMITS`, plus one for slower ones.
+    pub buckets: [u64; BUCKET_LIMITS.len() + 1],
+}
+
+impl PacketTiming {
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::default()
+        } else {
+            self.total / self.count as u32
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PacketTimings {
+    pub packets: HashMap<&'static str, PacketTiming>,
+}
+
+impl PacketTimings {
+    pub fn record(&mut self, name: &'static str, elapsed: Duration) {
+        let timing = self.packets.entry(name).or_default();
+        timing.count += 1;
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+        let bucket = BUCKET_LIMITS
+            .iter()
+            .position(|limit| elapsed <= *limit)
+            .unwrap_or(BUCKET_LIMITS.len());
+        timing.buckets[bucket] += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+}
+
+impl fmt::Display for PacketTimings {
+    /// One line per packet type, the most expensive ones in total first.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut packets: Vec<_> = self.packets.iter().collect();
+        packets.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+        write!(
+            f,
+            "packet decode timings (buckets <=10us/100us/1ms/10ms/more):"
+        )?;
+        for (name, timing) in packets {
+            write!(
+                f,
+                "\n  {}: count={} total={:?} mean={:?} max={:?} buckets={:?}",
+                name,
+                timing.count,
+                timing.total,
+                timing.mean(),
+                timing.max,
+                timing.buckets
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn record_packet_timing(name: &'static str, elapsed: Duration) {
+    TIMINGS.lock().unwrap().record(name, elapsed);
+}
+
+/// A snapshot of the timings recorded so far.
+pub fn packet_timings() -> PacketTimings {
+    TIMINGS.lock().unwrap().clone()
+}
+
+/// Returns the timings recorded so far, starting over with an empty histogram.
+pub fn take_packet_timings() -> PacketTimings {
+    std::mem::take(&mut *TIMINGS.lock().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packet::Packet;
+
+    #[test]
+    fn histogram_records_entries() {
+        let mut timings = PacketTimings::default();
+        assert!(timings.is_empty());
+        timings.record("ChunkData", Duration::from_micros(5));
+        timings.record("ChunkData", Duration::from_micros(500));
+        timings.record("ChunkData", Duration::from_millis(50));
+        timings.record("KeepAliveClientbound_i64", Duration::from_micros(1));
+
+        let chunk = &timings.packets["ChunkData"];
+        assert_eq!(chunk.count, 3);
+        assert_eq!(chunk.total, Duration::from_micros(50_505));
+        assert_eq!(chunk.max, Duration::from_millis(50));
+        assert_eq!(chunk.mean(), Duration::from_micros(16_835));
+        assert_eq!(chunk.buckets, [1, 0, 1, 0, 1]);
+        assert_eq!(
+            timings.packets["KeepAliveClientbound_i64"].buckets,
+            [1, 0, 0, 0, 0]
+        );
+
+        let keep_alive = Packet::KeepAliveClientbound_i64(Default::default());
+        assert_eq!(keep_alive.name(), "KeepAliveClientbound_i64");
+
+        let summary = timings.to_string();
+        let chunk_line = summary.find("ChunkData").unwrap();
+        let keep_alive_line = summary.find("KeepAliveClientbound_i64").unwrap();
+        assert!(chunk_line < keep_alive_line);
+    }
+}
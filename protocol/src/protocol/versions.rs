@@ -61,6 +61,38 @@ pub fn protocol_name_to_protocol_version(s: String) -> i32 {
     }
 }
 
+/// Returns the canonical name of a protocol version, the newest release using it.
+pub fn protocol_version_to_protocol_name(version: i32) -> Option<String> {
+    let name = match version {
+        754 => "1.16.5",
+        753 => "1.16.3",
+        751 => "1.16.2",
+        736 => "1.16.1",
+        735 => "1.16",
+        578 => "1.15.2",
+        575 => "1.15.1",
+        498 => "1.14.4",
+        490 => "1.14.3",
+        485 => "1.14.2",
+        480 => "1.14.1",
+        477 => "1.14",
+        452 => "19w02a",
+        451 => "18w50a",
+        404 => "1.13.2",
+        340 => "1.12.2",
+        316 => "1.11.2",
+        315 => "1.11",
+        210 => "1.10.2",
+        109 => "1.9.2",
+        107 => "1.9",
+        74 => "15w39c",
+        47 => "1.8.9",
+        5 => "1.7.10",
+        _ => return None,
+    };
+    Some(name.to_owned())
+}
+
 pub fn translate_internal_packet_id_for_version(
     version: i32,
     state: State,
@@ -13,7 +13,7 @@ protocol_packet_ids!(
             0x02 => ChatMessage
             0x03 => ClientStatus
             0x04 => ClientSettings
-            0x05 => TabComplete
+            0x05 => TabComplete_TransactionId
             0x06 => ConfirmTransactionServerbound
             0x07 => EnchantItem
             0x08 => ClickWindow
@@ -69,7 +69,7 @@ protocol_packet_ids!(
             0x0d => ServerDifficulty
             0x0e => ServerMessage_Position
             0x0f => MultiBlockChange_VarInt
-            0x10 => TabCompleteReply
+            0x10 => TabCompleteReply_TransactionId
             0x11 => DeclareCommands
             0x12 => ConfirmTransaction
             0x13 => WindowClose
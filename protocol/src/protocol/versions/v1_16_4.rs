@@ -14,7 +14,7 @@ protocol_packet_ids!(
             0x03 => ChatMessage
             0x04 => ClientStatus
             0x05 => ClientSettings
-            0x06 => TabComplete
+            0x06 => TabComplete_TransactionId
             0x07 => ConfirmTransactionServerbound
             0x08 => ClickWindowButton
             0x09 => ClickWindow
@@ -73,7 +73,7 @@ protocol_packet_ids!(
             0x0c => BossBar
             0x0d => ServerDifficulty_Locked
             0x0e => ServerMessage_Sender
-            0x0f => TabCompleteReply
+            0x0f => TabCompleteReply_TransactionId
             0x10 => DeclareCommands
             0x11 => ConfirmTransaction
             0x12 => WindowClose
@@ -58,6 +58,68 @@ impl Metadata {
         self.map.insert(key.index, val.wrap());
     }
 
+    /// The raw value at `index`, whatever its type.
+    pub fn get_raw(&self, index: i32) -> Option<&Value> {
+        self.map.get(&index)
+    }
+
+    /// The byte at `index`, `None` if it's missing or not a byte.
+    pub fn get_byte(&self, index: i32) -> Option<i8> {
+        match self.map.get(&index)? {
+            Value::Byte(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    /// The integer at `index`, `None` if it's missing or not an integer.
+    pub fn get_int(&self, index: i32) -> Option<i32> {
+        match self.map.get(&index)? {
+            Value::Int(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    /// The float at `index`, `None` if it's missing or not a float.
+    pub fn get_float(&self, index: i32) -> Option<f32> {
+        match self.map.get(&index)? {
+            Value::Float(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    /// The string at `index`, `None` if it's missing or not a string.
+    pub fn get_string(&self, index: i32) -> Option<&str> {
+        match self.map.get(&index)? {
+            Value::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// The boolean at `index`. Before 1.9 booleans were sent as bytes, so those count too.
+    pub fn get_bool(&self, index: i32) -> Option<bool> {
+        match self.map.get(&index)? {
+            Value::Bool(val) => Some(*val),
+            Value::Byte(val) => Some(*val != 0),
+            _ => None,
+        }
+    }
+
+    /// The chat component at `index`, `None` if it's missing, absent or not a component.
+    pub fn get_component(&self, index: i32) -> Option<&format::Component> {
+        match self.map.get(&index)? {
+            Value::FormatComponent(val) => Some(val),
+            Value::OptionalFormatComponent(val) => val.data.first(),
+            _ => None,
+        }
+    }
+
+    /// Whether the bits of `mask` are set in the byte of flags at `index`, e.g. index 0,
+    /// mask 0x01 for an entity being on fire. A missing byte has no flags set.
+    pub fn get_boolean_flag(&self, index: i32, mask: u8) -> bool {
+        self.get_byte(index)
+            .map_or(false, |flags| flags as u8 & mask == mask)
+    }
+
     fn put_raw<T: MetaValue>(&mut self, index: i32, val: T) {
         self.map.insert(index, val.wrap());
     }
@@ -183,21 +245,9 @@ impl Metadata {
                     ],
                 ),
                 8 => m.put_raw(index, Position::read_from(buf)?),
-                9 => {
-                    if bool::read_from(buf)? {
-                        m.put_raw(index, Option::<Position>::read_from(buf)?);
-                    } else {
-                        m.put_raw::<Option<Position>>(index, None);
-                    }
-                }
+                9 => m.put_raw(index, Option::<Position>::read_from(buf)?),
                 10 => m.put_raw(index, protocol::VarInt::read_from(buf)?),
-                11 => {
-                    if bool::read_from(buf)? {
-                        m.put_raw(index, Option::<protocol::UUID>::read_from(buf)?);
-                    } else {
-                        m.put_raw::<Option<protocol::UUID>>(index, None);
-                    }
-                }
+                11 => m.put_raw(index, Option::<protocol::UUID>::read_from(buf)?),
                 12 => m.put_raw(index, protocol::VarInt::read_from(buf)?.0 as u16),
                 13 => {
                     let ty = u8::read_from(buf)?;
@@ -258,7 +308,6 @@ impl Metadata {
                 }
                 Value::OptionalPosition(ref val) => {
                     u8::write_to(&9, buf)?;
-                    val.is_some().write_to(buf)?;
                     val.write_to(buf)?;
                 }
                 Value::Direction(ref val) => {
@@ -267,7 +316,6 @@ impl Metadata {
                 }
                 Value::OptionalUUID(ref val) => {
                     u8::write_to(&11, buf)?;
-                    val.is_some().write_to(buf)?;
                     val.write_to(buf)?;
                 }
                 Value::Block(ref val) => {
@@ -315,21 +363,9 @@ impl Metadata {
                     ],
                 ),
                 9 => m.put_raw(index, Position::read_from(buf)?),
-                10 => {
-                    if bool::read_from(buf)? {
-                        m.put_raw(index, Option::<Position>::read_from(buf)?);
-                    } else {
-                        m.put_raw::<Option<Position>>(index, None);
-                    }
-                }
+                10 => m.put_raw(index, Option::<Position>::read_from(buf)?),
                 11 => m.put_raw(index, protocol::VarInt::read_from(buf)?),
-                12 => {
-                    if bool::read_from(buf)? {
-                        m.put_raw(index, Option::<protocol::UUID>::read_from(buf)?);
-                    } else {
-                        m.put_raw::<Option<protocol::UUID>>(index, None);
-                    }
-                }
+                12 => m.put_raw(index, Option::<protocol::UUID>::read_from(buf)?),
                 13 => m.put_raw(index, protocol::VarInt::read_from(buf)?.0 as u16),
                 14 => {
                     let ty = u8::read_from(buf)?;
@@ -342,13 +378,7 @@ impl Metadata {
                 }
                 15 => panic!("TODO: particle"),
                 16 => m.put_raw(index, VillagerData::read_from(buf)?),
-                17 => {
-                    if bool::read_from(buf)? {
-                        m.put_raw(index, Option::<protocol::VarInt>::read_from(buf)?);
-                    } else {
-                        m.put_raw::<Option<protocol::VarInt>>(index, None);
-                    }
-                }
+                17 => m.put_raw(index, Option::<protocol::VarInt>::read_from(buf)?),
                 18 => m.put_raw(index, PoseData::read_from(buf)?),
                 _ => return Err(protocol::Error::Err("unknown metadata type".to_owned())),
             }
@@ -404,7 +434,6 @@ impl Metadata {
                 }
                 Value::OptionalPosition(ref val) => {
                     u8::write_to(&10, buf)?;
-                    val.is_some().write_to(buf)?;
                     val.write_to(buf)?;
                 }
                 Value::Direction(ref val) => {
@@ -413,7 +442,6 @@ impl Metadata {
                 }
                 Value::OptionalUUID(ref val) => {
                     u8::write_to(&12, buf)?;
-                    val.is_some().write_to(buf)?;
                     val.write_to(buf)?;
                 }
                 Value::Block(ref val) => {
@@ -978,4 +1006,47 @@ mod test {
             None => panic!("failed"),
         }
     }
+
+    #[test]
+    fn typed_fields() {
+        let mut blob = vec![];
+        // Entity flags: on fire and sneaking
+        0u8.write_to(&mut blob).unwrap();
+        protocol::VarInt(0).write_to(&mut blob).unwrap();
+        0x03i8.write_to(&mut blob).unwrap();
+        // Air ticks
+        1u8.write_to(&mut blob).unwrap();
+        protocol::VarInt(1).write_to(&mut blob).unwrap();
+        protocol::VarInt(300).write_to(&mut blob).unwrap();
+        // Custom name
+        2u8.write_to(&mut blob).unwrap();
+        protocol::VarInt(5).write_to(&mut blob).unwrap();
+        true.write_to(&mut blob).unwrap();
+        r#"{"text":"Steve"}"#.to_owned().write_to(&mut blob).unwrap();
+        // Custom name visible
+        3u8.write_to(&mut blob).unwrap();
+        protocol::VarInt(7).write_to(&mut blob).unwrap();
+        true.write_to(&mut blob).unwrap();
+        // Health
+        8u8.write_to(&mut blob).unwrap();
+        protocol::VarInt(2).write_to(&mut blob).unwrap();
+        20.0f32.write_to(&mut blob).unwrap();
+        0xFFu8.write_to(&mut blob).unwrap();
+
+        let m = Metadata::read_from113(&mut io::Cursor::new(blob)).unwrap();
+        assert_eq!(m.get_byte(0), Some(0x03));
+        assert!(m.get_boolean_flag(0, 0x01));
+        assert!(m.get_boolean_flag(0, 0x02));
+        assert!(!m.get_boolean_flag(0, 0x20));
+        assert_eq!(m.get_int(1), Some(300));
+        assert_eq!(
+            m.get_component(2).map(|name| name.to_plain_string()),
+            Some("Steve".to_owned())
+        );
+        assert_eq!(m.get_bool(3), Some(true));
+        assert_eq!(m.get_float(8), Some(20.0));
+        assert_eq!(m.get_string(8), None);
+        assert_eq!(m.get_byte(4), None);
+        assert!(!m.get_boolean_flag(4, 0x01));
+    }
 }
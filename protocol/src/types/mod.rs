@@ -49,4 +49,12 @@ impl GameMode {
     pub fn noclip(&self) -> bool {
         matches!(*self, GameMode::Spectator)
     }
+
+    pub fn can_dig(&self) -> bool {
+        matches!(*self, GameMode::Survival | GameMode::Creative)
+    }
+
+    pub fn instant_break(&self) -> bool {
+        matches!(*self, GameMode::Creative)
+    }
 }
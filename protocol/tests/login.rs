@@ -0,0 +1,100 @@
+//! Logs in to a minimal offline mode server running on a local socket.
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::thread;
+
+use leafish_protocol::protocol::packet::login::clientbound::{
+    LoginSuccess_UUID, SetInitialCompression,
+};
+use leafish_protocol::protocol::packet::Packet;
+use leafish_protocol::protocol::{
+    mojang, packet, Conn, Direction, PacketType, Serializable, State, VarInt, UUID,
+};
+
+/// 1.16.5
+const PROTOCOL_VERSION: i32 = 754;
+const PLAYER_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+fn receive(stream: &mut TcpStream, state: State, compression_threshold: i32) -> Packet {
+    let (id, mut data) = Conn::read_raw_packet_from(stream, compression_threshold).unwrap();
+    packet::packet_by_id(
+        PROTOCOL_VERSION,
+        state,
+        Direction::Serverbound,
+        id,
+        &mut data,
+    )
+    .unwrap()
+    .expect("unknown packet")
+}
+
+fn send<P: PacketType>(stream: &mut TcpStream, packet: P, compressed: bool) {
+    let mut data = vec![];
+    VarInt(packet.packet_id(PROTOCOL_VERSION))
+        .write_to(&mut data)
+        .unwrap();
+    packet.write(&mut data).unwrap();
+    let mut frame = vec![];
+    if compressed {
+        // Below the threshold, so sent as is
+        VarInt(data.len() as i32 + 1).write_to(&mut frame).unwrap();
+        VarInt(0).write_to(&mut frame).unwrap();
+    } else {
+        VarInt(data.len() as i32).write_to(&mut frame).unwrap();
+    }
+    frame.extend(data);
+    stream.write_all(&frame).unwrap();
+}
+
+#[test]
+fn login_to_offline_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        match receive(&mut stream, State::Handshaking, -1) {
+            Packet::Handshake(handshake) => {
+                assert_eq!(handshake.protocol_version.0, PROTOCOL_VERSION);
+                assert_eq!(handshake.next.0, 2);
+            }
+            packet => panic!("expected a handshake, got {:?}", packet),
+        }
+        let username = match receive(&mut stream, State::Login, -1) {
+            Packet::LoginStart(login_start) => login_start.username,
+            packet => panic!("expected a login start, got {:?}", packet),
+        };
+        send(
+            &mut stream,
+            SetInitialCompression {
+                threshold: VarInt(256),
+            },
+            false,
+        );
+        send(
+            &mut stream,
+            LoginSuccess_UUID {
+                uuid: UUID::from_str(PLAYER_UUID).unwrap(),
+                username,
+            },
+            true,
+        );
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    let mut conn = Conn::from_stream(stream, PROTOCOL_VERSION).unwrap();
+    let profile = mojang::Profile {
+        username: "Steve".to_owned(),
+        id: String::new(),
+        access_token: String::new(),
+    };
+    let login = conn.login(&profile).unwrap();
+    server.join().unwrap();
+
+    assert_eq!(login.username, "Steve");
+    assert_eq!(login.uuid.to_string(), PLAYER_UUID);
+    assert!(!login.online_mode);
+    assert_eq!(conn.state, State::Play);
+    assert_eq!(conn.compression_threshold(), 256);
+}
@@ -12,8 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::console;
+use std::fs;
+use std::io;
 use std::marker::PhantomData;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use leafish_protocol::protocol::microsoft;
+use leafish_protocol::protocol::mojang;
+use log::{error, warn};
+use serde_json::{json, Value};
+
+use crate::console;
+use crate::paths;
 
 pub const CL_USERNAME: console::CVar<String> = console::CVar {
     ty: PhantomData,
@@ -22,6 +34,7 @@ pub const CL_USERNAME: console::CVar<String> = console::CVar {
 to servers."#,
     mutable: false,
     serializable: true,
+    range: None,
     default: &|| "".to_owned(),
 };
 
@@ -32,6 +45,7 @@ pub const CL_UUID: console::CVar<String> = console::CVar {
 unlike their username."#,
     mutable: false,
     serializable: true,
+    range: None,
     default: &|| "".to_owned(),
 };
 
@@ -42,6 +56,7 @@ pub const AUTH_TOKEN: console::CVar<String> = console::CVar {
 or relogin to this account."#,
     mutable: false,
     serializable: true,
+    range: None,
     default: &|| "".to_owned(),
 };
 
@@ -52,6 +67,29 @@ pub const AUTH_CLIENT_TOKEN: console::CVar<String> = console::CVar {
 Used to identify this client vs others."#,
     mutable: false,
     serializable: true,
+    range: None,
+    default: &|| "".to_owned(),
+};
+
+pub const AUTH_ACCOUNT_TYPE: console::CVar<String> = console::CVar {
+    ty: PhantomData,
+    name: "auth_account_type",
+    description: r#"auth_account_type is the kind of account to log in with, "mojang" or
+"microsoft"."#,
+    mutable: false,
+    serializable: true,
+    range: None,
+    default: &|| "mojang".to_owned(),
+};
+
+pub const AUTH_MICROSOFT_CLIENT_ID: console::CVar<String> = console::CVar {
+    ty: PhantomData,
+    name: "auth_microsoft_client_id",
+    description: r#"auth_microsoft_client_id is the id of the Azure application used to
+log in with Microsoft accounts, which has to allow public client flows."#,
+    mutable: true,
+    serializable: true,
+    range: None,
     default: &|| "".to_owned(),
 };
 
@@ -60,4 +98,141 @@ pub fn register_vars(vars: &mut console::Vars) {
     vars.register(CL_UUID);
     vars.register(AUTH_TOKEN);
     vars.register(AUTH_CLIENT_TOKEN);
+    vars.register(AUTH_ACCOUNT_TYPE);
+    vars.register(AUTH_MICROSOFT_CLIENT_ID);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountType {
+    Mojang,
+    Microsoft,
+}
+
+impl AccountType {
+    pub fn name(self) -> &'static str {
+        match self {
+            AccountType::Mojang => "mojang",
+            AccountType::Microsoft => "microsoft",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AccountType::Mojang => "Mojang",
+            AccountType::Microsoft => "Microsoft",
+        }
+    }
+
+    pub fn next(self) -> AccountType {
+        match self {
+            AccountType::Mojang => AccountType::Microsoft,
+            AccountType::Microsoft => AccountType::Mojang,
+        }
+    }
+}
+
+pub fn account_type(vars: &console::Vars) -> AccountType {
+    match vars.get(AUTH_ACCOUNT_TYPE).as_str() {
+        "microsoft" => AccountType::Microsoft,
+        _ => AccountType::Mojang,
+    }
+}
+
+/// The Microsoft account logged in with last, kept as `microsoft_account.json` in the
+/// config directory so it can be refreshed without signing in again.
+pub fn microsoft_account_path() -> PathBuf {
+    paths::get_config_dir().join("microsoft_account.json")
+}
+
+pub fn load_microsoft_account() -> Option<microsoft::Account> {
+    match load_microsoft_account_from(&microsoft_account_path()) {
+        Ok(account) => account,
+        Err(err) => {
+            warn!("Failed to load the Microsoft account: {}", err);
+            None
+        }
+    }
+}
+
+pub fn load_microsoft_account_from(path: &Path) -> io::Result<Option<microsoft::Account>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let info: Value = serde_json::from_reader(io::BufReader::new(file))?;
+    let field = |key: &str| {
+        info.get(key)
+            .and_then(Value::as_str)
+            .map(|v| v.to_owned())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid account"))
+    };
+    Ok(Some(microsoft::Account {
+        profile: mojang::Profile {
+            username: field("username")?,
+            id: field("id")?,
+            access_token: field("access_token")?,
+        },
+        refresh_token: field("refresh_token")?,
+        expires_at: info.get("expires_at").and_then(Value::as_u64).unwrap_or(0),
+    }))
+}
+
+pub fn save_microsoft_account(account: &microsoft::Account) {
+    if let Err(err) = save_microsoft_account_to(&microsoft_account_path(), account) {
+        error!("Failed to save the Microsoft account: {}", err);
+    }
+}
+
+pub fn save_microsoft_account_to(path: &Path, account: &microsoft::Account) -> io::Result<()> {
+    let info = json!({
+        "username": account.profile.username,
+        "id": account.profile.id,
+        "access_token": account.profile.access_token,
+        "refresh_token": account.refresh_token,
+        "expires_at": account.expires_at,
+    });
+    // Only the user may read it, as the refresh token logs in to their account
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options.open(path)?;
+    // Files saved before keep their permissions otherwise
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    serde_json::to_writer_pretty(file, &info)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn microsoft_account_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "leafish-microsoft-account-test-{}.json",
+            std::process::id()
+        ));
+        let account = microsoft::Account {
+            profile: mojang::Profile {
+                username: "Steve".to_owned(),
+                id: "b50ad385829d3141a2167e7d7539ba7f".to_owned(),
+                access_token: "access".to_owned(),
+            },
+            refresh_token: "refresh".to_owned(),
+            expires_at: 1_600_000_000,
+        };
+        save_microsoft_account_to(&path, &account).unwrap();
+        #[cfg(unix)]
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        let loaded = load_microsoft_account_from(&path).unwrap().unwrap();
+        assert_eq!(loaded.refresh_token, "refresh");
+        assert_eq!(loaded.profile.username, "Steve");
+        fs::remove_file(&path).unwrap();
+    }
 }
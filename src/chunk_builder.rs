@@ -1,3 +1,4 @@
+use crate::console;
 use crate::model;
 use crate::render;
 use crate::resources;
@@ -7,17 +8,48 @@ use crate::world;
 use crate::world::{block, CPos, ComposedSection, World};
 use crossbeam_channel::unbounded;
 use crossbeam_channel::{Receiver, Sender};
+use instant::{Duration, Instant};
+use log::{info, warn};
 use parking_lot::RwLock;
 use rand::{self, Rng, SeedableRng};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::thread;
 
 const NUM_WORKERS: usize = 8;
+/// How many of the latest built sections the recent meshing time is averaged over.
+const RECENT_MESH_TIMES: usize = 100;
+
+pub const CHUNK_INFO: console::Command = console::Command {
+    name: "chunkinfo",
+    description: "prints how many chunks are loaded and how building their meshes is going",
+    run: chunk_info,
+};
+
+fn chunk_info(game: &mut crate::Game, _args: &[&str]) {
+    let server = match game.server.as_ref() {
+        Some(server) => server,
+        None => {
+            warn!("No chunks are loaded outside of a server");
+            return;
+        }
+    };
+    for line in game.chunk_builder.stats(&server.world).summary() {
+        info!("{}", line);
+    }
+}
+
+pub fn register_commands(vars: &mut console::Vars) {
+    vars.register_command(CHUNK_INFO);
+}
 
 pub struct ChunkBuilder {
-    threads: Vec<(Sender<BuildReq>, thread::JoinHandle<()>)>,
-    free_builders: Vec<(usize, Vec<u8>, Vec<u8>)>,
-    built_recv: Receiver<(usize, BuildReply)>,
+    pool: WorkerPool<BuildReq, BuildReply>,
+    /// Reusable vertex buffers, one pair per section that can be in flight at once.
+    free_buffers: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Dirty sections within the render distance the last time sections were handed out.
+    pending_sections: usize,
+    mesh_times: MeshTimes,
 
     models: Arc<RwLock<model::Factory>>,
     resource_version: usize,
@@ -30,24 +62,15 @@ impl ChunkBuilder {
     ) -> Self {
         let models = Arc::new(RwLock::new(model::Factory::new(resources, textures)));
 
-        let mut threads = vec![];
-        let mut free = vec![];
-        let (built_send, built_recv) = unbounded();
-        for i in 0..NUM_WORKERS {
-            let built_send = built_send.clone();
-            let (work_send, work_recv) = unbounded();
-            let models = models.clone();
-            let id = i;
-            threads.push((
-                work_send,
-                thread::spawn(move || build_func_threaded(id, models, work_recv, built_send)),
-            ));
-            free.push((i, vec![], vec![]));
-        }
+        let build_models = models.clone();
+        let pool = WorkerPool::new(NUM_WORKERS, move |work| {
+            build_func_1(build_models.clone(), work)
+        });
         ChunkBuilder {
-            threads,
-            free_builders: free,
-            built_recv,
+            pool,
+            free_buffers: (0..NUM_WORKERS).map(|_| (vec![], vec![])).collect(),
+            pending_sections: 0,
+            mesh_times: MeshTimes::default(),
             models,
             resource_version: 0xFFFF,
         }
@@ -58,6 +81,7 @@ impl ChunkBuilder {
         world: Arc<World>,
         renderer: Arc<RwLock<render::Renderer>>,
         version: usize,
+        render_distance: i32,
     ) {
         if version != self.resource_version {
             self.resource_version = version;
@@ -65,8 +89,9 @@ impl ChunkBuilder {
         }
 
         let mut renderer = renderer.write();
-        while let Ok((id, mut val)) = self.built_recv.try_recv() {
+        while let Some(mut val) = self.pool.try_recv() {
             world.clone().reset_building_flag(val.position);
+            self.mesh_times.add(val.build_time);
 
             let world = world.clone();
             let chunks = world.chunks.clone();
@@ -92,53 +117,204 @@ impl ChunkBuilder {
 
             val.solid_buffer.clear();
             val.trans_buffer.clear();
-            self.free_builders
-                .push((id, val.solid_buffer, val.trans_buffer));
+            self.free_buffers.push((val.solid_buffer, val.trans_buffer));
         }
-        if self.free_builders.is_empty() {
+        if self.free_buffers.is_empty() {
             return;
         }
+        // Sections beyond the render distance are about to be unloaded, don't bother building them
+        let center = CPos::from_block(renderer.camera.pos.x, renderer.camera.pos.z);
         let tmp_world = world.clone();
-        let dirty_sections = tmp_world
+        let mut dirty_sections = tmp_world
             .get_render_list()
             .iter()
             .map(|v| v.0)
+            .filter(|v| (v.0 - center.0).abs().max((v.2 - center.1).abs()) <= render_distance)
             .filter(|v| tmp_world.is_section_dirty(*v))
             .collect::<Vec<_>>();
+        // The queue is collected again every tick, so it follows the camera as it moves
+        sort_closest_first(&mut dirty_sections, renderer.camera.pos);
+        self.pending_sections = dirty_sections.len();
         for (x, y, z) in dirty_sections {
             tmp_world.set_building_flag((x, y, z));
-            let t_id = self.free_builders.pop().unwrap();
-
-            self.threads[t_id.0]
-                .0
-                .send(BuildReq {
-                    world: world.clone(),
-                    position: (x, y, z),
-                    solid_buffer: t_id.1,
-                    trans_buffer: t_id.2,
-                })
-                .unwrap();
-            if self.free_builders.is_empty() {
+            let (solid_buffer, trans_buffer) = self.free_buffers.pop().unwrap();
+
+            self.pool.submit(BuildReq {
+                world: world.clone(),
+                position: (x, y, z),
+                solid_buffer,
+                trans_buffer,
+            });
+            if self.free_buffers.is_empty() {
                 return;
             }
         }
     }
 
+    /// See `model::Factory::particle_texture`.
+    pub fn particle_texture(&self, block: block::Block) -> Option<render::Texture> {
+        self.models.read().particle_texture(block)
+    }
+
+    pub fn stats(&self, world: &World) -> ChunkStats {
+        ChunkStats {
+            loaded_chunks: world.chunks.len(),
+            pending_sections: self.pending_sections,
+            building_sections: NUM_WORKERS - self.free_buffers.len(),
+            mesh_memory: world
+                .get_render_list()
+                .iter()
+                .map(|(_, buffer)| buffer.read().memory_size())
+                .sum(),
+            meshed_sections: self.mesh_times.count,
+            average_mesh_time: self.mesh_times.average(),
+            recent_mesh_time: self.mesh_times.recent_average(),
+        }
+    }
+
     pub fn reset(&mut self) {
         // TODO: Find a safer solution!
         // Drain the built chunk data
-        loop {
-            let curr_data = self.built_recv.try_recv();
-            if curr_data.is_err() {
-                return;
-            }
-            let (id, mut val) = curr_data.unwrap();
+        while let Some(mut val) = self.pool.try_recv() {
             val.solid_buffer.clear();
             val.trans_buffer.clear();
-            self.free_builders
-                .push((id, val.solid_buffer, val.trans_buffer));
+            self.free_buffers.push((val.solid_buffer, val.trans_buffer));
+        }
+    }
+}
+
+/// How long building the sections' meshes took.
+#[derive(Default)]
+struct MeshTimes {
+    total: Duration,
+    count: u64,
+    recent: VecDeque<Duration>,
+}
+
+impl MeshTimes {
+    fn add(&mut self, time: Duration) {
+        self.total += time;
+        self.count += 1;
+        if self.recent.len() == RECENT_MESH_TIMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(time);
+    }
+
+    fn average(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::default();
+        }
+        Duration::from_secs_f64(self.total.as_secs_f64() / self.count as f64)
+    }
+
+    fn recent_average(&self) -> Duration {
+        if self.recent.is_empty() {
+            return Duration::default();
+        }
+        self.recent.iter().sum::<Duration>() / self.recent.len() as u32
+    }
+}
+
+/// A snapshot of the loaded chunks and how building their meshes is going, printed by the
+/// `chunkinfo` command.
+pub struct ChunkStats {
+    pub loaded_chunks: usize,
+    pub pending_sections: usize,
+    pub building_sections: usize,
+    /// The size of the vertex buffers uploaded for the sections being rendered, in bytes.
+    pub mesh_memory: usize,
+    pub meshed_sections: u64,
+    pub average_mesh_time: Duration,
+    /// Averaged over the latest `RECENT_MESH_TIMES` sections only.
+    pub recent_mesh_time: Duration,
+}
+
+impl ChunkStats {
+    /// The lines printed to the console.
+    pub fn summary(&self) -> Vec<String> {
+        vec![
+            format!("Loaded chunks: {}", self.loaded_chunks),
+            format!(
+                "Build queue: {} pending, {} building",
+                self.pending_sections, self.building_sections
+            ),
+            format!(
+                "Mesh memory: {:.2} MiB",
+                self.mesh_memory as f64 / (1024.0 * 1024.0)
+            ),
+            format!(
+                "Meshing time: {:.2} ms average over {} sections, {:.2} ms recently",
+                self.average_mesh_time.as_secs_f64() * 1000.0,
+                self.meshed_sections,
+                self.recent_mesh_time.as_secs_f64() * 1000.0
+            ),
+        ]
+    }
+}
+
+/// Orders section positions by the distance of their centre to `camera`, closest first.
+/// Ties are broken by position so the order doesn't depend on the order of the input.
+fn sort_closest_first(sections: &mut [(i32, i32, i32)], camera: cgmath::Point3<f64>) {
+    let distance = |&(x, y, z): &(i32, i32, i32)| {
+        let dx = x as f64 * 16.0 + 8.0 - camera.x;
+        let dy = y as f64 * 16.0 + 8.0 - camera.y;
+        let dz = z as f64 * 16.0 + 8.0 - camera.z;
+        dx * dx + dy * dy + dz * dz
+    };
+    sections.sort_by(|a, b| {
+        distance(a)
+            .partial_cmp(&distance(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.cmp(b))
+    });
+}
+
+/// A fixed set of threads running the same function on the jobs submitted to it. Jobs are
+/// started in the order they were submitted, whichever worker is free picks up the next one.
+struct WorkerPool<Req, Reply> {
+    work_send: Sender<Req>,
+    done_recv: Receiver<Reply>,
+    _threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl<Req: Send + 'static, Reply: Send + 'static> WorkerPool<Req, Reply> {
+    fn new<F>(workers: usize, func: F) -> Self
+    where
+        F: Fn(Req) -> Reply + Clone + Send + 'static,
+    {
+        let (work_send, work_recv) = unbounded::<Req>();
+        let (done_send, done_recv) = unbounded();
+        let threads = (0..workers)
+            .map(|_| {
+                let work_recv = work_recv.clone();
+                let done_send = done_send.clone();
+                let func = func.clone();
+                // Stops once the pool, and so the sending half of the work queue, is dropped
+                thread::spawn(move || {
+                    while let Ok(work) = work_recv.recv() {
+                        if done_send.send(func(work)).is_err() {
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect();
+        WorkerPool {
+            work_send,
+            done_recv,
+            _threads: threads,
         }
     }
+
+    fn submit(&self, work: Req) {
+        self.work_send.send(work).unwrap();
+    }
+
+    /// Returns a finished job's result without waiting for one.
+    fn try_recv(&self) -> Option<Reply> {
+        self.done_recv.try_recv().ok()
+    }
 }
 
 struct BuildReq {
@@ -155,24 +331,7 @@ struct BuildReply {
     trans_buffer: Vec<u8>,
     trans_count: usize,
     cull_info: CullInfo,
-}
-
-fn build_func_threaded(
-    id: usize,
-    models: Arc<RwLock<model::Factory>>,
-    work_recv: Receiver<BuildReq>,
-    built_send: Sender<(usize, BuildReply)>,
-) {
-    loop {
-        let work: BuildReq = match work_recv.recv() {
-            Ok(val) => val,
-            Err(_) => return,
-        };
-
-        let reply = build_func_1(models.clone(), work);
-
-        built_send.send((id, reply)).unwrap();
-    }
+    build_time: Duration,
 }
 
 fn build_func_1(models: Arc<RwLock<model::Factory>>, work: BuildReq) -> BuildReply {
@@ -182,6 +341,7 @@ fn build_func_1(models: Arc<RwLock<model::Factory>>, work: BuildReq) -> BuildRep
         mut solid_buffer,
         mut trans_buffer,
     } = work;
+    let start = Instant::now();
     let snapshot = ComposedSection::new(world, position.0, position.2, position.1, 2);
 
     let mut rng = rand_pcg::Pcg32::from_seed([
@@ -285,6 +445,7 @@ fn build_func_1(models: Arc<RwLock<model::Factory>>, work: BuildReq) -> BuildRep
         trans_buffer,
         trans_count,
         cull_info,
+        build_time: start.elapsed(),
     }
 }
 
@@ -390,3 +551,89 @@ impl CullInfo {
         self.0 |= 1 << (from.index() * 6 + to.index());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    #[test]
+    fn worker_pool_completes_all_jobs() {
+        const SECTIONS: i32 = 512;
+        // Stands in for meshing: walks every block of a synthetic section
+        let pool = WorkerPool::new(NUM_WORKERS, |position: (i32, i32, i32)| {
+            let mut solid = 0u32;
+            for i in 0..16 * 16 * 16 {
+                if (i ^ position.0 ^ position.2) % 3 == 0 {
+                    solid += 1;
+                }
+            }
+            (position, solid)
+        });
+        for i in 0..SECTIONS {
+            pool.submit((i % 32, i / 256, i / 32 % 8));
+        }
+
+        let mut done = HashSet::new();
+        while done.len() < SECTIONS as usize {
+            let (position, solid) = pool
+                .done_recv
+                .recv_timeout(Duration::from_secs(10))
+                .expect("worker pool stalled");
+            assert!(solid > 0);
+            assert!(done.insert(position), "{:?} built twice", position);
+        }
+        assert!(pool.try_recv().is_none());
+    }
+
+    #[test]
+    fn closest_section_is_built_first() {
+        let mut pending = vec![
+            (-3, 4, 0),
+            (5, 4, 5),
+            (1, 4, 0),
+            (0, 0, 0),
+            (1, 5, 1),
+            (0, 4, 1),
+        ];
+        // Standing in section (1, 4, 0)
+        sort_closest_first(&mut pending, cgmath::Point3::new(20.0, 70.0, 3.0));
+        assert_eq!(pending[0], (1, 4, 0));
+        assert_eq!(pending[1], (0, 4, 1));
+        assert_eq!(pending.last(), Some(&(5, 4, 5)));
+
+        // Flying over to the other side reverses the order
+        sort_closest_first(&mut pending, cgmath::Point3::new(90.0, 70.0, 90.0));
+        assert_eq!(pending[0], (5, 4, 5));
+        assert_eq!(pending.last(), Some(&(-3, 4, 0)));
+    }
+
+    #[test]
+    fn chunk_stats_summary() {
+        let mut times = MeshTimes::default();
+        for ms in 1..=4 {
+            times.add(Duration::from_millis(ms));
+        }
+        assert_eq!(times.average(), Duration::from_micros(2500));
+
+        let stats = ChunkStats {
+            loaded_chunks: 441,
+            pending_sections: 37,
+            building_sections: 8,
+            mesh_memory: 3 * 1024 * 1024 + 512 * 1024,
+            meshed_sections: times.count,
+            average_mesh_time: times.average(),
+            recent_mesh_time: Duration::from_micros(1250),
+        };
+        assert_eq!(
+            stats.summary(),
+            vec![
+                "Loaded chunks: 441",
+                "Build queue: 37 pending, 8 building",
+                "Mesh memory: 3.50 MiB",
+                "Meshing time: 2.50 ms average over 4 sections, 1.25 ms recently",
+            ]
+        );
+    }
+}
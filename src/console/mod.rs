@@ -15,19 +15,27 @@
 use crate::paths;
 
 use std::any::Any;
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::fs;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::Write;
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use crate::format::{Color, Component, TextComponent};
 use crate::render;
 use crate::ui;
+use instant::{Duration, Instant};
+use log::{info, warn};
 use parking_lot::Mutex;
 use parking_lot::RwLock;
 
+/// How often the config file is checked for changes.
+const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 const FILTERED_CRATES: &[&str] = &[
     //"reqwest", // TODO: needed?
     "mime",
@@ -39,6 +47,8 @@ pub struct CVar<T: Sized + Any + 'static> {
     pub description: &'static str,
     pub mutable: bool,
     pub serializable: bool,
+    /// The smallest and largest value allowed, only checked for numbers.
+    pub range: Option<(T, T)>,
     pub default: &'static dyn Fn() -> T,
 }
 
@@ -48,6 +58,7 @@ pub const LOG_LEVEL_TERM: CVar<String> = CVar {
     description: "log level of messages to log to the terminal",
     mutable: false,
     serializable: true,
+    range: None,
     default: &|| "info".to_owned(),
 };
 
@@ -57,12 +68,100 @@ pub const LOG_LEVEL_FILE: CVar<String> = CVar {
     description: "log level of messages to log to the log file",
     mutable: false,
     serializable: true,
+    range: None,
     default: &|| "trace".to_owned(),
 };
 
+/// What lines typed into the chat box start with to run one of the client's commands,
+/// lines starting with a `/` are the server's commands.
+pub const COMMAND_PREFIX: char = '.';
+
+/// An action run from the console instead of a value being set, typed into the chat box
+/// prefixed with `COMMAND_PREFIX`.
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run: fn(&mut crate::Game, &[&str]),
+}
+
+pub const HELP: Command = Command {
+    name: "help",
+    description: "lists the commands that can be run",
+    run: help,
+};
+
+pub const SET: Command = Command {
+    name: "set",
+    description: "sets a var to the given value, e.g. r_render_distance 12",
+    run: set,
+};
+
+fn set(game: &mut crate::Game, args: &[&str]) {
+    match run_set(&game.vars, args) {
+        Ok(()) => info!("Set {}", args.join(" ")),
+        Err(err) => warn!("{}", err),
+    }
+}
+
+/// Sets the var named by the `set` command's first argument to the rest, returns why it
+/// was refused.
+fn run_set(vars: &Vars, args: &[&str]) -> Result<(), String> {
+    match args {
+        [name, value @ ..] if !value.is_empty() => vars.set_from_str(name, &value.join(" ")),
+        _ => Err(format!("Usage: {}set <var> <value>", COMMAND_PREFIX)),
+    }
+}
+
+fn help(game: &mut crate::Game, _args: &[&str]) {
+    let mut commands = game.vars.commands.values().collect::<Vec<_>>();
+    commands.sort_by_key(|command| command.name);
+    for command in commands {
+        info!(
+            "{}{} - {}",
+            COMMAND_PREFIX, command.name, command.description
+        );
+    }
+}
+
+pub const CONFIG_HOT_RELOAD: CVar<bool> = CVar {
+    ty: PhantomData,
+    name: "config_hot_reload",
+    description: "Apply changes made to this file while the client is running",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| true,
+};
+
 pub fn register_vars(vars: &mut Vars) {
     vars.register(LOG_LEVEL_TERM);
     vars.register(LOG_LEVEL_FILE);
+    vars.register(CONFIG_HOT_RELOAD);
+    vars.register_command(HELP);
+    vars.register_command(SET);
+}
+
+/// Runs `line` if it starts with a registered command, returns whether it did. Other lines
+/// are left to be sent to the server.
+pub fn run_command(game: &mut crate::Game, line: &str) -> bool {
+    let (name, args) = match split_command(line) {
+        Some(command) => command,
+        None => return false,
+    };
+    let command = match game.vars.command(name) {
+        Some(command) => command,
+        None => return false,
+    };
+    (command.run)(game, &args);
+    true
+}
+
+/// The name and arguments of the client command `line` runs, if it's one.
+fn split_command(line: &str) -> Option<(&str, Vec<&str>)> {
+    let mut args = line.strip_prefix(COMMAND_PREFIX)?.split_whitespace();
+    let name = args.next()?;
+    Some((name, args.collect()))
 }
 
 fn log_level_from_str(s: &str, default: log::Level) -> log::Level {
@@ -83,8 +182,19 @@ impl Var for CVar<i64> {
         val.downcast_ref::<i64>().unwrap().to_string()
     }
 
-    fn deserialize(&self, input: &str) -> Box<dyn Any> {
-        Box::new(input.parse::<i64>().unwrap())
+    fn deserialize(&self, input: &str) -> Option<Box<dyn Any>> {
+        Some(Box::new(input.parse::<i64>().ok()?))
+    }
+
+    fn validate(&self, val: &Box<dyn Any>) -> Result<(), String> {
+        let val = *val.downcast_ref::<i64>().unwrap();
+        match self.range {
+            Some((min, max)) if val < min || val > max => Err(format!(
+                "{} must be between {} and {}, not {}",
+                self.name, min, max, val
+            )),
+            _ => Ok(()),
+        }
     }
 
     fn description(&self) -> &'static str {
@@ -94,6 +204,10 @@ impl Var for CVar<i64> {
     fn can_serialize(&self) -> bool {
         self.serializable
     }
+
+    fn is_mutable(&self) -> bool {
+        self.mutable
+    }
 }
 
 impl Var for CVar<bool> {
@@ -101,8 +215,8 @@ impl Var for CVar<bool> {
         val.downcast_ref::<bool>().unwrap().to_string()
     }
 
-    fn deserialize(&self, input: &str) -> Box<dyn Any> {
-        Box::new(input.parse::<bool>().unwrap())
+    fn deserialize(&self, input: &str) -> Option<Box<dyn Any>> {
+        Some(Box::new(input.parse::<bool>().ok()?))
     }
 
     fn description(&self) -> &'static str {
@@ -112,6 +226,10 @@ impl Var for CVar<bool> {
     fn can_serialize(&self) -> bool {
         self.serializable
     }
+
+    fn is_mutable(&self) -> bool {
+        self.mutable
+    }
 }
 
 impl Var for CVar<String> {
@@ -119,8 +237,9 @@ impl Var for CVar<String> {
         format!("\"{}\"", val.downcast_ref::<String>().unwrap())
     }
 
-    fn deserialize(&self, input: &str) -> Box<dyn Any> {
-        Box::new((&input[1..input.len() - 1]).to_owned())
+    fn deserialize(&self, input: &str) -> Option<Box<dyn Any>> {
+        let input = input.strip_prefix('"')?.strip_suffix('"')?;
+        Some(Box::new(input.to_owned()))
     }
 
     fn description(&self) -> &'static str {
@@ -129,13 +248,22 @@ impl Var for CVar<String> {
     fn can_serialize(&self) -> bool {
         self.serializable
     }
+
+    fn is_mutable(&self) -> bool {
+        self.mutable
+    }
 }
 
 pub trait Var {
     fn serialize(&self, val: &Box<dyn Any>) -> String;
-    fn deserialize(&self, input: &str) -> Box<dyn Any>;
+    fn deserialize(&self, input: &str) -> Option<Box<dyn Any>>;
+    /// Checks a value before it is set, returns why it was refused.
+    fn validate(&self, _val: &Box<dyn Any>) -> Result<(), String> {
+        Ok(())
+    }
     fn description(&self) -> &'static str;
     fn can_serialize(&self) -> bool;
+    fn is_mutable(&self) -> bool;
 }
 
 #[derive(Default)]
@@ -143,6 +271,13 @@ pub struct Vars {
     names: HashMap<String, &'static str>,
     vars: HashMap<&'static str, Box<dyn Var>>,
     var_values: HashMap<&'static str, RefCell<Box<dyn Any>>>,
+    commands: HashMap<&'static str, Command>,
+    /// The config as the client last read or wrote it.
+    config: RefCell<String>,
+    config_modified: Cell<Option<SystemTime>>,
+    config_checked: Cell<Option<Instant>>,
+    /// How many times vars were set or reloaded, see `changes`.
+    changes: Cell<u64>,
 }
 
 impl Vars {
@@ -163,6 +298,17 @@ impl Vars {
         self.vars.insert(var.name, Box::new(var));
     }
 
+    pub fn register_command(&mut self, command: Command) {
+        if self.commands.contains_key(command.name) || self.vars.contains_key(command.name) {
+            panic!("Key registered twice {}", command.name);
+        }
+        self.commands.insert(command.name, command);
+    }
+
+    pub fn command(&self, name: &str) -> Option<Command> {
+        self.commands.get(name).copied()
+    }
+
     pub fn get<T: Sized + Any>(&self, var: CVar<T>) -> Ref<T>
     where
         CVar<T>: Var,
@@ -172,59 +318,182 @@ impl Vars {
         Ref::map(var, |v| v.downcast_ref::<T>().unwrap())
     }
 
+    /// Sets the value unless the var refuses it, which is logged.
     pub fn set<T: Sized + Any>(&self, var: CVar<T>, val: T)
     where
         CVar<T>: Var,
     {
-        *self.var_values.get(var.name).unwrap().borrow_mut() = Box::new(val);
+        if let Err(err) = self.try_set(var, val) {
+            warn!("{}", err);
+        }
+    }
+
+    /// Sets the value, returns why it was refused if it's outside of the var's range.
+    pub fn try_set<T: Sized + Any>(&self, var: CVar<T>, val: T) -> Result<(), String>
+    where
+        CVar<T>: Var,
+    {
+        let val: Box<dyn Any> = Box::new(val);
+        var.validate(&val)?;
+        self.store(var.name, val);
+        Ok(())
+    }
+
+    /// Sets the var named `name` to the value `input` parses to, like it's written in the
+    /// config. Returns why it was refused, if it was.
+    pub fn set_from_str(&self, name: &str, input: &str) -> Result<(), String> {
+        let name = *self
+            .names
+            .get(name)
+            .ok_or_else(|| format!("Unknown var {}", name))?;
+        let var = self.vars.get(name).unwrap();
+        if !var.is_mutable() {
+            return Err(format!("{} can't be changed while running", name));
+        }
+        let val = var
+            .deserialize(input)
+            .ok_or_else(|| format!("Invalid value {} for {}", input, name))?;
+        var.validate(&val)?;
+        self.store(name, val);
+        Ok(())
+    }
+
+    fn store(&self, name: &'static str, val: Box<dyn Any>) {
+        *self.var_values.get(name).unwrap().borrow_mut() = val;
+        self.changes.set(self.changes.get() + 1);
         self.save_config();
     }
 
+    /// Counts the vars set so far, including the ones reloaded from the config. Comparing
+    /// it with an earlier count tells whether anything needs applying again.
+    pub fn changes(&self) -> u64 {
+        self.changes.get()
+    }
+
     pub fn load_config(&mut self) {
-        if let Ok(file) = fs::File::open(paths::get_config_dir().join("conf.cfg")) {
-            let reader = BufReader::new(file);
-            for line in reader.lines() {
-                let line = line.unwrap();
-                if line.starts_with('#') || line.is_empty() {
-                    continue;
-                }
-                let parts = line
-                    .splitn(2, ' ')
-                    .map(|v| v.to_owned())
-                    .collect::<Vec<String>>();
-                let (name, arg) = (&parts[0], &parts[1]);
-                if let Some(var_name) = self.names.get(name) {
-                    let var = self.vars.get(var_name).unwrap();
-                    let val = var.deserialize(arg);
-                    if var.can_serialize() {
-                        self.var_values.insert(var_name, RefCell::new(val));
-                    }
-                }
-            }
+        let path = config_path();
+        for (name, val) in self.read_config(&path) {
+            self.var_values.insert(name, RefCell::new(val));
         }
     }
 
     pub fn save_config(&self) {
-        let mut file =
-            BufWriter::new(fs::File::create(paths::get_config_dir().join("conf.cfg")).unwrap());
+        self.save_config_to(&config_path());
+    }
+
+    fn save_config_to(&self, path: &Path) {
+        let mut config = String::new();
         for (name, var) in &self.vars {
             if !var.can_serialize() {
                 continue;
             }
             for line in var.description().lines() {
-                writeln!(file, "# {}", line).unwrap();
+                writeln!(config, "# {}", line).unwrap();
             }
             write!(
-                file,
+                config,
                 "{} {}\n\n",
                 name,
                 var.serialize(&self.var_values.get(name).unwrap().borrow())
             )
             .unwrap();
         }
+        fs::write(path, &config).unwrap();
+        self.config_modified.set(modified_time(path));
+        *self.config.borrow_mut() = config;
+    }
+
+    /// Reloads the config once it was changed on disk by something other than the client,
+    /// with `CONFIG_HOT_RELOAD` enabled.
+    pub fn reload_config_if_modified(&self) {
+        if !*self.get(CONFIG_HOT_RELOAD) {
+            return;
+        }
+        let now = Instant::now();
+        match self.config_checked.get() {
+            Some(checked) if now.duration_since(checked) < CONFIG_CHECK_INTERVAL => return,
+            _ => self.config_checked.set(Some(now)),
+        }
+        let path = config_path();
+        let modified = modified_time(&path);
+        if modified != self.config_modified.get() {
+            self.config_modified.set(modified);
+            self.reload_config_from(&path);
+        }
+    }
+
+    /// Applies the vars that changed in the config, returns their names.
+    fn reload_config_from(&self, path: &Path) -> Vec<&'static str> {
+        let mut changed = vec![];
+        for (name, val) in self.read_config(path) {
+            let var = self.vars.get(name).unwrap();
+            let mut current = self.var_values.get(name).unwrap().borrow_mut();
+            let old = var.serialize(&current);
+            let new = var.serialize(&val);
+            if old != new && !var.is_mutable() {
+                // Like the set command, only a restart changes them
+                warn!(
+                    "Not reloading {} from the config, it can't be changed while running",
+                    name
+                );
+            } else if old != new {
+                info!("Reloaded {} from the config: {} -> {}", name, old, new);
+                *current = val;
+                self.changes.set(self.changes.get() + 1);
+                changed.push(name);
+            }
+        }
+        changed
+    }
+
+    /// Parses the serializable vars in the config, skipping values that can't be set. Nothing
+    /// is returned if the config is what the client last read or wrote, so the client's own
+    /// saves aren't reloaded.
+    fn read_config(&self, path: &Path) -> Vec<(&'static str, Box<dyn Any>)> {
+        let config = match fs::read_to_string(path) {
+            Ok(config) => config,
+            Err(_) => return vec![],
+        };
+        if config == *self.config.borrow() {
+            return vec![];
+        }
+        let mut values = vec![];
+        for line in config.lines() {
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, ' ');
+            let (name, arg) = (parts.next().unwrap(), parts.next().unwrap_or(""));
+            if let Some(&var_name) = self.names.get(name) {
+                let var = self.vars.get(var_name).unwrap();
+                if !var.can_serialize() {
+                    continue;
+                }
+                match var
+                    .deserialize(arg)
+                    .map(|val| var.validate(&val).map(|_| val))
+                {
+                    Some(Ok(val)) => values.push((var_name, val)),
+                    // Keeps the current value
+                    Some(Err(err)) => warn!("Ignoring {} from the config: {}", name, err),
+                    None => warn!("Ignoring {} from the config: invalid value {}", name, arg),
+                }
+            }
+        }
+        *self.config.borrow_mut() = config;
+        self.config_modified.set(modified_time(path));
+        values
     }
 }
 
+fn config_path() -> PathBuf {
+    paths::get_config_dir().join("conf.cfg")
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
 pub struct Console {
     history: Vec<Component>,
     dirty: bool,
@@ -305,7 +574,7 @@ impl Console {
         }
 
         let w = match ui_container.mode {
-            ui::Mode::Scaled => width,
+            ui::Mode::Scaled => width / ui_container.scale,
             ui::Mode::Unscaled(scale) => 854.0 / scale,
         };
         if self.elements.is_none() {
@@ -442,3 +711,92 @@ impl log::Log for ConsoleProxy {
 
 unsafe impl Send for ConsoleProxy {}
 unsafe impl Sync for ConsoleProxy {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_VOLUME: CVar<i64> = CVar {
+        ty: PhantomData,
+        name: "test_volume",
+        description: "",
+        mutable: true,
+        serializable: true,
+        range: Some((0, 100)),
+        default: &|| 50,
+    };
+
+    #[test]
+    fn server_commands_are_not_run() {
+        assert_eq!(
+            split_command(".connect localhost"),
+            Some(("connect", vec!["localhost"]))
+        );
+        assert_eq!(split_command("/help"), None);
+        assert_eq!(split_command("."), None);
+        assert_eq!(split_command("hello"), None);
+    }
+
+    #[test]
+    fn out_of_range_value_is_refused() {
+        let mut vars = Vars::new();
+        vars.register(TEST_VOLUME);
+        assert!(vars.try_set(TEST_VOLUME, 101).is_err());
+        assert!(vars.try_set(TEST_VOLUME, -1).is_err());
+        assert_eq!(*vars.get(TEST_VOLUME), 50);
+
+        let max: Box<dyn Any> = Box::new(100i64);
+        assert!(TEST_VOLUME.validate(&max).is_ok());
+    }
+
+    #[test]
+    fn refused_set_commands_explain_why() {
+        let mut vars = Vars::new();
+        vars.register(TEST_VOLUME);
+        vars.register(LOG_LEVEL_TERM);
+        assert_eq!(
+            run_set(&vars, &["test_volume", "101"]),
+            Err("test_volume must be between 0 and 100, not 101".to_owned())
+        );
+        assert_eq!(
+            run_set(&vars, &["test_volume", "loud"]),
+            Err("Invalid value loud for test_volume".to_owned())
+        );
+        assert_eq!(
+            run_set(&vars, &["log_level_term", "\"debug\""]),
+            Err("log_level_term can't be changed while running".to_owned())
+        );
+        assert_eq!(
+            run_set(&vars, &["volume", "10"]),
+            Err("Unknown var volume".to_owned())
+        );
+        assert!(run_set(&vars, &["test_volume"]).is_err());
+        assert_eq!(*vars.get(TEST_VOLUME), 50);
+        assert_eq!(vars.changes(), 0);
+    }
+
+    #[test]
+    fn external_edit_reloads_changed_var() {
+        let path =
+            std::env::temp_dir().join(format!("leafish-config-test-{}.cfg", std::process::id()));
+        let mut vars = Vars::new();
+        vars.register(TEST_VOLUME);
+        vars.register(LOG_LEVEL_TERM);
+        vars.save_config_to(&path);
+        // The client's own save isn't reloaded
+        assert!(vars.reload_config_from(&path).is_empty());
+        assert_eq!(vars.changes(), 0);
+
+        // Vars that can't be changed while running are left as they are
+        let config = fs::read_to_string(&path)
+            .unwrap()
+            .replace("test_volume 50", "test_volume 75")
+            .replace("log_level_term \"info\"", "log_level_term \"trace\"");
+        fs::write(&path, config).unwrap();
+        assert_eq!(vars.reload_config_from(&path), vec!["test_volume"]);
+        assert_eq!(*vars.get(TEST_VOLUME), 75);
+        assert_eq!(*vars.get(LOG_LEVEL_TERM), "info");
+        assert_eq!(vars.changes(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+pub mod piston;
 pub mod sign;
 
 use crate::ecs;
@@ -6,6 +7,7 @@ use crate::world::block::Block;
 
 pub fn add_systems(m: &mut ecs::Manager) {
     sign::add_systems(m);
+    piston::add_systems(m);
 }
 
 pub enum BlockEntityType {
@@ -0,0 +1,420 @@
+use crate::ecs;
+use crate::entity::GameInfo;
+use crate::render;
+use crate::render::model;
+use crate::shared::{Direction, Position};
+use crate::world;
+use crate::world::block::Block;
+use cgmath::{Matrix4, Vector3};
+
+/// How long pistons take to move, in frame deltas (1/60ths of a second). Two ticks, like vanilla.
+pub const MOVE_TIME: f64 = 6.0;
+/// How long a finished animation waits for the server to replace the moving blocks.
+const SETTLE_TIME: f64 = 60.0;
+/// The most blocks a piston pushes.
+const PUSH_LIMIT: i32 = 12;
+
+pub fn add_systems(m: &mut ecs::Manager) {
+    let sys = PistonRenderer::new(m);
+    m.add_render_system(sys);
+}
+
+/// Starts the animation of the piston at `pos`, the blocks it moves have to still be in
+/// `world` at their old positions.
+pub fn create_entity(
+    m: &mut ecs::Manager,
+    world: &world::World,
+    pos: Position,
+    mut animation: PistonAnimation,
+) -> ecs::Entity {
+    animation.collect_blocks(world, pos);
+    let e = m.create_entity();
+    m.add_component_direct(e, pos);
+    m.add_component_direct(e, animation);
+    e
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PistonAction {
+    Extend,
+    Retract,
+}
+
+/// A piston head moving along with the blocks it pushes or pulls. The server replaces
+/// them with invisible moving pistons until the move is done, so they are drawn here.
+pub struct PistonAnimation {
+    pub action: PistonAction,
+    /// Where the piston's head points.
+    pub facing: Direction,
+    /// Where the head and moved blocks move to.
+    pub direction: Direction,
+    pub sticky: bool,
+    /// Whether a sticky piston pulls the block in front of its head back with it.
+    pub pulls: bool,
+    pub duration: f64,
+    pub time: f64,
+    /// The moved blocks, at their positions before moving.
+    pub blocks: Vec<(Position, Block)>,
+
+    model: Option<model::ModelKey>,
+}
+
+impl PistonAnimation {
+    /// The animation a block action on `block` starts, if it's a piston. `action` is 0 to
+    /// extend, 1 to retract and 2 to retract without pulling a block, `param` the
+    /// direction the piston faces.
+    pub fn from_block_action(block: Block, action: u8, param: u8) -> Option<PistonAnimation> {
+        let sticky = match block {
+            Block::Piston { .. } => false,
+            Block::StickyPiston { .. } => true,
+            _ => return None,
+        };
+        let facing = match param {
+            0 => Direction::Down,
+            1 => Direction::Up,
+            2 => Direction::North,
+            3 => Direction::South,
+            4 => Direction::West,
+            5 => Direction::East,
+            _ => return None,
+        };
+        let (action, direction, pulls) = match action {
+            0 => (PistonAction::Extend, facing, false),
+            1 => (PistonAction::Retract, facing.opposite(), sticky),
+            // Retracting before the extension finished leaves the block behind
+            2 => (PistonAction::Retract, facing.opposite(), false),
+            _ => return None,
+        };
+        Some(PistonAnimation {
+            action,
+            facing,
+            direction,
+            sticky,
+            pulls,
+            duration: MOVE_TIME,
+            time: 0.0,
+            blocks: vec![],
+            model: None,
+        })
+    }
+
+    /// How far the move is done, from 0.0 to 1.0.
+    pub fn progress(&self) -> f64 {
+        (self.time / self.duration).min(1.0)
+    }
+
+    fn collect_blocks(&mut self, world: &world::World, pos: Position) {
+        let movable = |block: Block| {
+            block.get_material().collidable
+                && !matches!(block, Block::Obsidian {} | Block::Bedrock {})
+        };
+        match self.action {
+            PistonAction::Extend => {
+                for distance in 1..=PUSH_LIMIT {
+                    let block_pos = pos.shift_by(self.facing, distance);
+                    let block = world.get_block(block_pos);
+                    if !movable(block) {
+                        break;
+                    }
+                    self.blocks.push((block_pos, block));
+                }
+            }
+            PistonAction::Retract if self.pulls => {
+                let block_pos = pos.shift_by(self.facing, 2);
+                let block = world.get_block(block_pos);
+                if movable(block) {
+                    self.blocks.push((block_pos, block));
+                }
+            }
+            PistonAction::Retract => {}
+        }
+    }
+
+    /// Where the server places moving pistons until the move is done.
+    fn moving_positions(&self, pos: Position) -> Vec<Position> {
+        let mut positions: Vec<Position> = self
+            .blocks
+            .iter()
+            .map(|(block_pos, _)| block_pos.shift(self.direction))
+            .collect();
+        positions.push(match self.action {
+            PistonAction::Extend => pos.shift(self.facing),
+            PistonAction::Retract => pos,
+        });
+        positions
+    }
+}
+
+struct PistonRenderer {
+    filter: ecs::Filter,
+    position: ecs::Key<Position>,
+    animation: ecs::Key<PistonAnimation>,
+    game_info: ecs::Key<GameInfo>,
+}
+
+impl PistonRenderer {
+    fn new(m: &mut ecs::Manager) -> PistonRenderer {
+        let animation = m.get_key();
+        let position = m.get_key();
+        PistonRenderer {
+            filter: ecs::Filter::new().with(position).with(animation),
+            position,
+            animation,
+            game_info: m.get_key(),
+        }
+    }
+}
+
+impl ecs::System for PistonRenderer {
+    fn filter(&self) -> &ecs::Filter {
+        &self.filter
+    }
+
+    fn update(
+        &mut self,
+        m: &mut ecs::Manager,
+        world: &world::World,
+        renderer: &mut render::Renderer,
+        _: bool,
+        _: bool,
+    ) {
+        let world_entity = m.get_world();
+        let delta = m
+            .get_component_mut(world_entity, self.game_info)
+            .unwrap()
+            .delta;
+        for e in m.find(&self.filter) {
+            let position = *m.get_component(e, self.position).unwrap();
+            let animation = m.get_component_mut(e, self.animation).unwrap();
+            animation.time += delta;
+            if animation.time >= animation.duration {
+                let moving = animation
+                    .moving_positions(position)
+                    .into_iter()
+                    .any(|pos| matches!(world.get_block(pos), Block::PistonExtension { .. }));
+                if !moving || animation.time >= animation.duration + SETTLE_TIME {
+                    m.remove_entity(e);
+                    continue;
+                }
+            }
+            if let Some(model) = animation.model {
+                let (ox, oy, oz) = animation.direction.get_offset();
+                let progress = animation.progress() as f32;
+                let mdl = renderer.model.get_model(model).unwrap();
+                mdl.block_light = world.get_block_light(position) as f32;
+                mdl.sky_light = world.get_sky_light(position) as f32;
+                mdl.matrix[0] = Matrix4::from_translation(Vector3::new(
+                    position.x as f32 + ox as f32 * progress,
+                    -position.y as f32 - oy as f32 * progress,
+                    position.z as f32 + oz as f32 * progress,
+                ));
+            }
+        }
+    }
+
+    fn entity_added(
+        &mut self,
+        m: &mut ecs::Manager,
+        e: ecs::Entity,
+        _: &world::World,
+        renderer: &mut render::Renderer,
+    ) {
+        let position = *m.get_component(e, self.position).unwrap();
+        let animation = m.get_component_mut(e, self.animation).unwrap();
+
+        let textures = renderer.get_textures_ref();
+        let texture = |name: &str| {
+            render::Renderer::get_texture(textures, &format!("minecraft:blocks/{}", name))
+        };
+        let top = texture(if animation.sticky {
+            "piston_top_sticky"
+        } else {
+            "piston_top_normal"
+        });
+        let back = texture("piston_top_normal");
+        let side = texture("piston_side");
+        let inner = texture("piston_inner");
+        let bottom = texture("piston_bottom");
+
+        // The head starts inside the base when extending and in front of it when retracting
+        let head_offset = match animation.action {
+            PistonAction::Extend => 0.0,
+            PistonAction::Retract => 1.0,
+        };
+        let mut moving = vec![];
+        append_facing_box(
+            &mut moving,
+            animation.facing,
+            head_offset + 12.0 / 16.0,
+            head_offset + 1.0,
+            1.0,
+            faces(
+                animation.facing,
+                Some(top),
+                Some(back),
+                side.relative(0.0, 0.0, 1.0, 4.0 / 16.0),
+            ),
+        );
+        append_facing_box(
+            &mut moving,
+            animation.facing,
+            head_offset - 4.0 / 16.0,
+            head_offset + 12.0 / 16.0,
+            4.0 / 16.0,
+            faces(
+                animation.facing,
+                None,
+                None,
+                side.relative(0.0, 0.0, 1.0, 4.0 / 16.0),
+            ),
+        );
+        for (block_pos, block) in &animation.blocks {
+            let (plugin, name) = block.get_model();
+            let texture =
+                render::Renderer::get_texture(textures, &format!("{}:blocks/{}", plugin, name));
+            let offset = *block_pos - position;
+            model::append_box(
+                &mut moving,
+                offset.x as f32,
+                offset.y as f32,
+                offset.z as f32,
+                1.0,
+                1.0,
+                1.0,
+                [
+                    Some(texture.clone()),
+                    Some(texture.clone()),
+                    Some(texture.clone()),
+                    Some(texture.clone()),
+                    Some(texture.clone()),
+                    Some(texture),
+                ],
+            );
+        }
+
+        // The base is a moving piston itself while retracting
+        let mut base = vec![];
+        if animation.action == PistonAction::Retract {
+            append_facing_box(
+                &mut base,
+                animation.facing,
+                0.0,
+                12.0 / 16.0,
+                1.0,
+                faces(
+                    animation.facing,
+                    Some(inner),
+                    Some(bottom),
+                    side.relative(0.0, 4.0 / 16.0, 1.0, 12.0 / 16.0),
+                ),
+            );
+        }
+
+        let model = renderer
+            .model
+            .create_model(model::DEFAULT, vec![moving, base]);
+        {
+            let mdl = renderer.model.get_model(model).unwrap();
+            mdl.radius = PUSH_LIMIT as f32 + 2.0;
+            mdl.x = position.x as f32 + 0.5;
+            mdl.y = position.y as f32 + 0.5;
+            mdl.z = position.z as f32 + 0.5;
+            let origin = Matrix4::from_translation(Vector3::new(
+                position.x as f32,
+                -position.y as f32,
+                position.z as f32,
+            ));
+            mdl.matrix[0] = origin;
+            mdl.matrix[1] = origin;
+        }
+        animation.model = Some(model);
+    }
+
+    fn entity_removed(
+        &mut self,
+        m: &mut ecs::Manager,
+        e: ecs::Entity,
+        _: &world::World,
+        renderer: &mut render::Renderer,
+    ) {
+        let animation = m.get_component_mut(e, self.animation).unwrap();
+        if let Some(model) = animation.model {
+            renderer.model.remove_model(model);
+        }
+        animation.model = None;
+    }
+}
+
+/// The textures of a box facing `facing`, in the order `model::append_box` takes them.
+fn faces(
+    facing: Direction,
+    front: Option<render::Texture>,
+    back: Option<render::Texture>,
+    side: render::Texture,
+) -> [Option<render::Texture>; 6] {
+    let mut textures = [
+        Some(side.clone()),
+        Some(side.clone()),
+        Some(side.clone()),
+        Some(side.clone()),
+        Some(side.clone()),
+        Some(side),
+    ];
+    textures[facing.index()] = front;
+    textures[facing.opposite().index()] = back;
+    textures
+}
+
+/// Appends a box reaching from `from` to `to` along `facing`, measured from the back of the
+/// block at the origin, with a square cross-section `size` wide.
+fn append_facing_box(
+    verts: &mut Vec<model::Vertex>,
+    facing: Direction,
+    from: f32,
+    to: f32,
+    size: f32,
+    textures: [Option<render::Texture>; 6],
+) {
+    let (ox, oy, oz) = facing.get_offset();
+    let axis = |offset: i32| match offset {
+        1 => (from, to - from),
+        -1 => (1.0 - to, to - from),
+        _ => ((1.0 - size) / 2.0, size),
+    };
+    let (x, w) = axis(ox);
+    let (y, h) = axis(oy);
+    let (z, d) = axis(oz);
+    model::append_box(verts, x, y, z, w, h, d, textures);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_action_starts_piston_animation() {
+        let piston = Block::StickyPiston {
+            extended: false,
+            facing: Direction::North,
+        };
+        let extend = PistonAnimation::from_block_action(piston, 0, 2).unwrap();
+        assert_eq!(extend.action, PistonAction::Extend);
+        assert_eq!(extend.facing, Direction::North);
+        assert_eq!(extend.direction, Direction::North);
+        assert_eq!(extend.duration, MOVE_TIME);
+        assert_eq!(extend.progress(), 0.0);
+        assert!(extend.sticky);
+        assert!(!extend.pulls);
+
+        let retract = PistonAnimation::from_block_action(piston, 1, 2).unwrap();
+        assert_eq!(retract.action, PistonAction::Retract);
+        assert_eq!(retract.direction, Direction::South);
+        assert!(retract.pulls);
+        let cancelled = PistonAnimation::from_block_action(piston, 2, 2).unwrap();
+        assert_eq!(cancelled.direction, Direction::South);
+        assert!(!cancelled.pulls);
+
+        // Other blocks, like note blocks, use block actions too
+        assert!(PistonAnimation::from_block_action(Block::Bedrock {}, 0, 2).is_none());
+    }
+}
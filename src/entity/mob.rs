@@ -0,0 +1,167 @@
+//! Mobs and other living entities besides players. They aren't modelled yet, only their
+//! custom names are shown.
+
+use super::player::{name_tag_transform, name_tag_verts, NAME_TAG_HEIGHT};
+use super::{
+    Bounds, Interpolation, Light, Position, Rotation, TargetPosition, TargetRotation, Transform,
+};
+use crate::ecs;
+use crate::format;
+use crate::render;
+use crate::render::model;
+use crate::world;
+use cgmath::{Point3, Vector3};
+use collision::Aabb3;
+
+pub fn add_systems(m: &mut ecs::Manager) {
+    let sys = NameTagRenderer::new(m);
+    m.add_render_system(sys);
+}
+
+pub fn create_mob(m: &mut ecs::Manager) -> ecs::Entity {
+    let entity = m.create_entity();
+    m.add_component_direct(entity, Position::new(0.0, 0.0, 0.0));
+    m.add_component_direct(entity, TargetPosition::new(0.0, 0.0, 0.0));
+    m.add_component_direct(entity, Rotation::new(0.0, 0.0));
+    m.add_component_direct(entity, TargetRotation::new(0.0, 0.0));
+    m.add_component_direct(
+        entity,
+        Interpolation::new(Transform {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            yaw: 0.0,
+            pitch: 0.0,
+        }),
+    );
+    // Their real size depends on the type, a player's is close enough for lighting
+    m.add_component_direct(
+        entity,
+        Bounds::new(Aabb3::new(
+            Point3::new(-0.3, 0.0, -0.3),
+            Point3::new(0.3, 1.8, 0.3),
+        )),
+    );
+    m.add_component_direct(entity, NameTag::default());
+    m.add_component_direct(entity, Light::new());
+    entity
+}
+
+/// The custom name floating above a mob, if it was given one.
+#[derive(Default)]
+pub struct NameTag {
+    custom_name: Option<format::Component>,
+    model: Option<model::ModelKey>,
+    dirty: bool,
+}
+
+impl NameTag {
+    pub fn set_custom_name(&mut self, custom_name: Option<format::Component>) {
+        if self.custom_name != custom_name {
+            self.custom_name = custom_name;
+            self.dirty = true;
+        }
+    }
+}
+
+struct NameTagRenderer {
+    filter: ecs::Filter,
+    name_tag: ecs::Key<NameTag>,
+    position: ecs::Key<Position>,
+    light: ecs::Key<Light>,
+}
+
+impl NameTagRenderer {
+    fn new(m: &mut ecs::Manager) -> NameTagRenderer {
+        let name_tag = m.get_key();
+        let position = m.get_key();
+        let light = m.get_key();
+        NameTagRenderer {
+            filter: ecs::Filter::new().with(name_tag).with(position).with(light),
+            name_tag,
+            position,
+            light,
+        }
+    }
+}
+
+impl ecs::System for NameTagRenderer {
+    fn filter(&self) -> &ecs::Filter {
+        &self.filter
+    }
+
+    fn update(
+        &mut self,
+        m: &mut ecs::Manager,
+        world: &world::World,
+        renderer: &mut render::Renderer,
+        _: bool,
+        _: bool,
+    ) {
+        for e in m.find(&self.filter) {
+            if m.get_component(e, self.name_tag).unwrap().dirty {
+                self.entity_removed(m, e, world, renderer);
+                self.entity_added(m, e, world, renderer);
+            }
+            let name_tag = m.get_component(e, self.name_tag).unwrap();
+            let position = m.get_component(e, self.position).unwrap();
+            let light = m.get_component(e, self.light).unwrap();
+            if let Some(key) = name_tag.model {
+                let mdl = renderer.model.get_model(key).unwrap();
+                mdl.block_light = light.block_light;
+                mdl.sky_light = light.sky_light;
+                let (matrix, alpha) = name_tag_transform(
+                    &renderer.camera,
+                    position.position + Vector3::new(0.0, NAME_TAG_HEIGHT, 0.0),
+                );
+                mdl.colors[0][3] = alpha;
+                mdl.matrix[0] = matrix;
+            }
+        }
+    }
+
+    fn entity_added(
+        &mut self,
+        m: &mut ecs::Manager,
+        e: ecs::Entity,
+        _: &world::World,
+        renderer: &mut render::Renderer,
+    ) {
+        let name_tag = m.get_component_mut(e, self.name_tag).unwrap();
+        name_tag.dirty = false;
+        if let Some(custom_name) = name_tag.custom_name.as_ref() {
+            let verts = name_tag_verts(renderer, custom_name);
+            name_tag.model = Some(renderer.model.create_model(model::DEFAULT, vec![verts]));
+        }
+    }
+
+    fn entity_removed(
+        &mut self,
+        m: &mut ecs::Manager,
+        e: ecs::Entity,
+        _: &world::World,
+        renderer: &mut render::Renderer,
+    ) {
+        let name_tag = m.get_component_mut(e, self.name_tag).unwrap();
+        if let Some(model) = name_tag.model.take() {
+            renderer.model.remove_model(model);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_name_changes_rebuild_the_tag() {
+        let mut name_tag = NameTag::default();
+        name_tag.set_custom_name(None);
+        assert!(!name_tag.dirty);
+
+        let name = format::Component::from_legacy("§6Dinnerbone");
+        name_tag.set_custom_name(Some(name.clone()));
+        assert!(name_tag.dirty);
+        name_tag.dirty = false;
+        name_tag.set_custom_name(Some(name));
+        assert!(!name_tag.dirty);
+    }
+}
@@ -1,9 +1,11 @@
 pub mod block_entity;
+pub mod mob;
 pub mod player;
 
 use crate::ecs;
-use cgmath::Vector3;
+use cgmath::{Vector3, VectorSpace};
 use collision::Aabb3;
+use std::f64::consts::PI;
 
 mod systems;
 
@@ -12,6 +14,10 @@ pub fn add_systems(m: &mut ecs::Manager) {
     m.add_system(sys);
 
     player::add_systems(m);
+    mob::add_systems(m);
+
+    let sys = systems::UpdateInterpolation::new(m);
+    m.add_system(sys);
 
     let sys = systems::ApplyVelocity::new(m);
     m.add_system(sys);
@@ -52,6 +58,8 @@ impl Position {
 #[derive(Debug)]
 pub struct TargetPosition {
     pub position: Vector3<f64>,
+    /// The target at the start of the last logic tick, for entities moved by the client.
+    pub last_position: Vector3<f64>,
     pub lerp_amount: f64,
 }
 
@@ -59,6 +67,7 @@ impl TargetPosition {
     pub fn new(x: f64, y: f64, z: f64) -> TargetPosition {
         TargetPosition {
             position: Vector3::new(x, y, z),
+            last_position: Vector3::new(x, y, z),
             lerp_amount: 0.2,
         }
     }
@@ -118,6 +127,65 @@ impl TargetRotation {
     }
 }
 
+/// Where an entity was and which way it faced.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub position: Vector3<f64>,
+    pub yaw: f64,
+    pub pitch: f64,
+}
+
+/// The transforms of an entity moved by the server at the last two logic ticks. Rendering
+/// interpolates between them, so the entity moves smoothly however its updates arrive.
+#[derive(Debug)]
+pub struct Interpolation {
+    pub previous: Transform,
+    pub current: Transform,
+}
+
+impl Interpolation {
+    pub fn new(transform: Transform) -> Interpolation {
+        Interpolation {
+            previous: transform,
+            current: transform,
+        }
+    }
+
+    /// Moves on to the transform of a new logic tick.
+    pub fn push(&mut self, transform: Transform) {
+        self.previous = self.current;
+        self.current = transform;
+    }
+
+    /// Jumps to `transform` without interpolating, e.g. when the entity teleported.
+    pub fn snap(&mut self, transform: Transform) {
+        self.previous = transform;
+        self.current = transform;
+    }
+
+    /// The transform `partial_tick` of the way from the previous logic tick to the
+    /// current one. Angles turn the short way round.
+    pub fn lerp(&self, partial_tick: f64) -> Transform {
+        Transform {
+            position: self
+                .previous
+                .position
+                .lerp(self.current.position, partial_tick),
+            yaw: lerp_angle(self.previous.yaw, self.current.yaw, partial_tick),
+            pitch: lerp_angle(self.previous.pitch, self.current.pitch, partial_tick),
+        }
+    }
+}
+
+/// Interpolates between two angles in radians, returning an angle in `[0, 2π)`.
+fn lerp_angle(from: f64, to: f64, amount: f64) -> f64 {
+    let mut delta = (to - from).rem_euclid(PI * 2.0);
+    if delta > PI {
+        delta -= PI * 2.0;
+    }
+    (from + delta * amount).rem_euclid(PI * 2.0)
+}
+
 #[derive(Default)]
 pub struct Gravity {
     pub on_ground: bool,
@@ -142,6 +210,8 @@ impl Bounds {
 #[derive(Default)]
 pub struct GameInfo {
     pub delta: f64,
+    /// How far the current frame is between the last logic tick and the next one.
+    pub partial_tick: f64,
 }
 
 impl GameInfo {
@@ -161,3 +231,41 @@ impl Light {
         Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolation_halfway_is_midpoint() {
+        let mut interpolation = Interpolation::new(Transform {
+            position: Vector3::new(0.0, 64.0, 0.0),
+            yaw: 0.1,
+            pitch: 0.0,
+        });
+        interpolation.push(Transform {
+            position: Vector3::new(2.0, 65.0, -4.0),
+            // Turns through 0 rather than all the way round
+            yaw: PI * 2.0 - 0.1,
+            pitch: 0.5,
+        });
+
+        let halfway = interpolation.lerp(0.5);
+        assert_eq!(halfway.position, Vector3::new(1.0, 64.5, -2.0));
+        assert!(halfway.yaw.abs() < 1e-9 || (halfway.yaw - PI * 2.0).abs() < 1e-9);
+        assert!((halfway.pitch - 0.25).abs() < 1e-9);
+        assert_eq!(
+            interpolation.lerp(1.0).position,
+            Vector3::new(2.0, 65.0, -4.0)
+        );
+
+        // Teleports don't interpolate
+        let teleport = Transform {
+            position: Vector3::new(100.0, 70.0, 100.0),
+            yaw: 0.0,
+            pitch: 0.0,
+        };
+        interpolation.snap(teleport);
+        assert_eq!(interpolation.lerp(0.5), teleport);
+    }
+}
@@ -1,21 +1,44 @@
 use super::{
-    Bounds, GameInfo, Gravity, Light, Position, Rotation, TargetPosition, TargetRotation, Velocity,
+    Bounds, GameInfo, Gravity, Interpolation, Light, Position, Rotation, TargetPosition,
+    TargetRotation, Transform, Velocity,
 };
 use crate::ecs;
 use crate::format;
+use crate::model::BlockVertex;
 use crate::render;
 use crate::render::model::{self, FormatState};
 use crate::settings::Actionkey;
-use crate::shared::Position as BPosition;
+use crate::shared::{Direction, Position as BPosition};
 use crate::types::hash::FNVHash;
 use crate::types::GameMode;
 use crate::world;
-use cgmath::{self, Decomposed, Matrix4, Point3, Quaternion, Rad, Rotation3, Vector3};
+use cgmath::{
+    self, Decomposed, EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion, Rad, Rotation3,
+    Vector3,
+};
 use collision::{Aabb, Aabb3};
 use instant::Instant;
+use leafish_protocol::protocol::packet::play::clientbound::PlayerAbilities;
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 
+/// How long an arm swing lasts, in frame deltas (1/60ths of a second). About six ticks.
+pub const ARM_SWING_TIME: f64 = 6.0 * 3.0;
+
+/// Height of the camera above the player's feet while standing.
+pub const EYE_HEIGHT: f64 = 1.62;
+/// How far sneaking lowers the camera.
+pub const SNEAK_EYE_OFFSET: f64 = 0.08;
+/// Height of the camera while swimming, lying down in the water.
+pub const SWIM_EYE_HEIGHT: f64 = 0.4;
+
+/// Name tags further than this from the camera are hidden, like vanilla's 64 blocks.
+pub const NAME_TAG_DISTANCE: f64 = 64.0;
+/// How many blocks before `NAME_TAG_DISTANCE` name tags start fading out.
+const NAME_TAG_FADE: f64 = 8.0;
+/// How far above an entity's feet its name tag floats.
+pub(super) const NAME_TAG_HEIGHT: f64 = 24.0 / 16.0 + 0.6;
+
 pub fn add_systems(m: &mut ecs::Manager) {
     let sys = MovementHandler::new(m);
     m.add_system(sys);
@@ -41,7 +64,7 @@ pub fn create_local(m: &mut ecs::Manager) -> ecs::Entity {
             Point3::new(0.3, 1.8, 0.3),
         )),
     );
-    m.add_component_direct(entity, PlayerModel::new("", false, false, true));
+    m.add_component_direct(entity, PlayerModel::new("", true, false, true));
     m.add_component_direct(entity, Light::new());
     entity
 }
@@ -52,6 +75,14 @@ pub fn create_remote(m: &mut ecs::Manager, name: &str) -> ecs::Entity {
     m.add_component_direct(entity, TargetPosition::new(0.0, 0.0, 0.0));
     m.add_component_direct(entity, Rotation::new(0.0, 0.0));
     m.add_component_direct(entity, TargetRotation::new(0.0, 0.0));
+    m.add_component_direct(
+        entity,
+        Interpolation::new(Transform {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            yaw: 0.0,
+            pitch: 0.0,
+        }),
+    );
     m.add_component_direct(entity, Velocity::new(0.0, 0.0, 0.0));
     m.add_component_direct(
         entity,
@@ -70,10 +101,17 @@ pub struct PlayerModel {
     skin_url: Option<String>,
     dirty: bool,
     name: String,
+    /// The name given by the entity metadata, shown instead of `name` when
+    /// `custom_name_visible` is set.
+    custom_name: Option<format::Component>,
+    custom_name_visible: bool,
 
     has_head: bool,
     has_name_tag: bool,
     first_person: bool,
+    /// The item and block textures of the item held in the first person view, as
+    /// returned by `Material::texture_locations`.
+    held_item: Option<(String, String)>,
 
     dir: i32,
     time: f64,
@@ -89,10 +127,13 @@ impl PlayerModel {
             skin_url: None,
             dirty: false,
             name: name.to_owned(),
+            custom_name: None,
+            custom_name_visible: false,
 
             has_head,
             has_name_tag,
             first_person,
+            held_item: None,
 
             dir: 0,
             time: 0.0,
@@ -106,6 +147,83 @@ impl PlayerModel {
         self.skin_url = skin;
         self.dirty = true;
     }
+
+    /// Switches between only drawing the right arm in front of the camera and drawing
+    /// the whole player.
+    pub fn set_first_person(&mut self, first_person: bool) {
+        if self.first_person != first_person {
+            self.first_person = first_person;
+            self.dirty = true;
+        }
+    }
+
+    pub fn set_custom_name(&mut self, custom_name: Option<format::Component>) {
+        if self.custom_name != custom_name {
+            self.custom_name = custom_name;
+            self.dirty = true;
+        }
+    }
+
+    /// Sets the metadata flag asking for the custom name to always be shown.
+    pub fn set_custom_name_visible(&mut self, visible: bool) {
+        if self.custom_name_visible != visible {
+            self.custom_name_visible = visible;
+            self.dirty = true;
+        }
+    }
+
+    /// The text of the name tag floating above the player.
+    fn name_tag(&self) -> format::Component {
+        match self.custom_name {
+            Some(ref custom_name) if self.custom_name_visible => custom_name.clone(),
+            _ => format::Component::from_legacy(&self.name),
+        }
+    }
+
+    pub fn set_held_item(&mut self, textures: Option<(String, String)>) {
+        if self.held_item != textures {
+            self.held_item = textures;
+            self.dirty = true;
+        }
+    }
+
+    /// The parts of the model that are drawn. The first person view only shows the right
+    /// arm and the held item, the rest of the body would block the camera.
+    fn drawn_parts(&self) -> Vec<PlayerModelPart> {
+        PlayerModelPart::ALL
+            .iter()
+            .copied()
+            .filter(|part| match part {
+                PlayerModelPart::Head => self.has_head && !self.first_person,
+                PlayerModelPart::NameTag => self.has_name_tag,
+                PlayerModelPart::ArmRight => true,
+                PlayerModelPart::HeldItem => self.first_person && self.held_item.is_some(),
+                PlayerModelPart::Body
+                | PlayerModelPart::LegLeft
+                | PlayerModelPart::LegRight
+                | PlayerModelPart::ArmLeft => !self.first_person,
+            })
+            .collect()
+    }
+
+    /// Starts swinging the right arm, restarting the swing if one is in progress.
+    pub fn swing_arm(&mut self) {
+        self.arm_time = ARM_SWING_TIME;
+    }
+
+    pub fn is_swinging_arm(&self) -> bool {
+        self.arm_time > 0.0
+    }
+
+    fn tick_arm_swing(&mut self, delta: f64) {
+        self.arm_time = (self.arm_time - delta).max(0.0);
+    }
+
+    /// How far the arm is raised, from 0 at rest to 1 halfway through the swing.
+    fn arm_swing(&self) -> f64 {
+        let half = ARM_SWING_TIME / 2.0;
+        (half - (self.arm_time - half).abs()) / half
+    }
 }
 
 struct PlayerRenderer {
@@ -138,6 +256,7 @@ impl PlayerRenderer {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum PlayerModelPart {
     Head = 0,
     Body = 1,
@@ -146,7 +265,117 @@ enum PlayerModelPart {
     ArmLeft = 4,
     ArmRight = 5,
     NameTag = 6,
-    //Cape = 7, // TODO
+    HeldItem = 7,
+    //Cape = 8, // TODO
+}
+
+impl PlayerModelPart {
+    const ALL: [PlayerModelPart; 8] = [
+        PlayerModelPart::Head,
+        PlayerModelPart::Body,
+        PlayerModelPart::LegLeft,
+        PlayerModelPart::LegRight,
+        PlayerModelPart::ArmLeft,
+        PlayerModelPart::ArmRight,
+        PlayerModelPart::NameTag,
+        PlayerModelPart::HeldItem,
+    ];
+}
+
+/// Turns a name tag to face the camera, lying in the plane of the screen.
+fn name_tag_rotation(camera_yaw: f64, camera_pitch: f64) -> Quaternion<f32> {
+    use std::f32::consts::PI;
+    Quaternion::from_angle_y(Rad(PI + camera_yaw as f32))
+        * Quaternion::from_angle_x(Rad(-camera_pitch as f32))
+}
+
+/// How opaque a name tag `distance` blocks from the camera is, fading out towards
+/// `NAME_TAG_DISTANCE`.
+fn name_tag_alpha(distance: f64) -> f32 {
+    ((NAME_TAG_DISTANCE - distance) / NAME_TAG_FADE)
+        .max(0.0)
+        .min(1.0) as f32
+}
+
+/// Where the name tag floating at `position` is drawn and how opaque it is.
+pub(super) fn name_tag_transform(
+    camera: &render::Camera,
+    position: Vector3<f64>,
+) -> (Matrix4<f32>, f32) {
+    let alpha = name_tag_alpha((Point3::from_vec(position) - camera.pos).magnitude());
+    let matrix = Matrix4::from(Decomposed {
+        // Hidden entirely once faded out, so it doesn't hide what's behind it
+        scale: if alpha > 0.0 { 1.0 } else { 0.0 },
+        rot: name_tag_rotation(camera.yaw, camera.pitch),
+        disp: Vector3::new(position.x as f32, -position.y as f32, position.z as f32),
+    });
+    (matrix, alpha)
+}
+
+/// The text of a name tag on a dark, see-through box like vanilla's.
+pub(super) fn name_tag_verts(
+    renderer: &mut render::Renderer,
+    name: &format::Component,
+) -> Vec<model::Vertex> {
+    let background = render::Renderer::get_texture(renderer.get_textures_ref(), "leafish:solid");
+    let mut state = FormatState {
+        width: 0.0,
+        offset: 0.0,
+        text: Vec::new(),
+        renderer,
+        y_scale: 0.16,
+        x_scale: 0.01,
+    };
+    state.build(name, format::Color::White);
+    let width = state.width;
+
+    let mut verts = vec![];
+    const PADDING: f32 = 0.025;
+    for vert in BlockVertex::face_by_direction(Direction::North) {
+        verts.push(model::Vertex {
+            x: vert.x * (width + PADDING * 2.0) - (width * 0.5 + PADDING),
+            y: vert.y * (0.16 + PADDING * 2.0) + 2.1 / 16.0 - PADDING,
+            z: -0.6 / 16.0 + 0.01,
+            texture: background.clone(),
+            texture_x: vert.toffsetx as f64,
+            texture_y: vert.toffsety as f64,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 64,
+            id: 0,
+        });
+    }
+
+    // Center align text
+    for vert in &mut state.text {
+        vert.x += width * 0.5;
+    }
+    verts.extend_from_slice(&state.text);
+    verts
+}
+
+/// Places the right arm in the lower right of the first person view, raised by `swing`
+/// and swaying with the walk cycle `ang`.
+fn first_person_arm(camera: &render::Camera, swing: f64, ang: f64) -> Matrix4<f32> {
+    use std::f32::consts::PI;
+    let view = Matrix4::from(Decomposed {
+        scale: 1.0,
+        rot: Quaternion::from_angle_y(Rad(PI + camera.yaw as f32))
+            * Quaternion::from_angle_x(Rad(-camera.pitch as f32)),
+        disp: Vector3::new(
+            camera.pos.x as f32,
+            -camera.pos.y as f32,
+            camera.pos.z as f32,
+        ),
+    });
+    view * Matrix4::from_translation(Vector3::new(
+        0.45 + (ang * 0.06) as f32,
+        0.75 + (ang.abs() * 0.06) as f32,
+        -(swing * 0.2) as f32,
+    )) * Matrix4::from(Quaternion::from_angle_x(Rad(
+        -(PI / 2.0 + 0.3) - (swing * 0.6) as f32
+    )))
 }
 
 // TODO: Setup culling
@@ -187,37 +416,24 @@ impl ecs::System for PlayerRenderer {
                 mdl.block_light = light.block_light;
                 mdl.sky_light = light.sky_light;
 
-                let offset = if player_model.first_person {
-                    let ox = (rotation.yaw - PI64 / 2.0).cos() * 0.25;
-                    let oz = -(rotation.yaw - PI64 / 2.0).sin() * 0.25;
-                    Vector3::new(
-                        position.position.x as f32 - ox as f32,
-                        -position.position.y as f32,
-                        position.position.z as f32 - oz as f32,
-                    )
-                } else {
-                    Vector3::new(
-                        position.position.x as f32,
-                        -position.position.y as f32,
-                        position.position.z as f32,
-                    )
-                };
+                let offset = Vector3::new(
+                    position.position.x as f32,
+                    -position.position.y as f32,
+                    position.position.z as f32,
+                );
                 let offset_matrix = Matrix4::from(Decomposed {
                     scale: 1.0,
                     rot: Quaternion::from_angle_y(Rad(PI + rotation.yaw as f32)),
                     disp: offset,
                 });
 
-                // TODO This sucks
                 if player_model.has_name_tag {
-                    let ang = (position.position.x - renderer.camera.pos.x)
-                        .atan2(position.position.z - renderer.camera.pos.z)
-                        as f32;
-                    mdl.matrix[PlayerModelPart::NameTag as usize] = Matrix4::from(Decomposed {
-                        scale: 1.0,
-                        rot: Quaternion::from_angle_y(Rad(ang)),
-                        disp: offset + Vector3::new(0.0, (-24.0 / 16.0) - 0.6, 0.0),
-                    });
+                    let (matrix, alpha) = name_tag_transform(
+                        &renderer.camera,
+                        position.position + Vector3::new(0.0, NAME_TAG_HEIGHT, 0.0),
+                    );
+                    mdl.colors[PlayerModelPart::NameTag as usize][3] = alpha;
+                    mdl.matrix[PlayerModelPart::NameTag as usize] = matrix;
                 }
 
                 mdl.matrix[PlayerModelPart::Head as usize] = offset_matrix
@@ -261,11 +477,7 @@ impl ecs::System for PlayerRenderer {
                 }
                 player_model.idle_time = i_time;
 
-                if player_model.arm_time <= 0.0 {
-                    player_model.arm_time = 0.0;
-                } else {
-                    player_model.arm_time -= delta;
-                }
+                player_model.tick_arm_swing(delta);
 
                 mdl.matrix[PlayerModelPart::ArmRight as usize] = offset_matrix
                     * Matrix4::from_translation(Vector3::new(
@@ -278,7 +490,7 @@ impl ecs::System for PlayerRenderer {
                         (i_time.cos() * 0.06 - 0.06) as f32
                     )))
                     * Matrix4::from(Quaternion::from_angle_x(Rad((i_time.sin() * 0.06
-                        - ((7.5 - (player_model.arm_time - 7.5).abs()) / 7.5))
+                        - player_model.arm_swing())
                         as f32)));
 
                 mdl.matrix[PlayerModelPart::ArmLeft as usize] = offset_matrix
@@ -293,6 +505,12 @@ impl ecs::System for PlayerRenderer {
                     )))
                     * Matrix4::from(Quaternion::from_angle_x(Rad(-(i_time.sin() * 0.06) as f32)));
 
+                if player_model.first_person {
+                    let arm = first_person_arm(&renderer.camera, player_model.arm_swing(), ang);
+                    mdl.matrix[PlayerModelPart::ArmRight as usize] = arm;
+                    mdl.matrix[PlayerModelPart::HeldItem as usize] = arm;
+                }
+
                 let mut update = true;
                 if position.moved {
                     player_model.still_time = 0.0;
@@ -477,52 +695,62 @@ impl ecs::System for PlayerRenderer {
             );
         }
 
-        let mut name_verts = vec![];
-        if player_model.has_name_tag {
-            let mut state = FormatState {
-                width: 0.0,
-                offset: 0.0,
-                text: Vec::new(),
-                renderer,
-                y_scale: 0.16,
-                x_scale: 0.01,
-            };
-            let mut name = format::Component::Text(format::TextComponent::new(&player_model.name));
-            format::convert_legacy(&mut name);
-            state.build(&name, format::Color::Black);
-            // TODO: Remove black shadow and add dark, transparent box around name
-            let width = state.width;
-            // Center align text
-            for vert in &mut state.text {
-                vert.x += width * 0.5;
-                vert.r = 64;
-                vert.g = 64;
-                vert.b = 64;
+        let name_verts = if player_model.has_name_tag {
+            name_tag_verts(renderer, &player_model.name_tag())
+        } else {
+            vec![]
+        };
+
+        let mut item_verts = vec![];
+        if let Some((item, block)) = player_model.held_item.as_ref() {
+            let textures = renderer.get_textures_ref();
+            let texture =
+                render::Renderer::get_texture_optional(textures, &format!("minecraft:{}", item))
+                    .or_else(|| {
+                        render::Renderer::get_texture_optional(
+                            textures,
+                            &format!("minecraft:{}", block),
+                        )
+                    });
+            if let Some(texture) = texture {
+                // A flat square sticking out of the hand
+                model::append_box(
+                    &mut item_verts,
+                    -0.5 / 16.0,
+                    -20.0 / 16.0,
+                    -6.0 / 16.0,
+                    1.0 / 16.0,
+                    8.0 / 16.0,
+                    8.0 / 16.0,
+                    [
+                        Some(texture.clone()),
+                        Some(texture.clone()),
+                        Some(texture.clone()),
+                        Some(texture.clone()),
+                        Some(texture.clone()),
+                        Some(texture),
+                    ],
+                );
             }
-            name_verts.extend_from_slice(&state.text);
-            for vert in &mut state.text {
-                vert.x -= 0.01;
-                vert.y -= 0.01;
-                vert.z -= 0.05;
-                vert.r = 255;
-                vert.g = 255;
-                vert.b = 255;
+        }
+
+        let mut parts = vec![
+            head_verts,
+            body_verts,
+            part_verts[0].clone(),
+            part_verts[1].clone(),
+            part_verts[2].clone(),
+            part_verts[3].clone(),
+            name_verts,
+            item_verts,
+        ];
+        let drawn_parts = player_model.drawn_parts();
+        for part in PlayerModelPart::ALL.iter() {
+            if !drawn_parts.contains(part) {
+                parts[*part as usize].clear();
             }
-            name_verts.extend_from_slice(&state.text);
-        }
-
-        player_model.model = Some(renderer.model.create_model(
-            model::DEFAULT,
-            vec![
-                head_verts,
-                body_verts,
-                part_verts[0].clone(),
-                part_verts[1].clone(),
-                part_verts[2].clone(),
-                part_verts[3].clone(),
-                name_verts,
-            ],
-        ));
+        }
+        player_model.model = Some(renderer.model.create_model(model::DEFAULT, parts));
     }
 
     fn entity_removed(
@@ -542,6 +770,81 @@ impl ecs::System for PlayerRenderer {
     }
 }
 
+/// Vanilla's flying speed, which the movement below is tuned for.
+const DEFAULT_FLYING_SPEED: f32 = 0.05;
+/// Vanilla's walking speed, which the movement below is tuned for.
+const DEFAULT_WALKING_SPEED: f32 = 0.1;
+
+/// How much the camera bobs per block walked in a tick, and the most it bobs.
+const VIEW_BOB_SCALE: f64 = 0.25;
+const VIEW_BOB_MAX: f64 = 0.1;
+
+/// How far into its walking cycle the camera is and how much it bobs.
+#[derive(Clone, Copy, Debug, Default)]
+struct ViewBob {
+    walked: f64,
+    amount: f64,
+}
+
+const ABILITY_INVULNERABLE: u8 = 0x01;
+const ABILITY_FLYING: u8 = 0x02;
+const ABILITY_ALLOW_FLYING: u8 = 0x04;
+const ABILITY_INSTANT_BUILD: u8 = 0x08;
+
+/// What the server lets the player do and how fast they move, from its PlayerAbilities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Abilities {
+    pub invulnerable: bool,
+    pub flying: bool,
+    pub allow_flying: bool,
+    pub instant_build: bool,
+    pub flying_speed: f32,
+    pub walking_speed: f32,
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Abilities {
+            invulnerable: false,
+            flying: false,
+            allow_flying: false,
+            instant_build: false,
+            flying_speed: DEFAULT_FLYING_SPEED,
+            walking_speed: DEFAULT_WALKING_SPEED,
+        }
+    }
+}
+
+impl Abilities {
+    pub fn flags(&self) -> u8 {
+        let mut flags = 0;
+        for &(set, flag) in &[
+            (self.invulnerable, ABILITY_INVULNERABLE),
+            (self.flying, ABILITY_FLYING),
+            (self.allow_flying, ABILITY_ALLOW_FLYING),
+            (self.instant_build, ABILITY_INSTANT_BUILD),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        flags
+    }
+}
+
+impl From<&PlayerAbilities> for Abilities {
+    fn from(packet: &PlayerAbilities) -> Abilities {
+        Abilities {
+            invulnerable: packet.flags & ABILITY_INVULNERABLE != 0,
+            flying: packet.flags & ABILITY_FLYING != 0,
+            allow_flying: packet.flags & ABILITY_ALLOW_FLYING != 0,
+            instant_build: packet.flags & ABILITY_INSTANT_BUILD != 0,
+            flying_speed: packet.flying_speed,
+            walking_speed: packet.walking_speed,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct PlayerMovement {
     pub flying: bool,
@@ -550,6 +853,18 @@ pub struct PlayerMovement {
     pub when_last_jump_released: Option<Instant>,
     pub did_touch_ground: bool,
     pub pressed_keys: HashMap<Actionkey, bool, BuildHasherDefault<FNVHash>>,
+    sprinting: bool,
+    forward_held: bool,
+    when_last_forward_released: Option<Instant>,
+    /// Set when the food level drops below `settings::MIN_SPRINT_FOOD`.
+    pub too_hungry_to_sprint: bool,
+    sneaking: bool,
+    swimming: bool,
+    abilities: Abilities,
+    /// Whether the server knows the player is flying.
+    flying_sent: bool,
+    view_bob: ViewBob,
+    last_view_bob: ViewBob,
 }
 
 impl PlayerMovement {
@@ -557,7 +872,7 @@ impl PlayerMovement {
         Default::default()
     }
 
-    fn calculate_movement(&self, player_yaw: f64) -> (f64, f64, bool) {
+    fn calculate_movement(&self, player_yaw: f64) -> (f64, f64) {
         use std::f64::consts::PI;
         let mut forward = 0.0f64;
         let mut yaw = player_yaw - (PI / 2.0);
@@ -584,16 +899,145 @@ impl PlayerMovement {
             yaw += change;
         }
 
-        (
-            forward,
-            yaw,
-            self.is_key_pressed(Actionkey::Forward) && !self.is_key_pressed(Actionkey::Backward),
-        )
+        (forward, yaw)
     }
 
     fn is_key_pressed(&self, key: Actionkey) -> bool {
         self.pressed_keys.get(&key).map_or(false, |v| *v)
     }
+
+    pub fn is_sprinting(&self) -> bool {
+        self.sprinting
+    }
+
+    /// Starts sprinting when the sprint key is held or forward is double-tapped, and stops
+    /// once the player stops moving forward or can't sprint anymore.
+    pub fn update_sprint(&mut self, now: Instant, can_sprint: bool) {
+        let forward =
+            self.is_key_pressed(Actionkey::Forward) && !self.is_key_pressed(Actionkey::Backward);
+        if forward && !self.forward_held {
+            if let Some(released) = self.when_last_forward_released.take() {
+                let dt = now.saturating_duration_since(released);
+                if dt.as_millis() <= crate::settings::DOUBLE_TAP_SPRINT_MS as u128 {
+                    self.sprinting = true;
+                }
+            }
+        } else if !forward && self.forward_held {
+            self.when_last_forward_released = Some(now);
+        }
+        self.forward_held = forward;
+
+        if forward && self.is_key_pressed(Actionkey::Sprint) {
+            self.sprinting = true;
+        }
+        if !forward || !can_sprint {
+            self.sprinting = false;
+        }
+    }
+
+    pub fn abilities(&self) -> Abilities {
+        self.abilities
+    }
+
+    /// Applies the abilities the server sent, it already knows whether the player flies.
+    pub fn set_abilities(&mut self, abilities: Abilities) {
+        self.abilities = abilities;
+        self.flying = abilities.flying;
+        self.flying_sent = abilities.flying;
+    }
+
+    /// How much faster than vanilla's defaults the server lets the player fly when
+    /// flying, or walk otherwise.
+    fn speed_factor(&self) -> f64 {
+        if self.flying {
+            (self.abilities.flying_speed / DEFAULT_FLYING_SPEED) as f64
+        } else {
+            (self.abilities.walking_speed / DEFAULT_WALKING_SPEED) as f64
+        }
+    }
+
+    /// Toggles flying when jump is double-tapped, if the server allows flight.
+    pub fn update_flight(&mut self, now: Instant) {
+        if self.is_key_pressed(Actionkey::Jump) {
+            if self.when_last_jump_pressed.is_none() {
+                self.when_last_jump_pressed = Some(now);
+                if let Some(released) = self.when_last_jump_released {
+                    let dt = now.saturating_duration_since(released);
+                    if dt.as_millis() <= crate::settings::DOUBLE_JUMP_MS as u128 {
+                        self.want_to_fly = !self.want_to_fly;
+                        if self.abilities.allow_flying {
+                            self.flying = self.want_to_fly;
+                        }
+                    }
+                }
+            }
+        } else if self.when_last_jump_pressed.is_some() {
+            self.when_last_jump_released = Some(now);
+            self.when_last_jump_pressed = None;
+        }
+    }
+
+    /// Whether the player started or stopped flying since the server was last told,
+    /// which it then is.
+    pub fn take_flying_change(&mut self) -> Option<bool> {
+        if self.flying == self.flying_sent {
+            None
+        } else {
+            self.flying_sent = self.flying;
+            Some(self.flying)
+        }
+    }
+
+    /// Moves the camera bob on by a logic tick in which the player moved `moved` blocks
+    /// horizontally. It follows the speed, fading out when standing or not walking.
+    pub fn update_view_bob(&mut self, moved: f64, walking: bool) {
+        self.last_view_bob = self.view_bob;
+        let target = if walking {
+            (moved * VIEW_BOB_SCALE).min(VIEW_BOB_MAX)
+        } else {
+            0.0
+        };
+        self.view_bob.amount += (target - self.view_bob.amount) * 0.4;
+        if walking {
+            self.view_bob.walked += moved * 0.6;
+        }
+    }
+
+    /// The camera's sideways and vertical offsets from bobbing, `partial_tick` of the way
+    /// into the current logic tick.
+    pub fn view_bob(&self, partial_tick: f64) -> (f64, f64) {
+        let lerp = |from: f64, to: f64| from + (to - from) * partial_tick;
+        let walked = lerp(self.last_view_bob.walked, self.view_bob.walked);
+        let amount = lerp(self.last_view_bob.amount, self.view_bob.amount);
+        let phase = walked * std::f64::consts::PI;
+        (phase.sin() * amount * 0.5, -(phase.cos() * amount).abs())
+    }
+
+    pub fn is_sneaking(&self) -> bool {
+        self.sneaking
+    }
+
+    pub fn is_swimming(&self) -> bool {
+        self.swimming
+    }
+
+    /// Updates the sneaking and swimming poses. Sneaking while flying descends instead,
+    /// and sprinting in water swims.
+    pub fn update_pose(&mut self, in_water: bool) {
+        self.sneaking = self.is_key_pressed(Actionkey::Sneak) && !self.flying;
+        self.swimming = in_water && self.sprinting && !self.flying;
+    }
+
+    /// The camera's height above the player's feet in the current pose.
+    pub fn eye_height(&self) -> f64 {
+        if self.swimming {
+            SWIM_EYE_HEIGHT
+        } else if self.sneaking {
+            EYE_HEIGHT - SNEAK_EYE_OFFSET
+        } else {
+            EYE_HEIGHT
+        }
+    }
 }
 
 struct MovementHandler {
@@ -669,50 +1113,40 @@ impl ecs::System for MovementHandler {
             }
 
             // Detect double-tapping jump to toggle creative flight
-            if movement.is_key_pressed(Actionkey::Jump) {
-                if movement.when_last_jump_pressed.is_none() {
-                    movement.when_last_jump_pressed = Some(Instant::now());
-                    if movement.when_last_jump_released.is_some() {
-                        let dt = movement.when_last_jump_pressed.unwrap()
-                            - movement.when_last_jump_released.unwrap();
-                        if dt.as_secs() == 0
-                            && dt.subsec_millis() <= crate::settings::DOUBLE_JUMP_MS
-                        {
-                            movement.want_to_fly = !movement.want_to_fly;
-                            //info!("double jump! dt={:?} toggle want_to_fly = {}", dt, movement.want_to_fly);
-
-                            if gamemode.can_fly() && !gamemode.always_fly() {
-                                movement.flying = movement.want_to_fly;
-                            }
-                        }
-                    }
-                }
-            } else if movement.when_last_jump_pressed.is_some() {
-                movement.when_last_jump_released = Some(Instant::now());
-                movement.when_last_jump_pressed = None;
+            if !gamemode.always_fly() {
+                movement.update_flight(Instant::now());
             }
 
+            let can_sprint = !movement.too_hungry_to_sprint || gamemode.can_fly();
+            movement.update_sprint(Instant::now(), can_sprint);
+
             let position = m.get_component_mut(e, self.position).unwrap();
+            let in_water = is_in_water(world, position.position);
+            movement.update_pose(in_water);
             let rotation = m.get_component(e, self.rotation).unwrap();
             let velocity = m.get_component_mut(e, self.velocity).unwrap();
             let gravity = m.get_component_mut(e, self.gravity);
 
             let player_bounds = m.get_component(e, self.bounds).unwrap().bounds;
 
+            position.last_position = position.position;
             let mut last_position = position.position;
 
             if world.is_chunk_loaded(
                 (position.position.x as i32) >> 4,
                 (position.position.z as i32) >> 4,
             ) {
-                let (forward, yaw, is_forward) = movement.calculate_movement(rotation.yaw);
-                let mut speed = 0.21585;
-                let mut additional_speed =
-                    if movement.is_key_pressed(Actionkey::Sprint) && is_forward {
-                        0.2806 - 0.21585
-                    } else {
-                        0.0
-                    };
+                let (forward, yaw) = movement.calculate_movement(rotation.yaw);
+                let speed_factor = movement.speed_factor();
+                let mut speed = 0.21585 * speed_factor;
+                let mut additional_speed = if movement.is_sprinting() {
+                    (0.2806 - 0.21585) * speed_factor
+                } else {
+                    0.0
+                };
+                if movement.is_sneaking() {
+                    speed *= 0.3;
+                }
                 let looking_vec = calculate_looking_vector(rotation.yaw, rotation.pitch);
                 if movement.flying {
                     speed *= 2.5;
@@ -724,18 +1158,15 @@ impl ecs::System for MovementHandler {
                     if movement.is_key_pressed(Actionkey::Sneak) {
                         position.position.y -= speed + additional_speed;
                     }
-                } else if gravity.as_ref().map_or(false, |v| v.on_ground) {
-                    if movement.is_key_pressed(Actionkey::Jump) && velocity.velocity.y.abs() < 0.001
-                    {
-                        velocity.velocity.y = 0.42;
-                    }
+                    velocity.velocity.y *= 0.98;
                 } else {
-                    velocity.velocity.y -= 0.08;
-                    if velocity.velocity.y < -3.92 {
-                        velocity.velocity.y = -3.92;
-                    }
+                    velocity.velocity.y = fall_velocity(
+                        velocity.velocity.y,
+                        gravity.as_ref().map_or(false, |v| v.on_ground),
+                        in_water,
+                        movement.is_key_pressed(Actionkey::Jump),
+                    );
                 }
-                velocity.velocity.y *= 0.98;
                 // position.position.x += look_vec.0 * speed;
                 // position.position.z -= look_vec.1 * speed;
                 position.position.x +=
@@ -745,6 +1176,9 @@ impl ecs::System for MovementHandler {
                 position.position.y += velocity.velocity.y;
 
                 if !gamemode.noclip() {
+                    // Sneaking keeps the player from walking off the edge of a block
+                    let edge_protection =
+                        movement.is_sneaking() && gravity.as_ref().map_or(false, |v| v.on_ground);
                     let mut target = position.position;
                     position.position.y = last_position.y;
                     position.position.z = last_position.z;
@@ -755,38 +1189,33 @@ impl ecs::System for MovementHandler {
                     let (bounds, xhit) =
                         check_collisions(world, position, &last_position, player_bounds);
                     position.position.x = bounds.min.x + 0.3;
+                    if edge_protection && !has_ground_below(world, position, &last_position) {
+                        position.position.x = last_position.x;
+                    }
                     last_position.x = position.position.x;
 
                     position.position.z = target.z;
                     let (bounds, zhit) =
                         check_collisions(world, position, &last_position, player_bounds);
                     position.position.z = bounds.min.z + 0.3;
+                    if edge_protection && !has_ground_below(world, position, &last_position) {
+                        position.position.z = last_position.z;
+                    }
                     last_position.z = position.position.z;
 
-                    // Half block jumps
-                    // Minecraft lets you 'jump' up 0.5 blocks
-                    // for slabs and stairs (or smaller blocks).
-                    // Currently we implement this as a teleport to the
-                    // top of the block if we could move there
-                    // but this isn't smooth.
+                    // Walking into a block steps up onto it, currently as a teleport to
+                    // its top which isn't smooth
                     if (xhit || zhit) && gravity.as_ref().map_or(false, |v| v.on_ground) {
                         let mut ox = position.position.x;
                         let mut oz = position.position.z;
                         position.position.x = target.x;
                         position.position.z = target.z;
-                        for offset in 1..9 {
-                            let mini = player_bounds.add_v(cgmath::Vector3::new(
-                                0.0,
-                                offset as f64 / 16.0,
-                                0.0,
-                            ));
-                            let (_, hit) = check_collisions(world, position, &last_position, mini);
-                            if !hit {
-                                target.y += offset as f64 / 16.0;
-                                ox = target.x;
-                                oz = target.z;
-                                break;
-                            }
+                        if let Some(height) =
+                            step_up_height(world, position, &last_position, player_bounds)
+                        {
+                            target.y += height;
+                            ox = target.x;
+                            oz = target.z;
                         }
                         position.position.x = ox;
                         position.position.z = oz;
@@ -802,21 +1231,89 @@ impl ecs::System for MovementHandler {
                     }
 
                     if let Some(gravity) = gravity {
-                        let ground =
-                            Aabb3::new(Point3::new(-0.3, -0.005, -0.3), Point3::new(0.3, 0.0, 0.3));
                         let prev = gravity.on_ground;
-                        let (_, hit) = check_collisions(world, position, &last_position, ground);
-                        gravity.on_ground = hit;
+                        gravity.on_ground = has_ground_below(world, position, &last_position);
                         if !prev && gravity.on_ground {
                             movement.did_touch_ground = true;
                         }
                     }
                 }
             }
+
+            let step = position.position - position.last_position;
+            let on_ground = m
+                .get_component(e, self.gravity)
+                .map_or(false, |v| v.on_ground);
+            movement.update_view_bob(
+                (step.x * step.x + step.z * step.z).sqrt(),
+                on_ground && !movement.flying,
+            );
         }
     }
 }
 
+/// The vertical velocity for the next tick of a player that isn't flying. Jumping only
+/// works from the ground, except in water which slows falling and lets the player swim
+/// up while jump is held.
+fn fall_velocity(velocity: f64, on_ground: bool, in_water: bool, jumping: bool) -> f64 {
+    if in_water {
+        let velocity = if jumping {
+            velocity + 0.04
+        } else {
+            velocity - 0.02
+        };
+        return velocity * 0.8;
+    }
+    let velocity = if !on_ground {
+        (velocity - 0.08).max(-3.92)
+    } else if jumping && velocity.abs() < 0.001 {
+        0.42
+    } else {
+        velocity
+    };
+    velocity * 0.98
+}
+
+/// How high the player steps up onto blocks they walk into, enough for a single block.
+const STEP_HEIGHT: f64 = 1.0;
+
+/// How far the player has to move up to stand on what they walked into, in steps of a
+/// sixteenth of a block. `None` if it's higher than `STEP_HEIGHT` or there's no room above.
+fn step_up_height(
+    world: &world::World,
+    position: &mut TargetPosition,
+    last_position: &Vector3<f64>,
+    bounds: Aabb3<f64>,
+) -> Option<f64> {
+    (1..=(STEP_HEIGHT * 16.0) as i32)
+        .map(|offset| offset as f64 / 16.0)
+        .find(|&offset| {
+            let raised = bounds.add_v(Vector3::new(0.0, offset, 0.0));
+            !check_collisions(world, position, last_position, raised).1
+        })
+}
+
+fn has_ground_below(
+    world: &world::World,
+    position: &mut TargetPosition,
+    last_position: &Vector3<f64>,
+) -> bool {
+    let ground = Aabb3::new(Point3::new(-0.3, -0.005, -0.3), Point3::new(0.3, 0.0, 0.3));
+    check_collisions(world, position, last_position, ground).1
+}
+
+fn is_in_water(world: &world::World, position: Vector3<f64>) -> bool {
+    let pos = BPosition::new(
+        position.x.floor() as i32,
+        position.y.floor() as i32,
+        position.z.floor() as i32,
+    );
+    matches!(
+        world.get_block(pos),
+        world::block::Block::Water { .. } | world::block::Block::FlowingWater { .. }
+    )
+}
+
 fn calculate_looking_vector(yaw: f64, pitch: f64) -> (f64, f64) {
     let xz = pitch.to_radians().cos();
     let x = -xz * yaw.to_radians().sin();
@@ -914,3 +1411,357 @@ impl Collidable<Aabb3<f64>> for Aabb3<f64> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use instant::Duration;
+
+    fn tap_forward(movement: &mut PlayerMovement, pressed: Instant, released: Instant) {
+        movement.pressed_keys.insert(Actionkey::Forward, true);
+        movement.update_sprint(pressed, true);
+        movement.pressed_keys.insert(Actionkey::Forward, false);
+        movement.update_sprint(released, true);
+    }
+
+    #[test]
+    fn falling_player_lands_on_blocks() {
+        // Setting blocks queues light updates, which need somewhere to go
+        let (light_send, _light_recv) = crossbeam_channel::unbounded();
+        let world = world::World::new(754, light_send);
+        let stone = world::block::Stone {
+            variant: world::block::StoneVariant::Normal,
+        };
+        // Away from the unloaded chunks around, which are solid
+        world.set_block(BPosition::new(8, 64, 8), stone);
+        let bounds = Aabb3::new(Point3::new(-0.3, 0.0, -0.3), Point3::new(0.3, 1.8, 0.3));
+
+        let mut position = TargetPosition::new(8.5, 70.0, 8.5);
+        let mut velocity = 0.0;
+        let mut on_ground = false;
+        for _ in 0..40 {
+            let last_position = position.position;
+            velocity = fall_velocity(velocity, on_ground, false, false);
+            position.position.y += velocity;
+            let (landed, hit) = check_collisions(&world, &mut position, &last_position, bounds);
+            position.position.y = landed.min.y;
+            if hit {
+                velocity = 0.0;
+            }
+            on_ground = has_ground_below(&world, &mut position, &last_position);
+        }
+        assert!(on_ground);
+        assert_eq!(velocity, 0.0);
+        assert!((position.position.y - 65.0).abs() < 0.001);
+
+        // Jumping only works from the ground, and water slows the fall
+        assert!(fall_velocity(0.0, true, false, true) > 0.4);
+        assert!(fall_velocity(0.0, false, false, true) < 0.0);
+        assert!(fall_velocity(-0.5, false, true, false) > -0.5);
+        assert!(fall_velocity(0.0, false, true, true) > 0.0);
+    }
+
+    #[test]
+    fn player_steps_up_single_blocks() {
+        let (light_send, _light_recv) = crossbeam_channel::unbounded();
+        let world = world::World::new(754, light_send);
+        let stone = world::block::Stone {
+            variant: world::block::StoneVariant::Normal,
+        };
+        for x in 6..=10 {
+            world.set_block(BPosition::new(x, 64, 8), stone);
+        }
+        let bounds = Aabb3::new(Point3::new(-0.3, 0.0, -0.3), Point3::new(0.3, 1.8, 0.3));
+        let last_position = Vector3::new(8.5, 65.0, 8.5);
+        // Walking into the side of the next block
+        let mut position = TargetPosition::new(8.75, 65.0, 8.5);
+
+        world.set_block(BPosition::new(9, 65, 8), stone);
+        assert_eq!(
+            step_up_height(&world, &mut position, &last_position, bounds),
+            Some(1.0)
+        );
+        let slab = world::block::StoneSlab {
+            half: world::block::BlockHalf::Bottom,
+            variant: world::block::StoneSlabVariant::Stone,
+        };
+        world.set_block(BPosition::new(9, 65, 8), slab);
+        assert_eq!(
+            step_up_height(&world, &mut position, &last_position, bounds),
+            Some(0.5)
+        );
+
+        // Two blocks are too high
+        world.set_block(BPosition::new(9, 65, 8), stone);
+        world.set_block(BPosition::new(9, 66, 8), stone);
+        assert_eq!(
+            step_up_height(&world, &mut position, &last_position, bounds),
+            None
+        );
+    }
+
+    fn tap_jump(movement: &mut PlayerMovement, pressed: Instant, released: Instant) {
+        movement.pressed_keys.insert(Actionkey::Jump, true);
+        movement.update_flight(pressed);
+        movement.pressed_keys.insert(Actionkey::Jump, false);
+        movement.update_flight(released);
+    }
+
+    #[test]
+    fn double_tap_jump_toggles_flight() {
+        let mut movement = PlayerMovement::new();
+        movement.set_abilities(Abilities {
+            allow_flying: true,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let window = Duration::from_millis(crate::settings::DOUBLE_JUMP_MS as u64);
+
+        tap_jump(&mut movement, start, start + Duration::from_millis(50));
+        assert!(!movement.flying);
+        tap_jump(
+            &mut movement,
+            start + Duration::from_millis(100),
+            start + Duration::from_millis(150),
+        );
+        assert!(movement.flying);
+        assert_eq!(movement.take_flying_change(), Some(true));
+        assert_eq!(movement.take_flying_change(), None);
+
+        // Too slow to count as a double tap
+        let later = start + Duration::from_secs(1);
+        tap_jump(&mut movement, later, later + Duration::from_millis(50));
+        let later = later + Duration::from_millis(50) + window * 2;
+        tap_jump(&mut movement, later, later + Duration::from_millis(50));
+        assert!(movement.flying);
+
+        // The server stopping the flight doesn't need to be told about it
+        movement.set_abilities(Abilities::default());
+        assert_eq!(movement.take_flying_change(), None);
+        let later = later + Duration::from_secs(1);
+        tap_jump(&mut movement, later, later + Duration::from_millis(50));
+        tap_jump(
+            &mut movement,
+            later + Duration::from_millis(100),
+            later + Duration::from_millis(150),
+        );
+        assert!(!movement.flying);
+        assert_eq!(movement.take_flying_change(), None);
+    }
+
+    #[test]
+    fn abilities_from_packet() {
+        let packet = PlayerAbilities {
+            flags: 0x0d,
+            flying_speed: 0.1,
+            walking_speed: 0.2,
+        };
+        let abilities = Abilities::from(&packet);
+        assert_eq!(
+            abilities,
+            Abilities {
+                invulnerable: true,
+                flying: false,
+                allow_flying: true,
+                instant_build: true,
+                flying_speed: 0.1,
+                walking_speed: 0.2,
+            }
+        );
+        assert_eq!(abilities.flags(), 0x0d);
+
+        let mut movement = PlayerMovement::new();
+        assert_eq!(movement.speed_factor(), 1.0);
+        movement.set_abilities(abilities);
+        assert_eq!(movement.abilities().walking_speed, 0.2);
+        assert!((movement.speed_factor() - 2.0).abs() < 1e-6);
+        movement.flying = true;
+        assert!((movement.speed_factor() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn view_bob_follows_speed() {
+        let bob_at = |moved: f64| {
+            let mut movement = PlayerMovement::new();
+            for _ in 0..20 {
+                movement.update_view_bob(moved, true);
+            }
+            movement
+        };
+        let standing = bob_at(0.0);
+        assert_eq!(standing.view_bob.amount, 0.0);
+        assert_eq!(standing.view_bob(0.5), (0.0, 0.0));
+
+        let walking = bob_at(0.21585);
+        let sprinting = bob_at(0.2806);
+        assert!(walking.view_bob.amount > 0.0);
+        assert!(sprinting.view_bob.amount > walking.view_bob.amount);
+        assert!(sprinting.view_bob.amount <= VIEW_BOB_MAX);
+
+        // Flying doesn't bob, however fast
+        let mut flying = bob_at(0.21585);
+        for _ in 0..40 {
+            flying.update_view_bob(0.5, false);
+        }
+        assert!(flying.view_bob.amount < 0.001);
+    }
+
+    #[test]
+    fn double_tap_forward_sprints() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+
+        // Too slow a double-tap only walks
+        let mut movement = PlayerMovement::default();
+        tap_forward(&mut movement, start, start + ms(100));
+        movement.pressed_keys.insert(Actionkey::Forward, true);
+        movement.update_sprint(start + ms(1000), true);
+        assert!(!movement.is_sprinting());
+
+        let mut movement = PlayerMovement::default();
+        tap_forward(&mut movement, start, start + ms(100));
+        movement.pressed_keys.insert(Actionkey::Forward, true);
+        movement.update_sprint(start + ms(300), true);
+        assert!(movement.is_sprinting());
+
+        // Sprinting lasts while moving forward and stops when hungry
+        movement.update_sprint(start + ms(2000), true);
+        assert!(movement.is_sprinting());
+        movement.update_sprint(start + ms(2050), false);
+        assert!(!movement.is_sprinting());
+
+        // ...or when letting go of forward
+        movement.pressed_keys.insert(Actionkey::Sprint, true);
+        movement.update_sprint(start + ms(2100), true);
+        assert!(movement.is_sprinting());
+        movement.pressed_keys.insert(Actionkey::Forward, false);
+        movement.update_sprint(start + ms(2150), true);
+        assert!(!movement.is_sprinting());
+    }
+
+    #[test]
+    fn first_person_hides_body() {
+        let mut local = PlayerModel::new("", true, false, true);
+        let parts = local.drawn_parts();
+        for part in &[
+            PlayerModelPart::Head,
+            PlayerModelPart::Body,
+            PlayerModelPart::LegLeft,
+            PlayerModelPart::LegRight,
+            PlayerModelPart::ArmLeft,
+        ] {
+            assert!(!parts.contains(part), "{:?} is drawn", part);
+        }
+        assert_eq!(parts, vec![PlayerModelPart::ArmRight]);
+
+        local.set_held_item(Some(("items/stick".to_owned(), "blocks/stick".to_owned())));
+        assert!(local.dirty);
+        assert_eq!(
+            local.drawn_parts(),
+            vec![PlayerModelPart::ArmRight, PlayerModelPart::HeldItem]
+        );
+
+        // The third person view shows the whole player
+        local.set_first_person(false);
+        let parts = local.drawn_parts();
+        assert!(parts.contains(&PlayerModelPart::Head));
+        assert!(parts.contains(&PlayerModelPart::Body));
+        assert!(!parts.contains(&PlayerModelPart::HeldItem));
+
+        // Other players are drawn whole
+        let remote = PlayerModel::new("Steve", true, true, false);
+        assert_eq!(remote.drawn_parts().len(), 7);
+        assert!(!remote.drawn_parts().contains(&PlayerModelPart::HeldItem));
+    }
+
+    #[test]
+    fn sneaking_lowers_eye_height() {
+        let mut movement = PlayerMovement::default();
+        movement.update_pose(false);
+        assert_eq!(movement.eye_height(), EYE_HEIGHT);
+
+        movement.pressed_keys.insert(Actionkey::Sneak, true);
+        movement.update_pose(false);
+        assert!(movement.is_sneaking());
+        assert_eq!(movement.eye_height(), EYE_HEIGHT - SNEAK_EYE_OFFSET);
+
+        // Sneak descends while flying instead
+        movement.flying = true;
+        movement.update_pose(false);
+        assert_eq!(movement.eye_height(), EYE_HEIGHT);
+
+        movement.flying = false;
+        movement.pressed_keys.insert(Actionkey::Sneak, false);
+        movement.pressed_keys.insert(Actionkey::Sprint, true);
+        movement.pressed_keys.insert(Actionkey::Forward, true);
+        movement.update_sprint(Instant::now(), true);
+        movement.update_pose(true);
+        assert!(movement.is_swimming());
+        assert_eq!(movement.eye_height(), SWIM_EYE_HEIGHT);
+    }
+
+    #[test]
+    fn name_tag_faces_camera() {
+        use std::f64::consts::PI;
+        for &(yaw, pitch) in &[(0.0, 0.0), (PI / 2.0, 0.0), (PI, 0.3), (4.0, -0.8)] {
+            // The text's forward axis points where the camera looks, in model space
+            let forward = name_tag_rotation(yaw, pitch) * Vector3::new(0.0, 0.0, 1.0);
+            let look = render::look_direction(yaw, pitch);
+            let expected = Vector3::new(look.x, -look.y, look.z).cast::<f32>().unwrap();
+            assert!(
+                (forward - expected).magnitude() < 1e-5,
+                "yaw {} pitch {}: {:?} != {:?}",
+                yaw,
+                pitch,
+                forward,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn name_tag_fades_with_distance() {
+        assert_eq!(name_tag_alpha(5.0), 1.0);
+        assert_eq!(name_tag_alpha(NAME_TAG_DISTANCE - NAME_TAG_FADE), 1.0);
+        assert_eq!(name_tag_alpha(NAME_TAG_DISTANCE - NAME_TAG_FADE / 2.0), 0.5);
+        assert_eq!(name_tag_alpha(NAME_TAG_DISTANCE), 0.0);
+        assert_eq!(name_tag_alpha(200.0), 0.0);
+    }
+
+    #[test]
+    fn custom_name_needs_visible_flag() {
+        let mut model = PlayerModel::new("Steve", true, true, false);
+        assert_eq!(model.name_tag().to_plain_string(), "Steve");
+
+        let custom = format::Component::from_legacy("§6Builder");
+        model.set_custom_name(Some(custom));
+        assert!(model.dirty);
+        assert_eq!(model.name_tag().to_plain_string(), "Steve");
+
+        model.set_custom_name_visible(true);
+        assert_eq!(model.name_tag().to_plain_string(), "Builder");
+    }
+
+    #[test]
+    fn arm_swing_decays() {
+        let mut model = PlayerModel::new("", false, false, true);
+        assert!(!model.is_swinging_arm());
+        model.swing_arm();
+        assert!(model.is_swinging_arm());
+        assert_eq!(model.arm_swing(), 0.0);
+
+        // A tick is three frame deltas
+        for _ in 0..3 {
+            model.tick_arm_swing(3.0);
+        }
+        assert!((model.arm_swing() - 1.0).abs() < 1e-9);
+        for _ in 0..3 {
+            assert!(model.is_swinging_arm());
+            model.tick_arm_swing(3.0);
+        }
+        assert!(!model.is_swinging_arm());
+        assert_eq!(model.arm_swing(), 0.0);
+        model.tick_arm_swing(3.0);
+        assert_eq!(model.arm_time, 0.0);
+    }
+}
@@ -133,11 +133,67 @@ impl ecs::System for UpdateLastPosition {
     }
 }
 
+/// Records the server's latest transform of each entity at every logic tick, for
+/// rendering to interpolate between.
+pub struct UpdateInterpolation {
+    filter: ecs::Filter,
+    target_position: ecs::Key<TargetPosition>,
+    target_rotation: ecs::Key<TargetRotation>,
+    interpolation: ecs::Key<Interpolation>,
+}
+
+impl UpdateInterpolation {
+    pub fn new(m: &mut ecs::Manager) -> UpdateInterpolation {
+        let target_position = m.get_key();
+        let target_rotation = m.get_key();
+        let interpolation = m.get_key();
+        UpdateInterpolation {
+            filter: ecs::Filter::new()
+                .with(target_position)
+                .with(target_rotation)
+                .with(interpolation),
+            target_position,
+            target_rotation,
+            interpolation,
+        }
+    }
+}
+
+impl ecs::System for UpdateInterpolation {
+    fn filter(&self) -> &ecs::Filter {
+        &self.filter
+    }
+
+    fn update(
+        &mut self,
+        m: &mut ecs::Manager,
+        _: &world::World,
+        _: &mut render::Renderer,
+        _: bool,
+        _: bool,
+    ) {
+        for e in m.find(&self.filter) {
+            let target_pos = m.get_component(e, self.target_position).unwrap();
+            let target_rot = m.get_component(e, self.target_rotation).unwrap();
+            let transform = Transform {
+                position: target_pos.position,
+                yaw: target_rot.yaw,
+                pitch: target_rot.pitch,
+            };
+            m.get_component_mut(e, self.interpolation)
+                .unwrap()
+                .push(transform);
+        }
+    }
+}
+
 pub struct LerpPosition {
     filter: ecs::Filter,
     position: ecs::Key<Position>,
     target_position: ecs::Key<TargetPosition>,
     game_info: ecs::Key<GameInfo>,
+    movement: ecs::Key<super::player::PlayerMovement>,
+    interpolation: ecs::Key<Interpolation>,
 }
 
 impl LerpPosition {
@@ -149,6 +205,8 @@ impl LerpPosition {
             position,
             target_position,
             game_info: m.get_key(),
+            movement: m.get_key(),
+            interpolation: m.get_key(),
         }
     }
 }
@@ -167,15 +225,24 @@ impl ecs::System for LerpPosition {
         _: bool,
     ) {
         let world_entity = m.get_world();
-        let delta = m
-            .get_component_mut(world_entity, self.game_info)
-            .unwrap()
-            .delta
-            .min(5.0);
+        let game_info = m.get_component(world_entity, self.game_info).unwrap();
+        let delta = game_info.delta.min(5.0);
+        let partial_tick = game_info.partial_tick;
         for e in m.find(&self.filter) {
+            let is_player = m.get_component(e, self.movement).is_some();
             let pos = m.get_component_mut(e, self.position).unwrap();
             let target_pos = m.get_component(e, self.target_position).unwrap();
 
+            if is_player {
+                // Players move in fixed ticks, so interpolate between the last two ticks
+                pos.position = target_pos.last_position
+                    + (target_pos.position - target_pos.last_position) * partial_tick;
+                continue;
+            }
+            if let Some(interpolation) = m.get_component(e, self.interpolation) {
+                pos.position = interpolation.lerp(partial_tick).position;
+                continue;
+            }
             pos.position = pos.position
                 + (target_pos.position - pos.position) * delta * target_pos.lerp_amount;
             let len = (pos.position - target_pos.position).magnitude2();
@@ -191,6 +258,7 @@ pub struct LerpRotation {
     rotation: ecs::Key<Rotation>,
     target_rotation: ecs::Key<TargetRotation>,
     game_info: ecs::Key<GameInfo>,
+    interpolation: ecs::Key<Interpolation>,
 }
 
 impl LerpRotation {
@@ -202,6 +270,7 @@ impl LerpRotation {
             rotation,
             target_rotation,
             game_info: m.get_key(),
+            interpolation: m.get_key(),
         }
     }
 }
@@ -221,13 +290,17 @@ impl ecs::System for LerpRotation {
     ) {
         use std::f64::consts::PI;
         let world_entity = m.get_world();
-        let delta = m
-            .get_component_mut(world_entity, self.game_info)
-            .unwrap()
-            .delta
-            .min(5.0);
+        let game_info = m.get_component(world_entity, self.game_info).unwrap();
+        let delta = game_info.delta.min(5.0);
+        let partial_tick = game_info.partial_tick;
         for e in m.find(&self.filter) {
             let rot = m.get_component_mut(e, self.rotation).unwrap();
+            if let Some(interpolation) = m.get_component(e, self.interpolation) {
+                let transform = interpolation.lerp(partial_tick);
+                rot.yaw = transform.yaw;
+                rot.pitch = transform.pitch;
+                continue;
+            }
             let target_rot = m.get_component_mut(e, self.target_rotation).unwrap();
             target_rot.yaw = (PI * 2.0 + target_rot.yaw) % (PI * 2.0);
             target_rot.pitch = (PI * 2.0 + target_rot.pitch) % (PI * 2.0);
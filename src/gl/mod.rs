@@ -920,6 +920,29 @@ pub fn unbind_framebuffer_read() {
     }
 }
 
+/// Reads a block of pixels from the currently bound read framebuffer, bottom row first.
+pub fn read_pixels(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    format: TextureFormat,
+    ty: Type,
+    pixels: &mut [u8],
+) {
+    unsafe {
+        glow_context().read_pixels(
+            x,
+            y,
+            width,
+            height,
+            format,
+            ty,
+            PixelPackData::Slice(pixels),
+        );
+    }
+}
+
 pub fn unbind_framebuffer_draw() {
     unsafe {
         glow_context().bind_framebuffer(gl::DRAW_FRAMEBUFFER, None);
@@ -0,0 +1,407 @@
+use crate::inventory::material::versions::{to_id, to_material};
+use crate::inventory::player_inventory::PlayerInventory;
+use crate::inventory::{
+    ClickAction, Inventory, InventoryType, Item, Material, Slot, MAX_STACK_SIZE,
+};
+use crate::render::hud::{Hud, HudContext};
+use crate::render::inventory::InventoryWindow;
+use crate::render::Renderer;
+use crate::types::GameMode;
+use crate::ui;
+use crate::ui::{Container, HAttach, VAttach};
+use lazy_static::lazy_static;
+use leafish_protocol::item::Stack;
+use leafish_protocol::protocol::packet::play::serverbound::CreativeInventoryAction;
+use leafish_protocol::protocol::Version;
+use parking_lot::{Mutex, RwLock};
+use std::ops::Range;
+use std::sync::Arc;
+
+const COLUMNS: usize = 9;
+const ROWS: usize = 5;
+/// Where the hotbar starts in the player's inventory window.
+const HOTBAR_SLOT: i16 = 36;
+/// The slot clicks on the tab switching to the player's inventory are handled as.
+const INVENTORY_TAB_SLOT: i16 = -1;
+
+lazy_static! {
+    /// The items listed for each version, which takes a while to work out.
+    static ref ITEMS: Mutex<Vec<(Version, Arc<[(Material, isize)]>)>> = Mutex::new(vec![]);
+}
+
+/// Every known item of `version` along with the damage picking its variant.
+fn creative_items(version: Version) -> Arc<[(Material, isize)]> {
+    let mut items = ITEMS.lock();
+    if let Some((_, listed)) = items.iter().find(|(listed, _)| *listed == version) {
+        return listed.clone();
+    }
+    let listed: Arc<[(Material, isize)]> = (1..=u16::MAX)
+        .map(|id| to_material(id, version))
+        .filter(|material| !matches!(material, Material::Air))
+        .flat_map(|material| (0..variants(material, version)).map(move |damage| (material, damage)))
+        .collect();
+    items.push((version, listed.clone()));
+    listed
+}
+
+/// How many variants of `material` there are, told apart by their damage before 1.13.
+/// Since then each variant is an item of its own.
+fn variants(material: Material, version: Version) -> isize {
+    use Material::*;
+    if version >= Version::V1_13 {
+        return 1;
+    }
+    match material {
+        Stone | Sponge if version < Version::V1_8 => 1,
+        SkullItem if version < Version::V1_9 => 5,
+        Stone => 7,
+        Wood | Sapling | MonsterEggs | WoodStep | DoublePlant | SkullItem => 6,
+        Log | Leaves | SmoothBrick | RawFish => 4,
+        Dirt | Sandstone | LongGrass | QuartzBlock | Prismarine | RedSandstone => 3,
+        Sand | Sponge | CobbleWall | Leaves2 | Log2 | Coal | GoldenApple | CookedFish => 2,
+        RedRose => 9,
+        Step => 8,
+        Wool | StainedGlass | StainedClay | StainedGlassPane | Carpet | Concrete
+        | ConcretePowder | InkSack | Banner => 16,
+        _ => 1,
+    }
+}
+
+/// Lists every known item, a page at a time. Clicking one puts it into the selected
+/// hotbar slot, which only servers in creative mode accept.
+pub struct CreativeInventory {
+    items: Arc<[(Material, isize)]>,
+    page: usize,
+    slots: Vec<Slot>,
+    /// The size of the window's background, in UI units.
+    window_size: (f64, f64),
+    /// The center and half the size of the tab switching to the player's inventory.
+    inventory_tab: (f64, f64, f64, f64),
+    /// Whether the tab was clicked, see `take_replacement`.
+    show_player_inventory: bool,
+    dirty: bool,
+    version: Version,
+    hud_context: Arc<RwLock<HudContext>>,
+    player_inventory: Arc<RwLock<PlayerInventory>>,
+}
+
+impl CreativeInventory {
+    pub fn new(
+        version: Version,
+        hud_context: Arc<RwLock<HudContext>>,
+        player_inventory: Arc<RwLock<PlayerInventory>>,
+    ) -> Self {
+        let mut inventory = CreativeInventory {
+            items: creative_items(version),
+            page: 0,
+            slots: (0..COLUMNS * ROWS)
+                .map(|_| Slot::new(0.0, 0.0, 0.0))
+                .collect(),
+            window_size: (0.0, 0.0),
+            inventory_tab: (0.0, 0.0, 0.0, 0.0),
+            show_player_inventory: false,
+            dirty: false,
+            version,
+            hud_context,
+            player_inventory,
+        };
+        inventory.show_page(0);
+        inventory
+    }
+
+    fn pages(&self) -> usize {
+        ((self.items.len() + self.slots.len() - 1) / self.slots.len()).max(1)
+    }
+
+    fn show_page(&mut self, page: usize) {
+        self.page = page.min(self.pages() - 1);
+        let first = self.page * self.slots.len();
+        let version = self.version;
+        let items = &self.items;
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            slot.item = items.get(first + i).map(|&(material, damage)| Item {
+                stack: Stack {
+                    id: to_id(material, version) as isize,
+                    count: 1,
+                    damage: Some(damage),
+                    tag: None,
+                },
+                material,
+            });
+        }
+        self.dirty = true;
+    }
+
+    fn update_icons(&mut self, renderer: &Renderer) {
+        let scale = Hud::icon_scale(renderer);
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            let (column, row) = ((i % COLUMNS) as f64, (i / COLUMNS) as f64);
+            slot.update_position(
+                scale * (column * 18.0 - 80.5),
+                scale * (row * 18.0 - 42.0),
+                scale * 16.0,
+            );
+        }
+        self.dirty = true;
+    }
+}
+
+/// The packet putting `count` of `material` into the hotbar slot `hotbar_index`.
+pub fn give_packet(
+    material: Material,
+    damage: isize,
+    count: isize,
+    hotbar_index: u8,
+    version: Version,
+) -> CreativeInventoryAction {
+    CreativeInventoryAction {
+        slot: HOTBAR_SLOT + hotbar_index as i16,
+        clicked_item: Some(Stack {
+            id: to_id(material, version) as isize,
+            count,
+            // Only sent before 1.13, where it picks the item's variant
+            damage: Some(damage),
+            tag: None,
+        }),
+    }
+}
+
+impl Inventory for CreativeInventory {
+    fn size(&self) -> i16 {
+        self.slots.len() as i16
+    }
+
+    fn id(&self) -> i8 {
+        // Part of the player's own inventory window
+        0
+    }
+
+    fn name(&self) -> Option<&String> {
+        None
+    }
+
+    fn get_item(&self, slot: i16) -> &Option<Item> {
+        &self.slots[slot as usize].item
+    }
+
+    fn get_item_mut(&mut self, slot: i16) -> &mut Option<Item> {
+        self.dirty = true;
+        &mut self.slots[slot as usize].item
+    }
+
+    fn set_item(&mut self, slot: i16, item: Option<Item>) {
+        self.slots[slot as usize].item = item;
+        self.dirty = true;
+    }
+
+    fn init(
+        &mut self,
+        renderer: &mut Renderer,
+        ui_container: &mut Container,
+        inventory_window: &mut InventoryWindow,
+    ) {
+        inventory_window.elements.push(vec![]);
+        let basic_elements = inventory_window.elements.get_mut(0).unwrap();
+        let icon_scale = Hud::icon_scale(renderer);
+        let image = ui::ImageBuilder::new()
+            .texture_coords((0.0, 0.0, 195.0 / 256.0, 136.0 / 256.0))
+            .position(0.0, 0.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .size(icon_scale * 195.0, icon_scale * 136.0)
+            .texture("minecraft:gui/container/creative_inventory/tab_items")
+            .create(ui_container);
+        basic_elements.push(image);
+        self.window_size = (icon_scale * 195.0, icon_scale * 136.0);
+        let scale = icon_scale / 2.0;
+        inventory_window.text_elements.push(vec![]);
+        let basic_text_elements = inventory_window.text_elements.get_mut(0).unwrap();
+        let page_text = ui::TextBuilder::new()
+            .alignment(VAttach::Middle, HAttach::Center)
+            .scale_x(scale)
+            .scale_y(scale)
+            .position(0.0, -(icon_scale * 58.0))
+            .text("")
+            .colour((64, 64, 64, 255))
+            .shadow(false)
+            .create(ui_container);
+        basic_text_elements.push(page_text);
+        let tab_text = "Inventory";
+        let tab = (
+            icon_scale * 70.0,
+            -(icon_scale * 75.0),
+            renderer.ui.size_of_string(tab_text) * scale / 2.0,
+            icon_scale * 5.0,
+        );
+        let inventory_tab = ui::TextBuilder::new()
+            .alignment(VAttach::Middle, HAttach::Center)
+            .scale_x(scale)
+            .scale_y(scale)
+            .position(tab.0, tab.1)
+            .text(tab_text)
+            .create(ui_container);
+        basic_text_elements.push(inventory_tab);
+        self.inventory_tab = tab;
+        inventory_window.elements.push(vec![]);
+        inventory_window.text_elements.push(vec![]);
+        self.update_icons(renderer);
+    }
+
+    fn tick(
+        &mut self,
+        renderer: &mut Renderer,
+        ui_container: &mut Container,
+        inventory_window: &mut InventoryWindow,
+    ) {
+        if self.dirty {
+            self.dirty = false;
+            inventory_window.text_elements[0][0].borrow_mut().text =
+                format!("Items {}/{}", self.page + 1, self.pages());
+            inventory_window.elements.get_mut(1).unwrap().clear();
+            inventory_window.text_elements.get_mut(1).unwrap().clear();
+            for slot in self.slots.iter() {
+                if let Some(item) = slot.item.as_ref() {
+                    inventory_window.draw_item(item, slot.x, slot.y, 1, ui_container, renderer);
+                }
+            }
+        }
+    }
+
+    fn close(&mut self, _inventory_window: &mut InventoryWindow) {}
+
+    fn slot_at(&self, x: f64, y: f64) -> Option<i16> {
+        let (tab_x, tab_y, tab_width, tab_height) = self.inventory_tab;
+        if (x - tab_x).abs() <= tab_width && (y - tab_y).abs() <= tab_height {
+            return Some(INVENTORY_TAB_SLOT);
+        }
+        // There's nothing to drop outside of the window, so those clicks are ignored too
+        if x.abs() > self.window_size.0 / 2.0 || y.abs() > self.window_size.1 / 2.0 {
+            return None;
+        }
+        self.slots
+            .iter()
+            .position(|slot| {
+                (x - slot.x).abs() <= slot.size / 2.0 && (y - slot.y).abs() <= slot.size / 2.0
+            })
+            .map(|slot| slot as i16)
+    }
+
+    fn shift_click_target(&self, _slot: i16) -> Range<i16> {
+        0..0
+    }
+
+    fn handle_click(&mut self, slot: i16, action: ClickAction) -> bool {
+        if slot == INVENTORY_TAB_SLOT {
+            self.show_player_inventory = true;
+            return true;
+        }
+        let (material, damage) = match self.slots[slot as usize].item.as_ref() {
+            Some(item) => (item.material, item.stack.damage.unwrap_or(0)),
+            None => return true,
+        };
+        let (server, hotbar_index, game_mode) = {
+            let hud_context = self.hud_context.read();
+            (
+                hud_context.server.clone(),
+                hud_context.get_slot_index(),
+                hud_context.get_game_mode(),
+            )
+        };
+        if !matches!(game_mode, GameMode::Creative) {
+            return true;
+        }
+        let count = match action {
+            ClickAction::Right | ClickAction::ShiftRight => 1,
+            ClickAction::Left | ClickAction::ShiftLeft => MAX_STACK_SIZE,
+        };
+        let packet = give_packet(material, damage, count, hotbar_index, self.version);
+        self.player_inventory.write().set_item(
+            packet.slot,
+            packet
+                .clicked_item
+                .clone()
+                .map(|stack| Item { stack, material }),
+        );
+        if let Some(server) = server {
+            server.write_packet(packet);
+        }
+        true
+    }
+
+    fn on_scroll(&mut self, y: f64) {
+        if y > 0.0 && self.page > 0 {
+            self.show_page(self.page - 1);
+        } else if y < 0.0 {
+            self.show_page(self.page + 1);
+        }
+    }
+
+    fn take_replacement(&mut self) -> Option<Arc<RwLock<dyn Inventory + Sync + Send>>> {
+        if !self.show_player_inventory {
+            return None;
+        }
+        self.show_player_inventory = false;
+        Some(self.player_inventory.clone())
+    }
+
+    fn resize(
+        &mut self,
+        _width: u32,
+        _height: u32,
+        renderer: &mut Renderer,
+        ui_container: &mut Container,
+        inventory_window: &mut InventoryWindow,
+    ) {
+        inventory_window.clear_elements();
+        self.init(renderer, ui_container, inventory_window);
+    }
+
+    fn ty(&self) -> InventoryType {
+        InventoryType::Creative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn give_fills_selected_hotbar_slot() {
+        let packet = give_packet(Material::DiamondSword, 0, 1, 0, Version::V1_8);
+        assert_eq!(packet.slot, 36);
+        let stack = packet.clicked_item.unwrap();
+        assert_eq!((stack.id, stack.count), (276, 1));
+
+        let packet = give_packet(Material::Stone, 0, MAX_STACK_SIZE, 8, Version::V1_12);
+        assert_eq!(packet.slot, 44);
+        let stack = packet.clicked_item.unwrap();
+        assert_eq!((stack.id, stack.count, stack.damage), (1, 64, Some(0)));
+
+        // Variants are picked by their damage before 1.13
+        let stack = give_packet(Material::Wool, 14, 1, 0, Version::V1_12)
+            .clicked_item
+            .unwrap();
+        assert_eq!((stack.id, stack.damage), (35, Some(14)));
+    }
+
+    #[test]
+    fn variants_are_listed_before_the_flattening() {
+        let items = creative_items(Version::V1_12);
+        let damages = |listed: fn(&Material) -> bool| {
+            items
+                .iter()
+                .filter(|(material, _)| listed(material))
+                .map(|&(_, damage)| damage)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            damages(|material| matches!(material, Material::Wool)),
+            (0..16).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            damages(|material| matches!(material, Material::DiamondSword)),
+            vec![0]
+        );
+        // The list is worked out once
+        assert!(Arc::ptr_eq(&items, &creative_items(Version::V1_12)));
+    }
+}
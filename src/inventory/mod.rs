@@ -1,3 +1,4 @@
+pub mod creative_inventory;
 pub(crate) mod material;
 pub mod player_inventory;
 
@@ -6,11 +7,18 @@ use crate::render::hud::HudContext;
 use crate::render::inventory::InventoryWindow;
 use crate::render::Renderer;
 use crate::ui::Container;
+use crate::world::block::Tool;
 use leafish_protocol::item::Stack;
 use leafish_protocol::protocol::Version;
 use parking_lot::RwLock;
+use std::ops::Range;
 use std::sync::Arc;
 
+/// The slot a click outside of the window is sent for, dropping the carried stack.
+pub const SLOT_OUTSIDE: i16 = -999;
+/// How many items fit in a slot, ignoring items that stack to less.
+pub const MAX_STACK_SIZE: isize = 64;
+
 pub trait Inventory {
     fn size(&self) -> i16;
 
@@ -40,7 +48,25 @@ pub trait Inventory {
 
     fn close(&mut self, inventory_window: &mut InventoryWindow);
 
-    fn click_at(&self, cursor: (u32, u32)); // TODO: Pass mouse data (buttons, wheel etc and shift button state)
+    /// The slot under `(x, y)`, relative to the center of the screen in UI units.
+    /// Returns `SLOT_OUTSIDE` outside of the window and `None` on its background.
+    fn slot_at(&self, x: f64, y: f64) -> Option<i16>;
+
+    /// The slots a shift click on `slot` moves its stack into.
+    fn shift_click_target(&self, slot: i16) -> Range<i16>;
+
+    /// Handles a click on `slot` in place of the usual window click, returning whether
+    /// it did.
+    fn handle_click(&mut self, _slot: i16, _action: ClickAction) -> bool {
+        false
+    }
+
+    fn on_scroll(&mut self, _y: f64) {}
+
+    /// The inventory to show in place of this one, e.g. after switching tabs.
+    fn take_replacement(&mut self) -> Option<Arc<RwLock<dyn Inventory + Sync + Send>>> {
+        None
+    }
 
     fn resize(
         &mut self,
@@ -80,16 +106,19 @@ impl Slot {
 }
 
 pub struct InventoryContext {
+    /// The stack carried by the mouse cursor.
     pub cursor: Option<Item>,
     pub hotbar_index: u8,
     pub inventory: Option<Arc<RwLock<dyn Inventory + Send + Sync>>>,
     pub player_inventory: Arc<RwLock<PlayerInventory>>,
+    pub hud_context: Arc<RwLock<HudContext>>,
+    action_number: u16,
 }
 
 impl InventoryContext {
     pub fn new(
         version: Version,
-        renderer: &Renderer,
+        renderer: Option<&Renderer>,
         hud_context: Arc<RwLock<HudContext>>,
     ) -> Self {
         InventoryContext {
@@ -99,10 +128,150 @@ impl InventoryContext {
             player_inventory: Arc::new(RwLock::new(PlayerInventory::new(
                 version,
                 renderer,
-                hud_context,
+                hud_context.clone(),
             ))),
+            hud_context,
+            action_number: 0,
+        }
+    }
+
+    /// Applies a click on `slot` locally, moving items between it and the cursor,
+    /// and returns the action number to send the click with.
+    pub fn click(&mut self, inventory: &mut dyn Inventory, slot: i16, action: ClickAction) -> u16 {
+        self.action_number = self.action_number.wrapping_add(1);
+        if slot == SLOT_OUTSIDE {
+            if let Some(cursor) = self.cursor.as_mut() {
+                cursor.stack.count -= match action {
+                    ClickAction::Left | ClickAction::ShiftLeft => cursor.stack.count,
+                    ClickAction::Right | ClickAction::ShiftRight => 1,
+                };
+                if cursor.stack.count <= 0 {
+                    self.cursor = None;
+                }
+            }
+        } else if slot >= 0 && slot < inventory.size() {
+            match action {
+                ClickAction::Left | ClickAction::Right => click_slot(
+                    inventory.get_item_mut(slot),
+                    &mut self.cursor,
+                    action == ClickAction::Right,
+                ),
+                ClickAction::ShiftLeft | ClickAction::ShiftRight => {
+                    let target = inventory.shift_click_target(slot);
+                    move_stack(inventory, slot, target);
+                }
+            }
+        }
+        self.action_number
+    }
+}
+
+/// A click on an inventory slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickAction {
+    Left,
+    Right,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl ClickAction {
+    pub fn new(right: bool, shift: bool) -> ClickAction {
+        match (right, shift) {
+            (false, false) => ClickAction::Left,
+            (true, false) => ClickAction::Right,
+            (false, true) => ClickAction::ShiftLeft,
+            (true, true) => ClickAction::ShiftRight,
+        }
+    }
+
+    /// The `mode` and `button` fields of the click window packet.
+    pub fn mode_and_button(self) -> (u8, u8) {
+        match self {
+            ClickAction::Left => (0, 0),
+            ClickAction::Right => (0, 1),
+            ClickAction::ShiftLeft => (1, 0),
+            ClickAction::ShiftRight => (1, 1),
+        }
+    }
+}
+
+fn stacks_with(a: &Item, b: &Item) -> bool {
+    a.stack.id == b.stack.id && a.stack.damage == b.stack.damage
+}
+
+/// Picks up, places, merges or swaps stacks between a slot and the cursor. Right clicks
+/// pick up half of the slot or place a single item.
+fn click_slot(slot: &mut Option<Item>, cursor: &mut Option<Item>, right: bool) {
+    match (slot.as_mut(), cursor.as_mut()) {
+        (None, None) => {}
+        (Some(item), None) => {
+            if right && item.stack.count > 1 {
+                let mut half = item.clone();
+                half.stack.count = (item.stack.count + 1) / 2;
+                item.stack.count -= half.stack.count;
+                *cursor = Some(half);
+            } else {
+                *cursor = slot.take();
+            }
+        }
+        (None, Some(carried)) => {
+            if right && carried.stack.count > 1 {
+                let mut one = carried.clone();
+                one.stack.count = 1;
+                carried.stack.count -= 1;
+                *slot = Some(one);
+            } else {
+                *slot = cursor.take();
+            }
+        }
+        (Some(item), Some(carried)) => {
+            if stacks_with(item, carried) {
+                let wanted = if right { 1 } else { carried.stack.count };
+                let moved = wanted.min(MAX_STACK_SIZE - item.stack.count).max(0);
+                item.stack.count += moved;
+                carried.stack.count -= moved;
+                if carried.stack.count <= 0 {
+                    *cursor = None;
+                }
+            } else {
+                std::mem::swap(slot, cursor);
+            }
+        }
+    }
+}
+
+/// Moves the stack in `slot` into `target`, topping up matching stacks before
+/// filling empty slots.
+fn move_stack(inventory: &mut dyn Inventory, slot: i16, target: Range<i16>) {
+    let mut item = match inventory.get_item_mut(slot).take() {
+        Some(item) => item,
+        None => return,
+    };
+    for other in target.clone() {
+        if let Some(other) = inventory.get_item_mut(other).as_mut() {
+            if stacks_with(other, &item) {
+                let moved = item
+                    .stack
+                    .count
+                    .min(MAX_STACK_SIZE - other.stack.count)
+                    .max(0);
+                other.stack.count += moved;
+                item.stack.count -= moved;
+                if item.stack.count <= 0 {
+                    return;
+                }
+            }
         }
     }
+    for other in target {
+        let other = inventory.get_item_mut(other);
+        if other.is_none() {
+            *other = Some(item);
+            return;
+        }
+    }
+    inventory.set_item(slot, Some(item));
 }
 
 pub enum InventoryType {
@@ -118,15 +287,16 @@ pub enum InventoryType {
     Horse,
     Merchant,
     EntityEquipment,
+    Creative,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Item {
     pub stack: Stack,
     pub material: Material,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Material {
     Air,                             // 1.7.10 (id: 0, stack: 0)| 1.13 (id: 9648)
     Stone,                           // 1.7.10 (id: 1)| 1.13 (id: 22948)
@@ -1554,6 +1724,47 @@ impl Material {
         format!("{:?}", self)
     }
 
+    /// The kind of tool this item is and how much faster it breaks the blocks it's made for.
+    pub fn tool(&self) -> Option<(Tool, f64)> {
+        use Material::*;
+        Some(match self {
+            WoodPickaxe | WoodenPickaxe => (Tool::Pickaxe, 2.0),
+            StonePickaxe => (Tool::Pickaxe, 4.0),
+            IronPickaxe => (Tool::Pickaxe, 6.0),
+            DiamondPickaxe => (Tool::Pickaxe, 8.0),
+            NetheritePickaxe => (Tool::Pickaxe, 9.0),
+            GoldPickaxe | GoldenPickaxe => (Tool::Pickaxe, 12.0),
+            WoodAxe | WoodenAxe => (Tool::Axe, 2.0),
+            StoneAxe => (Tool::Axe, 4.0),
+            IronAxe => (Tool::Axe, 6.0),
+            DiamondAxe => (Tool::Axe, 8.0),
+            NetheriteAxe => (Tool::Axe, 9.0),
+            GoldAxe | GoldenAxe => (Tool::Axe, 12.0),
+            WoodSpade | WoodenShovel => (Tool::Shovel, 2.0),
+            StoneSpade | StoneShovel => (Tool::Shovel, 4.0),
+            IronSpade | IronShovel => (Tool::Shovel, 6.0),
+            DiamondSpade | DiamondShovel => (Tool::Shovel, 8.0),
+            NetheriteShovel => (Tool::Shovel, 9.0),
+            GoldSpade | GoldenShovel => (Tool::Shovel, 12.0),
+            WoodSword | WoodenSword | StoneSword | IronSword | DiamondSword | NetheriteSword
+            | GoldSword | GoldenSword => (Tool::Sword, 15.0),
+            Shears => (Tool::Shears, 5.0),
+            _ => return None,
+        })
+    }
+
+    /// The name shown for items that weren't renamed, split into words.
+    pub fn display_name(&self) -> String {
+        let mut result = String::new();
+        for (i, c) in self.name().chars().enumerate() {
+            if i != 0 && c.is_uppercase() {
+                result.push(' ');
+            }
+            result.push(c);
+        }
+        result
+    }
+
     pub fn texture_locations(&self) -> (String, String) {
         // TODO: Compute this at compile time and only lookup at runtime in (O(1))
         let mut result = String::new();
@@ -1570,3 +1781,48 @@ impl Material {
         (format!("items/{}", result), format!("blocks/{}", result))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stone(count: isize) -> Option<Item> {
+        Some(Item {
+            stack: Stack {
+                id: 1,
+                count,
+                damage: Some(0),
+                tag: None,
+            },
+            material: Material::Stone,
+        })
+    }
+
+    fn count(item: &Option<Item>) -> isize {
+        item.as_ref().map_or(0, |item| item.stack.count)
+    }
+
+    #[test]
+    fn click_actions_to_packet_fields() {
+        assert_eq!(ClickAction::new(false, false).mode_and_button(), (0, 0));
+        assert_eq!(ClickAction::new(true, false).mode_and_button(), (0, 1));
+        assert_eq!(ClickAction::new(false, true).mode_and_button(), (1, 0));
+        assert_eq!(ClickAction::new(true, true).mode_and_button(), (1, 1));
+
+        // Right clicking picks up half, rounding up, and places one at a time
+        let (mut slot, mut cursor) = (stone(5), None);
+        click_slot(&mut slot, &mut cursor, true);
+        assert_eq!((count(&slot), count(&cursor)), (2, 3));
+        let mut empty = None;
+        click_slot(&mut empty, &mut cursor, true);
+        assert_eq!((count(&empty), count(&cursor)), (1, 2));
+
+        // Left clicking merges as much as fits and keeps the rest on the cursor
+        let (mut slot, mut cursor) = (stone(60), stone(10));
+        click_slot(&mut slot, &mut cursor, false);
+        assert_eq!((count(&slot), count(&cursor)), (64, 6));
+        click_slot(&mut empty, &mut cursor, false);
+        assert_eq!((count(&empty), count(&cursor)), (7, 0));
+        assert!(cursor.is_none());
+    }
+}
@@ -1,9 +1,10 @@
-use crate::inventory::{Inventory, InventoryType, Item, Material, Slot};
+use crate::inventory::{Inventory, InventoryType, Item, Material, Slot, SLOT_OUTSIDE};
 use crate::render::hud::{Hud, HudContext};
 use crate::render::inventory::InventoryWindow;
 use crate::render::Renderer;
 use crate::ui;
 use crate::ui::{Container, HAttach, VAttach};
+use std::ops::Range;
 use std::sync::Arc;
 
 use leafish_protocol::protocol::Version;
@@ -11,6 +12,8 @@ use parking_lot::RwLock;
 
 pub struct PlayerInventory {
     slots: Vec<Slot>,
+    /// The size of the window's background, in UI units.
+    window_size: (f64, f64),
     dirty: bool,
     version: Version,
     hud_context: Arc<RwLock<HudContext>>,
@@ -19,10 +22,11 @@ pub struct PlayerInventory {
 impl PlayerInventory {
     pub fn new(
         version: Version,
-        renderer: &Renderer,
+        renderer: Option<&Renderer>,
         hud_context: Arc<RwLock<HudContext>>,
     ) -> Self {
-        let scale = Hud::icon_scale(renderer);
+        // Without a renderer the slot positions don't matter, they're updated before drawing
+        let scale = renderer.map_or_else(|| Hud::icon_scale_by_height(0), Hud::icon_scale);
         let size = scale * 16.0;
         let x_offset = -(size * 4.5);
         let y_offset = size * 4.25;
@@ -143,6 +147,7 @@ impl PlayerInventory {
         }
         PlayerInventory {
             slots,
+            window_size: (scale * 176.0, scale * 166.0),
             dirty: false,
             version,
             hud_context,
@@ -242,7 +247,7 @@ impl Inventory for PlayerInventory {
     }
 
     fn id(&self) -> i8 {
-        -1
+        0
     }
 
     fn name(&self) -> Option<&String> {
@@ -282,6 +287,7 @@ impl Inventory for PlayerInventory {
             .texture("minecraft:gui/container/inventory")
             .create(ui_container);
         basic_elements.push(image);
+        self.window_size = (icon_scale * 176.0, icon_scale * 166.0);
         if self.version < Version::V1_9 {
             // Removes the 2nd hand slot from the inv by rendering the background color over it.
             let image = ui::ImageBuilder::new()
@@ -312,6 +318,7 @@ impl Inventory for PlayerInventory {
             .create(ui_container);
         basic_text_elements.push(crafting_text);
         inventory_window.elements.push(vec![]);
+        inventory_window.text_elements.push(vec![]);
         self.update_icons(renderer);
         self.hud_context.clone().write().dirty_slots = true;
     }
@@ -325,6 +332,7 @@ impl Inventory for PlayerInventory {
         if self.dirty {
             self.dirty = false;
             inventory_window.elements.get_mut(1).unwrap().clear();
+            inventory_window.text_elements.get_mut(1).unwrap().clear();
             for slot in self.slots.iter() {
                 if slot.item.is_some() {
                     inventory_window.draw_item(
@@ -344,8 +352,25 @@ impl Inventory for PlayerInventory {
         // TODO
     }
 
-    fn click_at(&self, _cursor: (u32, u32)) {
-        // TODO
+    fn slot_at(&self, x: f64, y: f64) -> Option<i16> {
+        if x.abs() > self.window_size.0 / 2.0 || y.abs() > self.window_size.1 / 2.0 {
+            return Some(SLOT_OUTSIDE);
+        }
+        self.slots
+            .iter()
+            .position(|slot| {
+                (x - slot.x).abs() <= slot.size / 2.0 && (y - slot.y).abs() <= slot.size / 2.0
+            })
+            .map(|slot| slot as i16)
+    }
+
+    fn shift_click_target(&self, slot: i16) -> Range<i16> {
+        match slot {
+            // Between the main inventory and the hotbar
+            9..=35 => 36..45,
+            36..=44 => 9..36,
+            _ => 9..45,
+        }
     }
 
     fn resize(
@@ -0,0 +1,145 @@
+//! Finds worlds opened to LAN, which announce themselves over UDP multicast.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use instant::{Duration, Instant};
+use log::{debug, warn};
+use parking_lot::Mutex;
+
+const MULTICAST_ADDRESS: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+const MULTICAST_PORT: u16 = 4445;
+/// Worlds announce themselves every 1.5 seconds, ones not heard from for this long are
+/// assumed to be closed.
+const EXPIRY: Duration = Duration::from_secs(5);
+/// How long to wait before trying to listen again when the port is taken.
+const BIND_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A world opened to LAN.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LanServer {
+    pub motd: String,
+    pub address: String,
+}
+
+/// Parses a `[MOTD]message[/MOTD][AD]port[/AD]` broadcast into the message of the day and
+/// the port, `None` if it's malformed.
+pub fn parse_broadcast(broadcast: &str) -> Option<(String, u16)> {
+    let motd = between(broadcast, "[MOTD]", "[/MOTD]")?;
+    let port = between(broadcast, "[AD]", "[/AD]")?.trim().parse().ok()?;
+    Some((motd.to_owned(), port))
+}
+
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let from = text.find(start)? + start.len();
+    let to = from + text[from..].find(end)?;
+    Some(&text[from..to])
+}
+
+/// Listens for LAN broadcasts on a background thread until dropped.
+pub struct LanScanner {
+    servers: Arc<Mutex<HashMap<String, (LanServer, Instant)>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl LanScanner {
+    pub fn start() -> LanScanner {
+        let servers = Arc::new(Mutex::new(HashMap::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let servers = servers.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                // The port stays taken for a moment after the previous scanner was dropped,
+                // or for as long as another client listens on it
+                let mut warned = false;
+                while !stop.load(Ordering::Relaxed) {
+                    match Self::bind() {
+                        Ok(socket) => return Self::listen(socket, servers, stop),
+                        Err(err) if !warned => {
+                            warn!("Failed to listen for LAN worlds, retrying: {}", err);
+                            warned = true;
+                        }
+                        Err(_) => {}
+                    }
+                    thread::sleep(BIND_RETRY_INTERVAL);
+                }
+            });
+        }
+        LanScanner { servers, stop }
+    }
+
+    fn bind() -> io::Result<UdpSocket> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, MULTICAST_PORT))?;
+        socket.join_multicast_v4(&MULTICAST_ADDRESS, &Ipv4Addr::UNSPECIFIED)?;
+        // Wakes up now and then to notice the scanner was dropped
+        socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+        Ok(socket)
+    }
+
+    fn listen(
+        socket: UdpSocket,
+        servers: Arc<Mutex<HashMap<String, (LanServer, Instant)>>>,
+        stop: Arc<AtomicBool>,
+    ) {
+        let mut buf = [0; 1024];
+        while !stop.load(Ordering::Relaxed) {
+            let (len, from) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                // Timed out
+                Err(_) => continue,
+            };
+            let broadcast = String::from_utf8_lossy(&buf[..len]);
+            match parse_broadcast(&broadcast) {
+                Some((motd, port)) => {
+                    let address = format!("{}:{}", from.ip(), port);
+                    let server = LanServer {
+                        motd,
+                        address: address.clone(),
+                    };
+                    servers.lock().insert(address, (server, Instant::now()));
+                }
+                None => debug!("Ignoring malformed LAN broadcast from {}", from),
+            }
+        }
+    }
+
+    /// The worlds heard from recently, ordered by address.
+    pub fn servers(&self) -> Vec<LanServer> {
+        let mut servers = self.servers.lock();
+        let now = Instant::now();
+        servers.retain(|_, (_, seen)| now.duration_since(*seen) < EXPIRY);
+        let mut found = servers
+            .values()
+            .map(|(server, _)| server.clone())
+            .collect::<Vec<_>>();
+        found.sort_by(|a, b| a.address.cmp(&b.address));
+        found
+    }
+}
+
+impl Drop for LanScanner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broadcast_is_parsed() {
+        assert_eq!(
+            parse_broadcast("[MOTD]Steve - New World[/MOTD][AD]51234[/AD]"),
+            Some(("Steve - New World".to_owned(), 51234))
+        );
+        assert_eq!(parse_broadcast("[MOTD]No port[/MOTD]"), None);
+        assert_eq!(parse_broadcast("[MOTD]Bad port[/MOTD][AD]a lot[/AD]"), None);
+        assert_eq!(parse_broadcast("[AD]25565[/AD]"), None);
+    }
+}
@@ -37,25 +37,32 @@ pub mod chunk_builder;
 pub mod console;
 pub mod entity;
 mod inventory;
+mod lan;
 pub mod model;
+mod mouse;
+pub mod particle;
 pub mod paths;
 pub mod render;
 pub mod resources;
 pub mod screen;
+mod screenshot;
 pub mod server;
+pub mod servers;
 pub mod settings;
+pub mod sound;
 pub mod ui;
 pub mod world;
 
 use crate::protocol::mojang;
-use crate::render::hud::HudContext;
-use leafish_protocol::format::{Component, TextComponent};
+use crate::render::hud::{Hud, HudContext};
 use leafish_protocol::protocol::{Error, Version};
 use parking_lot::Mutex;
 use parking_lot::RwLock;
 use std::cell::RefCell;
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::thread;
 
@@ -68,9 +75,46 @@ const CL_BRAND: console::CVar<String> = console::CVar {
                   \"Vanilla\"",
     mutable: false,
     serializable: false,
+    range: None,
     default: &|| "Leafish".to_owned(),
 };
 
+const CONNECT: console::Command = console::Command {
+    name: "connect",
+    description: "leaves the current server and connects to the given address",
+    run: connect_command,
+};
+
+fn connect_command(game: &mut Game, args: &[&str]) {
+    let result = run_connect(args, |address| {
+        if let Some(server) = game.server.as_ref() {
+            server.disconnect(None);
+            game.leave_server();
+        }
+        info!("Connecting to {}", address);
+        screen::direct_connect::DirectConnect::connect(game, address);
+    });
+    if let Err(err) = result {
+        warn!("{}", err);
+    }
+}
+
+/// Validates the `connect` command's arguments before passing the address on to `connect`.
+fn run_connect<F: FnOnce(&str)>(args: &[&str], connect: F) -> Result<(), String> {
+    let address = match args {
+        [address] => address.trim(),
+        _ => {
+            return Err(format!(
+                "Usage: {}connect <address>",
+                console::COMMAND_PREFIX
+            ))
+        }
+    };
+    servers::validate_address(address)?;
+    connect(address);
+    Ok(())
+}
+
 pub struct Game {
     renderer: Arc<RwLock<render::Renderer>>,
     screen_sys: screen::ScreenSystem,
@@ -82,18 +126,198 @@ pub struct Game {
     server: Option<Arc<server::Server>>,
     focused: bool,
     chunk_builder: chunk_builder::ChunkBuilder,
+    sound: sound::SoundManager,
 
     connect_error: Option<Error>,
 
     dpi_factor: f64,
     last_mouse_x: f64,
     last_mouse_y: f64,
-    last_mouse_xrel: f64,
-    last_mouse_yrel: f64,
+    mouse_motion: mouse::MotionTracker,
+    scroll_remainder: (f64, f64),
+    /// Logical size of the window when it was last neither maximized nor fullscreen.
+    window_size: (i64, i64),
+    /// Position of the window when it was last neither maximized nor fullscreen.
+    window_position: Option<(i64, i64)>,
+    /// Frame time not yet consumed by a fixed logic tick, in 60fps frame units.
+    tick_accumulator: f64,
+    /// How far rendering is between the last logic tick and the next, from 0 to 1.
+    partial_tick: f64,
     is_ctrl_pressed: bool,
+    is_shift_pressed: bool,
     is_logo_pressed: bool,
     is_fullscreen: bool,
+    /// Set by the screenshot key, the frame is captured once it has been drawn.
+    screenshot_requested: bool,
+    frame_times: render::frame_times::FrameTimes,
+    /// When the next frame is due while `R_MAX_FPS` limits the frame rate.
+    frame_deadline: Instant,
     default_protocol_version: i32,
+
+    last_address: Option<String>,
+    last_hud_context: Option<Arc<RwLock<HudContext>>>,
+    /// Reconnects after kicks in a row, limited to `CL_RECONNECT_ATTEMPTS` so that a
+    /// server kicking right away isn't reconnected to forever.
+    auto_reconnects: u32,
+    /// The reconnect in progress, see `Game::reconnect`.
+    reconnecting: Option<Reconnecting>,
+    /// The language the translations were last loaded for, see `CL_LANGUAGE`.
+    language: String,
+    /// `Vars::changes` when the vars were last applied, see `Game::apply_changed_vars`.
+    var_changes: Option<u64>,
+}
+
+/// Length of a logic tick in 60fps frame units, giving a fixed 20 ticks per second.
+const TICK_LENGTH: f64 = 3.0;
+/// Caps the ticks run in a single frame so a long stall doesn't snowball.
+const MAX_TICKS_PER_FRAME: u32 = 10;
+
+/// Adds the frame's delta to the accumulator and returns how many logic ticks are due,
+/// leaving the leftover time in the accumulator.
+fn fixed_ticks(accumulator: &mut f64, delta: f64) -> u32 {
+    *accumulator += delta;
+    let ticks = (*accumulator / TICK_LENGTH).floor();
+    *accumulator -= ticks * TICK_LENGTH;
+    (ticks as u32).min(MAX_TICKS_PER_FRAME)
+}
+
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Returns how long to wait before the given (zero based) reconnect attempt.
+fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_BASE_DELAY
+        .checked_mul(1 << attempt.min(16))
+        .map_or(RECONNECT_MAX_DELAY, |delay| delay.min(RECONNECT_MAX_DELAY))
+}
+
+type ConnectResult = Result<Arc<server::Server>, Error>;
+
+/// Reconnecting to the last server, one attempt at a time without holding up the frame.
+struct Reconnecting {
+    /// The zero based attempt being made or waited for.
+    attempt: u32,
+    attempts: u32,
+    next_try: Instant,
+    /// The attempt being made on another thread, `None` while waiting for the next one.
+    pending: Option<mpsc::Receiver<ConnectResult>>,
+}
+
+impl Reconnecting {
+    fn new(attempts: u32, now: Instant) -> Reconnecting {
+        Reconnecting {
+            attempt: 0,
+            attempts: attempts.max(1),
+            next_try: now,
+            pending: None,
+        }
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.pending.is_none() && now >= self.next_try
+    }
+
+    /// The result of the attempt being made, once it finished. An attempt whose thread
+    /// went away without a result failed.
+    fn take_result(&mut self) -> Option<ConnectResult> {
+        match self.pending.as_ref()?.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(Error::Err("Unknown".to_string()))),
+        }
+    }
+
+    /// Schedules the next attempt after a failed one, returns false once all attempts
+    /// were made.
+    fn retry(&mut self, now: Instant) -> bool {
+        self.pending = None;
+        if self.attempt + 1 >= self.attempts {
+            return false;
+        }
+        self.next_try = now + reconnect_delay(self.attempt);
+        self.attempt += 1;
+        true
+    }
+}
+
+/// Whether a kick with the plain text `reason` should reconnect, because it contains one
+/// of the comma separated `messages`. Case is ignored.
+fn is_auto_reconnect_message(reason: &str, messages: &str) -> bool {
+    let reason = reason.to_lowercase();
+    messages
+        .split(',')
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .any(|message| reason.contains(&message.to_lowercase()))
+}
+
+/// Pings the server to detect its protocol version and Forge mods, falling back to the
+/// default protocol version if the ping fails.
+fn ping_server(
+    address: &str,
+    default_protocol_version: i32,
+) -> Result<
+    (
+        i32,
+        Vec<protocol::forge::ForgeMod>,
+        Option<i64>,
+        Option<protocol::forge::fml2::ForgeData>,
+    ),
+    Error,
+> {
+    let (protocol_version, forge_mods, fml_network_version, forge_data) =
+        match protocol::Conn::new(address, default_protocol_version)
+            .and_then(|conn| conn.do_status())
+        {
+            Ok(res) => {
+                info!(
+                    "Detected server protocol version {}",
+                    res.0.version.protocol
+                );
+                (
+                    res.0.version.protocol,
+                    res.0.forge_mods,
+                    res.0.fml_network_version,
+                    res.0.forge_data,
+                )
+            }
+            Err(err) => {
+                warn!(
+                    "Error pinging server {} to get protocol version: {:?}, defaulting to {}",
+                    address, err, default_protocol_version
+                );
+                (default_protocol_version, vec![], None, None)
+            }
+        };
+    if !Version::from_id(protocol_version as u32).is_supported() {
+        return Err(Error::Err(format!(
+            "The server's version isn't supported!\n(protocol version: {})",
+            protocol_version
+        )));
+    }
+    Ok((
+        protocol_version,
+        forge_mods,
+        fml_network_version,
+        forge_data,
+    ))
+}
+
+fn keep_alive_timeout(vars: &console::Vars) -> Duration {
+    Duration::from_secs((*vars.get(settings::CL_KEEP_ALIVE_TIMEOUT)).max(1) as u64)
+}
+
+fn create_profile(vars: &console::Vars) -> mojang::Profile {
+    let mut profile = mojang::Profile {
+        username: vars.get(auth::CL_USERNAME).clone(),
+        id: vars.get(auth::CL_UUID).clone(),
+        access_token: vars.get(auth::AUTH_TOKEN).clone(),
+    };
+    if profile.access_token.is_empty() {
+        // Offline servers derive the uuid from the username, do the same so it stays stable
+        profile.id = mojang::offline_uuid(&profile.username).to_string();
+    }
+    profile
 }
 
 impl Game {
@@ -102,76 +326,173 @@ impl Game {
         address: &str,
         hud_context: Arc<RwLock<HudContext>>,
     ) -> Result<(), Error> {
-        let (protocol_version, forge_mods, fml_network_version) =
-            match protocol::Conn::new(address, self.default_protocol_version)
-                .and_then(|conn| conn.do_status())
-            {
-                Ok(res) => {
-                    info!(
-                        "Detected server protocol version {}",
-                        res.0.version.protocol
-                    );
-                    (
-                        res.0.version.protocol,
-                        res.0.forge_mods,
-                        res.0.fml_network_version,
-                    )
-                }
-                Err(err) => {
-                    warn!(
-                        "Error pinging server {} to get protocol version: {:?}, defaulting to {}",
-                        address, err, self.default_protocol_version
-                    );
-                    (self.default_protocol_version, vec![], None)
-                }
-            };
-        if !Version::from_id(protocol_version as u32).is_supported() {
-            return Err(Error::Err(format!(
-                "The server's version isn't supported!\n(protocol version: {})",
-                protocol_version
-            )));
-        }
-        let address = address.to_owned();
+        self.last_address = Some(address.to_owned());
+        self.last_hud_context = Some(hud_context.clone());
+        let result = self
+            .spawn_connect(address, hud_context)
+            .recv()
+            .unwrap_or_else(|_| Err(Error::Err("Unknown".to_string())));
+        self.finish_connect(address, result)
+    }
+
+    /// Pings and joins `address` on another thread, sending the server over the returned
+    /// channel once joined.
+    fn spawn_connect(
+        &self,
+        address: &str,
+        hud_context: Arc<RwLock<HudContext>>,
+    ) -> mpsc::Receiver<ConnectResult> {
+        let server_address = address.to_owned();
+        let default_protocol_version = self.default_protocol_version;
         let resources = self.resource_manager.clone();
-        let profile = mojang::Profile {
-            username: self.vars.get(auth::CL_USERNAME).clone(),
-            id: self.vars.get(auth::CL_UUID).clone(),
-            access_token: self.vars.get(auth::AUTH_TOKEN).clone(),
-        };
+        let profile = create_profile(&self.vars);
         let renderer = self.renderer.clone();
-        let result = thread::spawn(move || {
-            server::Server::connect(
-                resources,
-                profile,
-                &address,
-                protocol_version,
-                forge_mods,
-                fml_network_version,
-                renderer,
-                hud_context.clone(),
-            )
-        })
-        .join();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = ping_server(&server_address, default_protocol_version).and_then(
+                |(protocol_version, forge_mods, fml_network_version, forge_data)| {
+                    server::Server::connect(
+                        resources,
+                        profile,
+                        &server_address,
+                        protocol_version,
+                        forge_mods,
+                        fml_network_version,
+                        forge_data,
+                        Some(renderer),
+                        hud_context,
+                    )
+                },
+            );
+            // Nobody waits for the result anymore if the game was closed meanwhile
+            let _ = tx.send(result);
+        });
+        rx
+    }
+
+    /// Switches to the server joined by `spawn_connect`, or keeps the error.
+    fn finish_connect(&mut self, address: &str, result: ConnectResult) -> Result<(), Error> {
         match result {
-            Ok(result) => {
-                match result {
-                    Ok(srv) => {
-                        self.server = Some(srv);
-                        Ok(())
-                    }
-                    Err(err) => {
-                        let str = err.to_string();
-                        self.connect_error = Some(err);
-                        // self.server.disconnect_reason = Some(Component::from_string(&*err.to_string()));
-                        Err(Error::Err(str))
-                    }
+            Ok(srv) => {
+                srv.set_keep_alive_timeout(keep_alive_timeout(&self.vars));
+                srv.set_render_distance(settings::render_distance(&self.vars));
+                self.server = Some(srv);
+                self.remember_recent_server(address);
+                Ok(())
+            }
+            Err(err) => {
+                let returned = copy_connect_error(&err);
+                self.connect_error = Some(err);
+                Err(returned)
+            }
+        }
+    }
+
+    /// Drops the disconnected server along with its screens and resources.
+    fn leave_server(&mut self) {
+        // The Hud, the terrain loading screen and anything opened over them
+        self.screen_sys.clear();
+        self.server = None;
+        self.resource_manager.write().clear_server_resource_pack();
+        self.renderer.clone().write().reset();
+        self.chunk_builder.reset();
+        self.focused = false;
+    }
+
+    /// Applies the vars that take effect outside of their own use, once after they changed.
+    fn apply_changed_vars(&mut self) {
+        let changes = self.vars.changes();
+        if self.var_changes == Some(changes) {
+            return;
+        }
+        self.var_changes = Some(changes);
+        protocol::set_default_port(settings::default_port(&self.vars));
+        protocol::socks::set_proxy(&self.vars.get(settings::CL_PROXY));
+        if let Some(server) = self.server.as_ref() {
+            server.set_render_distance(settings::render_distance(&self.vars));
+        }
+        if *self.vars.get(settings::CL_LANGUAGE) != self.language {
+            self.load_translations();
+        }
+    }
+
+    /// Loads the translations of the language set in `CL_LANGUAGE` from the resource packs.
+    /// Screens opened from then on use them, as does chat when it's shown.
+    fn load_translations(&mut self) {
+        let language = self.vars.get(settings::CL_LANGUAGE).clone();
+        format::set_translations(self.resource_manager.read().load_translations(&language));
+        self.language = language;
+    }
+
+    /// Adds `address` to the front of the direct connect history.
+    fn remember_recent_server(&self, address: &str) {
+        let mut recent = servers::parse_recent_servers(&self.vars.get(settings::CL_RECENT_SERVERS));
+        servers::remember_recent_server(&mut recent, address);
+        self.vars.set(settings::CL_RECENT_SERVERS, recent.join(","));
+    }
+
+    /// Starts connecting to the last server again. The attempts are made from
+    /// `poll_reconnect`, retrying with an exponential backoff.
+    pub fn reconnect(&mut self) -> Result<(), Error> {
+        if self.last_address.is_none() || self.last_hud_context.is_none() {
+            return Err(Error::Err("No previous server to reconnect to".to_string()));
+        }
+        let attempts = (*self.vars.get(settings::CL_RECONNECT_ATTEMPTS)).max(1) as u32;
+        self.reconnecting = Some(Reconnecting::new(attempts, Instant::now()));
+        Ok(())
+    }
+
+    /// Makes the next reconnect attempt once it's due, or takes the result of the one
+    /// being made. Returns how reconnecting ended once it did, the last error being kept
+    /// in `connect_error` too.
+    fn poll_reconnect(&mut self) -> Option<Result<(), Error>> {
+        let mut reconnecting = self.reconnecting.take()?;
+        let address = self.last_address.clone().unwrap();
+        let now = Instant::now();
+        if reconnecting.is_due(now) {
+            info!(
+                "Reconnecting to {} (attempt {}/{})",
+                address,
+                reconnecting.attempt + 1,
+                reconnecting.attempts
+            );
+            let hud_context = self.last_hud_context.clone().unwrap();
+            reconnecting.pending = Some(self.spawn_connect(&address, hud_context));
+        }
+        let result = match reconnecting.take_result() {
+            Some(result) => result,
+            None => {
+                self.reconnecting = Some(reconnecting);
+                return None;
+            }
+        };
+        match self.finish_connect(&address, result) {
+            Ok(()) => {
+                self.connect_error = None;
+                Some(Ok(()))
+            }
+            Err(err) => {
+                warn!("Reconnecting to {} failed: {}", address, err);
+                if reconnecting.retry(now) {
+                    self.reconnecting = Some(reconnecting);
+                    None
+                } else {
+                    Some(Err(err))
                 }
             }
-            Err(_) => Err(Error::Err("Unknown".to_string())),
         }
     }
 }
 
+/// Copies a connection error to return it while keeping the original in `connect_error`.
+/// A disconnect keeps its formatted reason, everything else is turned into its message.
+fn copy_connect_error(err: &Error) -> Error {
+    match err {
+        Error::Disconnect(reason) => Error::Disconnect(reason.clone()),
+        err => Error::Err(err.to_string()),
+    }
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "leafish")]
 struct Opt {
@@ -187,23 +508,246 @@ struct Opt {
     #[structopt(short = "n", long = "network-debug")]
     network_debug: bool,
 
-    /// Parse a network packet from a file
-    #[structopt(short = "N", long = "network-parse-packet")]
-    network_parse_packet: Option<String>,
+    /// Record how long each packet type takes to decode, printed on disconnect
+    #[structopt(long = "packet-timings")]
+    packet_timings: bool,
+
+    /// Record the packets received from the server to a file, replacing it on every
+    /// connection
+    #[structopt(long = "record-packets", parse(from_os_str))]
+    record_packets: Option<PathBuf>,
+
+    /// Also record the packets sent to the server with --record-packets
+    #[structopt(long = "record-serverbound", requires = "record_packets")]
+    record_serverbound: bool,
+
+    /// Reject VarInts with padding or overflowing bytes instead of decoding them like the
+    /// vanilla client does, e.g. to validate --replay-packets recordings
+    #[structopt(long = "strict-varints")]
+    strict_varints: bool,
+
+    /// Decode and log the packets of a file written by --record-packets
+    #[structopt(long = "replay-packets", parse(from_os_str))]
+    replay_packets: Option<PathBuf>,
+
+    /// Parse a network packet from a file, optionally followed by the protocol state
+    /// (handshaking, status, login or play; defaults to play)
+    #[structopt(
+        short = "N",
+        long = "network-parse-packet",
+        min_values = 1,
+        max_values = 2
+    )]
+    network_parse_packet: Option<Vec<String>>,
 
     /// Protocol version to use in the autodetection ping
     #[structopt(short = "p", long = "default-protocol-version")]
     default_protocol_version: Option<String>,
+
+    /// Connect to the server given by --server without creating a window. Only the network
+    /// and world state are ticked: nothing is rendered, entities aren't simulated (so the
+    /// player doesn't move or fall) and chat messages are only written to the log.
+    #[structopt(long = "headless", requires = "server")]
+    headless: bool,
+
+    /// Ping the given server, print its status to stdout and exit without creating a window
+    #[structopt(long = "ping")]
+    ping: Option<String>,
+
+    /// Print the status from --ping as JSON instead of key/value lines
+    #[structopt(long = "json", requires = "ping")]
+    json: bool,
+}
+
+const HEADLESS_TICK: Duration = Duration::from_millis(50);
+
+/// Resolves `Opt::default_protocol_version`, logging which version was selected.
+fn parse_default_protocol_version(name: Option<String>) -> i32 {
+    let version = protocol::versions::protocol_name_to_protocol_version(
+        name.unwrap_or_else(|| "".to_string()),
+    );
+    info!(
+        "Default protocol version: {} ({})",
+        version,
+        protocol::versions::protocol_version_to_protocol_name(version)
+            .unwrap_or_else(|| "unknown".to_string())
+    );
+    version
+}
+
+/// Pings `address` and prints its status to stdout, see `Opt::ping`. Returns whether the
+/// ping succeeded.
+fn run_ping(address: &str, default_protocol_version: i32, json: bool) -> bool {
+    let (status, ping) = match protocol::Conn::new(address, default_protocol_version)
+        .and_then(|conn| conn.do_status())
+    {
+        Ok(res) => res,
+        Err(err) => {
+            eprintln!("Failed to ping {}: {}", address, err);
+            return false;
+        }
+    };
+    let forge_mods: Vec<String> = status
+        .forge_mods
+        .iter()
+        .map(|m| format!("{} {}", m.modid, m.version))
+        .collect();
+    if json {
+        let out = serde_json::json!({
+            "address": address,
+            "version": status.version.name,
+            "protocol": status.version.protocol,
+            "players_online": status.players.online,
+            "players_max": status.players.max,
+            "motd": status.description.to_plain_string(),
+            "ping_ms": ping.as_millis() as u64,
+            "fml_network_version": status.fml_network_version,
+            "forge_mods": forge_mods,
+        });
+        println!("{}", out);
+    } else {
+        println!("address: {}", address);
+        println!("version: {}", status.version.name);
+        println!("protocol: {}", status.version.protocol);
+        println!("players_online: {}", status.players.online);
+        println!("players_max: {}", status.players.max);
+        // Multi-line MOTDs are kept on one line
+        println!(
+            "motd: {}",
+            status.description.to_plain_string().replace('\n', " ")
+        );
+        println!("ping_ms: {}", ping.as_millis());
+        if let Some(version) = status.fml_network_version {
+            println!("fml_network_version: {}", version);
+            println!("forge_mods: {}", forge_mods.join(", "));
+        }
+    }
+    true
+}
+
+/// Logs every packet of a recording made with `Opt::record_packets`. Returns whether the
+/// whole recording could be read.
+fn replay_packets(path: &Path) -> bool {
+    let reader = match protocol::recording::open(path) {
+        Ok(reader) => reader,
+        Err(err) => {
+            error!(
+                "Failed to open packet recording {}: {}",
+                path.display(),
+                err
+            );
+            return false;
+        }
+    };
+    let protocol_version = reader.protocol_version;
+    info!(
+        "Replaying packets recorded with protocol version {}",
+        protocol_version
+    );
+    for record in reader {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                error!("Failed to read packet recording: {}", err);
+                return false;
+            }
+        };
+        match record.decode(protocol_version) {
+            Ok(Some(packet)) => info!(
+                "[{:?}] {:?} {:?}: {:?}",
+                record.time, record.direction, record.state, packet
+            ),
+            Ok(None) => warn!(
+                "[{:?}] {:?} {:?}: unknown packet",
+                record.time, record.direction, record.state
+            ),
+            Err(err) => error!(
+                "[{:?}] {:?} {:?}: failed to parse packet: {}",
+                record.time, record.direction, record.state, err
+            ),
+        }
+    }
+    true
+}
+
+/// Runs the client without a window or renderer until the server disconnects, see `Opt::headless`.
+fn run_headless(
+    vars: Rc<console::Vars>,
+    resource_manager: Arc<RwLock<resources::Manager>>,
+    address: &str,
+    default_protocol_version: i32,
+) {
+    format::set_translations(
+        resource_manager
+            .read()
+            .load_translations(&vars.get(settings::CL_LANGUAGE)),
+    );
+    let result = ping_server(address, default_protocol_version).and_then(
+        |(protocol_version, forge_mods, fml_network_version, forge_data)| {
+            server::Server::connect(
+                resource_manager,
+                create_profile(&vars),
+                address,
+                protocol_version,
+                forge_mods,
+                fml_network_version,
+                forge_data,
+                None,
+                Arc::new(RwLock::new(HudContext::new())),
+            )
+        },
+    );
+    let server = match result {
+        Ok(server) => server,
+        Err(err) => {
+            error!("Failed to connect to {}: {}", address, err);
+            return;
+        }
+    };
+    server
+        .events()
+        .subscribe(|event| debug!("Server event: {:?}", event));
+    server.set_keep_alive_timeout(keep_alive_timeout(&vars));
+    server.set_render_distance(settings::render_distance(&vars));
+    info!("Connected to {} in headless mode", address);
+
+    let mut last_tick = Instant::now();
+    let mut tick_accumulator = 0.0;
+    while server.is_connected() {
+        let now = Instant::now();
+        let delta = now.duration_since(last_tick).as_secs_f64() * 60.0;
+        last_tick = now;
+        server.tick_headless(fixed_ticks(&mut tick_accumulator, delta));
+        for message in server.drain_chat_messages() {
+            info!(
+                "[{:?}] {}",
+                message.position,
+                message.message.to_plain_string()
+            );
+        }
+        // There's nothing to play them on
+        server.drain_sounds();
+
+        let frame_time = now.elapsed();
+        if frame_time < HEADLESS_TICK {
+            thread::sleep(HEADLESS_TICK - frame_time);
+        }
+    }
+    let reason = server
+        .disconnect_data
+        .clone()
+        .write()
+        .disconnect_reason
+        .take()
+        .map_or_else(|| "Disconnected".to_owned(), |reason| reason.to_string());
+    info!("Disconnected from {}: {}", address, reason);
 }
 
 // TODO: Hide own character and show only the right hand. (with an item)
 // TODO: Simplify error messages in server list.
 // TODO: Render skin of players joining after one self.
-// TODO: Implement arm swing animation!
 // TODO: Implement attacking entities!
 // TODO: Fix cursor grabbing/visibility/transparency of window.
-// TODO: Improve clouds.
-// TODO: Fix pistons.
 fn main() {
     let opt = Opt::from_args();
     let con = Arc::new(Mutex::new(console::Console::new()));
@@ -217,25 +761,85 @@ fn main() {
     let (vars, mut vsync) = {
         let mut vars = console::Vars::new();
         vars.register(CL_BRAND);
+        vars.register_command(CONNECT);
         console::register_vars(&mut vars);
         auth::register_vars(&mut vars);
         settings::register_vars(&mut vars);
+        chunk_builder::register_commands(&mut vars);
         vars.load_config();
         vars.save_config();
         con.lock().configure(&vars);
+        protocol::set_default_port(settings::default_port(&vars));
+        protocol::socks::set_proxy(&vars.get(settings::CL_PROXY));
         let vsync = *vars.get(settings::R_VSYNC);
         (Rc::new(vars), vsync)
     };
 
-    let (res, mut resui) = resources::Manager::new();
+    if opt.strict_varints {
+        protocol::enable_strict_varints();
+    }
+
+    if let Some(address) = opt.ping {
+        // Keep stdout parseable, failures are reported by run_ping
+        log::set_max_level(log::LevelFilter::Off);
+        let default_protocol_version = parse_default_protocol_version(opt.default_protocol_version);
+        if !run_ping(&address, default_protocol_version, opt.json) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = opt.replay_packets {
+        if !replay_packets(&path) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let (mut res, mut resui) = resources::Manager::new();
+    res.select_resource_pack(&vars.get(settings::CL_RESOURCE_PACK));
     let resource_manager = Arc::new(RwLock::new(res));
 
+    if opt.headless {
+        if let Some(username) = opt.username {
+            vars.set(auth::CL_USERNAME, username);
+        }
+        if opt.network_debug {
+            protocol::enable_network_debug();
+        }
+        if opt.packet_timings {
+            protocol::enable_packet_timings();
+        }
+        if let Some(path) = opt.record_packets {
+            protocol::recording::enable_recording(path, opt.record_serverbound);
+        }
+        let default_protocol_version = parse_default_protocol_version(opt.default_protocol_version);
+        run_headless(
+            vars,
+            resource_manager,
+            &opt.server.unwrap(),
+            default_protocol_version,
+        );
+        return;
+    }
+
     let events_loop = winit::event_loop::EventLoop::new();
 
-    let window_builder = winit::window::WindowBuilder::new()
+    let window_size = (
+        *vars.get(settings::R_WINDOW_WIDTH),
+        *vars.get(settings::R_WINDOW_HEIGHT),
+    );
+    let window_position = restore_window_position(&vars, &events_loop, window_size);
+    let mut window_builder = winit::window::WindowBuilder::new()
         .with_title("Leafish")
-        .with_inner_size(winit::dpi::LogicalSize::new(854.0, 480.0))
-        .with_maximized(true); // Why are we using this particular value here?
+        .with_inner_size(winit::dpi::LogicalSize::new(
+            window_size.0 as f64,
+            window_size.1 as f64,
+        ))
+        .with_maximized(*vars.get(settings::R_WINDOW_MAXIMIZED));
+    if let Some(position) = window_position {
+        window_builder = window_builder.with_position(position);
+    }
 
     let (context, shader_version, dpi_factor, glutin_window) = {
         let glutin_window = glutin::ContextBuilder::new()
@@ -281,7 +885,9 @@ fn main() {
     let mut last_frame = Instant::now();
 
     let mut screen_sys = screen::ScreenSystem::new();
-    if opt.server.is_none() {
+    if let Some(address) = &opt.server {
+        servers::SavedServers::seed(address);
+    } else {
         screen_sys.add_screen(Box::new(screen::Login::new(vars.clone())));
     }
 
@@ -290,10 +896,7 @@ fn main() {
     }
 
     let textures = renderer.get_textures();
-    let default_protocol_version = protocol::versions::protocol_name_to_protocol_version(
-        opt.default_protocol_version
-            .unwrap_or_else(|| "".to_string()),
-    );
+    let default_protocol_version = parse_default_protocol_version(opt.default_protocol_version);
     let game = Game {
         server: None,
         focused: false,
@@ -303,26 +906,60 @@ fn main() {
         console: con,
         vars,
         should_close: false,
+        sound: sound::SoundManager::new(resource_manager.clone()),
         chunk_builder: chunk_builder::ChunkBuilder::new(resource_manager, textures),
         connect_error: None,
         dpi_factor,
         last_mouse_x: 0.0,
         last_mouse_y: 0.0,
-        last_mouse_xrel: 0.0,
-        last_mouse_yrel: 0.0,
+        mouse_motion: mouse::MotionTracker::new(),
+        scroll_remainder: (0.0, 0.0),
+        window_size,
+        window_position: window_position.map(|position| (position.x as i64, position.y as i64)),
+        tick_accumulator: 0.0,
+        partial_tick: 0.0,
         is_ctrl_pressed: false,
+        is_shift_pressed: false,
         is_logo_pressed: false,
         is_fullscreen: false,
+        screenshot_requested: false,
+        frame_times: render::frame_times::FrameTimes::new(),
+        frame_deadline: Instant::now(),
         default_protocol_version,
+        last_address: None,
+        last_hud_context: None,
+        auto_reconnects: 0,
+        reconnecting: None,
+        language: String::new(),
+        var_changes: None,
     };
     game.renderer.write().camera.pos = cgmath::Point3::new(0.5, 13.2, 0.5);
     if opt.network_debug {
         protocol::enable_network_debug();
     }
+    if opt.packet_timings {
+        protocol::enable_packet_timings();
+    }
+    if let Some(path) = opt.record_packets {
+        protocol::recording::enable_recording(path, opt.record_serverbound);
+    }
 
-    if let Some(filename) = opt.network_parse_packet {
-        let data = fs::read(filename).unwrap();
-        protocol::try_parse_packet(data, default_protocol_version);
+    if let Some(args) = opt.network_parse_packet {
+        let data = fs::read(&args[0]).unwrap();
+        let state = match args.get(1) {
+            Some(state) => state.parse().unwrap(),
+            None => protocol::State::Play,
+        };
+        // Handshakes are only ever sent by the client
+        let dir = if state == protocol::State::Handshaking {
+            protocol::Direction::Serverbound
+        } else {
+            protocol::Direction::Clientbound
+        };
+        match protocol::try_parse_packet(data, default_protocol_version, state, dir) {
+            Ok(packet) => info!("parsed packet: {:?}", packet),
+            Err(err) => error!("failed to parse packet: {}", err),
+        }
         return;
     }
 
@@ -372,11 +1009,24 @@ fn main() {
             let dist = Instant::now().checked_duration_since(start);
             debug!("Ticking took {}", dist.unwrap().as_millis());
         }
+        if game.screenshot_requested {
+            game.screenshot_requested = false;
+            let (width, height) = winit_window.inner_size().into();
+            let hud_context = game
+                .server
+                .as_ref()
+                .map(|server| server.hud_context.clone());
+            screenshot::capture(width, height, hud_context);
+        }
         glutin_window
             .swap_buffers()
             .expect("Failed to swap GL buffers");
 
         if game.should_close {
+            if let Some(server) = game.server.take() {
+                server.shutdown();
+            }
+            save_window_geometry(&game, winit_window);
             *control_flow = winit::event_loop::ControlFlow::Exit;
         }
     });
@@ -384,6 +1034,68 @@ fn main() {
 
 const DEBUG: bool = false;
 
+/// Returns the saved window position, moved onto a connected monitor if it would otherwise
+/// be off-screen, e.g. because the monitor it was on has been unplugged.
+fn restore_window_position<T>(
+    vars: &console::Vars,
+    events_loop: &winit::event_loop::EventLoop<T>,
+    (width, height): (i64, i64),
+) -> Option<winit::dpi::PhysicalPosition<i32>> {
+    let (x, y) = (
+        *vars.get(settings::R_WINDOW_X),
+        *vars.get(settings::R_WINDOW_Y),
+    );
+    if x == settings::WINDOW_POSITION_UNSET || y == settings::WINDOW_POSITION_UNSET {
+        return None;
+    }
+    let (x, y) = (
+        x.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+        y.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+    );
+    let is_on_monitor = |monitor: &winit::monitor::MonitorHandle| {
+        let (position, size) = (monitor.position(), monitor.size());
+        x >= position.x
+            && y >= position.y
+            && x < position.x + size.width as i32
+            && y < position.y + size.height as i32
+    };
+    if events_loop.available_monitors().any(|m| is_on_monitor(&m)) {
+        return Some(winit::dpi::PhysicalPosition::new(x, y));
+    }
+
+    let monitor = events_loop
+        .primary_monitor()
+        .or_else(|| events_loop.available_monitors().next())?;
+    let (position, size) = (monitor.position(), monitor.size());
+    let scale_factor = monitor.scale_factor();
+    let (width, height) = (
+        (width as f64 * scale_factor) as i32,
+        (height as f64 * scale_factor) as i32,
+    );
+    Some(winit::dpi::PhysicalPosition::new(
+        x.clamp(
+            position.x,
+            (position.x + size.width as i32 - width).max(position.x),
+        ),
+        y.clamp(
+            position.y,
+            (position.y + size.height as i32 - height).max(position.y),
+        ),
+    ))
+}
+
+/// Stores the window's geometry so it can be restored in the next session.
+fn save_window_geometry(game: &Game, window: &winit::window::Window) {
+    game.vars
+        .set(settings::R_WINDOW_MAXIMIZED, window.is_maximized());
+    game.vars.set(settings::R_WINDOW_WIDTH, game.window_size.0);
+    game.vars.set(settings::R_WINDOW_HEIGHT, game.window_size.1);
+    if let Some((x, y)) = game.window_position {
+        game.vars.set(settings::R_WINDOW_X, x);
+        game.vars.set(settings::R_WINDOW_Y, y);
+    }
+}
+
 fn tick_all(
     window: &winit::window::Window,
     game: &mut Game,
@@ -394,39 +1106,69 @@ fn tick_all(
     vsync: &mut bool,
 ) {
     if game.server.is_some() {
-        if !game.server.as_ref().unwrap().is_connected() {
-            let disconnect_reason = if let Some(disconnect_reason) = game
-                .server
-                .as_ref()
-                .unwrap()
-                .disconnect_data
-                .clone()
-                .write()
-                .disconnect_reason
-                .take()
-            {
-                disconnect_reason
+        let connection_state = game.server.as_ref().unwrap().connection_state();
+        if let server::ConnectionState::Playing = connection_state {
+            // Kicks from here on aren't part of the same run of reconnects
+            game.auto_reconnects = 0;
+        } else if let server::ConnectionState::Disconnected(disconnect_reason) = connection_state {
+            game.leave_server();
+
+            let attempts = (*game.vars.get(settings::CL_RECONNECT_ATTEMPTS)).max(1) as u32;
+            let auto_reconnect = game.auto_reconnects < attempts
+                && is_auto_reconnect_message(
+                    &disconnect_reason.to_string(),
+                    &game.vars.get(settings::CL_AUTO_RECONNECT_MESSAGES),
+                );
+            if auto_reconnect {
+                info!("Kicked with \"{}\", reconnecting", disconnect_reason);
+                game.auto_reconnects += 1;
+            }
+            if auto_reconnect && game.reconnect().is_ok() {
+                let address = game.last_address.clone().unwrap();
+                game.screen_sys
+                    .add_screen(Box::new(screen::connecting::Connecting::new(&address)));
             } else {
-                Component::Text(TextComponent::new("Disconnected"))
-            };
-            while game.screen_sys.is_current_closable() {
-                game.screen_sys.pop_screen();
+                game.auto_reconnects = 0;
+                game.screen_sys
+                    .replace_screen(Box::new(screen::ServerList::new(
+                        Some(disconnect_reason),
+                        game.vars.get(settings::BACKGROUND_IMAGE).clone(),
+                    )));
             }
-            game.screen_sys
-                .replace_screen(Box::new(screen::ServerList::new(
-                    Some(disconnect_reason),
-                    game.vars.get(settings::BACKGROUND_IMAGE).clone(),
-                )));
-            game.server = None;
-            game.renderer.clone().write().reset();
-            game.focused = false;
         }
     } else {
         game.chunk_builder.reset();
+        match game.poll_reconnect() {
+            Some(Ok(())) => {
+                let hud_context = game.last_hud_context.clone().unwrap();
+                game.screen_sys
+                    .replace_screen(Box::new(Hud::new(hud_context)));
+                game.focused = true;
+            }
+            Some(Err(err)) => {
+                game.auto_reconnects = 0;
+                game.screen_sys
+                    .replace_screen(Box::new(screen::ServerList::new(
+                        Some(err.to_component()),
+                        game.vars.get(settings::BACKGROUND_IMAGE).clone(),
+                    )));
+            }
+            None => {}
+        }
     }
+    game.vars.reload_config_if_modified();
+    game.apply_changed_vars();
     let now = Instant::now();
     let diff = now.duration_since(*last_frame);
     *last_frame = now;
+    game.frame_times.push(diff);
+    if let Some(server) = game.server.as_ref() {
+        server
+            .hud_context
+            .clone()
+            .write()
+            .update_frame_times(&game.frame_times);
+    }
     let frame_time = 1e9f64 / 60.0;
     let delta = (diff.subsec_nanos() as f64) / frame_time;
     let physical_size = window.inner_size();
@@ -444,6 +1186,9 @@ fn tick_all(
             *last_resource_version
         }
     };
+    if version != *last_resource_version {
+        game.load_translations();
+    }
     *last_resource_version = version;
 
     let vsync_changed = *game.vars.get(settings::R_VSYNC);
@@ -455,12 +1200,14 @@ fn tick_all(
     }
     let fps_cap = *game.vars.get(settings::R_MAX_FPS);
 
+    let ticks = fixed_ticks(&mut game.tick_accumulator, delta);
+    game.partial_tick = game.tick_accumulator / TICK_LENGTH;
     if game.server.is_some() {
         game.server
             .as_ref()
             .unwrap()
             .clone()
-            .tick(game.renderer.clone(), delta, game); // TODO: Improve perf in load screen!
+            .tick(game.renderer.clone(), delta, ticks, game); // TODO: Improve perf in load screen!
     }
 
     // Check if window is valid, it might be minimized
@@ -468,6 +1215,18 @@ fn tick_all(
         return;
     }
 
+    let sprinting = game
+        .server
+        .as_ref()
+        .map_or(false, |server| server.is_sprinting());
+    let fov = if sprinting && *game.vars.get(settings::R_DYNAMIC_FOV) {
+        settings::fov(&game.vars) * settings::SPRINT_FOV_FACTOR
+    } else {
+        settings::fov(&game.vars)
+    };
+    game.renderer.clone().write().fov = fov;
+    game.renderer.clone().write().cloud_mode =
+        render::clouds::CloudMode::from_name(game.vars.get(settings::R_CLOUDS).as_str());
     if game.server.is_some() {
         game.renderer
             .clone()
@@ -477,6 +1236,7 @@ fn tick_all(
             game.server.as_ref().unwrap().world.clone(),
             game.renderer.clone(),
             version,
+            settings::render_distance(&game.vars),
         );
     } else if game.renderer.clone().read().safe_width != physical_width
         || game.renderer.clone().read().safe_height != physical_height
@@ -486,24 +1246,32 @@ fn tick_all(
         gl::viewport(0, 0, physical_width as i32, physical_height as i32);
     }
 
-    game.screen_sys
-        .tick(delta, game.renderer.clone(), &mut ui_container);
-    /* TODO: open console for chat messages
-    if let Some(received_chat_at) = game.server.received_chat_at {
-        if Instant::now().duration_since(received_chat_at).as_secs() < 5 {
-            game.console.lock().unwrap().activate()
-            // TODO: automatically deactivate the console after inactivity
+    game.sound.tick(version);
+    if let Some(server) = game.server.as_ref() {
+        let listener = game.renderer.read().camera.pos;
+        for sound in server.drain_sounds() {
+            game.sound
+                .play(&game.vars, &sound, (listener.x, listener.y, listener.z));
         }
     }
-    */
+
+    game.screen_sys
+        .tick(delta, game.renderer.clone(), &mut ui_container);
     game.console.lock().tick(
         &mut ui_container,
         game.renderer.clone(),
         delta,
         width as f64,
     );
+    ui_container.scale = ui::gui_scale(
+        *game.vars.get(settings::CL_GUI_SCALE),
+        width as f64,
+        height as f64,
+    );
     ui_container.tick(game.renderer.clone(), delta, width as f64, height as f64);
     let world = game.server.as_ref().map(|server| server.world.clone());
+    game.renderer.clone().write().frustum_culling = *game.vars.get(settings::R_FRUSTUM_CULLING);
+    game.renderer.clone().write().gamma = settings::gamma(&game.vars);
     game.renderer.clone().write().tick(
         world,
         delta,
@@ -523,13 +1291,42 @@ fn tick_all(
     }
 
     if fps_cap > 0 && !*vsync {
-        let frame_time = now.elapsed();
-        let sleep_interval = Duration::from_millis(1000 / fps_cap as u64);
-        if frame_time < sleep_interval {
-            thread::sleep(sleep_interval - frame_time);
+        let interval = Duration::from_secs(1) / fps_cap as u32;
+        if let Some(sleep) = frame_limit_sleep(&mut game.frame_deadline, interval, Instant::now()) {
+            thread::sleep(sleep);
         }
     }
 }
+
+/// Moves `deadline` on by a frame and returns how long to sleep until it. Deadlines stay on a
+/// fixed grid, so sleeping longer than asked due to the scheduler's granularity is made up for
+/// by a shorter sleep the next frame. A frame that ran past a whole interval restarts the grid.
+fn frame_limit_sleep(deadline: &mut Instant, interval: Duration, now: Instant) -> Option<Duration> {
+    *deadline += interval;
+    if *deadline > now {
+        return Some(*deadline - now);
+    }
+    if now - *deadline > interval {
+        *deadline = now;
+    }
+    None
+}
+
+/// Passes a mouse press on to the current screen, at the last known cursor position.
+fn screen_click(
+    game: &mut Game,
+    window: &winit::window::Window,
+    ui_container: &ui::Container,
+    right: bool,
+) {
+    let (width, height) = window
+        .inner_size()
+        .to_logical::<f64>(game.dpi_factor)
+        .into();
+    let (x, y) = ui_container.center_offset(game.last_mouse_x, game.last_mouse_y, width, height);
+    game.screen_sys.on_click(right, game.is_shift_pressed, x, y);
+}
+
 // TODO: Improve perf of 3, 6 and 10
 // TODO: Reenable: [server/mod.rs:1924][WARN] Block entity at (1371,53,-484) missing id tag: NamedTag("", Compound({"y": Int(53), "Sign": String(""), "x": Int(1371), "z": Int(-484)}))
 
@@ -548,24 +1345,18 @@ fn handle_window_event<T>(
             },
             ..
         } => {
-            let (rx, ry) = if xrel > 1000.0 || yrel > 1000.0 {
-                // Heuristic for if we were passed an absolute value instead of relative
-                // Workaround https://github.com/tomaka/glutin/issues/1084 MouseMotion event returns absolute instead of relative values, when running Linux in a VM
-                // Note SDL2 had a hint to handle this scenario:
-                // sdl2::hint::set_with_priority("SDL_MOUSE_RELATIVE_MODE_WARP", "1", &sdl2::hint::Hint::Override);
-                let s = 8000.0 + 0.01;
-                (
-                    (xrel - game.last_mouse_xrel) / s,
-                    (yrel - game.last_mouse_yrel) / s,
-                )
-            } else {
-                let s = 2000.0 + 0.01;
-                (xrel / s, yrel / s)
+            let mode = mouse::MotionMode::from_setting(&game.vars.get(settings::CL_MOUSE_MOTION));
+            let (rx, ry) = match game.mouse_motion.update(mode, (xrel, yrel)) {
+                (true, (dx, dy)) => {
+                    let s = 8000.0 + 0.01;
+                    (dx / s, dy / s)
+                }
+                (false, (dx, dy)) => {
+                    let s = 2000.0 + 0.01;
+                    (dx / s, dy / s)
+                }
             };
 
-            game.last_mouse_xrel = xrel;
-            game.last_mouse_yrel = yrel;
-
             use std::f64::consts::PI;
 
             if game.focused {
@@ -602,9 +1393,24 @@ fn handle_window_event<T>(
             match event {
                 WindowEvent::ModifiersChanged(modifiers_state) => {
                     game.is_ctrl_pressed = modifiers_state.ctrl();
+                    game.is_shift_pressed = modifiers_state.shift();
                     game.is_logo_pressed = modifiers_state.logo();
                 }
                 WindowEvent::CloseRequested => game.should_close = true,
+                // Only remember the geometry of the restored window, so that un-maximizing
+                // after a restart doesn't produce a window covering the whole screen.
+                WindowEvent::Resized(physical_size) => {
+                    if !window.is_maximized() && !game.is_fullscreen {
+                        let (width, height): (f64, f64) =
+                            physical_size.to_logical::<f64>(game.dpi_factor).into();
+                        game.window_size = (width as i64, height as i64);
+                    }
+                }
+                WindowEvent::Moved(position) => {
+                    if !window.is_maximized() && !game.is_fullscreen {
+                        game.window_position = Some((position.x as i64, position.y as i64));
+                    }
+                }
                 WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                     game.dpi_factor = scale_factor;
                 }
@@ -622,6 +1428,11 @@ fn handle_window_event<T>(
 
                 WindowEvent::MouseInput { state, button, .. } => match (state, button) {
                     (ElementState::Released, MouseButton::Left) => {
+                        if game.focused {
+                            if let Some(server) = game.server.as_ref() {
+                                server.on_left_release();
+                            }
+                        }
                         let physical_size = window.inner_size();
                         let (width, height) =
                             physical_size.to_logical::<f64>(game.dpi_factor).into();
@@ -653,6 +1464,8 @@ fn handle_window_event<T>(
                                 .as_ref()
                                 .unwrap()
                                 .on_right_click(game.renderer.clone());
+                        } else if !game.focused {
+                            screen_click(game, window, ui_container, true);
                         }
                     }
                     (ElementState::Pressed, MouseButton::Left) => {
@@ -661,6 +1474,8 @@ fn handle_window_event<T>(
                                 .as_ref()
                                 .unwrap()
                                 .on_left_click(game.renderer.clone());
+                        } else if !game.focused {
+                            screen_click(game, window, ui_container, false);
                         }
                     }
                     (_, _) => (),
@@ -675,21 +1490,36 @@ fn handle_window_event<T>(
                         let (width, height) =
                             physical_size.to_logical::<f64>(game.dpi_factor).into();
                         ui_container.hover_at(game, x, y, width, height);
+                        let (x, y) = ui_container.center_offset(x, y, width, height);
+                        game.screen_sys.on_cursor_moved(x, y);
                     }
                 }
-                WindowEvent::MouseWheel { delta, .. } => {
-                    // TODO: line vs pixel delta? does pixel scrolling (e.g. touchpad) need scaling?
-                    match delta {
-                        MouseScrollDelta::LineDelta(x, y) => {
-                            game.screen_sys.on_scroll(x.into(), y.into());
-                        }
-                        MouseScrollDelta::PixelDelta(position) => {
-                            let (x, y) = position.into();
-                            game.screen_sys.on_scroll(x, y);
+                WindowEvent::MouseWheel { delta, .. } => match delta {
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        game.screen_sys.on_scroll(x.into(), y.into());
+                    }
+                    MouseScrollDelta::PixelDelta(position) => {
+                        // Touchpads report pixels, convert them to lines so they scroll
+                        // as fast as a mouse wheel would, keeping the leftover for later.
+                        let (x, y): (f64, f64) = position.to_logical::<f64>(game.dpi_factor).into();
+                        let pixels_per_line =
+                            (*game.vars.get(settings::CL_SCROLL_PIXELS_PER_LINE)).max(1) as f64;
+                        let lines_x = game.scroll_remainder.0 + x / pixels_per_line;
+                        let lines_y = game.scroll_remainder.1 + y / pixels_per_line;
+                        game.scroll_remainder = (lines_x.fract(), lines_y.fract());
+                        if lines_x.trunc() != 0.0 || lines_y.trunc() != 0.0 {
+                            game.screen_sys.on_scroll(lines_x.trunc(), lines_y.trunc());
                         }
                     }
-                }
+                },
                 WindowEvent::KeyboardInput { input, .. } => {
+                    if let (ElementState::Released, Some(key)) =
+                        (input.state, input.virtual_keycode)
+                    {
+                        if game.screen_sys.on_key_release(key) {
+                            return false;
+                        }
+                    }
                     match (input.state, input.virtual_keycode) {
                         (ElementState::Released, Some(VirtualKeyCode::Escape)) => {
                             if game.server.is_some()
@@ -727,9 +1557,16 @@ fn handle_window_event<T>(
                             game.is_fullscreen = !game.is_fullscreen;
                         }
                         (ElementState::Pressed, Some(key)) => {
+                            // Screens waiting for a key, like the controls menu, get it exclusively
+                            if game.screen_sys.on_key_press(key) {
+                                return false;
+                            }
                             if let Some(action_key) =
                                 settings::Actionkey::get_by_keycode(key, &game.vars)
                             {
+                                if action_key == settings::Actionkey::Screenshot {
+                                    game.screenshot_requested = true;
+                                }
                                 if game.server.is_some() {
                                     game.server.as_ref().unwrap().key_press(
                                         true,
@@ -774,3 +1611,172 @@ fn handle_window_event<T>(
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_command_validates_address() {
+        let mut connected = None;
+        let result = run_connect(&["localhost:25566"], |address| {
+            connected = Some(address.to_owned())
+        });
+        assert!(result.is_ok());
+        assert_eq!(connected.as_deref(), Some("localhost:25566"));
+        assert!(run_connect(&["[::1]:25566"], |_| {}).is_ok());
+        assert!(run_connect(&["::1"], |_| {}).is_ok());
+
+        let invalid: &[&[&str]] = &[
+            &[],
+            &["localhost", "25566"],
+            &[":25565"],
+            &["localhost:99999"],
+            &["local host"],
+        ];
+        for args in invalid {
+            assert!(run_connect(args, |_| panic!("connected with {:?}", args)).is_err());
+        }
+    }
+
+    #[test]
+    fn connect_error_keeps_disconnect_reason() {
+        let reason = format::Component::from_string(r#"{"text":"Banned","color":"red"}"#);
+        match copy_connect_error(&Error::Disconnect(reason)) {
+            Error::Disconnect(format::Component::Text(txt)) => {
+                assert_eq!(txt.text, "Banned");
+                assert!(matches!(txt.modifier.color, Some(format::Color::Red)));
+            }
+            err => panic!("expected a disconnect, got {:?}", err),
+        }
+        let err = copy_connect_error(&Error::Err("Wrong packet".to_string()));
+        assert_eq!(err.to_string(), "protocol error: Wrong packet");
+    }
+
+    #[test]
+    fn reconnect_backoff_schedule() {
+        assert_eq!(reconnect_delay(0), Duration::from_millis(500));
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(5), Duration::from_secs(16));
+        assert_eq!(reconnect_delay(6), RECONNECT_MAX_DELAY);
+        assert_eq!(reconnect_delay(u32::MAX), RECONNECT_MAX_DELAY);
+    }
+
+    #[test]
+    fn reconnect_attempts_are_scheduled() {
+        let start = Instant::now();
+        let mut reconnecting = Reconnecting::new(3, start);
+        assert!(reconnecting.is_due(start));
+
+        // Waits out the backoff after each failed attempt, until all were made
+        let failed = start + Duration::from_secs(1);
+        assert!(reconnecting.retry(failed));
+        assert!(!reconnecting.is_due(failed));
+        assert!(reconnecting.is_due(failed + reconnect_delay(0)));
+        assert!(reconnecting.retry(failed));
+        assert!(!reconnecting.is_due(failed + reconnect_delay(0)));
+        assert!(reconnecting.is_due(failed + reconnect_delay(1)));
+        assert!(!reconnecting.retry(failed));
+
+        // A single attempt isn't retried
+        assert!(!Reconnecting::new(0, start).retry(start));
+    }
+
+    #[test]
+    fn failed_reconnect_attempts_are_retried() {
+        let start = Instant::now();
+        let mut reconnecting = Reconnecting::new(2, start);
+        assert!(reconnecting.take_result().is_none());
+
+        // Still connecting
+        let (tx, rx) = mpsc::channel();
+        reconnecting.pending = Some(rx);
+        assert!(reconnecting.take_result().is_none());
+        assert!(!reconnecting.is_due(start));
+
+        // A refused login is retried, the next attempt waiting for the backoff
+        let reason = format::Component::from_string(r#"{"text":"Server full","color":"red"}"#);
+        tx.send(Err(Error::Disconnect(reason))).unwrap();
+        match reconnecting.take_result() {
+            Some(Err(err)) => assert_eq!(err.to_string(), "Server full"),
+            _ => panic!("expected the attempt to fail"),
+        }
+        assert!(reconnecting.retry(start));
+        assert!(reconnecting.take_result().is_none());
+        assert!(reconnecting.is_due(start + reconnect_delay(0)));
+
+        // The last attempt's thread going away fails it too, leaving its error to show
+        let (tx, rx) = mpsc::channel::<ConnectResult>();
+        reconnecting.pending = Some(rx);
+        drop(tx);
+        let err = match reconnecting.take_result() {
+            Some(Err(err)) => err,
+            _ => panic!("expected the attempt to fail"),
+        };
+        assert!(!reconnecting.retry(start));
+        assert_eq!(err.to_component().to_string(), "protocol error: Unknown");
+    }
+
+    #[test]
+    fn kick_message_triggers_auto_reconnect() {
+        let messages = "Server restarting, proxy lost connection ,";
+        assert!(is_auto_reconnect_message("Server restarting", messages));
+        assert!(is_auto_reconnect_message(
+            "The server is RESTARTING now",
+            "restarting"
+        ));
+        assert!(is_auto_reconnect_message(
+            "Proxy lost connection to lobby-1",
+            messages
+        ));
+        assert!(!is_auto_reconnect_message("You have been banned", messages));
+        // Blank entries don't match everything
+        assert!(!is_auto_reconnect_message("Kicked by an operator", ""));
+        assert!(!is_auto_reconnect_message("Kicked by an operator", " , "));
+    }
+
+    #[test]
+    fn frame_limit_keeps_pace() {
+        let interval = Duration::from_millis(10);
+        let start = Instant::now();
+        let mut deadline = start;
+        assert_eq!(
+            frame_limit_sleep(&mut deadline, interval, start + Duration::from_millis(4)),
+            Some(Duration::from_millis(6))
+        );
+        // Oversleeping past the first deadline shortens the next sleep, keeping the pace
+        assert_eq!(
+            frame_limit_sleep(&mut deadline, interval, start + Duration::from_millis(15)),
+            Some(Duration::from_millis(5))
+        );
+        // A slow frame doesn't sleep, and one more than a frame late restarts the schedule
+        assert_eq!(
+            frame_limit_sleep(&mut deadline, interval, start + Duration::from_millis(31)),
+            None
+        );
+        assert_eq!(deadline, start + Duration::from_millis(30));
+        assert_eq!(
+            frame_limit_sleep(&mut deadline, interval, start + Duration::from_millis(100)),
+            None
+        );
+        assert_eq!(deadline, start + Duration::from_millis(100));
+    }
+
+    #[test]
+    fn fixed_tick_rate() {
+        let deltas = [0.5, 1.0, 2.5, 4.0];
+        let mut accumulator = 0.0;
+        let mut ticks = 0;
+        for frame in 0..100 {
+            ticks += fixed_ticks(&mut accumulator, deltas[frame % deltas.len()]);
+        }
+        // 100 frames add up to 200 frame units, or 66 whole ticks
+        assert_eq!(ticks, 66);
+        assert!((accumulator - 2.0).abs() < f64::EPSILON);
+
+        let mut accumulator = 0.0;
+        assert_eq!(fixed_ticks(&mut accumulator, 600.0), MAX_TICKS_PER_FRAME);
+        assert!(accumulator < TICK_LENGTH);
+    }
+}
@@ -169,6 +169,35 @@ impl Factory {
         true
     }
 
+    /// The texture particles of `block` show, taken from a side of its model. `None` until
+    /// the model has been loaded by building a chunk with the block in it.
+    pub fn particle_texture(&self, block: Block) -> Option<render::Texture> {
+        let (plugin, name) = block.get_model();
+        let state = self.models.get(&Key(plugin, name))?;
+        let model = if state.multipart.is_empty() {
+            state
+                .get_variants(&block.get_model_variant())?
+                .models
+                .first()?
+        } else {
+            state
+                .multipart
+                .iter()
+                .find(|rule| Self::eval_rules(block, &rule.rules))?
+                .apply
+                .models
+                .first()?
+        };
+        let side = model
+            .faces
+            .iter()
+            .find(|face| face.facing != Direction::Up && face.facing != Direction::Down);
+        side.or_else(|| model.faces.first())?
+            .vertices_texture
+            .first()
+            .cloned()
+    }
+
     pub fn get_state_model<R: Rng, W: Write>(
         models: &Arc<RwLock<Factory>>,
         block: Block,
@@ -0,0 +1,244 @@
+//! Converts raw mouse motion events into relative camera movement.
+//!
+//! Some environments, most notably Linux running in a VM, report absolute pointer
+//! coordinates in `DeviceEvent::MouseMotion` instead of relative deltas
+//! (https://github.com/tomaka/glutin/issues/1084). SDL2 has a hint for this scenario:
+//! `sdl2::hint::set_with_priority("SDL_MOUSE_RELATIVE_MODE_WARP", "1", &sdl2::hint::Hint::Override);`
+
+/// Number of consecutive events that have to look like absolute coordinates, moving in
+/// one direction, before the motion is treated as such.
+const ABSOLUTE_STREAK: u32 = 8;
+/// How much (as a fraction of its value) an absolute coordinate may change between two
+/// consecutive events. Relative deltas of a flick rarely stay this steady.
+const ABSOLUTE_MAX_CHANGE: f64 = 1.0 / 8.0;
+/// How many times `ABSOLUTE_MAX_CHANGE` a coordinate has to jump at once to clearly not be
+/// one, and the fewest pixels that is for coordinates close to 0.
+const ABSOLUTE_MAX_JUMP: f64 = 4.0;
+const ABSOLUTE_MIN_JUMP: f64 = 256.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MotionMode {
+    /// Detect absolute coordinates from the shape of the motion events.
+    Auto,
+    Relative,
+    Absolute,
+}
+
+impl MotionMode {
+    /// Parses the value of `settings::CL_MOUSE_MOTION`, falling back to `Auto`.
+    pub fn from_setting(value: &str) -> MotionMode {
+        match value {
+            "relative" => MotionMode::Relative,
+            "absolute" => MotionMode::Absolute,
+            _ => MotionMode::Auto,
+        }
+    }
+}
+
+/// What the motion events of `MotionMode::Auto` turned out to be.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Detected {
+    #[default]
+    Undecided,
+    Relative,
+    Absolute,
+}
+
+#[derive(Default)]
+pub struct MotionTracker {
+    last: Option<(f64, f64)>,
+    /// The sign of the last change of each coordinate that changed.
+    direction: (f64, f64),
+    absolute_streak: u32,
+    detected: Detected,
+}
+
+impl MotionTracker {
+    pub fn new() -> MotionTracker {
+        Default::default()
+    }
+
+    /// Feeds a raw `MouseMotion` value and returns whether it should be treated as an
+    /// absolute coordinate, along with the movement since the previous event. Until it's
+    /// known which they are, values don't move anything.
+    pub fn update(&mut self, mode: MotionMode, value: (f64, f64)) -> (bool, (f64, f64)) {
+        let last = self.last.replace(value);
+        self.detect(last, value);
+        let detected = match mode {
+            MotionMode::Auto => self.detected,
+            MotionMode::Relative => Detected::Relative,
+            MotionMode::Absolute => Detected::Absolute,
+        };
+        match (detected, last) {
+            (Detected::Relative, _) => (false, value),
+            (Detected::Absolute, Some(last)) => (true, (value.0 - last.0, value.1 - last.1)),
+            // The first position has nothing to move from
+            (Detected::Absolute, None) => (true, (0.0, 0.0)),
+            (Detected::Undecided, _) => (false, (0.0, 0.0)),
+        }
+    }
+
+    fn detect(&mut self, last: Option<(f64, f64)>, value: (f64, f64)) {
+        let last = match last {
+            Some(last) => last,
+            None => {
+                if value.0 < 0.0 || value.1 < 0.0 {
+                    self.detected = Detected::Relative;
+                }
+                return;
+            }
+        };
+        // A pointer position keeps changing the same way while the pointer moves, steady
+        // relative deltas from a drag repeat the same values instead
+        let change = (value.0 - last.0, value.1 - last.1);
+        let moving = change.0 != 0.0 || change.1 != 0.0;
+        let monotonic = Self::keeps_direction(self.direction.0, change.0)
+            && Self::keeps_direction(self.direction.1, change.1);
+        if moving
+            && monotonic
+            && Self::looks_absolute(last.0, value.0)
+            && Self::looks_absolute(last.1, value.1)
+        {
+            self.absolute_streak = self.absolute_streak.saturating_add(1);
+        } else {
+            self.absolute_streak = 0;
+        }
+        if change.0 != 0.0 {
+            self.direction.0 = change.0.signum();
+        }
+        if change.1 != 0.0 {
+            self.direction.1 = change.1.signum();
+        }
+
+        // Once found, a pointer stopping or turning around doesn't change what they are
+        if Self::clearly_relative(last.0, value.0) || Self::clearly_relative(last.1, value.1) {
+            self.detected = Detected::Relative;
+        } else if self.absolute_streak >= ABSOLUTE_STREAK {
+            self.detected = Detected::Absolute;
+        }
+    }
+
+    fn keeps_direction(direction: f64, change: f64) -> bool {
+        change == 0.0 || direction == 0.0 || direction == change.signum()
+    }
+
+    fn looks_absolute(last: f64, value: f64) -> bool {
+        value >= 0.0 && (value - last).abs() <= value * ABSOLUTE_MAX_CHANGE
+    }
+
+    /// Whether `value` can't be a coordinate following `last`.
+    fn clearly_relative(last: f64, value: f64) -> bool {
+        let max_jump =
+            (value.max(last) * ABSOLUTE_MAX_CHANGE * ABSOLUTE_MAX_JUMP).max(ABSOLUTE_MIN_JUMP);
+        value < 0.0 || (value - last).abs() > max_jump
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_absolute_motion() {
+        // A fast high DPI flick, which the old `> 1000.0` threshold mistook for absolute.
+        // The first value can't be told apart from a coordinate yet, the negative one can.
+        let mut tracker = MotionTracker::new();
+        assert_eq!(
+            tracker.update(MotionMode::Auto, (1200.0, 40.0)),
+            (false, (0.0, 0.0))
+        );
+        for &value in &[
+            (1800.0, -20.0),
+            (2400.0, 10.0),
+            (1500.0, 0.0),
+            (900.0, -5.0),
+            (1300.0, 30.0),
+            (2100.0, 0.0),
+            (1700.0, -10.0),
+            (1100.0, 5.0),
+            (600.0, 0.0),
+        ] {
+            assert_eq!(tracker.update(MotionMode::Auto, value), (false, value));
+        }
+
+        // A VM reporting the pointer's position while it moves slowly to the right
+        let mut tracker = MotionTracker::new();
+        let mut absolute = vec![];
+        for i in 0..12 {
+            let (is_absolute, motion) =
+                tracker.update(MotionMode::Auto, (4000.0 + i as f64 * 10.0, 3000.0));
+            if is_absolute {
+                assert_eq!(motion, (10.0, 0.0));
+            } else {
+                assert_eq!(motion, (0.0, 0.0));
+            }
+            absolute.push(is_absolute);
+        }
+        assert!(absolute[..ABSOLUTE_STREAK as usize].iter().all(|&a| !a));
+        assert!(absolute[ABSOLUTE_STREAK as usize..].iter().all(|&a| a));
+
+        // A steady drag repeats the same relative delta, which must not look absolute
+        let mut tracker = MotionTracker::new();
+        for &value in &[(5.0, -5.0), (5.0, 0.0), (1500.0, 2.0)] {
+            for _ in 0..ABSOLUTE_STREAK * 3 {
+                assert_eq!(tracker.update(MotionMode::Auto, value), (false, value));
+            }
+        }
+
+        // Positions turning back and forth don't keep one direction
+        let mut tracker = MotionTracker::new();
+        for i in 0..ABSOLUTE_STREAK * 3 {
+            let value = (4000.0 + (i % 2) as f64 * 10.0, 3000.0);
+            assert!(!tracker.update(MotionMode::Auto, value).0);
+        }
+
+        // Forcing the mode skips the detection
+        let mut tracker = MotionTracker::new();
+        assert_eq!(
+            tracker.update(MotionMode::Absolute, (100.0, 100.0)),
+            (true, (0.0, 0.0))
+        );
+        assert_eq!(
+            tracker.update(MotionMode::Absolute, (110.0, 100.0)),
+            (true, (10.0, 0.0))
+        );
+        assert_eq!(
+            tracker.update(MotionMode::Relative, (110.0, 100.0)),
+            (false, (110.0, 100.0))
+        );
+    }
+
+    #[test]
+    fn absolute_motion_stays_small() {
+        // A VM pointer moving right, pausing, turning back and going up to the edge
+        let mut positions = vec![];
+        positions.extend((0..12).map(|i| (4000.0 + i as f64 * 10.0, 3000.0)));
+        positions.extend((0..5).map(|_| (4110.0, 3000.0)));
+        positions.extend((0..20).map(|i| (4110.0 - i as f64 * 15.0, 3000.0 - i as f64)));
+        positions.extend((0..5).map(|_| (3825.0, 2981.0)));
+        positions.extend((0..30).map(|i| (3825.0, (2981.0 - i as f64 * 100.0).max(0.0))));
+        positions.extend((0..5).map(|i| (3825.0 + i as f64 * 5.0, 0.0)));
+
+        let mut tracker = MotionTracker::new();
+        let mut was_absolute = false;
+        for position in positions {
+            let (absolute, (dx, dy)) = tracker.update(MotionMode::Auto, position);
+            assert!(
+                dx.abs() <= 100.0 && dy.abs() <= 100.0,
+                "moved by {:?} at {:?}",
+                (dx, dy),
+                position
+            );
+            // Once found it stays absolute
+            assert!(absolute || !was_absolute, "lost track at {:?}", position);
+            was_absolute = absolute;
+        }
+        assert!(was_absolute);
+
+        // A relative mouse plugged in later is noticed again
+        assert_eq!(
+            tracker.update(MotionMode::Auto, (-4.0, 2.0)),
+            (false, (-4.0, 2.0))
+        );
+    }
+}
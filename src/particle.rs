@@ -0,0 +1,397 @@
+//! Short lived particles, like the bits flying off broken blocks and the ones the server
+//! spawns, simulated on the client and drawn together as one model.
+
+use crate::chunk_builder;
+use crate::render;
+use crate::render::model;
+use crate::shared::Position;
+use crate::world;
+use crate::world::block::Block;
+use cgmath::{InnerSpace, Vector3};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// The most particles alive at once, new ones are dropped while the pool is full.
+pub const MAX_PARTICLES: usize = 2048;
+/// The texture of every particle but the block ones, a grid of 16 by 16 sprites.
+const PARTICLES_TEXTURE: &str = "particle/particles";
+pub const WHITE: [u8; 3] = [255, 255, 255];
+pub const SMOKE_COLOR: [u8; 3] = [64, 64, 64];
+pub const REDSTONE_COLOR: [u8; 3] = [204, 0, 0];
+/// How much particles are pulled down, in blocks per frame delta squared.
+const GRAVITY: f64 = 0.04 / 9.0;
+/// How much of its speed a particle keeps every frame delta.
+const DRAG: f64 = 0.993;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleKind {
+    /// Bits of a block's texture, flying off when it's broken.
+    Block(Block),
+    /// Smoke, clouds and colored dust, shrinking away.
+    Puff,
+    Flame,
+    Crit,
+}
+
+impl ParticleKind {
+    fn gravity(&self) -> f64 {
+        match self {
+            ParticleKind::Block(_) | ParticleKind::Crit => GRAVITY,
+            ParticleKind::Puff | ParticleKind::Flame => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Particle {
+    pub kind: ParticleKind,
+    pub position: Vector3<f64>,
+    /// In blocks per frame delta (1/60ths of a second).
+    pub velocity: Vector3<f64>,
+    pub color: [u8; 3],
+    pub size: f32,
+    pub age: f64,
+    pub lifetime: f64,
+    /// Which part of a block's texture a block particle shows, from 0.0 to 0.75.
+    sprite: (f32, f32),
+}
+
+impl Particle {
+    pub fn new(kind: ParticleKind, position: Vector3<f64>, velocity: Vector3<f64>) -> Particle {
+        let mut rng = rand::thread_rng();
+        // In frame deltas, vanilla's ticks are three of them
+        let (size, lifetime) = match kind {
+            ParticleKind::Block(_) => (rng.gen_range(0.05..0.15), rng.gen_range(4.0..40.0) * 3.0),
+            ParticleKind::Puff => (rng.gen_range(0.1..0.25), rng.gen_range(8.0..40.0) * 3.0),
+            ParticleKind::Flame => (rng.gen_range(0.05..0.1), rng.gen_range(8.0..20.0) * 3.0),
+            ParticleKind::Crit => (0.1, rng.gen_range(6.0..20.0) * 3.0),
+        };
+        Particle {
+            kind,
+            position,
+            velocity,
+            color: WHITE,
+            size,
+            age: 0.0,
+            lifetime,
+            sprite: (
+                rng.gen_range(0..4) as f32 * 0.25,
+                rng.gen_range(0..4) as f32 * 0.25,
+            ),
+        }
+    }
+
+    /// Moves the particle along, returns whether it's still alive.
+    fn tick(&mut self, world: &world::World, delta: f64) -> bool {
+        self.age += delta;
+        if self.age >= self.lifetime {
+            return false;
+        }
+        self.velocity.y -= self.kind.gravity() * delta;
+        self.velocity *= DRAG.powf(delta);
+        let next = self.position + self.velocity * delta;
+        let block = world.get_block(Position::new(
+            next.x.floor() as i32,
+            next.y.floor() as i32,
+            next.z.floor() as i32,
+        ));
+        if block.get_material().collidable {
+            // Landed, sliding to a stop
+            self.velocity.y = 0.0;
+            self.velocity.x *= 0.7;
+            self.velocity.z *= 0.7;
+        } else {
+            self.position = next;
+        }
+        true
+    }
+}
+
+/// A fixed amount of particle slots, reused once their particles are gone.
+pub struct ParticlePool {
+    slots: Vec<Option<Particle>>,
+    free: Vec<usize>,
+    capacity: usize,
+}
+
+impl ParticlePool {
+    pub fn new(capacity: usize) -> ParticlePool {
+        ParticlePool {
+            slots: vec![],
+            free: vec![],
+            capacity,
+        }
+    }
+
+    /// Adds a particle, returning its slot, or `None` when the pool is full.
+    pub fn spawn(&mut self, particle: Particle) -> Option<usize> {
+        if let Some(slot) = self.free.pop() {
+            self.slots[slot] = Some(particle);
+            Some(slot)
+        } else if self.slots.len() < self.capacity {
+            self.slots.push(Some(particle));
+            Some(self.slots.len() - 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn remove(&mut self, slot: usize) {
+        if let Some(particle) = self.slots.get_mut(slot) {
+            if particle.take().is_some() {
+                self.free.push(slot);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.slots.iter().flatten()
+    }
+
+    fn tick(&mut self, world: &world::World, delta: f64) {
+        for (slot, particle) in self.slots.iter_mut().enumerate() {
+            if particle.as_mut().map_or(false, |p| !p.tick(world, delta)) {
+                *particle = None;
+                self.free.push(slot);
+            }
+        }
+    }
+}
+
+/// The particles of a server, drawn in a single model rebuilt every frame.
+pub struct Particles {
+    pool: ParticlePool,
+    /// How many of the requested particles are spawned, from 0.0 to 1.0. See
+    /// `settings::particle_density`.
+    pub density: f32,
+    model: Option<model::ModelKey>,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Particles {
+            pool: ParticlePool::new(MAX_PARTICLES),
+            density: 1.0,
+            model: None,
+        }
+    }
+}
+
+impl Particles {
+    /// Spawns `count` particles scaled by the density, spread around `position` by up to
+    /// `offset` and flying off at up to `speed` blocks per tick. Like vanilla, a count of 0
+    /// spawns a single particle moving along `offset` instead.
+    pub fn emit(
+        &mut self,
+        kind: ParticleKind,
+        color: [u8; 3],
+        position: Vector3<f64>,
+        offset: Vector3<f64>,
+        speed: f64,
+        count: i32,
+    ) {
+        let mut rng = rand::thread_rng();
+        if count == 0 {
+            if rng.gen::<f32>() < self.density {
+                let mut particle = Particle::new(kind, position, offset * speed / 3.0);
+                particle.color = color;
+                self.pool.spawn(particle);
+            }
+            return;
+        }
+        let count = (count as f32 * self.density).round() as usize;
+        for _ in 0..count {
+            let spread = Vector3::new(
+                offset.x * rng.gen_range(-1.0..1.0),
+                offset.y * rng.gen_range(-1.0..1.0),
+                offset.z * rng.gen_range(-1.0..1.0),
+            );
+            let velocity = Vector3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            ) * speed
+                / 3.0;
+            let mut particle = Particle::new(kind, position + spread, velocity);
+            particle.color = color;
+            if self.pool.spawn(particle).is_none() {
+                break;
+            }
+        }
+    }
+
+    /// Breaks `block` at `pos` into bits of its texture, four by four by four of them.
+    pub fn spawn_block_break(&mut self, pos: Position, block: Block) {
+        if !block.get_material().renderable {
+            return;
+        }
+        let kind = ParticleKind::Block(block);
+        let mut rng = rand::thread_rng();
+        for x in 0..4 {
+            for y in 0..4 {
+                for z in 0..4 {
+                    if rng.gen::<f32>() >= self.density {
+                        continue;
+                    }
+                    let inside = Vector3::new(
+                        (x as f64 + 0.5) / 4.0,
+                        (y as f64 + 0.5) / 4.0,
+                        (z as f64 + 0.5) / 4.0,
+                    );
+                    // Away from the middle of the block, and a bit upwards
+                    let velocity = (inside - Vector3::new(0.5, 0.5, 0.5)) * 0.1
+                        + Vector3::new(0.0, rng.gen_range(0.0..0.05), 0.0);
+                    let position = Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64) + inside;
+                    if self
+                        .pool
+                        .spawn(Particle::new(kind, position, velocity))
+                        .is_none()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn tick(&mut self, world: &world::World, delta: f64) {
+        self.pool.tick(world, delta);
+    }
+
+    /// Rebuilds the model of the particles, facing the camera. Block particles take their
+    /// texture from the block models loaded by `chunk_builder`.
+    pub fn render(
+        &mut self,
+        renderer: &mut render::Renderer,
+        chunk_builder: &chunk_builder::ChunkBuilder,
+    ) {
+        if self.pool.is_empty() && self.model.is_none() {
+            return;
+        }
+        let camera = &renderer.camera;
+        let forward = render::look_direction(camera.yaw, camera.pitch);
+        let right = forward.cross(Vector3::unit_y()).normalize();
+        let up = right.cross(forward).normalize();
+        let (right, up) = (right.cast::<f32>().unwrap(), up.cast::<f32>().unwrap());
+
+        let sprites = render::Renderer::get_texture(renderer.get_textures_ref(), PARTICLES_TEXTURE);
+        let mut block_textures = HashMap::new();
+        let mut verts = Vec::with_capacity(self.pool.len() * 8);
+        for particle in self.pool.iter() {
+            let progress = (particle.age / particle.lifetime) as f32;
+            let texture = match particle.kind {
+                ParticleKind::Block(block) => {
+                    let texture = block_textures
+                        .entry(block)
+                        .or_insert_with(|| chunk_builder.particle_texture(block));
+                    let (u, v) = particle.sprite;
+                    match texture {
+                        Some(texture) => texture.relative(u, v, 0.25, 0.25),
+                        // Not loaded, nothing to show
+                        None => continue,
+                    }
+                }
+                // Shrinks through the 8 sprites of the first row
+                ParticleKind::Puff => sprite(&sprites, 7 - ((progress * 8.0) as i32).min(7)),
+                ParticleKind::Flame => sprite(&sprites, 48),
+                ParticleKind::Crit => sprite(&sprites, 65),
+            };
+            let center = particle.position.cast::<f32>().unwrap();
+            let size = particle.size;
+            let corners = [
+                (-1.0, -1.0, 0.0, 1.0),
+                (-1.0, 1.0, 0.0, 0.0),
+                (1.0, -1.0, 1.0, 1.0),
+                (1.0, 1.0, 1.0, 0.0),
+            ];
+            let vertex = |&(across, upwards, texture_x, texture_y): &(f32, f32, f64, f64)| {
+                let pos = center + right * (across * size) + up * (upwards * size);
+                model::Vertex {
+                    x: pos.x,
+                    y: pos.y,
+                    z: pos.z,
+                    texture_x,
+                    texture_y,
+                    texture: texture.clone(),
+                    r: particle.color[0],
+                    g: particle.color[1],
+                    b: particle.color[2],
+                    a: 255,
+                    id: 0,
+                }
+            };
+            // Both sides, so that the winding doesn't matter
+            for &i in &[0, 1, 2, 3, 0, 2, 1, 3] {
+                verts.push(vertex(&corners[i]));
+            }
+        }
+
+        match self.model {
+            Some(model) => renderer.model.update_model(model, verts),
+            None => {
+                let model = renderer.model.create_model(model::DEFAULT, vec![verts]);
+                let mdl = renderer.model.get_model(model).unwrap();
+                // Drawn wherever the camera looks
+                mdl.radius = f32::MAX;
+                self.model = Some(model);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, renderer: &mut render::Renderer) {
+        if let Some(model) = self.model.take() {
+            renderer.model.remove_model(model);
+        }
+    }
+}
+
+/// The sprite at `index` of the particles texture.
+fn sprite(sprites: &render::Texture, index: i32) -> render::Texture {
+    const SIZE: f32 = 1.0 / 16.0;
+    sprites.relative(
+        (index % 16) as f32 * SIZE,
+        (index / 16) as f32 * SIZE,
+        SIZE,
+        SIZE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crit() -> Particle {
+        Particle::new(
+            ParticleKind::Crit,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn pool_reuses_freed_slots() {
+        let mut pool = ParticlePool::new(3);
+        assert_eq!(pool.spawn(crit()), Some(0));
+        assert_eq!(pool.spawn(crit()), Some(1));
+        pool.remove(0);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.spawn(crit()), Some(0));
+        assert_eq!(pool.spawn(crit()), Some(2));
+        // Full, until a particle is gone
+        assert_eq!(pool.spawn(crit()), None);
+        assert_eq!(pool.len(), 3);
+        // Removing twice frees the slot once
+        pool.remove(1);
+        pool.remove(1);
+        assert_eq!(pool.spawn(crit()), Some(1));
+        assert_eq!(pool.spawn(crit()), None);
+    }
+}
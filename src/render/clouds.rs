@@ -7,6 +7,55 @@ use cgmath::{Matrix4, Point3};
 use log::error;
 use parking_lot::RwLock;
 
+/// The height of the bottom of the cloud layer, like vanilla.
+pub const CLOUD_HEIGHT: f32 = 128.0;
+/// How often the cloud texture scrolls by completely per in-game day. Whole cycles let the
+/// clouds continue seamlessly when the time of day wraps around.
+const CYCLES_PER_DAY: f64 = 4.0;
+/// The size of the cloud texture, in clouds.
+const TEXTURE_SIZE: f64 = 256.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloudMode {
+    Off,
+    /// A flat layer.
+    Fast,
+    /// Solid boxes.
+    Fancy,
+}
+
+impl CloudMode {
+    /// Parses the value of `settings::R_CLOUDS`, unknown values draw fancy clouds.
+    pub fn from_name(name: &str) -> CloudMode {
+        match name {
+            "off" => CloudMode::Off,
+            "fast" => CloudMode::Fast,
+            _ => CloudMode::Fancy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CloudMode::Off => "off",
+            CloudMode::Fast => "fast",
+            CloudMode::Fancy => "fancy",
+        }
+    }
+
+    pub fn next(self) -> CloudMode {
+        match self {
+            CloudMode::Off => CloudMode::Fast,
+            CloudMode::Fast => CloudMode::Fancy,
+            CloudMode::Fancy => CloudMode::Off,
+        }
+    }
+}
+
+/// How many clouds the layer moved by at `world_time`, the time of day in ticks.
+pub fn scroll_offset(world_time: f64) -> f64 {
+    world_time * CYCLES_PER_DAY * TEXTURE_SIZE / 24000.0
+}
+
 pub struct Clouds {
     program: gl::Program,
     // Shader props
@@ -21,6 +70,7 @@ pub struct Clouds {
     u_textures: gl::Uniform,
     u_cloud_map: gl::Uniform,
     u_cloud_offset: gl::Uniform,
+    u_fancy: gl::Uniform,
 
     array: gl::VertexArray,
     _buffer: gl::Buffer,
@@ -31,7 +81,6 @@ pub struct Clouds {
     pub heightmap_data: Vec<u8>,
     pub dirty: bool,
 
-    offset: f64,
     num_points: usize,
 }
 
@@ -105,6 +154,7 @@ impl Clouds {
         let u_textures = program.uniform_location("textures").unwrap();
         let u_cloud_map = program.uniform_location("cloudMap").unwrap();
         let u_cloud_offset = program.uniform_location("cloudOffset").unwrap();
+        let u_fancy = program.uniform_location("fancy").unwrap();
 
         let array = gl::VertexArray::new();
         array.bind();
@@ -118,7 +168,7 @@ impl Clouds {
         for x in -160..160 {
             for z in -160..160 {
                 let _ = data.write_f32::<NativeEndian>(x as f32);
-                let _ = data.write_f32::<NativeEndian>(128.0);
+                let _ = data.write_f32::<NativeEndian>(CLOUD_HEIGHT);
                 let _ = data.write_f32::<NativeEndian>(z as f32);
                 num_points += 1;
             }
@@ -156,6 +206,7 @@ impl Clouds {
             u_textures,
             u_cloud_map,
             u_cloud_offset,
+            u_fancy,
 
             array,
             _buffer: buffer,
@@ -166,11 +217,12 @@ impl Clouds {
             heightmap_data,
             dirty: false,
 
-            offset: 0.0,
             num_points,
         }
     }
 
+    /// Draws the whole layer in a single call, `mode` must not be `CloudMode::Off`.
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &mut self,
         camera_pos: &Point3<f64>,
@@ -178,10 +230,9 @@ impl Clouds {
         camera_matrix: &Matrix4<f32>,
         light_level: f32,
         sky_offset: f32,
-        delta: f64,
+        world_time: f64,
+        mode: CloudMode,
     ) {
-        self.offset += delta;
-
         let tex = super::Renderer::get_texture(&self.textures, "leafish:environment/clouds");
 
         self.program.use_program();
@@ -201,7 +252,9 @@ impl Clouds {
             tex.get_height() as f32,
         );
         self.u_atlas.set_float(tex.atlas as f32);
-        self.u_cloud_offset.set_float((self.offset / 60.0) as f32);
+        self.u_cloud_offset
+            .set_float((scroll_offset(world_time) % TEXTURE_SIZE) as f32);
+        self.u_fancy.set_int((mode == CloudMode::Fancy) as i32);
         self.u_textures.set_int(0);
 
         gl::active_texture(1);
@@ -225,3 +278,25 @@ impl Clouds {
         gl::draw_arrays(gl::POINTS, 0, self.num_points);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clouds_scroll_with_world_time() {
+        assert_eq!(scroll_offset(0.0), 0.0);
+        // 4 cycles of 256 clouds per day, a bit under a cloud per second
+        assert_eq!(scroll_offset(20.0), 20.0 * 1024.0 / 24000.0);
+        assert_eq!(scroll_offset(6000.0), TEXTURE_SIZE);
+        // The end of the day lines up with its start
+        assert_eq!(scroll_offset(24000.0) % TEXTURE_SIZE, 0.0);
+
+        assert_eq!(CloudMode::from_name("fast"), CloudMode::Fast);
+        assert_eq!(CloudMode::from_name("unknown"), CloudMode::Fancy);
+        assert_eq!(
+            CloudMode::from_name(CloudMode::Off.name()).next(),
+            CloudMode::Fast
+        );
+    }
+}
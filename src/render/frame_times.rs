@@ -0,0 +1,86 @@
+//! A rolling window of recent frame times, shown as a graph in the debug overlay.
+
+use std::collections::VecDeque;
+
+use instant::Duration;
+
+/// How many frames the window covers, two seconds at 60fps.
+pub const FRAME_TIME_SAMPLES: usize = 120;
+
+#[derive(Clone, Debug, Default)]
+pub struct FrameTimes {
+    /// Frame times in milliseconds, oldest first.
+    samples: VecDeque<f64>,
+}
+
+/// Statistics over the frames in the window, in milliseconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameStats {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+}
+
+impl FrameTimes {
+    pub fn new() -> FrameTimes {
+        FrameTimes {
+            samples: VecDeque::with_capacity(FRAME_TIME_SAMPLES),
+        }
+    }
+
+    /// Adds the time a frame took, dropping the oldest one once the window is full.
+    pub fn push(&mut self, frame_time: Duration) {
+        if self.samples.len() == FRAME_TIME_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frame_time.as_secs_f64() * 1000.0);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = f64> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn stats(&self) -> Option<FrameStats> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let (min, max, sum) = self.samples().fold(
+            (f64::INFINITY, f64::NEG_INFINITY, 0.0),
+            |(min, max, sum), sample| (min.min(sample), max.max(sample), sum + sample),
+        );
+        Some(FrameStats {
+            min,
+            avg: sum / self.samples.len() as f64,
+            max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_frame_time_stats() {
+        let mut frame_times = FrameTimes::new();
+        assert_eq!(frame_times.stats(), None);
+
+        for ms in &[10, 20, 30, 40] {
+            frame_times.push(Duration::from_millis(*ms));
+        }
+        let stats = frame_times.stats().unwrap();
+        assert_eq!((stats.min, stats.max), (10.0, 40.0));
+        assert!((stats.avg - 25.0).abs() < 1e-9);
+
+        // Once full, the slow frames at the start fall out of the window
+        for _ in 0..FRAME_TIME_SAMPLES - 1 {
+            frame_times.push(Duration::from_millis(5));
+        }
+        assert_eq!(frame_times.samples().count(), FRAME_TIME_SAMPLES);
+        let stats = frame_times.stats().unwrap();
+        assert_eq!((stats.min, stats.max), (5.0, 40.0));
+        frame_times.push(Duration::from_millis(5));
+        let stats = frame_times.stats().unwrap();
+        assert_eq!((stats.min, stats.avg, stats.max), (5.0, 5.0, 5.0));
+    }
+}
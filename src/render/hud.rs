@@ -12,12 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::cmp;
 use std::cmp::Ordering;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use log::debug;
+use chrono::{DateTime, Local};
+use instant::{Duration, Instant};
 use parking_lot::RwLock;
 use rand::rngs::ThreadRng;
 use rand::Rng;
@@ -26,23 +26,222 @@ use crate::format;
 use crate::inventory::player_inventory::PlayerInventory;
 use crate::inventory::{Inventory, Item};
 use crate::render;
+use crate::render::frame_times::FrameTimes;
 use crate::render::Renderer;
 use crate::screen::Screen;
-use crate::server::Server;
+use crate::server::chat::ChatPosition;
+use crate::server::{ping_icon, Server};
 use crate::ui;
 use crate::ui::{Container, FormattedRef, HAttach, ImageRef, TextRef, VAttach};
 use leafish_protocol::protocol::packet::play::serverbound::HeldItemChange;
+use leafish_protocol::protocol::UUID;
 use leafish_protocol::types::GameMode;
 
 // Textures can be found at: assets/minecraft/textures/gui/icons.png
 
+/// Minimum time between two redraws of the debug overlay, in milliseconds.
+const DEBUG_UPDATE_INTERVAL: u128 = 250;
+/// Frame time at the top of the debug overlay's graph, twice the length of a 60fps frame.
+const FRAME_GRAPH_MAX_MS: f64 = 1000.0 / 30.0;
+/// Players listed in a column of the player list before starting another one.
+const PLAYER_LIST_ROWS: usize = 20;
+/// How many of the latest chat messages are shown above the hotbar.
+const SHOWN_CHAT_MESSAGES: usize = 10;
+/// How long a game info message stays above the hotbar, vanilla's 60 ticks.
+const ACTION_BAR_TIME: Duration = Duration::from_secs(3);
+/// How far a boss bar's shown health moves towards its actual health each frame delta
+/// (1/60th of a second), so changes slide rather than jump.
+const BOSS_BAR_LERP: f32 = 0.15;
+
+/// Information about the player and world shown by the debug overlay.
+#[derive(Clone, Copy, Default)]
+pub struct DebugInfo {
+    pub position: (f64, f64, f64),
+    pub yaw: f64,
+    pub pitch: f64,
+    pub loaded_chunks: usize,
+    pub drawn_chunks: usize,
+    pub culled_chunks: usize,
+}
+
+/// How the chat keeps and shows messages, from the `cl_chat_*` settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChatOptions {
+    /// Messages kept, the oldest ones are dropped after that.
+    pub max_messages: usize,
+    /// Whether messages start with the time they were received at.
+    pub timestamps: bool,
+    /// How long messages stay above the hotbar while the chat is closed, `None` for always.
+    pub fade_after: Option<Duration>,
+}
+
+impl Default for ChatOptions {
+    fn default() -> Self {
+        ChatOptions {
+            max_messages: 100,
+            timestamps: false,
+            fade_after: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+/// A message in the chat history.
+#[derive(Clone, Debug)]
+pub struct ChatLine {
+    pub message: format::Component,
+    pub received: Instant,
+    /// When it was received, shown in the local time zone.
+    received_at: DateTime<Local>,
+}
+
+impl ChatLine {
+    pub fn new(message: format::Component) -> ChatLine {
+        ChatLine {
+            message,
+            received: Instant::now(),
+            received_at: Local::now(),
+        }
+    }
+
+    /// The message as shown, starting with the local time it was received at when
+    /// `timestamps` are enabled.
+    pub fn text(&self, timestamps: bool) -> format::Component {
+        if !timestamps {
+            return self.message.clone();
+        }
+        let mut time = format::TextComponent::new(&self.received_at.format("[%H:%M] ").to_string());
+        time.modifier.color = Some(format::Color::Gray);
+        let mut text = format::TextComponent::new("");
+        text.modifier.extra = Some(vec![format::Component::Text(time), self.message.clone()]);
+        format::Component::Text(text)
+    }
+
+    /// Whether the message is still shown while the chat is closed.
+    pub fn is_fresh(&self, options: &ChatOptions) -> bool {
+        options
+            .fade_after
+            .map_or(true, |fade_after| self.received.elapsed() < fade_after)
+    }
+}
+
+/// The block the player is in and its biome, shown by the coordinates line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Coordinates {
+    pub block: (i32, i32, i32),
+    pub biome: &'static str,
+}
+
+impl Coordinates {
+    fn text(self) -> String {
+        let (x, y, z) = self.block;
+        format!("XYZ: {} {} {}  Biome: {}", x, y, z, self.biome)
+    }
+}
+
+/// The color of a boss bar, the row of `gui/bars.png` it is drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossBarColor {
+    Pink,
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White,
+}
+
+impl BossBarColor {
+    pub fn from_int(color: i32) -> BossBarColor {
+        match color {
+            1 => BossBarColor::Blue,
+            2 => BossBarColor::Red,
+            3 => BossBarColor::Green,
+            4 => BossBarColor::Yellow,
+            5 => BossBarColor::Purple,
+            6 => BossBarColor::White,
+            _ => BossBarColor::Pink,
+        }
+    }
+}
+
+/// Into how many segments notches divide a boss bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossBarDivision {
+    None,
+    Notches6,
+    Notches10,
+    Notches12,
+    Notches20,
+}
+
+impl BossBarDivision {
+    pub fn from_int(division: i32) -> BossBarDivision {
+        match division {
+            1 => BossBarDivision::Notches6,
+            2 => BossBarDivision::Notches10,
+            3 => BossBarDivision::Notches12,
+            4 => BossBarDivision::Notches20,
+            _ => BossBarDivision::None,
+        }
+    }
+}
+
+/// A boss bar shown at the top of the screen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BossBar {
+    pub title: format::Component,
+    /// From 0 to 1.
+    pub health: f32,
+    /// The health drawn, following `health` over a few frames.
+    pub shown_health: f32,
+    pub color: BossBarColor,
+    pub division: BossBarDivision,
+    /// Darken sky, play boss music and create fog, unused for now.
+    pub flags: u8,
+}
+
+impl BossBar {
+    pub fn new(
+        title: format::Component,
+        health: f32,
+        color: BossBarColor,
+        division: BossBarDivision,
+        flags: u8,
+    ) -> BossBar {
+        let health = health.max(0.0).min(1.0);
+        BossBar {
+            title,
+            health,
+            shown_health: health,
+            color,
+            division,
+            flags,
+        }
+    }
+}
+
+/// A change to the boss bars, as sent by the server's BossBar packet.
+pub enum BossBarUpdate {
+    Add(BossBar),
+    Remove,
+    Health(f32),
+    Title(format::Component),
+    Style(BossBarColor, BossBarDivision),
+    Flags(u8),
+}
+
 // TODO: read out "regen: bool"
 #[allow(dead_code)]
 pub struct HudContext {
     pub enabled: bool,
     pub debug: bool,
     fps: u32,
+    debug_info: DebugInfo,
+    frame_times: FrameTimes,
+    last_debug_update: u128,
     dirty_debug: bool,
+    coordinates: Option<Coordinates>,
+    dirty_coordinates: bool,
     hardcore: bool,  // TODO: Update this!
     wither: bool,    // TODO: Update this!
     poison: bool,    // TODO: Update this!
@@ -63,6 +262,7 @@ pub struct HudContext {
     dirty_armor: bool,
     exp: f32,
     exp_level: i32,
+    total_exp: i32,
     dirty_exp: bool,
     breath: i16, // TODO: Update this!
     dirty_breath: bool,
@@ -73,8 +273,21 @@ pub struct HudContext {
     dirty_slot_index: bool,
     game_mode: GameMode,
     dirty_game_mode: bool,
-    chat_history: Vec<format::Component>,
+    chat_history: Vec<ChatLine>,
+    chat_options: ChatOptions,
+    /// Messages received since joining, counting the ones no longer kept.
+    received_messages: usize,
     dirty_chat: bool,
+    /// Lines typed into the chat, oldest first.
+    sent_messages: Vec<String>,
+    /// The latest game info message, shown above the hotbar for a while.
+    action_bar: Option<ChatLine>,
+    dirty_action_bar: bool,
+    player_list_shown: bool,
+    dirty_player_list: bool,
+    /// Shown top to bottom in the order they were added.
+    boss_bars: Vec<(UUID, BossBar)>,
+    dirty_boss_bars: bool,
 }
 
 impl Default for render::hud::HudContext {
@@ -88,7 +301,12 @@ impl HudContext {
             enabled: true,
             debug: false,
             fps: 0,
+            debug_info: DebugInfo::default(),
+            frame_times: FrameTimes::new(),
+            last_debug_update: 0,
             dirty_debug: false,
+            coordinates: None,
+            dirty_coordinates: false,
             hardcore: false,
             wither: false,
             poison: false,
@@ -109,6 +327,7 @@ impl HudContext {
             dirty_armor: false,
             exp: 0.0, // 0.0 - 1.0
             exp_level: 0,
+            total_exp: 0,
             dirty_exp: false,
             breath: 0, /*-1*/
             // -1 = disabled (not under water) | 1 bubble = 30 | +2 = broken bubble -- -1 is causing crashes when attempting to join servers!
@@ -121,7 +340,16 @@ impl HudContext {
             game_mode: GameMode::Survival,
             dirty_game_mode: false,
             chat_history: Vec::new(),
+            chat_options: ChatOptions::default(),
+            received_messages: 0,
             dirty_chat: false,
+            sent_messages: Vec::new(),
+            action_bar: None,
+            dirty_action_bar: false,
+            player_list_shown: false,
+            dirty_player_list: false,
+            boss_bars: Vec::new(),
+            dirty_boss_bars: false,
         }
     }
 
@@ -164,9 +392,10 @@ impl HudContext {
         self.dirty_breath = true;
     }
 
-    pub fn update_exp(&mut self, exp: f32, level: i32) {
+    pub fn update_exp(&mut self, exp: f32, level: i32, total_exp: i32) {
         self.exp = exp;
         self.exp_level = level;
+        self.total_exp = total_exp;
         self.dirty_exp = true;
     }
 
@@ -182,6 +411,35 @@ impl HudContext {
         }
     }
 
+    /// Updates what the debug overlay shows, throttled to avoid re-laying out its text
+    /// every frame.
+    pub fn update_debug_info(&mut self, debug_info: DebugInfo) {
+        let start = SystemTime::now();
+        let time = start.duration_since(UNIX_EPOCH).unwrap().as_millis();
+        if !self.debug || time < self.last_debug_update + DEBUG_UPDATE_INTERVAL {
+            return;
+        }
+        self.last_debug_update = time;
+        self.debug_info = debug_info;
+        self.dirty_debug = true;
+    }
+
+    /// Updates the coordinates line, `None` hiding it. Only redrawn once the player moved
+    /// into another block.
+    pub fn update_coordinates(&mut self, coordinates: Option<Coordinates>) {
+        if self.coordinates != coordinates {
+            self.coordinates = coordinates;
+            self.dirty_coordinates = true;
+        }
+    }
+
+    /// Copies the frame times shown by the debug overlay, only while it is visible.
+    pub fn update_frame_times(&mut self, frame_times: &FrameTimes) {
+        if self.debug {
+            self.frame_times = frame_times.clone();
+        }
+    }
+
     pub fn update_game_mode(&mut self, game_mode: GameMode) {
         self.game_mode = game_mode;
         self.dirty_game_mode = true;
@@ -191,10 +449,160 @@ impl HudContext {
         self.slot_index
     }
 
+    pub fn get_health(&self) -> f32 {
+        self.health
+    }
+
+    pub fn get_food(&self) -> u8 {
+        self.food
+    }
+
+    /// The progress towards the next level, from 0 to 1.
+    pub fn get_exp(&self) -> f32 {
+        self.exp
+    }
+
+    pub fn get_exp_level(&self) -> i32 {
+        self.exp_level
+    }
+
+    /// All experience collected since the last death, shown as the score when dying.
+    pub fn get_total_exp(&self) -> i32 {
+        self.total_exp
+    }
+
+    pub fn get_game_mode(&self) -> GameMode {
+        self.game_mode
+    }
+
     pub fn display_message_in_chat(&mut self, message: format::Component) {
-        self.chat_history.push(message);
+        self.chat_history.push(ChatLine::new(message));
+        self.received_messages += 1;
+        self.trim_chat_history();
         self.dirty_chat = true;
     }
+
+    /// Shows a message from the server where its position asks for, game info above the
+    /// hotbar and everything else in the chat.
+    pub fn display_server_message(&mut self, message: format::Component, position: ChatPosition) {
+        match position {
+            ChatPosition::ActionBar => {
+                self.action_bar = Some(ChatLine::new(message));
+                self.dirty_action_bar = true;
+            }
+            ChatPosition::Chat | ChatPosition::System => self.display_message_in_chat(message),
+        }
+    }
+
+    /// The game info message above the hotbar, if it's still shown.
+    pub fn get_action_bar(&self) -> Option<&ChatLine> {
+        self.action_bar
+            .as_ref()
+            .filter(|line| line.received.elapsed() < ACTION_BAR_TIME)
+    }
+
+    pub fn get_chat_history(&self) -> &[ChatLine] {
+        &self.chat_history
+    }
+
+    pub fn get_received_messages(&self) -> usize {
+        self.received_messages
+    }
+
+    pub fn get_chat_options(&self) -> ChatOptions {
+        self.chat_options
+    }
+
+    pub fn set_chat_options(&mut self, options: ChatOptions) {
+        if self.chat_options != options {
+            self.chat_options = options;
+            self.trim_chat_history();
+            self.dirty_chat = true;
+        }
+    }
+
+    fn trim_chat_history(&mut self) {
+        let excess = self
+            .chat_history
+            .len()
+            .saturating_sub(self.chat_options.max_messages);
+        self.chat_history.drain(..excess);
+    }
+
+    /// Remembers a line typed into the chat, so it can be brought back with the arrow keys.
+    pub fn remember_sent_message(&mut self, line: String) {
+        if self.sent_messages.last() != Some(&line) {
+            self.sent_messages.push(line);
+        }
+    }
+
+    pub fn get_sent_messages(&self) -> &[String] {
+        &self.sent_messages
+    }
+
+    /// Shows the player list while its key is held.
+    pub fn show_player_list(&mut self, shown: bool) {
+        if self.player_list_shown != shown {
+            self.player_list_shown = shown;
+            self.dirty_player_list = true;
+        }
+    }
+
+    /// Redraws the player list after the server changed it.
+    pub fn update_player_list(&mut self) {
+        self.dirty_player_list |= self.player_list_shown;
+    }
+
+    /// Applies a change the server made to the boss bar `uuid`. Changes to bars that
+    /// don't exist are ignored.
+    pub fn update_boss_bar(&mut self, uuid: UUID, update: BossBarUpdate) {
+        let index = self.boss_bars.iter().position(|(id, _)| *id == uuid);
+        match (index, update) {
+            (None, BossBarUpdate::Add(bar)) => self.boss_bars.push((uuid, bar)),
+            (Some(index), BossBarUpdate::Add(bar)) => self.boss_bars[index].1 = bar,
+            (Some(index), BossBarUpdate::Remove) => {
+                self.boss_bars.remove(index);
+            }
+            (Some(index), BossBarUpdate::Health(health)) => {
+                self.boss_bars[index].1.health = health.max(0.0).min(1.0);
+            }
+            (Some(index), BossBarUpdate::Title(title)) => self.boss_bars[index].1.title = title,
+            (Some(index), BossBarUpdate::Style(color, division)) => {
+                self.boss_bars[index].1.color = color;
+                self.boss_bars[index].1.division = division;
+            }
+            (Some(index), BossBarUpdate::Flags(flags)) => self.boss_bars[index].1.flags = flags,
+            (None, _) => return,
+        }
+        self.dirty_boss_bars = true;
+    }
+
+    /// Removes all boss bars, e.g. when joining another server.
+    pub fn clear_boss_bars(&mut self) {
+        if !self.boss_bars.is_empty() {
+            self.boss_bars.clear();
+            self.dirty_boss_bars = true;
+        }
+    }
+
+    pub fn get_boss_bars(&self) -> &[(UUID, BossBar)] {
+        &self.boss_bars
+    }
+
+    /// Moves the shown health of the boss bars towards their health.
+    fn animate_boss_bars(&mut self, delta: f64) {
+        let step = (BOSS_BAR_LERP * delta as f32).min(1.0);
+        for (_, bar) in &mut self.boss_bars {
+            if bar.shown_health == bar.health {
+                continue;
+            }
+            bar.shown_health += (bar.health - bar.shown_health) * step;
+            if (bar.health - bar.shown_health).abs() < 0.001 {
+                bar.shown_health = bar.health;
+            }
+            self.dirty_boss_bars = true;
+        }
+    }
 }
 
 pub struct Hud {
@@ -210,10 +618,22 @@ pub struct Hud {
     slot_elements: Vec<ImageRef>,
     slot_index_elements: Vec<ImageRef>,
     debug_elements: Vec<TextRef>,
+    coordinate_elements: Vec<TextRef>,
+    frame_graph_elements: Vec<ImageRef>,
     chat_elements: Vec<FormattedRef>,
     chat_background_elements: Vec<ImageRef>,
+    /// When the oldest message above the hotbar fades out.
+    chat_fades_at: Option<Instant>,
+    action_bar_elements: Vec<FormattedRef>,
+    /// When the message above the hotbar disappears.
+    action_bar_fades_at: Option<Instant>,
+    player_list_elements: Vec<ImageRef>,
+    boss_bar_elements: Vec<ImageRef>,
+    boss_bar_text_elements: Vec<FormattedRef>,
     hud_context: Arc<RwLock<HudContext>>,
     random: ThreadRng,
+    /// What was scrolled short of a whole line, kept until it adds up to one.
+    scrolled: f64,
 }
 
 impl Hud {
@@ -231,10 +651,19 @@ impl Hud {
             slot_elements: vec![],
             slot_index_elements: vec![],
             debug_elements: vec![],
+            coordinate_elements: vec![],
+            frame_graph_elements: vec![],
             chat_elements: vec![],
             chat_background_elements: vec![],
+            chat_fades_at: None,
+            action_bar_elements: vec![],
+            action_bar_fades_at: None,
+            player_list_elements: vec![],
+            boss_bar_elements: vec![],
+            boss_bar_text_elements: vec![],
             hud_context,
             random: rand::thread_rng(),
+            scrolled: 0.0,
         }
     }
 }
@@ -247,6 +676,10 @@ impl Screen for Hud {
             self.render_slot_index(renderer, ui_container);
             self.render_crosshair(renderer, ui_container);
             self.render_chat(renderer, ui_container);
+            self.render_action_bar(renderer, ui_container);
+            self.render_player_list(renderer, ui_container);
+            self.render_boss_bars(renderer, ui_container);
+            self.render_coordinates(renderer, ui_container);
             let game_mode = self.hud_context.clone().read().game_mode;
             if matches!(game_mode, GameMode::Adventure | GameMode::Survival) {
                 self.render_health(renderer, ui_container);
@@ -269,13 +702,20 @@ impl Screen for Hud {
         self.slot_elements.clear();
         self.slot_index_elements.clear();
         self.debug_elements.clear();
+        self.frame_graph_elements.clear();
+        self.last_debug_enabled = false;
+        self.coordinate_elements.clear();
         self.chat_elements.clear();
         self.chat_background_elements.clear();
+        self.action_bar_elements.clear();
+        self.player_list_elements.clear();
+        self.boss_bar_elements.clear();
+        self.boss_bar_text_elements.clear();
     }
 
     fn tick(
         &mut self,
-        _delta: f64,
+        delta: f64,
         renderer: &mut render::Renderer,
         ui_container: &mut ui::Container,
     ) -> Option<Box<dyn Screen>> {
@@ -291,12 +731,17 @@ impl Screen for Hud {
             self.last_enabled = true;
             return None;
         }
-        if self.hud_context.clone().read().debug {
+        let debug = self.hud_context.clone().read().debug;
+        if debug && !self.last_debug_enabled {
             self.render_debug(renderer, ui_container);
             self.last_debug_enabled = true;
-        } else if self.last_debug_enabled {
+            // The debug overlay shows the coordinates in its place
+            self.coordinate_elements.clear();
+        } else if !debug && self.last_debug_enabled {
             self.debug_elements.clear();
+            self.frame_graph_elements.clear();
             self.last_debug_enabled = false;
+            self.render_coordinates(renderer, ui_container);
         }
         let game_mode = self.hud_context.clone().read().game_mode;
         if self.hud_context.clone().read().dirty_game_mode {
@@ -349,70 +794,65 @@ impl Screen for Hud {
             self.slot_index_elements.clear();
             self.render_slot_index(renderer, ui_container);
         }
-        if self.hud_context.clone().read().dirty_debug {
+        if debug && self.hud_context.clone().read().dirty_debug {
             self.debug_elements.clear();
+            self.frame_graph_elements.clear();
             self.render_debug(renderer, ui_container);
         }
+        if self.hud_context.clone().read().dirty_coordinates {
+            self.coordinate_elements.clear();
+            self.render_coordinates(renderer, ui_container);
+        }
+        if self
+            .chat_fades_at
+            .map_or(false, |fades_at| Instant::now() >= fades_at)
+        {
+            self.hud_context.clone().write().dirty_chat = true;
+        }
         if self.hud_context.clone().read().dirty_chat {
             self.chat_elements.clear();
             self.chat_background_elements.clear();
             self.render_chat(renderer, ui_container);
         }
+        if self
+            .action_bar_fades_at
+            .map_or(false, |fades_at| Instant::now() >= fades_at)
+        {
+            self.hud_context.clone().write().dirty_action_bar = true;
+        }
+        if self.hud_context.clone().read().dirty_action_bar {
+            self.action_bar_elements.clear();
+            self.render_action_bar(renderer, ui_container);
+        }
+        if self.hud_context.clone().read().dirty_player_list {
+            self.player_list_elements.clear();
+            self.render_player_list(renderer, ui_container);
+        }
+        self.hud_context.clone().write().animate_boss_bars(delta);
+        if self.hud_context.clone().read().dirty_boss_bars {
+            self.boss_bar_elements.clear();
+            self.boss_bar_text_elements.clear();
+            self.render_boss_bars(renderer, ui_container);
+        }
         None
     }
 
     fn on_scroll(&mut self, _: f64, y: f64) {
-        // TODO: Is there a threshold we have to implement?
-        let curr_slot = self
+        // Each line scrolled moves a slot, however many a single event carries
+        let lines = take_scrolled_lines(&mut self.scrolled, y);
+        if lines == 0 {
+            return;
+        }
+        let server = self
             .hud_context
             .clone()
             .read()
             .server
             .as_ref()
             .unwrap()
-            .clone()
-            .inventory_context
-            .clone()
-            .read()
-            .hotbar_index;
-        let new_slot = if y == -1.0 {
-            if curr_slot == 8 {
-                0
-            } else {
-                curr_slot + 1
-            }
-        } else if y == 1.0 {
-            if curr_slot == 0 {
-                8
-            } else {
-                curr_slot - 1
-            }
-        } else {
-            curr_slot
-        };
-        self.hud_context
-            .clone()
-            .read()
-            .server
-            .as_ref()
-            .unwrap()
-            .clone()
-            .write_packet(HeldItemChange {
-                slot: new_slot as i16,
-            });
-        self.hud_context
-            .clone()
-            .read()
-            .server
-            .as_ref()
-            .unwrap()
-            .clone()
-            .inventory_context
-            .clone()
-            .write()
-            .hotbar_index = new_slot;
-        self.hud_context.clone().write().slot_index = new_slot;
-        self.hud_context.clone().write().dirty_slot_index = true;
+            .clone();
+        let curr_slot = server.inventory_context.clone().read().hotbar_index;
+        server.select_hotbar_slot(scroll_hotbar_slot(curr_slot, lines));
     }
 
     fn on_resize(
@@ -434,8 +874,11 @@ impl Screen for Hud {
 }
 
 impl Hud {
+    /// The size of a HUD texture pixel in UI units. The HUD is as large as it is at the
+    /// automatic GUI scale, and smaller at smaller scales.
     pub fn icon_scale(renderer: &Renderer) -> f64 {
         Hud::icon_scale_by_height(renderer.safe_height)
+            / ui::auto_gui_scale(renderer.safe_width as f64, renderer.safe_height as f64)
     }
 
     pub fn icon_scale_by_height(height: u32) -> f64 {
@@ -453,7 +896,7 @@ impl Hud {
         let icon_scale = Hud::icon_scale(renderer);
         let x_offset = icon_scale * 182.0 / 2.0 * -1.0 + icon_scale * 9.0 / 2.0;
         let y_offset = icon_scale * 30.0;
-        let hp = hud_context.health.ceil();
+        let hp = health_points(hud_context.get_health());
         let max_health = hud_context.max_health;
         let absorbtion = hud_context.absorbtion;
         let last_health = hud_context.last_health;
@@ -483,7 +926,7 @@ impl Hud {
                 y -= icon_scale * 2.0;
             }
 
-            if hp <= 4.0 {
+            if hp <= 4 {
                 // Creates the jittery effect when player has less than 2.5 hearts
                 y += icon_scale * (self.random.gen_range(0..2) as f64);
                 redirty_health = true;
@@ -568,26 +1011,15 @@ impl Hud {
 
                 tmp_absorbtion -= 2.0;
             } else {
-                if heart * 2 + 1 < hp as isize {
-                    let image = ui::ImageBuilder::new()
-                        .texture_coords((
-                            (texture_offset + 36) as f64 / 256.0,
-                            (9.0 * hardcore_offset) as f64 / 256.0,
-                            9.0 / 256.0,
-                            9.0 / 256.0,
-                        ))
-                        .position(x, y)
-                        .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
-                        .size(icon_scale * 9.0, icon_scale * 9.0)
-                        .texture("minecraft:gui/icons")
-                        .create(ui_container);
-                    self.health_elements.push(image);
-                }
-
-                if heart * 2 + 1 == hp as isize {
+                let overlay = match bar_icon(hp, heart as u32) {
+                    BarIcon::Full => Some(36),
+                    BarIcon::Half => Some(45),
+                    BarIcon::Empty => None,
+                };
+                if let Some(overlay) = overlay {
                     let image = ui::ImageBuilder::new()
                         .texture_coords((
-                            (texture_offset + 45) as f64 / 256.0,
+                            (texture_offset + overlay) as f64 / 256.0,
                             (9.0 * hardcore_offset) as f64 / 256.0,
                             9.0 / 256.0,
                             9.0 / 256.0,
@@ -646,7 +1078,7 @@ impl Hud {
         let icon_scale = Hud::icon_scale(renderer);
         let hud_context = self.hud_context.clone();
         let hud_context = hud_context.read();
-        let food = hud_context.food;
+        let food = hud_context.get_food();
         let _last_food = hud_context.last_food;
         let x_offset = icon_scale * 182.0 / 2.0 + icon_scale * 9.0 / 2.0;
         let y_offset = icon_scale * 30.0;
@@ -677,38 +1109,25 @@ impl Hud {
                 .create(ui_container);
             self.food_elements.push(image);
 
-            match (i * 2 + 1).cmp(&food) {
-                Ordering::Less => {
-                    let image = ui::ImageBuilder::new()
-                        .texture_coords((
-                            (l7 + 36.0) / 256.0,
-                            27.0 / 256.0,
-                            9.0 / 256.0,
-                            9.0 / 256.0,
-                        ))
-                        .position(x, y_offset)
-                        .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
-                        .size(icon_scale * 9.0, icon_scale * 9.0)
-                        .texture("minecraft:gui/icons")
-                        .create(ui_container);
-                    self.food_elements.push(image);
-                }
-                Ordering::Equal => {
-                    let image = ui::ImageBuilder::new()
-                        .texture_coords((
-                            (l7 + 45.0) / 256.0,
-                            27.0 / 256.0,
-                            9.0 / 256.0,
-                            9.0 / 256.0,
-                        ))
-                        .position(x, y_offset)
-                        .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
-                        .size(icon_scale * 9.0, icon_scale * 9.0)
-                        .texture("minecraft:gui/icons")
-                        .create(ui_container);
-                    self.food_elements.push(image);
-                }
-                Ordering::Greater => debug!("Nothing happens here, but that's probably wrong"),
+            let overlay = match bar_icon(food as u32, i) {
+                BarIcon::Full => Some(36.0),
+                BarIcon::Half => Some(45.0),
+                BarIcon::Empty => None,
+            };
+            if let Some(overlay) = overlay {
+                let image = ui::ImageBuilder::new()
+                    .texture_coords((
+                        (l7 + overlay) / 256.0,
+                        27.0 / 256.0,
+                        9.0 / 256.0,
+                        9.0 / 256.0,
+                    ))
+                    .position(x, y_offset)
+                    .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
+                    .size(icon_scale * 9.0, icon_scale * 9.0)
+                    .texture("minecraft:gui/icons")
+                    .create(ui_container);
+                self.food_elements.push(image);
             }
         }
         self.hud_context.write().dirty_food = false;
@@ -719,12 +1138,13 @@ impl Hud {
         let y_offset = icon_scale * 24.0;
         let hud_context = self.hud_context.clone();
         let hud_context = hud_context.read();
-        let max_exp = if hud_context.exp_level >= 30 {
-            112 + (hud_context.exp_level - 30) * 9
-        } else if hud_context.exp_level >= 15 {
-            37 + (hud_context.exp_level - 15) * 5
+        let exp_level = hud_context.get_exp_level();
+        let max_exp = if exp_level >= 30 {
+            112 + (exp_level - 30) * 9
+        } else if exp_level >= 15 {
+            37 + (exp_level - 15) * 5
         } else {
-            7 + hud_context.exp_level * 2
+            7 + exp_level * 2
         };
         if max_exp > 0 {
             let image = ui::ImageBuilder::new()
@@ -736,7 +1156,7 @@ impl Hud {
                 .create(ui_container);
             self.exp_elements.push(image);
 
-            let scaled_length = hud_context.exp * 182.0;
+            let scaled_length = exp_bar_length(hud_context.get_exp());
             if scaled_length > 0.0 {
                 let shift = icon_scale * (((182.0) - scaled_length as f64) / 2.0);
                 let image = ui::ImageBuilder::new()
@@ -754,8 +1174,8 @@ impl Hud {
                 self.exp_elements.push(image);
             }
         }
-        if hud_context.exp_level > 0 {
-            let level_str = format!("{}", hud_context.exp_level);
+        if exp_level > 0 {
+            let level_str = format!("{}", exp_level);
             let scale = icon_scale / 2.0;
             let y = icon_scale * 26.0;
             self.exp_text_elements.push(
@@ -942,32 +1362,138 @@ impl Hud {
     }
 
     pub fn render_debug(&mut self, renderer: &mut Renderer, ui_container: &mut Container) {
-        let hud_context = self.hud_context.clone();
-        let hud_context = hud_context.read();
+        let (lines, frame_times) = {
+            let hud_context = self.hud_context.clone();
+            let hud_context = hud_context.read();
+            let info = hud_context.debug_info;
+            let (x, y, z) = info.position;
+            // Convert to the angles Minecraft displays, yaw 0 is south and pitch 0 level
+            let yaw = (-info.yaw.to_degrees()).rem_euclid(360.0);
+            let pitch = (-info.pitch.to_degrees()).rem_euclid(360.0) - 180.0;
+            let facing = match ((yaw + 45.0) / 90.0) as i32 % 4 {
+                0 => "south (Towards positive Z)",
+                1 => "west (Towards negative X)",
+                2 => "north (Towards negative Z)",
+                _ => "east (Towards positive X)",
+            };
+            let mut lines = vec![
+                format!("FPS: {}", hud_context.fps),
+                format!("XYZ: {:.3} / {:.5} / {:.3}", x, y, z),
+                format!(
+                    "Block: {} {} {}",
+                    x.floor() as i32,
+                    y.floor() as i32,
+                    z.floor() as i32
+                ),
+                format!(
+                    "Chunk: {} {} {}",
+                    x.floor() as i32 >> 4,
+                    y.floor() as i32 >> 4,
+                    z.floor() as i32 >> 4
+                ),
+                format!("Facing: {} ({:.1} / {:.1})", facing, yaw, pitch),
+                format!("Loaded chunks: {}", info.loaded_chunks),
+                format!(
+                    "Sections: {} drawn, {} culled",
+                    info.drawn_chunks, info.culled_chunks
+                ),
+            ];
+            if let Some(stats) = hud_context.frame_times.stats() {
+                lines.push(format!(
+                    "Frame time: {:.1} min / {:.1} avg / {:.1} max ms",
+                    stats.min, stats.avg, stats.max
+                ));
+            }
+            (lines, hud_context.frame_times.clone())
+        };
         let icon_scale = Hud::icon_scale(renderer);
         let scale = icon_scale / 2.0;
-        self.debug_elements.push(
+        let line_count = lines.len();
+        for (i, line) in lines.into_iter().enumerate() {
+            self.debug_elements.push(
+                ui::TextBuilder::new()
+                    .alignment(VAttach::Top, HAttach::Left)
+                    .scale_x(scale)
+                    .scale_y(scale)
+                    .position(icon_scale, icon_scale + 10.0 * scale * i as f64)
+                    .text(line)
+                    .colour((0, 102, 204, 255))
+                    .shadow(false)
+                    .create(ui_container),
+            );
+        }
+
+        // Frame time graph below the text, one bar per frame with the newest on the right
+        let graph_top = icon_scale + 10.0 * scale * (line_count as f64 + 0.5);
+        let graph_height = 40.0 * scale;
+        for (i, frame_time) in frame_times.samples().enumerate() {
+            let height = (frame_time / FRAME_GRAPH_MAX_MS).min(1.0) * graph_height;
+            let colour = if frame_time <= FRAME_GRAPH_MAX_MS / 2.0 {
+                (85, 255, 85, 200)
+            } else if frame_time <= FRAME_GRAPH_MAX_MS {
+                (255, 255, 85, 200)
+            } else {
+                (255, 85, 85, 200)
+            };
+            self.frame_graph_elements.push(
+                ui::ImageBuilder::new()
+                    .texture("leafish:solid")
+                    .alignment(VAttach::Top, HAttach::Left)
+                    .position(
+                        icon_scale + i as f64 * scale,
+                        graph_top + graph_height - height,
+                    )
+                    .size(scale, height.max(1.0))
+                    .colour(colour)
+                    .create(ui_container),
+            );
+        }
+        self.hud_context.write().dirty_debug = false;
+    }
+
+    pub fn render_coordinates(&mut self, renderer: &mut Renderer, ui_container: &mut Container) {
+        let (coordinates, debug) = {
+            let mut hud_context = self.hud_context.write();
+            hud_context.dirty_coordinates = false;
+            (hud_context.coordinates, hud_context.debug)
+        };
+        let coordinates = match coordinates {
+            Some(coordinates) if !debug => coordinates,
+            _ => return,
+        };
+        let icon_scale = Hud::icon_scale(renderer);
+        let scale = icon_scale / 2.0;
+        self.coordinate_elements.push(
             ui::TextBuilder::new()
                 .alignment(VAttach::Top, HAttach::Left)
                 .scale_x(scale)
                 .scale_y(scale)
                 .position(icon_scale, icon_scale)
-                .text(format!("FPS: {}", hud_context.fps))
-                .colour((0, 102, 204, 255))
-                .shadow(false)
+                .text(coordinates.text())
                 .create(ui_container),
         );
     }
 
     pub fn render_chat(&mut self, renderer: &mut Renderer, ui_container: &mut Container) {
         let hud_context = self.hud_context.clone();
-        let hud_context = hud_context.read();
+        let mut hud_context = hud_context.write();
+        hud_context.dirty_chat = false;
         let icon_scale = Hud::icon_scale(renderer);
         let scale = icon_scale / 2.0;
 
-        let history_size = hud_context.chat_history.len();
-
-        if history_size > 0 {
+        let options = hud_context.chat_options;
+        let shown: Vec<_> = hud_context
+            .chat_history
+            .iter()
+            .rev()
+            .take(SHOWN_CHAT_MESSAGES)
+            .take_while(|line| line.is_fresh(&options))
+            .collect();
+        self.chat_fades_at = options
+            .fade_after
+            .and_then(|fade_after| Some(shown.last()?.received + fade_after));
+
+        if !shown.is_empty() {
             self.chat_background_elements.push(
                 ui::ImageBuilder::new()
                     .texture("leafish:solid")
@@ -975,15 +1501,15 @@ impl Hud {
                     .position(0.0, scale * 85.0)
                     .size(
                         500.0 * scale,
-                        6.0 * scale + 10.0 * scale * (history_size as f64),
+                        6.0 * scale + 10.0 * scale * (shown.len() as f64),
                     )
                     .colour((0, 0, 0, 100))
                     .create(ui_container),
             );
         }
 
-        for i in 0..cmp::min(10, history_size) {
-            let message = hud_context.chat_history[history_size - 1 - i].clone();
+        for (i, line) in shown.iter().enumerate() {
+            let message = line.text(options.timestamps);
             let text = ui::FormattedBuilder::new()
                 .alignment(VAttach::Bottom, HAttach::Left)
                 .scale_x(scale)
@@ -997,6 +1523,208 @@ impl Hud {
         }
     }
 
+    /// Draws the latest game info message centered above the health and food bars.
+    pub fn render_action_bar(&mut self, renderer: &mut Renderer, ui_container: &mut Container) {
+        let hud_context = self.hud_context.clone();
+        let mut hud_context = hud_context.write();
+        hud_context.dirty_action_bar = false;
+        let line = match hud_context.get_action_bar() {
+            Some(line) => line,
+            None => {
+                self.action_bar_fades_at = None;
+                return;
+            }
+        };
+        self.action_bar_fades_at = Some(line.received + ACTION_BAR_TIME);
+        let icon_scale = Hud::icon_scale(renderer);
+        let scale = icon_scale / 2.0;
+        self.action_bar_elements.push(
+            ui::FormattedBuilder::new()
+                .alignment(VAttach::Bottom, HAttach::Center)
+                .scale_x(scale)
+                .scale_y(scale)
+                .position(0.0, icon_scale * 55.0)
+                .text(line.message.clone())
+                .create(ui_container),
+        );
+    }
+
+    pub fn render_player_list(&mut self, renderer: &mut Renderer, ui_container: &mut Container) {
+        let hud_context = self.hud_context.clone();
+        let mut hud_context = hud_context.write();
+        hud_context.dirty_player_list = false;
+        let server = match hud_context.server.clone() {
+            Some(server) if hud_context.player_list_shown => server,
+            _ => return,
+        };
+        drop(hud_context);
+        let player_list = server.player_list();
+        let player_list = player_list.read();
+        if player_list.is_empty() {
+            return;
+        }
+        let icon_scale = Hud::icon_scale(renderer);
+        let scale = icon_scale / 2.0;
+        let row_height = icon_scale * 9.0;
+        let players = player_list.sorted();
+        let columns = (players.len() + PLAYER_LIST_ROWS - 1) / PLAYER_LIST_ROWS;
+        let rows = (players.len() + columns - 1) / columns;
+
+        let names: Vec<_> = players
+            .iter()
+            .map(|info| {
+                let name = match &info.display_name {
+                    Some(display_name) => display_name.clone(),
+                    None => format::Component::Text(format::TextComponent::new(&info.name)),
+                };
+                if matches!(info.gamemode, GameMode::Spectator) {
+                    // Spectators are drawn in gray italics, like vanilla does
+                    format::Component::Text(format::TextComponent {
+                        text: "".to_owned(),
+                        modifier: format::Modifier {
+                            extra: Some(vec![name]),
+                            italic: Some(true),
+                            color: Some(format::Color::Gray),
+                            ..Default::default()
+                        },
+                    })
+                } else {
+                    name
+                }
+            })
+            .collect();
+        let name_width = names
+            .iter()
+            .map(|name| ui::Formatted::compute_size(renderer, name, -1.0).0 * scale)
+            .fold(0.0, f64::max);
+        // Room for the ping bars after the name
+        let column_width = name_width + icon_scale * 13.0;
+        let text_size = |text: Option<&format::Component>| {
+            text.map_or((0.0, 0.0), |text| {
+                let (width, height) = ui::Formatted::compute_size(renderer, text, -1.0);
+                (width * scale, height * scale)
+            })
+        };
+        let header_size = text_size(player_list.header());
+        let footer_size = text_size(player_list.footer());
+        let padding = icon_scale;
+        let width = (column_width * columns as f64 + icon_scale * (columns - 1) as f64)
+            .max(header_size.0)
+            .max(footer_size.0)
+            + padding * 2.0;
+        let height = header_size.1 + row_height * rows as f64 + footer_size.1 + padding * 2.0;
+
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .alignment(VAttach::Top, HAttach::Center)
+            .position(0.0, icon_scale * 10.0)
+            .size(width, height)
+            .colour((0, 0, 0, 100))
+            .create(ui_container);
+        {
+            let mut background = background.borrow_mut();
+            for (text, y) in [
+                (player_list.header(), padding),
+                (player_list.footer(), height - padding - footer_size.1),
+            ]
+            .iter()
+            {
+                if let Some(text) = text {
+                    ui::FormattedBuilder::new()
+                        .alignment(VAttach::Top, HAttach::Center)
+                        .scale_x(scale)
+                        .scale_y(scale)
+                        .position(0.0, *y)
+                        .text((*text).clone())
+                        .attach(&mut *background);
+                }
+            }
+            let columns_width = column_width * columns as f64 + icon_scale * (columns - 1) as f64;
+            let left = (width - columns_width) / 2.0;
+            for (i, (info, name)) in players.iter().zip(names).enumerate() {
+                let x = left + (i / rows) as f64 * (column_width + icon_scale);
+                let y = padding + header_size.1 + (i % rows) as f64 * row_height;
+                ui::ImageBuilder::new()
+                    .texture("leafish:solid")
+                    .alignment(VAttach::Top, HAttach::Left)
+                    .position(x, y)
+                    .size(column_width, row_height - icon_scale)
+                    .colour((255, 255, 255, 32))
+                    .attach(&mut *background);
+                ui::FormattedBuilder::new()
+                    .alignment(VAttach::Top, HAttach::Left)
+                    .scale_x(scale)
+                    .scale_y(scale)
+                    .position(x + icon_scale, y)
+                    .text(name)
+                    .attach(&mut *background);
+                let ping_row = ping_icon(info.ping) as f64;
+                ui::ImageBuilder::new()
+                    .texture_coords((
+                        0.0,
+                        (176.0 + ping_row * 8.0) / 256.0,
+                        10.0 / 256.0,
+                        8.0 / 256.0,
+                    ))
+                    .alignment(VAttach::Top, HAttach::Left)
+                    .position(x + column_width - icon_scale * 11.0, y)
+                    .size(icon_scale * 10.0, icon_scale * 8.0)
+                    .texture("minecraft:gui/icons")
+                    .attach(&mut *background);
+            }
+        }
+        self.player_list_elements.push(background);
+    }
+
+    /// Draws the boss bars stacked at the top of the screen, each below its title. Like
+    /// vanilla, bars that would reach past the top third of the screen are left out.
+    pub fn render_boss_bars(&mut self, renderer: &mut Renderer, ui_container: &mut Container) {
+        let hud_context = self.hud_context.clone();
+        let mut hud_context = hud_context.write();
+        hud_context.dirty_boss_bars = false;
+        let icon_scale = Hud::icon_scale(renderer);
+        let scale = icon_scale / 2.0;
+        for (i, (_, bar)) in hud_context.boss_bars.iter().enumerate() {
+            let y = icon_scale * (3.0 + 19.0 * i as f64);
+            if y + icon_scale * 14.0 > renderer.safe_height as f64 / ui_container.scale / 3.0 {
+                break;
+            }
+            self.boss_bar_text_elements.push(
+                ui::FormattedBuilder::new()
+                    .alignment(VAttach::Top, HAttach::Center)
+                    .scale_x(scale)
+                    .scale_y(scale)
+                    .position(0.0, y)
+                    .text(bar.title.clone())
+                    .create(ui_container),
+            );
+
+            let bar_y = y + icon_scale * 9.0;
+            let color_row = bar.color as usize as f64 * 10.0;
+            let notch_row = match bar.division {
+                BossBarDivision::None => None,
+                division => Some(80.0 + (division as usize - 1) as f64 * 10.0),
+            };
+            // The empty bar with its notches, then the filled part over it
+            let mut images = vec![(color_row, 182.0)];
+            images.extend(notch_row.map(|row| (row, 182.0)));
+            let filled = exp_bar_length(bar.shown_health) as f64;
+            if filled > 0.0 {
+                images.push((color_row + 5.0, filled));
+                images.extend(notch_row.map(|row| (row + 5.0, filled)));
+            }
+            for (row, length) in images {
+                self.boss_bar_elements.push(boss_bar_image(
+                    ui_container,
+                    icon_scale,
+                    row,
+                    length,
+                    bar_y,
+                ));
+            }
+        }
+    }
+
     pub fn draw_item(
         &self,
         item: &Item,
@@ -1030,3 +1758,257 @@ impl Hud {
         image
     }
 }
+
+/// The hotbar slot selected after scrolling `lines` from `slot`, one slot per line and
+/// wrapping around at either end. Scrolling down selects the slots to the right.
+pub fn scroll_hotbar_slot(slot: u8, lines: i32) -> u8 {
+    (slot as i32 - lines).rem_euclid(9) as u8
+}
+
+/// The packet selecting the hotbar `slot` when `selected` is, none if it's the same.
+pub fn held_item_change(selected: u8, slot: u8) -> Option<HeldItemChange> {
+    if selected == slot {
+        None
+    } else {
+        Some(HeldItemChange { slot: slot as i16 })
+    }
+}
+
+/// Adds `y` to what was `scrolled` before and takes the whole lines out of it.
+fn take_scrolled_lines(scrolled: &mut f64, y: f64) -> i32 {
+    *scrolled += y;
+    let lines = scrolled.trunc();
+    *scrolled -= lines;
+    lines as i32
+}
+
+/// How a heart or hunger shank is filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarIcon {
+    Full,
+    Half,
+    Empty,
+}
+
+/// The first `length` of the 182 pixels of row `row` of `gui/bars.png`, drawn
+/// starting at the left edge of a boss bar centered at the top of the screen.
+fn boss_bar_image(
+    ui_container: &mut Container,
+    icon_scale: f64,
+    row: f64,
+    length: f64,
+    y: f64,
+) -> ImageRef {
+    let shift = icon_scale * ((182.0 - length) / 2.0);
+    ui::ImageBuilder::new()
+        .texture_coords((0.0, row / 256.0, length / 256.0, 5.0 / 256.0))
+        .position(-shift, y)
+        .alignment(VAttach::Top, HAttach::Center)
+        .size(icon_scale * length, icon_scale * 5.0)
+        .texture("minecraft:gui/bars")
+        .create(ui_container)
+}
+
+/// The `index`th icon of a bar showing `points`, each icon holding two points.
+pub fn bar_icon(points: u32, index: u32) -> BarIcon {
+    match (index * 2 + 1).cmp(&points) {
+        Ordering::Less => BarIcon::Full,
+        Ordering::Equal => BarIcon::Half,
+        Ordering::Greater => BarIcon::Empty,
+    }
+}
+
+/// The points of health shown in the hearts, where any fraction of a point counts.
+pub fn health_points(health: f32) -> u32 {
+    health.max(0.0).ceil() as u32
+}
+
+/// How many of the experience bar's 182 pixels are filled at `progress`.
+pub fn exp_bar_length(progress: f32) -> f32 {
+    progress.max(0.0).min(1.0) * 182.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Actionkey;
+    use std::str::FromStr;
+
+    fn count_icons(points: u32) -> (usize, usize) {
+        let icons: Vec<_> = (0..10).map(|i| bar_icon(points, i)).collect();
+        (
+            icons.iter().filter(|&&icon| icon == BarIcon::Full).count(),
+            icons.iter().filter(|&&icon| icon == BarIcon::Half).count(),
+        )
+    }
+
+    #[test]
+    fn health_food_and_exp_bars() {
+        let mut hud_context = HudContext::new();
+        hud_context.update_health_and_food(6.5, 13, 5);
+        hud_context.update_exp(0.25, 3, 20);
+
+        // 6.5 health shows as 3 and a half hearts
+        assert_eq!(health_points(hud_context.get_health()), 7);
+        assert_eq!(count_icons(health_points(hud_context.get_health())), (3, 1));
+        assert_eq!(count_icons(hud_context.get_food() as u32), (6, 1));
+        assert_eq!(count_icons(20), (10, 0));
+        assert_eq!(count_icons(health_points(-2.0)), (0, 0));
+
+        assert_eq!(exp_bar_length(hud_context.get_exp()), 45.5);
+        assert_eq!(exp_bar_length(1.5), 182.0);
+        assert_eq!(hud_context.get_exp_level(), 3);
+        assert_eq!(hud_context.get_total_exp(), 20);
+    }
+
+    #[test]
+    fn chat_history_is_trimmed_to_limit() {
+        let mut hud_context = HudContext::new();
+        let options = ChatOptions {
+            max_messages: 3,
+            ..ChatOptions::default()
+        };
+        hud_context.set_chat_options(options);
+        for i in 0..5 {
+            hud_context.display_message_in_chat(format::Component::Text(
+                format::TextComponent::new(&format!("message {}", i)),
+            ));
+        }
+        let kept: Vec<_> = hud_context
+            .get_chat_history()
+            .iter()
+            .map(|line| line.message.to_string())
+            .collect();
+        assert_eq!(kept, ["message 2", "message 3", "message 4"]);
+        assert_eq!(hud_context.get_received_messages(), 5);
+
+        // Lowering the limit drops the oldest ones right away
+        hud_context.set_chat_options(ChatOptions {
+            max_messages: 1,
+            ..options
+        });
+        assert_eq!(hud_context.get_chat_history().len(), 1);
+        assert_eq!(
+            hud_context.get_chat_history()[0].message.to_string(),
+            "message 4"
+        );
+
+        let line = &hud_context.get_chat_history()[0];
+        assert!(line.text(true).to_string().ends_with("] message 4"));
+        assert!(line.is_fresh(&options));
+    }
+
+    #[test]
+    fn game_info_goes_to_action_bar() {
+        let mut hud_context = HudContext::new();
+        let message = |text| format::Component::Text(format::TextComponent::new(text));
+        hud_context.display_server_message(message("Chat"), ChatPosition::Chat);
+        hud_context.display_server_message(message("System"), ChatPosition::System);
+        hud_context.display_server_message(message("Sleeping 1/2"), ChatPosition::ActionBar);
+
+        let chat: Vec<_> = hud_context
+            .get_chat_history()
+            .iter()
+            .map(|line| line.message.to_string())
+            .collect();
+        assert_eq!(chat, ["Chat", "System"]);
+        assert_eq!(
+            hud_context
+                .get_action_bar()
+                .map(|line| line.message.to_string()),
+            Some("Sleeping 1/2".to_owned())
+        );
+    }
+
+    #[test]
+    fn boss_bar_updates() {
+        let mut hud_context = HudContext::new();
+        let uuid = UUID::from_str("c5d0f3a0-9bcb-4d38-b0a5-13a4f8f3c0a1").unwrap();
+        let title = format::Component::Text(format::TextComponent::new("Ender Dragon"));
+        hud_context.update_boss_bar(
+            uuid.clone(),
+            BossBarUpdate::Add(BossBar::new(
+                title.clone(),
+                1.0,
+                BossBarColor::from_int(5),
+                BossBarDivision::from_int(0),
+                0,
+            )),
+        );
+        hud_context.update_boss_bar(uuid.clone(), BossBarUpdate::Health(0.25));
+
+        let bars = hud_context.get_boss_bars();
+        assert_eq!(bars.len(), 1);
+        let bar = &bars[0].1;
+        assert_eq!(bar.title, title);
+        assert_eq!(bar.health, 0.25);
+        assert_eq!(bar.color, BossBarColor::Purple);
+        assert_eq!(bar.division, BossBarDivision::None);
+        // Slides down to the new health rather than jumping there
+        assert_eq!(bar.shown_health, 1.0);
+        hud_context.animate_boss_bars(1.0);
+        let shown = hud_context.get_boss_bars()[0].1.shown_health;
+        assert!(shown < 1.0 && shown > 0.25);
+        for _ in 0..100 {
+            hud_context.animate_boss_bars(1.0);
+        }
+        assert_eq!(hud_context.get_boss_bars()[0].1.shown_health, 0.25);
+
+        // Bars stack in the order they were added, updates to unknown ones are ignored
+        let other = UUID::from_str("0a6b3f42-1d2e-4c5f-8a9b-0c1d2e3f4a5b").unwrap();
+        hud_context.update_boss_bar(other.clone(), BossBarUpdate::Health(0.5));
+        assert_eq!(hud_context.get_boss_bars().len(), 1);
+        hud_context.update_boss_bar(
+            other.clone(),
+            BossBarUpdate::Add(BossBar::new(
+                title,
+                2.0,
+                BossBarColor::Red,
+                BossBarDivision::Notches10,
+                0,
+            )),
+        );
+        assert_eq!(hud_context.get_boss_bars()[1].0, other);
+        assert_eq!(hud_context.get_boss_bars()[1].1.health, 1.0);
+
+        hud_context.update_boss_bar(uuid, BossBarUpdate::Remove);
+        assert_eq!(hud_context.get_boss_bars().len(), 1);
+        assert_eq!(hud_context.get_boss_bars()[0].0, other);
+    }
+
+    #[test]
+    fn scrolling_adds_up_to_lines() {
+        let mut scrolled = 0.0;
+        // Smooth scrolling wheels send parts of a line at a time
+        assert_eq!(take_scrolled_lines(&mut scrolled, -0.5), 0);
+        assert_eq!(take_scrolled_lines(&mut scrolled, -0.75), -1);
+        assert_eq!(take_scrolled_lines(&mut scrolled, 0.25), 0);
+        assert_eq!(scrolled, 0.0);
+        assert_eq!(take_scrolled_lines(&mut scrolled, 3.0), 3);
+    }
+
+    #[test]
+    fn hotbar_selection_wraps() {
+        assert_eq!(scroll_hotbar_slot(0, -1), 1);
+        assert_eq!(scroll_hotbar_slot(8, -1), 0);
+        assert_eq!(scroll_hotbar_slot(0, 1), 8);
+        assert_eq!(scroll_hotbar_slot(4, 0), 4);
+        assert_eq!(scroll_hotbar_slot(7, -3), 1);
+        assert_eq!(scroll_hotbar_slot(1, 12), 7);
+
+        // Scrolling past the first slot tells the server the last one is held
+        let packet = held_item_change(0, scroll_hotbar_slot(0, 1)).unwrap();
+        assert_eq!(packet.slot, 8);
+        let packet = held_item_change(8, scroll_hotbar_slot(8, -1)).unwrap();
+        assert_eq!(packet.slot, 0);
+        assert!(held_item_change(4, scroll_hotbar_slot(4, 0)).is_none());
+
+        let slots: Vec<_> = Actionkey::values()
+            .iter()
+            .filter_map(|key| key.hotbar_slot())
+            .collect();
+        assert_eq!(slots, (0..9).collect::<Vec<u8>>());
+        assert_eq!(Actionkey::Hotbar9.hotbar_slot(), Some(8));
+        assert_eq!(Actionkey::Jump.hotbar_slot(), None);
+    }
+}
@@ -1,8 +1,9 @@
-use crate::inventory::{Inventory, InventoryContext, Item};
+use crate::inventory::{ClickAction, Inventory, InventoryContext, Item, SLOT_OUTSIDE};
 use crate::render::hud::Hud;
 use crate::render::Renderer;
 use crate::screen::Screen;
 use crate::ui;
+use crate::ui::tooltip::{self, Tooltip};
 use crate::ui::{Container, ImageRef, TextRef};
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -12,6 +13,17 @@ pub struct InventoryWindow {
     pub text_elements: Vec<Vec<TextRef>>,
     pub inventory: Arc<RwLock<dyn Inventory + Sync + Send>>,
     inventory_context: Arc<RwLock<InventoryContext>>,
+    /// The mouse position, relative to the center of the screen in UI units.
+    cursor_position: (f64, f64),
+    /// The elements drawing the stack carried by the cursor.
+    cursor_elements: Vec<ImageRef>,
+    cursor_text_elements: Vec<TextRef>,
+    /// The id and count of the carried stack when it was last drawn.
+    last_cursor: Option<(isize, isize)>,
+    cursor_dirty: bool,
+    /// The slot whose item is described by `tooltip`.
+    hovered_slot: Option<i16>,
+    tooltip: Option<Tooltip>,
 }
 
 impl Screen for InventoryWindow {
@@ -28,9 +40,31 @@ impl Screen for InventoryWindow {
     }
 
     fn on_deactive(&mut self, _renderer: &mut Renderer, _ui_container: &mut Container) {
-        self.inventory_context.clone().write().inventory = None;
+        let id = self.inventory.read().id() as u8;
+        let server = self
+            .inventory_context
+            .read()
+            .hud_context
+            .read()
+            .server
+            .clone();
+        {
+            let inventory_context = self.inventory_context.clone();
+            let mut inventory_context = inventory_context.write();
+            inventory_context.inventory = None;
+            // The server drops whatever was carried when the window closes
+            inventory_context.cursor = None;
+        }
+        if let Some(server) = server {
+            server.close_window(id);
+        }
         self.inventory.clone().write().close(self);
         self.clear_elements();
+        self.cursor_elements.clear();
+        self.cursor_text_elements.clear();
+        self.last_cursor = None;
+        self.hovered_slot = None;
+        self.tooltip = None;
     }
 
     fn tick(
@@ -39,10 +73,19 @@ impl Screen for InventoryWindow {
         renderer: &mut Renderer,
         ui_container: &mut Container,
     ) -> Option<Box<dyn Screen>> {
+        let replacement = self.inventory.clone().write().take_replacement();
+        if let Some(inventory) = replacement {
+            return Some(Box::new(InventoryWindow::new(
+                inventory,
+                self.inventory_context.clone(),
+            )));
+        }
         self.inventory
             .clone()
             .write()
             .tick(renderer, ui_container, self);
+        self.draw_cursor(ui_container, renderer);
+        self.draw_tooltip(ui_container, renderer);
         None
     }
 
@@ -59,6 +102,53 @@ impl Screen for InventoryWindow {
             .resize(width, height, renderer, ui_container, self);
     }
 
+    fn on_scroll(&mut self, _x: f64, y: f64) {
+        self.inventory.clone().write().on_scroll(y);
+    }
+
+    fn on_click(&mut self, right: bool, shift: bool, x: f64, y: f64) -> bool {
+        let action = ClickAction::new(right, shift);
+        let inventory_context = self.inventory_context.clone();
+        let mut inventory_context = inventory_context.write();
+        let inventory = self.inventory.clone();
+        let mut inventory = inventory.write();
+        let slot = match inventory.slot_at(x, y) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        if inventory.handle_click(slot, action) {
+            return true;
+        }
+        let clicked_item = if slot == SLOT_OUTSIDE {
+            None
+        } else {
+            inventory
+                .get_item(slot)
+                .as_ref()
+                .map(|item| item.stack.clone())
+        };
+        let action_number = inventory_context.click(&mut *inventory, slot, action);
+        let server = inventory_context.hud_context.read().server.clone();
+        if let Some(server) = server {
+            server.click_window(
+                inventory.id() as u8,
+                slot,
+                action,
+                action_number,
+                clicked_item,
+            );
+        }
+        self.cursor_dirty = true;
+        // The clicked slot's item changed, so its tooltip has to be rebuilt
+        self.hovered_slot = None;
+        true
+    }
+
+    fn on_cursor_moved(&mut self, x: f64, y: f64) {
+        self.cursor_position = (x, y);
+        self.cursor_dirty = true;
+    }
+
     fn is_closable(&self) -> bool {
         true
     }
@@ -74,6 +164,13 @@ impl InventoryWindow {
             text_elements: vec![],
             inventory,
             inventory_context,
+            cursor_position: (0.0, 0.0),
+            cursor_elements: vec![],
+            cursor_text_elements: vec![],
+            last_cursor: None,
+            cursor_dirty: false,
+            hovered_slot: None,
+            tooltip: None,
         }
     }
 }
@@ -88,6 +185,80 @@ impl InventoryWindow {
         ui_container: &mut Container,
         renderer: &Renderer,
     ) {
+        let (image, count) = Self::item_elements(item, x, y, 0, ui_container, renderer);
+        self.elements.get_mut(elements_idx).unwrap().push(image);
+        if let Some(count) = count {
+            self.text_elements
+                .get_mut(elements_idx)
+                .unwrap()
+                .push(count);
+        }
+    }
+
+    /// Redraws the stack carried by the cursor when it changed or moved.
+    fn draw_cursor(&mut self, ui_container: &mut Container, renderer: &Renderer) {
+        let inventory_context = self.inventory_context.clone();
+        let inventory_context = inventory_context.read();
+        let cursor = inventory_context
+            .cursor
+            .as_ref()
+            .map(|item| (item.stack.id, item.stack.count));
+        if !self.cursor_dirty && cursor == self.last_cursor {
+            return;
+        }
+        self.cursor_dirty = false;
+        self.last_cursor = cursor;
+        self.cursor_elements.clear();
+        self.cursor_text_elements.clear();
+        if let Some(item) = inventory_context.cursor.as_ref() {
+            let (x, y) = self.cursor_position;
+            // Drawn above the slots, which are redrawn whenever they change
+            let (image, count) = Self::item_elements(item, x, y, 1, ui_container, renderer);
+            self.cursor_elements.push(image);
+            self.cursor_text_elements.extend(count);
+        }
+    }
+
+    /// Describes the item under the cursor, unless a stack is being carried.
+    fn draw_tooltip(&mut self, ui_container: &mut Container, renderer: &Renderer) {
+        let (x, y) = self.cursor_position;
+        let carrying = self.inventory_context.read().cursor.is_some();
+        let inventory = self.inventory.clone();
+        let inventory = inventory.read();
+        let hovered = inventory
+            .slot_at(x, y)
+            .filter(|&slot| !carrying && slot >= 0 && inventory.get_item(slot).is_some());
+        if hovered != self.hovered_slot || hovered.is_none() {
+            self.hovered_slot = hovered;
+            self.tooltip = hovered.map(|slot| {
+                let item = inventory.get_item(slot).as_ref().unwrap();
+                let lines = tooltip::item_lines(&item.stack, &item.material.display_name());
+                let scale = Hud::icon_scale(renderer) / 2.0;
+                Tooltip::new(lines, scale, renderer, ui_container)
+            });
+        }
+        if let Some(tooltip) = self.tooltip.as_ref() {
+            let screen = match ui_container.mode {
+                ui::Mode::Unscaled(scale) => (854.0 / scale, 480.0 / scale),
+                ui::Mode::Scaled => (
+                    renderer.width as f64 / ui_container.scale,
+                    renderer.height as f64 / ui_container.scale,
+                ),
+            };
+            tooltip.move_to(x, y, screen);
+        }
+    }
+
+    /// Creates the icon for `item` centered on `(x, y)`, with its count if there's more
+    /// than one.
+    fn item_elements(
+        item: &Item,
+        x: f64,
+        y: f64,
+        draw_index: isize,
+        ui_container: &mut Container,
+        renderer: &Renderer,
+    ) -> (ImageRef, Option<TextRef>) {
         let icon_scale = Hud::icon_scale(renderer);
         let textures = item.material.texture_locations();
         let texture = if Renderer::get_texture_optional(&renderer.textures, &*textures.0).is_some()
@@ -102,8 +273,24 @@ impl InventoryWindow {
             .alignment(ui::VAttach::Middle, ui::HAttach::Center)
             .size(icon_scale * 16.0, icon_scale * 16.0)
             .texture(format!("minecraft:{}", texture))
+            .draw_index(draw_index)
             .create(ui_container);
-        self.elements.get_mut(elements_idx).unwrap().push(image);
+        if item.stack.count <= 1 {
+            return (image, None);
+        }
+        // Right aligned in the bottom corner of the slot, like vanilla
+        let text = item.stack.count.to_string();
+        let scale = icon_scale / 2.0;
+        let width = renderer.ui.size_of_string(&text) * scale;
+        let count = ui::TextBuilder::new()
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .scale_x(scale)
+            .scale_y(scale)
+            .position(x + icon_scale * 9.0 - width / 2.0, y + icon_scale * 5.0)
+            .text(text)
+            .draw_index(draw_index)
+            .create(ui_container);
+        (image, Some(count))
     }
 
     pub fn clear_elements(&mut self) {
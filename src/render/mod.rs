@@ -17,9 +17,11 @@ pub mod glsl;
 #[macro_use]
 pub mod shaders;
 pub mod clouds;
+pub mod frame_times;
 pub mod hud;
 pub mod inventory;
 pub mod model;
+pub mod sky;
 pub mod ui;
 
 use crate::gl;
@@ -45,10 +47,92 @@ use std::thread;
 
 const ATLAS_SIZE: usize = 2048;
 
+/// The projection for a window of the given size, `fov` being the vertical field of view in
+/// degrees. Windows taller than wide keep the vertical field of view of a square one.
+pub fn perspective(fov: f32, width: u32, height: u32) -> cgmath::PerspectiveFov<f32> {
+    cgmath::PerspectiveFov {
+        fovy: cgmath::Rad::from(cgmath::Deg(fov)),
+        aspect: (width as f32 / height as f32).max(1.0),
+        near: 0.1f32,
+        far: 500.0f32,
+    }
+}
+
+/// The bounding box of a chunk section in the renderer's coordinates, which flip the y axis.
+pub fn section_bounds(pos: (i32, i32, i32)) -> collision::Aabb3<f32> {
+    let min = cgmath::Point3::new(
+        pos.0 as f32 * 16.0,
+        -pos.1 as f32 * 16.0,
+        pos.2 as f32 * 16.0,
+    );
+    collision::Aabb3::new(min, min + cgmath::Vector3::new(16.0, -16.0, 16.0))
+}
+
+/// Whether any part of the chunk section at `pos` lies within `frustum`.
+pub fn is_section_visible(frustum: &collision::Frustum<f32>, pos: (i32, i32, i32)) -> bool {
+    frustum.contains(&section_bounds(pos)) != collision::Relation::Out
+}
+
+/// Where the world is rendered from, which is behind or in front of the player in the
+/// third person perspectives.
 pub struct Camera {
     pub pos: cgmath::Point3<f64>,
     pub yaw: f64,
     pub pitch: f64,
+    pub perspective: Perspective,
+    /// The player's eyes and the direction they look in, which blocks are targeted from.
+    pub eye: cgmath::Point3<f64>,
+    pub look: cgmath::Vector3<f64>,
+}
+
+/// How far the third person camera is from the player's eyes, unless a block is in the way.
+pub const THIRD_PERSON_DISTANCE: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Perspective {
+    FirstPerson,
+    /// Looking over the player's back.
+    ThirdPersonBack,
+    /// Looking at the player's face.
+    ThirdPersonFront,
+}
+
+impl Perspective {
+    pub fn next(self) -> Perspective {
+        match self {
+            Perspective::FirstPerson => Perspective::ThirdPersonBack,
+            Perspective::ThirdPersonBack => Perspective::ThirdPersonFront,
+            Perspective::ThirdPersonFront => Perspective::FirstPerson,
+        }
+    }
+
+    /// The direction from the player's eyes towards the camera, `None` in first person.
+    pub fn camera_direction(self, yaw: f64, pitch: f64) -> Option<cgmath::Vector3<f64>> {
+        match self {
+            Perspective::FirstPerson => None,
+            Perspective::ThirdPersonBack => Some(-look_direction(yaw, pitch)),
+            Perspective::ThirdPersonFront => Some(look_direction(yaw, pitch)),
+        }
+    }
+
+    /// The yaw and pitch of the camera for a player looking along `yaw` and `pitch`. The
+    /// front view turns around to face the player.
+    pub fn camera_rotation(self, yaw: f64, pitch: f64) -> (f64, f64) {
+        match self {
+            Perspective::ThirdPersonFront => (yaw + std::f64::consts::PI, -pitch),
+            _ => (yaw, pitch),
+        }
+    }
+}
+
+/// The unit vector pointing where something with the given yaw and pitch looks.
+pub fn look_direction(yaw: f64, pitch: f64) -> cgmath::Vector3<f64> {
+    use std::f64::consts::PI;
+    cgmath::Vector3::new(
+        (yaw - PI / 2.0).cos() * -pitch.cos(),
+        -pitch.sin(),
+        -(yaw - PI / 2.0).sin() * -pitch.cos(),
+    )
 }
 
 pub struct Renderer {
@@ -71,10 +155,19 @@ pub struct Renderer {
     element_buffer_type: gl::Type,
 
     pub camera: Camera,
+    /// Vertical field of view in degrees, applied by the next `update_camera`.
+    pub fov: f32,
+    /// The field of view `perspective_matrix` was built with.
+    projection_fov: f32,
     perspective_matrix: cgmath::Matrix4<f32>,
     camera_matrix: cgmath::Matrix4<f32>,
     pub frustum: collision::Frustum<f32>,
     pub view_vector: cgmath::Vector3<f32>,
+    /// Skip drawing chunk sections outside of `frustum`, see `settings::R_FRUSTUM_CULLING`.
+    pub frustum_culling: bool,
+    /// Chunk sections drawn and skipped by frustum culling in the last frame.
+    pub chunks_drawn: usize,
+    pub chunks_culled: usize,
 
     pub frame_id: u32,
 
@@ -88,6 +181,13 @@ pub struct Renderer {
     // Light renderering
     pub light_level: f32,
     pub sky_offset: f32,
+    /// The time of day in ticks, which moves the clouds.
+    pub world_time: f64,
+    /// How heavily it rains, which darkens the sky.
+    pub rain_level: f32,
+    pub cloud_mode: clouds::CloudMode,
+    /// Passed to the chunk shaders as the `gamma` uniform, see `settings::gamma`.
+    pub gamma: f32,
     skin_request: Sender<String>,
     skin_reply: Receiver<(String, Option<image::DynamicImage>)>,
 }
@@ -102,6 +202,12 @@ impl ChunkBuffer {
     pub fn new() -> ChunkBuffer {
         Default::default()
     }
+
+    /// The size of the uploaded vertex data, in bytes.
+    pub fn memory_size(&self) -> usize {
+        self.solid.as_ref().map_or(0, |info| info.buffer_size)
+            + self.trans.as_ref().map_or(0, |info| info.buffer_size)
+    }
 }
 
 struct ChunkRenderInfo {
@@ -129,6 +235,7 @@ init_shader! {
             required texture => "textures",
             required light_level => "lightLevel",
             required sky_offset => "skyOffset",
+            required gamma => "gamma",
         },
     }
 }
@@ -151,6 +258,7 @@ init_shader! {
             required texture => "textures",
             required light_level => "lightLevel",
             required sky_offset => "skyOffset",
+            required gamma => "gamma",
         },
     }
 }
@@ -200,7 +308,6 @@ impl Renderer {
         gl::depth_func(gl::LESS_OR_EQUAL);
 
         let clouds = Some(clouds::Clouds::new(&greg, textures.clone()));
-        // TODO: setting to disable clouds on native, too, if desired
 
         Renderer {
             resource_version: version,
@@ -229,10 +336,18 @@ impl Renderer {
                 pos: cgmath::Point3::new(0.0, 0.0, 0.0),
                 yaw: 0.0,
                 pitch: ::std::f64::consts::PI,
+                perspective: Perspective::FirstPerson,
+                eye: cgmath::Point3::new(0.0, 0.0, 0.0),
+                look: cgmath::Vector3::zero(),
             },
+            fov: 90.0,
+            projection_fov: 90.0,
             perspective_matrix: cgmath::Matrix4::identity(),
             camera_matrix: cgmath::Matrix4::identity(),
             frustum: collision::Frustum::from_matrix4(cgmath::Matrix4::identity()).unwrap(),
+            frustum_culling: true,
+            chunks_drawn: 0,
+            chunks_culled: 0,
             view_vector: cgmath::Vector3::zero(),
 
             frame_id: 1,
@@ -241,6 +356,10 @@ impl Renderer {
 
             light_level: 0.8,
             sky_offset: 1.0,
+            world_time: 0.0,
+            rain_level: 0.0,
+            cloud_mode: clouds::CloudMode::Fancy,
+            gamma: 0.0,
             skin_request: skin_req,
             skin_reply,
         }
@@ -252,7 +371,6 @@ impl Renderer {
 
     // TODO: Improve perf!
     pub fn update_camera(&mut self, width: u32, height: u32) {
-        use std::f64::consts::PI as PI64;
         // Not a sane place to put this but it works
         {
             let rm = self.resources.read();
@@ -266,31 +384,25 @@ impl Renderer {
             }
         }
 
-        if self.height != height || self.width != width {
+        let resized = self.height != height || self.width != width;
+        if resized {
             self.width = width;
             self.height = height;
             self.safe_width = width;
             self.safe_height = height;
             gl::viewport(0, 0, width as i32, height as i32);
-
-            let fovy = cgmath::Rad::from(cgmath::Deg(90.0_f32));
-            let aspect = (width as f32 / height as f32).max(1.0);
-
-            self.perspective_matrix = cgmath::Matrix4::from(cgmath::PerspectiveFov {
-                fovy,
-                aspect,
-                near: 0.1f32,
-                far: 500.0f32,
-            });
-
+        }
+        if resized || self.fov != self.projection_fov {
+            self.projection_fov = self.fov;
+            self.perspective_matrix = cgmath::Matrix4::from(perspective(self.fov, width, height));
+        }
+        if resized {
             self.init_trans(width, height);
         }
 
-        self.view_vector = cgmath::Vector3::new(
-            ((self.camera.yaw - PI64 / 2.0).cos() * -self.camera.pitch.cos()) as f32,
-            (-self.camera.pitch.sin()) as f32,
-            (-(self.camera.yaw - PI64 / 2.0).sin() * -self.camera.pitch.cos()) as f32,
-        );
+        self.view_vector = look_direction(self.camera.yaw, self.camera.pitch)
+            .cast()
+            .unwrap();
         let camera = cgmath::Point3::new(
             -self.camera.pos.x as f32,
             -self.camera.pos.y as f32,
@@ -333,13 +445,8 @@ impl Renderer {
 
             gl::enable(gl::MULTISAMPLE);
 
-            let time_offset = self.sky_offset * 0.9;
-            gl::clear_color(
-                (122.0 / 255.0) * time_offset,
-                (165.0 / 255.0) * time_offset,
-                (247.0 / 255.0) * time_offset,
-                1.0,
-            );
+            let [red, green, blue] = sky::sky_color(self.world_time, self.rain_level);
+            gl::clear_color(red, green, blue, 1.0);
             gl::clear(gl::ClearFlags::Color | gl::ClearFlags::Depth);
             // Chunk rendering
             self.chunk_shader.program.use_program();
@@ -353,10 +460,19 @@ impl Renderer {
             self.chunk_shader.texture.set_int(0);
             self.chunk_shader.light_level.set_float(self.light_level);
             self.chunk_shader.sky_offset.set_float(self.sky_offset);
+            self.chunk_shader.gamma.set_float(self.gamma);
 
             let tmp_world = world.as_ref().unwrap().clone();
 
+            self.chunks_drawn = 0;
+            self.chunks_culled = 0;
             for (pos, info) in tmp_world.get_render_list() {
+                // The render list can lag behind the camera, so check it against the frustum again
+                if self.frustum_culling && !is_section_visible(&self.frustum, pos) {
+                    self.chunks_culled += 1;
+                    continue;
+                }
+                self.chunks_drawn += 1;
                 if let Some(solid) = info.clone().read().solid.as_ref() {
                     if solid.count > 0 {
                         self.chunk_shader
@@ -392,14 +508,17 @@ impl Renderer {
                 if tmp_world.copy_cloud_heightmap(&mut clouds.heightmap_data) {
                     clouds.dirty = true;
                 }
-                clouds.draw(
-                    &self.camera.pos,
-                    &self.perspective_matrix,
-                    &self.camera_matrix,
-                    self.light_level,
-                    self.sky_offset,
-                    delta,
-                );
+                if self.cloud_mode != clouds::CloudMode::Off {
+                    clouds.draw(
+                        &self.camera.pos,
+                        &self.perspective_matrix,
+                        &self.camera_matrix,
+                        self.light_level,
+                        self.sky_offset,
+                        self.world_time,
+                        self.cloud_mode,
+                    );
+                }
             }
 
             if self.trans.is_some() {
@@ -418,6 +537,7 @@ impl Renderer {
                 self.chunk_shader_alpha
                     .sky_offset
                     .set_float(self.sky_offset);
+                self.chunk_shader_alpha.gamma.set_float(self.gamma);
 
                 // Copy the depth buffer
                 let trans = self.trans.as_ref().unwrap();
@@ -458,6 +578,9 @@ impl Renderer {
         if world.is_some() {
             let tmp_world = world.as_ref().unwrap().clone();
             for (pos, info) in tmp_world.get_render_list().iter().rev() {
+                if self.frustum_culling && !is_section_visible(&self.frustum, *pos) {
+                    continue;
+                }
                 if let Some(trans) = info.clone().read().trans.as_ref() {
                     if trans.count > 0 {
                         self.chunk_shader_alpha
@@ -1671,3 +1794,89 @@ pub fn generate_element_buffer(size: usize) -> (Vec<u8>, gl::Type) {
 
     (data, ty)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings;
+
+    #[test]
+    fn projection_follows_fov_setting() {
+        let projection = perspective(settings::clamp_fov(70), 1920, 1080);
+        assert_eq!(projection.fovy, cgmath::Rad::from(cgmath::Deg(70.0)));
+        assert!((projection.aspect - 16.0 / 9.0).abs() < 1e-6);
+
+        let projection = perspective(settings::clamp_fov(170), 600, 800);
+        assert_eq!(
+            projection.fovy,
+            cgmath::Rad::from(cgmath::Deg(settings::MAX_FOV as f32))
+        );
+        assert_eq!(projection.aspect, 1.0);
+        assert_eq!(settings::clamp_fov(0), settings::MIN_FOV as f32);
+    }
+
+    #[test]
+    fn section_frustum_intersection() {
+        // Looking towards negative z from the middle of section (0, 0, 0), y is flipped
+        let eye = cgmath::Point3::new(8.0, -8.0, 8.0);
+        let view = cgmath::Matrix4::look_at(
+            eye,
+            eye + cgmath::Vector3::new(0.0, 0.0, -1.0),
+            cgmath::Vector3::new(0.0, -1.0, 0.0),
+        );
+        let perspective = cgmath::perspective(cgmath::Deg(90.0_f32), 1.0, 0.1, 500.0);
+        let frustum = collision::Frustum::from_matrix4(perspective * view).unwrap();
+
+        // The section containing the camera and the ones in front of it
+        assert!(is_section_visible(&frustum, (0, 0, 0)));
+        assert!(is_section_visible(&frustum, (0, 0, -2)));
+        // Partially inside the edge of the view
+        assert!(is_section_visible(&frustum, (1, 0, -2)));
+        assert!(is_section_visible(&frustum, (0, 2, -4)));
+
+        // Behind the camera
+        assert!(!is_section_visible(&frustum, (0, 0, 2)));
+        // Outside of the 90 degree field of view
+        assert!(!is_section_visible(&frustum, (10, 0, -2)));
+        assert!(!is_section_visible(&frustum, (0, -10, -2)));
+        // Beyond the far plane
+        assert!(!is_section_visible(&frustum, (0, 0, -40)));
+    }
+
+    #[test]
+    fn third_person_camera_offset() {
+        use cgmath::InnerSpace;
+        use std::f64::consts::PI;
+        let close = |a: cgmath::Vector3<f64>, b: cgmath::Vector3<f64>| (a - b).magnitude() < 1e-9;
+
+        // Looking straight ahead towards negative z at yaw 0
+        let look = look_direction(0.0, 0.0);
+        assert!(close(look, cgmath::Vector3::new(0.0, 0.0, -1.0)));
+        assert_eq!(Perspective::FirstPerson.camera_direction(0.0, 0.0), None);
+        let back = Perspective::ThirdPersonBack
+            .camera_direction(0.0, 0.0)
+            .unwrap();
+        assert!(close(back, cgmath::Vector3::new(0.0, 0.0, 1.0)));
+
+        // Looking down at 45 degrees puts the camera above and behind the player
+        let back = Perspective::ThirdPersonBack
+            .camera_direction(PI / 2.0, PI / 4.0)
+            .unwrap();
+        let half = (0.5f64).sqrt();
+        assert!(close(back, cgmath::Vector3::new(half, half, 0.0)));
+        assert!((back.magnitude() - 1.0).abs() < 1e-9);
+
+        // The front camera sits where the player looks, turned around to face them
+        let front = Perspective::ThirdPersonFront
+            .camera_direction(PI / 2.0, PI / 4.0)
+            .unwrap();
+        assert!(close(front, -back));
+        let (yaw, pitch) = Perspective::ThirdPersonFront.camera_rotation(PI / 2.0, PI / 4.0);
+        assert!(close(look_direction(yaw, pitch), back));
+
+        assert_eq!(
+            Perspective::FirstPerson.next().next().next(),
+            Perspective::FirstPerson
+        );
+    }
+}
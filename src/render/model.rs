@@ -150,14 +150,7 @@ impl Manager {
         };
 
         Self::rebuild_model(&mut model);
-        if self.max_index < model.count as usize {
-            let (data, ty) = super::generate_element_buffer(model.count as usize);
-            self.index_buffer.bind(gl::ELEMENT_ARRAY_BUFFER);
-            self.index_buffer
-                .set_data(gl::ELEMENT_ARRAY_BUFFER, &data, gl::DYNAMIC_DRAW);
-            self.max_index = model.count as usize;
-            self.index_type = ty;
-        }
+        self.ensure_indices(model.count as usize);
 
         let collection = &mut self.collections[ckey.0];
         let key = ModelKey(ckey, collection.next_id);
@@ -167,6 +160,33 @@ impl Manager {
         key
     }
 
+    /// Replaces the vertices of a model with a single part, for models changing every frame.
+    pub fn update_model(&mut self, key: ModelKey, verts: Vec<Vertex>) {
+        let count = {
+            let collection = &mut self.collections[(key.0).0];
+            let model = match collection.models.get_mut(&key) {
+                Some(model) => model,
+                None => return,
+            };
+            model.verts = verts;
+            Self::rebuild_model(model);
+            model.count as usize
+        };
+        self.ensure_indices(count);
+    }
+
+    /// Grows the shared index buffer to cover `count` indices.
+    fn ensure_indices(&mut self, count: usize) {
+        if self.max_index < count {
+            let (data, ty) = super::generate_element_buffer(count);
+            self.index_buffer.bind(gl::ELEMENT_ARRAY_BUFFER);
+            self.index_buffer
+                .set_data(gl::ELEMENT_ARRAY_BUFFER, &data, gl::DYNAMIC_DRAW);
+            self.max_index = count;
+            self.index_type = ty;
+        }
+    }
+
     pub fn remove_model(&mut self, key: ModelKey) {
         let collection = &mut self.collections[(key.0).0];
         collection.models.remove(&key);
@@ -459,6 +479,9 @@ impl<'a> FormatState<'a> {
                     }
                 }
             }
+            format::Component::Translate(ref translation) => {
+                self.build(&format::Component::Text(translation.to_text()), color)
+            }
         }
     }
 
@@ -1,4 +1,6 @@
 uniform sampler2DArray textures;
+// Brightness from 0.0 ("Moody", the lighting as is) to 1.0 ("Bright"), see `settings::R_BRIGHTNESS`
+uniform float gamma;
 
 in vec3 vColor;
 in vec4 vTextureInfo;
@@ -22,13 +24,19 @@ out float revealage;
 
 #include lookup_texture
 
+// Lifts dark light levels while keeping full light as is, the same curve vanilla uses
+vec3 applyGamma(vec3 light) {
+    vec3 inv = 1.0 - light;
+    return mix(light, 1.0 - inv * inv * inv * inv, gamma);
+}
+
 void main() {
     vec4 col = atlasTexture();
     #ifndef alpha
     if (col.a < 0.5) discard;
     #endif
     col *= vec4(vColor, 1.0);
-    col.rgb *= vLighting;
+    col.rgb *= applyGamma(vLighting);
 
     #ifndef alpha
     fragColor = col;
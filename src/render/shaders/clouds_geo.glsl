@@ -5,6 +5,7 @@ uniform mat4 perspectiveMatrix;
 uniform mat4 cameraMatrix;
 uniform vec3 offset;
 uniform float cloudOffset;
+uniform bool fancy;
 
 uniform vec4 textureInfo;
 uniform float atlas;
@@ -62,6 +63,21 @@ void main() {
 		frontOffset = 0.0;
 	}
 
+	if (!fancy) {
+		// A flat layer, visible from above and below
+		setVertex(base, vec3(0.0, 0.0, frontOffset), 1.0);
+		setVertex(base, vec3(1.0, 0.0, frontOffset), 1.0);
+		setVertex(base, vec3(0.0, 0.0, backOffset), 1.0);
+		setVertex(base, vec3(1.0, 0.0, backOffset), 1.0);
+		EndPrimitive();
+		setVertex(base, vec3(0.0, 0.0, frontOffset), 0.7);
+		setVertex(base, vec3(0.0, 0.0, backOffset), 0.7);
+		setVertex(base, vec3(1.0, 0.0, frontOffset), 0.7);
+		setVertex(base, vec3(1.0, 0.0, backOffset), 0.7);
+		EndPrimitive();
+		return;
+	}
+
 	// Top
 	setVertex(base, vec3(0.0, 1.0, frontOffset), 1.0);
 	setVertex(base, vec3(1.0, 1.0, frontOffset), 1.0);
@@ -0,0 +1,96 @@
+//! How bright and which color the sky is over the course of a day, like vanilla.
+
+use std::f32::consts::PI;
+
+/// The color of the sky at noon.
+const DAY_COLOR: [f32; 3] = [122.0 / 255.0, 165.0 / 255.0, 247.0 / 255.0];
+
+/// How far the sun moved from noon at `world_time`, the time of day in ticks. 0.5 is
+/// midnight, the sun moves a bit faster around sunrise and sunset.
+pub fn celestial_angle(world_time: f64) -> f32 {
+    let angle = (((1.0 + world_time as f32) / 24000.0) - 0.25).rem_euclid(1.0);
+    let eased = 1.0 - ((angle * PI).cos() + 1.0) / 2.0;
+    angle + (eased - angle) / 3.0
+}
+
+/// How much light the sky gives at `world_time`, from 0.2 at night to 1.0 during the day.
+/// Passed to the shaders as `skyOffset`.
+pub fn sky_light(world_time: f64) -> f32 {
+    let angle = celestial_angle(world_time);
+    let darkness = (1.0 - ((angle * PI * 2.0).cos() * 2.0 + 0.2)).clamp(0.0, 1.0);
+    (1.0 - darkness) * 0.8 + 0.2
+}
+
+/// The color of the horizon around sunrise and sunset with how strongly it shows, `None`
+/// while the sun is too far above or below the horizon.
+pub fn sunrise_color(world_time: f64) -> Option<[f32; 4]> {
+    let height = (celestial_angle(world_time) * PI * 2.0).cos();
+    if !(-0.4..=0.4).contains(&height) {
+        return None;
+    }
+    let amount = height / 0.4 * 0.5 + 0.5;
+    let alpha = 1.0 - (1.0 - (amount * PI).sin()) * 0.99;
+    Some([
+        amount * 0.3 + 0.7,
+        amount * amount * 0.7 + 0.2,
+        0.2,
+        alpha * alpha,
+    ])
+}
+
+/// The color the sky is cleared with at `world_time`, turning gray the heavier it rains.
+pub fn sky_color(world_time: f64, rain_level: f32) -> [f32; 3] {
+    let angle = celestial_angle(world_time);
+    let brightness = ((angle * PI * 2.0).cos() * 2.0 + 0.5).clamp(0.0, 1.0);
+    let mut color = [
+        DAY_COLOR[0] * brightness,
+        DAY_COLOR[1] * brightness,
+        DAY_COLOR[2] * brightness,
+    ];
+    if let Some(sunrise) = sunrise_color(world_time) {
+        // Only tints the sky, the sunrise itself is on the horizon
+        let alpha = sunrise[3] * 0.5;
+        for (channel, sunrise) in color.iter_mut().zip(sunrise.iter()) {
+            *channel = *channel * (1.0 - alpha) + sunrise * alpha;
+        }
+    }
+    if rain_level > 0.0 {
+        let gray = (color[0] * 0.3 + color[1] * 0.59 + color[2] * 0.11) * 0.6;
+        let amount = rain_level * 0.75;
+        for channel in &mut color {
+            *channel = *channel * (1.0 - amount) + gray * amount;
+        }
+    }
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f32, expected: f32) {
+        assert!(
+            (value - expected).abs() < 0.01,
+            "expected {}, got {}",
+            expected,
+            value
+        );
+    }
+
+    #[test]
+    fn sky_light_follows_time_of_day() {
+        // Noon and midnight
+        assert_close(sky_light(6000.0), 1.0);
+        assert_close(sky_light(18000.0), 0.2);
+        // Sunrise and sunset are as bright as each other
+        assert_close(sky_light(0.0), 0.703);
+        assert_close(sky_light(12000.0), 0.703);
+        // Getting brighter through the sunrise, wrapping around the end of the day
+        assert!(sky_light(23000.0) < sky_light(0.0));
+        assert!(sky_light(0.0) < sky_light(1000.0));
+
+        assert!(sunrise_color(6000.0).is_none());
+        assert!(sunrise_color(18000.0).is_none());
+        assert!(sunrise_color(0.0).is_some());
+    }
+}
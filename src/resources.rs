@@ -20,6 +20,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::hash::BuildHasherDefault;
 use std::io;
+use std::io::Read;
 use std::path;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
@@ -27,6 +28,7 @@ use std::thread;
 
 use crate::types::hash::FNVHash;
 use crate::ui;
+use log::{info, warn};
 use std::fs::File;
 
 const RESOURCES_VERSION: &str = "1.12.2";
@@ -34,6 +36,10 @@ const VANILLA_CLIENT_URL: &str =
     "https://launcher.mojang.com/v1/objects/0f275bc1547d01fa5f56ba34bdc87d981ee12daf/client.jar";
 const ASSET_VERSION: &str = "1.12";
 const ASSET_INDEX_URL: &str = "https://launchermeta.mojang.com/mc/assets/1.12/67e29e024e664064c1f04c728604f83c24cbc218/1.12.json";
+/// The language used for the translations the selected language doesn't have.
+pub const FALLBACK_LANGUAGE: &str = "en_us";
+/// The `pack_format` of resource packs made for the same version as the vanilla assets.
+const PACK_FORMAT: i64 = 3;
 
 pub trait Pack: Sync + Send {
     fn open(&self, name: &str) -> Option<Box<dyn io::Read>>;
@@ -41,6 +47,10 @@ pub trait Pack: Sync + Send {
 
 pub struct Manager {
     packs: Vec<Box<dyn Pack>>,
+    /// The resource pack selected by the user, which overrides the default assets.
+    resource_pack: Option<Box<dyn Pack>>,
+    /// The resource pack sent by the server, which overrides all other packs.
+    server_resource_pack: Option<Box<dyn Pack>>,
     version: usize,
 
     vanilla_chan: Option<mpsc::Receiver<bool>>,
@@ -81,6 +91,8 @@ impl Manager {
     pub fn new() -> (Manager, ManagerUI) {
         let mut m = Manager {
             packs: Vec::new(),
+            resource_pack: None,
+            server_resource_pack: None,
             version: 0,
             vanilla_chan: None,
             vanilla_assets_chan: None,
@@ -114,7 +126,7 @@ impl Manager {
             }
         } else {
             let path = format!("assets/{}/{}", plugin, name);
-            for pack in self.packs.iter().rev() {
+            for pack in self.ordered_packs() {
                 if let Some(val) = pack.open(&path) {
                     return Some(val);
                 }
@@ -127,7 +139,7 @@ impl Manager {
     pub fn open_all(&self, plugin: &str, name: &str) -> Vec<Box<dyn io::Read>> {
         let mut ret = Vec::new();
         let path = format!("assets/{}/{}", plugin, name);
-        for pack in self.packs.iter().rev() {
+        for pack in self.ordered_packs() {
             if let Some(val) = pack.open(&path) {
                 ret.push(val);
             }
@@ -135,6 +147,97 @@ impl Manager {
         ret
     }
 
+    /// Reads the translations of `language`, like `de_de`, with the `en_us` ones filling in
+    /// the keys it's missing.
+    pub fn load_translations(&self, language: &str) -> HashMap<String, String> {
+        let mut translations = self
+            .read_translations(FALLBACK_LANGUAGE)
+            .unwrap_or_default();
+        if language != FALLBACK_LANGUAGE {
+            match self.read_translations(language) {
+                Some(localized) => translations.extend(localized),
+                None => warn!("No translations found for language {}", language),
+            }
+        }
+        translations
+    }
+
+    /// Newer packs have their translations as JSON and older ones, like the vanilla
+    /// assets, as `key=value` lines.
+    fn read_translations(&self, language: &str) -> Option<HashMap<String, String>> {
+        if let Some(file) = self.open("minecraft", &format!("lang/{}.json", language)) {
+            match serde_json::from_reader(file) {
+                Ok(translations) => return Some(translations),
+                Err(err) => warn!("Failed to parse lang/{}.json: {}", language, err),
+            }
+        }
+        let mut file = self.open("minecraft", &format!("lang/{}.lang", language))?;
+        let mut lang = String::new();
+        if let Err(err) = file.read_to_string(&mut lang) {
+            warn!("Failed to read lang/{}.lang: {}", language, err);
+            return None;
+        }
+        Some(
+            lang.lines()
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| {
+                    let mut parts = line.splitn(2, '=');
+                    Some((parts.next()?.to_owned(), parts.next()?.to_owned()))
+                })
+                .collect(),
+        )
+    }
+
+    /// The packs to look for assets in, the ones overriding the others first.
+    fn ordered_packs(&self) -> impl Iterator<Item = &Box<dyn Pack>> {
+        self.server_resource_pack
+            .iter()
+            .chain(self.resource_pack.iter())
+            .chain(self.packs.iter().rev())
+    }
+
+    /// Loads the resource pack zip `name` from `resource_packs_dir` on top of the
+    /// default assets, replacing the previously selected one. An empty name only
+    /// removes the previous pack.
+    pub fn select_resource_pack(&mut self, name: &str) {
+        if name.is_empty() {
+            self.set_resource_pack(None);
+            return;
+        }
+        match load_zip_pack(&resource_packs_dir().join(name)) {
+            Ok(pack) => self.set_resource_pack(Some(Box::new(pack))),
+            Err(err) => {
+                warn!("Failed to load resource pack {}: {}", name, err);
+                self.set_resource_pack(None);
+            }
+        }
+    }
+
+    /// Bumping the version rebuilds the texture atlas, models and chunks with the new
+    /// pack's assets.
+    fn set_resource_pack(&mut self, pack: Option<Box<dyn Pack>>) {
+        if self.resource_pack.is_none() && pack.is_none() {
+            return;
+        }
+        self.resource_pack = pack;
+        self.version += 1;
+    }
+
+    /// Loads the resource pack zip at `path` sent by the server on top of all other
+    /// packs, until `clear_server_resource_pack` is called.
+    pub fn set_server_resource_pack(&mut self, path: &path::Path) -> zip::result::ZipResult<()> {
+        self.server_resource_pack = Some(Box::new(load_zip_pack(path)?));
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Removes the server's resource pack, when leaving the server.
+    pub fn clear_server_resource_pack(&mut self) {
+        if self.server_resource_pack.take().is_some() {
+            self.version += 1;
+        }
+    }
+
     pub fn tick(&mut self, mui: &mut ManagerUI, ui_container: &mut ui::Container, delta: f64) {
         let delta = delta.min(5.0);
         // Check to see if the download of vanilla has completed
@@ -497,6 +600,84 @@ impl Manager {
     }
 }
 
+/// Where the resource pack zips the user can choose from are kept.
+pub fn resource_packs_dir() -> path::PathBuf {
+    paths::get_data_dir().join("resourcepacks")
+}
+
+/// The file names of the resource pack zips in `resource_packs_dir`, sorted.
+pub fn list_resource_packs() -> Vec<String> {
+    let mut packs: Vec<String> = fs::read_dir(resource_packs_dir())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.to_lowercase().ends_with(".zip"))
+        .collect();
+    packs.sort();
+    packs
+}
+
+/// Opens the resource pack zip at `path`, warning when it was made for another version.
+fn load_zip_pack(path: &path::Path) -> zip::result::ZipResult<ZipPack<fs::File>> {
+    let pack = ZipPack::new(fs::File::open(path)?)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    info!(
+        "Loaded resource pack {} ({} textures, {} models)",
+        name,
+        pack.count_files("assets/minecraft/textures/"),
+        pack.count_files("assets/minecraft/models/")
+    );
+    match pack.pack_format() {
+        Some(PACK_FORMAT) => {}
+        Some(format) => warn!(
+            "Resource pack {} has pack format {} instead of {}, some of its assets might not be used",
+            name, format, PACK_FORMAT
+        ),
+        None => warn!("Resource pack {} has no valid pack.mcmeta", name),
+    }
+    Ok(pack)
+}
+
+/// A resource pack zip, as made for the vanilla client.
+struct ZipPack<R> {
+    archive: Mutex<zip::ZipArchive<R>>,
+}
+
+impl<R: io::Read + io::Seek> ZipPack<R> {
+    fn new(reader: R) -> zip::result::ZipResult<ZipPack<R>> {
+        Ok(ZipPack {
+            archive: Mutex::new(zip::ZipArchive::new(reader)?),
+        })
+    }
+
+    /// The `pack_format` in the pack's pack.mcmeta, if it has a valid one.
+    fn pack_format(&self) -> Option<i64> {
+        let mut archive = self.archive.lock().unwrap();
+        let meta: serde_json::Value =
+            serde_json::from_reader(archive.by_name("pack.mcmeta").ok()?).ok()?;
+        meta.pointer("/pack/pack_format").and_then(|v| v.as_i64())
+    }
+
+    fn count_files(&self, prefix: &str) -> usize {
+        let archive = self.archive.lock().unwrap();
+        archive
+            .file_names()
+            .filter(|name| name.starts_with(prefix) && !name.ends_with('/'))
+            .count()
+    }
+}
+
+impl<R: io::Read + io::Seek + Send> Pack for ZipPack<R> {
+    fn open(&self, name: &str) -> Option<Box<dyn io::Read>> {
+        let mut archive = self.archive.lock().unwrap();
+        let mut file = archive.by_name(name).ok()?;
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data).ok()?;
+        Some(Box::new(io::Cursor::new(data)))
+    }
+}
+
 struct DirPack {
     root: path::PathBuf,
 }
@@ -577,3 +758,91 @@ impl<'a, T: io::Read> io::Read for ProgressRead<'a, T> {
         Ok(size)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leafish_protocol::format;
+    use std::io::Write;
+
+    #[test]
+    fn resource_pack_overrides_texture() {
+        let mut zip = zip::ZipWriter::new(io::Cursor::new(vec![]));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("pack.mcmeta", options).unwrap();
+        zip.write_all(br#"{"pack": {"pack_format": 3, "description": "Tiny"}}"#)
+            .unwrap();
+        zip.start_file("assets/minecraft/textures/font/ascii.png", options)
+            .unwrap();
+        zip.write_all(b"not really a png").unwrap();
+        let pack = ZipPack::new(zip.finish().unwrap()).unwrap();
+        assert_eq!(pack.pack_format(), Some(PACK_FORMAT));
+        assert_eq!(pack.count_files("assets/minecraft/textures/"), 1);
+        assert_eq!(pack.count_files("assets/minecraft/models/"), 0);
+
+        let mut manager = Manager {
+            packs: vec![Box::new(InternalPack)],
+            resource_pack: None,
+            server_resource_pack: None,
+            version: 0,
+            vanilla_chan: None,
+            vanilla_assets_chan: None,
+            vanilla_progress: Arc::new(Mutex::new(Progress { tasks: vec![] })),
+        };
+        let read = |manager: &Manager| {
+            let mut data = vec![];
+            manager
+                .open("minecraft", "textures/font/ascii.png")
+                .unwrap()
+                .read_to_end(&mut data)
+                .unwrap();
+            data
+        };
+        let default = read(&manager);
+        assert_ne!(default, b"not really a png");
+
+        manager.set_resource_pack(Some(Box::new(pack)));
+        assert_eq!(manager.version(), 1);
+        assert_eq!(read(&manager), b"not really a png");
+        // Files the pack doesn't have still come from the defaults
+        assert!(manager.open("leafish", "textures/gui/cog.png").is_some());
+
+        manager.set_resource_pack(None);
+        assert_eq!(manager.version(), 2);
+        assert_eq!(read(&manager), default);
+    }
+
+    #[test]
+    fn language_selects_translations() {
+        let mut zip = zip::ZipWriter::new(io::Cursor::new(vec![]));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("assets/minecraft/lang/en_us.json", options)
+            .unwrap();
+        zip.write_all(br#"{"test.greeting": "Hello %s", "test.farewell": "Bye %s"}"#)
+            .unwrap();
+        zip.start_file("assets/minecraft/lang/de_de.lang", options)
+            .unwrap();
+        zip.write_all(b"# German\ntest.greeting=Hallo %s\n")
+            .unwrap();
+        let manager = Manager {
+            packs: vec![],
+            resource_pack: Some(Box::new(ZipPack::new(zip.finish().unwrap()).unwrap())),
+            server_resource_pack: None,
+            version: 0,
+            vanilla_chan: None,
+            vanilla_assets_chan: None,
+            vanilla_progress: Arc::new(Mutex::new(Progress { tasks: vec![] })),
+        };
+        let args = ["Steve".to_owned()];
+
+        format::set_translations(manager.load_translations("en_us"));
+        assert_eq!(format::translate("test.greeting", &args), "Hello Steve");
+
+        format::set_translations(manager.load_translations("de_de"));
+        assert_eq!(format::translate("test.greeting", &args), "Hallo Steve");
+        // Keys the language doesn't have fall back to English
+        assert_eq!(format::translate("test.farewell", &args), "Bye Steve");
+    }
+}
@@ -0,0 +1,302 @@
+use std::cmp;
+use std::sync::Arc;
+
+use crate::console;
+use crate::render;
+use crate::render::hud::{Hud, HudContext};
+use crate::server::chat::Completions;
+use crate::ui;
+
+use parking_lot::RwLock;
+use winit::event::VirtualKeyCode;
+
+/// How many of the latest messages are shown above the input box.
+const SHOWN_MESSAGES: usize = 10;
+/// How many tab completions are listed at once.
+const SHOWN_SUGGESTIONS: usize = 10;
+
+/// The input box for typing chat messages and commands, drawn over the game.
+pub struct Chat {
+    hud_context: Arc<RwLock<HudContext>>,
+    /// What the input box starts with, "/" when opened to type a command.
+    initial_input: String,
+    /// The sent line being shown, while going through them with the arrow keys.
+    history_index: Option<usize>,
+    /// The line being typed before going through the sent lines.
+    draft: String,
+    shown_messages: usize,
+    completion: Option<Completion>,
+    dirty_suggestions: bool,
+    elements: Option<UIElements>,
+}
+
+struct UIElements {
+    input: ui::TextBoxRef,
+    _background: Option<ui::ImageRef>,
+    _messages: Vec<ui::FormattedRef>,
+    _suggestions: Option<ui::ImageRef>,
+}
+
+/// A tab-complete request, cycled through by pressing tab again once answered.
+struct Completion {
+    /// The input the completions were requested for.
+    input: String,
+    completions: Option<Completions>,
+    selected: usize,
+}
+
+impl Completion {
+    /// The input with the selected match filled in.
+    fn completed(&self) -> Option<String> {
+        self.completions
+            .as_ref()
+            .map(|completions| completions.apply(&self.input, self.selected))
+    }
+}
+
+impl Chat {
+    pub fn new(hud_context: Arc<RwLock<HudContext>>, initial_input: &str) -> Chat {
+        Chat {
+            hud_context,
+            initial_input: initial_input.to_owned(),
+            history_index: None,
+            draft: String::new(),
+            shown_messages: 0,
+            completion: None,
+            dirty_suggestions: false,
+            elements: None,
+        }
+    }
+
+    /// Fills in the next match when the server already answered for the current input,
+    /// otherwise asks it for them.
+    fn tab_complete(&mut self) {
+        let elements = match self.elements.as_ref() {
+            Some(elements) => elements,
+            None => return,
+        };
+        let mut input = elements.input.borrow_mut();
+        if let Some(completion) = self.completion.as_mut() {
+            if completion.completed().as_ref() == Some(&input.input) {
+                let count = completion.completions.as_ref().unwrap().matches.len();
+                completion.selected = (completion.selected + 1) % count;
+                input.input = completion.completed().unwrap();
+                self.dirty_suggestions = true;
+                return;
+            }
+        }
+        let server = self.hud_context.read().server.clone();
+        if let Some(server) = server {
+            server.request_tab_completions(&input.input);
+            self.completion = Some(Completion {
+                input: input.input.clone(),
+                completions: None,
+                selected: 0,
+            });
+            self.dirty_suggestions = true;
+        }
+    }
+
+    fn render_suggestions(
+        &self,
+        renderer: &render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> Option<ui::ImageRef> {
+        let completion = self.completion.as_ref()?;
+        let matches = &completion.completions.as_ref()?.matches;
+        // Scroll along to keep the selected match in view
+        let first = (completion.selected + 1).saturating_sub(SHOWN_SUGGESTIONS);
+        let shown = &matches[first..cmp::min(first + SHOWN_SUGGESTIONS, matches.len())];
+        let width = shown
+            .iter()
+            .map(|text| renderer.ui.size_of_string(text))
+            .fold(0.0, f64::max);
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Left)
+            .position(2.0, 28.0)
+            .size(width + 8.0, 18.0 * shown.len() as f64 + 4.0)
+            .colour((0, 0, 0, 200))
+            .draw_index(1)
+            .create(ui_container);
+        for (i, text) in shown.iter().enumerate() {
+            let colour = if first + i == completion.selected {
+                (255, 255, 85, 255)
+            } else {
+                (170, 170, 170, 255)
+            };
+            ui::TextBuilder::new()
+                .text(text.clone())
+                .position(4.0, 2.0 + 18.0 * i as f64)
+                .alignment(ui::VAttach::Top, ui::HAttach::Left)
+                .colour(colour)
+                .attach(&mut *background.borrow_mut());
+        }
+        Some(background)
+    }
+
+    fn render_messages(
+        &mut self,
+        renderer: &render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> (Option<ui::ImageRef>, Vec<ui::FormattedRef>) {
+        let hud_context = self.hud_context.read();
+        let history = hud_context.get_chat_history();
+        let timestamps = hud_context.get_chat_options().timestamps;
+        self.shown_messages = hud_context.get_received_messages();
+        let scale = Hud::icon_scale(renderer) / 2.0;
+        let count = cmp::min(SHOWN_MESSAGES, history.len());
+        if count == 0 {
+            return (None, vec![]);
+        }
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Left)
+            .position(0.0, 30.0)
+            .size(500.0 * scale, 6.0 * scale + 10.0 * scale * count as f64)
+            .colour((0, 0, 0, 100))
+            .create(ui_container);
+        let messages = history
+            .iter()
+            .rev()
+            .take(count)
+            .enumerate()
+            .map(|(i, line)| {
+                ui::FormattedBuilder::new()
+                    .alignment(ui::VAttach::Bottom, ui::HAttach::Left)
+                    .scale_x(scale)
+                    .scale_y(scale)
+                    .position(scale * 5.0, 30.0 + scale * (i * 10) as f64)
+                    .text(line.text(timestamps))
+                    .max_width(490.0 * scale)
+                    .create(ui_container)
+            })
+            .collect();
+        (Some(background), messages)
+    }
+}
+
+impl super::Screen for Chat {
+    fn on_active(&mut self, renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        let input = ui::TextBoxBuilder::new()
+            .input(&*self.initial_input)
+            .position(2.0, 2.0)
+            .size(500.0, 24.0)
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Left)
+            .create(ui_container);
+        ui::TextBox::make_focusable(&input, ui_container);
+        {
+            let hud_context = self.hud_context.clone();
+            input.borrow_mut().add_submit_func(move |textbox, game| {
+                let line = textbox.input.trim().to_owned();
+                // Closed first, commands can change the screens
+                game.screen_sys.pop_screen();
+                game.focused = true;
+                if line.is_empty() {
+                    return;
+                }
+                hud_context.write().remember_sent_message(line.clone());
+                // The client's own commands print to the console instead
+                if console::run_command(game, &line) {
+                    game.console.lock().activate();
+                } else if let Some(server) = game.server.as_ref() {
+                    server.send_chat(&line);
+                }
+            });
+        }
+        let (background, messages) = self.render_messages(renderer, ui_container);
+        self.elements = Some(UIElements {
+            input,
+            _background: background,
+            _messages: messages,
+            _suggestions: None,
+        });
+        self.dirty_suggestions = true;
+    }
+
+    fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
+        // Keep what was typed for when another screen opened on top is closed
+        if let Some(elements) = self.elements.take() {
+            self.initial_input = elements.input.borrow().input.clone();
+        }
+    }
+
+    fn tick(
+        &mut self,
+        _delta: f64,
+        renderer: &mut render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> Option<Box<dyn super::Screen>> {
+        let received = self.hud_context.read().get_received_messages();
+        if received != self.shown_messages {
+            let (background, messages) = self.render_messages(renderer, ui_container);
+            let elements = self.elements.as_mut().unwrap();
+            elements._background = background;
+            elements._messages = messages;
+        }
+
+        let server = self.hud_context.read().server.clone();
+        let elements = self.elements.as_ref().unwrap();
+        let mut input = elements.input.borrow_mut();
+        if let Some(completion) = self.completion.as_mut() {
+            if completion.completions.is_none() {
+                if let Some(completions) = server.and_then(|server| server.take_tab_completions()) {
+                    // Only fill in the first match if nothing was typed in the meantime
+                    if !completions.matches.is_empty() && completion.input == input.input {
+                        completion.completions = Some(completions);
+                        input.input = completion.completed().unwrap();
+                    } else {
+                        self.completion = None;
+                    }
+                    self.dirty_suggestions = true;
+                }
+            } else if completion.completed().as_ref() != Some(&input.input) {
+                // Typing hides the suggestions again
+                self.completion = None;
+                self.dirty_suggestions = true;
+            }
+        }
+        drop(input);
+        if self.dirty_suggestions {
+            self.dirty_suggestions = false;
+            let suggestions = self.render_suggestions(renderer, ui_container);
+            self.elements.as_mut().unwrap()._suggestions = suggestions;
+        }
+        None
+    }
+
+    fn on_key_press(&mut self, key: VirtualKeyCode) -> bool {
+        if key == VirtualKeyCode::Tab {
+            self.tab_complete();
+            return true;
+        }
+        let elements = match self.elements.as_ref() {
+            Some(elements) => elements,
+            None => return false,
+        };
+        let hud_context = self.hud_context.read();
+        let sent = hud_context.get_sent_messages();
+        let index = match (key, self.history_index) {
+            (VirtualKeyCode::Up, None) if !sent.is_empty() => Some(sent.len() - 1),
+            (VirtualKeyCode::Up, Some(index)) => Some(index.saturating_sub(1)),
+            (VirtualKeyCode::Down, Some(index)) if index + 1 < sent.len() => Some(index + 1),
+            (VirtualKeyCode::Down, _) => None,
+            (VirtualKeyCode::Up, None) => return true,
+            _ => return false,
+        };
+        let mut input = elements.input.borrow_mut();
+        if self.history_index.is_none() {
+            self.draft = input.input.clone();
+        }
+        input.input = match index {
+            Some(index) => sent[index].clone(),
+            None => self.draft.clone(),
+        };
+        self.history_index = index;
+        true
+    }
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,259 @@
+use crate::console;
+use crate::format;
+use crate::render;
+use crate::settings::{self, Actionkey};
+use crate::ui;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use winit::event::VirtualKeyCode;
+
+const CONFLICT_COLOUR: (u8, u8, u8, u8) = (255, 85, 85, 255);
+const DEFAULT_COLOUR: (u8, u8, u8, u8) = (255, 255, 255, 255);
+/// How many actions are shown at once, as many as fit above the done button.
+const ACTIONS_PER_PAGE: usize = 16;
+
+pub struct Controls {
+    vars: Rc<console::Vars>,
+    elements: Option<UIElements>,
+    /// The action waiting for a key press to be bound to.
+    rebinding: Rc<Cell<Option<Actionkey>>>,
+    /// The page of actions to show, changed by the page buttons.
+    page: Rc<Cell<usize>>,
+    shown_page: usize,
+}
+
+struct UIElements {
+    background: ui::ImageRef,
+    _buttons: Vec<ui::ButtonRef>,
+    labels: Vec<(Actionkey, ui::TextRef)>,
+}
+
+impl Controls {
+    pub fn new(vars: Rc<console::Vars>) -> Self {
+        Controls {
+            vars,
+            elements: None,
+            rebinding: Rc::new(Cell::new(None)),
+            page: Rc::new(Cell::new(0)),
+            shown_page: 0,
+        }
+    }
+
+    fn pages() -> usize {
+        (Actionkey::values().len() + ACTIONS_PER_PAGE - 1) / ACTIONS_PER_PAGE
+    }
+
+    /// The actions shown on `page`.
+    fn page_actions(page: usize) -> Vec<Actionkey> {
+        Actionkey::values()
+            .into_iter()
+            .skip(page * ACTIONS_PER_PAGE)
+            .take(ACTIONS_PER_PAGE)
+            .collect()
+    }
+
+    fn page_button(
+        ui_container: &mut ui::Container,
+        text: &str,
+        x: f64,
+        page: &Rc<Cell<usize>>,
+        step: isize,
+    ) -> ui::ButtonRef {
+        let button = ui::ButtonBuilder::new()
+            .position(x, 50.0)
+            .size(40.0, 40.0)
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut button = button.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(text)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *button);
+            button.add_text(txt);
+            let page = page.clone();
+            button.add_click_func(move |_, _| {
+                let pages = Self::pages() as isize;
+                page.set((page.get() as isize + step).rem_euclid(pages) as usize);
+                true
+            });
+        }
+        button
+    }
+
+    fn label(vars: &console::Vars, action: Actionkey) -> String {
+        let key = match settings::keycode_from_name(&vars.get(action.get_cvar())) {
+            Some(key) => format!("{:?}", key),
+            None => "None".into(),
+        };
+        format!("{}: {}", action.name(), key)
+    }
+
+    fn update_labels(&self) {
+        let elements = self.elements.as_ref().unwrap();
+        let conflicts = Actionkey::conflicts(&self.vars);
+        for (action, label) in &elements.labels {
+            let mut label = label.borrow_mut();
+            label.text = Self::label(&self.vars, *action);
+            label.colour = if conflicts.contains(action) {
+                CONFLICT_COLOUR
+            } else {
+                DEFAULT_COLOUR
+            };
+        }
+    }
+}
+
+impl super::Screen for Controls {
+    fn on_active(&mut self, _renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .position(0.0, 0.0)
+            .size(854.0, 480.0)
+            .colour((0, 0, 0, 100))
+            .create(ui_container);
+
+        let mut buttons = vec![];
+        let mut labels = vec![];
+
+        // Two columns, from top and down
+        let page = self.page.get().min(Self::pages() - 1);
+        self.page.set(page);
+        self.shown_page = page;
+        for (i, action) in Self::page_actions(page).into_iter().enumerate() {
+            let button = ui::ButtonBuilder::new()
+                .position(
+                    if i % 2 == 0 { -160.0 } else { 160.0 },
+                    -200.0 + (i / 2) as f64 * 32.0,
+                )
+                .size(300.0, 30.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            {
+                let mut button = button.borrow_mut();
+                let txt = ui::TextBuilder::new()
+                    .text(Self::label(&self.vars, action))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .attach(&mut *button);
+                let txt_key = txt.clone();
+                button.add_text(txt);
+                let rebinding = self.rebinding.clone();
+                button.add_click_func(move |_, _| {
+                    rebinding.set(Some(action));
+                    txt_key.borrow_mut().text = format!("> {}: ??? <", action.name());
+                    true
+                });
+                labels.push((action, txt_key));
+            }
+            buttons.push(button);
+        }
+
+        let done_button = ui::ButtonBuilder::new()
+            .position(0.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut done_button = done_button.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(format::translate_or("gui.done", "Done"))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *done_button);
+            done_button.add_text(txt);
+            done_button.add_click_func(|_, game| {
+                game.screen_sys.pop_screen();
+                true
+            });
+        }
+        buttons.push(done_button);
+        if Self::pages() > 1 {
+            buttons.push(Self::page_button(ui_container, "<", -180.0, &self.page, -1));
+            buttons.push(Self::page_button(ui_container, ">", 180.0, &self.page, 1));
+        }
+
+        self.elements = Some(UIElements {
+            background,
+            _buttons: buttons,
+            labels,
+        });
+        self.rebinding.set(None);
+        self.update_labels();
+    }
+
+    fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
+        self.elements = None;
+    }
+
+    // Called every frame the screen is active
+    fn tick(
+        &mut self,
+        _delta: f64,
+        renderer: &mut render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> Option<Box<dyn super::Screen>> {
+        if self.page.get() != self.shown_page {
+            self.on_deactive(renderer, ui_container);
+            self.on_active(renderer, ui_container);
+        }
+        let elements = self.elements.as_mut().unwrap();
+        {
+            let mode = ui_container.mode;
+            let mut background = elements.background.borrow_mut();
+            background.width = match mode {
+                ui::Mode::Unscaled(scale) => 854.0 / scale,
+                ui::Mode::Scaled => renderer.width as f64,
+            };
+            background.height = match mode {
+                ui::Mode::Unscaled(scale) => 480.0 / scale,
+                ui::Mode::Scaled => renderer.height as f64,
+            };
+        }
+        None
+    }
+
+    // Events
+    fn on_scroll(&mut self, _x: f64, y: f64) {
+        let pages = Self::pages();
+        if y < 0.0 {
+            self.page.set((self.shown_page + 1).min(pages - 1));
+        } else if y > 0.0 {
+            self.page.set(self.shown_page.saturating_sub(1));
+        }
+    }
+
+    fn on_key_press(&mut self, key: VirtualKeyCode) -> bool {
+        let action = match self.rebinding.take() {
+            Some(action) => action,
+            None => return false,
+        };
+        // Escape cancels rebinding, keeping the old key, as do keys that can't be bound
+        if key != VirtualKeyCode::Escape {
+            if let Some(name) = settings::key_name(key) {
+                self.vars.set(action.get_cvar(), name.to_owned());
+            }
+        }
+        if self.elements.is_some() {
+            self.update_labels();
+        }
+        true
+    }
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_action_is_on_a_page() {
+        let shown = (0..Controls::pages())
+            .flat_map(Controls::page_actions)
+            .collect::<Vec<_>>();
+        assert_eq!(shown, Actionkey::values());
+        assert!(Controls::pages() > 1);
+    }
+}
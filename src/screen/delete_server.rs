@@ -12,16 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
-use std::fs;
-
-use crate::paths;
+use crate::format;
 use crate::render;
+use crate::servers::SavedServers;
 use crate::settings;
 use crate::ui;
 
-use serde_json::{self, Value};
-
 // TODO: make use of "background_img: String"
 #[allow(dead_code)]
 pub struct DeleteServerEntry {
@@ -57,28 +53,9 @@ impl DeleteServerEntry {
     }
 
     fn delete_server(index: usize) {
-        let mut servers_info = match fs::File::open(paths::get_data_dir().join("servers.json")) {
-            Ok(val) => serde_json::from_reader(val).unwrap(),
-            Err(_) => {
-                let mut info = BTreeMap::default();
-                info.insert("servers".to_owned(), Value::Array(vec![]));
-                Value::Object(info.into_iter().collect())
-            }
-        };
-
-        {
-            let servers = servers_info
-                .as_object_mut()
-                .unwrap()
-                .get_mut("servers")
-                .unwrap()
-                .as_array_mut()
-                .unwrap();
-            servers.remove(index);
-        }
-
-        let mut out = fs::File::create(paths::get_data_dir().join("servers.json")).unwrap();
-        serde_json::to_writer_pretty(&mut out, &servers_info).unwrap();
+        let mut servers = SavedServers::load();
+        servers.remove(index);
+        servers.save();
     }
 }
 
@@ -130,7 +107,7 @@ impl super::Screen for DeleteServerEntry {
         {
             let mut cancel = cancel.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Cancel")
+                .text(format::translate_or("gui.cancel", "Cancel"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *cancel);
             cancel.add_text(txt);
@@ -0,0 +1,177 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::console;
+use crate::format;
+use crate::render;
+use crate::render::hud::{Hud, HudContext};
+use crate::servers;
+use crate::settings;
+use crate::ui;
+
+use log::warn;
+use parking_lot::RwLock;
+
+pub struct DirectConnect {
+    vars: Rc<console::Vars>,
+    elements: Option<UIElements>,
+}
+
+struct UIElements {
+    logo: ui::logo::Logo,
+
+    _address: ui::TextBoxRef,
+    _recent: Vec<ui::ButtonRef>,
+    _connect: ui::ButtonRef,
+    _cancel: ui::ButtonRef,
+}
+
+impl DirectConnect {
+    pub fn new(vars: Rc<console::Vars>) -> DirectConnect {
+        DirectConnect {
+            vars,
+            elements: None,
+        }
+    }
+
+    /// Shows the connecting screen while connecting to `address`, then the game or the
+    /// server list with what went wrong.
+    pub fn connect(game: &mut crate::Game, address: &str) {
+        let address = address.trim().to_owned();
+        if address.is_empty() {
+            return;
+        }
+        game.screen_sys
+            .replace_screen(Box::new(super::connecting::Connecting::new(&address)));
+        let hud_context = Arc::new(RwLock::new(HudContext::new()));
+        let result = game.connect_to(&address, hud_context.clone());
+        game.screen_sys.pop_screen();
+        if let Err(error) = result {
+            warn!("Failed to connect to {}: {}", address, error);
+            game.screen_sys.add_screen(Box::new(super::ServerList::new(
+                Some(error.to_component()),
+                game.vars.get(settings::BACKGROUND_IMAGE).clone(),
+            )));
+        } else {
+            game.screen_sys.add_screen(Box::new(Hud::new(hud_context)));
+            game.focused = true;
+        }
+    }
+}
+
+impl super::Screen for DirectConnect {
+    fn on_active(&mut self, renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        let logo = ui::logo::Logo::new(renderer.resources.clone(), ui_container);
+
+        // Address
+        let server_address = ui::TextBoxBuilder::new()
+            .input("")
+            .position(0.0, -80.0)
+            .size(400.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        ui::TextBox::make_focusable(&server_address, ui_container);
+        ui::TextBuilder::new()
+            .text("Address")
+            .position(0.0, -18.0)
+            .attach(&mut *server_address.borrow_mut());
+
+        // Recently used addresses, clicking one fills in the address
+        let mut recent = vec![];
+        let recent_addresses =
+            servers::parse_recent_servers(&self.vars.get(settings::CL_RECENT_SERVERS));
+        for (i, recent_address) in recent_addresses.into_iter().enumerate() {
+            let button = ui::ButtonBuilder::new()
+                .position(0.0, -30.0 + i as f64 * 35.0)
+                .size(400.0, 30.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            {
+                let mut button = button.borrow_mut();
+                let txt = ui::TextBuilder::new()
+                    .text(recent_address.clone())
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .attach(&mut *button);
+                button.add_text(txt);
+                let server_address = server_address.clone();
+                button.add_click_func(move |_, _| {
+                    server_address.borrow_mut().input = recent_address.clone();
+                    true
+                });
+            }
+            recent.push(button);
+        }
+
+        // Connect
+        let connect = ui::ButtonBuilder::new()
+            .position(110.0, 160.0)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut connect = connect.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Connect")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *connect);
+            connect.add_text(txt);
+            let server_address = server_address.clone();
+            connect.add_click_func(move |_, game| {
+                let address = server_address.borrow().input.clone();
+                Self::connect(game, &address);
+                true
+            });
+        }
+
+        // Cancel
+        let cancel = ui::ButtonBuilder::new()
+            .position(-110.0, 160.0)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut cancel = cancel.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(format::translate_or("gui.cancel", "Cancel"))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *cancel);
+            cancel.add_text(txt);
+            cancel.add_click_func(|_, game| {
+                game.screen_sys
+                    .replace_screen(Box::new(super::ServerList::new(
+                        None,
+                        game.vars.get(settings::BACKGROUND_IMAGE).clone(),
+                    )));
+                true
+            });
+        }
+
+        self.elements = Some(UIElements {
+            logo,
+            _address: server_address,
+            _recent: recent,
+            _connect: connect,
+            _cancel: cancel,
+        });
+    }
+
+    fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
+        // Clean up
+        self.elements = None
+    }
+
+    fn tick(
+        &mut self,
+        _delta: f64,
+        renderer: &mut render::Renderer,
+        _ui_container: &mut ui::Container,
+    ) -> Option<Box<dyn super::Screen>> {
+        let elements = self.elements.as_mut().unwrap();
+        elements.logo.tick(renderer);
+        None
+    }
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,160 @@
+//! The screen covering the world while joining a server, until the terrain around the
+//! player is ready to be shown.
+
+use crate::format;
+use crate::render;
+use crate::ui;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Spinner frames, each shown for this many frame deltas (1/60ths of a second).
+const SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+const SPINNER_FRAME_TIME: f64 = 8.0;
+
+/// How many of the chunks expected around the player have been received.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TerrainProgress {
+    pub received: usize,
+    pub expected: usize,
+}
+
+impl TerrainProgress {
+    pub fn new(received: usize, expected: usize) -> TerrainProgress {
+        TerrainProgress { received, expected }
+    }
+
+    /// The received chunks as a percentage of the expected ones. Servers with a larger view
+    /// distance than expected send more chunks, which don't count beyond 100%.
+    pub fn percent(&self) -> u32 {
+        if self.expected == 0 {
+            return 100;
+        }
+        (self.received.min(self.expected) * 100 / self.expected) as u32
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "{} / {} chunks ({}%)",
+            self.received.min(self.expected),
+            self.expected,
+            self.percent()
+        )
+    }
+}
+
+pub struct DownloadingTerrain {
+    progress: Arc<RwLock<TerrainProgress>>,
+    elements: Option<UIElements>,
+    spinner_time: f64,
+}
+
+struct UIElements {
+    background: ui::ImageRef,
+    _title: ui::TextRef,
+    progress: ui::TextRef,
+    spinner: ui::TextRef,
+}
+
+impl DownloadingTerrain {
+    pub fn new(progress: Arc<RwLock<TerrainProgress>>) -> DownloadingTerrain {
+        DownloadingTerrain {
+            progress,
+            elements: None,
+            spinner_time: 0.0,
+        }
+    }
+}
+
+impl super::Screen for DownloadingTerrain {
+    fn on_active(&mut self, renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .position(0.0, 0.0)
+            .size(renderer.width as f64, renderer.height as f64)
+            .colour((0, 0, 0, 255))
+            .create(ui_container);
+
+        let title = ui::TextBuilder::new()
+            .text(format::translate_or(
+                "multiplayer.downloadingTerrain",
+                "Downloading terrain",
+            ))
+            .position(0.0, -16.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        let progress = ui::TextBuilder::new()
+            .text(self.progress.read().label())
+            .position(0.0, 4.0)
+            .colour((170, 170, 170, 255))
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        let spinner = ui::TextBuilder::new()
+            .text(SPINNER[0])
+            .position(0.0, 24.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        self.elements = Some(UIElements {
+            background,
+            _title: title,
+            progress,
+            spinner,
+        });
+    }
+
+    fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
+        self.elements = None;
+    }
+
+    fn tick(
+        &mut self,
+        delta: f64,
+        renderer: &mut render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> Option<Box<dyn super::Screen>> {
+        self.spinner_time += delta;
+        let frame = (self.spinner_time / SPINNER_FRAME_TIME) as usize % SPINNER.len();
+        let elements = self.elements.as_mut().unwrap();
+        {
+            let mode = ui_container.mode;
+            let mut background = elements.background.borrow_mut();
+            background.width = match mode {
+                ui::Mode::Unscaled(scale) => 854.0 / scale,
+                ui::Mode::Scaled => renderer.width as f64,
+            };
+            background.height = match mode {
+                ui::Mode::Unscaled(scale) => 480.0 / scale,
+                ui::Mode::Scaled => renderer.height as f64,
+            };
+        }
+        let label = self.progress.read().label();
+        if elements.progress.borrow().text != label {
+            elements.progress.borrow_mut().text = label;
+        }
+        if elements.spinner.borrow().text != SPINNER[frame] {
+            elements.spinner.borrow_mut().text = SPINNER[frame].to_owned();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_percentage() {
+        assert_eq!(TerrainProgress::new(0, 289).percent(), 0);
+        assert_eq!(TerrainProgress::new(144, 289).percent(), 49);
+        assert_eq!(TerrainProgress::new(289, 289).percent(), 100);
+        // More than expected, from a server with a larger view distance
+        assert_eq!(TerrainProgress::new(441, 289).percent(), 100);
+        assert_eq!(TerrainProgress::new(0, 0).percent(), 100);
+        assert_eq!(
+            TerrainProgress::new(300, 289).label(),
+            "289 / 289 chunks (100%)"
+        );
+    }
+}
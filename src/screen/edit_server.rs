@@ -12,15 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
-use std::fs;
-
-use crate::paths;
+use crate::format;
+use crate::servers::{SavedServers, ServerEntry};
 use crate::ui;
 use crate::{render, settings};
 
-use serde_json::{self, Value};
-
 pub struct EditServerEntry {
     elements: Option<UIElements>,
     entry_info: Option<(usize, String, String)>,
@@ -44,39 +40,17 @@ impl EditServerEntry {
     }
 
     fn save_servers(index: Option<usize>, name: &str, address: &str) {
-        let mut servers_info = match fs::File::open(paths::get_data_dir().join("servers.json")) {
-            Ok(val) => serde_json::from_reader(val).unwrap(),
-            Err(_) => {
-                let mut info = BTreeMap::default();
-                info.insert("servers".to_owned(), Value::Array(vec![]));
-                Value::Object(info.into_iter().collect())
-            }
-        };
-
-        let new_entry = {
-            let mut entry = BTreeMap::default();
-            entry.insert("name".to_owned(), Value::String(name.to_owned()));
-            entry.insert("address".to_owned(), Value::String(address.to_owned()));
-            Value::Object(entry.into_iter().collect())
+        let mut servers = SavedServers::load();
+        let entry = ServerEntry {
+            name: name.to_owned(),
+            address: address.to_owned(),
         };
-
-        {
-            let servers = servers_info
-                .as_object_mut()
-                .unwrap()
-                .get_mut("servers")
-                .unwrap()
-                .as_array_mut()
-                .unwrap();
-            if let Some(index) = index {
-                *servers.get_mut(index).unwrap() = new_entry;
-            } else {
-                servers.push(new_entry);
-            }
+        if let Some(index) = index {
+            servers.edit(index, entry);
+        } else {
+            servers.add(entry);
         }
-
-        let mut out = fs::File::create(paths::get_data_dir().join("servers.json")).unwrap();
-        serde_json::to_writer_pretty(&mut out, &servers_info).unwrap();
+        servers.save();
     }
 }
 
@@ -119,7 +93,7 @@ impl super::Screen for EditServerEntry {
         {
             let mut done = done.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Done")
+                .text(format::translate_or("gui.done", "Done"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *done);
             done.add_text(txt);
@@ -150,7 +124,7 @@ impl super::Screen for EditServerEntry {
         {
             let mut cancel = cancel.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Cancel")
+                .text(format::translate_or("gui.cancel", "Cancel"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *cancel);
             cancel.add_text(txt);
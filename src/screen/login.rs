@@ -14,36 +14,51 @@
 
 use std::cell::Cell;
 use std::rc::Rc;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
 
+use log::warn;
 use rand::{self, Rng};
 
-use crate::auth;
+use crate::auth::{self, AccountType};
 use crate::console;
 use crate::protocol;
-use crate::protocol::mojang;
+use crate::protocol::{microsoft, mojang};
 use crate::render;
 use crate::settings;
 use crate::ui;
 
+/// Shown before signing in to a Microsoft account started.
+const SIGN_IN_HINT: &str = "Press Login to sign in to Microsoft in your browser";
+
 pub struct Login {
     elements: Option<UIElements>,
     vars: Rc<console::Vars>,
+    /// Whether to log in with the saved account right away, only done when the game starts.
+    auto_login: bool,
 }
 
 struct UIElements {
     logo: ui::logo::Logo,
 
+    account_type: AccountType,
+    _account_btn: ui::ButtonRef,
+    switch_account: Rc<Cell<bool>>,
     login_btn: ui::ButtonRef,
     login_btn_text: ui::TextRef,
     login_error: ui::TextRef,
-    username_txt: ui::TextBoxRef,
-    password_txt: ui::TextBoxRef,
+    /// The username and password boxes of Mojang accounts.
+    credentials: Option<(ui::TextBoxRef, ui::TextBoxRef)>,
+    /// Where to sign in with Microsoft accounts, and the code to enter there.
+    sign_in_txt: Option<(ui::TextRef, ui::TextRef)>,
     _disclaimer: ui::TextRef,
     try_login: Rc<Cell<bool>>,
     refresh: bool,
     login_res: Option<mpsc::Receiver<Result<mojang::Profile, protocol::Error>>>,
+    device_code: Option<mpsc::Receiver<microsoft::DeviceCode>>,
+    /// Set when signing in to Microsoft was cancelled, so its result isn't saved.
+    login_cancelled: Arc<AtomicBool>,
 
     profile: mojang::Profile,
 }
@@ -53,8 +68,52 @@ impl Login {
         Login {
             elements: None,
             vars,
+            auto_login: true,
+        }
+    }
+}
+
+/// Logs in with the saved Microsoft account, refreshing it if it expired. Without one, or
+/// if it can't be refreshed, the user signs in with the code sent over `device_code`,
+/// unless `sign_in` is false. Nothing is saved once `cancelled` is set.
+fn microsoft_login(
+    client_id: &str,
+    sign_in: bool,
+    device_code: mpsc::Sender<microsoft::DeviceCode>,
+    cancelled: &AtomicBool,
+) -> Result<mojang::Profile, protocol::Error> {
+    if client_id.is_empty() {
+        return Err(protocol::Error::Err(format!(
+            "Set {} to log in with a Microsoft account",
+            auth::AUTH_MICROSOFT_CLIENT_ID.name
+        )));
+    }
+    let client = microsoft::Client::new(client_id);
+    let refreshed = auth::load_microsoft_account()
+        .map(|account| account.refresh_if_expired(&client, microsoft::unix_time()));
+    let account = match refreshed {
+        Some(Ok(account)) => account,
+        Some(Err(err)) if !sign_in => return Err(err),
+        None if !sign_in => {
+            return Err(protocol::Error::Err(
+                "Not signed in to a Microsoft account".to_owned(),
+            ))
         }
+        refreshed => {
+            if let Some(Err(err)) = refreshed {
+                warn!("Failed to refresh the Microsoft account: {}", err);
+            }
+            let code = client.request_device_code()?;
+            // The screen may be gone already, signing in still saves the account
+            let _ = device_code.send(code.clone());
+            client.wait_for_login(&code)?
+        }
+    };
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(protocol::Error::Err("Signing in was cancelled".to_owned()));
     }
+    auth::save_microsoft_account(&account);
+    Ok(account.profile)
 }
 
 impl super::Screen for Login {
@@ -62,6 +121,28 @@ impl super::Screen for Login {
         let logo = ui::logo::Logo::new(renderer.resources.clone(), ui_container);
 
         let try_login = Rc::new(Cell::new(false));
+        let account_type = auth::account_type(&self.vars);
+
+        // Account type
+        let switch_account = Rc::new(Cell::new(false));
+        let account_btn = ui::ButtonBuilder::new()
+            .position(0.0, -90.0)
+            .size(400.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut btn = account_btn.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(format!("Account: {}", account_type.label()))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *btn);
+            btn.add_text(txt);
+            let sa = switch_account.clone();
+            btn.add_click_func(move |_, _| {
+                sa.set(true);
+                true
+            });
+        }
 
         // Login
         let login_btn = ui::ButtonBuilder::new()
@@ -92,34 +173,54 @@ impl super::Screen for Login {
             .alignment(ui::VAttach::Middle, ui::HAttach::Center)
             .create(ui_container);
 
-        // Username
-        let username_txt = ui::TextBoxBuilder::new()
-            .position(0.0, -20.0)
-            .size(400.0, 40.0)
-            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
-            .create(ui_container);
-        ui::TextBox::make_focusable(&username_txt, ui_container);
-        ui::TextBuilder::new()
-            .text("Username/Email:")
-            .position(0.0, -18.0)
-            .attach(&mut *username_txt.borrow_mut());
-
-        // Password
-        let password_txt = ui::TextBoxBuilder::new()
-            .position(0.0, 40.0)
-            .size(400.0, 40.0)
-            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
-            .password(true)
-            .create(ui_container);
-        ui::TextBox::make_focusable(&password_txt, ui_container);
-        ui::TextBuilder::new()
-            .text("Password:")
-            .position(0.0, -18.0)
-            .attach(&mut *password_txt.borrow_mut());
-        let tl = try_login.clone();
-        password_txt.borrow_mut().add_submit_func(move |_, _| {
-            tl.set(true);
-        });
+        let (credentials, sign_in_txt) = match account_type {
+            AccountType::Mojang => {
+                // Username
+                let username_txt = ui::TextBoxBuilder::new()
+                    .position(0.0, -20.0)
+                    .size(400.0, 40.0)
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .create(ui_container);
+                ui::TextBox::make_focusable(&username_txt, ui_container);
+                ui::TextBuilder::new()
+                    .text("Username/Email:")
+                    .position(0.0, -18.0)
+                    .attach(&mut *username_txt.borrow_mut());
+
+                // Password
+                let password_txt = ui::TextBoxBuilder::new()
+                    .position(0.0, 40.0)
+                    .size(400.0, 40.0)
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .password(true)
+                    .create(ui_container);
+                ui::TextBox::make_focusable(&password_txt, ui_container);
+                ui::TextBuilder::new()
+                    .text("Password:")
+                    .position(0.0, -18.0)
+                    .attach(&mut *password_txt.borrow_mut());
+                let tl = try_login.clone();
+                password_txt.borrow_mut().add_submit_func(move |_, _| {
+                    tl.set(true);
+                });
+                (Some((username_txt, password_txt)), None)
+            }
+            AccountType::Microsoft => {
+                // Filled in with the code to sign in with once logging in started
+                let sign_in = ui::TextBuilder::new()
+                    .text(SIGN_IN_HINT)
+                    .position(0.0, -10.0)
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .create(ui_container);
+                let code = ui::TextBuilder::new()
+                    .text("")
+                    .position(0.0, 20.0)
+                    .colour((255, 255, 85, 255))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .create(ui_container);
+                (None, Some((sign_in, code)))
+            }
+        };
 
         // Disclaimer
         let disclaimer = ui::TextBuilder::new()
@@ -134,23 +235,31 @@ impl super::Screen for Login {
             id: self.vars.get(auth::CL_UUID).clone(),
             access_token: self.vars.get(auth::AUTH_TOKEN).clone(),
         };
-        let refresh = profile.is_complete();
-        try_login.set(refresh);
+        let refresh = match account_type {
+            AccountType::Mojang => profile.is_complete(),
+            AccountType::Microsoft => auth::microsoft_account_path().exists(),
+        };
+        try_login.set(refresh && self.auto_login);
 
         self.elements = Some(UIElements {
             logo,
             profile,
+            account_type,
+            _account_btn: account_btn,
+            switch_account,
             login_btn,
             login_btn_text,
             login_error,
             try_login,
             refresh,
             login_res: None,
+            device_code: None,
+            login_cancelled: Arc::new(AtomicBool::new(false)),
 
             _disclaimer: disclaimer,
 
-            username_txt,
-            password_txt,
+            credentials,
+            sign_in_txt,
         });
     }
     fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
@@ -166,6 +275,68 @@ impl super::Screen for Login {
     ) -> Option<Box<dyn super::Screen>> {
         let elements = self.elements.as_mut().unwrap();
 
+        if elements.switch_account.get() && elements.login_res.is_none() {
+            let account_type = elements.account_type.next();
+            self.vars
+                .set(auth::AUTH_ACCOUNT_TYPE, account_type.name().to_owned());
+            return Some(Box::new(Login {
+                elements: None,
+                vars: self.vars.clone(),
+                auto_login: false,
+            }));
+        }
+        elements.switch_account.set(false);
+
+        // Signing in to Microsoft waits for the user, so it can be cancelled. The thread
+        // keeps waiting until the code expires, but doesn't save the account it signed in to.
+        if elements.try_login.get()
+            && elements.login_res.is_some()
+            && elements.account_type == AccountType::Microsoft
+        {
+            elements.try_login.set(false);
+            elements.login_res = None;
+            elements.device_code = None;
+            elements.login_cancelled.store(true, Ordering::SeqCst);
+            elements.login_btn_text.borrow_mut().text = "Login".into();
+            if let Some((sign_in, code_txt)) = elements.sign_in_txt.as_ref() {
+                sign_in.borrow_mut().text = SIGN_IN_HINT.into();
+                code_txt.borrow_mut().text = "".into();
+            }
+        }
+        if elements.try_login.get()
+            && elements.login_res.is_none()
+            && elements.account_type == AccountType::Microsoft
+        {
+            elements.try_login.set(false);
+            let (tx, rx) = mpsc::channel();
+            let (code_tx, code_rx) = mpsc::channel();
+            elements.login_res = Some(rx);
+            elements.device_code = Some(code_rx);
+            elements.login_btn_text.borrow_mut().text = "Cancel".into();
+            elements.login_error.borrow_mut().text = "".into();
+            let client_id = self.vars.get(auth::AUTH_MICROSOFT_CLIENT_ID).clone();
+            // The saved account is refreshed on its own when starting, but only signing in
+            // again when asked to
+            let sign_in = !self.auto_login;
+            self.auto_login = false;
+            let cancelled = Arc::new(AtomicBool::new(false));
+            elements.login_cancelled = cancelled.clone();
+            thread::spawn(move || {
+                let _ = tx.send(microsoft_login(&client_id, sign_in, code_tx, &cancelled));
+            });
+        }
+        if let Some(code) = elements
+            .device_code
+            .as_ref()
+            .and_then(|rx| rx.try_recv().ok())
+        {
+            if let Some((sign_in, code_txt)) = elements.sign_in_txt.as_ref() {
+                sign_in.borrow_mut().text =
+                    format!("Open {} in your browser and enter", code.verification_uri);
+                code_txt.borrow_mut().text = code.user_code;
+            }
+        }
+
         if elements.try_login.get() && elements.login_res.is_none() {
             elements.try_login.set(false);
             let (tx, rx) = mpsc::channel();
@@ -181,8 +352,9 @@ impl super::Screen for Login {
                 self.vars.set(auth::AUTH_CLIENT_TOKEN, client_token);
             }
             let client_token = self.vars.get(auth::AUTH_CLIENT_TOKEN).clone();
-            let username = elements.username_txt.borrow().input.clone();
-            let password = elements.password_txt.borrow().input.clone();
+            let (username_txt, password_txt) = elements.credentials.as_ref().unwrap();
+            let username = username_txt.borrow().input.clone();
+            let password = password_txt.borrow().input.clone();
             let refresh = elements.refresh;
             let profile = elements.profile.clone();
             thread::spawn(move || {
@@ -213,12 +385,16 @@ impl super::Screen for Login {
                     }
                     Err(err) => {
                         elements.login_error.borrow_mut().text = format!("{}", err);
+                        if let Some((_, code_txt)) = elements.sign_in_txt.as_ref() {
+                            code_txt.borrow_mut().text = "".into();
+                        }
                     }
                 }
             }
         }
         if done {
             elements.login_res = None;
+            elements.device_code = None;
         }
 
         elements.logo.tick(renderer);
@@ -16,11 +16,17 @@ mod server_list;
 pub use self::server_list::*;
 mod login;
 pub use self::login::*;
+mod controls;
+pub use self::controls::*;
 
+pub mod chat;
 pub mod connecting;
 pub mod delete_server;
+pub mod direct_connect;
+pub mod downloading_terrain;
 pub mod edit_server;
 
+pub mod resource_pack_prompt;
 pub mod respawn;
 pub mod settings_menu;
 
@@ -32,6 +38,7 @@ use crate::ui;
 use crate::ui::Container;
 use parking_lot::RwLock;
 use std::sync::Arc;
+use winit::event::VirtualKeyCode;
 
 pub trait Screen {
     // Called once
@@ -53,6 +60,20 @@ pub trait Screen {
     // Events
     fn on_scroll(&mut self, _x: f64, _y: f64) {}
 
+    /// Called when a key is pressed, returns whether the screen consumed it.
+    fn on_key_press(&mut self, _key: VirtualKeyCode) -> bool {
+        false
+    }
+
+    /// Called when the left or right mouse button is pressed, with the cursor relative to
+    /// the center of the screen in UI units. Returns whether the screen consumed it.
+    fn on_click(&mut self, _right: bool, _shift: bool, _x: f64, _y: f64) -> bool {
+        false
+    }
+
+    /// Called when the mouse moves, relative to the center of the screen in UI units.
+    fn on_cursor_moved(&mut self, _x: f64, _y: f64) {}
+
     fn on_resize(
         &mut self,
         _width: u32,
@@ -67,7 +88,13 @@ pub trait Screen {
     }
 }
 
+/// Identifies a screen added to the `ScreenSystem`, to remove it again wherever it
+/// ended up in the stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScreenId(u64);
+
 struct ScreenInfo {
+    id: ScreenId,
     screen: Box<dyn Screen>,
     init: bool,
     active: bool,
@@ -79,6 +106,9 @@ struct ScreenInfo {
 pub struct ScreenSystem {
     screens: Vec<ScreenInfo>,
     remove_queue: Vec<ScreenInfo>,
+    next_id: u64,
+    /// The key a screen consumed the press of, whose release is consumed too.
+    consumed_key: Option<VirtualKeyCode>,
 }
 
 impl ScreenSystem {
@@ -86,14 +116,18 @@ impl ScreenSystem {
         Default::default()
     }
 
-    pub fn add_screen(&mut self, screen: Box<dyn Screen>) {
+    pub fn add_screen(&mut self, screen: Box<dyn Screen>) -> ScreenId {
+        let id = ScreenId(self.next_id);
+        self.next_id += 1;
         self.screens.push(ScreenInfo {
+            id,
             screen,
             init: false,
             active: false,
             last_width: -1,
             last_height: -1,
         });
+        id
     }
 
     pub fn pop_screen(&mut self) {
@@ -102,9 +136,27 @@ impl ScreenSystem {
         }
     }
 
-    pub fn replace_screen(&mut self, screen: Box<dyn Screen>) {
+    /// Removes the screen `id` even if other screens were opened over it, returns whether
+    /// it was still there.
+    pub fn remove_screen(&mut self, id: ScreenId) -> bool {
+        match self.screens.iter().position(|screen| screen.id == id) {
+            Some(index) => {
+                let screen = self.screens.remove(index);
+                self.remove_queue.push(screen);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes every screen.
+    pub fn clear(&mut self) {
+        self.remove_queue.append(&mut self.screens);
+    }
+
+    pub fn replace_screen(&mut self, screen: Box<dyn Screen>) -> ScreenId {
         self.pop_screen();
-        self.add_screen(screen);
+        self.add_screen(screen)
     }
 
     pub fn is_current_closable(&self) -> bool {
@@ -181,4 +233,117 @@ impl ScreenSystem {
         let current = self.screens.last_mut().unwrap();
         current.screen.on_scroll(x, y);
     }
+
+    pub fn on_key_press(&mut self, key: VirtualKeyCode) -> bool {
+        if self.screens.is_empty() {
+            return false;
+        }
+        let current = self.screens.last_mut().unwrap();
+        let consumed = current.screen.on_key_press(key);
+        if consumed {
+            self.consumed_key = Some(key);
+        }
+        consumed
+    }
+
+    /// Called when a key is released, returns whether it's the release of a press a screen
+    /// consumed. Otherwise e.g. the Escape cancelling a rebind would also close the screen.
+    pub fn on_key_release(&mut self, key: VirtualKeyCode) -> bool {
+        if self.consumed_key == Some(key) {
+            self.consumed_key = None;
+            return true;
+        }
+        false
+    }
+
+    pub fn on_click(&mut self, right: bool, shift: bool, x: f64, y: f64) -> bool {
+        if self.screens.is_empty() {
+            return false;
+        }
+        let current = self.screens.last_mut().unwrap();
+        current.screen.on_click(right, shift, x, y)
+    }
+
+    pub fn on_cursor_moved(&mut self, x: f64, y: f64) {
+        if self.screens.is_empty() {
+            return;
+        }
+        let current = self.screens.last_mut().unwrap();
+        current.screen.on_cursor_moved(x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestScreen {
+        closable: bool,
+        consumes_keys: bool,
+    }
+
+    impl Screen for TestScreen {
+        fn on_active(&mut self, _renderer: &mut render::Renderer, _ui: &mut ui::Container) {}
+        fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui: &mut ui::Container) {}
+
+        fn tick(
+            &mut self,
+            _delta: f64,
+            _renderer: &mut render::Renderer,
+            _ui_container: &mut ui::Container,
+        ) -> Option<Box<dyn Screen>> {
+            None
+        }
+
+        fn on_key_press(&mut self, _key: VirtualKeyCode) -> bool {
+            self.consumes_keys
+        }
+
+        fn is_closable(&self) -> bool {
+            self.closable
+        }
+    }
+
+    fn test_screen(closable: bool) -> Box<dyn Screen> {
+        Box::new(TestScreen {
+            closable,
+            consumes_keys: false,
+        })
+    }
+
+    #[test]
+    fn screens_are_removed_under_other_screens() {
+        let mut screen_sys = ScreenSystem::new();
+        screen_sys.add_screen(test_screen(false));
+        let loading = screen_sys.add_screen(test_screen(false));
+        screen_sys.add_screen(test_screen(true));
+
+        assert!(screen_sys.remove_screen(loading));
+        assert!(!screen_sys.remove_screen(loading));
+        // The screen opened over it stays on top
+        assert_eq!(screen_sys.screens.len(), 2);
+        assert!(screen_sys.is_current_closable());
+
+        screen_sys.clear();
+        assert!(screen_sys.screens.is_empty());
+        assert_eq!(screen_sys.remove_queue.len(), 3);
+    }
+
+    #[test]
+    fn consumed_presses_consume_their_release() {
+        let mut screen_sys = ScreenSystem::new();
+        screen_sys.add_screen(Box::new(TestScreen {
+            closable: true,
+            consumes_keys: true,
+        }));
+        assert!(screen_sys.on_key_press(VirtualKeyCode::Escape));
+        assert!(screen_sys.on_key_release(VirtualKeyCode::Escape));
+        // The next release is the screen's to handle again
+        assert!(!screen_sys.on_key_release(VirtualKeyCode::Escape));
+
+        screen_sys.pop_screen();
+        screen_sys.add_screen(test_screen(true));
+        assert!(!screen_sys.on_key_press(VirtualKeyCode::Escape));
+        assert!(!screen_sys.on_key_release(VirtualKeyCode::Escape));
+    }
 }
@@ -0,0 +1,122 @@
+use crate::format;
+use crate::render;
+use crate::server::resource_pack::Request;
+use crate::ui;
+
+/// Asks whether to use the resource pack the server sent.
+pub struct ResourcePackPrompt {
+    request: Request,
+    elements: Option<UIElements>,
+}
+
+struct UIElements {
+    _background: ui::ImageRef,
+    _prompt: ui::TextRef,
+    _url: ui::TextRef,
+    _accept: ui::ButtonRef,
+    _decline: ui::ButtonRef,
+}
+
+impl ResourcePackPrompt {
+    pub fn new(request: Request) -> ResourcePackPrompt {
+        ResourcePackPrompt {
+            request,
+            elements: None,
+        }
+    }
+}
+
+impl super::Screen for ResourcePackPrompt {
+    fn on_active(&mut self, renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .position(0.0, 0.0)
+            .size(renderer.width as f64, renderer.height as f64)
+            .colour((0, 0, 0, 100))
+            .create(ui_container);
+
+        let prompt = ui::TextBuilder::new()
+            .text(format::translate_or(
+                "multiplayer.texturePrompt.line1",
+                "The server recommends the use of a custom resource pack.",
+            ))
+            .position(0.0, -40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        let url = ui::TextBuilder::new()
+            .text(self.request.url.clone())
+            .position(0.0, -10.0)
+            .scale_x(0.5)
+            .scale_y(0.5)
+            .colour((170, 170, 170, 255))
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        let accept = ui::ButtonBuilder::new()
+            .position(110.0, 40.0)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut accept = accept.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(format::translate_or("gui.yes", "Yes"))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *accept);
+            accept.add_text(txt);
+            let request = self.request.clone();
+            accept.add_click_func(move |_, game| {
+                if let Some(server) = game.server.as_ref() {
+                    server.accept_resource_pack(request.clone());
+                }
+                game.screen_sys.pop_screen();
+                game.focused = true;
+                true
+            });
+        }
+
+        let decline = ui::ButtonBuilder::new()
+            .position(-110.0, 40.0)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut decline = decline.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(format::translate_or("gui.no", "No"))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *decline);
+            decline.add_text(txt);
+            let request = self.request.clone();
+            decline.add_click_func(move |_, game| {
+                if let Some(server) = game.server.as_ref() {
+                    server.decline_resource_pack(&request);
+                }
+                game.screen_sys.pop_screen();
+                game.focused = true;
+                true
+            });
+        }
+
+        self.elements = Some(UIElements {
+            _background: background,
+            _prompt: prompt,
+            _url: url,
+            _accept: accept,
+            _decline: decline,
+        });
+    }
+
+    fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
+        self.elements = None;
+    }
+
+    fn tick(
+        &mut self,
+        _delta: f64,
+        _renderer: &mut render::Renderer,
+        _ui_container: &mut ui::Container,
+    ) -> Option<Box<dyn super::Screen>> {
+        None
+    }
+}
@@ -1,3 +1,4 @@
+use crate::format;
 use crate::render::hud::Hud;
 use crate::render::Renderer;
 use crate::screen::Screen;
@@ -39,7 +40,7 @@ impl super::Screen for Respawn {
             .colour((104, 0, 0, 100))
             .create(ui_container);
         let text = ui::TextBuilder::new()
-            .text("You died!")
+            .text(format::translate_or("deathScreen.title", "You died!"))
             .position(0.0, -(icon_scale * 10.0 * 3.0))
             .colour((255, 255, 255, 255))
             .scale_y(icon_scale)
@@ -62,7 +63,7 @@ impl super::Screen for Respawn {
         {
             let mut respawn_button = respawn_button.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Respawn")
+                .text(format::translate_or("deathScreen.respawn", "Respawn"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *respawn_button);
             respawn_button.add_text(txt);
@@ -92,7 +93,10 @@ impl super::Screen for Respawn {
         {
             let mut main_menu_button = main_menu_button.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Title screen")
+                .text(format::translate_or(
+                    "deathScreen.titleScreen",
+                    "Title screen",
+                ))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *main_menu_button);
             main_menu_button.add_text(txt);
@@ -13,16 +13,17 @@
 // limitations under the License.
 
 use std::cell::RefCell;
-use std::fs;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::thread;
 
 use crate::format;
 use crate::format::{Component, TextComponent};
-use crate::paths;
+use crate::lan::{LanScanner, LanServer};
 use crate::protocol;
 use crate::render;
+use crate::servers::{SavedServers, ServerEntry};
 use crate::settings;
 use crate::ui;
 
@@ -30,17 +31,39 @@ use crate::render::hud::{Hud, HudContext};
 use crate::render::Renderer;
 use crate::ui::Container;
 use crossbeam_channel::unbounded;
-use crossbeam_channel::{Receiver, TryRecvError};
-use instant::Duration;
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use instant::{Duration, Instant};
+use log::warn;
 use parking_lot::RwLock;
 use rand::Rng;
 
+const FAVICON_PREFIX: &str = "data:image/png;base64,";
+/// How many servers are pinged at the same time.
+const MAX_CONCURRENT_PINGS: usize = 8;
+/// How often the worlds found on the LAN are checked for changes.
+const LAN_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
 pub struct ServerList {
     elements: Option<UIElements>,
     disconnect_reason: Option<Component>,
 
     needs_reload: Rc<RefCell<bool>>,
     background_image: String,
+    /// Server icons by address, kept across refreshes of the list.
+    favicons: HashMap<String, Favicon>,
+    lan_scanner: Option<LanScanner>,
+    /// The worlds found on the LAN, listed after the saved servers.
+    lan_servers: Vec<LanServer>,
+    lan_checked: Instant,
+}
+
+/// A server icon uploaded as a dynamic texture.
+struct Favicon {
+    /// The data URI the icon was decoded from.
+    data: String,
+    /// The name the dynamic texture was added under.
+    name: String,
+    texture: String,
 }
 
 struct UIElements {
@@ -48,6 +71,7 @@ struct UIElements {
     servers: Vec<Server>,
 
     _add_btn: ui::ButtonRef,
+    _direct_btn: ui::ButtonRef,
     _refresh_btn: ui::ButtonRef,
     _options_btn: ui::ButtonRef,
     _disclaimer: ui::TextRef,
@@ -57,6 +81,9 @@ struct UIElements {
 }
 
 struct Server {
+    address: String,
+    /// Whether it's a world found on the LAN rather than a saved server.
+    lan: bool,
     back: ui::ImageRef,
     offset: f64,
     y: f64,
@@ -67,10 +94,18 @@ struct Server {
     version: ui::FormattedRef,
 
     icon: ui::ImageRef,
-    icon_texture: Option<String>,
 
-    done_ping: bool,
+    ping_state: PingState,
     recv: Receiver<PingInfo>,
+    /// The message of the day and the list of Forge mods shown instead of it on hover.
+    mods_tooltip: Rc<RefCell<Option<(Component, Component)>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PingState {
+    Pinging,
+    Ok,
+    Failed,
 }
 
 struct PingInfo {
@@ -82,7 +117,15 @@ struct PingInfo {
     protocol_version: i32,
     protocol_name: String,
     forge_mods: Vec<crate::protocol::forge::ForgeMod>,
-    favicon: Option<image::DynamicImage>,
+    favicon: Option<String>,
+}
+
+/// The entry a LAN world is listed with, named by its message of the day.
+fn lan_entry(server: &LanServer) -> ServerEntry {
+    ServerEntry {
+        name: server.motd.clone(),
+        address: server.address.clone(),
+    }
 }
 
 impl Server {
@@ -102,120 +145,277 @@ impl ServerList {
             disconnect_reason,
             needs_reload: Rc::new(RefCell::new(false)),
             background_image,
+            favicons: HashMap::new(),
+            lan_scanner: None,
+            lan_servers: vec![],
+            lan_checked: Instant::now(),
+        }
+    }
+
+    /// Returns whether worlds were opened or closed on the LAN since the last check.
+    fn check_lan_servers(&mut self) -> bool {
+        let now = Instant::now();
+        if now.duration_since(self.lan_checked) < LAN_REFRESH_INTERVAL {
+            return false;
+        }
+        self.lan_checked = now;
+        if let Some(scanner) = &self.lan_scanner {
+            let found = scanner.servers();
+            if found != self.lan_servers {
+                self.lan_servers = found;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Decodes a `data:image/png;base64,` favicon, returning `None` if it's malformed.
+    fn decode_favicon(data: &str) -> Option<image::DynamicImage> {
+        let data_base64 = match data.strip_prefix(FAVICON_PREFIX) {
+            Some(data_base64) => data_base64,
+            None => {
+                warn!("Unsupported server favicon format");
+                return None;
+            }
+        };
+        let data_base64: String = data_base64.chars().filter(|c| !c.is_whitespace()).collect();
+        match base64::decode(data_base64)
+            .map_err(|err| err.to_string())
+            .and_then(|data| image::load_from_memory(&data).map_err(|err| err.to_string()))
+        {
+            Ok(favicon) => Some(favicon),
+            Err(err) => {
+                warn!("Failed to decode server favicon: {}", err);
+                None
+            }
         }
     }
 
+    /// Returns the texture of the favicon of the server at `address`, decoding and uploading
+    /// it only if it changed since it was last seen.
+    fn favicon_texture(
+        favicons: &mut HashMap<String, Favicon>,
+        renderer: &mut render::Renderer,
+        address: &str,
+        data: String,
+    ) -> Option<String> {
+        if let Some(favicon) = favicons.get(address) {
+            if favicon.data == data {
+                return Some(favicon.texture.clone());
+            }
+        }
+        let image = Self::decode_favicon(&data)?;
+        let name: String = std::iter::repeat(())
+            .map(|()| rand::thread_rng().sample(&rand::distributions::Alphanumeric) as char)
+            .take(30)
+            .collect();
+        let mut tex = renderer.get_textures_ref().write();
+        if let Some(old) = favicons.remove(address) {
+            tex.remove_dynamic(&old.name);
+        }
+        let texture = tex.put_dynamic(&name, image).name;
+        favicons.insert(
+            address.to_owned(),
+            Favicon {
+                data,
+                name,
+                texture: texture.clone(),
+            },
+        );
+        Some(texture)
+    }
+
     fn reload_server_list(
         &mut self,
         renderer: &mut render::Renderer,
         ui_container: &mut ui::Container,
     ) {
-        let elements = self.elements.as_mut().unwrap();
         *self.needs_reload.borrow_mut() = false;
-        {
-            // Clean up previous list icons.
-            let mut tex = renderer.get_textures_ref().write();
-            for server in &mut elements.servers {
-                if let Some(ref icon) = server.icon_texture {
-                    tex.remove_dynamic(icon);
-                }
-            }
-        }
-        elements.servers.clear();
-
-        let file = match fs::File::open(paths::get_data_dir().join("servers.json")) {
-            Ok(val) => val,
-            Err(_) => return,
-        };
-        let servers_info: serde_json::Value = serde_json::from_reader(file).unwrap();
-        let servers = servers_info.get("servers").unwrap().as_array().unwrap();
+        self.elements.as_mut().unwrap().servers.clear();
+
+        // Saved servers have their index in the list, LAN worlds are at the end
+        let servers = SavedServers::load()
+            .entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| (Some(index), entry))
+            .chain(
+                self.lan_servers
+                    .iter()
+                    .map(|server| (None, lan_entry(server))),
+            )
+            .collect::<Vec<_>>();
+        let listed = servers
+            .iter()
+            .map(|(_, entry)| entry.address.clone())
+            .collect::<Vec<_>>();
+        self.remove_unlisted_favicons(renderer, &listed);
         let mut offset = 0.0;
+        let mut pings = vec![];
+        for (index, entry) in servers {
+            pings.push(self.add_server(index, entry, offset, ui_container));
+            offset += 1.0;
+        }
+        // Don't block the main thread whilst pinging the servers
+        Self::spawn_pings(pings);
+    }
 
-        for (index, svr) in servers.iter().enumerate() {
-            let name = svr.get("name").unwrap().as_str().unwrap().to_owned();
-            let address = svr.get("address").unwrap().as_str().unwrap().to_owned();
-
-            // Everything is attached to this
-            let back = ui::ImageBuilder::new()
-                .texture("leafish:solid")
-                .position(0.0, offset * 100.0)
-                .size(700.0, 100.0)
-                .colour((0, 0, 0, 100))
-                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
-                .create(ui_container);
+    /// Replaces the LAN worlds at the end of the list with the ones found now, pinging
+    /// only the new ones.
+    fn reload_lan_servers(
+        &mut self,
+        renderer: &mut render::Renderer,
+        ui_container: &mut ui::Container,
+    ) {
+        let lan_servers = &self.lan_servers;
+        let elements = self.elements.as_mut().unwrap();
+        elements.servers.retain(|server| {
+            !server.lan || lan_servers.iter().any(|lan| lan.address == server.address)
+        });
+        // Closes the gaps left by worlds that went away
+        let first = elements.servers.first().map_or(0.0, |server| server.offset);
+        for (i, server) in elements.servers.iter_mut().enumerate() {
+            server.offset = first + i as f64;
+            server.update_position();
+        }
+        let mut offset = first + elements.servers.len() as f64;
+
+        let listed = elements
+            .servers
+            .iter()
+            .map(|server| server.address.clone())
+            .collect::<Vec<_>>();
+        let new = self
+            .lan_servers
+            .iter()
+            .filter(|lan| !listed.contains(&lan.address))
+            .map(lan_entry)
+            .collect::<Vec<_>>();
+        self.remove_unlisted_favicons(renderer, &listed);
+        let mut pings = vec![];
+        for entry in new {
+            pings.push(self.add_server(None, entry, offset, ui_container));
+            offset += 1.0;
+        }
+        Self::spawn_pings(pings);
+    }
 
-            let (send, recv) = unbounded();
-            // Make whole entry interactable
-            {
-                let mut backr = back.borrow_mut();
-                let address = address.clone();
-                backr.add_hover_func(move |this, over, _| {
-                    this.colour.3 = if over { 200 } else { 100 };
-                    false
-                });
-                backr.add_click_func(move |_, game| {
-                    game.screen_sys
-                        .replace_screen(Box::new(super::connecting::Connecting::new(&address)));
-                    let hud_context = Arc::new(RwLock::new(HudContext::new()));
-                    let result = game.connect_to(&address, hud_context.clone());
-                    game.screen_sys.pop_screen();
-                    if let Err(error) = result {
-                        game.screen_sys.add_screen(Box::new(ServerList::new(
-                            Some(Component::Text(TextComponent::new(&*error.to_string()))),
-                            game.vars.get(settings::BACKGROUND_IMAGE).clone(),
-                        )));
-                    } else {
-                        game.screen_sys.add_screen(Box::new(Hud::new(hud_context)));
-                        game.focused = true;
-                    }
-                    true
-                });
+    /// Cleans up the icons of servers that were removed from the list.
+    fn remove_unlisted_favicons(&mut self, renderer: &mut render::Renderer, listed: &[String]) {
+        let mut tex = renderer.get_textures_ref().write();
+        self.favicons.retain(|address, favicon| {
+            let listed = listed.contains(address);
+            if !listed {
+                tex.remove_dynamic(&favicon.name);
             }
+            listed
+        });
+    }
 
-            // Server name
-            ui::TextBuilder::new()
-                .text(name.clone())
-                .position(100.0, 5.0)
-                .attach(&mut *back.borrow_mut());
+    /// Adds an entry at `offset`, a saved server if it has an `index` and otherwise a LAN
+    /// world. Returns where to send the entry's ping.
+    fn add_server(
+        &mut self,
+        index: Option<usize>,
+        ServerEntry { name, address }: ServerEntry,
+        offset: f64,
+        ui_container: &mut ui::Container,
+    ) -> (String, Sender<PingInfo>) {
+        // Everything is attached to this
+        let back = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .position(0.0, offset * 100.0)
+            .size(700.0, 100.0)
+            .colour((0, 0, 0, 100))
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
 
-            // Server icon
-            let icon = ui::ImageBuilder::new()
-                .texture("misc/unknown_server")
-                .position(5.0, 5.0)
-                .size(90.0, 90.0)
-                .attach(&mut *back.borrow_mut());
+        let (send, recv) = unbounded();
+        // Make whole entry interactable
+        {
+            let mut backr = back.borrow_mut();
+            let address = address.clone();
+            backr.add_hover_func(move |this, over, _| {
+                this.colour.3 = if over { 200 } else { 100 };
+                false
+            });
+            backr.add_click_func(move |_, game| {
+                game.screen_sys
+                    .replace_screen(Box::new(super::connecting::Connecting::new(&address)));
+                let hud_context = Arc::new(RwLock::new(HudContext::new()));
+                let result = game.connect_to(&address, hud_context.clone());
+                game.screen_sys.pop_screen();
+                if let Err(error) = result {
+                    game.screen_sys.add_screen(Box::new(ServerList::new(
+                        Some(error.to_component()),
+                        game.vars.get(settings::BACKGROUND_IMAGE).clone(),
+                    )));
+                } else {
+                    game.screen_sys.add_screen(Box::new(Hud::new(hud_context)));
+                    game.focused = true;
+                }
+                true
+            });
+        }
 
-            // Ping indicator
-            let ping = ui::ImageBuilder::new()
-                .texture("gui/icons")
-                .position(5.0, 5.0)
-                .size(20.0, 16.0)
-                .texture_coords((0.0, 56.0 / 256.0, 10.0 / 256.0, 8.0 / 256.0))
-                .alignment(ui::VAttach::Top, ui::HAttach::Right)
-                .attach(&mut *back.borrow_mut());
+        // Server name
+        ui::TextBuilder::new()
+            .text(name.clone())
+            .position(100.0, 5.0)
+            .attach(&mut *back.borrow_mut());
 
-            // Player count
-            let players = ui::TextBuilder::new()
-                .text("???")
-                .position(30.0, 5.0)
-                .alignment(ui::VAttach::Top, ui::HAttach::Right)
-                .attach(&mut *back.borrow_mut());
+        // Server icon
+        let icon = ui::ImageBuilder::new()
+            .texture("misc/unknown_server")
+            .position(5.0, 5.0)
+            .size(90.0, 90.0)
+            .attach(&mut *back.borrow_mut());
 
-            // Server's message of the day
-            let motd = ui::FormattedBuilder::new()
-                .text(Component::Text(TextComponent::new("Connecting...")))
-                .position(100.0, 23.0)
-                .max_width(700.0 - (90.0 + 10.0 + 5.0))
-                .attach(&mut *back.borrow_mut());
+        // Ping indicator
+        let ping = ui::ImageBuilder::new()
+            .texture("gui/icons")
+            .position(5.0, 5.0)
+            .size(20.0, 16.0)
+            .texture_coords((0.0, 56.0 / 256.0, 10.0 / 256.0, 8.0 / 256.0))
+            .alignment(ui::VAttach::Top, ui::HAttach::Right)
+            .attach(&mut *back.borrow_mut());
+
+        // Player count
+        let players = ui::TextBuilder::new()
+            .text("???")
+            .position(30.0, 5.0)
+            .alignment(ui::VAttach::Top, ui::HAttach::Right)
+            .attach(&mut *back.borrow_mut());
+
+        // Server's message of the day
+        let motd = ui::FormattedBuilder::new()
+            .text(Component::Text(TextComponent::new("Connecting...")))
+            .position(100.0, 23.0)
+            .max_width(700.0 - (90.0 + 10.0 + 5.0))
+            .attach(&mut *back.borrow_mut());
+
+        let mods_tooltip: Rc<RefCell<Option<(Component, Component)>>> = Rc::new(RefCell::new(None));
+        {
+            let mods_tooltip = mods_tooltip.clone();
+            let motd = motd.clone();
+            back.borrow_mut().add_hover_func(move |_, over, _| {
+                if let Some((message, mods)) = &*mods_tooltip.borrow() {
+                    motd.borrow_mut()
+                        .set_text(if over { mods.clone() } else { message.clone() });
+                }
+                false
+            });
+        }
 
-            // Version information
-            let version = ui::FormattedBuilder::new()
-                .text(Component::Text(TextComponent::new("")))
-                .position(100.0, 5.0)
-                .max_width(700.0 - (90.0 + 10.0 + 5.0))
-                .alignment(ui::VAttach::Bottom, ui::HAttach::Left)
-                .attach(&mut *back.borrow_mut());
+        // Version information
+        let version = ui::FormattedBuilder::new()
+            .text(Component::Text(TextComponent::new("")))
+            .position(100.0, 5.0)
+            .max_width(700.0 - (90.0 + 10.0 + 5.0))
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Left)
+            .attach(&mut *back.borrow_mut());
 
+        if let Some(index) = index {
             // Delete entry button
             let delete_entry = ui::ButtonBuilder::new()
                 .position(0.0, 0.0)
@@ -229,7 +429,6 @@ impl ServerList {
                     .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                     .attach(&mut *btn);
                 btn.add_text(txt);
-                let index = index;
                 let sname = name.clone();
                 let saddr = address.clone();
                 btn.add_click_func(move |_, game| {
@@ -258,7 +457,6 @@ impl ServerList {
                     .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                     .attach(&mut *btn);
                 btn.add_text(txt);
-                let index = index;
                 let sname = name.clone();
                 let saddr = address.clone();
                 btn.add_click_func(move |_, game| {
@@ -273,75 +471,121 @@ impl ServerList {
                 })
             }
 
-            let mut server = Server {
-                back,
-                offset,
-                y: 0.0,
-                done_ping: false,
-                recv,
-
-                motd,
-                ping,
-                players,
-                version,
-
-                icon,
-                icon_texture: None,
-            };
-            server.update_position();
-            elements.servers.push(server);
-            offset += 1.0;
+            // Move entry up and down buttons
+            for (i, (label, up)) in [("^", true), ("v", false)].iter().enumerate() {
+                let move_entry = ui::ButtonBuilder::new()
+                    .position(50.0 + i as f64 * 25.0, 0.0)
+                    .size(25.0, 25.0)
+                    .alignment(ui::VAttach::Bottom, ui::HAttach::Right)
+                    .attach(&mut *back.borrow_mut());
+                let mut btn = move_entry.borrow_mut();
+                let txt = ui::TextBuilder::new()
+                    .text(*label)
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .attach(&mut *btn);
+                btn.add_text(txt);
+                let up = *up;
+                let nr = self.needs_reload.clone();
+                btn.add_click_func(move |_, _| {
+                    let mut servers = SavedServers::load();
+                    if up {
+                        servers.move_up(index);
+                    } else {
+                        servers.move_down(index);
+                    }
+                    servers.save();
+                    *nr.borrow_mut() = true;
+                    true
+                });
+            }
+        } else {
+            ui::TextBuilder::new()
+                .text("LAN")
+                .position(5.0, 5.0)
+                .colour((255, 255, 85, 255))
+                .alignment(ui::VAttach::Bottom, ui::HAttach::Right)
+                .attach(&mut *back.borrow_mut());
+        }
 
-            // Don't block the main thread whilst pinging the server
+        let mut server = Server {
+            address: address.clone(),
+            lan: index.is_none(),
+            back,
+            offset,
+            y: 0.0,
+            ping_state: PingState::Pinging,
+            recv,
+
+            motd,
+            ping,
+            players,
+            version,
+
+            icon,
+            mods_tooltip,
+        };
+        server.update_position();
+        self.elements.as_mut().unwrap().servers.push(server);
+        (address, send)
+    }
+
+    /// Pings the servers on a few background threads, sending each result back as soon
+    /// as it's known.
+    fn spawn_pings(pings: Vec<(String, Sender<PingInfo>)>) {
+        let workers = pings.len().min(MAX_CONCURRENT_PINGS);
+        let (queue, jobs) = unbounded();
+        for ping in pings {
+            queue.send(ping).unwrap();
+        }
+        drop(queue);
+        for _ in 0..workers {
+            let jobs = jobs.clone();
             thread::spawn(move || {
-                match protocol::Conn::new(&address, protocol::SUPPORTED_PROTOCOLS[0])
-                    .and_then(|conn| conn.do_status())
-                {
-                    Ok(res) => {
-                        let mut desc = res.0.description;
-                        format::convert_legacy(&mut desc);
-                        let favicon = if let Some(icon) = res.0.favicon {
-                            let data_base64 = &icon["data:image/png;base64,".len()..];
-                            let data_base64: String =
-                                data_base64.chars().filter(|c| !c.is_whitespace()).collect();
-                            let data = base64::decode(data_base64).unwrap();
-                            Some(image::load_from_memory(&data).unwrap())
-                        } else {
-                            None
-                        };
-                        drop(send.send(PingInfo {
-                            motd: desc,
-                            ping: res.1,
-                            exists: true,
-                            online: res.0.players.online,
-                            max: res.0.players.max,
-                            protocol_version: res.0.version.protocol,
-                            protocol_name: res.0.version.name,
-                            forge_mods: res.0.forge_mods,
-                            favicon,
-                        }));
-                    }
-                    Err(err) => {
-                        let e = format!("{}", err);
-                        let mut msg = TextComponent::new(&e);
-                        msg.modifier.color = Some(format::Color::Red);
-                        let _ = send.send(PingInfo {
-                            motd: Component::Text(msg),
-                            ping: Duration::new(99999, 0),
-                            exists: false,
-                            online: 0,
-                            max: 0,
-                            protocol_version: 0,
-                            protocol_name: "".to_owned(),
-                            forge_mods: vec![],
-                            favicon: None,
-                        });
-                    }
+                for (address, send) in jobs.iter() {
+                    drop(send.send(Self::ping(&address)));
                 }
             });
         }
     }
 
+    fn ping(address: &str) -> PingInfo {
+        match protocol::Conn::new(address, protocol::SUPPORTED_PROTOCOLS[0])
+            .and_then(|conn| conn.do_status())
+        {
+            Ok(res) => {
+                let mut desc = res.0.description;
+                format::convert_legacy(&mut desc);
+                PingInfo {
+                    motd: desc,
+                    ping: res.1,
+                    exists: true,
+                    online: res.0.players.online,
+                    max: res.0.players.max,
+                    protocol_version: res.0.version.protocol,
+                    protocol_name: res.0.version.name,
+                    forge_mods: res.0.forge_mods,
+                    favicon: res.0.favicon,
+                }
+            }
+            Err(err) => {
+                let e = format!("{}", err);
+                let mut msg = TextComponent::new(&e);
+                msg.modifier.color = Some(format::Color::Red);
+                PingInfo {
+                    motd: Component::Text(msg),
+                    ping: Duration::new(99999, 0),
+                    exists: false,
+                    online: 0,
+                    max: 0,
+                    protocol_version: 0,
+                    protocol_name: "".to_owned(),
+                    forge_mods: vec![],
+                    favicon: None,
+                }
+            }
+        }
+    }
+
     fn init_list(&mut self, renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
         let logo = ui::logo::Logo::new(renderer.resources.clone(), ui_container);
 
@@ -355,7 +599,7 @@ impl ServerList {
         {
             let mut refresh = refresh.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Refresh")
+                .text(format::translate_or("selectServer.refresh", "Refresh"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *refresh);
             refresh.add_text(txt);
@@ -387,6 +631,28 @@ impl ServerList {
             })
         }
 
+        // Connect to an address without saving it
+        let direct = ui::ButtonBuilder::new()
+            .position(100.0, -50.0 - 15.0)
+            .size(100.0, 30.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .draw_index(2)
+            .create(ui_container);
+        {
+            let mut direct = direct.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Direct")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *direct);
+            direct.add_text(txt);
+            direct.add_click_func(move |_, game| {
+                game.screen_sys.replace_screen(Box::new(
+                    super::direct_connect::DirectConnect::new(game.vars.clone()),
+                ));
+                true
+            })
+        }
+
         // Options menu
         let options = ui::ButtonBuilder::new()
             .position(5.0, 25.0)
@@ -471,6 +737,7 @@ impl ServerList {
             servers: vec![],
 
             _add_btn: add,
+            _direct_btn: direct,
             _refresh_btn: refresh,
             _options_btn: options,
             _disclaimer: disclaimer,
@@ -483,6 +750,9 @@ impl ServerList {
 
 impl super::Screen for ServerList {
     fn on_active(&mut self, renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        if self.lan_scanner.is_none() {
+            self.lan_scanner = Some(LanScanner::start());
+        }
         self.init_list(renderer, ui_container);
         *self.needs_reload.borrow_mut() = true;
     }
@@ -490,12 +760,9 @@ impl super::Screen for ServerList {
     fn on_deactive(&mut self, renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
         // Clean up
         {
-            let elements = self.elements.as_mut().unwrap();
             let mut tex = renderer.get_textures_ref().write();
-            for server in &mut elements.servers {
-                if let Some(ref icon) = server.icon_texture {
-                    tex.remove_dynamic(icon);
-                }
+            for (_, favicon) in self.favicons.drain() {
+                tex.remove_dynamic(&favicon.name);
             }
         }
         self.elements = None
@@ -507,8 +774,11 @@ impl super::Screen for ServerList {
         renderer: &mut render::Renderer,
         ui_container: &mut ui::Container,
     ) -> Option<Box<dyn super::Screen>> {
+        let lan_changed = self.check_lan_servers();
         if *self.needs_reload.borrow() {
             self.reload_server_list(renderer, ui_container);
+        } else if lan_changed {
+            self.reload_lan_servers(renderer, ui_container);
         }
         let elements = self.elements.as_mut().unwrap();
 
@@ -529,10 +799,25 @@ impl super::Screen for ServerList {
 
             // Keep checking to see if the server has finished being
             // pinged
-            if !s.done_ping {
+            if s.ping_state == PingState::Pinging {
                 match s.recv.try_recv() {
                     Ok(res) => {
-                        s.done_ping = true;
+                        s.ping_state = if res.exists {
+                            PingState::Ok
+                        } else {
+                            PingState::Failed
+                        };
+                        if !res.forge_mods.is_empty() {
+                            let mods = res
+                                .forge_mods
+                                .iter()
+                                .map(|m| format!("{} {}", m.modid, m.version))
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            let mods =
+                                Component::Text(TextComponent::new(&format!("Mods: {}", mods)));
+                            *s.mods_tooltip.borrow_mut() = Some((res.motd.clone(), mods));
+                        }
                         s.motd.borrow_mut().set_text(res.motd);
                         // Selects the icon for the given ping range
                         // TODO: switch to as_millis() experimental duration_as_u128 #50202 once available?
@@ -563,8 +848,16 @@ impl super::Screen for ServerList {
                                 };
                                 players.text = txt;
                             }
-                            let sm =
-                                format!("{} mods + {}", res.forge_mods.len(), res.protocol_name);
+                            let sm = format!(
+                                "Modded (Forge, {} {}) {}",
+                                res.forge_mods.len(),
+                                if res.forge_mods.len() == 1 {
+                                    "mod"
+                                } else {
+                                    "mods"
+                                },
+                                res.protocol_name
+                            );
                             let st = if !res.forge_mods.is_empty() {
                                 &sm
                             } else {
@@ -576,22 +869,15 @@ impl super::Screen for ServerList {
                             format::convert_legacy(&mut msg);
                             s.version.borrow_mut().set_text(msg);
                         }
-                        if let Some(favicon) = res.favicon {
-                            let name: String = std::iter::repeat(())
-                                .map(|()| {
-                                    rand::thread_rng().sample(&rand::distributions::Alphanumeric)
-                                        as char
-                                })
-                                .take(30)
-                                .collect();
-                            let tex = renderer.get_textures_ref();
-                            s.icon_texture = Some(name.clone());
-                            let icon_tex = tex.write().put_dynamic(&name, favicon);
-                            s.icon.borrow_mut().texture = icon_tex.name;
+                        // Keeps the default icon if the server has none or it's malformed
+                        if let Some(texture) = res.favicon.and_then(|data| {
+                            Self::favicon_texture(&mut self.favicons, renderer, &s.address, data)
+                        }) {
+                            s.icon.borrow_mut().texture = texture;
                         }
                     }
                     Err(TryRecvError::Disconnected) => {
-                        s.done_ping = true;
+                        s.ping_state = PingState::Failed;
                         let mut txt = TextComponent::new("Channel dropped");
                         txt.modifier.color = Some(format::Color::Red);
                         s.motd.borrow_mut().set_text(Component::Text(txt));
@@ -1,6 +1,10 @@
 use crate::console;
+use crate::format;
 use crate::render;
+use crate::render::clouds::CloudMode;
+use crate::resources;
 use crate::settings;
+use crate::sound::SoundCategory;
 use crate::ui;
 
 use std::rc::Rc;
@@ -11,7 +15,7 @@ pub struct UIElements {
 }
 
 pub struct SettingsMenu {
-    _vars: Rc<console::Vars>,
+    vars: Rc<console::Vars>,
     elements: Option<UIElements>,
     show_disconnect_button: bool,
 }
@@ -19,7 +23,7 @@ pub struct SettingsMenu {
 impl SettingsMenu {
     pub fn new(vars: Rc<console::Vars>, show_disconnect_button: bool) -> Self {
         SettingsMenu {
-            _vars: vars,
+            vars,
             elements: None,
             show_disconnect_button,
         }
@@ -66,7 +70,7 @@ impl super::Screen for SettingsMenu {
         {
             let mut video_settings = video_settings.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Video settings...")
+                .text(format::translate_or("options.video", "Video settings..."))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *video_settings);
             video_settings.add_text(txt);
@@ -86,10 +90,15 @@ impl super::Screen for SettingsMenu {
         {
             let mut controls_settings = controls_settings.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Controls...")
+                .text(format::translate_or("options.controls", "Controls..."))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *controls_settings);
             controls_settings.add_text(txt);
+            controls_settings.add_click_func(|_, game| {
+                game.screen_sys
+                    .add_screen(Box::new(super::Controls::new(game.vars.clone())));
+                true
+            });
         }
         buttons.push(controls_settings);
 
@@ -101,7 +110,7 @@ impl super::Screen for SettingsMenu {
         {
             let mut lang_settings = lang_settings.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Language...")
+                .text(format::translate_or("options.language", "Language..."))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *lang_settings);
             lang_settings.add_text(txt);
@@ -116,7 +125,10 @@ impl super::Screen for SettingsMenu {
         {
             let mut skin_settings = skin_settings.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Skin Customization...")
+                .text(format::translate_or(
+                    "options.skinCustomisation",
+                    "Skin Customization...",
+                ))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *skin_settings);
             skin_settings.add_text(txt);
@@ -128,6 +140,58 @@ impl super::Screen for SettingsMenu {
         }
         buttons.push(skin_settings);
 
+        let resource_pack_settings = ui::ButtonBuilder::new()
+            .position(-160.0, -100.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut resource_pack_settings = resource_pack_settings.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(resource_pack_label(
+                    &self.vars.get(settings::CL_RESOURCE_PACK),
+                ))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *resource_pack_settings);
+            let txt_resource_pack = txt.clone();
+            resource_pack_settings.add_text(txt);
+            resource_pack_settings.add_click_func(move |_, game| {
+                // Cycles through the zips in the resource packs directory, then the defaults
+                let current = game.vars.get(settings::CL_RESOURCE_PACK).clone();
+                let packs = resources::list_resource_packs();
+                let next = match packs.iter().position(|pack| *pack == current) {
+                    Some(i) => packs.get(i + 1).cloned().unwrap_or_default(),
+                    None if current.is_empty() => packs.first().cloned().unwrap_or_default(),
+                    None => String::new(),
+                };
+                game.resource_manager.write().select_resource_pack(&next);
+                txt_resource_pack.borrow_mut().text = resource_pack_label(&next);
+                game.vars.set(settings::CL_RESOURCE_PACK, next);
+                true
+            });
+        }
+        buttons.push(resource_pack_settings);
+
+        let accessibility_settings = ui::ButtonBuilder::new()
+            .position(-160.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut accessibility_settings = accessibility_settings.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Accessibility...")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *accessibility_settings);
+            accessibility_settings.add_text(txt);
+            accessibility_settings.add_click_func(|_, game| {
+                game.screen_sys
+                    .add_screen(Box::new(AccessibilitySettingsMenu::new(game.vars.clone())));
+                true
+            });
+        }
+        buttons.push(accessibility_settings);
+
         // Center bottom items
         let done_button = ui::ButtonBuilder::new()
             .position(0.0, 50.0)
@@ -137,7 +201,7 @@ impl super::Screen for SettingsMenu {
         {
             let mut done_button = done_button.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Done")
+                .text(format::translate_or("gui.done", "Done"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *done_button);
             done_button.add_text(txt);
@@ -160,7 +224,7 @@ impl super::Screen for SettingsMenu {
             {
                 let mut disconnect_button = disconnect_button.borrow_mut();
                 let txt = ui::TextBuilder::new()
-                    .text("Disconnect")
+                    .text(format::translate_or("menu.disconnect", "Disconnect"))
                     .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                     .attach(&mut *disconnect_button);
                 disconnect_button.add_text(txt);
@@ -219,6 +283,60 @@ impl super::Screen for SettingsMenu {
     }
 }
 
+fn resource_pack_label(name: &str) -> String {
+    format!(
+        "Resource pack: {}",
+        if name.is_empty() {
+            "Default"
+        } else {
+            name.trim_end_matches(".zip")
+        }
+    )
+}
+
+fn fov_label(fov: i64) -> String {
+    format!(
+        "FOV: {}",
+        match fov {
+            90 => "Normal".into(),
+            110 => "Quake pro".into(),
+            val => val.to_string(),
+        }
+    )
+}
+
+fn brightness_label(brightness: i64) -> String {
+    format!(
+        "Brightness: {}",
+        match brightness {
+            0 => "Moody".into(),
+            100 => "Bright".into(),
+            val => format!("+{}%", val),
+        }
+    )
+}
+
+fn clouds_label(mode: CloudMode) -> String {
+    format!(
+        "Clouds: {}",
+        match mode {
+            CloudMode::Off => "Off",
+            CloudMode::Fast => "Fast",
+            CloudMode::Fancy => "Fancy",
+        }
+    )
+}
+
+fn gui_scale_label(gui_scale: i64) -> String {
+    format!(
+        "GUI scale: {}",
+        match gui_scale {
+            ui::AUTO_GUI_SCALE => "Auto".into(),
+            val => val.to_string(),
+        }
+    )
+}
+
 pub struct VideoSettingsMenu {
     vars: Rc<console::Vars>,
     elements: Option<UIElements>,
@@ -248,6 +366,9 @@ impl super::Screen for VideoSettingsMenu {
         let r_max_fps = *self.vars.get(settings::R_MAX_FPS);
         let r_fov = *self.vars.get(settings::R_FOV);
         let r_vsync = *self.vars.get(settings::R_VSYNC);
+        let r_brightness = *self.vars.get(settings::R_BRIGHTNESS);
+        let r_clouds = CloudMode::from_name(self.vars.get(settings::R_CLOUDS).as_str());
+        let cl_gui_scale = *self.vars.get(settings::CL_GUI_SCALE);
 
         // Setting buttons
         // TODO: Slider
@@ -259,20 +380,49 @@ impl super::Screen for VideoSettingsMenu {
         {
             let mut fov_setting = fov_setting.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text(format!(
-                    "FOV: {}",
-                    match r_fov {
-                        90 => "Normal".into(),
-                        110 => "Quake pro".into(),
-                        val => val.to_string(),
-                    }
-                ))
+                .text(fov_label(r_fov))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *fov_setting);
+            let txt_fov = txt.clone();
             fov_setting.add_text(txt);
+            fov_setting.add_click_func(move |_, game| {
+                // Steps up by 10 degrees, wrapping around to the narrowest
+                let r_fov = *game.vars.get(settings::R_FOV);
+                let r_fov = if r_fov >= settings::MAX_FOV {
+                    settings::MIN_FOV
+                } else {
+                    (r_fov / 10 * 10 + 10).clamp(settings::MIN_FOV, settings::MAX_FOV)
+                };
+                txt_fov.borrow_mut().text = fov_label(r_fov);
+                game.vars.set(settings::R_FOV, r_fov);
+                true
+            });
         }
         buttons.push(fov_setting);
 
+        let brightness_setting = ui::ButtonBuilder::new()
+            .position(-160.0, -50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut brightness_setting = brightness_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(brightness_label(r_brightness))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *brightness_setting);
+            let txt_brightness = txt.clone();
+            brightness_setting.add_text(txt);
+            brightness_setting.add_click_func(move |_, game| {
+                // Steps through moody, +25%, +50%, +75% and bright
+                let r_brightness = (*game.vars.get(settings::R_BRIGHTNESS) / 25 * 25 + 25) % 125;
+                txt_brightness.borrow_mut().text = brightness_label(r_brightness);
+                game.vars.set(settings::R_BRIGHTNESS, r_brightness);
+                true
+            });
+        }
+        buttons.push(brightness_setting);
+
         let vsync_setting = ui::ButtonBuilder::new()
             .position(-160.0, 0.0)
             .size(300.0, 40.0)
@@ -321,6 +471,54 @@ impl super::Screen for VideoSettingsMenu {
         }
         buttons.push(fps_setting);
 
+        let clouds_setting = ui::ButtonBuilder::new()
+            .position(-160.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut clouds_setting = clouds_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(clouds_label(r_clouds))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *clouds_setting);
+            let txt_clouds = txt.clone();
+            clouds_setting.add_text(txt);
+            clouds_setting.add_click_func(move |_, game| {
+                let r_clouds =
+                    CloudMode::from_name(game.vars.get(settings::R_CLOUDS).as_str()).next();
+                txt_clouds.borrow_mut().text = clouds_label(r_clouds);
+                game.vars
+                    .set(settings::R_CLOUDS, r_clouds.name().to_owned());
+                true
+            });
+        }
+        buttons.push(clouds_setting);
+
+        let gui_scale_setting = ui::ButtonBuilder::new()
+            .position(160.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut gui_scale_setting = gui_scale_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(gui_scale_label(cl_gui_scale))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *gui_scale_setting);
+            let txt_gui_scale = txt.clone();
+            gui_scale_setting.add_text(txt);
+            gui_scale_setting.add_click_func(move |_, game| {
+                // Steps through auto, then 1 to the largest
+                let cl_gui_scale =
+                    (*game.vars.get(settings::CL_GUI_SCALE) + 1) % (ui::MAX_GUI_SCALE + 1);
+                txt_gui_scale.borrow_mut().text = gui_scale_label(cl_gui_scale);
+                game.vars.set(settings::CL_GUI_SCALE, cl_gui_scale);
+                true
+            });
+        }
+        buttons.push(gui_scale_setting);
+
         let done_button = ui::ButtonBuilder::new()
             .position(0.0, 50.0)
             .size(300.0, 40.0)
@@ -329,7 +527,7 @@ impl super::Screen for VideoSettingsMenu {
         {
             let mut done_button = done_button.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Done")
+                .text(format::translate_or("gui.done", "Done"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *done_button);
             done_button.add_text(txt);
@@ -379,15 +577,195 @@ impl super::Screen for VideoSettingsMenu {
     }
 }
 
+fn reduce_motion_label(reduce_motion: bool) -> String {
+    format!(
+        "Reduce motion: {}",
+        if reduce_motion { "On" } else { "Off" }
+    )
+}
+
+/// The label of an effect reduce motion turns off, which shows when it overrides the
+/// effect's own setting.
+fn motion_effect_label(name: &str, on: bool, reduce_motion: bool) -> String {
+    format!(
+        "{}: {}",
+        name,
+        match (on, reduce_motion) {
+            (true, true) => "Reduced",
+            (true, false) => "On",
+            (false, _) => "Off",
+        }
+    )
+}
+
+pub struct AccessibilitySettingsMenu {
+    vars: Rc<console::Vars>,
+    elements: Option<UIElements>,
+}
+
+impl AccessibilitySettingsMenu {
+    pub fn new(vars: Rc<console::Vars>) -> Self {
+        AccessibilitySettingsMenu {
+            vars,
+            elements: None,
+        }
+    }
+}
+
+impl super::Screen for AccessibilitySettingsMenu {
+    fn on_active(&mut self, _renderer: &mut render::Renderer, ui_container: &mut ui::Container) {
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .position(0.0, 0.0)
+            .size(854.0, 480.0)
+            .colour((0, 0, 0, 100))
+            .create(ui_container);
+
+        let mut buttons = vec![];
+
+        // Load defaults
+        let cl_reduce_motion = *self.vars.get(settings::CL_REDUCE_MOTION);
+        let cl_view_bobbing = *self.vars.get(settings::CL_VIEW_BOBBING);
+
+        // Setting buttons
+        let view_bobbing_setting = ui::ButtonBuilder::new()
+            .position(160.0, -50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        let txt_view_bobbing = {
+            let mut view_bobbing_setting = view_bobbing_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(motion_effect_label(
+                    "View bobbing",
+                    cl_view_bobbing,
+                    cl_reduce_motion,
+                ))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *view_bobbing_setting);
+            let txt_view_bobbing = txt.clone();
+            view_bobbing_setting.add_text(txt.clone());
+            view_bobbing_setting.add_click_func(move |_, game| {
+                let cl_view_bobbing = !*game.vars.get(settings::CL_VIEW_BOBBING);
+                txt_view_bobbing.borrow_mut().text = motion_effect_label(
+                    "View bobbing",
+                    cl_view_bobbing,
+                    *game.vars.get(settings::CL_REDUCE_MOTION),
+                );
+                game.vars.set(settings::CL_VIEW_BOBBING, cl_view_bobbing);
+                true
+            });
+            txt
+        };
+        buttons.push(view_bobbing_setting);
+
+        // Turns all of the effects above off at once
+        let reduce_motion_setting = ui::ButtonBuilder::new()
+            .position(-160.0, -50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut reduce_motion_setting = reduce_motion_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(reduce_motion_label(cl_reduce_motion))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *reduce_motion_setting);
+            let txt_reduce_motion = txt.clone();
+            reduce_motion_setting.add_text(txt);
+            reduce_motion_setting.add_click_func(move |_, game| {
+                let cl_reduce_motion = !*game.vars.get(settings::CL_REDUCE_MOTION);
+                txt_reduce_motion.borrow_mut().text = reduce_motion_label(cl_reduce_motion);
+                txt_view_bobbing.borrow_mut().text = motion_effect_label(
+                    "View bobbing",
+                    *game.vars.get(settings::CL_VIEW_BOBBING),
+                    cl_reduce_motion,
+                );
+                game.vars.set(settings::CL_REDUCE_MOTION, cl_reduce_motion);
+                true
+            });
+        }
+        buttons.push(reduce_motion_setting);
+
+        let done_button = ui::ButtonBuilder::new()
+            .position(0.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Bottom, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut done_button = done_button.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(format::translate_or("gui.done", "Done"))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *done_button);
+            done_button.add_text(txt);
+            done_button.add_click_func(|_, game| {
+                game.screen_sys.pop_screen();
+                true
+            });
+        }
+        buttons.push(done_button);
+        self.elements = Some(UIElements {
+            background,
+            _buttons: buttons,
+        });
+    }
+
+    fn on_deactive(&mut self, _renderer: &mut render::Renderer, _ui_container: &mut ui::Container) {
+        self.elements = None;
+    }
+
+    // Called every frame the screen is active
+    fn tick(
+        &mut self,
+        _delta: f64,
+        renderer: &mut render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> Option<Box<dyn super::Screen>> {
+        let elements = self.elements.as_mut().unwrap();
+        {
+            let mode = ui_container.mode;
+            let mut background = elements.background.borrow_mut();
+            background.width = match mode {
+                ui::Mode::Unscaled(scale) => 854.0 / scale,
+                ui::Mode::Scaled => renderer.width as f64,
+            };
+            background.height = match mode {
+                ui::Mode::Unscaled(scale) => 480.0 / scale,
+                ui::Mode::Scaled => renderer.height as f64,
+            };
+        }
+        None
+    }
+
+    // Events
+    fn on_scroll(&mut self, _x: f64, _y: f64) {}
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+}
+
+fn volume_label(category: SoundCategory, volume: i64) -> String {
+    format!(
+        "{}: {}",
+        category.name(),
+        match volume {
+            0 => "Off".into(),
+            val => format!("{}%", val),
+        }
+    )
+}
+
 pub struct AudioSettingsMenu {
-    _vars: Rc<console::Vars>,
+    vars: Rc<console::Vars>,
     elements: Option<UIElements>,
 }
 
 impl AudioSettingsMenu {
     pub fn new(vars: Rc<console::Vars>) -> AudioSettingsMenu {
         AudioSettingsMenu {
-            _vars: vars,
+            vars,
             elements: None,
         }
     }
@@ -404,7 +782,41 @@ impl super::Screen for AudioSettingsMenu {
 
         let mut buttons = vec![];
 
-        // TODO
+        // Master volume on top, the categories below it in two columns
+        // TODO: Slider
+        for (i, category) in SoundCategory::values().into_iter().enumerate() {
+            let (x, y) = if i == 0 {
+                (0.0, -200.0)
+            } else {
+                (
+                    if i % 2 == 1 { -160.0 } else { 160.0 },
+                    -200.0 + ((i + 1) / 2) as f64 * 50.0,
+                )
+            };
+            let volume = *self.vars.get(category.get_cvar());
+            let volume_setting = ui::ButtonBuilder::new()
+                .position(x, y)
+                .size(300.0, 40.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            {
+                let mut volume_setting = volume_setting.borrow_mut();
+                let txt = ui::TextBuilder::new()
+                    .text(volume_label(category, volume))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .attach(&mut *volume_setting);
+                let txt_volume = txt.clone();
+                volume_setting.add_text(txt);
+                volume_setting.add_click_func(move |_, game| {
+                    // Steps up by 10%, wrapping around to off
+                    let volume = (*game.vars.get(category.get_cvar()) / 10 * 10 + 10) % 110;
+                    txt_volume.borrow_mut().text = volume_label(category, volume);
+                    game.vars.set(category.get_cvar(), volume);
+                    true
+                });
+            }
+            buttons.push(volume_setting);
+        }
 
         let done_button = ui::ButtonBuilder::new()
             .position(0.0, 50.0)
@@ -414,7 +826,7 @@ impl super::Screen for AudioSettingsMenu {
         {
             let mut done_button = done_button.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Done")
+                .text(format::translate_or("gui.done", "Done"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *done_button);
             done_button.add_text(txt);
@@ -591,7 +1003,7 @@ impl super::Screen for SkinSettingsMenu {
         {
             let mut done_button = done_button.borrow_mut();
             let txt = ui::TextBuilder::new()
-                .text("Done")
+                .text(format::translate_or("gui.done", "Done"))
                 .alignment(ui::VAttach::Middle, ui::HAttach::Center)
                 .attach(&mut *done_button);
             done_button.add_text(txt);
@@ -0,0 +1,133 @@
+//! Saves the contents of the window as a PNG in the screenshots folder.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use parking_lot::RwLock;
+
+use crate::gl;
+use crate::paths;
+use crate::render::hud::HudContext;
+use leafish_protocol::format::{Component, TextComponent};
+
+pub fn get_screenshots_dir() -> PathBuf {
+    paths::get_data_dir().join("screenshots")
+}
+
+/// Formats the file name for a screenshot taken `secs` seconds after the unix epoch, in UTC,
+/// like `2021-08-14_13.37.05.png`.
+pub fn file_name(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let secs_of_day = secs % 86400;
+
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}.{:02}.{:02}.png",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// Picks a path in `dir` that isn't taken yet, numbering screenshots taken in the same second.
+fn unused_path(dir: &Path, name: &str) -> PathBuf {
+    let stem = name.trim_end_matches(".png");
+    let mut path = dir.join(name);
+    let mut i = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.png", stem, i));
+        i += 1;
+    }
+    path
+}
+
+/// Reads the default framebuffer and saves it on another thread. Once saved, a message with
+/// the file name is shown in the chat of `hud_context`, if given.
+pub fn capture(width: u32, height: u32, hud_context: Option<Arc<RwLock<HudContext>>>) {
+    if width == 0 || height == 0 {
+        warn!("Can't take a screenshot of an empty window");
+        return;
+    }
+    let mut pixels = vec![0; (width * height * 4) as usize];
+    gl::unbind_framebuffer_read();
+    gl::read_pixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        &mut pixels,
+    );
+
+    thread::spawn(move || {
+        // OpenGL starts at the bottom row, images at the top
+        let row = (width * 4) as usize;
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for line in pixels.chunks_exact(row).rev() {
+            flipped.extend_from_slice(line);
+        }
+        // The framebuffer's alpha isn't meaningful, the window is always opaque
+        for alpha in flipped.iter_mut().skip(3).step_by(4) {
+            *alpha = 255;
+        }
+
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let dir = get_screenshots_dir();
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(|err| err.to_string())
+            .and_then(|_| {
+                let path = unused_path(&dir, &file_name(secs));
+                image::RgbaImage::from_raw(width, height, flipped)
+                    .ok_or_else(|| "framebuffer size mismatch".to_owned())?
+                    .save(&path)
+                    .map_err(|err| err.to_string())?;
+                Ok(path)
+            });
+        match result {
+            Ok(path) => {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                info!("Saved screenshot to {}", path.display());
+                if let Some(hud_context) = hud_context {
+                    hud_context.write().display_message_in_chat(Component::Text(
+                        TextComponent::new(&format!("Saved screenshot as {}", name)),
+                    ));
+                }
+            }
+            Err(err) => warn!("Failed to save screenshot: {}", err),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screenshot_file_names() {
+        assert_eq!(file_name(0), "1970-01-01_00.00.00.png");
+        assert_eq!(file_name(1628948225), "2021-08-14_13.37.05.png");
+        // Leap day and the last second of a year
+        assert_eq!(file_name(951782400), "2000-02-29_00.00.00.png");
+        assert_eq!(file_name(1609459199), "2020-12-31_23.59.59.png");
+    }
+}
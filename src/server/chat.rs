@@ -0,0 +1,201 @@
+use crate::format::Component;
+use crate::protocol::packet::play::serverbound;
+use crate::protocol::{Version, UUID};
+
+/// Maximum amount of messages kept until `Server::drain_chat_messages` is called.
+pub const MAX_QUEUED_MESSAGES: usize = 256;
+
+/// Where a chat message is meant to be displayed, as sent by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatPosition {
+    Chat,
+    System,
+    ActionBar,
+}
+
+impl ChatPosition {
+    pub fn from_byte(position: u8) -> ChatPosition {
+        match position {
+            1 => ChatPosition::System,
+            2 => ChatPosition::ActionBar,
+            _ => ChatPosition::Chat,
+        }
+    }
+
+    /// The position of a message from a server speaking `version`. 1.7 doesn't send one,
+    /// all its messages are chat. From 1.8 on the position byte tells chat, system
+    /// messages and game info (shown above the hotbar) apart, 1.16 adding the sender.
+    pub fn for_version(position: Option<u8>, version: Version) -> ChatPosition {
+        match position {
+            Some(position) if version >= Version::V1_8 => ChatPosition::from_byte(position),
+            _ => ChatPosition::Chat,
+        }
+    }
+}
+
+/// A parsed chat message received from the server.
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    pub message: Component,
+    pub position: ChatPosition,
+    pub sender: Option<UUID>,
+}
+
+impl ChatMessage {
+    /// Older protocols don't send a position at all, those messages always end up in the chat.
+    pub fn new(
+        message: Component,
+        position: Option<u8>,
+        sender: Option<UUID>,
+        version: Version,
+    ) -> ChatMessage {
+        ChatMessage {
+            message,
+            position: ChatPosition::for_version(position, version),
+            sender,
+        }
+    }
+}
+
+/// The longest message the server accepts, in characters. 1.11 raised it from 100 to 256.
+pub fn max_message_length(version: Version) -> usize {
+    if version >= Version::V1_11 {
+        256
+    } else {
+        100
+    }
+}
+
+/// The packets sending a line typed into the chat. Long messages are split over several
+/// packets, while commands can't be and are cut off instead.
+pub fn chat_packets(line: &str, version: Version) -> Vec<serverbound::ChatMessage> {
+    // Servers kick players sending control characters
+    let chars: Vec<char> = line.trim().chars().filter(|c| !c.is_control()).collect();
+    if chars.is_empty() {
+        return vec![];
+    }
+    let max_length = max_message_length(version);
+    let chunks: Vec<&[char]> = if chars[0] == '/' {
+        vec![&chars[..chars.len().min(max_length)]]
+    } else {
+        chars.chunks(max_length).collect()
+    };
+    chunks
+        .into_iter()
+        .map(|chunk| serverbound::ChatMessage {
+            message: chunk.iter().collect(),
+        })
+        .collect()
+}
+
+/// Suggestions from the server for completing the chat input, each replacing the
+/// `length` characters starting at `start`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completions {
+    pub start: usize,
+    pub length: usize,
+    pub matches: Vec<String>,
+}
+
+impl Completions {
+    /// Before 1.13 the server only sends the matches, which replace the last word of
+    /// the text that was sent.
+    pub fn for_last_word(text: &str, matches: Vec<String>) -> Completions {
+        let length = text.chars().rev().take_while(|&c| c != ' ').count();
+        Completions {
+            start: text.chars().count() - length,
+            length,
+            matches,
+        }
+    }
+
+    /// The chat input with the match at `index` put in place of the completed text.
+    pub fn apply(&self, input: &str, index: usize) -> String {
+        let chars: Vec<char> = input.chars().collect();
+        let start = self.start.min(chars.len());
+        let end = (self.start + self.length).min(chars.len());
+        let mut completed: String = chars[..start].iter().collect();
+        completed.push_str(&self.matches[index]);
+        completed.extend(&chars[end..]);
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_chat_message() {
+        let component = Component::from_string(r#"{"text":"hello"}"#);
+
+        let legacy = ChatMessage::new(component.clone(), None, None, Version::V1_7);
+        assert_eq!(legacy.position, ChatPosition::Chat);
+        assert_eq!(legacy.message.to_string(), "hello");
+
+        let action_bar = ChatMessage::new(component.clone(), Some(2), None, Version::V1_8);
+        assert_eq!(action_bar.position, ChatPosition::ActionBar);
+
+        let sender = UUID::from_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap();
+        let system = ChatMessage::new(
+            component.clone(),
+            Some(1),
+            Some(sender.clone()),
+            Version::V1_16,
+        );
+        assert_eq!(system.position, ChatPosition::System);
+        assert_eq!(system.sender, Some(sender.clone()));
+
+        let game_info = ChatMessage::new(component, Some(2), Some(sender), Version::V1_16);
+        assert_eq!(game_info.position, ChatPosition::ActionBar);
+        assert_eq!(
+            ChatPosition::for_version(Some(2), Version::V1_7),
+            ChatPosition::Chat
+        );
+    }
+
+    #[test]
+    fn typed_line_to_chat_packets() {
+        let packets = chat_packets("  hello\r", Version::V1_8);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].message, "hello");
+        assert!(chat_packets(" ", Version::V1_8).is_empty());
+
+        let long = "a".repeat(250);
+        let packets = chat_packets(&long, Version::V1_8);
+        let lengths: Vec<_> = packets.iter().map(|p| p.message.len()).collect();
+        assert_eq!(lengths, vec![100, 100, 50]);
+        assert_eq!(chat_packets(&long, Version::V1_11)[0].message, long);
+
+        let command = format!("/say {}", long);
+        let packets = chat_packets(&command, Version::V1_10);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].message, command[..100]);
+    }
+
+    #[test]
+    fn apply_tab_completion() {
+        let legacy = Completions::for_last_word(
+            "/gamemode s",
+            vec!["spectator".to_owned(), "survival".to_owned()],
+        );
+        assert_eq!((legacy.start, legacy.length), (10, 1));
+        assert_eq!(legacy.apply("/gamemode s", 0), "/gamemode spectator");
+        assert_eq!(legacy.apply("/gamemode s", 1), "/gamemode survival");
+
+        let command = Completions::for_last_word("/tp", vec!["/tp".to_owned()]);
+        assert_eq!(command.apply("/tp", 0), "/tp");
+
+        // Since 1.13 the server says where the match goes, leaving the rest alone
+        let completions = Completions {
+            start: 6,
+            length: 3,
+            matches: vec!["Steve".to_owned()],
+        };
+        assert_eq!(
+            completions.apply("/give Ste diamond", 0),
+            "/give Steve diamond"
+        );
+    }
+}
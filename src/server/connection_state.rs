@@ -0,0 +1,98 @@
+use crate::format::Component;
+use crate::protocol::packet::Packet;
+use log::info;
+
+/// How far along the connection to a server is, from the initial handshake to playing.
+#[derive(Debug, Clone)]
+pub enum ConnectionState {
+    Connecting,
+    LoggingIn,
+    /// Logged in, but the server hasn't placed the player in the world yet.
+    DownloadingTerrain,
+    Playing,
+    Disconnected(Component),
+}
+
+impl ConnectionState {
+    /// Returns the state the connection moves to after receiving `packet`, if it changes.
+    pub fn next(&self, packet: &Packet) -> Option<ConnectionState> {
+        use ConnectionState::*;
+        match (self, packet) {
+            (Disconnected(_), _) => None,
+            (_, Packet::LoginDisconnect(disconnect)) => {
+                Some(Disconnected(disconnect.reason.clone()))
+            }
+            (_, Packet::Disconnect(disconnect)) => Some(Disconnected(disconnect.reason.clone())),
+            (Connecting, Packet::SetInitialCompression(_))
+            | (Connecting, Packet::EncryptionRequest(_))
+            | (Connecting, Packet::EncryptionRequest_i16(_))
+            | (Connecting, Packet::LoginPluginRequest(_)) => Some(LoggingIn),
+            (Connecting, Packet::LoginSuccess_String(_))
+            | (Connecting, Packet::LoginSuccess_UUID(_))
+            | (LoggingIn, Packet::LoginSuccess_String(_))
+            | (LoggingIn, Packet::LoginSuccess_UUID(_)) => Some(DownloadingTerrain),
+            // The server spawns the player into the world once the terrain around it is sent
+            (DownloadingTerrain, Packet::TeleportPlayer_WithConfirm(_))
+            | (DownloadingTerrain, Packet::TeleportPlayer_NoConfirm(_))
+            | (DownloadingTerrain, Packet::TeleportPlayer_OnGround(_)) => Some(Playing),
+            _ => None,
+        }
+    }
+
+    /// Moves to the state following `packet`, logging the transition.
+    pub fn update(&mut self, packet: &Packet) {
+        if let Some(next) = self.next(packet) {
+            self.set(next);
+        }
+    }
+
+    pub fn set(&mut self, next: ConnectionState) {
+        info!("Connection state: {:?} -> {:?}", self, next);
+        *self = next;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packet::login;
+
+    #[test]
+    fn connection_state_transitions() {
+        let mut state = ConnectionState::Connecting;
+        let packets = vec![
+            Packet::SetInitialCompression(Default::default()),
+            Packet::EncryptionRequest(Default::default()),
+            Packet::LoginSuccess_String(login::clientbound::LoginSuccess_String {
+                uuid: "b50ad385-829d-3141-a216-7e7d7539ba7f".to_owned(),
+                username: "Notch".to_owned(),
+            }),
+            Packet::ChunkData_NoEntities(Default::default()),
+            Packet::TeleportPlayer_WithConfirm(Default::default()),
+            Packet::TimeUpdate(Default::default()),
+            Packet::Disconnect(Default::default()),
+            Packet::TeleportPlayer_WithConfirm(Default::default()),
+        ];
+        let mut states = vec![];
+        for packet in &packets {
+            state.update(packet);
+            states.push(format!("{:?}", state));
+        }
+        assert_eq!(states[0], "LoggingIn");
+        assert_eq!(states[1], "LoggingIn");
+        assert_eq!(states[2], "DownloadingTerrain");
+        assert_eq!(states[3], "DownloadingTerrain");
+        assert_eq!(states[4], "Playing");
+        assert_eq!(states[5], "Playing");
+        assert!(matches!(state, ConnectionState::Disconnected(_)));
+        assert_eq!(states[6], states[7]);
+
+        let mut state = ConnectionState::LoggingIn;
+        state.update(&Packet::LoginDisconnect(
+            login::clientbound::LoginDisconnect {
+                reason: Component::from_string("Kicked"),
+            },
+        ));
+        assert!(matches!(state, ConnectionState::Disconnected(_)));
+    }
+}
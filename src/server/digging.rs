@@ -0,0 +1,118 @@
+use crate::shared::{Direction, Position};
+use crate::world::block::{self, Tool};
+
+/// Ticks to wait after breaking a block before digging the next one.
+pub const DIG_DELAY_TICKS: u32 = 5;
+/// Number of crack textures, `destroy_stage_0` to `destroy_stage_9`.
+pub const CRACK_STAGES: u32 = 10;
+
+pub const DIG_START: u8 = 0;
+pub const DIG_CANCEL: u8 = 1;
+pub const DIG_FINISH: u8 = 2;
+
+/// Ticks it takes to break `block` with the held `tool` and its speed, `None` if it can't
+/// be broken. Tool tiers aren't checked, any tool of the right kind can harvest the block.
+pub fn break_ticks(block: block::Block, tool: Option<(Tool, f64)>, on_ground: bool) -> Option<u32> {
+    let hardness = block.get_hardness();
+    if hardness.is_infinite() {
+        return None;
+    }
+    let right_tool = tool.filter(|(tool, _)| block.get_tool() == Some(*tool));
+    let mut speed = right_tool.map_or(1.0, |(_, speed)| speed);
+    if !on_ground {
+        speed /= 5.0;
+    }
+    let divisor = if block.requires_tool() && right_tool.is_none() {
+        100.0
+    } else {
+        30.0
+    };
+    Some(((hardness * divisor / speed).ceil() as u32).max(1))
+}
+
+/// The block currently being dug.
+#[derive(Debug)]
+pub struct DigTarget {
+    pub pos: Position,
+    pub face: Direction,
+    ticks: u32,
+    stage: u32,
+}
+
+impl DigTarget {
+    pub fn new(pos: Position, face: Direction) -> DigTarget {
+        DigTarget {
+            pos,
+            face,
+            ticks: 0,
+            stage: 0,
+        }
+    }
+
+    /// Digs for another tick, returning whether the block broke.
+    pub fn tick(&mut self, break_ticks: Option<u32>) -> bool {
+        self.ticks += 1;
+        match break_ticks {
+            Some(total) => {
+                self.stage = (self.ticks * CRACK_STAGES / total).min(CRACK_STAGES - 1);
+                self.ticks >= total
+            }
+            None => false,
+        }
+    }
+
+    /// The crack texture to show on the block.
+    pub fn stage(&self) -> u32 {
+        self.stage
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Digging {
+    /// Whether the attack button is held down.
+    pub held: bool,
+    /// Set on click so a click shorter than a tick still digs.
+    pub clicked: bool,
+    /// Ticks left before the next block can be dug.
+    pub delay: u32,
+    pub target: Option<DigTarget>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stone_break_time() {
+        let stone = block::Stone {
+            variant: block::StoneVariant::Normal,
+        };
+        assert_eq!(break_ticks(stone, None, true), Some(150));
+        assert_eq!(
+            break_ticks(stone, Some((Tool::Pickaxe, 2.0)), true),
+            Some(23)
+        );
+        assert_eq!(
+            break_ticks(stone, Some((Tool::Pickaxe, 8.0)), true),
+            Some(6)
+        );
+        assert_eq!(
+            break_ticks(stone, Some((Tool::Pickaxe, 8.0)), false),
+            Some(29)
+        );
+        // The wrong tool is no better than a hand
+        assert_eq!(
+            break_ticks(stone, Some((Tool::Shovel, 8.0)), true),
+            Some(150)
+        );
+        assert_eq!(break_ticks(block::Bedrock {}, None, true), None);
+
+        let mut target = DigTarget::new(Position::new(0, 64, 0), Direction::Up);
+        let total = break_ticks(stone, Some((Tool::Pickaxe, 8.0)), true);
+        for _ in 0..5 {
+            assert!(!target.tick(total));
+        }
+        assert_eq!(target.stage(), 8);
+        assert!(target.tick(total));
+    }
+}
@@ -0,0 +1,276 @@
+//! Lets bots and other tools follow what happens on the server, without having to
+//! handle every protocol version's packets themselves.
+
+use crate::format::Component;
+use crate::protocol::packet::Packet;
+use crate::protocol::UUID;
+use crate::shared::Position;
+use parking_lot::RwLock;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Something that happened on the server, decoded from the packets of any supported
+/// version.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A chat message, `position` is 0 for chat, 1 for system messages and 2 for
+    /// messages above the hotbar.
+    Chat {
+        message: Component,
+        position: u8,
+        sender: Option<UUID>,
+    },
+    /// A single block changed, `block_id` being the block state id of the server's
+    /// version. Before 1.8 it's combined from the id and metadata like later versions.
+    BlockChange {
+        location: Position,
+        block_id: i32,
+    },
+    EntitySpawn {
+        entity_id: i32,
+        uuid: Option<UUID>,
+        kind: EntityKind,
+        position: (f64, f64, f64),
+    },
+    EntityDestroy {
+        entity_ids: Vec<i32>,
+    },
+    HealthChange {
+        health: f32,
+        food: i32,
+        saturation: f32,
+    },
+    Disconnect {
+        reason: Component,
+    },
+}
+
+/// What kind of entity spawned, with the type id the server's version uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Player,
+    Mob(i32),
+    Object(i32),
+}
+
+impl Event {
+    /// The event a packet causes, if it's one of the covered ones.
+    pub fn from_packet(packet: &Packet) -> Option<Event> {
+        macro_rules! spawn {
+            ($spawn:expr, $uuid:expr, $kind:expr) => {
+                Event::EntitySpawn {
+                    entity_id: $spawn.entity_id.0,
+                    uuid: $uuid,
+                    kind: $kind,
+                    position: (
+                        f64::from($spawn.x),
+                        f64::from($spawn.y),
+                        f64::from($spawn.z),
+                    ),
+                }
+            };
+        }
+
+        Some(match packet {
+            Packet::ServerMessage_NoPosition(msg) => Event::Chat {
+                message: msg.message.clone(),
+                position: 0,
+                sender: None,
+            },
+            Packet::ServerMessage_Position(msg) => Event::Chat {
+                message: msg.message.clone(),
+                position: msg.position,
+                sender: None,
+            },
+            Packet::ServerMessage_Sender(msg) => Event::Chat {
+                message: msg.message.clone(),
+                position: msg.position,
+                sender: Some(msg.sender.clone()),
+            },
+            Packet::BlockChange_VarInt(change) => Event::BlockChange {
+                location: change.location,
+                block_id: change.block_id.0,
+            },
+            Packet::BlockChange_u8(change) => Event::BlockChange {
+                location: Position::new(change.x, change.y as i32, change.z),
+                block_id: (change.block_id.0 << 4) | (change.block_metadata & 0xF) as i32,
+            },
+            Packet::SpawnPlayer_f64_NoMeta(spawn) => {
+                spawn!(spawn, Some(spawn.uuid.clone()), EntityKind::Player)
+            }
+            Packet::SpawnPlayer_f64(spawn) => {
+                spawn!(spawn, Some(spawn.uuid.clone()), EntityKind::Player)
+            }
+            Packet::SpawnPlayer_i32(spawn) => {
+                spawn!(spawn, Some(spawn.uuid.clone()), EntityKind::Player)
+            }
+            Packet::SpawnPlayer_i32_HeldItem(spawn) => {
+                spawn!(spawn, Some(spawn.uuid.clone()), EntityKind::Player)
+            }
+            Packet::SpawnPlayer_i32_HeldItem_String(spawn) => {
+                spawn!(spawn, UUID::from_str(&spawn.uuid).ok(), EntityKind::Player)
+            }
+            Packet::SpawnMob_NoMeta(spawn) => {
+                spawn!(spawn, Some(spawn.uuid.clone()), EntityKind::Mob(spawn.ty.0))
+            }
+            Packet::SpawnMob_WithMeta(spawn) => {
+                spawn!(spawn, Some(spawn.uuid.clone()), EntityKind::Mob(spawn.ty.0))
+            }
+            Packet::SpawnMob_u8(spawn) => spawn!(
+                spawn,
+                Some(spawn.uuid.clone()),
+                EntityKind::Mob(spawn.ty as i32)
+            ),
+            Packet::SpawnMob_u8_i32(spawn) => spawn!(
+                spawn,
+                Some(spawn.uuid.clone()),
+                EntityKind::Mob(spawn.ty as i32)
+            ),
+            Packet::SpawnMob_u8_i32_NoUUID(spawn) => {
+                spawn!(spawn, None, EntityKind::Mob(spawn.ty as i32))
+            }
+            Packet::SpawnObject(spawn) => spawn!(
+                spawn,
+                Some(spawn.uuid.clone()),
+                EntityKind::Object(spawn.ty as i32)
+            ),
+            Packet::SpawnObject_i32(spawn) => spawn!(
+                spawn,
+                Some(spawn.uuid.clone()),
+                EntityKind::Object(spawn.ty as i32)
+            ),
+            Packet::SpawnObject_i32_NoUUID(spawn) => {
+                spawn!(spawn, None, EntityKind::Object(spawn.ty as i32))
+            }
+            Packet::SpawnObject_VarInt(spawn) => spawn!(
+                spawn,
+                Some(spawn.uuid.clone()),
+                EntityKind::Object(spawn.ty.0)
+            ),
+            Packet::EntityDestroy(destroy) => Event::EntityDestroy {
+                entity_ids: destroy.entity_ids.data.iter().map(|id| id.0).collect(),
+            },
+            Packet::EntityDestroy_u8(destroy) => Event::EntityDestroy {
+                entity_ids: destroy.entity_ids.data.clone(),
+            },
+            Packet::UpdateHealth(health) => Event::HealthChange {
+                health: health.health,
+                food: health.food.0,
+                saturation: health.food_saturation,
+            },
+            Packet::UpdateHealth_u16(health) => Event::HealthChange {
+                health: health.health,
+                food: health.food as i32,
+                saturation: health.food_saturation,
+            },
+            Packet::Disconnect(disconnect) => Event::Disconnect {
+                reason: disconnect.reason.clone(),
+            },
+            Packet::LoginDisconnect(disconnect) => Event::Disconnect {
+                reason: disconnect.reason.clone(),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Identifies a callback registered with `Events::subscribe`, to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionId(usize);
+
+type Callback = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// The callbacks following a server's events. They are called on the network thread
+/// as packets arrive, before the client itself handles them.
+#[derive(Default)]
+pub struct Events {
+    callbacks: RwLock<Vec<(SubscriptionId, Callback)>>,
+    next_id: AtomicUsize,
+}
+
+impl Events {
+    pub fn subscribe<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.callbacks.write().push((id, Arc::new(callback)));
+        id
+    }
+
+    #[allow(dead_code)]
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.callbacks.write().retain(|(other, _)| *other != id);
+    }
+
+    /// Calls every callback with the event `packet` causes, if any.
+    pub fn dispatch_packet(&self, packet: &Packet) {
+        if self.callbacks.read().is_empty() {
+            return;
+        }
+        if let Some(event) = Event::from_packet(packet) {
+            self.dispatch(&event);
+        }
+    }
+
+    pub fn dispatch(&self, event: &Event) {
+        // Callbacks are called without holding the lock, so that they can subscribe
+        // and unsubscribe themselves
+        let callbacks: Vec<Callback> = self
+            .callbacks
+            .read()
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packet::play::clientbound;
+    use parking_lot::Mutex;
+
+    #[test]
+    fn chat_callback_receives_chat_packet() {
+        let events = Events::default();
+        let received = Arc::new(Mutex::new(vec![]));
+        let id = {
+            let received = received.clone();
+            events.subscribe(move |event| {
+                if let Event::Chat {
+                    message, position, ..
+                } = event
+                {
+                    received.lock().push((message.to_string(), *position));
+                }
+            })
+        };
+
+        events.dispatch_packet(&Packet::ServerMessage_Position(
+            clientbound::ServerMessage_Position {
+                message: Component::from_string(r#"{"text":"<Steve> hi"}"#),
+                position: 0,
+            },
+        ));
+        // Packets without an event are skipped
+        events.dispatch_packet(&Packet::KeepAliveClientbound_VarInt(Default::default()));
+        events.dispatch_packet(&Packet::UpdateHealth(clientbound::UpdateHealth {
+            health: 19.0,
+            ..Default::default()
+        }));
+        assert_eq!(*received.lock(), vec![("<Steve> hi".to_owned(), 0)]);
+
+        events.unsubscribe(id);
+        events.dispatch_packet(&Packet::ServerMessage_NoPosition(
+            clientbound::ServerMessage_NoPosition {
+                message: Component::from_string(r#"{"text":"ignored"}"#),
+            },
+        ));
+        assert_eq!(received.lock().len(), 1);
+    }
+}
@@ -0,0 +1,62 @@
+use instant::{Duration, Instant};
+
+/// How long to wait for a keep-alive before giving up on the connection, unless configured
+/// by `settings::CL_KEEP_ALIVE_TIMEOUT`. Vanilla servers send one every 15 seconds.
+pub const DEFAULT_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tracks when the server last sent a keep-alive, to detect stalled connections.
+#[derive(Debug, Clone)]
+pub struct KeepAlive {
+    last_received: Instant,
+    timeout: Duration,
+}
+
+impl KeepAlive {
+    pub fn new(now: Instant, timeout: Duration) -> KeepAlive {
+        KeepAlive {
+            last_received: now,
+            timeout,
+        }
+    }
+
+    pub fn received(&mut self, now: Instant) {
+        self.last_received = now;
+    }
+
+    /// When the last keep-alive arrived, or the connection was made if none has yet.
+    pub fn last_received(&self) -> Instant {
+        self.last_received
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn timed_out(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_received) > self.timeout
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missed_keep_alive_times_out() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start, Duration::from_secs(30));
+        assert!(!keep_alive.timed_out(start + Duration::from_secs(20)));
+
+        // The server keeps sending them every 15 seconds
+        for i in 1..=4 {
+            let now = start + Duration::from_secs(15 * i);
+            keep_alive.received(now);
+            assert!(!keep_alive.timed_out(now + Duration::from_secs(10)));
+        }
+        assert_eq!(keep_alive.last_received(), start + Duration::from_secs(60));
+
+        // ...until it stalls
+        assert!(!keep_alive.timed_out(start + Duration::from_secs(90)));
+        assert!(keep_alive.timed_out(start + Duration::from_secs(91)));
+    }
+}
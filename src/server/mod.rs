@@ -14,47 +14,70 @@
 
 use crate::entity;
 use crate::format;
-use crate::inventory::material::versions::to_material;
-use crate::inventory::{Inventory, InventoryContext, Item};
+use crate::inventory::creative_inventory::CreativeInventory;
+use crate::inventory::material::versions::{self as material_versions, to_material};
+use crate::inventory::{ClickAction, Inventory, InventoryContext, Item};
+use crate::particle;
 use crate::protocol::{self, forge, mojang, packet};
 use crate::render;
-use crate::render::hud::HudContext;
+use crate::render::hud::{
+    held_item_change, BossBar, BossBarColor, BossBarDivision, BossBarUpdate, Coordinates,
+    DebugInfo, HudContext,
+};
 use crate::render::Renderer;
 use crate::resources;
+use crate::screen::chat::Chat;
+use crate::screen::downloading_terrain::{DownloadingTerrain, TerrainProgress};
+use crate::screen::resource_pack_prompt::ResourcePackPrompt;
 use crate::screen::respawn::Respawn;
-use crate::screen::ScreenSystem;
-use crate::settings::Actionkey;
-use crate::shared::{Axis, Position};
+use crate::screen::{ScreenId, ScreenSystem};
+use crate::settings::{self, Actionkey};
+use crate::shared::{Axis, Direction, Position};
+use crate::sound::{self, SoundCategory, SoundEvent, SoundName};
 use crate::types::hash::FNVHash;
-use crate::types::GameMode;
+use crate::types::{self, GameMode};
 use crate::world;
 use crate::world::{block, CPos, LightData, LightUpdate};
 use crate::{ecs, Game};
 use cgmath::prelude::*;
+use collision::Aabb;
 use crossbeam_channel::unbounded;
 use crossbeam_channel::{Receiver, Sender};
 use instant::{Duration, Instant};
 use leafish_protocol::format::{Component, TextComponent};
+use leafish_protocol::item::Stack;
 use leafish_protocol::protocol::packet::play::serverbound::{
     ClientSettings, ClientSettings_u8_Handsfree,
 };
 use leafish_protocol::protocol::packet::Packet;
 use leafish_protocol::protocol::{Conn, Version};
-use log::{debug, error, info, warn};
+use log::{debug, info, warn};
 use parking_lot::Mutex;
 use parking_lot::RwLock;
 use rand::{self, Rng};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::hash::BuildHasherDefault;
 use std::io::Cursor;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::thread;
+use std::thread::{self, JoinHandle};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod chat;
+mod connection_state;
+pub use self::connection_state::ConnectionState;
+mod digging;
+pub mod events;
+use self::digging::{DigTarget, Digging};
+mod keep_alive;
+pub use self::keep_alive::{KeepAlive, DEFAULT_KEEP_ALIVE_TIMEOUT};
+mod player_list;
+pub use self::player_list::{ping_icon, PlayerInfo, PlayerList};
 pub mod plugin_messages;
+pub mod resource_pack;
 mod sun;
 pub mod target;
+mod weather;
 
 #[derive(Default)]
 pub struct DisconnectData {
@@ -86,7 +109,21 @@ pub struct Server {
     pub protocol_version: i32,
     mapped_protocol_version: Version,
     forge_mods: Vec<forge::ForgeMod>,
+    fml_network_version: Option<i64>,
     pub disconnect_data: Arc<RwLock<DisconnectData>>,
+    connection_state: RwLock<ConnectionState>,
+    keep_alive: RwLock<KeepAlive>,
+    /// Chunks around the player to keep loaded, also sent to the server as the view distance.
+    render_distance: RwLock<i32>,
+    /// The id the server gave the local player, used when sending entity actions.
+    entity_id: RwLock<i32>,
+    /// Whether the server was last told the player is sprinting.
+    sprinting_sent: RwLock<bool>,
+    /// Whether the server was last told the player is sneaking.
+    sneaking_sent: RwLock<bool>,
+    /// The block ids from before the flattening, which the materials of held items are
+    /// placed by. `None` for servers that still use them.
+    legacy_block_ids: Option<block::VanillaIDMap>,
 
     pub world: Arc<world::World>,
     pub entities: Arc<RwLock<ecs::Manager>>,
@@ -105,17 +142,28 @@ pub struct Server {
     gamemode: ecs::Key<GameMode>,
     pub rotation: ecs::Key<entity::Rotation>,
     target_rotation: ecs::Key<entity::TargetRotation>,
+    interpolation: ecs::Key<entity::Interpolation>,
     //
     pub player: Arc<RwLock<Option<ecs::Entity>>>,
     entity_map: Arc<RwLock<HashMap<i32, ecs::Entity, BuildHasherDefault<FNVHash>>>>,
-    players: Arc<RwLock<HashMap<protocol::UUID, PlayerInfo, BuildHasherDefault<FNVHash>>>>,
-
-    tick_timer: RwLock<f64>,
-    entity_tick_timer: RwLock<f64>,
-    pub received_chat_at: Arc<RwLock<Option<Instant>>>,
+    players: Arc<RwLock<PlayerList>>,
+
+    chat_messages: Mutex<VecDeque<chat::ChatMessage>>,
+    /// The id and text of the last tab-complete request.
+    tab_complete_request: Mutex<(i32, String)>,
+    tab_completions: Mutex<Option<chat::Completions>>,
+    sounds: Mutex<Vec<SoundEvent>>,
+    /// A resource pack the server sent, until the user is asked about it.
+    resource_pack_request: Mutex<Option<resource_pack::Request>>,
+    resource_pack_download: Mutex<Option<resource_pack::Download>>,
+    events: events::Events,
 
     sun_model: RwLock<Option<sun::SunModel>>,
+    weather: RwLock<weather::Weather>,
+    weather_model: RwLock<Option<weather::WeatherModel>>,
+    particles: RwLock<particle::Particles>,
     target_info: Arc<RwLock<target::Info>>,
+    digging: RwLock<Digging>,
     pub render_list_computer: Sender<bool>,
     pub render_list_computer_notify: Receiver<bool>,
     pub hud_context: Arc<RwLock<HudContext>>,
@@ -125,17 +173,15 @@ pub struct Server {
     pub dead: RwLock<bool>,
     just_died: RwLock<bool>,
     close_death_screen: RwLock<bool>,
-}
-
-#[derive(Debug)]
-pub struct PlayerInfo {
-    name: String,
-    uuid: protocol::UUID,
-    skin_url: Option<String>,
-
-    display_name: Option<format::Component>,
-    ping: i32,
-    gamemode: GameMode,
+    terrain_progress: Arc<RwLock<TerrainProgress>>,
+    /// When the downloading terrain screen was opened, `None` until it is.
+    terrain_screen_opened: RwLock<Option<(ScreenId, Instant)>>,
+    terrain_loaded: RwLock<bool>,
+    /// The player's chunk and the render distance chunks were last unloaded around.
+    chunks_unloaded_around: RwLock<Option<(CPos, i32)>>,
+    toggle_perspective: RwLock<bool>,
+    /// The thread reading packets, joined on shutdown.
+    reader: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl Server {
@@ -146,10 +192,12 @@ impl Server {
         protocol_version: i32,
         forge_mods: Vec<forge::ForgeMod>,
         fml_network_version: Option<i64>,
-        renderer: Arc<RwLock<Renderer>>,
+        forge_data: Option<forge::fml2::ForgeData>,
+        renderer: Option<Arc<RwLock<Renderer>>>,
         hud_context: Arc<RwLock<HudContext>>,
     ) -> Result<Arc<Server>, protocol::Error> {
         let mut conn = protocol::Conn::new(address, protocol_version)?;
+        conn.start_recording()?;
 
         let tag = match fml_network_version {
             Some(1) => "\0FML\0",
@@ -158,198 +206,101 @@ impl Server {
             _ => panic!("unsupported FML network version: {:?}", fml_network_version),
         };
 
-        let host = conn.host.clone() + tag;
-        let port = conn.port;
-        conn.write_packet(protocol::packet::handshake::serverbound::Handshake {
-            protocol_version: protocol::VarInt(protocol_version),
-            host,
-            port,
-            next: protocol::VarInt(2),
-        })?;
-        conn.state = protocol::State::Login;
-        conn.write_packet(protocol::packet::login::serverbound::LoginStart {
-            username: profile.username.clone(),
-        })?;
-
-        use std::rc::Rc;
-        let (server_id, public_key, verify_token);
-        loop {
-            match conn.read_packet()? {
-                protocol::packet::Packet::SetInitialCompression(val) => {
-                    conn.set_compression(val.threshold.0);
-                }
-                protocol::packet::Packet::EncryptionRequest(val) => {
-                    server_id = Rc::new(val.server_id);
-                    public_key = Rc::new(val.public_key.data);
-                    verify_token = Rc::new(val.verify_token.data);
-                    break;
-                }
-                protocol::packet::Packet::EncryptionRequest_i16(val) => {
-                    server_id = Rc::new(val.server_id);
-                    public_key = Rc::new(val.public_key.data);
-                    verify_token = Rc::new(val.verify_token.data);
-                    break;
-                }
-                protocol::packet::Packet::LoginSuccess_String(val) => {
-                    warn!("Server is running in offline mode");
-                    debug!("Login: {} {}", val.username, val.uuid);
-                    conn.state = protocol::State::Play;
-                    let uuid = protocol::UUID::from_str(&val.uuid).unwrap();
-                    let server = Server::connect0(
-                        conn,
-                        protocol_version,
-                        forge_mods,
-                        uuid,
-                        resources,
-                        renderer,
-                        hud_context,
-                    );
-                    return Ok(server);
-                }
-                protocol::packet::Packet::LoginSuccess_UUID(val) => {
-                    warn!("Server is running in offline mode");
-                    debug!("Login: {} {:?}", val.username, val.uuid);
-                    conn.state = protocol::State::Play;
-                    let server = Server::connect0(
-                        conn,
-                        protocol_version,
-                        forge_mods,
-                        val.uuid,
-                        resources,
-                        renderer,
-                        hud_context,
-                    );
-
-                    return Ok(server);
-                }
-                protocol::packet::Packet::LoginDisconnect(val) => {
-                    return Err(protocol::Error::Disconnect(val.reason))
-                }
-                val => return Err(protocol::Error::Err(format!("Wrong packet 1: {:?}", val))),
-            };
-        }
-
-        let mut shared = [0; 16];
-        rand::thread_rng().fill(&mut shared);
-
-        let shared_e = rsa_public_encrypt_pkcs1::encrypt(&public_key, &shared).unwrap();
-        let token_e = rsa_public_encrypt_pkcs1::encrypt(&public_key, &verify_token).unwrap();
-
-        profile.join_server(&server_id, &shared, &public_key)?;
-
-        if protocol_version >= 47 {
-            conn.write_packet(protocol::packet::login::serverbound::EncryptionResponse {
-                shared_secret: protocol::LenPrefixedBytes::new(shared_e),
-                verify_token: protocol::LenPrefixedBytes::new(token_e),
-            })?;
-        } else {
-            conn.write_packet(
-                protocol::packet::login::serverbound::EncryptionResponse_i16 {
-                    shared_secret: protocol::LenPrefixedBytes::new(shared_e),
-                    verify_token: protocol::LenPrefixedBytes::new(token_e),
-                },
-            )?;
-        }
-
-        conn.enable_encyption(&shared);
-
-        let uuid;
-        let compression_threshold = conn.compression_threshold;
-        loop {
-            match conn.read_packet()? {
-                protocol::packet::Packet::SetInitialCompression(val) => {
-                    conn.set_compression(val.threshold.0);
-                }
-                protocol::packet::Packet::LoginSuccess_String(val) => {
-                    debug!("Login: {} {}", val.username, val.uuid);
-                    uuid = protocol::UUID::from_str(&val.uuid).unwrap();
-                    conn.state = protocol::State::Play;
-                    break;
-                }
-                protocol::packet::Packet::LoginSuccess_UUID(val) => {
-                    debug!("Login: {} {:?}", val.username, val.uuid);
-                    uuid = val.uuid;
-                    conn.state = protocol::State::Play;
-                    break;
-                }
-                protocol::packet::Packet::LoginDisconnect(val) => {
-                    return Err(protocol::Error::Disconnect(val.reason))
-                }
-                protocol::packet::Packet::LoginPluginRequest(req) => {
-                    match req.channel.as_ref() {
-                        "fml:loginwrapper" => {
-                            let mut cursor = std::io::Cursor::new(req.data);
-                            let channel: String = protocol::Serializable::read_from(&mut cursor)?;
-
-                            let (id, mut data) = protocol::Conn::read_raw_packet_from(
-                                &mut cursor,
-                                compression_threshold,
-                            )?;
-
-                            match channel.as_ref() {
-                                "fml:handshake" => {
-                                    let packet =
-                                        forge::fml2::FmlHandshake::packet_by_id(id, &mut data)?;
-                                    use forge::fml2::FmlHandshake::*;
-                                    match packet {
-                                        ModList {
+        let mut state = ConnectionState::Connecting;
+        let on_packet = |packet: &protocol::packet::Packet| state.update(packet);
+        let login = conn.login_with(&profile, tag, on_packet, |conn, req| {
+            match req.channel.as_ref() {
+                "fml:loginwrapper" => {
+                    let mut cursor = std::io::Cursor::new(req.data);
+                    let channel: String = protocol::Serializable::read_from(&mut cursor)?;
+
+                    let (id, mut data) = protocol::Conn::read_raw_packet_from(
+                        &mut cursor,
+                        conn.compression_threshold(),
+                    )?;
+
+                    match channel.as_ref() {
+                        "fml:handshake" => {
+                            let packet = forge::fml2::FmlHandshake::packet_by_id(id, &mut data)?;
+                            use forge::fml2::FmlHandshake::*;
+                            match packet {
+                                ModList {
+                                    mod_names,
+                                    channels,
+                                    registries,
+                                } => {
+                                    info!(
+                                        "ModList mod_names={:?} channels={:?} registries={:?}",
+                                        mod_names, channels, registries
+                                    );
+                                    let reply = match &forge_data {
+                                        Some(data) => {
+                                            data.mod_list_reply(mod_names, channels, registries)
+                                        }
+                                        None => ModListReply {
                                             mod_names,
                                             channels,
                                             registries,
-                                        } => {
-                                            info!("ModList mod_names={:?} channels={:?} registries={:?}", mod_names, channels, registries);
-                                            conn.write_fml2_handshake_plugin_message(
-                                                req.message_id,
-                                                Some(&ModListReply {
-                                                    mod_names,
-                                                    channels,
-                                                    registries,
-                                                }),
-                                            )?;
-                                        }
-                                        ServerRegistry {
-                                            name,
-                                            snapshot_present: _,
-                                            snapshot: _,
-                                        } => {
-                                            info!("ServerRegistry {:?}", name);
-                                            conn.write_fml2_handshake_plugin_message(
-                                                req.message_id,
-                                                Some(&Acknowledgement),
-                                            )?;
-                                        }
-                                        ConfigurationData { filename, contents } => {
-                                            info!(
-                                                "ConfigurationData filename={:?} contents={}",
-                                                filename,
-                                                String::from_utf8_lossy(&contents)
-                                            );
-                                            conn.write_fml2_handshake_plugin_message(
-                                                req.message_id,
-                                                Some(&Acknowledgement),
-                                            )?;
-                                        }
-                                        _ => unimplemented!(),
-                                    }
+                                        },
+                                    };
+                                    conn.write_fml2_handshake_plugin_message(
+                                        req.message_id,
+                                        Some(&reply),
+                                    )?;
+                                }
+                                ServerRegistry {
+                                    name,
+                                    snapshot_present: _,
+                                    snapshot: _,
+                                } => {
+                                    info!("ServerRegistry {:?}", name);
+                                    conn.write_fml2_handshake_plugin_message(
+                                        req.message_id,
+                                        Some(&Acknowledgement),
+                                    )?;
+                                }
+                                ConfigurationData { filename, contents } => {
+                                    info!(
+                                        "ConfigurationData filename={:?} contents={}",
+                                        filename,
+                                        String::from_utf8_lossy(&contents)
+                                    );
+                                    conn.write_fml2_handshake_plugin_message(
+                                        req.message_id,
+                                        Some(&Acknowledgement),
+                                    )?;
+                                }
+                                packet => {
+                                    return Err(protocol::Error::Err(format!(
+                                        "Unexpected FML2 handshake packet: {:?}",
+                                        packet
+                                    )))
                                 }
-                                _ => panic!(
-                                    "unknown LoginPluginRequest fml:loginwrapper channel: {:?}",
-                                    channel
-                                ),
                             }
                         }
-                        _ => panic!("unsupported LoginPluginRequest channel: {:?}", req.channel),
+                        _ => {
+                            warn!("Declining unsupported Forge login channel: {:?}", channel);
+                            conn.write_fml2_handshake_plugin_message(req.message_id, None)?;
+                        }
                     }
                 }
-                val => return Err(protocol::Error::Err(format!("Wrong packet 2: {:?}", val))),
+                _ => {
+                    warn!(
+                        "Declining unsupported LoginPluginRequest channel: {:?}",
+                        req.channel
+                    );
+                    conn.write_login_plugin_response(req.message_id, false, &[])?;
+                }
             }
-        }
+            Ok(())
+        })?;
 
         let server = Server::connect0(
             conn,
             protocol_version,
             forge_mods,
-            uuid,
+            fml_network_version,
+            login.uuid,
             resources,
             renderer,
             hud_context,
@@ -362,22 +313,27 @@ impl Server {
         conn: Conn,
         protocol_version: i32,
         forge_mods: Vec<forge::ForgeMod>,
+        fml_network_version: Option<i64>,
         uuid: protocol::UUID,
         resources: Arc<RwLock<resources::Manager>>,
-        renderer: Arc<RwLock<Renderer>>,
+        renderer: Option<Arc<RwLock<Renderer>>>,
         hud_context: Arc<RwLock<HudContext>>,
     ) -> Arc<Server> {
         let server_callback = Arc::new(Mutex::new(None));
         let inner_server = server_callback.clone();
         let mut inner_server = inner_server.lock();
-        Self::spawn_reader(conn.clone(), server_callback.clone());
+        let reader = Self::spawn_reader(conn.clone(), server_callback.clone());
         let light_updater = Self::spawn_light_updater(server_callback.clone());
-        let render_list_computer =
-            Self::spawn_render_list_computer(server_callback, renderer.clone());
+        let render_list_computer = match renderer.clone() {
+            Some(renderer) => Self::spawn_render_list_computer(server_callback, renderer),
+            // Nothing to render without a renderer, requests go nowhere instead of piling up
+            None => (unbounded().0, crossbeam_channel::never()),
+        };
         let conn = Arc::new(RwLock::new(Some(conn)));
         let server = Arc::new(Server::new(
             protocol_version,
             forge_mods,
+            fml_network_version,
             uuid,
             resources,
             conn,
@@ -385,25 +341,39 @@ impl Server {
             render_list_computer.0.clone(),
             render_list_computer.1,
             hud_context,
-            &renderer.read(),
+            renderer.as_ref().map(|renderer| renderer.read()).as_deref(),
         ));
         server.hud_context.clone().write().server = Some(server.clone());
+        *server.reader.lock() = Some(reader);
 
         let actual_server = server.clone();
         inner_server.replace(actual_server);
-        render_list_computer.0.send(true).unwrap();
+        if renderer.is_some() {
+            render_list_computer.0.send(true).unwrap();
+        }
         server
     }
 
-    fn spawn_reader(mut read: protocol::Conn, server: Arc<Mutex<Option<Arc<Server>>>>) {
+    fn spawn_reader(
+        mut read: protocol::Conn,
+        server: Arc<Mutex<Option<Arc<Server>>>>,
+    ) -> JoinHandle<()> {
         thread::spawn(move || loop {
             let server = server.clone().lock().as_ref().unwrap().clone();
             let pck = read.read_packet();
+            if let Ok(pck) = &pck {
+                server.connection_state.write().update(pck);
+                server.events.dispatch_packet(pck);
+            }
             match pck {
                 Ok(pck) => match pck {
                     Packet::KeepAliveClientbound_i64(keep_alive) => {
                         server.on_keep_alive_i64(keep_alive);
                     }
+                    Packet::SetCompression(compression) => {
+                        // Shared with the writing half of the connection
+                        read.set_compression(compression.threshold.0);
+                    }
                     Packet::KeepAliveClientbound_VarInt(keep_alive) => {
                         server.on_keep_alive_varint(keep_alive);
                     }
@@ -440,6 +410,12 @@ impl Server {
                     Packet::MultiBlockChange_u16(block_change) => {
                         server.on_multi_block_change_u16(block_change);
                     }
+                    Packet::BlockAction(block_action) => {
+                        server.on_block_action(block_action);
+                    }
+                    Packet::BlockAction_u16(block_action) => {
+                        server.on_block_action_u16(block_action);
+                    }
                     Packet::UpdateBlockEntity(block_update) => {
                         server.on_block_entity_update(block_update);
                     }
@@ -533,6 +509,18 @@ impl Server {
                     Packet::Respawn_WorldName(respawn) => {
                         server.on_respawn_worldname(respawn);
                     }
+                    Packet::EntityMetadata(entity_metadata) => {
+                        server.on_entity_metadata(
+                            entity_metadata.entity_id.0,
+                            &entity_metadata.metadata,
+                        );
+                    }
+                    Packet::EntityMetadata_i32(entity_metadata) => {
+                        server.on_entity_metadata(
+                            entity_metadata.entity_id,
+                            &entity_metadata.metadata,
+                        );
+                    }
                     Packet::EntityTeleport_f64(entity_teleport) => {
                         server.on_entity_teleport_f64(entity_teleport);
                     }
@@ -566,6 +554,45 @@ impl Server {
                     Packet::SpawnPlayer_f64_NoMeta(spawn) => {
                         server.on_player_spawn_f64_nometa(spawn);
                     }
+                    Packet::SpawnMob_NoMeta(spawn) => {
+                        server.on_mob_spawn(spawn.entity_id.0, spawn.x, spawn.y, spawn.z, None);
+                    }
+                    Packet::SpawnMob_WithMeta(spawn) => {
+                        server.on_mob_spawn(
+                            spawn.entity_id.0,
+                            spawn.x,
+                            spawn.y,
+                            spawn.z,
+                            Some(&spawn.metadata),
+                        );
+                    }
+                    Packet::SpawnMob_u8(spawn) => {
+                        server.on_mob_spawn(
+                            spawn.entity_id.0,
+                            spawn.x,
+                            spawn.y,
+                            spawn.z,
+                            Some(&spawn.metadata),
+                        );
+                    }
+                    Packet::SpawnMob_u8_i32(spawn) => {
+                        server.on_mob_spawn(
+                            spawn.entity_id.0,
+                            f64::from(spawn.x),
+                            f64::from(spawn.y),
+                            f64::from(spawn.z),
+                            Some(&spawn.metadata),
+                        );
+                    }
+                    Packet::SpawnMob_u8_i32_NoUUID(spawn) => {
+                        server.on_mob_spawn(
+                            spawn.entity_id.0,
+                            f64::from(spawn.x),
+                            f64::from(spawn.y),
+                            f64::from(spawn.z),
+                            Some(&spawn.metadata),
+                        );
+                    }
                     Packet::PlayerInfo(player_info) => {
                         server.on_player_info(player_info);
                     }
@@ -603,6 +630,9 @@ impl Server {
                     Packet::ChangeGameState(game_state) => {
                         server.on_game_state_change(game_state);
                     }
+                    Packet::PlayerAbilities(abilities) => {
+                        server.on_player_abilities(abilities);
+                    }
                     Packet::UpdateHealth(update_health) => {
                         server.on_update_health(
                             update_health.health,
@@ -632,6 +662,136 @@ impl Server {
                     Packet::ServerMessage_Sender(server_message) => {
                         server.on_servermessage_sender(server_message);
                     }
+                    Packet::TabCompleteReply(reply) => {
+                        server.on_tab_complete_reply(reply);
+                    }
+                    Packet::TabCompleteReply_TransactionId(reply) => {
+                        server.on_tab_complete_reply_transaction_id(reply);
+                    }
+                    Packet::NamedSoundEffect(effect) => {
+                        server.on_sound_effect(
+                            SoundName::Event(effect.name),
+                            effect.category.0,
+                            (effect.x, effect.y, effect.z),
+                            effect.volume,
+                            effect.pitch,
+                        );
+                    }
+                    Packet::NamedSoundEffect_u8(effect) => {
+                        server.on_sound_effect(
+                            SoundName::Event(effect.name),
+                            effect.category.0,
+                            (effect.x, effect.y, effect.z),
+                            effect.volume,
+                            f32::from(effect.pitch) / 63.0,
+                        );
+                    }
+                    Packet::NamedSoundEffect_u8_NoCategory(effect) => {
+                        // Only the volume settings decide the category before 1.9
+                        server.on_sound_effect(
+                            SoundName::Event(effect.name),
+                            SoundCategory::Master as i32,
+                            (effect.x, effect.y, effect.z),
+                            effect.volume,
+                            f32::from(effect.pitch) / 63.0,
+                        );
+                    }
+                    Packet::Effect(effect) => {
+                        server.on_effect(effect.effect_id, effect.location, effect.data);
+                    }
+                    Packet::Effect_u8y(effect) => {
+                        server.on_effect(
+                            effect.effect_id,
+                            Position::new(effect.x, effect.y as i32, effect.z),
+                            effect.data,
+                        );
+                    }
+                    Packet::Particle_f64(p) => {
+                        let particle = server.modern_particle(
+                            p.particle_id,
+                            p.block_state.0,
+                            [p.red, p.green, p.blue],
+                        );
+                        server.on_particle(
+                            particle,
+                            cgmath::Vector3::new(p.x, p.y, p.z),
+                            cgmath::Vector3::new(p.offset_x, p.offset_y, p.offset_z),
+                            p.speed,
+                            p.count,
+                        );
+                    }
+                    Packet::Particle_Data(p) => {
+                        let particle = server.modern_particle(
+                            p.particle_id,
+                            p.block_state.0,
+                            [p.red, p.green, p.blue],
+                        );
+                        server.on_particle(
+                            particle,
+                            cgmath::Vector3::new(p.x, p.y, p.z).cast().unwrap(),
+                            cgmath::Vector3::new(p.offset_x, p.offset_y, p.offset_z),
+                            p.speed,
+                            p.count,
+                        );
+                    }
+                    Packet::Particle_Data13(p) => {
+                        let particle = server.modern_particle(
+                            p.particle_id,
+                            p.block_state.0,
+                            [p.red, p.green, p.blue],
+                        );
+                        server.on_particle(
+                            particle,
+                            cgmath::Vector3::new(p.x, p.y, p.z).cast().unwrap(),
+                            cgmath::Vector3::new(p.offset_x, p.offset_y, p.offset_z),
+                            p.speed,
+                            p.count,
+                        );
+                    }
+                    Packet::Particle_VarIntArray(p) => {
+                        let particle = server.legacy_particle(p.particle_id, p.data1.0);
+                        server.on_particle(
+                            particle,
+                            cgmath::Vector3::new(p.x, p.y, p.z).cast().unwrap(),
+                            cgmath::Vector3::new(p.offset_x, p.offset_y, p.offset_z),
+                            p.speed,
+                            p.count,
+                        );
+                    }
+                    Packet::Particle_Named(p) => {
+                        let particle = server.named_particle(&p.particle_id);
+                        server.on_particle(
+                            particle,
+                            cgmath::Vector3::new(p.x, p.y, p.z).cast().unwrap(),
+                            cgmath::Vector3::new(p.offset_x, p.offset_y, p.offset_z),
+                            p.speed,
+                            p.count,
+                        );
+                    }
+                    Packet::SoundEffect(effect) => {
+                        server.on_sound_effect(
+                            SoundName::Id(effect.name.0),
+                            effect.category.0,
+                            (effect.x, effect.y, effect.z),
+                            effect.volume,
+                            effect.pitch,
+                        );
+                    }
+                    Packet::SoundEffect_u8(effect) => {
+                        server.on_sound_effect(
+                            SoundName::Id(effect.name.0),
+                            effect.category.0,
+                            (effect.x, effect.y, effect.z),
+                            effect.volume,
+                            f32::from(effect.pitch) / 63.0,
+                        );
+                    }
+                    Packet::ResourcePackSend(pack) => {
+                        server.on_resource_pack_send(pack);
+                    }
+                    Packet::PlayerListHeaderFooter(header_footer) => {
+                        server.on_player_list_header_footer(header_footer);
+                    }
                     Packet::PlayerInfo_String(player_info) => {
                         server.on_player_info_string(player_info);
                     }
@@ -641,12 +801,22 @@ impl Server {
                     Packet::PluginMessageClientbound(plugin_message) => {
                         server.on_plugin_message_clientbound_1(plugin_message);
                     }
+                    Packet::BossBar(boss_bar) => {
+                        server.on_boss_bar(boss_bar);
+                    }
                     Packet::SetExperience(set_exp) => {
-                        server
-                            .hud_context
-                            .clone()
-                            .write()
-                            .update_exp(set_exp.experience_bar, set_exp.level.0);
+                        server.hud_context.clone().write().update_exp(
+                            set_exp.experience_bar,
+                            set_exp.level.0,
+                            set_exp.total_experience.0,
+                        );
+                    }
+                    Packet::SetExperience_i16(set_exp) => {
+                        server.hud_context.clone().write().update_exp(
+                            set_exp.experience_bar,
+                            set_exp.level as i32,
+                            set_exp.total_experience as i32,
+                        );
                     }
                     Packet::SetCurrentHotbarSlot(set_slot) => {
                         if set_slot.slot <= 8 {
@@ -713,6 +883,10 @@ impl Server {
                     }
                 },
                 Err(err) => {
+                    // The connection was closed on our side, nothing more will arrive
+                    if server.conn.read().is_none() {
+                        break;
+                    }
                     if server
                         .disconnect_data
                         .clone()
@@ -796,6 +970,7 @@ impl Server {
         let server = Arc::new(Server::new(
             protocol::SUPPORTED_PROTOCOLS[0],
             vec![],
+            None,
             protocol::UUID::default(),
             resources,
             Arc::new(RwLock::new(None)),
@@ -803,7 +978,7 @@ impl Server {
             render_list.0,
             render_list.1,
             Arc::new(RwLock::new(HudContext::new())),
-            &renderer.read(),
+            Some(&renderer.read()),
         ));
         inner_server.replace(server.clone());
         let mut rng = rand::thread_rng();
@@ -892,6 +1067,7 @@ impl Server {
     fn new(
         protocol_version: i32,
         forge_mods: Vec<forge::ForgeMod>,
+        fml_network_version: Option<i64>,
         uuid: protocol::UUID,
         resources: Arc<RwLock<resources::Manager>>,
         conn: Arc<RwLock<Option<protocol::Conn>>>,
@@ -899,7 +1075,7 @@ impl Server {
         render_list_computer: Sender<bool>,
         render_list_computer_notify: Receiver<bool>,
         hud_context: Arc<RwLock<HudContext>>,
-        renderer: &Renderer,
+        renderer: Option<&Renderer>,
     ) -> Server {
         let mut entities = ecs::Manager::new();
         entity::add_systems(&mut entities);
@@ -924,7 +1100,23 @@ impl Server {
             protocol_version,
             mapped_protocol_version: Version::from_id(protocol_version as u32),
             forge_mods,
+            fml_network_version,
             disconnect_data: Arc::new(RwLock::new(DisconnectData::default())),
+            // Servers are only created once the login has completed
+            connection_state: RwLock::new(ConnectionState::DownloadingTerrain),
+            keep_alive: RwLock::new(KeepAlive::new(Instant::now(), DEFAULT_KEEP_ALIVE_TIMEOUT)),
+            render_distance: RwLock::new(settings::clamp_render_distance(
+                settings::DEFAULT_RENDER_DISTANCE,
+            )),
+            entity_id: RwLock::new(0),
+            sprinting_sent: RwLock::new(false),
+            sneaking_sent: RwLock::new(false),
+            // 1.12.2's
+            legacy_block_ids: if protocol_version >= 404 {
+                Some(block::VanillaIDMap::new(340))
+            } else {
+                None
+            },
 
             world: Arc::new(world::World::new(protocol_version, light_updater)),
             world_data: Arc::new(RwLock::new(WorldData::default())),
@@ -941,22 +1133,29 @@ impl Server {
             gamemode: entities.get_key(),
             rotation: entities.get_key(),
             target_rotation: entities.get_key(),
+            interpolation: entities.get_key(),
             //
             entities: Arc::new(RwLock::new(entities)),
             player: Arc::new(RwLock::new(None)),
             entity_map: Arc::new(RwLock::new(HashMap::with_hasher(
                 BuildHasherDefault::default(),
             ))),
-            players: Arc::new(RwLock::new(HashMap::with_hasher(
-                BuildHasherDefault::default(),
-            ))),
-
-            tick_timer: RwLock::from(0.0),
-            entity_tick_timer: RwLock::from(0.0),
-            received_chat_at: Arc::new(RwLock::new(None)),
+            players: Arc::new(RwLock::new(PlayerList::new())),
+
+            chat_messages: Mutex::new(VecDeque::new()),
+            tab_complete_request: Mutex::new((0, String::new())),
+            tab_completions: Mutex::new(None),
+            sounds: Mutex::new(vec![]),
+            resource_pack_request: Mutex::new(None),
+            resource_pack_download: Mutex::new(None),
+            events: events::Events::default(),
             sun_model: RwLock::new(None),
+            weather: RwLock::new(weather::Weather::default()),
+            weather_model: RwLock::new(None),
+            particles: RwLock::new(particle::Particles::default()),
 
             target_info: Arc::new(RwLock::new(target::Info::new())),
+            digging: RwLock::new(Digging::default()),
             render_list_computer,
             render_list_computer_notify,
             hud_context,
@@ -966,11 +1165,43 @@ impl Server {
             dead: RwLock::new(false),
             just_died: RwLock::new(false),
             close_death_screen: RwLock::new(false),
+            terrain_progress: Arc::new(RwLock::new(TerrainProgress::default())),
+            terrain_screen_opened: RwLock::new(None),
+            terrain_loaded: RwLock::new(false),
+            chunks_unloaded_around: RwLock::new(None),
+            toggle_perspective: RwLock::new(false),
+            reader: Mutex::new(None),
+        }
+    }
+
+    /// Leaves the server when quitting, closing the connection so the server sees a normal
+    /// disconnect instead of a timeout.
+    pub fn shutdown(&self) {
+        close_connection(&self.conn);
+        self.disconnect(None);
+        if let Some(reader) = self.reader.lock().take() {
+            drop(reader.join());
         }
     }
 
     pub fn disconnect(&self, reason: Option<format::Component>) {
         self.conn.clone().write().take();
+        if protocol::is_packet_timings() {
+            let timings = protocol::timings::take_packet_timings();
+            if !timings.is_empty() {
+                info!("{}", timings);
+            }
+        }
+        {
+            let mut state = self.connection_state.write();
+            if !matches!(*state, ConnectionState::Disconnected(_)) {
+                state.set(ConnectionState::Disconnected(
+                    reason
+                        .clone()
+                        .unwrap_or_else(|| Component::Text(TextComponent::new("Disconnected"))),
+                ));
+            }
+        }
         self.disconnect_data.clone().write().disconnect_reason = reason;
         if let Some(player) = self.player.clone().write().take() {
             self.entities.clone().write().remove_entity(player);
@@ -978,12 +1209,74 @@ impl Server {
         self.disconnect_data.clone().write().just_disconnected = true;
     }
 
+    /// Forge mods the server reported when it was pinged, empty for vanilla servers.
+    pub fn forge_mods(&self) -> &[forge::ForgeMod] {
+        &self.forge_mods
+    }
+
+    /// FML network protocol version of a Forge server, `None` for vanilla servers.
+    pub fn fml_network_version(&self) -> Option<i64> {
+        self.fml_network_version
+    }
+
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state.read().clone()
+    }
+
+    /// When the server last sent a keep-alive, or the connection was made if it hasn't yet.
+    pub fn last_keep_alive(&self) -> Instant {
+        self.keep_alive.read().last_received()
+    }
+
+    pub fn set_keep_alive_timeout(&self, timeout: Duration) {
+        self.keep_alive.write().set_timeout(timeout);
+    }
+
+    /// Changes how many chunks around the player are kept loaded. Once in game the new view
+    /// distance is sent to the server, so it can send the chunks that came into range.
+    pub fn set_render_distance(&self, render_distance: i32) {
+        if *self.render_distance.read() == render_distance {
+            return;
+        }
+        *self.render_distance.write() = render_distance;
+        if self.player.clone().read().is_some() {
+            self.send_client_settings();
+        }
+    }
+
+    /// Disconnects if the server stopped sending keep-alives.
+    fn check_keep_alive(&self) {
+        if self.is_connected() && self.keep_alive.read().timed_out(Instant::now()) {
+            warn!("No keep-alive received in time, disconnecting");
+            self.disconnect(Some(Component::Text(TextComponent::new("Timed out"))));
+        }
+    }
+
+    pub fn is_sprinting(&self) -> bool {
+        self.player.clone().read().map_or(false, |player| {
+            self.entities
+                .clone()
+                .read()
+                .get_component(player, self.player_movement)
+                .map_or(false, |movement| movement.is_sprinting())
+        })
+    }
+
     pub fn is_connected(&self) -> bool {
         let tmp = self.conn.clone();
         return tmp.read().is_some();
     }
 
-    pub fn tick(&self, renderer: Arc<RwLock<render::Renderer>>, delta: f64, game: &mut Game) {
+    /// Advances the server by one rendered frame, running `ticks` fixed logic ticks first.
+    /// Rendering happens at display rate with entities interpolated by `game.partial_tick`.
+    pub fn tick(
+        &self,
+        renderer: Arc<RwLock<render::Renderer>>,
+        delta: f64,
+        ticks: u32,
+        game: &mut Game,
+    ) {
+        self.check_keep_alive();
         let start = SystemTime::now();
         let time = start.duration_since(UNIX_EPOCH).unwrap().as_millis();
         if *self.fps_start.read() + 1000 < time {
@@ -1001,6 +1294,8 @@ impl Server {
             game.screen_sys.pop_screen();
             game.focused = true;
         }
+        self.downloading_terrain_tick(game);
+        self.resource_pack_tick(game);
         let version = self.resources.read().version();
         if version != *self.version.read() {
             *self.version.write() = version;
@@ -1012,8 +1307,24 @@ impl Server {
             self.sun_model.write().replace(sun::SunModel::new(renderer));
         }
 
+        if *self.toggle_perspective.read() {
+            *self.toggle_perspective.write() = false;
+            renderer.camera.perspective = renderer.camera.perspective.next();
+        }
+
         // Copy to camera
         if let Some(player) = *self.player.clone().read() {
+            let perspective = renderer.camera.perspective;
+            let held_item = self.with_held_item(|item| item.material.texture_locations());
+            if let Some(model) = self
+                .entities
+                .clone()
+                .write()
+                .get_component_mut_direct::<entity::player::PlayerModel>(player)
+            {
+                model.set_first_person(perspective == render::Perspective::FirstPerson);
+                model.set_held_item(held_item);
+            }
             let position = self
                 .entities
                 .clone()
@@ -1026,20 +1337,101 @@ impl Server {
                 .read()
                 .get_component(player, self.rotation)
                 .unwrap();
-            renderer.camera.pos =
-                cgmath::Point3::from_vec(position.position + cgmath::Vector3::new(0.0, 1.62, 0.0));
-            renderer.camera.yaw = rotation.yaw;
-            renderer.camera.pitch = rotation.pitch;
+            let movement = self
+                .entities
+                .clone()
+                .read()
+                .get_component(player, self.player_movement);
+            let eye_height =
+                movement.map_or(entity::player::EYE_HEIGHT, |movement| movement.eye_height());
+            let (sway, bob) = match movement {
+                Some(movement) if settings::motion_effects(&game.vars).view_bobbing => {
+                    movement.view_bob(game.partial_tick)
+                }
+                _ => (0.0, 0.0),
+            };
+            let eye = cgmath::Point3::from_vec(
+                position.position + cgmath::Vector3::new(0.0, eye_height, 0.0),
+            );
+            renderer.camera.eye = eye;
+            renderer.camera.look = render::look_direction(rotation.yaw, rotation.pitch);
+            renderer.camera.pos = match perspective.camera_direction(rotation.yaw, rotation.pitch) {
+                Some(direction) => {
+                    eye + direction * self.third_person_distance(eye.to_vec(), direction)
+                }
+                // Bobbing only moves the view, not where the player looks from
+                None => {
+                    let side =
+                        render::look_direction(rotation.yaw + std::f64::consts::FRAC_PI_2, 0.0);
+                    eye + side * sway + cgmath::Vector3::new(0.0, bob, 0.0)
+                }
+            };
+            let (yaw, pitch) = perspective.camera_rotation(rotation.yaw, rotation.pitch);
+            renderer.camera.yaw = yaw;
+            renderer.camera.pitch = pitch;
+            if self.hud_context.clone().read().debug {
+                self.hud_context
+                    .clone()
+                    .write()
+                    .update_debug_info(DebugInfo {
+                        position: (
+                            position.position.x,
+                            position.position.y,
+                            position.position.z,
+                        ),
+                        yaw: rotation.yaw,
+                        pitch: rotation.pitch,
+                        loaded_chunks: self.world.chunks.len(),
+                        drawn_chunks: renderer.chunks_drawn,
+                        culled_chunks: renderer.chunks_culled,
+                    });
+            }
+            let coordinates = if *game.vars.get(settings::CL_SHOW_COORDINATES) {
+                let (x, y, z) = (
+                    position.position.x.floor() as i32,
+                    position.position.y.floor() as i32,
+                    position.position.z.floor() as i32,
+                );
+                Some(Coordinates {
+                    block: (x, y, z),
+                    biome: self.world.get_biome(x, z).name,
+                })
+            } else {
+                None
+            };
+            self.hud_context
+                .clone()
+                .write()
+                .update_coordinates(coordinates);
+            self.hud_context
+                .clone()
+                .write()
+                .set_chat_options(settings::chat_options(&game.vars));
         }
-        self.entity_tick(renderer, delta, game.focused, *self.dead.read());
+        self.entity_tick(
+            renderer,
+            delta,
+            ticks,
+            game.partial_tick,
+            game.focused,
+            *self.dead.read(),
+        );
 
-        *self.tick_timer.write() += delta;
-        while *self.tick_timer.read() >= 3.0 && self.is_connected() {
+        for _ in 0..ticks {
+            if !self.is_connected() {
+                break;
+            }
             self.minecraft_tick();
-            *self.tick_timer.write() -= 3.0;
         }
 
         self.update_time(renderer, delta);
+        self.update_weather(renderer, delta, *game.vars.get(settings::R_PARTICLES));
+        {
+            let mut particles = self.particles.write();
+            particles.density = settings::particle_density(&game.vars);
+            particles.tick(&self.world, delta);
+            particles.render(renderer, &game.chunk_builder);
+        }
         if let Some(sun_model) = self.sun_model.write().as_mut() {
             sun_model.tick(
                 renderer,
@@ -1050,24 +1442,37 @@ impl Server {
         let world = self.world.clone();
         world.tick(&mut self.entities.clone().write());
 
+        self.unload_chunks_tick();
         if self.player.clone().read().is_some() {
             if *self.just_died.read() {
                 *self.just_died.write() = false;
                 while game.screen_sys.is_current_closable() {
                     game.screen_sys.pop_screen();
                 }
-                game.screen_sys.add_screen(Box::new(Respawn::new(0))); // TODO: Use the correct score!
+                let score = self.hud_context.read().get_total_exp();
+                game.screen_sys
+                    .add_screen(Box::new(Respawn::new(score.max(0) as u32)));
                 game.focused = false;
             }
             let world = self.world.clone();
-            if let Some((pos, bl, _, _)) = target::trace_ray(
-                &world,
-                4.0,
-                renderer.camera.pos.to_vec(),
-                renderer.view_vector.cast().unwrap(),
-                target::test_block,
-            ) {
-                self.target_info.clone().write().update(renderer, pos, bl);
+            // Nothing is targeted while a screen is open over the world
+            let hit = if game.screen_sys.is_current_closable() {
+                None
+            } else {
+                world.raycast(renderer.camera.eye.to_vec(), renderer.camera.look, 4.0)
+            };
+            if let Some(hit) = hit {
+                let crack = self
+                    .digging
+                    .read()
+                    .target
+                    .as_ref()
+                    .filter(|target| target.pos == hit.position && target.stage() > 0)
+                    .map(|target| (hit.position, hit.block, target.stage()));
+                let target_info = self.target_info.clone();
+                let mut target_info = target_info.write();
+                target_info.update(renderer, hit.position, hit.block, hit.face);
+                target_info.update_crack(renderer, crack);
             } else {
                 self.target_info.clone().write().clear(renderer);
             }
@@ -1076,27 +1481,53 @@ impl Server {
         }
     }
 
-    fn entity_tick(&self, renderer: &mut render::Renderer, delta: f64, focused: bool, dead: bool) {
+    /// Ticks the connection and world without a renderer, used by the headless mode.
+    /// Entities aren't simulated since their systems depend on the renderer.
+    pub fn tick_headless(&self, ticks: u32) {
+        self.check_keep_alive();
+        // There's nothing to show a resource pack on
+        if let Some(request) = self.resource_pack_request.lock().take() {
+            self.decline_resource_pack(&request);
+        }
+        for _ in 0..ticks {
+            if !self.is_connected() {
+                break;
+            }
+            self.minecraft_tick();
+        }
+        self.world.clone().tick(&mut self.entities.clone().write());
+    }
+
+    fn entity_tick(
+        &self,
+        renderer: &mut render::Renderer,
+        delta: f64,
+        ticks: u32,
+        partial_tick: f64,
+        focused: bool,
+        dead: bool,
+    ) {
         let world_entity = self.entities.clone().read().get_world();
         // Update the game's state for entities to read
-        self.entities
-            .clone()
-            .write()
-            .get_component_mut(world_entity, self.game_info)
-            .unwrap()
-            .delta = delta;
+        {
+            let entities = self.entities.clone();
+            let mut entities = entities.write();
+            let game_info = entities
+                .get_component_mut(world_entity, self.game_info)
+                .unwrap();
+            game_info.delta = delta;
+            game_info.partial_tick = partial_tick;
+        }
 
         if self.is_connected() || self.disconnect_data.clone().read().just_disconnected {
             // Allow an extra tick when disconnected to clean up
             self.disconnect_data.clone().write().just_disconnected = false;
-            *self.entity_tick_timer.write() += delta;
-            while *self.entity_tick_timer.read() >= 3.0 {
+            for _ in 0..ticks {
                 let world = self.world.clone();
                 self.entities
                     .clone()
                     .write()
                     .tick(&world, renderer, focused, dead);
-                *self.entity_tick_timer.write() -= 3.0;
             }
             let world = self.world.clone();
             self.entities
@@ -1115,6 +1546,10 @@ impl Server {
         if let Some(sun_model) = self.sun_model.write().as_mut() {
             sun_model.remove(renderer);
         }
+        if let Some(mut weather_model) = self.weather_model.write().take() {
+            weather_model.remove(renderer);
+        }
+        self.particles.write().remove(renderer);
         self.target_info.clone().write().clear(renderer);
     }
 
@@ -1137,31 +1572,44 @@ impl Server {
             let time = self.world_data.clone().read().world_time_target;
             self.world_data.clone().write().world_time = time;
         }
-        renderer.sky_offset = self.calculate_sky_offset();
+        let world_time = self.world_data.clone().read().world_time;
+        renderer.sky_offset =
+            render::sky::sky_light(world_time) * self.weather.read().sky_light_factor();
+        renderer.world_time = world_time;
     }
 
-    fn calculate_sky_offset(&self) -> f32 {
-        use std::f32::consts::PI;
-        let mut offset =
-            ((1.0 + self.world_data.clone().read().world_time as f32) / 24000.0) - 0.25;
-        if offset < 0.0 {
-            offset += 1.0;
-        } else if offset > 1.0 {
-            offset -= 1.0;
-        }
-
-        let prev_offset = offset;
-        offset = 1.0 - (((offset * PI).cos() + 1.0) / 2.0);
-        offset = prev_offset + (offset - prev_offset) / 3.0;
+    fn update_weather(&self, renderer: &mut render::Renderer, delta: f64, particles: bool) {
+        let mut weather = self.weather.write();
+        weather.tick(delta);
+        renderer.rain_level = weather.rain_level;
 
-        offset = 1.0 - ((offset * PI * 2.0).cos() * 2.0 + 0.2);
-        if offset > 1.0 {
-            offset = 1.0;
-        } else if offset < 0.0 {
-            offset = 0.0;
+        let camera = renderer.camera.pos;
+        let precipitation = weather::Precipitation::for_biome(
+            self.world
+                .get_biome(camera.x.floor() as i32, camera.z.floor() as i32),
+        );
+        let mut weather_model = self.weather_model.write();
+        if !particles
+            || precipitation == weather::Precipitation::None
+            || (!weather.raining && weather.rain_level <= 0.0)
+        {
+            if let Some(mut weather_model) = weather_model.take() {
+                weather_model.remove(renderer);
+            }
+            return;
+        }
+        if weather_model
+            .as_ref()
+            .map_or(true, |model| model.precipitation != precipitation)
+        {
+            if let Some(mut old) = weather_model.take() {
+                old.remove(renderer);
+            }
+            weather_model.replace(weather::WeatherModel::new(renderer, precipitation));
+        }
+        if let Some(weather_model) = weather_model.as_mut() {
+            weather_model.tick(renderer, delta, weather.rain_level);
         }
-        offset = 1.0 - offset;
-        offset * 0.8 + 0.2
     }
 
     pub fn minecraft_tick(&self) {
@@ -1202,6 +1650,42 @@ impl Server {
                 on_ground
             };
 
+            // Other players see the pose through these, servers since 1.13 also
+            // switch to the swimming pose when sprinting in water
+            let sneaking = movement.is_sneaking();
+            if sneaking != *self.sneaking_sent.read() {
+                *self.sneaking_sent.write() = sneaking;
+                self.send_player_action(if sneaking {
+                    PLAYER_ACTION_START_SNEAK
+                } else {
+                    PLAYER_ACTION_STOP_SNEAK
+                });
+            }
+            let gamemode = *self
+                .entities
+                .clone()
+                .read()
+                .get_component(player, self.gamemode)
+                .unwrap();
+            self.dig_tick(gamemode, on_ground);
+
+            if let Some(flying) = movement.take_flying_change() {
+                self.send_flying(entity::player::Abilities {
+                    flying,
+                    ..movement.abilities()
+                });
+            }
+
+            let sprinting = movement.is_sprinting();
+            if sprinting != *self.sprinting_sent.read() {
+                *self.sprinting_sent.write() = sprinting;
+                self.send_player_action(if sprinting {
+                    PLAYER_ACTION_START_SPRINT
+                } else {
+                    PLAYER_ACTION_STOP_SPRINT
+                });
+            }
+
             // Sync our position to the server
             // Use the smaller packets when possible
             if self.protocol_version >= 47 {
@@ -1218,7 +1702,7 @@ impl Server {
                 let packet = packet::play::serverbound::PlayerPositionLook_HeadY {
                     x: position.position.x,
                     feet_y: position.position.y,
-                    head_y: position.position.y + 1.62,
+                    head_y: position.position.y + movement.eye_height(),
                     z: position.position.z,
                     yaw: -(rotation.yaw as f32) * (180.0 / PI),
                     pitch: (-rotation.pitch as f32) * (180.0 / PI) + 180.0,
@@ -1229,6 +1713,46 @@ impl Server {
         }
     }
 
+    /// Sends an entity action such as `PLAYER_ACTION_START_SPRINT` for the local player.
+    fn send_player_action(&self, action_id: i32) {
+        let entity_id = *self.entity_id.read();
+        if self.protocol_version >= 47 {
+            self.write_packet(packet::play::serverbound::PlayerAction {
+                entity_id: protocol::VarInt(entity_id),
+                action_id: protocol::VarInt(action_id),
+                jump_boost: protocol::VarInt(0),
+            });
+        } else {
+            // 1.7 numbers the actions from 1
+            self.write_packet(packet::play::serverbound::PlayerAction_i32 {
+                entity_id,
+                action_id: (action_id + 1) as i8,
+                jump_boost: 0,
+            });
+        }
+    }
+
+    /// How far the third person camera can move away from `eye` along `direction` before
+    /// it would be inside a block.
+    fn third_person_distance(
+        &self,
+        eye: cgmath::Vector3<f64>,
+        direction: cgmath::Vector3<f64>,
+    ) -> f64 {
+        match self
+            .world
+            .raycast(eye, direction, render::THIRD_PERSON_DISTANCE)
+        {
+            Some(hit) => {
+                // Stay a bit away from the block, so the near plane doesn't cut into it
+                ((hit.point - eye).magnitude() - 0.2)
+                    .max(0.0)
+                    .min(render::THIRD_PERSON_DISTANCE)
+            }
+            None => render::THIRD_PERSON_DISTANCE,
+        }
+    }
+
     pub fn key_press(
         &self,
         down: bool,
@@ -1236,7 +1760,8 @@ impl Server {
         screen_sys: &mut ScreenSystem,
         focused: &mut bool,
     ) {
-        if *focused || key == Actionkey::OpenInv {
+        // Releasing the player list key hides it even if a screen was opened meanwhile
+        if *focused || key == Actionkey::OpenInv || (key == Actionkey::PlayerList && !down) {
             let mut state_changed = false;
             if let Some(player) = *self.player.clone().write() {
                 if let Some(movement) = self
@@ -1262,9 +1787,22 @@ impl Server {
                                 .read()
                                 .player_inventory
                                 .clone();
+                            let creative = matches!(
+                                self.hud_context.read().get_game_mode(),
+                                GameMode::Creative
+                            );
+                            let inventory: Arc<RwLock<dyn Inventory + Sync + Send>> = if creative {
+                                Arc::new(RwLock::new(CreativeInventory::new(
+                                    self.mapped_protocol_version,
+                                    self.hud_context.clone(),
+                                    player_inv,
+                                )))
+                            } else {
+                                player_inv
+                            };
                             screen_sys.add_screen(Box::new(
                                 render::inventory::InventoryWindow::new(
-                                    player_inv,
+                                    inventory,
                                     self.inventory_context.clone(),
                                 ),
                             ));
@@ -1272,6 +1810,24 @@ impl Server {
                         }
                     }
                 }
+                Actionkey::OpenChat | Actionkey::OpenCommand => {
+                    // The key is released while the chat is open, so its state isn't checked
+                    if down {
+                        let initial_input = if key == Actionkey::OpenCommand {
+                            "/"
+                        } else {
+                            ""
+                        };
+                        screen_sys.add_screen(Box::new(Chat::new(
+                            self.hud_context.clone(),
+                            initial_input,
+                        )));
+                        *focused = false;
+                    }
+                }
+                Actionkey::PlayerList => {
+                    self.hud_context.write().show_player_list(down);
+                }
                 Actionkey::ToggleHud => {
                     if down && state_changed {
                         let curr = self.hud_context.read().enabled;
@@ -1284,39 +1840,267 @@ impl Server {
                         self.hud_context.write().debug = !curr;
                     }
                 }
-                _ => {}
+                Actionkey::TogglePerspective => {
+                    if down && state_changed {
+                        *self.toggle_perspective.write() = true;
+                    }
+                }
+                _ => {
+                    if let Some(slot) = key.hotbar_slot() {
+                        if down && state_changed {
+                            self.select_hotbar_slot(slot);
+                        }
+                    }
+                }
             };
         }
     }
 
-    pub fn on_left_click(&self, _renderer: Arc<RwLock<render::Renderer>>) {
-        // TODO: Check these values!
-        if self.mapped_protocol_version < Version::V1_8 {
-            self.write_packet(packet::play::serverbound::ArmSwing_Handsfree_ID {
-                entity_id: 0,
-                animation: 0,
-            });
-        } else if self.mapped_protocol_version < Version::V1_9 {
+    /// Sends a line typed into the chat, split over as many messages as needed.
+    pub fn send_chat(&self, line: &str) {
+        for packet in chat::chat_packets(line, self.mapped_protocol_version) {
+            self.write_packet(packet);
+        }
+    }
+
+    /// Asks the server how the chat input could be completed, see `take_tab_completions`.
+    pub fn request_tab_completions(&self, text: &str) {
+        let id = {
+            let mut request = self.tab_complete_request.lock();
+            request.0 += 1;
+            request.1 = text.to_owned();
+            request.0
+        };
+        if self.mapped_protocol_version >= Version::V1_13 {
+            self.write_packet(packet::play::serverbound::TabComplete_TransactionId {
+                transaction_id: protocol::VarInt(id),
+                text: text.to_owned(),
+            });
+        } else if self.mapped_protocol_version >= Version::V1_9 {
+            self.write_packet(packet::play::serverbound::TabComplete {
+                text: text.to_owned(),
+                assume_command: false,
+                has_target: false,
+                target: None,
+            });
+        } else if self.mapped_protocol_version == Version::V1_8 {
+            self.write_packet(packet::play::serverbound::TabComplete_NoAssume {
+                text: text.to_owned(),
+                has_target: false,
+                target: None,
+            });
+        } else {
+            self.write_packet(packet::play::serverbound::TabComplete_NoAssume_NoTarget {
+                text: text.to_owned(),
+            });
+        }
+    }
+
+    /// Returns the reply to the last tab-complete request, once it arrived.
+    pub fn take_tab_completions(&self) -> Option<chat::Completions> {
+        self.tab_completions.lock().take()
+    }
+
+    /// Selects the hotbar slot, from 0 to 8, and lets the server know.
+    pub fn select_hotbar_slot(&self, slot: u8) {
+        let selected = self.inventory_context.clone().read().hotbar_index;
+        let packet = match held_item_change(selected, slot) {
+            Some(packet) => packet,
+            None => return,
+        };
+        self.write_packet(packet);
+        self.inventory_context.clone().write().hotbar_index = slot;
+        self.hud_context.clone().write().update_slot_index(slot);
+    }
+
+    pub fn on_left_click(&self, _renderer: Arc<RwLock<render::Renderer>>) {
+        self.swing_arm();
+        let mut digging = self.digging.write();
+        digging.held = true;
+        digging.clicked = true;
+    }
+
+    pub fn on_left_release(&self) {
+        let mut digging = self.digging.write();
+        digging.held = false;
+        if let Some(target) = digging.target.take() {
+            self.send_digging(digging::DIG_CANCEL, target.pos, target.face);
+        }
+    }
+
+    /// Digs the targeted block while attacking, breaking it once dug long enough, or
+    /// right away in creative mode.
+    fn dig_tick(&self, gamemode: GameMode, on_ground: bool) {
+        let mut digging = self.digging.write();
+        digging.delay = digging.delay.saturating_sub(1);
+        let attacking = digging.held || digging.clicked;
+        digging.clicked = false;
+        if !attacking || !gamemode.can_dig() {
+            return;
+        }
+        let target = self.target_info.read().target();
+
+        // Looking at another block starts over
+        if let Some(current) = digging.target.take() {
+            if target.map_or(false, |(pos, _, _)| pos == current.pos) {
+                digging.target = Some(current);
+            } else {
+                self.send_digging(digging::DIG_CANCEL, current.pos, current.face);
+            }
+        }
+        let (pos, block, face) = match target {
+            Some(target) if digging.delay == 0 => target,
+            _ => return,
+        };
+
+        if gamemode.instant_break() {
+            self.send_digging(digging::DIG_START, pos, face);
+            self.world.set_block(pos, block::Air {});
+            self.show_block_break(pos, block);
+            digging.delay = digging::DIG_DELAY_TICKS;
+            return;
+        }
+        if digging.target.is_none() {
+            self.send_digging(digging::DIG_START, pos, face);
+            digging.target = Some(DigTarget::new(pos, face));
+        }
+        let break_ticks = digging::break_ticks(block, self.held_tool(), on_ground);
+        if digging.target.as_mut().unwrap().tick(break_ticks) {
+            self.send_digging(digging::DIG_FINISH, pos, face);
+            self.world.set_block(pos, block::Air {});
+            self.show_block_break(pos, block);
+            digging.target = None;
+            digging.delay = digging::DIG_DELAY_TICKS;
+        }
+    }
+
+    /// Plays the sound of `block` breaking at `pos` and breaks it into particles, which the
+    /// server leaves to the player breaking it.
+    fn show_block_break(&self, pos: Position, block: block::Block) {
+        self.particles.write().spawn_block_break(pos, block);
+        self.play_sound(SoundEvent {
+            name: SoundName::Event(format!("block.{}.break", sound::block_sound_group(block))),
+            category: SoundCategory::Blocks,
+            position: Some((
+                f64::from(pos.x) + 0.5,
+                f64::from(pos.y) + 0.5,
+                f64::from(pos.z) + 0.5,
+            )),
+            volume: 1.0,
+            pitch: 0.8,
+        });
+    }
+
+    /// The kind of tool in the selected hotbar slot and its speed, if it's a tool.
+    fn held_tool(&self) -> Option<(block::Tool, f64)> {
+        self.with_held_item(|item| item.material.tool()).flatten()
+    }
+
+    /// Calls `f` with the item in the selected hotbar slot, if there is one.
+    fn with_held_item<T>(&self, f: impl FnOnce(&Item) -> T) -> Option<T> {
+        let hud_context = self.hud_context.clone();
+        let hud_context = hud_context.read();
+        let inventory = hud_context.player_inventory.as_ref()?.clone();
+        let inventory = inventory.read();
+        inventory
+            .get_item((36 + hud_context.get_slot_index()) as i16)
+            .as_ref()
+            .map(f)
+    }
+
+    fn send_digging(&self, status: u8, pos: Position, face: Direction) {
+        if self.mapped_protocol_version < Version::V1_8 {
+            self.write_packet(packet::play::serverbound::PlayerDigging_u8_u8y {
+                status,
+                x: pos.x,
+                y: pos.y as u8,
+                z: pos.z,
+                face: face.index() as u8,
+            });
+        } else if self.mapped_protocol_version < Version::V1_9 {
+            self.write_packet(packet::play::serverbound::PlayerDigging_u8 {
+                status,
+                location: pos,
+                face: face.index() as u8,
+            });
+        } else {
+            self.write_packet(packet::play::serverbound::PlayerDigging {
+                status: protocol::VarInt(status as i32),
+                location: pos,
+                face: face.index() as u8,
+            });
+        }
+    }
+
+    /// Sends a click on `slot` of the window `id`, with the item that was in the slot.
+    pub fn click_window(
+        &self,
+        id: u8,
+        slot: i16,
+        action: ClickAction,
+        action_number: u16,
+        clicked_item: Option<Stack>,
+    ) {
+        let (mode, button) = action.mode_and_button();
+        if self.mapped_protocol_version < Version::V1_9 {
+            self.write_packet(packet::play::serverbound::ClickWindow_u8 {
+                id,
+                slot,
+                button,
+                action_number,
+                mode,
+                clicked_item,
+            });
+        } else {
+            self.write_packet(packet::play::serverbound::ClickWindow {
+                id,
+                slot,
+                button,
+                action_number,
+                mode: protocol::VarInt(mode as i32),
+                clicked_item,
+            });
+        }
+    }
+
+    pub fn close_window(&self, id: u8) {
+        self.write_packet(packet::play::serverbound::CloseWindow { id });
+    }
+
+    /// Swings the player's arm and lets the server know so others see it too.
+    fn swing_arm(&self) {
+        if self.mapped_protocol_version < Version::V1_8 {
+            self.write_packet(packet::play::serverbound::ArmSwing_Handsfree_ID {
+                entity_id: *self.entity_id.read(),
+                animation: 1,
+            });
+        } else if self.mapped_protocol_version < Version::V1_9 {
             self.write_packet(packet::play::serverbound::ArmSwing_Handsfree { empty: () })
         } else {
             self.write_packet(packet::play::serverbound::ArmSwing {
                 hand: Default::default(),
             });
         }
-        // TODO: Implement clientside animation.
+        if let Some(player) = *self.player.clone().read() {
+            if let Some(model) = self
+                .entities
+                .clone()
+                .write()
+                .get_component_mut_direct::<entity::player::PlayerModel>(player)
+            {
+                model.swing_arm();
+            }
+        }
     }
 
     pub fn on_right_click(&self, renderer: Arc<RwLock<render::Renderer>>) {
         if self.player.clone().read().is_some() {
             let world = self.world.clone();
             let renderer = &mut renderer.write();
-            if let Some((pos, _, face, at)) = target::trace_ray(
-                &world,
-                4.0,
-                renderer.camera.pos.to_vec(),
-                renderer.view_vector.cast().unwrap(),
-                target::test_block,
-            ) {
+            if let Some(hit) =
+                world.raycast(renderer.camera.eye.to_vec(), renderer.camera.look, 4.0)
+            {
+                let (pos, bl, face, at) = (hit.position, hit.block, hit.face, hit.cursor());
                 if self.protocol_version >= 477 {
                     self.write_packet(
                         packet::play::serverbound::PlayerBlockPlacement_insideblock {
@@ -1348,18 +2132,7 @@ impl Server {
                         cursor_z: (at.z * 16.0) as u8,
                     });
                 } else if self.protocol_version >= 47 {
-                    let item = self
-                        .hud_context
-                        .clone()
-                        .read()
-                        .player_inventory
-                        .as_ref()
-                        .unwrap()
-                        .clone()
-                        .read()
-                        .get_item((36 + self.hud_context.clone().read().get_slot_index()) as i16)
-                        .as_ref()
-                        .map(|item| item.stack.clone());
+                    let item = self.with_held_item(|item| item.stack.clone());
                     self.write_packet(packet::play::serverbound::PlayerBlockPlacement_u8_Item {
                         location: pos,
                         face: face.index() as u8,
@@ -1369,23 +2142,12 @@ impl Server {
                         cursor_z: (at.z * 16.0) as u8,
                     });
                 } else {
-                    let item = self
-                        .hud_context
-                        .clone()
-                        .read()
-                        .player_inventory
-                        .as_ref()
-                        .unwrap()
-                        .clone()
-                        .read()
-                        .get_item((36 + self.hud_context.clone().read().get_slot_index()) as i16)
-                        .as_ref()
-                        .map(|item| item.stack.clone());
+                    let item = self.with_held_item(|item| item.stack.clone());
                     self.write_packet(
                         packet::play::serverbound::PlayerBlockPlacement_u8_Item_u8y {
                             x: pos.x,
                             y: pos.y as u8,
-                            z: pos.x,
+                            z: pos.z,
                             face: face.index() as u8,
                             hand: item,
                             cursor_x: (at.x * 16.0) as u8,
@@ -1394,20 +2156,79 @@ impl Server {
                         },
                     );
                 }
-                if self.mapped_protocol_version < Version::V1_8 {
-                    self.write_packet(packet::play::serverbound::ArmSwing_Handsfree_ID {
-                        entity_id: 0,
-                        animation: 0,
-                    });
-                } else if self.mapped_protocol_version < Version::V1_9 {
-                    self.write_packet(packet::play::serverbound::ArmSwing_Handsfree { empty: () })
-                } else {
-                    self.write_packet(packet::play::serverbound::ArmSwing {
-                        hand: Default::default(),
-                    });
-                }
+                self.swing_arm();
+                self.place_held_block(pos, bl, face);
+            }
+        }
+    }
+
+    /// Places the held block right away instead of waiting for the server, which corrects
+    /// it with a block change if the placement failed. The block is placed as the item's
+    /// default state, the server sends its actual orientation.
+    fn place_held_block(&self, target: Position, target_block: block::Block, face: Direction) {
+        // The server uses the block instead
+        if target_block.is_interactable() && !*self.sneaking_sent.read() {
+            return;
+        }
+        let block = match self.with_held_item(|item| self.held_block(item)) {
+            Some(block) if block != (block::Missing {}) => block,
+            _ => return,
+        };
+        let pos = target::placement_position(target, target_block, face);
+        if !self.world.get_block(pos).is_replaceable() {
+            return;
+        }
+
+        // Blocks can't be placed inside the player
+        let player = *self.player.clone().read();
+        if let Some(player) = player.filter(|_| block.get_material().collidable) {
+            let entities = self.entities.clone();
+            let entities = entities.read();
+            let position = entities.get_component(player, self.position).unwrap();
+            let player_bounds = collision::Aabb3::new(
+                cgmath::Point3::new(-0.3, 0.0, -0.3),
+                cgmath::Point3::new(0.3, 1.8, 0.3),
+            )
+            .add_v(position.position);
+            let posf = cgmath::Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64);
+            let blocked = block.get_collision_boxes().into_iter().any(|bound| {
+                let bound = bound.add_v(posf);
+                bound.min.x < player_bounds.max.x
+                    && bound.max.x > player_bounds.min.x
+                    && bound.min.y < player_bounds.max.y
+                    && bound.max.y > player_bounds.min.y
+                    && bound.min.z < player_bounds.max.z
+                    && bound.max.z > player_bounds.min.z
+            });
+            if blocked {
+                return;
+            }
+        }
+        self.world.set_block(pos, block);
+    }
+
+    /// The block placed by `item`, `Block::Missing` for items that aren't blocks.
+    fn held_block(&self, item: &Item) -> block::Block {
+        let (id, id_map) = match &self.legacy_block_ids {
+            // Block items have their own ids since the flattening, their materials still
+            // know the ones from before without the variant
+            Some(legacy_block_ids) => (
+                usize::from(material_versions::to_id(item.material, Version::V1_12)) << 4,
+                legacy_block_ids,
+            ),
+            None => {
+                let damage = item.stack.damage.unwrap_or(0) as usize & 0xf;
+                (
+                    ((item.stack.id as usize) << 4) | damage,
+                    &*self.world.id_map,
+                )
             }
+        };
+        // Only the ids below 256 are blocks
+        if id >> 4 == 0 || id >> 4 >= 256 {
+            return block::Missing {};
         }
+        id_map.by_vanilla_id(id, self.world.modded_block_ids.clone())
     }
 
     pub fn write_packet<T: protocol::PacketType>(&self, p: T) {
@@ -1427,6 +2248,7 @@ impl Server {
     }
 
     fn on_keep_alive_i64(&self, keep_alive: packet::play::clientbound::KeepAliveClientbound_i64) {
+        self.keep_alive.write().received(Instant::now());
         self.write_packet(packet::play::serverbound::KeepAliveServerbound_i64 {
             id: keep_alive.id,
         });
@@ -1436,12 +2258,14 @@ impl Server {
         &self,
         keep_alive: packet::play::clientbound::KeepAliveClientbound_VarInt,
     ) {
+        self.keep_alive.write().received(Instant::now());
         self.write_packet(packet::play::serverbound::KeepAliveServerbound_VarInt {
             id: keep_alive.id,
         });
     }
 
     fn on_keep_alive_i32(&self, keep_alive: packet::play::clientbound::KeepAliveClientbound_i32) {
+        self.keep_alive.write().received(Instant::now());
         self.write_packet(packet::play::serverbound::KeepAliveServerbound_i32 {
             id: keep_alive.id,
         });
@@ -1627,6 +2451,34 @@ impl Server {
         self.on_game_join(join.gamemode, join.entity_id)
     }
 
+    fn on_boss_bar(&self, boss_bar: packet::play::clientbound::BossBar) {
+        let update = match boss_bar.action.0 {
+            0 => BossBarUpdate::Add(BossBar::new(
+                boss_bar.title,
+                boss_bar.health,
+                BossBarColor::from_int(boss_bar.color.0),
+                BossBarDivision::from_int(boss_bar.style.0),
+                boss_bar.flags,
+            )),
+            1 => BossBarUpdate::Remove,
+            2 => BossBarUpdate::Health(boss_bar.health),
+            3 => BossBarUpdate::Title(boss_bar.title),
+            4 => BossBarUpdate::Style(
+                BossBarColor::from_int(boss_bar.color.0),
+                BossBarDivision::from_int(boss_bar.style.0),
+            ),
+            5 => BossBarUpdate::Flags(boss_bar.flags),
+            action => {
+                warn!("Unknown boss bar action {}", action);
+                return;
+            }
+        };
+        self.hud_context
+            .clone()
+            .write()
+            .update_boss_bar(boss_bar.uuid, update);
+    }
+
     fn on_game_join(&self, gamemode: u8, entity_id: i32) {
         let gamemode = GameMode::from_int((gamemode & 0x7) as i32);
         let player = entity::player::create_local(&mut self.entities.clone().write());
@@ -1640,20 +2492,30 @@ impl Server {
             model.set_skin(info.skin_url.clone());
         }
         self.hud_context.clone().write().update_game_mode(gamemode);
+        // Bars of the last server a reconnect came from
+        self.hud_context.clone().write().clear_boss_bars();
         *self
             .entities
             .clone()
             .write()
             .get_component_mut(player, self.gamemode)
             .unwrap() = gamemode;
+        // Until the server sends the abilities
         self.entities
             .clone()
             .write()
             .get_component_mut(player, self.player_movement)
             .unwrap()
-            .flying = gamemode.can_fly();
+            .set_abilities(entity::player::Abilities {
+                allow_flying: gamemode.can_fly(),
+                flying: gamemode.always_fly(),
+                ..Default::default()
+            });
 
         self.entity_map.clone().write().insert(entity_id, player);
+        *self.entity_id.write() = entity_id;
+        *self.sprinting_sent.write() = false;
+        *self.sneaking_sent.write() = false;
         self.player.clone().write().replace(player);
 
         // Let the server know who we are
@@ -1666,19 +2528,24 @@ impl Server {
         } else {
             self.write_packet(brand.into_message17());
         }
+        self.send_client_settings();
+    }
+
+    fn send_client_settings(&self) {
+        let view_distance = *self.render_distance.read() as u8;
         if self.protocol_version <= 48 {
             // 1 snapshot after 1.8
             self.write_packet(ClientSettings_u8_Handsfree {
                 locale: "en_us".to_string(), // TODO: Make this configurable!
-                view_distance: 8,            // TODO: Make this configurable!
-                chat_mode: 0,                // TODO: Make this configurable!
-                chat_colors: true,           // TODO: Make this configurable!
-                displayed_skin_parts: 127,   // TODO: Make this configurable!
+                view_distance,
+                chat_mode: 0,              // TODO: Make this configurable!
+                chat_colors: true,         // TODO: Make this configurable!
+                displayed_skin_parts: 127, // TODO: Make this configurable!
             });
         } else {
             self.write_packet(ClientSettings {
-                locale: "en_us".to_string(),   // TODO: Make this configurable!
-                view_distance: 8,              // TODO: Make this configurable!
+                locale: "en_us".to_string(), // TODO: Make this configurable!
+                view_distance,
                 chat_mode: Default::default(), // TODO: Make this configurable!
                 chat_colors: true,             // TODO: Make this configurable!
                 displayed_skin_parts: 127,     // TODO: Make this configurable!
@@ -1730,7 +2597,7 @@ impl Server {
                 .write()
                 .update_health_and_food(20.0, 20, 0); // TODO: Verify this!
             self.hud_context.clone().write().update_slot_index(0);
-            self.hud_context.clone().write().update_exp(0.0, 0);
+            self.hud_context.clone().write().update_exp(0.0, 0, 0);
             self.hud_context.clone().write().update_absorbtion(0.0);
             self.hud_context.clone().write().update_armor(0);
             // self.hud_context.clone().write().update_breath(-1); // TODO: Fix this!
@@ -1744,7 +2611,8 @@ impl Server {
     }
 
     fn on_time_update(&self, time_update: packet::play::clientbound::TimeUpdate) {
-        self.world_data.clone().write().world_age = time_update.time_of_day;
+        // Negative while the daylight cycle is off, the moon phase still follows it
+        self.world_data.clone().write().world_age = time_update.time_of_day.abs();
         self.world_data.clone().write().world_time_target =
             (time_update.time_of_day % 24000) as f64;
         if self.world_data.clone().read().world_time_target < 0.0 {
@@ -1756,6 +2624,13 @@ impl Server {
     }
 
     fn on_game_state_change(&self, game_state: packet::play::clientbound::ChangeGameState) {
+        if self
+            .weather
+            .write()
+            .on_game_state(game_state.reason, game_state.value)
+        {
+            return;
+        }
         if game_state.reason == 3 {
             if let Some(player) = *self.player.write() {
                 let gamemode = GameMode::from_int(game_state.value as i32);
@@ -1766,16 +2641,54 @@ impl Server {
                     .write()
                     .get_component_mut(player, self.gamemode)
                     .unwrap() = gamemode;
-                self.entities
+                let movement = self
+                    .entities
                     .clone()
                     .write()
                     .get_component_mut(player, self.player_movement)
-                    .unwrap()
-                    .flying = gamemode.can_fly();
+                    .unwrap();
+                movement.set_abilities(entity::player::Abilities {
+                    allow_flying: gamemode.can_fly(),
+                    flying: gamemode.always_fly(),
+                    ..movement.abilities()
+                });
             }
         }
     }
 
+    fn on_player_abilities(&self, abilities: packet::play::clientbound::PlayerAbilities) {
+        if let Some(player) = *self.player.read() {
+            if let Some(movement) = self
+                .entities
+                .clone()
+                .write()
+                .get_component_mut(player, self.player_movement)
+            {
+                movement.set_abilities(entity::player::Abilities::from(&abilities));
+            }
+        }
+    }
+
+    /// Tells the server the player started or stopped flying.
+    fn send_flying(&self, abilities: entity::player::Abilities) {
+        if self.mapped_protocol_version >= Version::V1_16 {
+            // The other flags are ignored, so only flying is sent
+            let flying = entity::player::Abilities {
+                flying: abilities.flying,
+                ..Default::default()
+            };
+            self.write_packet(packet::play::serverbound::ClientAbilities_u8 {
+                flags: flying.flags(),
+            });
+        } else {
+            self.write_packet(packet::play::serverbound::ClientAbilities_f32 {
+                flags: abilities.flags(),
+                flying_speed: abilities.flying_speed,
+                walking_speed: abilities.walking_speed,
+            });
+        }
+    }
+
     fn on_entity_destroy(&self, entity_destroy: packet::play::clientbound::EntityDestroy) {
         for id in entity_destroy.entity_ids.data {
             if let Some(entity) = self.entity_map.clone().write().remove(&id.0) {
@@ -1867,6 +2780,28 @@ impl Server {
             target_position.position.z = z;
             target_rotation.yaw = -(yaw / 256.0) * PI * 2.0;
             target_rotation.pitch = -(pitch / 256.0) * PI * 2.0;
+            self.snap_interpolation(*entity, target_position, target_rotation);
+        }
+    }
+
+    /// Makes an entity jump to its target instead of moving there smoothly.
+    fn snap_interpolation(
+        &self,
+        entity: ecs::Entity,
+        target_position: &entity::TargetPosition,
+        target_rotation: &entity::TargetRotation,
+    ) {
+        if let Some(interpolation) = self
+            .entities
+            .clone()
+            .write()
+            .get_component_mut(entity, self.interpolation)
+        {
+            interpolation.snap(entity::Transform {
+                position: target_position.position,
+                yaw: target_rotation.yaw,
+                pitch: target_rotation.pitch,
+            });
         }
     }
 
@@ -1914,6 +2849,86 @@ impl Server {
         }
     }
 
+    fn on_entity_metadata(&self, entity_id: i32, metadata: &types::Metadata) {
+        // Before 1.8 only living entities had custom names, further down their metadata
+        let (name_index, visible_index) = if self.protocol_version >= 47 {
+            (2, 3)
+        } else {
+            (10, 11)
+        };
+        // Updates only carry the values that changed
+        let custom_name = metadata.get_raw(name_index).map(|_| {
+            // Names are chat components since 1.13, legacy formatted strings before
+            metadata
+                .get_component(name_index)
+                .cloned()
+                .or_else(|| metadata.get_string(name_index).map(Component::from_legacy))
+                .filter(|name| !name.to_plain_string().is_empty())
+        });
+        if let Some(entity) = self.entity_map.clone().read().get(&entity_id) {
+            let mut entities = self.entities.write();
+            if let Some(model) =
+                entities.get_component_mut_direct::<entity::player::PlayerModel>(*entity)
+            {
+                if let Some(custom_name) = custom_name {
+                    model.set_custom_name(custom_name);
+                }
+                if let Some(visible) = metadata.get_bool(visible_index) {
+                    model.set_custom_name_visible(visible);
+                }
+            } else if let Some(name_tag) =
+                entities.get_component_mut_direct::<entity::mob::NameTag>(*entity)
+            {
+                if let Some(custom_name) = custom_name {
+                    name_tag.set_custom_name(custom_name);
+                }
+            }
+        }
+    }
+
+    fn on_mob_spawn(
+        &self,
+        entity_id: i32,
+        x: f64,
+        y: f64,
+        z: f64,
+        metadata: Option<&types::Metadata>,
+    ) {
+        if let Some(entity) = self.entity_map.clone().write().remove(&entity_id) {
+            self.entities.clone().write().remove_entity(entity);
+        }
+        let entity = entity::mob::create_mob(&mut self.entities.clone().write());
+        let position = self
+            .entities
+            .clone()
+            .write()
+            .get_component_mut(entity, self.position)
+            .unwrap();
+        let target_position = self
+            .entities
+            .clone()
+            .write()
+            .get_component_mut(entity, self.target_position)
+            .unwrap();
+        position.position.x = x;
+        position.position.y = y;
+        position.position.z = z;
+        target_position.position.x = x;
+        target_position.position.y = y;
+        target_position.position.z = z;
+        let target_rotation = self
+            .entities
+            .clone()
+            .write()
+            .get_component_mut(entity, self.target_rotation)
+            .unwrap();
+        self.snap_interpolation(entity, target_position, target_rotation);
+        self.entity_map.clone().write().insert(entity_id, entity);
+        if let Some(metadata) = metadata {
+            self.on_entity_metadata(entity_id, metadata);
+        }
+    }
+
     fn on_entity_look(&self, entity_id: i32, yaw: f64, pitch: f64) {
         use std::f64::consts::PI;
         if let Some(entity) = self.entity_map.clone().read().get(&entity_id) {
@@ -2069,19 +3084,15 @@ impl Server {
     ) {
         // 1.7.10: populate the player list here, since we only now know the UUID
         let uuid = protocol::UUID::from_str(&spawn.uuid).unwrap();
-        self.players
-            .clone()
-            .write()
-            .entry(uuid.clone())
-            .or_insert(PlayerInfo {
-                name: spawn.name.clone(),
-                uuid,
-                skin_url: None,
+        self.players.clone().write().insert_if_missing(PlayerInfo {
+            name: spawn.name.clone(),
+            uuid,
+            skin_url: None,
 
-                display_name: None,
-                ping: 0, // TODO: don't overwrite from PlayerInfo_String
-                gamemode: GameMode::from_int(0),
-            });
+            display_name: None,
+            ping: 0, // TODO: don't overwrite from PlayerInfo_String
+            gamemode: GameMode::from_int(0),
+        });
 
         self.on_player_spawn(
             spawn.entity_id.0,
@@ -2150,6 +3161,7 @@ impl Server {
         rotation.pitch = -(pitch / 256.0) * PI * 2.0;
         target_rotation.yaw = rotation.yaw;
         target_rotation.pitch = rotation.pitch;
+        self.snap_interpolation(entity, target_position, target_rotation);
         if let Some(info) = self.players.clone().read().get(&uuid) {
             let model = self
                 .entities
@@ -2199,7 +3211,7 @@ impl Server {
         let flags: u8 = 0; // always absolute
         self.on_teleport_player(
             teleport.x,
-            teleport.eyes_y - 1.62,
+            teleport.eyes_y - entity::player::EYE_HEIGHT,
             teleport.z,
             teleport.yaw as f64,
             teleport.pitch as f64,
@@ -2260,6 +3272,9 @@ impl Server {
             ) - 180.0)
                 * (PI / 180.0));
 
+            // Don't interpolate across the teleport
+            position.last_position = position.position;
+
             if (flags & (TeleportFlag::RelX as u8)) == 0 {
                 velocity.velocity.x = 0.0;
             }
@@ -2393,103 +3408,396 @@ impl Server {
     }
 
     fn on_player_info(&self, player_info: packet::play::clientbound::PlayerInfo) {
-        use crate::protocol::packet::PlayerDetail::*;
         for detail in player_info.inner.players {
-            match detail {
-                Add {
-                    name,
-                    uuid,
-                    properties,
-                    display,
-                    gamemode,
-                    ping,
-                } => {
-                    let players = self.players.clone();
-                    let mut players = players.write();
-                    let info = players.entry(uuid.clone()).or_insert(PlayerInfo {
-                        name: name.clone(),
-                        uuid,
-                        skin_url: None,
-
-                        display_name: display.clone(),
-                        ping: ping.0,
-                        gamemode: GameMode::from_int(gamemode.0),
-                    });
-                    // Re-set the props of the player in case of dodgy server implementations
-                    info.name = name;
-                    info.display_name = display;
-                    info.ping = ping.0;
-                    info.gamemode = GameMode::from_int(gamemode.0);
-                    for prop in properties {
-                        if prop.name != "textures" {
-                            continue;
-                        }
-                        // Ideally we would check the signature of the blob to
-                        // verify it was from Mojang and not faked by the server
-                        // but this requires the public key which is distributed
-                        // authlib. We could download authlib on startup and extract
-                        // the key but this seems like overkill compared to just
-                        // whitelisting Mojang's texture servers instead.
-                        let skin_blob_result = &base64::decode(&prop.value);
-                        let skin_blob = match skin_blob_result {
-                            Ok(val) => val,
-                            Err(err) => {
-                                error!("Failed to decode skin blob, {:?}", err);
-                                continue;
-                            }
-                        };
-                        let skin_blob: serde_json::Value = match serde_json::from_slice(skin_blob) {
-                            Ok(val) => val,
-                            Err(err) => {
-                                error!("Failed to parse skin blob, {:?}", err);
-                                continue;
-                            }
-                        };
-                        if let Some(skin_url) = skin_blob
-                            .pointer("/textures/SKIN/url")
-                            .and_then(|v| v.as_str())
-                        {
-                            info.skin_url = Some(skin_url.to_owned());
-                        }
-                    }
+            let added = match &detail {
+                protocol::packet::PlayerDetail::Add { uuid, .. } => Some(uuid.clone()),
+                _ => None,
+            };
+            self.players.clone().write().apply(detail);
+
+            // Refresh our own skin when the server sends it to us.
+            // The join game packet can come before this packet meaning
+            // we may not have the skin in time for spawning ourselves.
+            // This isn't an issue for other players because this packet
+            // must come before the spawn player packet.
+            if added.as_ref() == Some(&self.uuid) {
+                let skin_url = self
+                    .players
+                    .clone()
+                    .read()
+                    .get(&self.uuid)
+                    .and_then(|info| info.skin_url.clone());
+                let model = self
+                    .entities
+                    .clone()
+                    .write()
+                    .get_component_mut_direct::<entity::player::PlayerModel>(
+                        self.player.clone().write().unwrap(),
+                    )
+                    .unwrap();
+                model.set_skin(skin_url);
+            }
+        }
+        self.hud_context.clone().write().update_player_list();
+    }
 
-                    // Refresh our own skin when the server sends it to us.
-                    // The join game packet can come before this packet meaning
-                    // we may not have the skin in time for spawning ourselves.
-                    // This isn't an issue for other players because this packet
-                    // must come before the spawn player packet.
-                    if info.uuid == self.uuid {
-                        let model = self
-                            .entities
-                            .clone()
-                            .write()
-                            .get_component_mut_direct::<entity::player::PlayerModel>(
-                                self.player.clone().write().unwrap(),
-                            )
-                            .unwrap();
-                        model.set_skin(info.skin_url.clone());
-                    }
-                }
-                UpdateGamemode { uuid, gamemode } => {
-                    if let Some(info) = self.players.clone().write().get_mut(&uuid) {
-                        info.gamemode = GameMode::from_int(gamemode.0);
-                    }
-                }
-                UpdateLatency { uuid, ping } => {
-                    if let Some(info) = self.players.clone().write().get_mut(&uuid) {
-                        info.ping = ping.0;
-                    }
-                }
-                UpdateDisplayName { uuid, display } => {
-                    if let Some(info) = self.players.clone().write().get_mut(&uuid) {
-                        info.display_name = display;
-                    }
-                }
-                Remove { uuid } => {
-                    self.players.clone().write().remove(&uuid);
+    fn on_resource_pack_send(&self, pack: packet::play::clientbound::ResourcePackSend) {
+        *self.resource_pack_request.lock() = Some(resource_pack::Request {
+            url: pack.url,
+            hash: pack.hash,
+        });
+    }
+
+    /// Covers the world with the downloading terrain screen until the chunks around the
+    /// player are meshed, or the server is slow enough about it that waiting is pointless.
+    fn downloading_terrain_tick(&self, game: &mut Game) {
+        if *self.terrain_loaded.read() {
+            return;
+        }
+        let render_distance = *self.render_distance.read();
+        let expected = ((render_distance * 2 + 1) * (render_distance * 2 + 1)) as usize;
+        let playing = matches!(self.connection_state(), ConnectionState::Playing);
+        let center = self.player.clone().read().and_then(|player| {
+            self.entities
+                .clone()
+                .read()
+                .get_component(player, self.target_position)
+                .map(|pos| CPos::from_block(pos.position.x, pos.position.z))
+        });
+        let world = self.world.clone();
+        // The player's position is only known once the server placed them in the world
+        let received = match center {
+            Some(center) if playing => world.loaded_chunks_around(center, render_distance),
+            _ => world.chunks.len(),
+        };
+        *self.terrain_progress.write() = TerrainProgress::new(received, expected);
+
+        let opened = *self.terrain_screen_opened.read();
+        let (screen, opened) = match opened {
+            Some(opened) => opened,
+            None => {
+                let screen = game.screen_sys.add_screen(Box::new(DownloadingTerrain::new(
+                    self.terrain_progress.clone(),
+                )));
+                *self.terrain_screen_opened.write() = Some((screen, Instant::now()));
+                return;
+            }
+        };
+        let ready = match center {
+            Some(center) if playing => {
+                world.is_terrain_ready(center, TERRAIN_READY_RADIUS)
+                    || opened.elapsed() > TERRAIN_TIMEOUT
+            }
+            _ => false,
+        };
+        // Screens opened over it, like resource pack prompts, are kept
+        if ready {
+            game.screen_sys.remove_screen(screen);
+            *self.terrain_loaded.write() = true;
+        }
+    }
+
+    /// Unloads the chunks out of range whenever the player moved to another chunk or the
+    /// render distance changed. Nothing is unloaded before the server placed the player in
+    /// the world, as the player isn't where the chunks are yet.
+    fn unload_chunks_tick(&self) {
+        if !matches!(self.connection_state(), ConnectionState::Playing) {
+            return;
+        }
+        let center = self.player.clone().read().and_then(|player| {
+            self.entities
+                .clone()
+                .read()
+                .get_component(player, self.target_position)
+                .map(|pos| CPos::from_block(pos.position.x, pos.position.z))
+        });
+        let center = match center {
+            Some(center) => center,
+            None => return,
+        };
+        let around = (center, *self.render_distance.read());
+        if *self.chunks_unloaded_around.read() == Some(around) {
+            return;
+        }
+        *self.chunks_unloaded_around.write() = Some(around);
+        self.world
+            .unload_distant_chunks(around.0, around.1, &mut self.entities.clone().write());
+    }
+
+    /// Asks about resource packs the server sent, unless the settings already decide,
+    /// and applies them once downloaded.
+    fn resource_pack_tick(&self, game: &mut Game) {
+        let request = self.resource_pack_request.lock().take();
+        if let Some(request) = request {
+            match game.vars.get(settings::CL_SERVER_RESOURCE_PACKS).as_str() {
+                "enabled" => self.accept_resource_pack(request),
+                "disabled" => self.decline_resource_pack(&request),
+                _ => {
+                    game.screen_sys
+                        .add_screen(Box::new(ResourcePackPrompt::new(request)));
+                    game.focused = false;
                 }
             }
         }
+
+        let result = match self
+            .resource_pack_download
+            .lock()
+            .as_ref()
+            .and_then(|download| download.poll())
+        {
+            Some(result) => result,
+            None => return,
+        };
+        let request = self.resource_pack_download.lock().take().unwrap().request;
+        let result = result.and_then(|path| {
+            self.resources
+                .write()
+                .set_server_resource_pack(&path)
+                .map_err(|err| err.to_string())
+        });
+        match result {
+            Ok(()) => self.send_resource_pack_status(&request, resource_pack::STATUS_LOADED),
+            Err(err) => {
+                warn!(
+                    "Failed to load the server's resource pack {}: {}",
+                    request.url, err
+                );
+                self.send_resource_pack_status(&request, resource_pack::STATUS_FAILED);
+            }
+        }
+    }
+
+    /// Downloads and applies the resource pack the server sent.
+    pub fn accept_resource_pack(&self, request: resource_pack::Request) {
+        self.send_resource_pack_status(&request, resource_pack::STATUS_ACCEPTED);
+        *self.resource_pack_download.lock() = Some(resource_pack::Download::start(request));
+    }
+
+    pub fn decline_resource_pack(&self, request: &resource_pack::Request) {
+        self.send_resource_pack_status(request, resource_pack::STATUS_DECLINED);
+    }
+
+    fn send_resource_pack_status(&self, request: &resource_pack::Request, status: i32) {
+        // Only 1.9 sends the hash along
+        if self.mapped_protocol_version == Version::V1_9 {
+            self.write_packet(packet::play::serverbound::ResourcePackStatus_hash {
+                hash: request.hash.clone(),
+                result: protocol::VarInt(status),
+            });
+        } else {
+            self.write_packet(packet::play::serverbound::ResourcePackStatus {
+                result: protocol::VarInt(status),
+            });
+        }
+    }
+
+    fn on_player_list_header_footer(
+        &self,
+        header_footer: packet::play::clientbound::PlayerListHeaderFooter,
+    ) {
+        self.players
+            .clone()
+            .write()
+            .set_header_footer(header_footer.header, header_footer.footer);
+        self.hud_context.clone().write().update_player_list();
+    }
+
+    /// Where to subscribe to what happens on the server, see `events::Event`.
+    pub fn events(&self) -> &events::Events {
+        &self.events
+    }
+
+    /// Everyone the server lists as online.
+    pub fn player_list(&self) -> Arc<RwLock<PlayerList>> {
+        self.players.clone()
+    }
+
+    fn on_tab_complete_reply(&self, reply: packet::play::clientbound::TabCompleteReply) {
+        let text = self.tab_complete_request.lock().1.clone();
+        *self.tab_completions.lock() =
+            Some(chat::Completions::for_last_word(&text, reply.matches.data));
+    }
+
+    fn on_tab_complete_reply_transaction_id(
+        &self,
+        reply: packet::play::clientbound::TabCompleteReply_TransactionId,
+    ) {
+        // Replies to older requests no longer match the input
+        if reply.transaction_id.0 != self.tab_complete_request.lock().0 {
+            return;
+        }
+        *self.tab_completions.lock() = Some(chat::Completions {
+            start: reply.start.0.max(0) as usize,
+            length: reply.length.0.max(0) as usize,
+            matches: reply.matches.data.into_iter().map(|m| m.text).collect(),
+        });
+    }
+
+    fn on_sound_effect(
+        &self,
+        name: SoundName,
+        category: i32,
+        (x, y, z): (i32, i32, i32),
+        volume: f32,
+        pitch: f32,
+    ) {
+        // Only the registry of the resources' version is known
+        if let SoundName::Id(id) = name {
+            if self.protocol_version != sound::SOUND_REGISTRY_PROTOCOL {
+                debug!("Unsupported sound id {}", id);
+                return;
+            }
+        }
+        // Positions are sent as fixed point numbers with 3 fractional bits
+        self.play_sound(SoundEvent {
+            name,
+            category: SoundCategory::from_id(category),
+            position: Some((f64::from(x) / 8.0, f64::from(y) / 8.0, f64::from(z) / 8.0)),
+            volume,
+            pitch,
+        });
+    }
+
+    fn on_effect(&self, effect_id: i32, location: Position, data: i32) {
+        // Only a block being broken by someone else, the others are sounds and particles
+        // this client doesn't show yet
+        if effect_id != 2001 {
+            return;
+        }
+        let id = if self.mapped_protocol_version < Version::V1_13 {
+            // The block id with its metadata in the upper bits
+            ((data & 0xFFF) << 4) | ((data >> 12) & 0xF)
+        } else {
+            data
+        };
+        let block = self
+            .world
+            .id_map
+            .by_vanilla_id(id as usize, self.world.modded_block_ids.clone());
+        self.show_block_break(location, block);
+    }
+
+    /// The particle of a 1.13+ particle packet, `block_state` and `color` only being sent
+    /// for the particles that use them.
+    fn modern_particle(
+        &self,
+        particle_id: i32,
+        block_state: i32,
+        color: [f32; 3],
+    ) -> Option<(particle::ParticleKind, [u8; 3])> {
+        // 1.14 added a few particles before dust, falling dust and flames
+        let (dust, falling_dust, flame) = if self.mapped_protocol_version < Version::V1_14 {
+            (11, 20, 23)
+        } else {
+            (14, 23, 26)
+        };
+        match particle_id {
+            id if id == 3 || id == falling_dust => Some((
+                particle::ParticleKind::Block(
+                    self.world
+                        .id_map
+                        .by_vanilla_id(block_state as usize, self.world.modded_block_ids.clone()),
+                ),
+                particle::WHITE,
+            )),
+            id if id == dust => Some((
+                particle::ParticleKind::Puff,
+                [
+                    (color[0].clamp(0.0, 1.0) * 255.0) as u8,
+                    (color[1].clamp(0.0, 1.0) * 255.0) as u8,
+                    (color[2].clamp(0.0, 1.0) * 255.0) as u8,
+                ],
+            )),
+            id if id == flame => Some((particle::ParticleKind::Flame, particle::WHITE)),
+            5 => Some((particle::ParticleKind::Puff, particle::WHITE)),
+            6 => Some((particle::ParticleKind::Crit, particle::WHITE)),
+            _ => None,
+        }
+    }
+
+    /// The particle of a 1.8 to 1.12 particle packet, `data` being the block of block
+    /// particles.
+    fn legacy_particle(
+        &self,
+        particle_id: i32,
+        data: i32,
+    ) -> Option<(particle::ParticleKind, [u8; 3])> {
+        match particle_id {
+            // Block crack and block dust, the block id with its metadata in the upper bits
+            37 | 38 => Some((
+                particle::ParticleKind::Block(self.world.id_map.by_vanilla_id(
+                    (((data & 0xFFF) << 4) | ((data >> 12) & 0xF)) as usize,
+                    self.world.modded_block_ids.clone(),
+                )),
+                particle::WHITE,
+            )),
+            9 => Some((particle::ParticleKind::Crit, particle::WHITE)),
+            11 | 12 => Some((particle::ParticleKind::Puff, particle::SMOKE_COLOR)),
+            26 => Some((particle::ParticleKind::Flame, particle::WHITE)),
+            29 => Some((particle::ParticleKind::Puff, particle::WHITE)),
+            30 => Some((particle::ParticleKind::Puff, particle::REDSTONE_COLOR)),
+            _ => None,
+        }
+    }
+
+    /// The particle of a 1.7 particle packet, named instead of numbered.
+    fn named_particle(&self, name: &str) -> Option<(particle::ParticleKind, [u8; 3])> {
+        match name {
+            "crit" => Some((particle::ParticleKind::Crit, particle::WHITE)),
+            "smoke" | "largesmoke" => Some((particle::ParticleKind::Puff, particle::SMOKE_COLOR)),
+            "flame" => Some((particle::ParticleKind::Flame, particle::WHITE)),
+            "cloud" => Some((particle::ParticleKind::Puff, particle::WHITE)),
+            "reddust" => Some((particle::ParticleKind::Puff, particle::REDSTONE_COLOR)),
+            _ => {
+                // blockcrack_<id>_<meta>
+                let mut parts = name.strip_prefix("blockcrack_")?.split('_');
+                let id: usize = parts.next()?.parse().ok()?;
+                let meta: usize = parts.next().map_or(Some(0), |meta| meta.parse().ok())?;
+                let block = self.world.id_map.by_vanilla_id(
+                    (id << 4) | (meta & 0xF),
+                    self.world.modded_block_ids.clone(),
+                );
+                Some((particle::ParticleKind::Block(block), particle::WHITE))
+            }
+        }
+    }
+
+    fn on_particle(
+        &self,
+        particle: Option<(particle::ParticleKind, [u8; 3])>,
+        position: cgmath::Vector3<f64>,
+        offset: cgmath::Vector3<f32>,
+        speed: f32,
+        count: i32,
+    ) {
+        let (kind, color) = match particle {
+            Some(particle) => particle,
+            None => return,
+        };
+        self.particles.write().emit(
+            kind,
+            color,
+            position,
+            offset.cast().unwrap(),
+            speed as f64,
+            count,
+        );
+    }
+
+    /// Queues a sound until it's played by `drain_sounds`' caller.
+    pub fn play_sound(&self, sound: SoundEvent) {
+        let mut sounds = self.sounds.lock();
+        // Drop the oldest sounds if nobody is playing them, e.g. without a window
+        if sounds.len() >= sound::MAX_QUEUED_SOUNDS {
+            sounds.remove(0);
+        }
+        sounds.push(sound);
+    }
+
+    /// Returns all sounds to play since the last call.
+    pub fn drain_sounds(&self) -> Vec<SoundEvent> {
+        std::mem::take(&mut *self.sounds.lock())
     }
 
     fn on_servermessage_noposition(&self, m: packet::play::clientbound::ServerMessage_NoPosition) {
@@ -2507,18 +3815,31 @@ impl Server {
     fn on_servermessage(
         &self,
         message: &format::Component,
-        _position: Option<u8>,
-        _sender: Option<protocol::UUID>,
+        position: Option<u8>,
+        sender: Option<protocol::UUID>,
     ) {
         info!("Received chat message: {}", message);
+        let message = chat::ChatMessage::new(
+            message.clone(),
+            position,
+            sender,
+            self.mapped_protocol_version,
+        );
         self.hud_context
             .clone()
             .write()
-            .display_message_in_chat(message.clone());
-        self.received_chat_at
-            .clone()
-            .write()
-            .replace(Instant::now());
+            .display_server_message(message.message.clone(), message.position);
+        let mut chat_messages = self.chat_messages.lock();
+        // Don't grow forever if nobody drains the queue
+        if chat_messages.len() >= chat::MAX_QUEUED_MESSAGES {
+            chat_messages.pop_front();
+        }
+        chat_messages.push_back(message);
+    }
+
+    /// Returns all chat messages received since the last call.
+    pub fn drain_chat_messages(&self) -> Vec<chat::ChatMessage> {
+        self.chat_messages.lock().drain(..).collect()
     }
 
     fn load_block_entities(&self, block_entities: Vec<Option<crate::nbt::NamedTag>>) {
@@ -2564,6 +3885,18 @@ impl Server {
                 chunk_data.data.data,
             )
             .unwrap();
+        if chunk_data.new {
+            self.world.load_biomes_3d(
+                chunk_data.chunk_x,
+                chunk_data.chunk_z,
+                &chunk_data
+                    .biomes
+                    .data
+                    .iter()
+                    .map(|biome| biome.0)
+                    .collect::<Vec<_>>(),
+            );
+        }
         self.load_block_entities(chunk_data.block_entities.data);
     }
 
@@ -2581,6 +3914,13 @@ impl Server {
                 chunk_data.data.data,
             )
             .unwrap();
+        if chunk_data.new {
+            self.world.load_biomes_3d(
+                chunk_data.chunk_x,
+                chunk_data.chunk_z,
+                &chunk_data.biomes.data,
+            );
+        }
         self.load_block_entities(chunk_data.block_entities.data);
     }
 
@@ -2595,6 +3935,13 @@ impl Server {
                 chunk_data.data.data,
             )
             .unwrap();
+        if chunk_data.new {
+            self.world.load_biomes_3d(
+                chunk_data.chunk_x,
+                chunk_data.chunk_z,
+                &chunk_data.biomes.data,
+            );
+        }
         self.load_block_entities(chunk_data.block_entities.data);
     }
 
@@ -2716,6 +4063,39 @@ impl Server {
         world.set_block(location, block)
     }
 
+    fn on_block_action(&self, block_action: packet::play::clientbound::BlockAction) {
+        self.on_block_action_in_world(
+            block_action.location,
+            block_action.byte1,
+            block_action.byte2,
+        );
+    }
+
+    fn on_block_action_u16(&self, block_action: packet::play::clientbound::BlockAction_u16) {
+        self.on_block_action_in_world(
+            crate::shared::Position::new(block_action.x, block_action.y as i32, block_action.z),
+            block_action.byte1,
+            block_action.byte2,
+        );
+    }
+
+    fn on_block_action_in_world(&self, location: Position, action: u8, param: u8) {
+        let world = self.world.clone();
+        let block = world.get_block(location);
+        // Sent before the blocks are replaced by moving pistons, so the moved ones are
+        // still in the world
+        if let Some(animation) =
+            entity::block_entity::piston::PistonAnimation::from_block_action(block, action, param)
+        {
+            entity::block_entity::piston::create_entity(
+                &mut self.entities.clone().write(),
+                &world,
+                location,
+                animation,
+            );
+        }
+    }
+
     fn on_block_change_varint(&self, block_change: packet::play::clientbound::BlockChange_VarInt) {
         self.on_block_change(block_change.location, block_change.block_id.0)
     }
@@ -2820,10 +4200,32 @@ impl Server {
     }
 
     pub fn on_update_health(&self, health: f32, food: u8, saturation: u8) {
-        self.hud_context
-            .clone()
-            .write()
-            .update_health_and_food(health, food, saturation);
+        let previous_health = {
+            let hud_context = self.hud_context.clone();
+            let mut hud_context = hud_context.write();
+            let previous_health = hud_context.get_health();
+            hud_context.update_health_and_food(health, food, saturation);
+            previous_health
+        };
+        if health < previous_health && health > 0.0 {
+            self.play_sound(SoundEvent {
+                name: SoundName::Event("entity.player.hurt".to_owned()),
+                category: SoundCategory::Players,
+                position: None,
+                volume: 1.0,
+                pitch: rand::thread_rng().gen_range(0.8..1.2),
+            });
+        }
+        if let Some(player) = *self.player.clone().read() {
+            if let Some(movement) = self
+                .entities
+                .clone()
+                .write()
+                .get_component_mut(player, self.player_movement)
+            {
+                movement.too_hungry_to_sprint = food < settings::MIN_SPRINT_FOOD;
+            }
+        }
         if health <= 0.0 && !*self.dead.read() {
             *self.dead.write() = true;
             *self.just_died.write() = true;
@@ -2831,6 +4233,16 @@ impl Server {
     }
 }
 
+const PLAYER_ACTION_START_SNEAK: i32 = 0;
+const PLAYER_ACTION_STOP_SNEAK: i32 = 1;
+const PLAYER_ACTION_START_SPRINT: i32 = 3;
+const PLAYER_ACTION_STOP_SPRINT: i32 = 4;
+
+/// How many chunks around the player have to be meshed before the downloading terrain
+/// screen closes, and how long it waits for them at most.
+const TERRAIN_READY_RADIUS: i32 = 2;
+const TERRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, Copy)]
 enum TeleportFlag {
@@ -2848,3 +4260,36 @@ fn calculate_relative_teleport(flag: TeleportFlag, flags: u8, base: f64, val: f6
         base + val
     }
 }
+
+/// Takes the connection out of `conn` and closes it, if there still is one.
+fn close_connection(conn: &RwLock<Option<Conn>>) {
+    let conn = conn.write().take();
+    if let Some(mut conn) = conn {
+        if let Err(err) = conn.close() {
+            warn!("Failed to close the connection: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn shutdown_closes_the_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut remote, _) = listener.accept().unwrap();
+        let conn = RwLock::new(Some(
+            Conn::from_stream(stream, protocol::SUPPORTED_PROTOCOLS[0]).unwrap(),
+        ));
+        close_connection(&conn);
+        assert!(conn.read().is_none());
+        // The server sees the connection end instead of it timing out
+        assert_eq!(remote.read(&mut [0; 1]).unwrap(), 0);
+        // Nothing's left to close the second time
+        close_connection(&conn);
+    }
+}
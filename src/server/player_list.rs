@@ -0,0 +1,224 @@
+use crate::format::Component;
+use crate::protocol::packet::{PlayerDetail, PlayerProperty};
+use crate::protocol::UUID;
+use crate::types::hash::FNVHash;
+use crate::types::GameMode;
+use log::error;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+
+#[derive(Debug)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub uuid: UUID,
+    pub skin_url: Option<String>,
+
+    pub display_name: Option<Component>,
+    pub ping: i32,
+    pub gamemode: GameMode,
+}
+
+/// Everyone the server lists as online, as shown in the player list overlay.
+#[derive(Default)]
+pub struct PlayerList {
+    players: HashMap<UUID, PlayerInfo, BuildHasherDefault<FNVHash>>,
+    header: Option<Component>,
+    footer: Option<Component>,
+}
+
+impl PlayerList {
+    pub fn new() -> PlayerList {
+        Default::default()
+    }
+
+    pub fn get(&self, uuid: &UUID) -> Option<&PlayerInfo> {
+        self.players.get(uuid)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// Adds a player the server didn't list before, 1.7 only lists them once spawned.
+    pub fn insert_if_missing(&mut self, info: PlayerInfo) {
+        self.players.entry(info.uuid.clone()).or_insert(info);
+    }
+
+    /// Applies an entry of a PlayerInfo packet.
+    pub fn apply(&mut self, detail: PlayerDetail) {
+        match detail {
+            PlayerDetail::Add {
+                name,
+                uuid,
+                properties,
+                display,
+                gamemode,
+                ping,
+            } => {
+                let info = self.players.entry(uuid.clone()).or_insert(PlayerInfo {
+                    name: name.clone(),
+                    uuid,
+                    skin_url: None,
+
+                    display_name: display.clone(),
+                    ping: ping.0,
+                    gamemode: GameMode::from_int(gamemode.0),
+                });
+                // Re-set the props of the player in case of dodgy server implementations
+                info.name = name;
+                info.display_name = display;
+                info.ping = ping.0;
+                info.gamemode = GameMode::from_int(gamemode.0);
+                if let Some(skin_url) = skin_url(&properties) {
+                    info.skin_url = Some(skin_url);
+                }
+            }
+            PlayerDetail::UpdateGamemode { uuid, gamemode } => {
+                if let Some(info) = self.players.get_mut(&uuid) {
+                    info.gamemode = GameMode::from_int(gamemode.0);
+                }
+            }
+            PlayerDetail::UpdateLatency { uuid, ping } => {
+                if let Some(info) = self.players.get_mut(&uuid) {
+                    info.ping = ping.0;
+                }
+            }
+            PlayerDetail::UpdateDisplayName { uuid, display } => {
+                if let Some(info) = self.players.get_mut(&uuid) {
+                    info.display_name = display;
+                }
+            }
+            PlayerDetail::Remove { uuid } => {
+                self.players.remove(&uuid);
+            }
+        }
+    }
+
+    /// The players in the order they're listed: spectators last, otherwise by name.
+    pub fn sorted(&self) -> Vec<&PlayerInfo> {
+        let mut players: Vec<_> = self.players.values().collect();
+        players.sort_by_cached_key(|info| {
+            (
+                matches!(info.gamemode, GameMode::Spectator),
+                info.name.to_lowercase(),
+            )
+        });
+        players
+    }
+
+    /// Sets the text shown above and below the list, servers clear them by sending
+    /// empty text.
+    pub fn set_header_footer(&mut self, header: Component, footer: Component) {
+        let non_empty = |text: Component| {
+            if text.to_string().is_empty() {
+                None
+            } else {
+                Some(text)
+            }
+        };
+        self.header = non_empty(header);
+        self.footer = non_empty(footer);
+    }
+
+    pub fn header(&self) -> Option<&Component> {
+        self.header.as_ref()
+    }
+
+    pub fn footer(&self) -> Option<&Component> {
+        self.footer.as_ref()
+    }
+}
+
+/// Finds the skin in the player's textures property.
+fn skin_url(properties: &[PlayerProperty]) -> Option<String> {
+    let mut skin_url = None;
+    for prop in properties {
+        if prop.name != "textures" {
+            continue;
+        }
+        // Ideally we would check the signature of the blob to
+        // verify it was from Mojang and not faked by the server
+        // but this requires the public key which is distributed
+        // authlib. We could download authlib on startup and extract
+        // the key but this seems like overkill compared to just
+        // whitelisting Mojang's texture servers instead.
+        let skin_blob_result = &base64::decode(&prop.value);
+        let skin_blob = match skin_blob_result {
+            Ok(val) => val,
+            Err(err) => {
+                error!("Failed to decode skin blob, {:?}", err);
+                continue;
+            }
+        };
+        let skin_blob: serde_json::Value = match serde_json::from_slice(skin_blob) {
+            Ok(val) => val,
+            Err(err) => {
+                error!("Failed to parse skin blob, {:?}", err);
+                continue;
+            }
+        };
+        if let Some(url) = skin_blob
+            .pointer("/textures/SKIN/url")
+            .and_then(|v| v.as_str())
+        {
+            skin_url = Some(url.to_owned());
+        }
+    }
+    skin_url
+}
+
+/// The row of the ping bars in the icons texture, from 0 for five bars to 4 for one and
+/// 5 for a player not (yet) connected.
+pub fn ping_icon(ping: i32) -> u8 {
+    match ping {
+        i32::MIN..=-1 => 5,
+        0..=149 => 0,
+        150..=299 => 1,
+        300..=599 => 2,
+        600..=999 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::VarInt;
+    use std::str::FromStr;
+
+    fn add(uuid: &UUID, name: &str, gamemode: i32) -> PlayerDetail {
+        PlayerDetail::Add {
+            uuid: uuid.clone(),
+            name: name.to_owned(),
+            properties: vec![],
+            gamemode: VarInt(gamemode),
+            ping: VarInt(42),
+            display: None,
+        }
+    }
+
+    #[test]
+    fn player_info_updates_roster() {
+        let steve = UUID::from_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap();
+        let alex = UUID::from_str("ec561538-f3fd-461d-aff5-086b22154bce").unwrap();
+        let mut list = PlayerList::new();
+        list.apply(add(&steve, "Steve", 3));
+        list.apply(add(&alex, "alex", 0));
+        assert_eq!(list.sorted().len(), 2);
+
+        list.apply(PlayerDetail::UpdateLatency {
+            uuid: alex.clone(),
+            ping: VarInt(700),
+        });
+        assert_eq!(ping_icon(list.get(&alex).unwrap().ping), 3);
+        // Spectators are listed after everyone else
+        let names: Vec<_> = list.sorted().iter().map(|p| p.name.clone()).collect();
+        assert_eq!(names, vec!["alex", "Steve"]);
+
+        list.apply(PlayerDetail::Remove {
+            uuid: steve.clone(),
+        });
+        assert_eq!(list.sorted().len(), 1);
+        assert!(list.get(&steve).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::paths;
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+/// The results reported back to the server by `ResourcePackStatus`.
+pub const STATUS_LOADED: i32 = 0;
+pub const STATUS_DECLINED: i32 = 1;
+pub const STATUS_FAILED: i32 = 2;
+pub const STATUS_ACCEPTED: i32 = 3;
+
+/// Larger packs are refused, like vanilla does.
+const MAX_SIZE: u64 = 50 * 1024 * 1024;
+
+/// A resource pack the server asked the client to use.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub url: String,
+    /// The SHA-1 of the pack as hex, servers may leave it empty.
+    pub hash: String,
+}
+
+impl Request {
+    /// Where the pack is cached, named after its hash or its URL if there's none.
+    fn cache_path(&self) -> PathBuf {
+        let name = if is_valid_hash(&self.hash) {
+            self.hash.to_lowercase()
+        } else {
+            sha1_hex(self.url.as_bytes())
+        };
+        paths::get_cache_dir()
+            .join("server-resource-packs")
+            .join(format!("{}.zip", name))
+    }
+}
+
+/// A pack being downloaded on another thread.
+pub struct Download {
+    pub request: Request,
+    result: mpsc::Receiver<Result<PathBuf, String>>,
+}
+
+impl Download {
+    pub fn start(request: Request) -> Download {
+        let (send, recv) = mpsc::channel();
+        let thread_request = request.clone();
+        thread::spawn(move || {
+            // The receiver is gone if the server was left in the meantime
+            let _ = send.send(download(&thread_request));
+        });
+        Download {
+            request,
+            result: recv,
+        }
+    }
+
+    /// The path of the verified zip once the download is done, or why it failed.
+    pub fn poll(&self) -> Option<Result<PathBuf, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err("the download stopped".to_owned())),
+        }
+    }
+}
+
+fn download(request: &Request) -> Result<PathBuf, String> {
+    let path = request.cache_path();
+    if is_cached(&path, &request.hash) {
+        return Ok(path);
+    }
+    let mut res = reqwest::blocking::get(&request.url).map_err(|err| err.to_string())?;
+    if !res.status().is_success() {
+        return Err(format!("server replied with {}", res.status()));
+    }
+    let mut data = vec![];
+    (&mut res)
+        .take(MAX_SIZE + 1)
+        .read_to_end(&mut data)
+        .map_err(|err| err.to_string())?;
+    if data.len() as u64 > MAX_SIZE {
+        return Err(format!("larger than {} bytes", MAX_SIZE));
+    }
+    if !matches_hash(&data, &request.hash) {
+        return Err(format!(
+            "SHA-1 is {} instead of {}",
+            sha1_hex(&data),
+            request.hash
+        ));
+    }
+    fs::create_dir_all(path.parent().unwrap()).map_err(|err| err.to_string())?;
+    let tmp_file = path.with_extension("tmp");
+    fs::write(&tmp_file, &data).map_err(|err| err.to_string())?;
+    fs::rename(&tmp_file, &path).map_err(|err| err.to_string())?;
+    Ok(path)
+}
+
+/// Whether the pack cached at `path` can be used instead of downloading it again. Packs
+/// without a hash are always downloaded, the server may have updated them since.
+fn is_cached(path: &Path, hash: &str) -> bool {
+    is_valid_hash(hash) && matches!(fs::read(path), Ok(data) if matches_hash(&data, hash))
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn sha1_hex(data: &[u8]) -> String {
+    Sha1::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Whether `data` has the SHA-1 `hash`. Packs sent without a valid hash can't be
+/// checked, so they are used as they are.
+pub fn matches_hash(data: &[u8], hash: &str) -> bool {
+    !is_valid_hash(hash) || sha1_hex(data).eq_ignore_ascii_case(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn download_must_match_hash() {
+        let data = b"resource pack";
+        let hash = sha1_hex(data);
+        assert_eq!(hash.len(), 40);
+        assert!(matches_hash(data, &hash));
+        assert!(matches_hash(data, &hash.to_uppercase()));
+        // A corrupted or swapped download is rejected
+        assert!(!matches_hash(b"resource pach", &hash));
+        assert!(!matches_hash(
+            data,
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+        ));
+        // Nothing to check against
+        assert!(matches_hash(data, ""));
+    }
+
+    #[test]
+    fn packs_without_hash_are_downloaded_again() {
+        let path = std::env::temp_dir().join(format!(
+            "leafish-resource-pack-test-{}.zip",
+            std::process::id()
+        ));
+        let data = b"resource pack";
+        fs::write(&path, data).unwrap();
+        assert!(is_cached(&path, &sha1_hex(data)));
+        assert!(!is_cached(&path, &sha1_hex(b"updated resource pack")));
+        assert!(!is_cached(&path, ""));
+        assert!(!is_cached(&path, "not a hash"));
+        fs::remove_file(&path).unwrap();
+        assert!(!is_cached(&path, &sha1_hex(data)));
+    }
+}
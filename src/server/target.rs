@@ -1,14 +1,16 @@
 use crate::render;
 use crate::render::model;
 use crate::shared::{Direction, Position};
-use crate::world;
 use crate::world::block;
-use collision::{self, Aabb};
+use collision::Aabb;
 
 pub struct Info {
     model: Option<model::ModelKey>,
     last_block: block::Block,
     last_pos: Position,
+    last_face: Direction,
+    crack_model: Option<model::ModelKey>,
+    last_crack: Option<(Position, u32)>,
 }
 
 impl Default for Info {
@@ -23,6 +25,18 @@ impl Info {
             model: None,
             last_block: block::Air {},
             last_pos: Position::new(0, 0, 0),
+            last_face: Direction::Up,
+            crack_model: None,
+            last_crack: None,
+        }
+    }
+
+    /// The block being looked at and the face of it.
+    pub fn target(&self) -> Option<(Position, block::Block, Direction)> {
+        if self.last_block == (block::Air {}) {
+            None
+        } else {
+            Some((self.last_pos, self.last_block, self.last_face))
         }
     }
 
@@ -31,9 +45,17 @@ impl Info {
         if let Some(model) = self.model.take() {
             renderer.model.remove_model(model);
         }
+        self.update_crack(renderer, None);
     }
 
-    pub fn update(&mut self, renderer: &mut render::Renderer, pos: Position, bl: block::Block) {
+    pub fn update(
+        &mut self,
+        renderer: &mut render::Renderer,
+        pos: Position,
+        bl: block::Block,
+        face: Direction,
+    ) {
+        self.last_face = face;
         if self.last_block == bl && self.last_pos == pos {
             return;
         }
@@ -44,85 +66,25 @@ impl Info {
         }
         let mut parts = vec![];
 
-        const LINE_SIZE: f64 = 1.0 / 128.0;
         let tex = render::Renderer::get_texture(renderer.get_textures_ref(), "leafish:solid");
-
-        for bound in bl.get_collision_boxes() {
-            let bound = bound.add_v(cgmath::Vector3::new(
-                pos.x as f64,
-                pos.y as f64,
-                pos.z as f64,
-            ));
-            for point in [
-                (bound.min.x, bound.min.z),
-                (bound.min.x, bound.max.z),
-                (bound.max.x, bound.min.z),
-                (bound.max.x, bound.max.z),
-            ]
-            .iter()
-            {
-                model::append_box(
-                    &mut parts,
-                    (point.0 - LINE_SIZE) as f32,
-                    (bound.min.y - LINE_SIZE) as f32,
-                    (point.1 - LINE_SIZE) as f32,
-                    (LINE_SIZE * 2.0) as f32,
-                    ((bound.max.y - bound.min.y) + LINE_SIZE * 2.0) as f32,
-                    (LINE_SIZE * 2.0) as f32,
-                    [
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                    ],
-                );
-            }
-
-            for point in [
-                (bound.min.x, bound.min.z, bound.max.x, bound.min.z),
-                (bound.min.x, bound.max.z, bound.max.x, bound.max.z),
-                (bound.min.x, bound.min.z, bound.min.x, bound.max.z),
-                (bound.max.x, bound.min.z, bound.max.x, bound.max.z),
-            ]
-            .iter()
-            {
-                model::append_box(
-                    &mut parts,
-                    (point.0 - LINE_SIZE) as f32,
-                    (bound.min.y - LINE_SIZE) as f32,
-                    (point.1 - LINE_SIZE) as f32,
-                    ((point.2 - point.0) + (LINE_SIZE * 2.0)) as f32,
-                    (LINE_SIZE * 2.0) as f32,
-                    ((point.3 - point.1) + (LINE_SIZE * 2.0)) as f32,
-                    [
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                    ],
-                );
-                model::append_box(
-                    &mut parts,
-                    (point.0 - LINE_SIZE) as f32,
-                    (bound.max.y - LINE_SIZE) as f32,
-                    (point.1 - LINE_SIZE) as f32,
-                    ((point.2 - point.0) + (LINE_SIZE * 2.0)) as f32,
-                    (LINE_SIZE * 2.0) as f32,
-                    ((point.3 - point.1) + (LINE_SIZE * 2.0)) as f32,
-                    [
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                        Some(tex.clone()),
-                    ],
-                );
-            }
+        for edge in outline_edges(pos, bl) {
+            model::append_box(
+                &mut parts,
+                edge.min.x as f32,
+                edge.min.y as f32,
+                edge.min.z as f32,
+                (edge.max.x - edge.min.x) as f32,
+                (edge.max.y - edge.min.y) as f32,
+                (edge.max.z - edge.min.z) as f32,
+                [
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                ],
+            );
         }
 
         for part in &mut parts {
@@ -133,188 +95,201 @@ impl Info {
 
         self.model = Some(renderer.model.create_model(model::DEFAULT, vec![parts]));
     }
-}
 
-#[allow(clippy::type_complexity)]
-pub fn test_block(
-    world: &world::World,
-    pos: Position,
-    s: cgmath::Vector3<f64>,
-    d: cgmath::Vector3<f64>,
-) -> (
-    bool,
-    Option<(Position, block::Block, Direction, cgmath::Vector3<f64>)>,
-) {
-    let block = world.get_block(pos);
-    let posf = cgmath::Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64);
-    for bound in block.get_collision_boxes() {
-        let bound = bound.add_v(posf);
-        if let Some(hit) = intersects_line(bound, s, d) {
-            let cursor = hit - posf;
-            let face = find_face(bound, hit);
-            return (true, Some((pos, block, face, cursor)));
+    /// Shows the cracks of the block being dug, at the given stage of breaking.
+    pub fn update_crack(
+        &mut self,
+        renderer: &mut render::Renderer,
+        crack: Option<(Position, block::Block, u32)>,
+    ) {
+        let key = crack.map(|(pos, _, stage)| (pos, stage));
+        if self.last_crack == key {
+            return;
         }
-    }
-    (false, None)
-}
-
-fn find_face(bound: collision::Aabb3<f64>, hit: cgmath::Vector3<f64>) -> Direction {
-    if (bound.min.x - hit.x).abs() < 0.01 {
-        Direction::West
-    } else if (bound.max.x - hit.x).abs() < 0.01 {
-        Direction::East
-    } else if (bound.min.y - hit.y).abs() < 0.01 {
-        Direction::Down
-    } else if (bound.max.y - hit.y).abs() < 0.01 {
-        Direction::Up
-    } else if (bound.min.z - hit.z).abs() < 0.01 {
-        Direction::North
-    } else if (bound.max.z - hit.z).abs() < 0.01 {
-        Direction::South
-    } else {
-        Direction::Up
-    }
-}
-
-fn intersects_line(
-    bound: collision::Aabb3<f64>,
-    origin: cgmath::Vector3<f64>,
-    dir: cgmath::Vector3<f64>,
-) -> Option<cgmath::Vector3<f64>> {
-    const RIGHT: usize = 0;
-    const LEFT: usize = 1;
-    const MIDDLE: usize = 2;
-    let mut quadrant = [0, 0, 0];
-    let mut candidate_plane = [0.0, 0.0, 0.0];
-    let mut max_t = [0.0, 0.0, 0.0];
-    let mut inside = true;
-    for i in 0..3 {
-        if origin[i] < bound.min[i] {
-            quadrant[i] = LEFT;
-            candidate_plane[i] = bound.min[i];
-            inside = false;
-        } else if origin[i] > bound.max[i] {
-            quadrant[i] = RIGHT;
-            candidate_plane[i] = bound.max[i];
-            inside = false;
-        } else {
-            quadrant[i] = MIDDLE;
+        self.last_crack = key;
+        if let Some(model) = self.crack_model.take() {
+            renderer.model.remove_model(model);
         }
-    }
-    if inside {
-        return Some(origin);
-    }
+        let (pos, bl, stage) = match crack {
+            Some(crack) => crack,
+            None => return,
+        };
 
-    for i in 0..3 {
-        if quadrant[i] != MIDDLE && dir[i] != 0.0 {
-            max_t[i] = (candidate_plane[i] - origin[i]) / dir[i];
-        }
-    }
-    let mut which_plane = 0;
-    for i in 1..3 {
-        if max_t[which_plane] < max_t[i] {
-            which_plane = i;
+        // Slightly larger than the block to avoid z-fighting with it
+        const OFFSET: f64 = 1.0 / 512.0;
+        let tex = render::Renderer::get_texture(
+            renderer.get_textures_ref(),
+            &format!("minecraft:blocks/destroy_stage_{}", stage),
+        );
+        let mut parts = vec![];
+        for bound in bl.get_collision_boxes() {
+            let bound = bound.add_v(cgmath::Vector3::new(
+                pos.x as f64,
+                pos.y as f64,
+                pos.z as f64,
+            ));
+            model::append_box(
+                &mut parts,
+                (bound.min.x - OFFSET) as f32,
+                (bound.min.y - OFFSET) as f32,
+                (bound.min.z - OFFSET) as f32,
+                (bound.max.x - bound.min.x + OFFSET * 2.0) as f32,
+                (bound.max.y - bound.min.y + OFFSET * 2.0) as f32,
+                (bound.max.z - bound.min.z + OFFSET * 2.0) as f32,
+                [
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                    Some(tex.clone()),
+                ],
+            );
         }
+        self.crack_model = Some(renderer.model.create_model(model::DEFAULT, vec![parts]));
     }
-    if max_t[which_plane] < 0.0 {
-        return None;
-    }
+}
 
-    let mut coord = cgmath::Vector3::new(0.0, 0.0, 0.0);
-    for i in 0..3 {
-        if which_plane != i {
-            coord[i] = origin[i] + max_t[which_plane] * dir[i];
-            if coord[i] < bound.min[i] || coord[i] > bound.max[i] {
-                return None;
+/// Thin boxes along the twelve edges of each collision box of `bl` at `pos`, which make
+/// up the outline of a targeted block.
+pub fn outline_edges(pos: Position, bl: block::Block) -> Vec<collision::Aabb3<f64>> {
+    const LINE_SIZE: f64 = 1.0 / 128.0;
+    let corner = cgmath::Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64);
+    let thickness = cgmath::Vector3::new(LINE_SIZE, LINE_SIZE, LINE_SIZE);
+    let line = |from: cgmath::Point3<f64>, to: cgmath::Point3<f64>| {
+        collision::Aabb3::new(from - thickness, to + thickness)
+    };
+    let mut edges = vec![];
+    for bound in bl.get_collision_boxes() {
+        let bound = bound.add_v(corner);
+        let (min, max) = (bound.min, bound.max);
+        for &(x, z) in &[
+            (min.x, min.z),
+            (min.x, max.z),
+            (max.x, min.z),
+            (max.x, max.z),
+        ] {
+            edges.push(line(
+                cgmath::Point3::new(x, min.y, z),
+                cgmath::Point3::new(x, max.y, z),
+            ));
+        }
+        for &y in &[min.y, max.y] {
+            for &z in &[min.z, max.z] {
+                edges.push(line(
+                    cgmath::Point3::new(min.x, y, z),
+                    cgmath::Point3::new(max.x, y, z),
+                ));
+            }
+            for &x in &[min.x, max.x] {
+                edges.push(line(
+                    cgmath::Point3::new(x, y, min.z),
+                    cgmath::Point3::new(x, y, max.z),
+                ));
             }
-        } else {
-            coord[i] = candidate_plane[i];
         }
     }
-    Some(coord)
+    edges
 }
 
-pub fn trace_ray<F, R>(
-    world: &world::World,
-    max: f64,
-    s: cgmath::Vector3<f64>,
-    d: cgmath::Vector3<f64>,
-    collide_func: F,
-) -> Option<R>
-where
-    F: Fn(&world::World, Position, cgmath::Vector3<f64>, cgmath::Vector3<f64>) -> (bool, Option<R>),
-{
-    struct Gen {
-        count: i32,
-        base: f64,
-        d: f64,
+/// Where a block placed against `face` of the targeted block goes. Blocks such as tall
+/// grass or water are replaced instead of placed against.
+pub fn placement_position(
+    target: Position,
+    target_block: block::Block,
+    face: Direction,
+) -> Position {
+    if target_block.is_replaceable() {
+        target
+    } else {
+        target.shift(face)
     }
-    impl Gen {
-        fn new(start: f64, mut d: f64) -> Gen {
-            let base = if d > 0.0 {
-                (start.ceil() - start) / d
-            } else if d < 0.0 {
-                d = d.abs();
-                (start - start.floor()) / d
-            } else {
-                0.0
-            };
-            Gen { count: 0, base, d }
-        }
+}
 
-        fn next(&mut self) -> f64 {
-            self.count += 1;
-            if self.d == 0.0 {
-                f64::INFINITY
-            } else {
-                self.base + ((self.count as f64 - 1.0) / self.d)
-            }
-        }
-    }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::world::World;
+    use crossbeam_channel::unbounded;
 
-    let mut x_gen = Gen::new(s.x, d.x);
-    let mut y_gen = Gen::new(s.y, d.y);
-    let mut z_gen = Gen::new(s.z, d.z);
-    let mut next_nx = x_gen.next();
-    let mut next_ny = y_gen.next();
-    let mut next_nz = z_gen.next();
+    #[test]
+    fn outline_follows_raycast_hit() {
+        // Setting blocks queues light updates, which need somewhere to go
+        let (light_send, _light_recv) = unbounded();
+        let world = World::new(754, light_send);
+        let slab = block::StoneSlab {
+            variant: block::StoneSlabVariant::Stone,
+            half: block::BlockHalf::Bottom,
+        };
+        world.set_block(Position::new(2, 64, 0), slab);
 
-    let mut x = s.x.floor() as i32;
-    let mut y = s.y.floor() as i32;
-    let mut z = s.z.floor() as i32;
+        let hit = world
+            .raycast(
+                cgmath::Vector3::new(2.5, 66.0, 0.5),
+                cgmath::Vector3::new(0.0, -1.0, 0.0),
+                4.0,
+            )
+            .unwrap();
+        assert_eq!(hit.position, Position::new(2, 64, 0));
+        let edges = outline_edges(hit.position, hit.block);
+        assert_eq!(edges.len(), 12);
 
-    loop {
-        let (hit, ret) = collide_func(world, Position::new(x, y, z), s, d);
-        if hit {
-            return ret;
-        }
-        let next_n = if next_nx <= next_ny {
-            if next_nx <= next_nz {
-                let old = next_nx;
-                next_nx = x_gen.next();
-                x += d.x.signum() as i32;
-                old
-            } else {
-                let old = next_nz;
-                next_nz = z_gen.next();
-                z += d.z.signum() as i32;
-                old
-            }
-        } else if next_ny <= next_nz {
-            let old = next_ny;
-            next_ny = y_gen.next();
-            y += d.y.signum() as i32;
-            old
-        } else {
-            let old = next_nz;
-            next_nz = z_gen.next();
-            z += d.z.signum() as i32;
-            old
-        };
-        if next_n > max {
-            break;
-        }
+        // The edges span the slab's half height box, just outside of it
+        let min = edges.iter().fold(edges[0].min, |min, edge| {
+            cgmath::Point3::new(
+                min.x.min(edge.min.x),
+                min.y.min(edge.min.y),
+                min.z.min(edge.min.z),
+            )
+        });
+        let max = edges.iter().fold(edges[0].max, |max, edge| {
+            cgmath::Point3::new(
+                max.x.max(edge.max.x),
+                max.y.max(edge.max.y),
+                max.z.max(edge.max.z),
+            )
+        });
+        let line = 1.0 / 128.0;
+        assert_eq!(min, cgmath::Point3::new(2.0 - line, 64.0 - line, -line));
+        assert_eq!(
+            max,
+            cgmath::Point3::new(3.0 + line, 64.5 + line, 1.0 + line)
+        );
     }
 
-    None
+    #[test]
+    fn placement_offsets() {
+        let target = Position::new(10, 64, -3);
+        let stone = block::Stone {
+            variant: block::StoneVariant::Normal,
+        };
+        let placed: Vec<_> = Direction::all()
+            .into_iter()
+            .map(|face| placement_position(target, stone, face))
+            .collect();
+        assert_eq!(
+            placed,
+            vec![
+                Position::new(10, 63, -3),
+                Position::new(10, 65, -3),
+                Position::new(10, 64, -4),
+                Position::new(10, 64, -2),
+                Position::new(9, 64, -3),
+                Position::new(11, 64, -3),
+            ]
+        );
+
+        // Tall grass is replaced rather than built upon
+        let grass = block::TallGrass {
+            variant: block::TallGrassVariant::TallGrass,
+        };
+        assert_eq!(placement_position(target, grass, Direction::Up), target);
+        let snow = block::SnowLayer { layers: 1 };
+        assert_eq!(placement_position(target, snow, Direction::East), target);
+        let snow = block::SnowLayer { layers: 2 };
+        assert_eq!(
+            placement_position(target, snow, Direction::East),
+            Position::new(11, 64, -3)
+        );
+    }
 }
@@ -0,0 +1,232 @@
+use crate::render;
+use crate::render::model;
+use crate::world::biome::Biome;
+use cgmath::{Matrix4, Point3, Vector3};
+use rand::{self, Rng};
+
+/// How much the rain level changes per tick while it starts or stops, like vanilla.
+const RAIN_STEP: f32 = 0.01;
+
+/// The particles are split into groups that fall with their own offset, more of them are
+/// shown the heavier it rains.
+const GROUPS: usize = 8;
+const PARTICLES_PER_GROUP: usize = 64;
+/// How far around the camera particles fall, in blocks.
+const RADIUS: f32 = 12.0;
+/// The height of the column of particles falling around the camera.
+const HEIGHT: f32 = 16.0;
+
+/// The weather the server sends through `ChangeGameState`.
+#[derive(Debug, Default)]
+pub struct Weather {
+    pub raining: bool,
+    /// The rain level being ramped towards, from 0.0 to 1.0.
+    pub rain_target: f32,
+    pub rain_level: f32,
+    pub thunder_level: f32,
+}
+
+impl Weather {
+    /// Applies a `ChangeGameState` packet, returns whether it was about the weather.
+    pub fn on_game_state(&mut self, reason: u8, value: f32) -> bool {
+        match reason {
+            1 => {
+                self.raining = true;
+                self.rain_target = 1.0;
+            }
+            2 => {
+                self.raining = false;
+                self.rain_target = 0.0;
+            }
+            // Newer servers send the level they ramp themselves
+            7 => self.rain_target = value.clamp(0.0, 1.0),
+            8 => self.thunder_level = value.clamp(0.0, 1.0),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Moves the rain level towards its target, `delta` in frame deltas (1/60ths of a second).
+    pub fn tick(&mut self, delta: f64) {
+        let step = RAIN_STEP * (delta / 3.0) as f32;
+        if self.rain_level < self.rain_target {
+            self.rain_level = (self.rain_level + step).min(self.rain_target);
+        } else {
+            self.rain_level = (self.rain_level - step).max(self.rain_target);
+        }
+    }
+
+    /// How much of the sky light is left while it rains or thunders.
+    pub fn sky_light_factor(&self) -> f32 {
+        (1.0 - self.rain_level * 5.0 / 16.0)
+            * (1.0 - self.thunder_level * self.rain_level * 5.0 / 16.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precipitation {
+    None,
+    Rain,
+    Snow,
+}
+
+impl Precipitation {
+    pub fn for_biome(biome: Biome) -> Precipitation {
+        if biome.moisture <= 0 {
+            // Deserts and the nether stay dry
+            Precipitation::None
+        } else if biome.temperature < 15 {
+            Precipitation::Snow
+        } else {
+            Precipitation::Rain
+        }
+    }
+
+    /// How fast the particles fall, in blocks per frame delta.
+    fn fall_speed(self) -> f64 {
+        match self {
+            Precipitation::Snow => 0.05,
+            _ => 0.5,
+        }
+    }
+}
+
+/// The rain or snow particles falling around the camera.
+pub struct WeatherModel {
+    model: model::ModelKey,
+    pub precipitation: Precipitation,
+    time: f64,
+}
+
+impl WeatherModel {
+    pub fn new(renderer: &mut render::Renderer, precipitation: Precipitation) -> WeatherModel {
+        let (texture, width, height) = match precipitation {
+            Precipitation::Snow => ("environment/snow", 0.4, 0.4),
+            _ => ("environment/rain", 0.3, 1.0),
+        };
+        let tex = render::Renderer::get_texture(renderer.get_textures_ref(), texture);
+        let mut rng = rand::thread_rng();
+        let mut groups = vec![];
+        for _ in 0..GROUPS {
+            let mut verts = vec![];
+            for _ in 0..PARTICLES_PER_GROUP {
+                let x = rng.gen_range(-RADIUS..RADIUS);
+                let y = rng.gen_range(0.0..HEIGHT);
+                let z = rng.gen_range(-RADIUS..RADIUS);
+                let column = rng.gen_range(0..4) as f64 * 0.25;
+                // Repeated a column higher, so that the group can wrap around while falling
+                for y in &[y, y + HEIGHT] {
+                    for &(dx, dz) in &[(width, 0.0), (0.0, width)] {
+                        append_quad(
+                            &mut verts,
+                            &tex,
+                            column,
+                            Point3::new(x - dx / 2.0, *y, z - dz / 2.0),
+                            Vector3::new(dx, height, dz),
+                        );
+                    }
+                }
+            }
+            groups.push(verts);
+        }
+        let model = renderer.model.create_model(model::DEFAULT, groups);
+        renderer.model.get_model(model).unwrap().radius = RADIUS * 2.0 + HEIGHT;
+        WeatherModel {
+            model,
+            precipitation,
+            time: 0.0,
+        }
+    }
+
+    /// Moves the particles along with the camera, showing more groups the heavier it rains.
+    pub fn tick(&mut self, renderer: &mut render::Renderer, delta: f64, rain_level: f32) {
+        self.time += delta * self.precipitation.fall_speed();
+        let camera = renderer.camera.pos;
+        let shown = (rain_level * GROUPS as f32).ceil() as usize;
+        let mdl = renderer.model.get_model(self.model).unwrap();
+        mdl.x = camera.x as f32;
+        mdl.y = camera.y as f32;
+        mdl.z = camera.z as f32;
+        mdl.sky_light = 15.0;
+        mdl.block_light = 15.0;
+        for (group, matrix) in mdl.matrix.iter_mut().enumerate() {
+            if group >= shown {
+                // Collapsed into a point, so nothing is drawn
+                *matrix = Matrix4::from_scale(0.0);
+                continue;
+            }
+            let offset = group as f64 * HEIGHT as f64 / GROUPS as f64;
+            let fall = ((self.time + offset) % HEIGHT as f64) as f32;
+            *matrix = Matrix4::from_translation(Vector3::new(
+                camera.x.floor() as f32,
+                -(camera.y.floor() as f32 - HEIGHT / 2.0 - fall),
+                camera.z.floor() as f32,
+            ));
+        }
+    }
+
+    pub fn remove(&mut self, renderer: &mut render::Renderer) {
+        renderer.model.remove_model(self.model);
+    }
+}
+
+/// Appends a vertical quad visible from both sides, spanning `size` from `origin`.
+fn append_quad(
+    verts: &mut Vec<model::Vertex>,
+    tex: &render::Texture,
+    column: f64,
+    origin: Point3<f32>,
+    size: Vector3<f32>,
+) {
+    let corners = [
+        (0.0, 0.0, column, 1.0),
+        (0.0, 1.0, column, 0.0),
+        (1.0, 0.0, column + 0.25, 1.0),
+        (1.0, 1.0, column + 0.25, 0.0),
+    ];
+    let vertex = |&(across, up, texture_x, texture_y): &(f32, f32, f64, f64)| model::Vertex {
+        x: origin.x + size.x * across,
+        y: origin.y + size.y * up,
+        z: origin.z + size.z * across,
+        texture_x,
+        texture_y: texture_y * 0.125,
+        texture: tex.clone(),
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+        id: 0,
+    };
+    for corner in &corners {
+        verts.push(vertex(corner));
+    }
+    // The back side, wound the other way around
+    for &i in &[0, 2, 1, 3] {
+        verts.push(vertex(&corners[i]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rain_started_ramps_level() {
+        let mut weather = Weather::default();
+        assert!(weather.on_game_state(1, 0.0));
+        assert!(weather.raining);
+        assert_eq!(weather.rain_target, 1.0);
+        assert_eq!(weather.rain_level, 0.0);
+
+        // A tick later it only just started
+        weather.tick(3.0);
+        assert!((weather.rain_level - RAIN_STEP).abs() < 1e-6);
+        assert!(weather.sky_light_factor() < 1.0);
+
+        assert!(weather.on_game_state(2, 0.0));
+        assert!(!weather.raining);
+        assert_eq!(weather.rain_target, 0.0);
+        // Game mode changes aren't about the weather
+        assert!(!weather.on_game_state(3, 1.0));
+    }
+}
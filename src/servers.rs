@@ -0,0 +1,280 @@
+//! The saved server list, stored as `servers.json` in the data directory.
+
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use leafish_protocol::protocol;
+use log::{error, warn};
+use serde_json::{json, Value};
+
+use crate::paths;
+
+/// How many addresses the direct connect history keeps.
+pub const MAX_RECENT_SERVERS: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerEntry {
+    pub name: String,
+    pub address: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SavedServers {
+    pub entries: Vec<ServerEntry>,
+}
+
+impl SavedServers {
+    pub fn path() -> PathBuf {
+        paths::get_data_dir().join("servers.json")
+    }
+
+    /// Loads the saved servers, starting with an empty list if the file is missing or broken.
+    pub fn load() -> SavedServers {
+        Self::load_or_back_up(&Self::path())
+    }
+
+    /// Like `load_from`, but a broken file is moved aside to `<path>.bak` rather than
+    /// left to be replaced by the empty list on the next save.
+    pub fn load_or_back_up(path: &Path) -> SavedServers {
+        match Self::load_from(path) {
+            Ok(servers) => servers,
+            Err(err) => {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(".bak");
+                let backup = PathBuf::from(backup);
+                match fs::rename(path, &backup) {
+                    Ok(()) => warn!(
+                        "Failed to load the server list, moved it to {}: {}",
+                        backup.display(),
+                        err
+                    ),
+                    Err(rename_err) => error!(
+                        "Failed to load the server list: {}, and to move it aside: {}",
+                        err, rename_err
+                    ),
+                }
+                Default::default()
+            }
+        }
+    }
+
+    pub fn load_from(path: &Path) -> io::Result<SavedServers> {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Default::default()),
+            Err(err) => return Err(err),
+        };
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let info: Value = serde_json::from_reader(io::BufReader::new(file))?;
+        let servers = info
+            .get("servers")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("missing servers"))?;
+        let mut entries = vec![];
+        for server in servers {
+            let field = |key: &str| {
+                server
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .map(|v| v.to_owned())
+                    .ok_or_else(|| invalid("invalid server entry"))
+            };
+            entries.push(ServerEntry {
+                name: field("name")?,
+                address: field("address")?,
+            });
+        }
+        Ok(SavedServers { entries })
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = self.save_to(&Self::path()) {
+            error!("Failed to save the server list: {}", err);
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let servers: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| json!({ "name": entry.name, "address": entry.address }))
+            .collect();
+        let mut out = fs::File::create(path)?;
+        serde_json::to_writer_pretty(&mut out, &json!({ "servers": servers }))?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, entry: ServerEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn edit(&mut self, index: usize, entry: ServerEntry) {
+        if let Some(old) = self.entries.get_mut(index) {
+            *old = entry;
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+        }
+    }
+
+    /// Swaps the entry with the one above it, if any.
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.entries.len() {
+            self.entries.swap(index - 1, index);
+        }
+    }
+
+    /// Swaps the entry with the one below it, if any.
+    pub fn move_down(&mut self, index: usize) {
+        if index + 1 < self.entries.len() {
+            self.entries.swap(index, index + 1);
+        }
+    }
+
+    /// Adds `address` as the first saved server when the list is still empty.
+    pub fn seed(address: &str) {
+        let mut servers = Self::load();
+        if servers.entries.is_empty() {
+            servers.add(ServerEntry {
+                name: address.to_owned(),
+                address: address.to_owned(),
+            });
+            servers.save();
+        }
+    }
+}
+
+/// Parses the direct connect history stored in `settings::CL_RECENT_SERVERS`.
+pub fn parse_recent_servers(setting: &str) -> Vec<String> {
+    setting
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| address.to_owned())
+        .collect()
+}
+
+/// Moves `address` to the front of the history, dropping its older occurrence and the
+/// oldest addresses beyond `MAX_RECENT_SERVERS`.
+pub fn remember_recent_server(recent: &mut Vec<String>, address: &str) {
+    let address = address.trim();
+    if address.is_empty() {
+        return;
+    }
+    recent.retain(|recent| !recent.eq_ignore_ascii_case(address));
+    recent.insert(0, address.to_owned());
+    recent.truncate(MAX_RECENT_SERVERS);
+}
+
+/// Checks that `address` is a host with an optional port, like the direct connect screen
+/// takes them.
+pub fn validate_address(address: &str) -> Result<(), String> {
+    let (host, port) = protocol::split_address(address);
+    if host.is_empty() {
+        return Err(format!("Missing host in \"{}\"", address));
+    }
+    if host.parse::<IpAddr>().is_err()
+        && !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid host \"{}\"", host));
+    }
+    if let Some(port) = port {
+        match port.parse::<u16>() {
+            Ok(port) if port != 0 => {}
+            _ => return Err(format!("Invalid port \"{}\"", port)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_list_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("leafish-servers-test-{}.json", std::process::id()));
+        assert_eq!(
+            SavedServers::load_from(&path).unwrap(),
+            SavedServers::default()
+        );
+
+        let mut servers = SavedServers::default();
+        for (name, address) in &[
+            ("Local", "localhost"),
+            ("Hypixel", "mc.hypixel.net"),
+            ("Test", "127.0.0.1:25566"),
+        ] {
+            servers.add(ServerEntry {
+                name: name.to_string(),
+                address: address.to_string(),
+            });
+        }
+        servers.move_up(2);
+        servers.move_down(2);
+        servers.edit(
+            0,
+            ServerEntry {
+                name: "Home".to_string(),
+                address: "localhost".to_string(),
+            },
+        );
+        servers.remove(1);
+        servers.save_to(&path).unwrap();
+
+        let loaded = SavedServers::load_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, servers);
+        let names: Vec<_> = loaded.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Home", "Hypixel"]);
+    }
+
+    #[test]
+    fn broken_server_list_is_kept() {
+        let path = std::env::temp_dir().join(format!(
+            "leafish-servers-broken-test-{}.json",
+            std::process::id()
+        ));
+        let backup = path.with_extension("json.bak");
+        fs::write(&path, "{\"servers\": [{\"name\": ").unwrap();
+
+        let mut servers = SavedServers::load_or_back_up(&path);
+        assert_eq!(servers, SavedServers::default());
+        servers.add(ServerEntry {
+            name: "Local".to_owned(),
+            address: "localhost".to_owned(),
+        });
+        servers.save_to(&path).unwrap();
+
+        let kept = fs::read_to_string(&backup).unwrap();
+        fs::remove_file(&backup).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(kept, "{\"servers\": [{\"name\": ");
+    }
+
+    #[test]
+    fn recent_servers_dedup_and_cap() {
+        let mut recent = parse_recent_servers("localhost, mc.example.com,,");
+        assert_eq!(recent, vec!["localhost", "mc.example.com"]);
+
+        remember_recent_server(&mut recent, "MC.example.com");
+        assert_eq!(recent, vec!["MC.example.com", "localhost"]);
+        remember_recent_server(&mut recent, " ");
+        assert_eq!(recent.len(), 2);
+
+        for i in 0..MAX_RECENT_SERVERS * 2 {
+            remember_recent_server(&mut recent, &format!("10.0.0.{}", i));
+        }
+        assert_eq!(recent.len(), MAX_RECENT_SERVERS);
+        assert_eq!(recent[0], format!("10.0.0.{}", MAX_RECENT_SERVERS * 2 - 1));
+        assert_eq!(parse_recent_servers(&recent.join(",")), recent);
+    }
+}
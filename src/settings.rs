@@ -1,5 +1,10 @@
 use crate::console;
 use crate::console::CVar;
+use crate::render::hud::ChatOptions;
+use crate::resources;
+use crate::ui;
+use instant::Duration;
+use leafish_protocol::protocol;
 use std::marker::PhantomData;
 use winit::event::VirtualKeyCode;
 
@@ -9,36 +14,476 @@ pub const R_MAX_FPS: console::CVar<i64> = console::CVar {
     description: "fps_max caps the maximum FPS for the rendering engine",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| 60,
 };
 
 pub const R_FOV: console::CVar<i64> = console::CVar {
     ty: PhantomData,
     name: "r_fov",
-    description: "Setting for controlling the client field of view",
+    description: "Setting for controlling the client field of view, between 30 and 110 degrees",
     mutable: true,
     serializable: true,
+    range: Some((MIN_FOV, MAX_FOV)),
     default: &|| 90,
 };
 
+pub const R_DYNAMIC_FOV: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "r_dynamic_fov",
+    description: "Widen the field of view while sprinting",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| true,
+};
+
+pub const MIN_FOV: i64 = 30;
+pub const MAX_FOV: i64 = 110;
+/// How much wider the field of view gets while sprinting, with `R_DYNAMIC_FOV` enabled.
+pub const SPRINT_FOV_FACTOR: f32 = 1.15;
+
+pub fn clamp_fov(fov: i64) -> f32 {
+    fov.clamp(MIN_FOV, MAX_FOV) as f32
+}
+
+pub fn fov(vars: &console::Vars) -> f32 {
+    clamp_fov(*vars.get(R_FOV))
+}
+
 pub const R_VSYNC: console::CVar<bool> = console::CVar {
     ty: PhantomData,
     name: "r_vsync",
     description: "Toggle to enable/disable vsync",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
+pub const R_RENDER_DISTANCE: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_render_distance",
+    description: "Distance in chunks around the player to keep loaded, between 2 and 32",
+    mutable: true,
+    serializable: true,
+    range: Some((MIN_RENDER_DISTANCE as i64, MAX_RENDER_DISTANCE as i64)),
+    default: &|| DEFAULT_RENDER_DISTANCE,
+};
+
+pub const R_BRIGHTNESS: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_brightness",
+    description: "Brightens dark areas, from 0 (moody) to 100 (bright)",
+    mutable: true,
+    serializable: true,
+    range: Some((0, 100)),
+    default: &|| 0,
+};
+
+/// The `R_BRIGHTNESS` setting as the gamma passed to the chunk shaders, from 0.0 to 1.0.
+pub fn gamma(vars: &console::Vars) -> f32 {
+    (*vars.get(R_BRIGHTNESS)).clamp(0, 100) as f32 / 100.0
+}
+
+pub const CL_GUI_SCALE: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_gui_scale",
+    description: "Size of menus and the HUD, from 1 to 4, or 0 to pick it from the window size",
+    mutable: true,
+    serializable: true,
+    range: Some((ui::AUTO_GUI_SCALE, ui::MAX_GUI_SCALE)),
+    default: &|| ui::AUTO_GUI_SCALE,
+};
+
+pub const R_CLOUDS: console::CVar<String> = console::CVar {
+    ty: PhantomData,
+    name: "r_clouds",
+    description: "How to draw clouds: \"fancy\" for solid clouds, \"fast\" for a flat layer \
+                  or \"off\"",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::from("fancy"),
+};
+
+pub const R_PARTICLES: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "r_particles",
+    description: "Draw particles like rain, snow and bits of broken blocks",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| true,
+};
+
+pub const R_PARTICLE_DENSITY: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_particle_density",
+    description: "How many particles are spawned, from 0 (none) to 100 (all of them)",
+    mutable: true,
+    serializable: true,
+    range: Some((0, 100)),
+    default: &|| 100,
+};
+
+/// The share of particles spawned, from 0.0 to 1.0, none while `R_PARTICLES` is off.
+pub fn particle_density(vars: &console::Vars) -> f32 {
+    if !*vars.get(R_PARTICLES) {
+        return 0.0;
+    }
+    (*vars.get(R_PARTICLE_DENSITY)).clamp(0, 100) as f32 / 100.0
+}
+
+pub const R_FRUSTUM_CULLING: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "r_frustum_culling",
+    description: "Skip drawing chunks outside of the view, disable to debug missing chunks",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| true,
+};
+
+pub const DEFAULT_RENDER_DISTANCE: i64 = 8;
+pub const MIN_RENDER_DISTANCE: i32 = 2;
+pub const MAX_RENDER_DISTANCE: i32 = 32;
+
+/// Clamps a configured render distance to the range servers and the chunk builder cope with.
+pub fn clamp_render_distance(distance: i64) -> i32 {
+    distance.clamp(MIN_RENDER_DISTANCE as i64, MAX_RENDER_DISTANCE as i64) as i32
+}
+
+pub fn render_distance(vars: &console::Vars) -> i32 {
+    clamp_render_distance(*vars.get(R_RENDER_DISTANCE))
+}
+
+pub const R_WINDOW_WIDTH: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_window_width",
+    description: "Width of the window when it isn't maximized",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| 854,
+};
+
+pub const R_WINDOW_HEIGHT: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_window_height",
+    description: "Height of the window when it isn't maximized",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| 480,
+};
+
+/// Value of `R_WINDOW_X`/`R_WINDOW_Y` when no position has been saved yet.
+pub const WINDOW_POSITION_UNSET: i64 = i64::MIN;
+
+pub const R_WINDOW_X: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_window_x",
+    description: "X position of the window when it isn't maximized",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| WINDOW_POSITION_UNSET,
+};
+
+pub const R_WINDOW_Y: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "r_window_y",
+    description: "Y position of the window when it isn't maximized",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| WINDOW_POSITION_UNSET,
+};
+
+pub const R_WINDOW_MAXIMIZED: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "r_window_maximized",
+    description: "Whether the window is maximized",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| true,
+};
+
 pub const CL_MASTER_VOLUME: console::CVar<i64> = console::CVar {
     ty: PhantomData,
     name: "cl_master_volume",
     description: "Main volume control",
     mutable: true,
     serializable: true,
+    range: Some((0, 100)),
     default: &|| 100,
 };
 
+macro_rules! create_volume {
+    ($name:expr, $description:expr) => {
+        console::CVar {
+            ty: PhantomData,
+            name: $name,
+            description: $description,
+            mutable: true,
+            serializable: true,
+            range: Some((0, 100)),
+            default: &|| 100,
+        }
+    };
+}
+
+pub const CL_VOLUME_MUSIC: console::CVar<i64> =
+    create_volume!("cl_volume_music", "Volume of the music, from 0 to 100");
+pub const CL_VOLUME_RECORDS: console::CVar<i64> = create_volume!(
+    "cl_volume_records",
+    "Volume of jukeboxes and note blocks, from 0 to 100"
+);
+pub const CL_VOLUME_WEATHER: console::CVar<i64> = create_volume!(
+    "cl_volume_weather",
+    "Volume of rain and thunder, from 0 to 100"
+);
+pub const CL_VOLUME_BLOCKS: console::CVar<i64> =
+    create_volume!("cl_volume_blocks", "Volume of block sounds, from 0 to 100");
+pub const CL_VOLUME_HOSTILE: console::CVar<i64> =
+    create_volume!("cl_volume_hostile", "Volume of hostile mobs, from 0 to 100");
+pub const CL_VOLUME_NEUTRAL: console::CVar<i64> = create_volume!(
+    "cl_volume_neutral",
+    "Volume of friendly mobs, from 0 to 100"
+);
+pub const CL_VOLUME_PLAYERS: console::CVar<i64> = create_volume!(
+    "cl_volume_players",
+    "Volume of player sounds, from 0 to 100"
+);
+pub const CL_VOLUME_AMBIENT: console::CVar<i64> = create_volume!(
+    "cl_volume_ambient",
+    "Volume of ambient sounds like caves, from 0 to 100"
+);
+pub const CL_VOLUME_VOICE: console::CVar<i64> = create_volume!(
+    "cl_volume_voice",
+    "Volume of voices and speech, from 0 to 100"
+);
+
+pub const CL_RECONNECT_ATTEMPTS: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_reconnect_attempts",
+    description: "Number of times to retry connecting to the last server when reconnecting",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| 5,
+};
+
+pub const CL_AUTO_RECONNECT_MESSAGES: console::CVar<String> = CVar {
+    ty: PhantomData,
+    name: "cl_auto_reconnect_messages",
+    description:
+        "Parts of kick messages, separated by commas, on which to reconnect to the server \
+                  instead of returning to the server list, e.g. \"Server restarting\"",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::new(),
+};
+
+pub const CL_KEEP_ALIVE_TIMEOUT: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_keep_alive_timeout",
+    description: "Seconds to wait for a keep-alive from the server before disconnecting",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| 30,
+};
+
+pub const CL_DEFAULT_PORT: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_default_port",
+    description: "Port to connect to when a server address has none and no SRV record",
+    mutable: true,
+    serializable: true,
+    range: Some((1, u16::MAX as i64)),
+    default: &|| protocol::VANILLA_PORT as i64,
+};
+
+pub fn default_port(vars: &console::Vars) -> u16 {
+    (*vars.get(CL_DEFAULT_PORT)).clamp(1, u16::MAX as i64) as u16
+}
+
+pub const CL_PROXY: console::CVar<String> = console::CVar {
+    ty: PhantomData,
+    name: "cl_proxy",
+    description: "SOCKS5 proxy to connect to servers through, as \
+                  [username:password@]host[:port], or empty to connect directly",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::new(),
+};
+
+pub const CL_SCROLL_PIXELS_PER_LINE: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_scroll_pixels_per_line",
+    description: "Amount of pixels a touchpad has to scroll to count as one line (mouse notch), \
+                  lower values make touchpad scrolling more sensitive",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| 20,
+};
+
+pub const CL_MOUSE_MOTION: console::CVar<String> = CVar {
+    ty: PhantomData,
+    name: "cl_mouse_motion",
+    description: "How to interpret raw mouse motion: \"relative\", \"absolute\" (for VMs that \
+                  report the pointer position instead of its movement) or \"auto\" to detect it",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::from("auto"),
+};
+
+pub const CL_RECENT_SERVERS: console::CVar<String> = CVar {
+    ty: PhantomData,
+    name: "cl_recent_servers",
+    description: "Addresses recently connected to, most recent first, separated by commas",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::new(),
+};
+
+pub const CL_RESOURCE_PACK: console::CVar<String> = CVar {
+    ty: PhantomData,
+    name: "cl_resource_pack",
+    description: "File name of the resource pack zip in the resourcepacks data directory to use, \
+                  empty for the default assets",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::new(),
+};
+
+pub const CL_LANGUAGE: console::CVar<String> = CVar {
+    ty: PhantomData,
+    name: "cl_language",
+    description: "Language of translated text like chat and death messages, e.g. en_us or de_de",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| resources::FALLBACK_LANGUAGE.to_owned(),
+};
+
+pub const CL_SERVER_RESOURCE_PACKS: console::CVar<String> = CVar {
+    ty: PhantomData,
+    name: "cl_server_resource_packs",
+    description: "What to do with resource packs sent by servers: \"prompt\" to ask first, \
+                  \"enabled\" to always use them or \"disabled\" to decline them",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| String::from("prompt"),
+};
+
+pub const CL_CHAT_HISTORY: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_chat_history",
+    description: "How many chat messages are kept, older ones are dropped",
+    mutable: true,
+    serializable: true,
+    range: Some((1, MAX_CHAT_HISTORY)),
+    default: &|| 100,
+};
+
+pub const CL_CHAT_TIMESTAMPS: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "cl_chat_timestamps",
+    description: "Start chat messages with the local time they were received at",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| false,
+};
+
+pub const CL_CHAT_FADE: console::CVar<i64> = console::CVar {
+    ty: PhantomData,
+    name: "cl_chat_fade",
+    description: "Seconds chat messages stay on screen while the chat is closed, 0 to keep them",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| 10,
+};
+
+/// The most chat messages `CL_CHAT_HISTORY` can keep.
+pub const MAX_CHAT_HISTORY: i64 = 1000;
+
+pub fn chat_options(vars: &console::Vars) -> ChatOptions {
+    let fade = *vars.get(CL_CHAT_FADE);
+    ChatOptions {
+        max_messages: (*vars.get(CL_CHAT_HISTORY)).clamp(1, MAX_CHAT_HISTORY) as usize,
+        timestamps: *vars.get(CL_CHAT_TIMESTAMPS),
+        fade_after: if fade > 0 {
+            Some(Duration::from_secs(fade as u64))
+        } else {
+            None
+        },
+    }
+}
+
+pub const CL_SHOW_COORDINATES: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "cl_show_coordinates",
+    description: "Always show the block you're in and its biome in the corner of the screen",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| false,
+};
+
+pub const CL_VIEW_BOBBING: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "cl_view_bobbing",
+    description: "Bob the camera up and down while walking",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| true,
+};
+
+pub const CL_REDUCE_MOTION: console::CVar<bool> = console::CVar {
+    ty: PhantomData,
+    name: "cl_reduce_motion",
+    description:
+        "Turn off every effect that moves the view on its own, whatever their own settings",
+    mutable: true,
+    serializable: true,
+    range: None,
+    default: &|| false,
+};
+
+/// The effects moving the view on their own that are on, which can make people prone to
+/// motion sickness feel sick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MotionEffects {
+    pub view_bobbing: bool,
+}
+
+impl MotionEffects {
+    /// The effects turned on by their own settings, none of them when motion is reduced.
+    pub fn new(reduce_motion: bool, view_bobbing: bool) -> MotionEffects {
+        MotionEffects {
+            view_bobbing: view_bobbing && !reduce_motion,
+        }
+    }
+}
+
+pub fn motion_effects(vars: &console::Vars) -> MotionEffects {
+    MotionEffects::new(*vars.get(CL_REDUCE_MOTION), *vars.get(CL_VIEW_BOBBING))
+}
+
 // https://github.com/SpigotMC/BungeeCord/blob/bda160562792a913cba3a65ba4996de60d0d6d68/proxy/src/main/java/net/md_5/bungee/PlayerSkinConfiguration.java#L20
 pub const S_CAPE: console::CVar<bool> = console::CVar {
     //
@@ -47,6 +492,7 @@ pub const S_CAPE: console::CVar<bool> = console::CVar {
     description: "Toggle your cape",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -57,6 +503,7 @@ pub const S_JACKET: console::CVar<bool> = console::CVar {
     description: "Toggle your jacket",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -67,6 +514,7 @@ pub const S_LEFT_SLEEVE: console::CVar<bool> = console::CVar {
     description: "Toggle your left sleeve",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -77,6 +525,7 @@ pub const S_RIGHT_SLEEVE: console::CVar<bool> = console::CVar {
     description: "Toggle your right sleeve",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -87,6 +536,7 @@ pub const S_LEFT_PANTS: console::CVar<bool> = console::CVar {
     description: "Toggle your left pants",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -97,6 +547,7 @@ pub const S_RIGHT_PANTS: console::CVar<bool> = console::CVar {
     description: "Toggle your right pants",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -107,6 +558,7 @@ pub const S_HAT: console::CVar<bool> = console::CVar {
     description: "Toggle your hat",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| false,
 };
 
@@ -118,40 +570,108 @@ macro_rules! create_keybind {
             description: $description,
             mutable: true,
             serializable: true,
-            default: &|| VirtualKeyCode::$keycode as i64,
+            range: None,
+            default: &|| key_name(VirtualKeyCode::$keycode).unwrap().to_owned(),
         }
     };
 }
 
-pub const CL_KEYBIND_FORWARD: console::CVar<i64> =
+pub const CL_KEYBIND_FORWARD: console::CVar<String> =
     create_keybind!(W, "cl_keybind_forward", "Keybinding for moving forward");
-pub const CL_KEYBIND_BACKWARD: console::CVar<i64> =
+pub const CL_KEYBIND_BACKWARD: console::CVar<String> =
     create_keybind!(S, "cl_keybind_backward", "Keybinding for moving backward");
-pub const CL_KEYBIND_LEFT: console::CVar<i64> =
+pub const CL_KEYBIND_LEFT: console::CVar<String> =
     create_keybind!(A, "cl_keybind_left", "Keybinding for moving the left");
-pub const CL_KEYBIND_RIGHT: console::CVar<i64> =
+pub const CL_KEYBIND_RIGHT: console::CVar<String> =
     create_keybind!(D, "cl_keybind_right", "Keybinding for moving to the right");
-pub const CL_KEYBIND_OPEN_INV: console::CVar<i64> = create_keybind!(
+pub const CL_KEYBIND_OPEN_INV: console::CVar<String> = create_keybind!(
     E,
     "cl_keybind_open_inv",
     "Keybinding for opening the inventory"
 );
-pub const CL_KEYBIND_SNEAK: console::CVar<i64> =
+pub const CL_KEYBIND_SNEAK: console::CVar<String> =
     create_keybind!(LShift, "cl_keybind_sneak", "Keybinding for sneaking");
-pub const CL_KEYBIND_SPRINT: console::CVar<i64> =
+pub const CL_KEYBIND_SPRINT: console::CVar<String> =
     create_keybind!(LControl, "cl_keybind_sprint", "Keybinding for sprinting");
-pub const CL_KEYBIND_JUMP: console::CVar<i64> =
+pub const CL_KEYBIND_JUMP: console::CVar<String> =
     create_keybind!(Space, "cl_keybind_jump", "Keybinding for jumping");
-pub const CL_KEYBIND_TOGGLE_HUD: console::CVar<i64> = create_keybind!(
+pub const CL_KEYBIND_TOGGLE_HUD: console::CVar<String> = create_keybind!(
     F1,
     "cl_keybind_toggle_hud",
     "Keybinding for toggling the hud"
 );
-pub const CL_KEYBIND_TOGGLE_DEBUG: console::CVar<i64> = create_keybind!(
+pub const CL_KEYBIND_TOGGLE_DEBUG: console::CVar<String> = create_keybind!(
     F3,
     "cl_keybind_toggle_debug",
     "Keybinding for toggling the debug info"
 );
+pub const CL_KEYBIND_SCREENSHOT: console::CVar<String> = create_keybind!(
+    F2,
+    "cl_keybind_screenshot",
+    "Keybinding for taking a screenshot"
+);
+pub const CL_KEYBIND_TOGGLE_PERSPECTIVE: console::CVar<String> = create_keybind!(
+    F5,
+    "cl_keybind_toggle_perspective",
+    "Keybinding for switching between first and third person"
+);
+pub const CL_KEYBIND_CHAT: console::CVar<String> =
+    create_keybind!(T, "cl_keybind_chat", "Keybinding for opening the chat");
+pub const CL_KEYBIND_COMMAND: console::CVar<String> = create_keybind!(
+    Slash,
+    "cl_keybind_command",
+    "Keybinding for opening the chat to type a command"
+);
+pub const CL_KEYBIND_PLAYER_LIST: console::CVar<String> = create_keybind!(
+    Tab,
+    "cl_keybind_player_list",
+    "Keybinding for showing the player list while held"
+);
+pub const CL_KEYBIND_HOTBAR_1: console::CVar<String> = create_keybind!(
+    Key1,
+    "cl_keybind_hotbar_1",
+    "Keybinding for selecting hotbar slot 1"
+);
+pub const CL_KEYBIND_HOTBAR_2: console::CVar<String> = create_keybind!(
+    Key2,
+    "cl_keybind_hotbar_2",
+    "Keybinding for selecting hotbar slot 2"
+);
+pub const CL_KEYBIND_HOTBAR_3: console::CVar<String> = create_keybind!(
+    Key3,
+    "cl_keybind_hotbar_3",
+    "Keybinding for selecting hotbar slot 3"
+);
+pub const CL_KEYBIND_HOTBAR_4: console::CVar<String> = create_keybind!(
+    Key4,
+    "cl_keybind_hotbar_4",
+    "Keybinding for selecting hotbar slot 4"
+);
+pub const CL_KEYBIND_HOTBAR_5: console::CVar<String> = create_keybind!(
+    Key5,
+    "cl_keybind_hotbar_5",
+    "Keybinding for selecting hotbar slot 5"
+);
+pub const CL_KEYBIND_HOTBAR_6: console::CVar<String> = create_keybind!(
+    Key6,
+    "cl_keybind_hotbar_6",
+    "Keybinding for selecting hotbar slot 6"
+);
+pub const CL_KEYBIND_HOTBAR_7: console::CVar<String> = create_keybind!(
+    Key7,
+    "cl_keybind_hotbar_7",
+    "Keybinding for selecting hotbar slot 7"
+);
+pub const CL_KEYBIND_HOTBAR_8: console::CVar<String> = create_keybind!(
+    Key8,
+    "cl_keybind_hotbar_8",
+    "Keybinding for selecting hotbar slot 8"
+);
+pub const CL_KEYBIND_HOTBAR_9: console::CVar<String> = create_keybind!(
+    Key9,
+    "cl_keybind_hotbar_9",
+    "Keybinding for selecting hotbar slot 9"
+);
 
 pub const BACKGROUND_IMAGE: console::CVar<String> = CVar {
     ty: PhantomData,
@@ -159,16 +679,60 @@ pub const BACKGROUND_IMAGE: console::CVar<String> = CVar {
     description: "Select the background image",
     mutable: true,
     serializable: true,
+    range: None,
     default: &|| String::from("leafish:gui/background"),
 };
 
 pub const DOUBLE_JUMP_MS: u32 = 100;
+/// Maximum time between releasing and pressing forward again to start sprinting.
+pub const DOUBLE_TAP_SPRINT_MS: u32 = 350;
+/// Food level below which the player is too hungry to sprint.
+pub const MIN_SPRINT_FOOD: u8 = 7;
 
 pub fn register_vars(vars: &mut console::Vars) {
     vars.register(R_MAX_FPS);
     vars.register(R_FOV);
+    vars.register(R_DYNAMIC_FOV);
     vars.register(R_VSYNC);
+    vars.register(R_RENDER_DISTANCE);
+    vars.register(R_FRUSTUM_CULLING);
+    vars.register(R_BRIGHTNESS);
+    vars.register(R_CLOUDS);
+    vars.register(CL_GUI_SCALE);
+    vars.register(R_PARTICLES);
+    vars.register(R_PARTICLE_DENSITY);
+    vars.register(R_WINDOW_WIDTH);
+    vars.register(R_WINDOW_HEIGHT);
+    vars.register(R_WINDOW_X);
+    vars.register(R_WINDOW_Y);
+    vars.register(R_WINDOW_MAXIMIZED);
     vars.register(CL_MASTER_VOLUME);
+    vars.register(CL_VOLUME_MUSIC);
+    vars.register(CL_VOLUME_RECORDS);
+    vars.register(CL_VOLUME_WEATHER);
+    vars.register(CL_VOLUME_BLOCKS);
+    vars.register(CL_VOLUME_HOSTILE);
+    vars.register(CL_VOLUME_NEUTRAL);
+    vars.register(CL_VOLUME_PLAYERS);
+    vars.register(CL_VOLUME_AMBIENT);
+    vars.register(CL_VOLUME_VOICE);
+    vars.register(CL_RECONNECT_ATTEMPTS);
+    vars.register(CL_AUTO_RECONNECT_MESSAGES);
+    vars.register(CL_KEEP_ALIVE_TIMEOUT);
+    vars.register(CL_DEFAULT_PORT);
+    vars.register(CL_PROXY);
+    vars.register(CL_SCROLL_PIXELS_PER_LINE);
+    vars.register(CL_MOUSE_MOTION);
+    vars.register(CL_RECENT_SERVERS);
+    vars.register(CL_RESOURCE_PACK);
+    vars.register(CL_LANGUAGE);
+    vars.register(CL_SERVER_RESOURCE_PACKS);
+    vars.register(CL_SHOW_COORDINATES);
+    vars.register(CL_VIEW_BOBBING);
+    vars.register(CL_REDUCE_MOTION);
+    vars.register(CL_CHAT_HISTORY);
+    vars.register(CL_CHAT_TIMESTAMPS);
+    vars.register(CL_CHAT_FADE);
     vars.register(CL_KEYBIND_FORWARD);
     vars.register(CL_KEYBIND_BACKWARD);
     vars.register(CL_KEYBIND_LEFT);
@@ -179,6 +743,20 @@ pub fn register_vars(vars: &mut console::Vars) {
     vars.register(CL_KEYBIND_JUMP);
     vars.register(CL_KEYBIND_TOGGLE_HUD);
     vars.register(CL_KEYBIND_TOGGLE_DEBUG);
+    vars.register(CL_KEYBIND_SCREENSHOT);
+    vars.register(CL_KEYBIND_TOGGLE_PERSPECTIVE);
+    vars.register(CL_KEYBIND_CHAT);
+    vars.register(CL_KEYBIND_COMMAND);
+    vars.register(CL_KEYBIND_PLAYER_LIST);
+    vars.register(CL_KEYBIND_HOTBAR_1);
+    vars.register(CL_KEYBIND_HOTBAR_2);
+    vars.register(CL_KEYBIND_HOTBAR_3);
+    vars.register(CL_KEYBIND_HOTBAR_4);
+    vars.register(CL_KEYBIND_HOTBAR_5);
+    vars.register(CL_KEYBIND_HOTBAR_6);
+    vars.register(CL_KEYBIND_HOTBAR_7);
+    vars.register(CL_KEYBIND_HOTBAR_8);
+    vars.register(CL_KEYBIND_HOTBAR_9);
     vars.register(S_CAPE);
     vars.register(S_JACKET);
     vars.register(S_LEFT_SLEEVE);
@@ -201,6 +779,20 @@ pub enum Actionkey {
     Jump,
     ToggleHud,
     ToggleDebug,
+    Screenshot,
+    TogglePerspective,
+    OpenChat,
+    OpenCommand,
+    PlayerList,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    Hotbar7,
+    Hotbar8,
+    Hotbar9,
 }
 
 impl Actionkey {
@@ -216,19 +808,95 @@ impl Actionkey {
             Actionkey::Jump,
             Actionkey::ToggleHud,
             Actionkey::ToggleDebug,
+            Actionkey::Screenshot,
+            Actionkey::TogglePerspective,
+            Actionkey::OpenChat,
+            Actionkey::OpenCommand,
+            Actionkey::PlayerList,
+            Actionkey::Hotbar1,
+            Actionkey::Hotbar2,
+            Actionkey::Hotbar3,
+            Actionkey::Hotbar4,
+            Actionkey::Hotbar5,
+            Actionkey::Hotbar6,
+            Actionkey::Hotbar7,
+            Actionkey::Hotbar8,
+            Actionkey::Hotbar9,
         ]
     }
 
+    /// Human readable name of the action, as shown in the controls menu.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Actionkey::Forward => "Forward",
+            Actionkey::Backward => "Backward",
+            Actionkey::Left => "Left",
+            Actionkey::Right => "Right",
+            Actionkey::OpenInv => "Inventory",
+            Actionkey::Sneak => "Sneak",
+            Actionkey::Sprint => "Sprint",
+            Actionkey::Jump => "Jump",
+            Actionkey::ToggleHud => "Toggle HUD",
+            Actionkey::ToggleDebug => "Debug info",
+            Actionkey::Screenshot => "Screenshot",
+            Actionkey::TogglePerspective => "Perspective",
+            Actionkey::OpenChat => "Chat",
+            Actionkey::OpenCommand => "Command",
+            Actionkey::PlayerList => "Player list",
+            Actionkey::Hotbar1 => "Hotbar slot 1",
+            Actionkey::Hotbar2 => "Hotbar slot 2",
+            Actionkey::Hotbar3 => "Hotbar slot 3",
+            Actionkey::Hotbar4 => "Hotbar slot 4",
+            Actionkey::Hotbar5 => "Hotbar slot 5",
+            Actionkey::Hotbar6 => "Hotbar slot 6",
+            Actionkey::Hotbar7 => "Hotbar slot 7",
+            Actionkey::Hotbar8 => "Hotbar slot 8",
+            Actionkey::Hotbar9 => "Hotbar slot 9",
+        }
+    }
+
+    /// The hotbar slot, from 0 to 8, selected by the action.
+    pub fn hotbar_slot(&self) -> Option<u8> {
+        Some(match *self {
+            Actionkey::Hotbar1 => 0,
+            Actionkey::Hotbar2 => 1,
+            Actionkey::Hotbar3 => 2,
+            Actionkey::Hotbar4 => 3,
+            Actionkey::Hotbar5 => 4,
+            Actionkey::Hotbar6 => 5,
+            Actionkey::Hotbar7 => 6,
+            Actionkey::Hotbar8 => 7,
+            Actionkey::Hotbar9 => 8,
+            _ => return None,
+        })
+    }
+
+    /// Returns the actions sharing their key with at least one other action. Only the
+    /// first of them is triggered by `get_by_keycode`.
+    pub fn conflicts(vars: &console::Vars) -> Vec<Actionkey> {
+        let values = Actionkey::values();
+        values
+            .iter()
+            .filter(|key| {
+                let name = vars.get(key.get_cvar());
+                values
+                    .iter()
+                    .any(|other| other != *key && *vars.get(other.get_cvar()) == *name)
+            })
+            .copied()
+            .collect()
+    }
+
     pub fn get_by_keycode(keycode: VirtualKeyCode, vars: &console::Vars) -> Option<Actionkey> {
         for steven_key in Actionkey::values() {
-            if keycode as i64 == *vars.get(steven_key.get_cvar()) {
+            if key_name(keycode) == Some(vars.get(steven_key.get_cvar()).as_str()) {
                 return Some(steven_key);
             }
         }
         None
     }
 
-    pub fn get_cvar(&self) -> console::CVar<i64> {
+    pub fn get_cvar(&self) -> console::CVar<String> {
         match *self {
             Actionkey::Forward => CL_KEYBIND_FORWARD,
             Actionkey::Backward => CL_KEYBIND_BACKWARD,
@@ -240,6 +908,83 @@ impl Actionkey {
             Actionkey::Jump => CL_KEYBIND_JUMP,
             Actionkey::ToggleHud => CL_KEYBIND_TOGGLE_HUD,
             Actionkey::ToggleDebug => CL_KEYBIND_TOGGLE_DEBUG,
+            Actionkey::Screenshot => CL_KEYBIND_SCREENSHOT,
+            Actionkey::TogglePerspective => CL_KEYBIND_TOGGLE_PERSPECTIVE,
+            Actionkey::OpenChat => CL_KEYBIND_CHAT,
+            Actionkey::OpenCommand => CL_KEYBIND_COMMAND,
+            Actionkey::PlayerList => CL_KEYBIND_PLAYER_LIST,
+            Actionkey::Hotbar1 => CL_KEYBIND_HOTBAR_1,
+            Actionkey::Hotbar2 => CL_KEYBIND_HOTBAR_2,
+            Actionkey::Hotbar3 => CL_KEYBIND_HOTBAR_3,
+            Actionkey::Hotbar4 => CL_KEYBIND_HOTBAR_4,
+            Actionkey::Hotbar5 => CL_KEYBIND_HOTBAR_5,
+            Actionkey::Hotbar6 => CL_KEYBIND_HOTBAR_6,
+            Actionkey::Hotbar7 => CL_KEYBIND_HOTBAR_7,
+            Actionkey::Hotbar8 => CL_KEYBIND_HOTBAR_8,
+            Actionkey::Hotbar9 => CL_KEYBIND_HOTBAR_9,
+        }
+    }
+}
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        /// The keys actions can be bound to, with the name their binding is saved as.
+        const KEY_NAMES: &[(VirtualKeyCode, &str)] =
+            &[$((VirtualKeyCode::$key, stringify!($key))),*];
+    };
+}
+
+key_names! {
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J,
+    K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8,
+    F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, Snapshot,
+    Scroll, Pause, Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back,
+    Return, Space, Numlock, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6,
+    Numpad7, Numpad8, Numpad9, NumpadEnter, Apostrophe, Backslash, Capital, Comma, Equals,
+    Grave, LAlt, LBracket, LControl, LShift, LWin, Minus, Period, RAlt, RBracket, RControl,
+    RShift, RWin, Semicolon, Slash, Tab,
+}
+
+/// The name a keybinding setting saves `key` as, `None` if it can't be bound.
+pub fn key_name(key: VirtualKeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+/// Turns the value of a keybinding setting back into the key it names.
+pub fn keycode_from_name(name: &str) -> Option<VirtualKeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| *key_name == name)
+        .map(|(code, _)| *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduce_motion_turns_effects_off() {
+        assert!(MotionEffects::new(false, true).view_bobbing);
+        assert!(!MotionEffects::new(false, false).view_bobbing);
+        // Overrides each effect's own setting, which is kept for when it's turned back off
+        assert!(!MotionEffects::new(true, true).view_bobbing);
+        assert!(!MotionEffects::new(true, false).view_bobbing);
+    }
+
+    #[test]
+    fn keybindings_are_saved_by_name() {
+        for action in Actionkey::values() {
+            let default = (action.get_cvar().default)();
+            let key = keycode_from_name(&default).unwrap();
+            assert_eq!(key_name(key), Some(default.as_str()));
         }
+        assert_eq!(key_name(VirtualKeyCode::LShift), Some("LShift"));
+        assert_eq!(keycode_from_name("Key1"), Some(VirtualKeyCode::Key1));
+        // Old configs saved the key's number, which may mean another key by now
+        assert_eq!(keycode_from_name("17"), None);
+        assert_eq!(keycode_from_name(""), None);
     }
 }
@@ -0,0 +1,462 @@
+//! Plays the sounds the server asks for, along with the ones the client makes itself,
+//! from the sounds of the loaded resource packs.
+
+use crate::console;
+use crate::resources;
+use crate::settings;
+use crate::world::block::{Block, Tool};
+use log::{debug, warn};
+use parking_lot::RwLock;
+use rand::Rng;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Read};
+use std::sync::Arc;
+
+/// Maximum amount of sounds kept until `Server::drain_sounds` is called.
+pub const MAX_QUEUED_SOUNDS: usize = 64;
+/// How far away a sound at full volume can be heard, in blocks. Louder sounds carry
+/// further.
+const ATTENUATION_DISTANCE: f64 = 16.0;
+/// The protocol version (1.12.2) of the resources' sound registry, which servers of other
+/// versions number differently. See `SoundRegistry::event_by_id`.
+pub const SOUND_REGISTRY_PROTOCOL: i32 = 340;
+
+/// The volume slider a sound is controlled by, in the order the protocol numbers them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCategory {
+    Master,
+    Music,
+    Records,
+    Weather,
+    Blocks,
+    Hostile,
+    Neutral,
+    Players,
+    Ambient,
+    Voice,
+}
+
+impl SoundCategory {
+    pub fn values() -> Vec<SoundCategory> {
+        vec![
+            SoundCategory::Master,
+            SoundCategory::Music,
+            SoundCategory::Records,
+            SoundCategory::Weather,
+            SoundCategory::Blocks,
+            SoundCategory::Hostile,
+            SoundCategory::Neutral,
+            SoundCategory::Players,
+            SoundCategory::Ambient,
+            SoundCategory::Voice,
+        ]
+    }
+
+    pub fn from_id(id: i32) -> SoundCategory {
+        SoundCategory::values()
+            .get(id as usize)
+            .copied()
+            .unwrap_or(SoundCategory::Master)
+    }
+
+    /// Human readable name of the category, as shown in the audio settings.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            SoundCategory::Master => "Master Volume",
+            SoundCategory::Music => "Music",
+            SoundCategory::Records => "Jukebox/Note Blocks",
+            SoundCategory::Weather => "Weather",
+            SoundCategory::Blocks => "Blocks",
+            SoundCategory::Hostile => "Hostile Creatures",
+            SoundCategory::Neutral => "Friendly Creatures",
+            SoundCategory::Players => "Players",
+            SoundCategory::Ambient => "Ambient/Environment",
+            SoundCategory::Voice => "Voice/Speech",
+        }
+    }
+
+    pub fn get_cvar(&self) -> console::CVar<i64> {
+        match *self {
+            SoundCategory::Master => settings::CL_MASTER_VOLUME,
+            SoundCategory::Music => settings::CL_VOLUME_MUSIC,
+            SoundCategory::Records => settings::CL_VOLUME_RECORDS,
+            SoundCategory::Weather => settings::CL_VOLUME_WEATHER,
+            SoundCategory::Blocks => settings::CL_VOLUME_BLOCKS,
+            SoundCategory::Hostile => settings::CL_VOLUME_HOSTILE,
+            SoundCategory::Neutral => settings::CL_VOLUME_NEUTRAL,
+            SoundCategory::Players => settings::CL_VOLUME_PLAYERS,
+            SoundCategory::Ambient => settings::CL_VOLUME_AMBIENT,
+            SoundCategory::Voice => settings::CL_VOLUME_VOICE,
+        }
+    }
+
+    /// The volume of the category's sounds, including the master volume, from 0 to 1.
+    pub fn volume(&self, vars: &console::Vars) -> f32 {
+        let volume = |cvar| (*vars.get(cvar)).clamp(0, 100) as f32 / 100.0;
+        volume(settings::CL_MASTER_VOLUME) * volume(self.get_cvar())
+    }
+}
+
+/// How a sound is named.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoundName {
+    /// The sound event, or the name the sound had before 1.9.
+    Event(String),
+    /// The index of the sound event in the sound registry.
+    Id(i32),
+}
+
+/// A sound to play, either in the world or right at the listener.
+#[derive(Debug, Clone)]
+pub struct SoundEvent {
+    pub name: SoundName,
+    pub category: SoundCategory,
+    /// Where the sound comes from, `None` for sounds that aren't attenuated.
+    pub position: Option<(f64, f64, f64)>,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+/// The sound files of each sound event, as listed by the sounds.json of the resource packs.
+#[derive(Default)]
+pub struct SoundRegistry {
+    events: HashMap<String, Vec<String>>,
+    /// The vanilla sound events, in the order of the sound registry.
+    ids: Vec<String>,
+}
+
+impl SoundRegistry {
+    pub fn load(resources: &resources::Manager) -> SoundRegistry {
+        let mut registry = SoundRegistry::default();
+        // Packs further up the list add to or replace the sounds of the ones below
+        for mut file in resources
+            .open_all("minecraft", "sounds.json")
+            .into_iter()
+            .rev()
+        {
+            let mut data = String::new();
+            if let Err(err) = file.read_to_string(&mut data) {
+                warn!("Failed to read sounds.json: {}", err);
+                continue;
+            }
+            match serde_json::from_str(&data) {
+                // The lowest pack is the vanilla one, which defines the registry
+                Ok(json) if registry.ids.is_empty() => {
+                    registry.set_ids(&json);
+                    registry.add(&json);
+                }
+                Ok(json) => registry.add(&json),
+                Err(err) => warn!("Failed to parse sounds.json: {}", err),
+            }
+        }
+        registry
+    }
+
+    /// Adds the events of a sounds.json file.
+    pub fn add(&mut self, json: &serde_json::Value) {
+        let events = match json.as_object() {
+            Some(events) => events,
+            None => return,
+        };
+        for (event, info) in events {
+            let files = info
+                .get("sounds")
+                .and_then(|sounds| sounds.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|sound| match sound {
+                    serde_json::Value::String(name) => Some(name.clone()),
+                    // Sounds referring to other events aren't supported
+                    serde_json::Value::Object(sound)
+                        if sound.get("type").map_or(true, |ty| ty == "file") =>
+                    {
+                        sound
+                            .get("name")
+                            .and_then(|name| name.as_str())
+                            .map(str::to_owned)
+                    }
+                    _ => None,
+                });
+            let replace = info
+                .get("replace")
+                .and_then(|replace| replace.as_bool())
+                .unwrap_or(false);
+            let entry = self.events.entry(event.clone()).or_insert_with(Vec::new);
+            if replace {
+                entry.clear();
+            }
+            entry.extend(files);
+        }
+    }
+
+    /// Numbers the events of the vanilla sounds.json the way the sound registry does, which
+    /// registers them sorted by name.
+    pub fn set_ids(&mut self, json: &serde_json::Value) {
+        self.ids = json
+            .as_object()
+            .map(|events| events.keys().cloned().collect())
+            .unwrap_or_default();
+        self.ids.sort();
+    }
+
+    /// The event with the index `id` in the sound registry of `SOUND_REGISTRY_PROTOCOL`.
+    pub fn event_by_id(&self, id: i32) -> Option<&str> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| self.ids.get(id))
+            .map(String::as_str)
+    }
+
+    /// The files the event picks from, without the "sounds/" directory and extension.
+    pub fn files(&self, event: &str) -> &[String] {
+        let event = event.trim_start_matches("minecraft:");
+        self.events.get(event).map_or(&[], |files| &files[..])
+    }
+}
+
+/// Where a sound file is found in the resource packs, e.g. "dig/stone1" is
+/// "sounds/dig/stone1.ogg" in the "minecraft" namespace.
+pub fn sound_path(name: &str) -> (&str, String) {
+    let (namespace, path) = match name.find(':') {
+        Some(pos) => (&name[..pos], &name[pos + 1..]),
+        None => ("minecraft", name),
+    };
+    (namespace, format!("sounds/{}.ogg", path))
+}
+
+/// Maps the sound names used before 1.9 to the events which replaced them, so they
+/// can be found in the same sounds.json. Only the most common ones are known.
+pub fn modern_name(name: &str) -> String {
+    let group = |group: &str| match group {
+        "cloth" => "cloth",
+        "grass" => "grass",
+        "gravel" => "gravel",
+        "sand" => "sand",
+        "snow" => "snow",
+        "stone" => "stone",
+        "wood" => "wood",
+        "glass" => "glass",
+        _ => "stone",
+    };
+    if let Some(group_name) = name.strip_prefix("dig.") {
+        return format!("block.{}.break", group(group_name));
+    }
+    if let Some(group_name) = name.strip_prefix("step.") {
+        return format!("block.{}.step", group(group_name));
+    }
+    match name {
+        "game.player.hurt" | "damage.hit" => "entity.player.hurt",
+        "game.player.die" => "entity.player.death",
+        "random.break" => "entity.item.break",
+        "random.pop" => "entity.item.pickup",
+        "random.orb" => "entity.experience_orb.pickup",
+        "random.click" => "block.lever.click",
+        "random.door_open" => "block.wooden_door.open",
+        "random.door_close" => "block.wooden_door.close",
+        name => name,
+    }
+    .to_owned()
+}
+
+/// The sound group of a block, the part of its sound events between "block." and the
+/// action, e.g. "stone" for "block.stone.break".
+pub fn block_sound_group(block: Block) -> &'static str {
+    match block {
+        Block::Grass { .. } | Block::TallGrass { .. } | Block::Leaves { .. } => "grass",
+        Block::Dirt { .. } | Block::Gravel { .. } => "gravel",
+        Block::Sand { .. } => "sand",
+        Block::Wool { .. } => "cloth",
+        Block::Snow { .. } | Block::SnowLayer { .. } => "snow",
+        Block::Glass { .. } | Block::StainedGlass { .. } | Block::GlassPane { .. } => "glass",
+        Block::Ice { .. } => "glass",
+        _ => match block.get_tool() {
+            Some(Tool::Axe) => "wood",
+            Some(Tool::Shovel) => "gravel",
+            _ => "stone",
+        },
+    }
+}
+
+/// How loud a sound played with `volume` is `distance` blocks away, falling off linearly.
+pub fn attenuation(distance: f64, volume: f32) -> f32 {
+    let range = ATTENUATION_DISTANCE * f64::from(volume.max(1.0));
+    (1.0 - distance / range).max(0.0) as f32
+}
+
+pub struct SoundManager {
+    // Sounds stop playing once the stream is dropped
+    _stream: Option<rodio::OutputStream>,
+    handle: Option<rodio::OutputStreamHandle>,
+    resources: Arc<RwLock<resources::Manager>>,
+    resource_version: Option<usize>,
+    registry: SoundRegistry,
+    files: HashMap<String, Arc<[u8]>>,
+}
+
+impl SoundManager {
+    pub fn new(resources: Arc<RwLock<resources::Manager>>) -> SoundManager {
+        let (stream, handle) = match rodio::OutputStream::try_default() {
+            Ok((stream, handle)) => (Some(stream), Some(handle)),
+            Err(err) => {
+                warn!("No audio output available, sounds are disabled: {}", err);
+                (None, None)
+            }
+        };
+        SoundManager {
+            _stream: stream,
+            handle,
+            resources,
+            resource_version: None,
+            registry: SoundRegistry::default(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Reloads the sounds when the resource packs changed.
+    pub fn tick(&mut self, resource_version: usize) {
+        if self.resource_version == Some(resource_version) || self.handle.is_none() {
+            return;
+        }
+        if let Some(resources) = self.resources.try_read() {
+            self.registry = SoundRegistry::load(&resources);
+            self.files.clear();
+            self.resource_version = Some(resource_version);
+        }
+    }
+
+    /// Plays `sound` as heard from `listener`.
+    pub fn play(&mut self, vars: &console::Vars, sound: &SoundEvent, listener: (f64, f64, f64)) {
+        use rodio::Source;
+
+        let handle = match &self.handle {
+            Some(handle) => handle,
+            None => return,
+        };
+        let mut volume = sound.category.volume(vars) * sound.volume.min(1.0);
+        if let Some((x, y, z)) = sound.position {
+            let distance =
+                ((x - listener.0).powi(2) + (y - listener.1).powi(2) + (z - listener.2).powi(2))
+                    .sqrt();
+            volume *= attenuation(distance, sound.volume);
+        }
+        if volume <= 0.0 {
+            return;
+        }
+        let name = match &sound.name {
+            SoundName::Event(name) => modern_name(name),
+            SoundName::Id(id) => match self.registry.event_by_id(*id) {
+                Some(name) => name.to_owned(),
+                None => {
+                    debug!("Unknown sound id {}", id);
+                    return;
+                }
+            },
+        };
+        let files = self.registry.files(&name);
+        if files.is_empty() {
+            debug!("No sound files for {}", name);
+            return;
+        }
+        let file = files[rand::thread_rng().gen_range(0..files.len())].clone();
+        let data = match self.files.get(&file) {
+            Some(data) => data.clone(),
+            None => {
+                let (namespace, path) = sound_path(&file);
+                let mut data = vec![];
+                match self.resources.read().open(namespace, &path) {
+                    Some(mut reader) => {
+                        if let Err(err) = reader.read_to_end(&mut data) {
+                            warn!("Failed to read sound {}: {}", path, err);
+                            return;
+                        }
+                    }
+                    None => {
+                        debug!("Missing sound file {}", path);
+                        return;
+                    }
+                }
+                let data: Arc<[u8]> = data.into();
+                self.files.insert(file, data.clone());
+                data
+            }
+        };
+        // Each playback reads the cached file through its own cursor
+        let source = match rodio::Decoder::new(io::Cursor::new(data)) {
+            Ok(source) => source,
+            Err(err) => {
+                warn!("Failed to decode sound {}: {}", name, err);
+                return;
+            }
+        };
+        let source = source.amplify(volume).speed(sound.pitch.max(0.01));
+        if let Err(err) = handle.play_raw(source.convert_samples()) {
+            warn!("Failed to play sound {}: {}", name, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sound_event_to_resource_path() {
+        let mut registry = SoundRegistry::default();
+        registry.add(
+            &serde_json::from_str(
+                r#"{
+                    "block.stone.break": {"sounds": ["dig/stone1", {"name": "dig/stone2", "volume": 0.8}]},
+                    "entity.player.hurt": {"sounds": ["damage/hit1"], "subtitle": "subtitles.entity.player.hurt"}
+                }"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            registry.files("minecraft:block.stone.break"),
+            ["dig/stone1", "dig/stone2"]
+        );
+        assert_eq!(
+            sound_path(&registry.files("block.stone.break")[1]),
+            ("minecraft", "sounds/dig/stone2.ogg".to_owned())
+        );
+        assert_eq!(
+            sound_path("mymod:custom/boom"),
+            ("mymod", "sounds/custom/boom.ogg".to_owned())
+        );
+
+        // Names from before 1.9 are looked up by the events that replaced them
+        assert_eq!(modern_name("dig.stone"), "block.stone.break");
+        assert_eq!(
+            registry.files(&modern_name("game.player.hurt")),
+            ["damage/hit1"]
+        );
+        assert!(registry.files("block.unknown.break").is_empty());
+
+        // Resource packs can replace the sounds of an event
+        registry.add(
+            &serde_json::from_str(
+                r#"{"block.stone.break": {"replace": true, "sounds": ["custom/crack"]}}"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(registry.files("block.stone.break"), ["custom/crack"]);
+
+        // Servers sending ids number the vanilla events by name
+        registry.set_ids(
+            &serde_json::from_str(
+                r#"{"entity.player.hurt": {}, "ambient.cave": {}, "block.stone.break": {}}"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(registry.event_by_id(0), Some("ambient.cave"));
+        assert_eq!(registry.event_by_id(2), Some("entity.player.hurt"));
+        assert_eq!(registry.event_by_id(3), None);
+        assert_eq!(registry.event_by_id(-1), None);
+
+        assert_eq!(attenuation(0.0, 1.0), 1.0);
+        assert_eq!(attenuation(8.0, 1.0), 0.5);
+        assert_eq!(attenuation(20.0, 1.0), 0.0);
+        assert_eq!(attenuation(20.0, 2.0), 0.375);
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod logo;
+pub mod tooltip;
 
 use crate::format;
 use crate::render;
@@ -26,6 +27,30 @@ use winit::event::VirtualKeyCode;
 const SCALED_WIDTH: f64 = 854.0;
 const SCALED_HEIGHT: f64 = 480.0;
 
+/// The GUI scale setting picking the scale from the window size.
+pub const AUTO_GUI_SCALE: i64 = 0;
+pub const MAX_GUI_SCALE: i64 = 4;
+
+/// The largest scale, in window pixels per UI unit, at which the layout still fits in a
+/// window of the given size, like vanilla's automatic GUI scale.
+pub fn auto_gui_scale(width: f64, height: f64) -> f64 {
+    (width / SCALED_WIDTH)
+        .min(height / SCALED_HEIGHT)
+        .floor()
+        .clamp(1.0, MAX_GUI_SCALE as f64)
+}
+
+/// The scale to draw the UI at for a GUI scale `setting`. Scales too large for the window
+/// are lowered to the largest that fits, like vanilla does.
+pub fn gui_scale(setting: i64, width: f64, height: f64) -> f64 {
+    let auto = auto_gui_scale(width, height);
+    if setting == AUTO_GUI_SCALE {
+        auto
+    } else {
+        (setting as f64).clamp(1.0, auto)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum Mode {
     Scaled,
@@ -275,6 +300,8 @@ pub struct Container {
 
     pub mode: Mode,
     last_mode: Mode,
+    /// Window pixels per UI unit in the scaled mode, from `gui_scale`.
+    pub scale: f64,
     version: usize,
 
     last_sw: f64,
@@ -297,6 +324,7 @@ impl Container {
 
             mode: Mode::Scaled,
             last_mode: Mode::Scaled,
+            scale: 1.0,
             version: 0xFFFF,
 
             last_sw: 0.0,
@@ -306,6 +334,16 @@ impl Container {
         }
     }
 
+    fn scale_factors(&self, width: f64, height: f64) -> (f64, f64) {
+        match self.mode {
+            Mode::Scaled => (
+                SCALED_WIDTH / width * self.scale,
+                SCALED_HEIGHT / height * self.scale,
+            ),
+            Mode::Unscaled(scale) => (scale, scale),
+        }
+    }
+
     pub fn tick(
         &mut self,
         renderer: Arc<RwLock<render::Renderer>>,
@@ -313,10 +351,7 @@ impl Container {
         width: f64,
         height: f64,
     ) {
-        let (sw, sh) = match self.mode {
-            Mode::Scaled => (SCALED_WIDTH / width, SCALED_HEIGHT / height),
-            Mode::Unscaled(scale) => (scale, scale),
-        };
+        let (sw, sh) = self.scale_factors(width, height);
         let renderer = &mut renderer.write();
 
         if self.last_sw != sw
@@ -366,10 +401,7 @@ impl Container {
     }
 
     pub fn hover_at(&mut self, game: &mut crate::Game, x: f64, y: f64, width: f64, height: f64) {
-        let (sw, sh) = match self.mode {
-            Mode::Scaled => (SCALED_WIDTH / width, SCALED_HEIGHT / height),
-            Mode::Unscaled(scale) => (scale, scale),
-        };
+        let (sw, sh) = self.scale_factors(width, height);
         let mx = (x / width) * SCALED_WIDTH;
         let my = (y / height) * SCALED_HEIGHT;
 
@@ -380,10 +412,7 @@ impl Container {
     }
 
     pub fn click_at(&mut self, game: &mut crate::Game, x: f64, y: f64, width: f64, height: f64) {
-        let (sw, sh) = match self.mode {
-            Mode::Scaled => (SCALED_WIDTH / width, SCALED_HEIGHT / height),
-            Mode::Unscaled(scale) => (scale, scale),
-        };
+        let (sw, sh) = self.scale_factors(width, height);
         let mx = (x / width) * SCALED_WIDTH;
         let my = (y / height) * SCALED_HEIGHT;
 
@@ -404,6 +433,18 @@ impl Container {
         }
     }
 
+    /// Converts a window position into an offset from the center of the screen in UI
+    /// units, matching the positions of elements attached to the middle and center.
+    pub fn center_offset(&self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+        let (sw, sh) = self.scale_factors(width, height);
+        let mx = (x / width) * SCALED_WIDTH;
+        let my = (y / height) * SCALED_HEIGHT;
+        (
+            (mx - SCALED_WIDTH / 2.0) / sw,
+            (my - SCALED_HEIGHT / 2.0) / sh,
+        )
+    }
+
     fn add_focusable(&mut self, el: WeakElement) {
         self.focusable_elements.push(el);
     }
@@ -1240,6 +1281,9 @@ impl<'a> FormatState<'a> {
                     }
                 }
             }
+            format::Component::Translate(ref translation) => {
+                self.build(&format::Component::Text(translation.to_text()), color)
+            }
         }
     }
 
@@ -1637,6 +1681,10 @@ impl UIElement for TextBox {
             self.input.pop();
             return;
         }
+        // Tab and return are typed as well, but only trigger their actions
+        if c.is_control() {
+            return;
+        }
 
         self.input.push(c);
     }
@@ -1659,3 +1707,32 @@ impl TextBox {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gui_scale_fits_window() {
+        // The largest scale that fits, up to the maximum
+        assert_eq!(gui_scale(AUTO_GUI_SCALE, 1280.0, 720.0), 1.0);
+        assert_eq!(gui_scale(AUTO_GUI_SCALE, 1920.0, 1080.0), 2.0);
+        assert_eq!(gui_scale(AUTO_GUI_SCALE, 2560.0, 1440.0), 3.0);
+        assert_eq!(gui_scale(AUTO_GUI_SCALE, 7680.0, 4320.0), 4.0);
+        // Small windows still get a scale
+        assert_eq!(gui_scale(AUTO_GUI_SCALE, 640.0, 360.0), 1.0);
+
+        assert_eq!(gui_scale(1, 2560.0, 1440.0), 1.0);
+        assert_eq!(gui_scale(2, 2560.0, 1440.0), 2.0);
+        // Too large for the window
+        assert_eq!(gui_scale(4, 2560.0, 1440.0), 3.0);
+        assert_eq!(gui_scale(3, 1920.0, 1200.0), 2.0);
+
+        // A UI unit covers as many window pixels as the scale
+        let mut container = Container::new();
+        container.scale = gui_scale(2, 1920.0, 1080.0);
+        let (sw, sh) = container.scale_factors(1920.0, 1080.0);
+        assert_eq!(sw, SCALED_WIDTH / 1920.0 * 2.0);
+        assert_eq!(sh, SCALED_HEIGHT / 1080.0 * 2.0);
+    }
+}
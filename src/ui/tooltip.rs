@@ -0,0 +1,319 @@
+//! Tooltips describing the item under the mouse cursor.
+
+use crate::format::{self, Color, Component, Modifier, TextComponent};
+use crate::nbt::Tag;
+use crate::render;
+use crate::ui;
+use leafish_protocol::item::Stack;
+
+/// How far the tooltip is drawn from the cursor, before scaling.
+const CURSOR_OFFSET: f64 = 12.0;
+/// The space between the border of the tooltip and its text, before scaling.
+const PADDING: f64 = 3.0;
+
+pub struct Tooltip {
+    background: ui::ImageRef,
+    size: (f64, f64),
+    offset: f64,
+}
+
+impl Tooltip {
+    /// Creates a tooltip showing `lines`, which is placed next to the cursor by `move_to`.
+    pub fn new(
+        lines: Vec<Component>,
+        scale: f64,
+        renderer: &render::Renderer,
+        ui_container: &mut ui::Container,
+    ) -> Tooltip {
+        let padding = PADDING * scale;
+        let mut width: f64 = 0.0;
+        let mut height = 0.0;
+        let sizes: Vec<_> = lines
+            .iter()
+            .map(|line| ui::Formatted::compute_size(renderer, line, -1.0))
+            .collect();
+        for (w, h) in &sizes {
+            width = width.max(w * scale);
+            height += h * scale;
+        }
+        let background = ui::ImageBuilder::new()
+            .texture("leafish:solid")
+            .alignment(ui::VAttach::Top, ui::HAttach::Left)
+            .size(width + padding * 2.0, height + padding * 2.0)
+            .colour((16, 0, 16, 240))
+            .draw_index(2)
+            .create(ui_container);
+        let mut y = padding;
+        for (line, (_, h)) in lines.into_iter().zip(sizes) {
+            ui::FormattedBuilder::new()
+                .text(line)
+                .scale_x(scale)
+                .scale_y(scale)
+                .position(padding, y)
+                .alignment(ui::VAttach::Top, ui::HAttach::Left)
+                .attach(&mut *background.borrow_mut());
+            y += h * scale;
+        }
+        Tooltip {
+            background,
+            size: (width + padding * 2.0, height + padding * 2.0),
+            offset: CURSOR_OFFSET * scale,
+        }
+    }
+
+    /// Places the tooltip next to the cursor at `(x, y)`, relative to the center of
+    /// the screen, keeping it within a screen of `screen` UI units.
+    pub fn move_to(&self, x: f64, y: f64, screen: (f64, f64)) {
+        let (left, top) = position(
+            (x + screen.0 / 2.0, y + screen.1 / 2.0),
+            self.offset,
+            self.size,
+            screen,
+        );
+        let mut background = self.background.borrow_mut();
+        background.x = left;
+        background.y = top;
+    }
+}
+
+/// The top left corner of a tooltip of `size` shown at `cursor`, flipped to the other
+/// side of the cursor when it would leave the screen on the right and clamped to the
+/// screen otherwise.
+pub fn position(
+    cursor: (f64, f64),
+    offset: f64,
+    size: (f64, f64),
+    screen: (f64, f64),
+) -> (f64, f64) {
+    let mut left = cursor.0 + offset;
+    if left + size.0 > screen.0 {
+        left = cursor.0 - offset - size.0;
+    }
+    let top = (cursor.1 - offset).min(screen.1 - size.1);
+    (left.max(0.0), top.max(0.0))
+}
+
+/// The lines describing `stack`: its custom name or `fallback_name`, its lore and its
+/// enchantments.
+pub fn item_lines(stack: &Stack, fallback_name: &str) -> Vec<Component> {
+    let tag = stack.tag.as_ref().and_then(|tag| tag.1.as_compound());
+    let display = tag
+        .and_then(|tag| tag.get("display"))
+        .and_then(Tag::as_compound);
+    let mut lines = vec![];
+    match display
+        .and_then(|display| display.get("Name"))
+        .and_then(Tag::as_str)
+    {
+        Some(name) => lines.push(coloured(parse_text(name), Color::White, true)),
+        None => lines.push(Component::Text(TextComponent::new(fallback_name))),
+    }
+    if let Some(lore) = display
+        .and_then(|display| display.get("Lore"))
+        .and_then(Tag::as_list)
+    {
+        for line in lore.iter().filter_map(Tag::as_str) {
+            lines.push(coloured(parse_text(line), Color::DarkPurple, true));
+        }
+    }
+    // Enchanted books keep theirs separately, as they don't apply to the book itself
+    for key in &["Enchantments", "StoredEnchantments", "ench"] {
+        let enchantments = match tag.and_then(|tag| tag.get(*key)).and_then(Tag::as_list) {
+            Some(enchantments) => enchantments,
+            None => continue,
+        };
+        for enchantment in enchantments.iter().filter_map(Tag::as_compound) {
+            let id = match enchantment.get("id") {
+                Some(Tag::String(id)) => id.trim_start_matches("minecraft:").to_owned(),
+                Some(Tag::Short(id)) => match legacy_enchantment(*id) {
+                    Some(id) => id.to_owned(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let level = match enchantment.get("lvl") {
+                Some(Tag::Short(level)) => *level as i32,
+                Some(Tag::Int(level)) => *level,
+                _ => 1,
+            };
+            let text = format!("{} {}", title_case(&id), roman_numeral(level));
+            lines.push(coloured(
+                Component::Text(TextComponent::new(&text)),
+                Color::Gray,
+                false,
+            ));
+        }
+    }
+    lines
+}
+
+/// Parses a name or lore line, which are JSON text since 1.13 and legacy formatted
+/// strings before.
+fn parse_text(text: &str) -> Component {
+    if text.starts_with('{') || text.starts_with('"') {
+        Component::from_string(text)
+    } else {
+        let mut component = Component::Text(TextComponent::new(text));
+        format::convert_legacy(&mut component);
+        component
+    }
+}
+
+/// Wraps `component` so that it's drawn in `color` and optionally italic, unless it
+/// sets its own style.
+fn coloured(component: Component, color: Color, italic: bool) -> Component {
+    Component::Text(TextComponent {
+        text: "".to_owned(),
+        modifier: Modifier {
+            extra: Some(vec![component]),
+            italic: Some(italic),
+            color: Some(color),
+            ..Default::default()
+        },
+    })
+}
+
+/// Maps the numeric enchantment ids used before 1.13 to their names.
+fn legacy_enchantment(id: i16) -> Option<&'static str> {
+    Some(match id {
+        0 => "protection",
+        1 => "fire_protection",
+        2 => "feather_falling",
+        3 => "blast_protection",
+        4 => "projectile_protection",
+        5 => "respiration",
+        6 => "aqua_affinity",
+        7 => "thorns",
+        8 => "depth_strider",
+        9 => "frost_walker",
+        10 => "binding_curse",
+        16 => "sharpness",
+        17 => "smite",
+        18 => "bane_of_arthropods",
+        19 => "knockback",
+        20 => "fire_aspect",
+        21 => "looting",
+        22 => "sweeping",
+        32 => "efficiency",
+        33 => "silk_touch",
+        34 => "unbreaking",
+        35 => "fortune",
+        48 => "power",
+        49 => "punch",
+        50 => "flame",
+        51 => "infinity",
+        61 => "luck_of_the_sea",
+        62 => "lure",
+        70 => "mending",
+        71 => "vanishing_curse",
+        _ => return None,
+    })
+}
+
+fn title_case(id: &str) -> String {
+    id.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn roman_numeral(level: i32) -> String {
+    match level {
+        1 => "I".to_owned(),
+        2 => "II".to_owned(),
+        3 => "III".to_owned(),
+        4 => "IV".to_owned(),
+        5 => "V".to_owned(),
+        6 => "VI".to_owned(),
+        7 => "VII".to_owned(),
+        8 => "VIII".to_owned(),
+        9 => "IX".to_owned(),
+        10 => "X".to_owned(),
+        _ => level.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::NamedTag;
+
+    #[test]
+    fn item_nbt_to_lines() {
+        let mut display = Tag::new_compound();
+        display.put(
+            "Name",
+            Tag::String(r#"{"text":"Excalibur","color":"gold"}"#.to_owned()),
+        );
+        display.put(
+            "Lore",
+            Tag::List(vec![
+                Tag::String("§7Pulled from the stone".to_owned()),
+                Tag::String(r#"{"text":"Still sharp"}"#.to_owned()),
+            ]),
+        );
+        let mut sharpness = Tag::new_compound();
+        sharpness.put("id", Tag::String("minecraft:sharpness".to_owned()));
+        sharpness.put("lvl", Tag::Short(5));
+        let mut fire_aspect = Tag::new_compound();
+        fire_aspect.put("id", Tag::Short(20));
+        fire_aspect.put("lvl", Tag::Short(2));
+        let mut tag = Tag::new_compound();
+        tag.put("display", display);
+        tag.put("Enchantments", Tag::List(vec![sharpness]));
+        tag.put("ench", Tag::List(vec![fire_aspect]));
+        let stack = Stack {
+            id: 276,
+            count: 1,
+            damage: Some(0),
+            tag: Some(NamedTag("".to_owned(), tag)),
+        };
+        let lines: Vec<_> = item_lines(&stack, "Diamond Sword")
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Excalibur",
+                "Pulled from the stone",
+                "Still sharp",
+                "Sharpness V",
+                "Fire Aspect II"
+            ]
+        );
+
+        let plain = item_lines(&Default::default(), "Diamond Sword");
+        assert_eq!(plain.len(), 1);
+        assert_eq!(plain[0].to_string(), "Diamond Sword");
+    }
+
+    #[test]
+    fn tooltip_stays_on_screen() {
+        let screen = (854.0, 480.0);
+        assert_eq!(
+            position((100.0, 100.0), 12.0, (50.0, 20.0), screen),
+            (112.0, 88.0)
+        );
+        // Flipped to the left of the cursor near the right edge
+        assert_eq!(
+            position((840.0, 100.0), 12.0, (50.0, 20.0), screen),
+            (778.0, 88.0)
+        );
+        // Pushed up from the bottom and right from the top left corner
+        assert_eq!(
+            position((5.0, 475.0), 12.0, (50.0, 20.0), screen),
+            (17.0, 460.0)
+        );
+        assert_eq!(
+            position((5.0, 5.0), 12.0, (900.0, 20.0), screen),
+            (0.0, 0.0)
+        );
+    }
+}
@@ -4,14 +4,16 @@ use lazy_static::lazy_static;
 #[derive(Clone, Copy)]
 pub struct Biome {
     pub id: usize,
+    pub name: &'static str,
     pub temperature: i16,
     pub moisture: i16,
 }
 
 impl Biome {
-    const fn new(id: usize, t: i16, m: i16) -> Biome {
+    const fn new(id: usize, name: &'static str, t: i16, m: i16) -> Biome {
         Biome {
             id,
+            name,
             temperature: t,
             moisture: m * t,
         }
@@ -62,68 +64,87 @@ macro_rules! define_biomes {
 }
 
 define_biomes! {
-pub const OCEAN: Biome = Biome::new(0, 50, 50);
-pub const PLAINS: Biome = Biome::new(1, 80, 40);
-pub const DESERT: Biome = Biome::new(2, 200, 0);
-pub const EXTREME_HILLS: Biome = Biome::new(3, 20, 30);
-pub const FOREST: Biome = Biome::new(4, 70, 80);
-pub const TAIGA: Biome = Biome::new(5, 5, 80);
-pub const SWAMPLAND: Biome = Biome::new(6, 80, 90);
-pub const RIVER: Biome = Biome::new(7, 50, 50);
-pub const HELL: Biome = Biome::new(8, 200, 0);
-pub const THE_END: Biome = Biome::new(9, 50, 50);
-pub const FROZEN_OCEAN: Biome = Biome::new(10, 0, 50);
-pub const FROZEN_RIVER: Biome = Biome::new(11, 0, 50);
-pub const ICE_PLAINS: Biome = Biome::new(12, 0, 50);
-pub const ICE_MOUNTAINS: Biome = Biome::new(13, 0, 50);
-pub const MUSHROOM_ISLAND: Biome = Biome::new(14, 90, 100);
-pub const MUSHROOM_ISLAND_SHORE: Biome = Biome::new(15, 90, 100);
-pub const BEACH: Biome = Biome::new(16, 80, 40);
-pub const DESERT_HILLS: Biome = Biome::new(17, 200, 0);
-pub const FOREST_HILLS: Biome = Biome::new(18, 70, 80);
-pub const TAIGA_HILLS: Biome = Biome::new(19, 20, 70);
-pub const EXTREME_HILLS_EDGE: Biome = Biome::new(20, 20, 30);
-pub const JUNGLE: Biome = Biome::new(21, 120, 90);
-pub const JUNGLE_HILLS: Biome = Biome::new(22, 120, 90);
-pub const JUNGLE_EDGE: Biome = Biome::new(23, 95, 80);
-pub const DEEP_OCEAN: Biome = Biome::new(24, 50, 50);
-pub const STONE_BEACH: Biome = Biome::new(25, 20, 30);
-pub const COLD_BEACH: Biome = Biome::new(26, 5, 30);
-pub const BIRCH_FOREST: Biome = Biome::new(27, 60, 60);
-pub const BIRCH_FOREST_HILLS: Biome = Biome::new(28, 60, 60);
-pub const ROOFED_FOREST: Biome = Biome::new(29, 70, 80);
-pub const COLD_TAIGA: Biome = Biome::new(30, -50, 40);
-pub const COLD_TAIGA_HILLS: Biome = Biome::new(31, -50, 40);
-pub const MEGA_TAIGA: Biome = Biome::new(32, 30, 80);
-pub const MEGA_TAIGA_HILLS: Biome = Biome::new(33, 30, 80);
-pub const EXTREME_HILLS_PLUS: Biome = Biome::new(34, 20, 30);
-pub const SAVANNA: Biome = Biome::new(35, 120, 0);
-pub const SAVANNA_PLATEAU: Biome = Biome::new(36, 100, 0);
-pub const MESA: Biome = Biome::new(37, 200, 0);
-pub const MESA_PLATEAU_FOREST: Biome = Biome::new(38, 200, 0);
-pub const MESA_PLATEAU: Biome = Biome::new(39, 200, 0);
+pub const OCEAN: Biome = Biome::new(0, "Ocean", 50, 50);
+pub const PLAINS: Biome = Biome::new(1, "Plains", 80, 40);
+pub const DESERT: Biome = Biome::new(2, "Desert", 200, 0);
+pub const EXTREME_HILLS: Biome = Biome::new(3, "Extreme Hills", 20, 30);
+pub const FOREST: Biome = Biome::new(4, "Forest", 70, 80);
+pub const TAIGA: Biome = Biome::new(5, "Taiga", 5, 80);
+pub const SWAMPLAND: Biome = Biome::new(6, "Swampland", 80, 90);
+pub const RIVER: Biome = Biome::new(7, "River", 50, 50);
+pub const HELL: Biome = Biome::new(8, "Hell", 200, 0);
+pub const THE_END: Biome = Biome::new(9, "The End", 50, 50);
+pub const FROZEN_OCEAN: Biome = Biome::new(10, "Frozen Ocean", 0, 50);
+pub const FROZEN_RIVER: Biome = Biome::new(11, "Frozen River", 0, 50);
+pub const ICE_PLAINS: Biome = Biome::new(12, "Ice Plains", 0, 50);
+pub const ICE_MOUNTAINS: Biome = Biome::new(13, "Ice Mountains", 0, 50);
+pub const MUSHROOM_ISLAND: Biome = Biome::new(14, "Mushroom Island", 90, 100);
+pub const MUSHROOM_ISLAND_SHORE: Biome = Biome::new(15, "Mushroom Island Shore", 90, 100);
+pub const BEACH: Biome = Biome::new(16, "Beach", 80, 40);
+pub const DESERT_HILLS: Biome = Biome::new(17, "Desert Hills", 200, 0);
+pub const FOREST_HILLS: Biome = Biome::new(18, "Forest Hills", 70, 80);
+pub const TAIGA_HILLS: Biome = Biome::new(19, "Taiga Hills", 20, 70);
+pub const EXTREME_HILLS_EDGE: Biome = Biome::new(20, "Extreme Hills Edge", 20, 30);
+pub const JUNGLE: Biome = Biome::new(21, "Jungle", 120, 90);
+pub const JUNGLE_HILLS: Biome = Biome::new(22, "Jungle Hills", 120, 90);
+pub const JUNGLE_EDGE: Biome = Biome::new(23, "Jungle Edge", 95, 80);
+pub const DEEP_OCEAN: Biome = Biome::new(24, "Deep Ocean", 50, 50);
+pub const STONE_BEACH: Biome = Biome::new(25, "Stone Beach", 20, 30);
+pub const COLD_BEACH: Biome = Biome::new(26, "Cold Beach", 5, 30);
+pub const BIRCH_FOREST: Biome = Biome::new(27, "Birch Forest", 60, 60);
+pub const BIRCH_FOREST_HILLS: Biome = Biome::new(28, "Birch Forest Hills", 60, 60);
+pub const ROOFED_FOREST: Biome = Biome::new(29, "Roofed Forest", 70, 80);
+pub const COLD_TAIGA: Biome = Biome::new(30, "Cold Taiga", -50, 40);
+pub const COLD_TAIGA_HILLS: Biome = Biome::new(31, "Cold Taiga Hills", -50, 40);
+pub const MEGA_TAIGA: Biome = Biome::new(32, "Mega Taiga", 30, 80);
+pub const MEGA_TAIGA_HILLS: Biome = Biome::new(33, "Mega Taiga Hills", 30, 80);
+pub const EXTREME_HILLS_PLUS: Biome = Biome::new(34, "Extreme Hills+", 20, 30);
+pub const SAVANNA: Biome = Biome::new(35, "Savanna", 120, 0);
+pub const SAVANNA_PLATEAU: Biome = Biome::new(36, "Savanna Plateau", 100, 0);
+pub const MESA: Biome = Biome::new(37, "Mesa", 200, 0);
+pub const MESA_PLATEAU_FOREST: Biome = Biome::new(38, "Mesa Plateau Forest", 200, 0);
+pub const MESA_PLATEAU: Biome = Biome::new(39, "Mesa Plateau", 200, 0);
+pub const SMALL_END_ISLANDS: Biome = Biome::new(40, "Small End Islands", 50, 50);
+pub const END_MIDLANDS: Biome = Biome::new(41, "End Midlands", 50, 50);
+pub const END_HIGHLANDS: Biome = Biome::new(42, "End Highlands", 50, 50);
+pub const END_BARRENS: Biome = Biome::new(43, "End Barrens", 50, 50);
+pub const WARM_OCEAN: Biome = Biome::new(44, "Warm Ocean", 50, 50);
+pub const LUKEWARM_OCEAN: Biome = Biome::new(45, "Lukewarm Ocean", 50, 50);
+pub const COLD_OCEAN: Biome = Biome::new(46, "Cold Ocean", 50, 50);
+pub const DEEP_WARM_OCEAN: Biome = Biome::new(47, "Deep Warm Ocean", 50, 50);
+pub const DEEP_LUKEWARM_OCEAN: Biome = Biome::new(48, "Deep Lukewarm Ocean", 50, 50);
+pub const DEEP_COLD_OCEAN: Biome = Biome::new(49, "Deep Cold Ocean", 50, 50);
+pub const DEEP_FROZEN_OCEAN: Biome = Biome::new(50, "Deep Frozen Ocean", 0, 50);
 
-pub const SUNFLOWER_PLAINS: Biome = Biome::new(129, 80, 40);
-pub const DESERT_MOUNTAIN: Biome = Biome::new(130, 200, 0);
-pub const EXTREME_HILLS_MOUNTAINS: Biome = Biome::new(131, 20, 30);
-pub const FLOWER_FOREST: Biome = Biome::new(132, 70, 80);
-pub const TAIGA_M: Biome = Biome::new(133, 5, 80);
-pub const SWAMPLAND_MOUNTAINS: Biome = Biome::new(134, 80, 90);
-pub const ICE_PLAINS_SPIKES: Biome = Biome::new(140, 0, 50);
-pub const JUNGLE_MOUNTAINS: Biome = Biome::new(149, 120, 90);
-pub const JUNGLE_EDGE_MOUNTAINS: Biome = Biome::new(151, 95, 80);
-pub const BIRCH_FOREST_MOUNTAINS: Biome = Biome::new(155, 60, 60);
-pub const BIRCH_FOREST_HILLS_MOUNTAINS: Biome = Biome::new(156, 60, 60);
-pub const ROOFED_FOREST_MOUNTAINS: Biome = Biome::new(157, 70, 80);
-pub const COLD_TAIGA_MOUNTAINS: Biome = Biome::new(158, -50, 40);
-pub const MEGA_SPRUCE_TAIGA: Biome = Biome::new(160, 25, 80);
-pub const MEGA_SPRUCE_TAIGA_HILLS: Biome = Biome::new(161, 30, 80);
-pub const EXTREME_HILLS_PLUS_MOUNTAINS: Biome = Biome::new(162, 20, 30);
-pub const SAVANNA_MOUNTAINS: Biome = Biome::new(163, 120, 0);
-pub const SAVANNA_PLATEAU_MOUNTAINS: Biome = Biome::new(164, 100, 0);
-pub const MESA_BRYCE: Biome = Biome::new(165, 200, 0);
-pub const MESA_PLATEAU_FOREST_MOUNTAINS: Biome = Biome::new(166, 200, 0);
-pub const MESA_PLATEAU_MOUNTAINS: Biome = Biome::new(167, 200, 0);
+pub const THE_VOID: Biome = Biome::new(127, "The Void", 50, 50);
 
-pub const INVALID: Biome = Biome::new(255, 0, 0);
+pub const SUNFLOWER_PLAINS: Biome = Biome::new(129, "Sunflower Plains", 80, 40);
+pub const DESERT_MOUNTAIN: Biome = Biome::new(130, "Desert M", 200, 0);
+pub const EXTREME_HILLS_MOUNTAINS: Biome = Biome::new(131, "Extreme Hills M", 20, 30);
+pub const FLOWER_FOREST: Biome = Biome::new(132, "Flower Forest", 70, 80);
+pub const TAIGA_M: Biome = Biome::new(133, "Taiga M", 5, 80);
+pub const SWAMPLAND_MOUNTAINS: Biome = Biome::new(134, "Swampland M", 80, 90);
+pub const ICE_PLAINS_SPIKES: Biome = Biome::new(140, "Ice Plains Spikes", 0, 50);
+pub const JUNGLE_MOUNTAINS: Biome = Biome::new(149, "Jungle M", 120, 90);
+pub const JUNGLE_EDGE_MOUNTAINS: Biome = Biome::new(151, "Jungle Edge M", 95, 80);
+pub const BIRCH_FOREST_MOUNTAINS: Biome = Biome::new(155, "Birch Forest M", 60, 60);
+pub const BIRCH_FOREST_HILLS_MOUNTAINS: Biome = Biome::new(156, "Birch Forest Hills M", 60, 60);
+pub const ROOFED_FOREST_MOUNTAINS: Biome = Biome::new(157, "Roofed Forest M", 70, 80);
+pub const COLD_TAIGA_MOUNTAINS: Biome = Biome::new(158, "Cold Taiga M", -50, 40);
+pub const MEGA_SPRUCE_TAIGA: Biome = Biome::new(160, "Mega Spruce Taiga", 25, 80);
+pub const MEGA_SPRUCE_TAIGA_HILLS: Biome = Biome::new(161, "Mega Spruce Taiga Hills", 30, 80);
+pub const EXTREME_HILLS_PLUS_MOUNTAINS: Biome = Biome::new(162, "Extreme Hills+ M", 20, 30);
+pub const SAVANNA_MOUNTAINS: Biome = Biome::new(163, "Savanna M", 120, 0);
+pub const SAVANNA_PLATEAU_MOUNTAINS: Biome = Biome::new(164, "Savanna Plateau M", 100, 0);
+pub const MESA_BRYCE: Biome = Biome::new(165, "Mesa (Bryce)", 200, 0);
+pub const MESA_PLATEAU_FOREST_MOUNTAINS: Biome = Biome::new(166, "Mesa Plateau Forest M", 200, 0);
+pub const MESA_PLATEAU_MOUNTAINS: Biome = Biome::new(167, "Mesa Plateau M", 200, 0);
+pub const BAMBOO_JUNGLE: Biome = Biome::new(168, "Bamboo Jungle", 95, 90);
+pub const BAMBOO_JUNGLE_HILLS: Biome = Biome::new(169, "Bamboo Jungle Hills", 95, 90);
+pub const SOUL_SAND_VALLEY: Biome = Biome::new(170, "Soul Sand Valley", 200, 0);
+pub const CRIMSON_FOREST: Biome = Biome::new(171, "Crimson Forest", 200, 0);
+pub const WARPED_FOREST: Biome = Biome::new(172, "Warped Forest", 200, 0);
+pub const BASALT_DELTAS: Biome = Biome::new(173, "Basalt Deltas", 200, 0);
+
+pub const INVALID: Biome = Biome::new(255, "Unknown", 0, 0);
 }
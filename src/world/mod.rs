@@ -37,6 +37,7 @@ use byteorder::ReadBytesExt;
 use instant::Instant;
 
 pub mod biome;
+pub mod raycast;
 mod storage;
 
 use crate::chunk_builder::CullInfo;
@@ -48,6 +49,10 @@ use dashmap::DashMap;
 use lazy_static::lazy_static;
 use parking_lot::RwLock;
 
+/// Chunks are only unloaded this many chunks beyond the render distance, so the server
+/// can keep sending the edge of the view without them flickering in and out.
+pub const CHUNK_UNLOAD_MARGIN: i32 = 2;
+
 pub struct World {
     pub chunks: Arc<DashMap<CPos, Chunk, BuildHasherDefault<FNVHash>>>,
     pub lighting_cache: Arc<RwLock<HashMap<CPos, LightData, BuildHasherDefault<FNVHash>>>>,
@@ -229,6 +234,13 @@ impl World {
         }
     }
 
+    pub fn get_biome(&self, x: i32, z: i32) -> biome::Biome {
+        match self.chunks.clone().get(&CPos(x >> 4, z >> 4)) {
+            Some(chunk) => chunk.get_biome(x & 0xF, z & 0xF),
+            None => biome::Biome::by_id(0),
+        }
+    }
+
     fn set_sky_light(&self, pos: Position, light: u8) {
         let cpos = CPos(pos.x >> 4, pos.z >> 4);
         let chunks = self.chunks.clone();
@@ -543,16 +555,7 @@ impl World {
                     chunk.sections_rendered_on[pos.1 as usize] = frame_id;
                 }
 
-                let min = cgmath::Point3::new(
-                    pos.0 as f32 * 16.0,
-                    -pos.1 as f32 * 16.0,
-                    pos.2 as f32 * 16.0,
-                );
-                let bounds =
-                    collision::Aabb3::new(min, min + cgmath::Vector3::new(16.0, -16.0, 16.0));
-                if tmp_frustum.contains(&bounds) == collision::Relation::Out
-                    && *from != Direction::Invalid
-                {
+                if !render::is_section_visible(&tmp_frustum, *pos) && *from != Direction::Invalid {
                     return;
                 }
                 (
@@ -837,6 +840,74 @@ impl World {
         }
     }
 
+    /// Unloads the chunks farther than `render_distance + CHUNK_UNLOAD_MARGIN` chunks from
+    /// `center`, dropping their render buffers along with them. Chunks with sections still
+    /// being built are kept until the build finished. Returns how many chunks were unloaded.
+    pub fn unload_distant_chunks(
+        &self,
+        center: CPos,
+        render_distance: i32,
+        m: &mut ecs::Manager,
+    ) -> usize {
+        let max_distance = render_distance + CHUNK_UNLOAD_MARGIN;
+        let distant = self
+            .chunks
+            .iter()
+            .filter(|chunk| {
+                let CPos(x, z) = chunk.position;
+                (x - center.0).abs().max((z - center.1).abs()) > max_distance
+                    && !chunk.sections.iter().flatten().any(|sec| sec.building)
+            })
+            .map(|chunk| chunk.position)
+            .collect::<Vec<_>>();
+        for pos in &distant {
+            self.unload_chunk(pos.0, pos.1, m);
+        }
+        if !distant.is_empty() {
+            debug!(
+                "Unloaded {} chunks beyond {} chunks of {:?}",
+                distant.len(),
+                max_distance,
+                center
+            );
+        }
+        distant.len()
+    }
+
+    /// How many of the chunks within `radius` chunks of `center` are loaded.
+    pub fn loaded_chunks_around(&self, center: CPos, radius: i32) -> usize {
+        self.chunks
+            .iter()
+            .filter(|chunk| {
+                let CPos(x, z) = chunk.position;
+                (x - center.0).abs().max((z - center.1).abs()) <= radius
+            })
+            .count()
+    }
+
+    /// Whether every chunk within `radius` chunks of `center` is loaded, with all its
+    /// sections meshed.
+    pub fn is_terrain_ready(&self, center: CPos, radius: i32) -> bool {
+        for x in center.0 - radius..=center.0 + radius {
+            for z in center.1 - radius..=center.1 + radius {
+                match self.chunks.get(&CPos(x, z)) {
+                    Some(chunk) => {
+                        if chunk
+                            .sections
+                            .iter()
+                            .flatten()
+                            .any(|sec| sec.dirty || sec.building)
+                        {
+                            return false;
+                        }
+                    }
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+
     pub fn load_chunk(
         &self,
         x: i32,
@@ -902,8 +973,15 @@ impl World {
             }
 
             if new && read_biomes {
-                // read biomes is always true (as param) except for load_chunk_19
-                data.read_exact(&mut chunk.biomes)?;
+                // 1.15+ sends the biomes outside of the chunk data, see `load_biomes_3d`
+                if version == 19 && self.protocol_version >= 393 {
+                    // An int per column since 1.13
+                    for biome in chunk.biomes.iter_mut() {
+                        *biome = data.read_i32::<byteorder::BigEndian>()? as u8;
+                    }
+                } else {
+                    data.read_exact(&mut chunk.biomes)?;
+                }
             }
 
             chunk.calculate_heightmap();
@@ -1399,6 +1477,22 @@ impl World {
         )
     }
 
+    /// Stores the biomes 1.15+ sends with a new chunk, one for every cell of 4 by 4 by 4
+    /// blocks. Only columns are kept, with the biomes of the cells at sea level.
+    pub fn load_biomes_3d(&self, x: i32, z: i32, biomes: &[i32]) {
+        const SEA_LEVEL_CELL: usize = 63 >> 2;
+        if let Some(mut chunk) = self.chunks.get_mut(&CPos(x, z)) {
+            for bz in 0..16 {
+                for bx in 0..16 {
+                    let cell = (SEA_LEVEL_CELL << 4) | ((bz >> 2) << 2) | (bx >> 2);
+                    if let Some(&biome) = biomes.get(cell) {
+                        chunk.biomes[(bz << 4) | bx] = biome as u8;
+                    }
+                }
+            }
+        }
+    }
+
     fn flag_section_dirty(&self, x: i32, y: i32, z: i32) {
         if !(0..=15).contains(&y) {
             return;
@@ -1418,9 +1512,16 @@ impl block::WorldAccess for World {
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub struct CPos(pub i32, pub i32);
 
+impl CPos {
+    /// The chunk containing the given block coordinates.
+    pub fn from_block(x: f64, z: f64) -> CPos {
+        CPos((x.floor() as i32) >> 4, (z.floor() as i32) >> 4)
+    }
+}
+
 pub struct Chunk {
     position: CPos,
 
@@ -1578,8 +1679,6 @@ impl Chunk {
         }
     }
 
-    // TODO: make use of "get_biome"
-    #[allow(dead_code)]
     fn get_biome(&self, x: i32, z: i32) -> biome::Biome {
         biome::Biome::by_id(self.biomes[((z << 4) | x) as usize] as usize)
     }
@@ -1834,3 +1933,91 @@ impl ComposedSection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings;
+
+    fn world_with_chunks(radius: i32) -> World {
+        let (light_send, _) = unbounded();
+        let world = World::new(754, light_send);
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                let mut chunk = Chunk::new(CPos(x, z));
+                chunk.sections[0] = Some(Section::new(0, true));
+                world.chunks.insert(CPos(x, z), chunk);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn distant_chunks_are_unloaded() {
+        let world = world_with_chunks(10);
+        let mut m = ecs::Manager::new();
+        assert_eq!(world.unload_distant_chunks(CPos(0, 0), 8, &mut m), 0);
+        assert_eq!(world.chunks.len(), 21 * 21);
+
+        // A pending rebuild pins the chunk
+        world.set_building_flag((-10, 0, 0));
+
+        // Walk 5 chunks along +x, columns -10 to -6 fall out of 8 + 2 chunks
+        let removed =
+            world.unload_distant_chunks(CPos::from_block(5.0 * 16.0 + 3.5, 0.0), 8, &mut m);
+        assert_eq!(removed, 5 * 21 - 1);
+        assert!(world.is_chunk_loaded(-5, 10));
+        assert!(!world.is_chunk_loaded(-6, 0));
+        assert!(world.is_chunk_loaded(-10, 0));
+
+        world.reset_building_flag((-10, 0, 0));
+        assert_eq!(world.unload_distant_chunks(CPos(5, 0), 8, &mut m), 1);
+        assert!(!world.is_chunk_loaded(-10, 0));
+    }
+
+    #[test]
+    fn lowering_render_distance_evicts_chunks() {
+        let world = world_with_chunks(12);
+        let mut m = ecs::Manager::new();
+        let render_distance = settings::clamp_render_distance(10);
+        assert_eq!(
+            world.unload_distant_chunks(CPos(0, 0), render_distance, &mut m),
+            0
+        );
+
+        let render_distance = settings::clamp_render_distance(4);
+        world.unload_distant_chunks(CPos(0, 0), render_distance, &mut m);
+        assert_eq!(world.chunks.len(), 13 * 13);
+        assert!(world.is_chunk_loaded(6, -6));
+        assert!(!world.is_chunk_loaded(7, 0));
+        assert!(!world.is_chunk_loaded(0, -7));
+
+        // Out of range distances are clamped instead of unloading everything
+        let render_distance = settings::clamp_render_distance(-5);
+        assert_eq!(render_distance, settings::MIN_RENDER_DISTANCE);
+        world.unload_distant_chunks(CPos(0, 0), render_distance, &mut m);
+        assert_eq!(world.chunks.len(), 9 * 9);
+        assert_eq!(
+            settings::clamp_render_distance(1000),
+            settings::MAX_RENDER_DISTANCE
+        );
+    }
+
+    #[test]
+    fn biome_at_player_is_named() {
+        let world = world_with_chunks(1);
+        // Plains everywhere but a desert cell at sea level, blocks -12 to -9 of chunk -1
+        let mut biomes = vec![biome::PLAINS.id as i32; 1024];
+        biomes[((63 >> 2) << 4) | (1 << 2) | 1] = biome::DESERT.id as i32;
+        world.load_biomes_3d(-1, 0, &biomes);
+
+        let player = (-10.3, 70.0, 5.8);
+        let biome = world.get_biome(player.0.floor() as i32, player.2.floor() as i32);
+        assert_eq!(biome.name, "Desert");
+        assert_eq!(world.get_biome(-8, 5).name, "Plains");
+        assert_eq!(world.get_biome(-13, 5).name, "Plains");
+        // Unknown ids and unloaded chunks
+        assert_eq!(biome::Biome::by_id(200).name, "Unknown");
+        assert_eq!(world.get_biome(100, 100).name, "Ocean");
+    }
+}
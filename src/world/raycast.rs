@@ -0,0 +1,314 @@
+//! Finding the block a ray hits first, for targeting blocks and placing the camera.
+
+use cgmath::{InnerSpace, Point3, Vector3, Zero};
+use collision::{Aabb, Aabb3};
+
+use super::{block, World};
+use crate::shared::{Direction, Position};
+
+/// The faces a ray enters a box through along each axis, when moving towards +x, +y and
+/// +z, and when moving towards -x, -y and -z.
+const ENTERED_MOVING_UP: [Direction; 3] = [Direction::West, Direction::Down, Direction::North];
+const ENTERED_MOVING_DOWN: [Direction; 3] = [Direction::East, Direction::Up, Direction::South];
+
+/// Where a ray hit a block.
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    pub position: Position,
+    pub block: block::Block,
+    /// The face of the block the ray entered through.
+    pub face: Direction,
+    /// The point the ray hit, in world coordinates.
+    pub point: Vector3<f64>,
+}
+
+impl RayHit {
+    /// The hit point relative to the block's corner, as sent when placing a block.
+    pub fn cursor(&self) -> Vector3<f64> {
+        self.point
+            - Vector3::new(
+                self.position.x as f64,
+                self.position.y as f64,
+                self.position.z as f64,
+            )
+    }
+}
+
+impl World {
+    /// The first block hit by a ray from `origin` along `dir`, at most `max_dist` blocks
+    /// away. Walks the blocks the ray passes in order (Amanatides and Woo's voxel
+    /// traversal) and tests their collision boxes, so slabs, stairs and such are hit
+    /// where they are, while blocks without any, like air or flowers, are passed
+    /// through. Boxes reaching out of their block, like fences, are only hit inside it.
+    pub fn raycast(
+        &self,
+        origin: Vector3<f64>,
+        dir: Vector3<f64>,
+        max_dist: f64,
+    ) -> Option<RayHit> {
+        if dir.is_zero() {
+            return None;
+        }
+        let dir = dir.normalize();
+
+        let mut block = [
+            origin.x.floor() as i32,
+            origin.y.floor() as i32,
+            origin.z.floor() as i32,
+        ];
+        let mut step = [0; 3];
+        // How far along the ray the next block boundary on each axis is, and how far
+        // apart the boundaries are
+        let mut next_boundary = [f64::INFINITY; 3];
+        let mut boundary_distance = [f64::INFINITY; 3];
+        for axis in 0..3 {
+            if dir[axis] > 0.0 {
+                step[axis] = 1;
+                next_boundary[axis] = (block[axis] as f64 + 1.0 - origin[axis]) / dir[axis];
+                boundary_distance[axis] = 1.0 / dir[axis];
+            } else if dir[axis] < 0.0 {
+                step[axis] = -1;
+                next_boundary[axis] = (block[axis] as f64 - origin[axis]) / dir[axis];
+                boundary_distance[axis] = -1.0 / dir[axis];
+            }
+        }
+
+        loop {
+            let position = Position::new(block[0], block[1], block[2]);
+            if let Some(hit) = self.raycast_block(position, origin, dir, max_dist) {
+                return Some(hit);
+            }
+            let axis = if next_boundary[0] <= next_boundary[1] {
+                if next_boundary[0] <= next_boundary[2] {
+                    0
+                } else {
+                    2
+                }
+            } else if next_boundary[1] <= next_boundary[2] {
+                1
+            } else {
+                2
+            };
+            if next_boundary[axis] > max_dist {
+                return None;
+            }
+            block[axis] += step[axis];
+            next_boundary[axis] += boundary_distance[axis];
+        }
+    }
+
+    /// The closest hit on the collision boxes of the block at `position`.
+    fn raycast_block(
+        &self,
+        position: Position,
+        origin: Vector3<f64>,
+        dir: Vector3<f64>,
+        max_dist: f64,
+    ) -> Option<RayHit> {
+        let block = self.get_block(position);
+        let corner = Vector3::new(position.x as f64, position.y as f64, position.z as f64);
+        block
+            .get_collision_boxes()
+            .into_iter()
+            .filter_map(clip_to_block)
+            .filter_map(|bound| ray_enters(bound.add_v(corner), origin, dir))
+            .filter(|&(distance, _)| distance <= max_dist)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .map(|(distance, face)| RayHit {
+                position,
+                block,
+                face,
+                point: origin + dir * distance,
+            })
+    }
+}
+
+/// The part of a collision box inside its block, `None` if none of it is.
+fn clip_to_block(bound: Aabb3<f64>) -> Option<Aabb3<f64>> {
+    let min = Point3::new(
+        bound.min.x.max(0.0),
+        bound.min.y.max(0.0),
+        bound.min.z.max(0.0),
+    );
+    let max = Point3::new(
+        bound.max.x.min(1.0),
+        bound.max.y.min(1.0),
+        bound.max.z.min(1.0),
+    );
+    if min.x > max.x || min.y > max.y || min.z > max.z {
+        None
+    } else {
+        Some(Aabb3::new(min, max))
+    }
+}
+
+/// How far along the ray it enters `bound` and through which face, `None` if it misses.
+/// Rays starting inside the box hit it right away.
+fn ray_enters(
+    bound: Aabb3<f64>,
+    origin: Vector3<f64>,
+    dir: Vector3<f64>,
+) -> Option<(f64, Direction)> {
+    let mut enter = 0.0;
+    let mut exit = f64::INFINITY;
+    let mut face = Direction::Up;
+    for axis in 0..3 {
+        if dir[axis] == 0.0 {
+            if origin[axis] < bound.min[axis] || origin[axis] > bound.max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let (near, far, near_face) = if dir[axis] > 0.0 {
+            (bound.min[axis], bound.max[axis], ENTERED_MOVING_UP[axis])
+        } else {
+            (bound.max[axis], bound.min[axis], ENTERED_MOVING_DOWN[axis])
+        };
+        let near = (near - origin[axis]) / dir[axis];
+        if near > enter {
+            enter = near;
+            face = near_face;
+        }
+        exit = exit.min((far - origin[axis]) / dir[axis]);
+    }
+    if enter > exit {
+        None
+    } else {
+        Some((enter, face))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{CPos, Chunk};
+    use crossbeam_channel::unbounded;
+
+    fn stone() -> block::Block {
+        block::Stone {
+            variant: block::StoneVariant::Normal,
+        }
+    }
+
+    fn world_with_blocks(blocks: &[(Position, block::Block)]) -> World {
+        let (light_send, _) = unbounded();
+        let world = World::new(754, light_send);
+        // Unloaded chunks are full of missing blocks, which rays hit
+        for x in -1..=1 {
+            for z in -1..=1 {
+                world.chunks.insert(CPos(x, z), Chunk::new(CPos(x, z)));
+            }
+        }
+        for &(position, block) in blocks {
+            world.set_block_raw(position, block);
+        }
+        world
+    }
+
+    #[test]
+    fn axis_aligned_rays_hit_blocks() {
+        let world = world_with_blocks(&[
+            (Position::new(5, 70, 0), stone()),
+            (Position::new(0, 65, 0), stone()),
+            (Position::new(0, 70, -3), stone()),
+        ]);
+        let origin = Vector3::new(0.5, 70.5, 0.5);
+
+        let hit = world
+            .raycast(origin, Vector3::new(1.0, 0.0, 0.0), 10.0)
+            .unwrap();
+        assert_eq!(hit.position, Position::new(5, 70, 0));
+        assert_eq!(hit.face, Direction::West);
+        assert_eq!(hit.point, Vector3::new(5.0, 70.5, 0.5));
+        assert_eq!(hit.cursor(), Vector3::new(0.0, 0.5, 0.5));
+
+        let hit = world
+            .raycast(origin, Vector3::new(0.0, -2.0, 0.0), 10.0)
+            .unwrap();
+        assert_eq!(hit.position, Position::new(0, 65, 0));
+        assert_eq!(hit.face, Direction::Up);
+        assert_eq!(hit.point, Vector3::new(0.5, 66.0, 0.5));
+
+        let hit = world
+            .raycast(origin, Vector3::new(0.0, 0.0, -1.0), 10.0)
+            .unwrap();
+        assert_eq!(hit.position, Position::new(0, 70, -3));
+        assert_eq!(hit.face, Direction::South);
+
+        // Out of reach, or nothing there
+        assert!(world
+            .raycast(origin, Vector3::new(1.0, 0.0, 0.0), 4.0)
+            .is_none());
+        assert!(world
+            .raycast(origin, Vector3::new(0.0, 0.0, 1.0), 10.0)
+            .is_none());
+    }
+
+    #[test]
+    fn rays_hit_partial_blocks_where_they_are() {
+        let slab = block::StoneSlab {
+            variant: block::StoneSlabVariant::Stone,
+            half: block::BlockHalf::Bottom,
+        };
+        let world = world_with_blocks(&[(Position::new(3, 70, 0), slab)]);
+
+        // Over the bottom slab
+        assert!(world
+            .raycast(
+                Vector3::new(0.5, 70.75, 0.5),
+                Vector3::new(1.0, 0.0, 0.0),
+                10.0
+            )
+            .is_none());
+        let hit = world
+            .raycast(
+                Vector3::new(0.5, 70.25, 0.5),
+                Vector3::new(1.0, 0.0, 0.0),
+                10.0,
+            )
+            .unwrap();
+        assert_eq!(hit.position, Position::new(3, 70, 0));
+        assert_eq!(hit.point, Vector3::new(3.0, 70.25, 0.5));
+        // Its top face is half way up the block
+        let hit = world
+            .raycast(
+                Vector3::new(3.5, 72.0, 0.5),
+                Vector3::new(0.0, -1.0, 0.0),
+                10.0,
+            )
+            .unwrap();
+        assert_eq!(hit.face, Direction::Up);
+        assert_eq!(hit.point, Vector3::new(3.5, 70.5, 0.5));
+    }
+
+    #[test]
+    fn rays_hit_tall_blocks_inside_their_block() {
+        let fence = block::Fence {
+            north: false,
+            south: false,
+            west: false,
+            east: false,
+            waterlogged: false,
+        };
+        let world = world_with_blocks(&[(Position::new(3, 70, 0), fence)]);
+
+        // Its collision box reaches half way into the block above, rays there pass
+        assert!(world
+            .raycast(
+                Vector3::new(0.5, 71.25, 0.5),
+                Vector3::new(1.0, 0.0, 0.0),
+                10.0
+            )
+            .is_none());
+        let hit = world
+            .raycast(
+                Vector3::new(3.5, 72.0, 0.5),
+                Vector3::new(0.0, -1.0, 0.0),
+                10.0,
+            )
+            .unwrap();
+        assert_eq!(hit.position, Position::new(3, 70, 0));
+        assert_eq!(hit.face, Direction::Up);
+        assert_eq!(hit.point, Vector3::new(3.5, 71.0, 0.5));
+    }
+}